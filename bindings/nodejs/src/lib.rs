@@ -31,6 +31,8 @@ pub enum Scheme {
     Azblob,
     /// [azdfs][crate::services::Azdfs]: Azure Data Lake Storage Gen2.
     Azdfs,
+    /// [cos][crate::services::Cos]: Tencent Cloud COS services.
+    Cos,
     /// [dashmap][crate::services::Dashmap]: dashmap backend support.
     Dashmap,
     /// [fs][crate::services::Fs]: POSIX alike file system.
@@ -92,6 +94,11 @@ impl Operator {
                     .map_err(format_napi_error)?
                     .finish(),
             )),
+            Scheme::Cos => Ok(Self(
+                opendal::Operator::new(opendal::services::Cos::from_map(ops))
+                    .map_err(format_napi_error)?
+                    .finish(),
+            )),
             Scheme::Gcs => Ok(Self(
                 opendal::Operator::new(opendal::services::Gcs::from_map(ops))
                     .map_err(format_napi_error)?
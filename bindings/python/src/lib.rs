@@ -37,6 +37,9 @@ fn build_operator(scheme: od::Scheme, map: HashMap<String, String>) -> PyResult<
         od::Scheme::Azdfs => od::Operator::from_map::<Azdfs>(map)
             .map_err(format_pyerr)?
             .finish(),
+        od::Scheme::Cos => od::Operator::from_map::<Cos>(map)
+            .map_err(format_pyerr)?
+            .finish(),
         od::Scheme::Fs => od::Operator::from_map::<Fs>(map)
             .map_err(format_pyerr)?
             .finish(),
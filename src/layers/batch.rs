@@ -0,0 +1,563 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use log::debug;
+use parking_lot::Mutex;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// Add automatic batching for `delete` operations.
+///
+/// # Notes
+///
+/// Every `delete` call will be buffered for a short window (10ms by default)
+/// or until `max_batch_size` (1000 by default) deletes are pending, and then
+/// flushed as a single `batch` call. Each caller gets its own result back
+/// once the batch resolves.
+///
+/// - Services without batch delete support will delete directly.
+/// - Pending deletes will be flushed while the operator is dropped.
+/// - The background flush task is spawned on the current tokio runtime,
+///   deletes outside a tokio runtime will be sent directly. If the runtime
+///   of the task has been shut down, a new task will be spawned on the
+///   current one.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anyhow::Result;
+/// use opendal::layers::BatchLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+/// use opendal::Scheme;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(BatchLayer::new().with_window(Duration::from_millis(50)))
+///     .finish();
+/// ```
+#[derive(Debug, Clone)]
+pub struct BatchLayer {
+    window: Duration,
+    max_batch_size: usize,
+}
+
+impl Default for BatchLayer {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_millis(10),
+            max_batch_size: 1000,
+        }
+    }
+}
+
+impl BatchLayer {
+    /// Create a new BatchLayer with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how long deletes will be buffered before flushing.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Set the max number of deletes in one batch.
+    ///
+    /// Pending deletes will be flushed immediately once reached.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_batch_size` is `0`.
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        assert!(max_batch_size > 0, "max_batch_size must be larger than 0");
+
+        self.max_batch_size = max_batch_size;
+        self
+    }
+}
+
+impl<A: Accessor> Layer<A> for BatchLayer {
+    type LayeredAccessor = BatchAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        let can_batch_delete = inner.info().capability().batch_delete;
+
+        BatchAccessor {
+            inner: Arc::new(inner),
+            window: self.window,
+            max_batch_size: self.max_batch_size,
+            can_batch_delete,
+            sender: Mutex::new(None),
+        }
+    }
+}
+
+/// A pending delete waiting for the batch to be flushed.
+struct DeleteRequest {
+    path: String,
+    args: OpDelete,
+    sender: oneshot::Sender<Result<RpDelete>>,
+}
+
+pub struct BatchAccessor<A: Accessor> {
+    inner: Arc<A>,
+    window: Duration,
+    max_batch_size: usize,
+    can_batch_delete: bool,
+
+    /// The background flush task will be spawned at the first delete, and
+    /// exits after all senders have been dropped.
+    sender: Mutex<Option<mpsc::UnboundedSender<DeleteRequest>>>,
+}
+
+impl<A: Accessor> Debug for BatchAccessor<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BatchAccessor")
+            .field("inner", &self.inner)
+            .field("window", &self.window)
+            .field("max_batch_size", &self.max_batch_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A: Accessor> BatchAccessor<A> {
+    /// Get the sender of the background flush task, returns `None` if we
+    /// are not in a tokio runtime.
+    ///
+    /// The task will be spawned again if it has been stopped, for example,
+    /// the runtime it's bound to has been shut down.
+    fn sender(&self) -> Option<mpsc::UnboundedSender<DeleteRequest>> {
+        let rt = tokio::runtime::Handle::try_current().ok()?;
+
+        let mut sender = self.sender.lock();
+        if let Some(tx) = sender.as_ref().filter(|tx| !tx.is_closed()) {
+            return Some(tx.clone());
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        rt.spawn(flush_loop(
+            self.inner.clone(),
+            rx,
+            self.window,
+            self.max_batch_size,
+        ));
+        *sender = Some(tx.clone());
+        Some(tx)
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for BatchAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("BatchLayer")
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.inner.write(path, args).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        if !self.can_batch_delete {
+            return self.inner.delete(path, args).await;
+        }
+
+        let sender = match self.sender() {
+            Some(sender) => sender,
+            None => return self.inner.delete(path, args).await,
+        };
+
+        let (tx, rx) = oneshot::channel();
+        if let Err(mpsc::error::SendError(req)) = sender.send(DeleteRequest {
+            path: path.to_string(),
+            args,
+            sender: tx,
+        }) {
+            // The flush task has been stopped in the meantime.
+            return self.inner.delete(path, req.args).await;
+        }
+
+        rx.await.map_err(|_| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "batch flush task dropped the request",
+            )
+            .with_operation(Operation::Delete)
+            .with_context("path", path)
+        })?
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        self.inner.scan(path, args).await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        self.inner.blocking_scan(path, args)
+    }
+}
+
+/// Collect pending deletes until the window expired or `max_batch_size`
+/// reached, and flush them in one batch.
+///
+/// The loop exits after the channel has been closed and all pending
+/// deletes have been flushed.
+async fn flush_loop<A: Accessor>(
+    inner: Arc<A>,
+    mut rx: mpsc::UnboundedReceiver<DeleteRequest>,
+    window: Duration,
+    max_batch_size: usize,
+) {
+    while let Some(first) = rx.recv().await {
+        let deadline = Instant::now() + window;
+        let mut pending = vec![first];
+        let mut closed = false;
+
+        while pending.len() < max_batch_size {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(req)) => pending.push(req),
+                Ok(None) => {
+                    closed = true;
+                    break;
+                }
+                Err(_) => break,
+            }
+        }
+
+        flush(inner.as_ref(), pending).await;
+
+        if closed {
+            break;
+        }
+    }
+}
+
+/// Flush pending deletes and route results back to their callers.
+async fn flush<A: Accessor>(inner: &A, pending: Vec<DeleteRequest>) {
+    debug!("batch layer flushing {} pending deletes", pending.len());
+
+    // The same path could be deleted by multiple callers in one window,
+    // we only send it once and share the result.
+    let mut ops = Vec::with_capacity(pending.len());
+    let mut waiters: HashMap<String, Vec<oneshot::Sender<Result<RpDelete>>>> = HashMap::new();
+    for req in pending {
+        let senders = waiters.entry(req.path.clone()).or_default();
+        if senders.is_empty() {
+            ops.push((req.path, req.args));
+        }
+        senders.push(req.sender);
+    }
+
+    let results = match inner
+        .batch(OpBatch::new(BatchOperations::Delete(ops)))
+        .await
+    {
+        Ok(rp) => {
+            let BatchedResults::Delete(results) = rp.into_results();
+            results
+        }
+        Err(err) => {
+            for sender in waiters.into_values().flatten() {
+                let _ = sender.send(Err(copy_error(&err)));
+            }
+            return;
+        }
+    };
+
+    for (path, res) in results {
+        if let Some(senders) = waiters.remove(&path) {
+            send_result(senders, res);
+        }
+    }
+
+    // Services must return results for every path, report as unexpected
+    // if not.
+    for (path, senders) in waiters {
+        send_result(
+            senders,
+            Err(Error::new(
+                ErrorKind::Unexpected,
+                "batch delete result is missing for path",
+            )
+            .with_operation(Operation::Delete)
+            .with_context("path", path)),
+        );
+    }
+}
+
+fn send_result(senders: Vec<oneshot::Sender<Result<RpDelete>>>, res: Result<RpDelete>) {
+    let mut senders = senders.into_iter();
+    let first = match senders.next() {
+        Some(v) => v,
+        None => return,
+    };
+
+    for sender in senders {
+        let copied = match &res {
+            Ok(rp) => Ok(rp.clone()),
+            Err(err) => Err(copy_error(err)),
+        };
+        // Callers could have been canceled, it's fine to ignore.
+        let _ = sender.send(copied);
+    }
+    let _ = first.send(res);
+}
+
+/// Error is not cloneable, build a new one that carries the same
+/// kind, status and message.
+fn copy_error(err: &Error) -> Error {
+    let copied = Error::new(err.kind(), &err.to_string());
+    if err.is_temporary() {
+        copied.set_temporary()
+    } else {
+        copied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+
+    use super::*;
+
+    #[derive(Default, Clone)]
+    struct MockBuilder {
+        batches: Arc<Mutex<Vec<usize>>>,
+        deletes: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Builder for MockBuilder {
+        const SCHEME: Scheme = Scheme::Custom("mock");
+        type Accessor = MockService;
+
+        fn from_map(_: HashMap<String, String>) -> Self {
+            Self::default()
+        }
+
+        fn build(&mut self) -> Result<Self::Accessor> {
+            Ok(MockService {
+                batches: self.batches.clone(),
+                deletes: self.deletes.clone(),
+            })
+        }
+    }
+
+    #[derive(Debug, Clone, Default)]
+    struct MockService {
+        batches: Arc<Mutex<Vec<usize>>>,
+        deletes: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Accessor for MockService {
+        type Reader = ();
+        type BlockingReader = ();
+        type Writer = ();
+        type BlockingWriter = ();
+        type Pager = ();
+        type BlockingPager = ();
+
+        fn info(&self) -> AccessorInfo {
+            let mut am = AccessorInfo::default();
+            am.set_capabilities(AccessorCapability::Batch);
+            am.set_capability(Capability {
+                delete: true,
+                batch: true,
+                batch_delete: true,
+                ..Default::default()
+            });
+
+            am
+        }
+
+        async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+            self.deletes.lock().unwrap().push(path.to_string());
+            Ok(RpDelete::default())
+        }
+
+        async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+            let BatchOperations::Delete(ops) = args.into_operation();
+            self.batches.lock().unwrap().push(ops.len());
+
+            let results = ops
+                .into_iter()
+                .map(|(path, _)| {
+                    let res = if path.starts_with("denied") {
+                        Err(Error::new(ErrorKind::PermissionDenied, "access denied"))
+                    } else {
+                        Ok(RpDelete::default())
+                    };
+                    (path, res)
+                })
+                .collect();
+            Ok(RpBatch::new(BatchedResults::Delete(results)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_in_batch() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let builder = MockBuilder::default();
+        let op = Operator::new(builder.clone())
+            .unwrap()
+            .layer(BatchLayer::new().with_window(Duration::from_millis(100)))
+            .finish();
+
+        let (a, b, c, d) = futures::join!(
+            op.delete("a"),
+            op.delete("b"),
+            op.delete("denied_c"),
+            op.delete("a"),
+        );
+        assert!(a.is_ok());
+        assert!(b.is_ok());
+        assert_eq!(c.unwrap_err().kind(), ErrorKind::PermissionDenied);
+        assert!(d.is_ok());
+
+        assert_eq!(*builder.batches.lock().unwrap(), vec![3]);
+        assert!(builder.deletes.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_delete_without_runtime() {
+        let builder = MockBuilder::default();
+        let op = Operator::new(builder.clone())
+            .unwrap()
+            .layer(BatchLayer::new())
+            .finish();
+
+        futures::executor::block_on(op.delete("a")).unwrap();
+
+        assert!(builder.batches.lock().unwrap().is_empty());
+        assert_eq!(*builder.deletes.lock().unwrap(), vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn test_delete_after_runtime_shutdown() {
+        let builder = MockBuilder::default();
+        let op = Operator::new(builder.clone())
+            .unwrap()
+            .layer(BatchLayer::new())
+            .finish();
+
+        for path in ["a", "b"] {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_time()
+                .build()
+                .unwrap();
+            rt.block_on(op.delete(path)).unwrap();
+            drop(rt);
+        }
+
+        assert_eq!(*builder.batches.lock().unwrap(), vec![1, 1]);
+        assert!(builder.deletes.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_flush_at_max_batch_size() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let builder = MockBuilder::default();
+        let op = Operator::new(builder.clone())
+            .unwrap()
+            .layer(
+                BatchLayer::new()
+                    .with_window(Duration::from_secs(3600))
+                    .with_max_batch_size(2),
+            )
+            .finish();
+
+        let (a, b) = futures::join!(op.delete("a"), op.delete("b"));
+        assert!(a.is_ok());
+        assert!(b.is_ok());
+
+        assert_eq!(*builder.batches.lock().unwrap(), vec![2]);
+    }
+
+    #[tokio::test]
+    async fn test_flush_on_drop() {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let builder = MockBuilder::default();
+        let accessor = BatchLayer::new()
+            .with_window(Duration::from_secs(3600))
+            .layer(builder.clone().build().unwrap());
+
+        let (tx, rx) = oneshot::channel();
+        accessor
+            .sender()
+            .unwrap()
+            .send(DeleteRequest {
+                path: "a".to_string(),
+                args: OpDelete::new(),
+                sender: tx,
+            })
+            .unwrap();
+        drop(accessor);
+
+        assert!(rx.await.unwrap().is_ok());
+        assert_eq!(*builder.batches.lock().unwrap(), vec![1]);
+    }
+}
@@ -111,6 +111,10 @@ impl<A: Accessor> LayeredAccessor for ChaosAccessor<A> {
         &self.inner
     }
 
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("ChaosLayer")
+    }
+
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
         self.inner
             .read(path, args)
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::min;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::io;
@@ -43,7 +44,7 @@ use crate::*;
 ///
 /// # Internal
 ///
-/// So far CompleteLayer will do two completion:
+/// So far CompleteLayer will do the following completions:
 ///
 /// ## Read
 ///
@@ -115,6 +116,35 @@ use crate::*;
 /// - if only `hierarchy`, with [`oio::to_hierarchy_pager`].
 /// - If neither not supported, something must be wrong.
 ///
+/// ## Root and Dir
+///
+/// Services behave differently on root and dirs, CompleteLayer will make
+/// them conform to the following matrix:
+///
+/// | Operation                          | Result                          |
+/// |------------------------------------|---------------------------------|
+/// | `stat("/")`                        | `EntryMode::DIR`                |
+/// | `stat("a/")` with `a/` exists      | `EntryMode::DIR`                |
+/// | `stat("a/")` with only `a/b` exist | `EntryMode::DIR`                |
+/// | `stat("a/")` with nothing under it | `NotFound`                      |
+/// | `list("a/")` on not exist dir      | empty pager                     |
+/// | `list("a/")` with `error_on_not_found` on not exist dir | `NotFound` |
+///
+/// Stat on a dir that the underlying service reports as not found will
+/// send one extra list with limit `1` to probe for children, so that
+/// services without real dirs like s3 can see the dirs that only exist
+/// implicitly via their children.
+///
+/// ## Path
+///
+/// CompleteLayer will reject invalid paths before sending them to services:
+///
+/// - Paths rejected by [`check_path`] like `a/../b` will return
+///   `ConfigInvalid`.
+/// - `read` and `write` on dir paths like `a/` will return `IsADirectory`.
+/// - `list` and `scan` on file paths like `a` will return `NotADirectory`.
+/// - `create` will check the path against the mode to create.
+///
 /// [`AccessorHint`]: crate::raw::AccessorHint
 pub struct CompleteLayer;
 
@@ -143,18 +173,149 @@ impl<A: Accessor> Debug for CompleteReaderAccessor<A> {
 }
 
 impl<A: Accessor> CompleteReaderAccessor<A> {
+    /// Services that don't support `if_not_exists` or `if_match` will
+    /// overwrite the existing path silently, so we must reject them here.
+    fn check_write_args(&self, op: Operation, path: &str, args: &OpWrite) -> Result<()> {
+        if args.if_not_exists() && !self.meta.capability().write_with_if_not_exists {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "write with if_not_exists is not supported",
+            )
+            .with_operation(op)
+            .with_context("service", self.meta.scheme())
+            .with_context("path", path));
+        }
+        if args.if_match().is_some() && !self.meta.capability().write_with_if_match {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "write with if_match is not supported",
+            )
+            .with_operation(op)
+            .with_context("service", self.meta.scheme())
+            .with_context("path", path));
+        }
+        if args.last_modified().is_some() && !self.meta.capability().write_with_last_modified {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "write with last_modified is not supported",
+            )
+            .with_operation(op)
+            .with_context("service", self.meta.scheme())
+            .with_context("path", path));
+        }
+        if !args.tags().is_empty() && !self.meta.capability().write_with_tags {
+            return Err(
+                Error::new(ErrorKind::Unsupported, "write with tags is not supported")
+                    .with_operation(op)
+                    .with_context("service", self.meta.scheme())
+                    .with_context("path", path),
+            );
+        }
+        if args.append_existing() && !self.meta.capability().write_with_append_existing {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "write with append_existing is not supported",
+            )
+            .with_operation(op)
+            .with_context("service", self.meta.scheme())
+            .with_context("path", path));
+        }
+        if args.session().is_some() && !self.meta.capability().write_with_session {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "write with session is not supported",
+            )
+            .with_operation(op)
+            .with_context("service", self.meta.scheme())
+            .with_context("path", path));
+        }
+
+        Ok(())
+    }
+
+    /// Reject invalid paths and paths that don't match the expected mode.
+    ///
+    /// `EntryMode::Unknown` means both file and dir paths are accepted.
+    fn check_path_args(&self, op: Operation, path: &str, mode: EntryMode) -> Result<()> {
+        check_path(path).map_err(|err| {
+            err.with_operation(op)
+                .with_context("service", self.meta.scheme())
+        })?;
+
+        let (kind, message) = match mode {
+            EntryMode::FILE if path.ends_with('/') => {
+                (ErrorKind::IsADirectory, "path is a directory")
+            }
+            EntryMode::DIR if !path.ends_with('/') => {
+                (ErrorKind::NotADirectory, "path is not a directory")
+            }
+            _ => return Ok(()),
+        };
+        Err(Error::new(kind, message)
+            .with_operation(op)
+            .with_context("service", self.meta.scheme())
+            .with_context("path", path))
+    }
+
+    fn check_stat_args(&self, op: Operation, path: &str, args: &OpStat) -> Result<()> {
+        if args.tags() && !self.meta.capability().stat_with_tags {
+            return Err(
+                Error::new(ErrorKind::Unsupported, "stat with tags is not supported")
+                    .with_operation(op)
+                    .with_context("service", self.meta.scheme())
+                    .with_context("path", path),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Check if we need to resolve suffix range via `stat` before read.
+    fn need_resolve_suffix_range(&self, range: BytesRange) -> bool {
+        range.offset().is_none()
+            && range.size().is_some()
+            && !self.meta.capability().read_with_suffix_range
+    }
+
     async fn complete_reader(
         &self,
         path: &str,
-        args: OpRead,
+        mut args: OpRead,
     ) -> Result<(RpRead, CompleteReader<A, A::Reader>)> {
         let (seekable, streamable) = (
             self.meta.hints().contains(AccessorHint::ReadSeekable),
             self.meta.hints().contains(AccessorHint::ReadStreamable),
         );
 
+        // Services that can't read with suffix range will be served by
+        // a stat and a normal ranged read.
+        let mut resolved = None;
+        if self.need_resolve_suffix_range(args.range()) {
+            let total_size = self
+                .inner
+                .stat(path, OpStat::new())
+                .await?
+                .into_metadata()
+                .content_length();
+            let (range, content_range) = resolve_suffix_range(args.range(), total_size);
+            args = args.with_range(range);
+            resolved = Some(content_range);
+        }
+
         let range = args.range();
         let (rp, r) = self.inner.read(path, args).await?;
+        let rp = fill_content_range(rp, resolved);
+
+        // The size is unknown if server doesn't return content length, we
+        // can't seek on it and can only stream until EOF.
+        if !seekable && !rp.metadata().bit().contains(Metakey::ContentLength) {
+            return if streamable {
+                Ok((rp, CompleteReader::AlreadyComplete(r)))
+            } else {
+                let r = oio::into_streamable_reader(r, 256 * 1024);
+                Ok((rp, CompleteReader::NeedStreamable(r)))
+            };
+        }
         let content_length = rp.metadata().content_length();
 
         match (seekable, streamable) {
@@ -168,17 +329,22 @@ impl<A: Accessor> CompleteReaderAccessor<A> {
                     (Some(offset), _) => (offset, content_length),
                     (None, None) => (0, content_length),
                     (None, Some(size)) => {
-                        // TODO: we can read content range to calculate
-                        // the total content length.
-                        let om = self.inner.stat(path, OpStat::new()).await?.into_metadata();
-                        let total_size = om.content_length();
-                        let (offset, size) = if size > total_size {
-                            (0, total_size)
-                        } else {
-                            (total_size - size, size)
-                        };
-
-                        (offset, size)
+                        // Use the returned content range to learn the
+                        // actual offset if possible.
+                        let returned = rp.content_range().and_then(|v| v.range());
+                        match returned {
+                            Some(v) => (v.start, v.end - v.start),
+                            None => {
+                                let om =
+                                    self.inner.stat(path, OpStat::new()).await?.into_metadata();
+                                let total_size = om.content_length();
+                                if size > total_size {
+                                    (0, total_size)
+                                } else {
+                                    (total_size - size, size)
+                                }
+                            }
+                        }
                     }
                 };
                 let r = oio::into_reader::by_range(self.inner.clone(), path, r, offset, size);
@@ -196,14 +362,27 @@ impl<A: Accessor> CompleteReaderAccessor<A> {
     fn complete_blocking_reader(
         &self,
         path: &str,
-        args: OpRead,
+        mut args: OpRead,
     ) -> Result<(RpRead, CompleteReader<A, A::BlockingReader>)> {
         let (seekable, streamable) = (
             self.meta.hints().contains(AccessorHint::ReadSeekable),
             self.meta.hints().contains(AccessorHint::ReadStreamable),
         );
 
+        let mut resolved = None;
+        if self.need_resolve_suffix_range(args.range()) {
+            let total_size = self
+                .inner
+                .blocking_stat(path, OpStat::new())?
+                .into_metadata()
+                .content_length();
+            let (range, content_range) = resolve_suffix_range(args.range(), total_size);
+            args = args.with_range(range);
+            resolved = Some(content_range);
+        }
+
         let (rp, r) = self.inner.blocking_read(path, args)?;
+        let rp = fill_content_range(rp, resolved);
 
         match (seekable, streamable) {
             (true, true) => Ok((rp, CompleteReader::AlreadyComplete(r))),
@@ -218,10 +397,125 @@ impl<A: Accessor> CompleteReaderAccessor<A> {
         }
     }
 
+    async fn complete_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        match self.inner.stat(path, args).await {
+            // Root always exists, but other errors like invalid credentials
+            // must be kept for users like `Operator::check`.
+            Err(err) if err.kind() == ErrorKind::NotFound && path == "/" => {
+                Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound && path.ends_with('/') => {
+                if self.probe_dir(path).await? {
+                    Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+                } else {
+                    Err(err)
+                }
+            }
+            v => v,
+        }
+    }
+
+    fn complete_blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        match self.inner.blocking_stat(path, args) {
+            // Root always exists, but other errors like invalid credentials
+            // must be kept for users like `BlockingOperator::check`.
+            Err(err) if err.kind() == ErrorKind::NotFound && path == "/" => {
+                Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound && path.ends_with('/') => {
+                if self.blocking_probe_dir(path)? {
+                    Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+                } else {
+                    Err(err)
+                }
+            }
+            v => v,
+        }
+    }
+
+    /// Check if there is any entry under given dir.
+    async fn probe_dir(&self, path: &str) -> Result<bool> {
+        if !self.can_list_or_scan() {
+            return Ok(false);
+        }
+
+        let mut p = match self.list_pager(path, OpList::new().with_limit(1)).await {
+            Ok((_, p)) => p,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err),
+        };
+        while let Some(entries) = oio::Page::next(&mut p).await? {
+            if !entries.is_empty() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Check if there is any entry under given dir.
+    fn blocking_probe_dir(&self, path: &str) -> Result<bool> {
+        if !self.can_list_or_scan() {
+            return Ok(false);
+        }
+
+        let mut p = match self.blocking_list_pager(path, OpList::new().with_limit(1)) {
+            Ok((_, p)) => p,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err),
+        };
+        while let Some(entries) = oio::BlockingPage::next(&mut p)? {
+            if !entries.is_empty() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn can_list_or_scan(&self) -> bool {
+        self.meta.capabilities().contains(AccessorCapability::List)
+            || self.meta.capabilities().contains(AccessorCapability::Scan)
+    }
+
     async fn complete_list(
         &self,
         path: &str,
         args: OpList,
+    ) -> Result<(RpList, CompletePager<A, A::Pager>)> {
+        // Stat will return `NotFound` consistently for dirs that don't
+        // exist, no matter how the underlying service lists them.
+        if args.error_on_not_found() {
+            self.complete_stat(path, OpStat::new()).await?;
+        }
+
+        match self.list_pager(path, args).await {
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                Ok((RpList::default(), CompletePager::Empty))
+            }
+            v => v,
+        }
+    }
+
+    fn complete_blocking_list(
+        &self,
+        path: &str,
+        args: OpList,
+    ) -> Result<(RpList, CompletePager<A, A::BlockingPager>)> {
+        if args.error_on_not_found() {
+            self.complete_blocking_stat(path, OpStat::new())?;
+        }
+
+        match self.blocking_list_pager(path, args) {
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                Ok((RpList::default(), CompletePager::Empty))
+            }
+            v => v,
+        }
+    }
+
+    async fn list_pager(
+        &self,
+        path: &str,
+        args: OpList,
     ) -> Result<(RpList, CompletePager<A, A::Pager>)> {
         let (can_list, can_scan) = (
             self.meta.capabilities().contains(AccessorCapability::List),
@@ -244,7 +538,7 @@ impl<A: Accessor> CompleteReaderAccessor<A> {
         }
     }
 
-    fn complete_blocking_list(
+    fn blocking_list_pager(
         &self,
         path: &str,
         args: OpList,
@@ -326,8 +620,8 @@ impl<A: Accessor> LayeredAccessor for CompleteReaderAccessor<A> {
     type Inner = A;
     type Reader = CompleteReader<A, A::Reader>;
     type BlockingReader = CompleteReader<A, A::BlockingReader>;
-    type Writer = A::Writer;
-    type BlockingWriter = A::BlockingWriter;
+    type Writer = CompleteWriter<A::Writer>;
+    type BlockingWriter = CompleteWriter<A::BlockingWriter>;
     type Pager = CompletePager<A, A::Pager>;
     type BlockingPager = CompletePager<A, A::BlockingPager>;
 
@@ -335,16 +629,57 @@ impl<A: Accessor> LayeredAccessor for CompleteReaderAccessor<A> {
         &self.inner
     }
 
+    fn layer_name(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Update capability to reflect the features completed by this layer.
+    fn metadata(&self) -> AccessorInfo {
+        let mut meta = self.meta.clone();
+
+        let mut cap = meta.capability();
+        if cap.read {
+            cap.read_can_next = true;
+            cap.read_can_seek = true;
+        }
+        if cap.list || cap.scan {
+            cap.list = true;
+            cap.scan = true;
+        }
+        meta.set_capability(cap);
+
+        meta
+    }
+
+    async fn create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        self.check_path_args(Operation::Create, path, args.mode())?;
+
+        self.inner.create(path, args).await
+    }
+
+    fn blocking_create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        self.check_path_args(Operation::BlockingCreate, path, args.mode())?;
+
+        self.inner.blocking_create(path, args)
+    }
+
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.check_path_args(Operation::Read, path, EntryMode::FILE)?;
+
         self.complete_reader(path, args).await
     }
 
     fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.check_path_args(Operation::BlockingRead, path, EntryMode::FILE)?;
+
         self.complete_blocking_reader(path, args)
     }
 
     async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
-        self.inner.stat(path, args).await.map(|v| {
+        self.check_path_args(Operation::Stat, path, EntryMode::Unknown)?;
+        self.check_stat_args(Operation::Stat, path, &args)?;
+
+        self.complete_stat(path, args).await.map(|v| {
             v.map_metadata(|m| {
                 let bit = m.bit();
                 m.with_bit(bit | Metakey::Complete)
@@ -353,7 +688,10 @@ impl<A: Accessor> LayeredAccessor for CompleteReaderAccessor<A> {
     }
 
     fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
-        self.inner.blocking_stat(path, args).map(|v| {
+        self.check_path_args(Operation::BlockingStat, path, EntryMode::Unknown)?;
+        self.check_stat_args(Operation::BlockingStat, path, &args)?;
+
+        self.complete_blocking_stat(path, args).map(|v| {
             v.map_metadata(|m| {
                 let bit = m.bit();
                 m.with_bit(bit | Metakey::Complete)
@@ -362,28 +700,159 @@ impl<A: Accessor> LayeredAccessor for CompleteReaderAccessor<A> {
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        self.inner.write(path, args).await
+        self.check_path_args(Operation::Write, path, EntryMode::FILE)?;
+        self.check_write_args(Operation::Write, path, &args)?;
+
+        let size = args.content_length();
+        self.inner
+            .write(path, args)
+            .await
+            .map(|(rp, w)| (rp, CompleteWriter::new(w, size)))
     }
 
     fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
-        self.inner.blocking_write(path, args)
+        self.check_path_args(Operation::BlockingWrite, path, EntryMode::FILE)?;
+        self.check_write_args(Operation::BlockingWrite, path, &args)?;
+
+        let size = args.content_length();
+        self.inner
+            .blocking_write(path, args)
+            .map(|(rp, w)| (rp, CompleteWriter::new(w, size)))
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.check_path_args(Operation::Delete, path, EntryMode::Unknown)?;
+
+        self.inner.delete(path, args).await
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.check_path_args(Operation::BlockingDelete, path, EntryMode::Unknown)?;
+
+        self.inner.blocking_delete(path, args)
     }
 
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.check_path_args(Operation::List, path, EntryMode::DIR)?;
+
         self.complete_list(path, args).await
     }
 
     fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.check_path_args(Operation::BlockingList, path, EntryMode::DIR)?;
+
         self.complete_blocking_list(path, args)
     }
 
     async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        self.check_path_args(Operation::Scan, path, EntryMode::DIR)?;
+
         self.complete_scan(path, args).await
     }
 
     fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        self.check_path_args(Operation::BlockingScan, path, EntryMode::DIR)?;
+
         self.complete_blocking_scan(path, args)
     }
+
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        self.check_path_args(Operation::Restore, path, EntryMode::FILE)?;
+
+        self.inner.restore(path, args).await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.check_path_args(Operation::Rename, from, EntryMode::Unknown)?;
+        self.check_path_args(Operation::Rename, to, EntryMode::Unknown)?;
+
+        self.inner.rename(from, to, args).await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.check_path_args(Operation::Copy, from, EntryMode::Unknown)?;
+        self.check_path_args(Operation::Copy, to, EntryMode::Unknown)?;
+
+        self.inner.copy(from, to, args).await
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        match args.operation() {
+            BatchOperations::Delete(ops) => {
+                for (path, _) in ops {
+                    self.check_path_args(Operation::Batch, path, EntryMode::Unknown)?;
+                }
+            }
+        }
+
+        self.inner.batch(args).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        path: &str,
+        args: OpAbortMultipart,
+    ) -> Result<RpAbortMultipart> {
+        self.check_path_args(Operation::AbortMultipart, path, EntryMode::FILE)?;
+
+        self.inner.abort_multipart(path, args).await
+    }
+
+    async fn get_acl(&self, path: &str, args: OpGetAcl) -> Result<RpGetAcl> {
+        self.check_path_args(Operation::GetAcl, path, EntryMode::Unknown)?;
+
+        self.inner.get_acl(path, args).await
+    }
+
+    async fn set_acl(&self, path: &str, args: OpSetAcl) -> Result<RpSetAcl> {
+        self.check_path_args(Operation::SetAcl, path, EntryMode::Unknown)?;
+
+        self.inner.set_acl(path, args).await
+    }
+
+    async fn get_tags(&self, path: &str, args: OpGetTags) -> Result<RpGetTags> {
+        self.check_path_args(Operation::GetTags, path, EntryMode::Unknown)?;
+
+        self.inner.get_tags(path, args).await
+    }
+
+    async fn put_tags(&self, path: &str, args: OpPutTags) -> Result<RpPutTags> {
+        self.check_path_args(Operation::PutTags, path, EntryMode::Unknown)?;
+
+        self.inner.put_tags(path, args).await
+    }
+
+    fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.check_path_args(Operation::Presign, path, EntryMode::Unknown)?;
+
+        self.inner.presign(path, args)
+    }
+}
+
+/// Convert suffix range into an absolute range with given total size.
+///
+/// Returns the absolute range to read and the content range that will be
+/// returned.
+fn resolve_suffix_range(range: BytesRange, total_size: u64) -> (BytesRange, BytesContentRange) {
+    let size = min(range.size().unwrap_or_default(), total_size);
+    let offset = total_size - size;
+
+    let mut content_range = BytesContentRange::default().with_size(total_size);
+    if size > 0 {
+        content_range = content_range.with_range(offset, offset + size - 1);
+    }
+
+    (BytesRange::new(Some(offset), Some(size)), content_range)
+}
+
+/// Fill resolved content range into [`RpRead`] if services didn't return it.
+fn fill_content_range(rp: RpRead, content_range: Option<BytesContentRange>) -> RpRead {
+    match content_range {
+        Some(v) if rp.content_range().is_none() => {
+            RpRead::with_metadata(rp.into_metadata().with_content_range(v))
+        }
+        _ => rp,
+    }
 }
 
 pub enum CompleteReader<A: Accessor, R> {
@@ -472,6 +941,8 @@ pub enum CompletePager<A: Accessor, P> {
     AlreadyComplete(P),
     NeedFlat(ToFlatPager<Arc<A>, P>),
     NeedHierarchy(ToHierarchyPager<P>),
+    /// The dir to list doesn't exist.
+    Empty,
 }
 
 #[async_trait]
@@ -487,6 +958,7 @@ where
             AlreadyComplete(p) => p.next().await,
             NeedFlat(p) => p.next().await,
             NeedHierarchy(p) => p.next().await,
+            Empty => Ok(None),
         }
     }
 }
@@ -503,6 +975,392 @@ where
             AlreadyComplete(p) => p.next(),
             NeedFlat(p) => p.next(),
             NeedHierarchy(p) => p.next(),
+            Empty => Ok(None),
+        }
+    }
+}
+
+/// CompleteWriter will make sure the bytes written matches the declared
+/// content length.
+///
+/// - `write` with a different size will be rejected.
+/// - `append` more bytes than declared will abort the writer.
+/// - `close` with less bytes than declared will abort the writer.
+///
+/// No check will happen if content length is not declared.
+pub struct CompleteWriter<W> {
+    inner: W,
+    size: Option<u64>,
+    written: u64,
+}
+
+impl<W> CompleteWriter<W> {
+    pub fn new(inner: W, size: Option<u64>) -> CompleteWriter<W> {
+        CompleteWriter {
+            inner,
+            size,
+            written: 0,
+        }
+    }
+
+    fn check_write(&self, len: u64) -> Result<()> {
+        match self.size {
+            Some(size) if size != len => Err(Error::new(
+                ErrorKind::Unexpected,
+                "write content length mismatch",
+            )
+            .with_context("expect", size.to_string())
+            .with_context("actual", len.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_append(&self, len: u64) -> Result<()> {
+        match self.size {
+            Some(size) if self.written + len > size => Err(Error::new(
+                ErrorKind::Unexpected,
+                "writer got more data than declared content length",
+            )
+            .with_context("expect", size.to_string())
+            .with_context("actual", (self.written + len).to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_close(&self) -> Result<()> {
+        match self.size {
+            Some(size) if self.written != size => Err(Error::new(
+                ErrorKind::Unexpected,
+                "writer got less data than declared content length",
+            )
+            .with_context("expect", size.to_string())
+            .with_context("actual", self.written.to_string())),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl<W> oio::Write for CompleteWriter<W>
+where
+    W: oio::Write,
+{
+    async fn write(&mut self, bs: bytes::Bytes) -> Result<()> {
+        let len = bs.len() as u64;
+        self.check_write(len)?;
+
+        self.inner.write(bs).await?;
+        self.written = len;
+        Ok(())
+    }
+
+    async fn append(&mut self, bs: bytes::Bytes) -> Result<()> {
+        let len = bs.len() as u64;
+        if let Err(err) = self.check_append(len) {
+            return Err(with_abort_result(err, self.inner.abort().await));
+        }
+
+        self.inner.append(bs).await?;
+        self.written += len;
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        if let Err(err) = self.check_close() {
+            return Err(with_abort_result(err, self.inner.abort().await));
+        }
+
+        self.inner.close().await
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        self.inner.abort().await
+    }
+}
+
+impl<W> oio::BlockingWrite for CompleteWriter<W>
+where
+    W: oio::BlockingWrite,
+{
+    fn write(&mut self, bs: bytes::Bytes) -> Result<()> {
+        let len = bs.len() as u64;
+        self.check_write(len)?;
+
+        self.inner.write(bs)?;
+        self.written = len;
+        Ok(())
+    }
+
+    fn append(&mut self, bs: bytes::Bytes) -> Result<()> {
+        let len = bs.len() as u64;
+        if let Err(err) = self.check_append(len) {
+            return Err(with_abort_result(err, self.inner.abort()));
+        }
+
+        self.inner.append(bs)?;
+        self.written += len;
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<RpWrite> {
+        if let Err(err) = self.check_close() {
+            return Err(with_abort_result(err, self.inner.abort()));
+        }
+
+        self.inner.close()
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        self.inner.abort()
+    }
+}
+
+/// Attach the error of aborting to the error that caused the abort, the
+/// original error is always returned since it's what users need to fix.
+fn with_abort_result(err: Error, res: Result<()>) -> Error {
+    match res {
+        Ok(()) => err,
+        Err(abort_err) => err.with_context("abort_error", abort_err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use anyhow::Result;
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::services::Memory;
+
+    #[tokio::test]
+    async fn test_root_and_dir() -> Result<()> {
+        let op = Operator::new(Memory::default())?.finish();
+
+        assert_eq!(op.stat("/").await?.mode(), EntryMode::DIR);
+
+        op.write("a/b/c", "Hello, World!").await?;
+        assert_eq!(op.stat("a/").await?.mode(), EntryMode::DIR);
+        assert_eq!(op.stat("a/b/").await?.mode(), EntryMode::DIR);
+        let err = op.stat("x/").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let entries: Vec<_> = op.list("x/").await?.try_collect().await?;
+        assert!(entries.is_empty());
+        let res = op
+            .list_with("x/", OpList::new().with_error_on_not_found(true))
+            .await;
+        assert_eq!(res.err().map(|e| e.kind()), Some(ErrorKind::NotFound));
+        let entries: Vec<_> = op
+            .list_with("a/", OpList::new().with_error_on_not_found(true))
+            .await?
+            .try_collect()
+            .await?;
+        assert_eq!(entries.len(), 1);
+
+        let op = op.blocking();
+        assert_eq!(op.stat("/")?.mode(), EntryMode::DIR);
+        assert_eq!(op.stat("a/b/")?.mode(), EntryMode::DIR);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_path() -> Result<()> {
+        let op = Operator::new(Memory::default())?.finish();
+        let acc = CompleteLayer.layer(Memory::default().build()?);
+
+        op.write("./a//b", "Hello, World!").await?;
+        assert_eq!(op.read("a/./b").await?, b"Hello, World!");
+
+        let err = op.write("a/../b", "Hello, World!").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+        let err = op.stat("a\0b").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+        let err = op.rename("a/b", "../b").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+        let res = Accessor::batch(
+            &acc,
+            OpBatch::new(BatchOperations::Delete(vec![
+                ("a/b".to_string(), OpDelete::new()),
+                ("a/../b".to_string(), OpDelete::new()),
+            ])),
+        )
+        .await;
+        assert_eq!(res.err().map(|e| e.kind()), Some(ErrorKind::ConfigInvalid));
+        let err = Accessor::put_tags(&acc, "a/../b", OpPutTags::new(HashMap::new()))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        // Accessors called directly must also be protected.
+        let res = Accessor::write(&acc, "a/", OpWrite::new()).await;
+        assert_eq!(res.err().map(|e| e.kind()), Some(ErrorKind::IsADirectory));
+        let res = Accessor::read(&acc, "a/", OpRead::new()).await;
+        assert_eq!(res.err().map(|e| e.kind()), Some(ErrorKind::IsADirectory));
+        let res = Accessor::list(&acc, "a", OpList::new()).await;
+        assert_eq!(res.err().map(|e| e.kind()), Some(ErrorKind::NotADirectory));
+        let err = Accessor::create(&acc, "a", OpCreate::new(EntryMode::DIR))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotADirectory);
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct MockBuilder;
+
+    impl Builder for MockBuilder {
+        const SCHEME: Scheme = Scheme::Custom("mock");
+        type Accessor = MockService;
+
+        fn from_map(_: HashMap<String, String>) -> Self {
+            Self
+        }
+
+        fn build(&mut self) -> crate::Result<Self::Accessor> {
+            Ok(MockService)
+        }
+    }
+
+    /// Service that can't list and rejects all requests as the credentials
+    /// are invalid.
+    #[derive(Debug)]
+    struct MockService;
+
+    #[async_trait]
+    impl Accessor for MockService {
+        type Reader = ();
+        type BlockingReader = ();
+        type Writer = ();
+        type BlockingWriter = ();
+        type Pager = ();
+        type BlockingPager = ();
+
+        fn info(&self) -> AccessorInfo {
+            let mut am = AccessorInfo::default();
+            am.set_capabilities(AccessorCapability::Read | AccessorCapability::Blocking);
+            am.set_capability(Capability {
+                read: true,
+                stat: true,
+                blocking: true,
+                ..Default::default()
+            });
+
+            am
+        }
+
+        async fn stat(&self, _: &str, _: OpStat) -> crate::Result<RpStat> {
+            Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "invalid credentials",
+            ))
+        }
+
+        fn blocking_stat(&self, _: &str, _: OpStat) -> crate::Result<RpStat> {
+            Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "invalid credentials",
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_without_list() -> Result<()> {
+        let op = Operator::new(MockBuilder)?.finish();
+        assert!(!op.info().capability().list);
+
+        let err = op.check().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        let err = op.blocking().check().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        Ok(())
+    }
+
+    /// Writer that fails to abort, and records how many times abort is called.
+    #[derive(Default)]
+    struct MockWriter {
+        aborted: usize,
+    }
+
+    #[async_trait]
+    impl oio::Write for MockWriter {
+        async fn write(&mut self, _: bytes::Bytes) -> crate::Result<()> {
+            Ok(())
+        }
+
+        async fn append(&mut self, _: bytes::Bytes) -> crate::Result<()> {
+            Ok(())
+        }
+
+        async fn close(&mut self) -> crate::Result<RpWrite> {
+            Ok(RpWrite::new())
+        }
+
+        async fn abort(&mut self) -> crate::Result<()> {
+            self.aborted += 1;
+            Err(Error::new(ErrorKind::Unexpected, "abort failed"))
+        }
+    }
+
+    impl oio::BlockingWrite for MockWriter {
+        fn write(&mut self, _: bytes::Bytes) -> crate::Result<()> {
+            Ok(())
+        }
+
+        fn append(&mut self, _: bytes::Bytes) -> crate::Result<()> {
+            Ok(())
+        }
+
+        fn close(&mut self) -> crate::Result<RpWrite> {
+            Ok(RpWrite::new())
+        }
+
+        fn abort(&mut self) -> crate::Result<()> {
+            self.aborted += 1;
+            Err(Error::new(ErrorKind::Unexpected, "abort failed"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_writer_abort_on_length_mismatch() {
+        let mut w = CompleteWriter::new(MockWriter::default(), Some(4));
+        oio::Write::append(&mut w, bytes::Bytes::from("ab"))
+            .await
+            .unwrap();
+        let err = oio::Write::close(&mut w).await.unwrap_err();
+        assert!(err.to_string().contains("less data"));
+        assert!(err.context_value("abort_error").is_some());
+        assert_eq!(w.inner.aborted, 1);
+
+        let mut w = CompleteWriter::new(MockWriter::default(), Some(4));
+        let err = oio::BlockingWrite::append(&mut w, bytes::Bytes::from("abcde")).unwrap_err();
+        assert!(err.to_string().contains("more data"));
+        assert!(err.context_value("abort_error").is_some());
+        assert_eq!(w.inner.aborted, 1);
+
+        let mut w = CompleteWriter::new(MockWriter::default(), Some(4));
+        oio::BlockingWrite::append(&mut w, bytes::Bytes::from("ab")).unwrap();
+        let err = oio::BlockingWrite::close(&mut w).unwrap_err();
+        assert!(err.to_string().contains("less data"));
+        assert_eq!(w.inner.aborted, 1);
+    }
+
+    #[test]
+    fn test_resolve_suffix_range() {
+        let cases = vec![
+            ("normal", 8, 1024, (1016, 8), Some(1016..1024)),
+            ("larger than total", 2048, 1024, (0, 1024), Some(0..1024)),
+            ("empty object", 8, 0, (0, 0), None),
+        ];
+
+        for (name, size, total, (offset, length), returned) in cases {
+            let (range, content_range) =
+                resolve_suffix_range(BytesRange::new(None, Some(size)), total);
+
+            assert_eq!(range, BytesRange::new(Some(offset), Some(length)), "{name}");
+            assert_eq!(content_range.range(), returned, "{name}");
+            assert_eq!(content_range.size(), Some(total), "{name}");
         }
     }
 }
@@ -0,0 +1,367 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::SeekFrom;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::ops::*;
+use crate::raw::oio::PageOperation;
+use crate::raw::oio::ReadOperation;
+use crate::raw::oio::WriteOperation;
+use crate::raw::*;
+use crate::*;
+
+/// DeadlineLayer will honor the deadline carried by operations.
+///
+/// # Notes
+///
+/// - Only `read`, `write`, `stat`, `delete` and `list` (and their blocking
+///   versions) carry deadlines for now. The deadline will be checked before
+///   these calls, and every call on the returning reader, writer and pager.
+/// - Other operations (like `create`, `rename`, `copy` and `presign`) don't
+///   carry deadlines, so they are passed through without checking.
+/// - `abort` of writers is used to clean up, so it's not limited by deadline.
+/// - The deadline will be kept in scope while calling underlying accessor,
+///   so that [`HttpClient`] can use the remaining time as request timeout.
+/// - This layer must be the outermost internal layer so that extra calls
+///   made by other internal layers (like `stat` before `read`) are covered.
+pub struct DeadlineLayer;
+
+impl<A: Accessor> Layer<A> for DeadlineLayer {
+    type LayeredAccessor = DeadlineAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        DeadlineAccessor { inner }
+    }
+}
+
+#[derive(Debug)]
+pub struct DeadlineAccessor<A: Accessor> {
+    inner: A,
+}
+
+fn check(deadline: Option<Instant>, op: impl Into<&'static str>, path: &str) -> Result<()> {
+    check_deadline(deadline).map_err(|err| err.with_operation(op).with_context("path", path))
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for DeadlineAccessor<A> {
+    type Inner = A;
+    type Reader = DeadlineWrapper<A::Reader>;
+    type BlockingReader = DeadlineWrapper<A::BlockingReader>;
+    type Writer = DeadlineWrapper<A::Writer>;
+    type BlockingWriter = DeadlineWrapper<A::BlockingWriter>;
+    type Pager = DeadlineWrapper<A::Pager>;
+    type BlockingPager = DeadlineWrapper<A::BlockingPager>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn layer_name(&self) -> Option<&'static str> {
+        None
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let deadline = args.deadline();
+        check(deadline, Operation::Read, path)?;
+
+        DeadlineFuture::new(deadline, self.inner.read(path, args))
+            .await
+            .map(|(rp, r)| (rp, DeadlineWrapper::new(r, deadline, path)))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let deadline = args.deadline();
+        check(deadline, Operation::Write, path)?;
+
+        DeadlineFuture::new(deadline, self.inner.write(path, args))
+            .await
+            .map(|(rp, w)| (rp, DeadlineWrapper::new(w, deadline, path)))
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let deadline = args.deadline();
+        check(deadline, Operation::Stat, path)?;
+
+        DeadlineFuture::new(deadline, self.inner.stat(path, args)).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let deadline = args.deadline();
+        check(deadline, Operation::Delete, path)?;
+
+        DeadlineFuture::new(deadline, self.inner.delete(path, args)).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        let deadline = args.deadline();
+        check(deadline, Operation::List, path)?;
+
+        DeadlineFuture::new(deadline, self.inner.list(path, args))
+            .await
+            .map(|(rp, p)| (rp, DeadlineWrapper::new(p, deadline, path)))
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        self.inner
+            .scan(path, args)
+            .await
+            .map(|(rp, p)| (rp, DeadlineWrapper::new(p, None, path)))
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let deadline = args.deadline();
+        check(deadline, Operation::BlockingRead, path)?;
+
+        scope_deadline(deadline, || self.inner.blocking_read(path, args))
+            .map(|(rp, r)| (rp, DeadlineWrapper::new(r, deadline, path)))
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let deadline = args.deadline();
+        check(deadline, Operation::BlockingWrite, path)?;
+
+        scope_deadline(deadline, || self.inner.blocking_write(path, args))
+            .map(|(rp, w)| (rp, DeadlineWrapper::new(w, deadline, path)))
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let deadline = args.deadline();
+        check(deadline, Operation::BlockingStat, path)?;
+
+        scope_deadline(deadline, || self.inner.blocking_stat(path, args))
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let deadline = args.deadline();
+        check(deadline, Operation::BlockingDelete, path)?;
+
+        scope_deadline(deadline, || self.inner.blocking_delete(path, args))
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        let deadline = args.deadline();
+        check(deadline, Operation::BlockingList, path)?;
+
+        scope_deadline(deadline, || self.inner.blocking_list(path, args))
+            .map(|(rp, p)| (rp, DeadlineWrapper::new(p, deadline, path)))
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        self.inner
+            .blocking_scan(path, args)
+            .map(|(rp, p)| (rp, DeadlineWrapper::new(p, None, path)))
+    }
+}
+
+pub struct DeadlineWrapper<T> {
+    inner: T,
+    deadline: Option<Instant>,
+    path: String,
+}
+
+impl<T> DeadlineWrapper<T> {
+    fn new(inner: T, deadline: Option<Instant>, path: &str) -> Self {
+        Self {
+            inner,
+            deadline,
+            path: path.to_string(),
+        }
+    }
+}
+
+impl<T: oio::Read> oio::Read for DeadlineWrapper<T> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        check(self.deadline, ReadOperation::Read, &self.path)?;
+
+        scope_deadline(self.deadline, || self.inner.poll_read(cx, buf))
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+        check(self.deadline, ReadOperation::Seek, &self.path)?;
+
+        scope_deadline(self.deadline, || self.inner.poll_seek(cx, pos))
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        if let Err(err) = check(self.deadline, ReadOperation::Next, &self.path) {
+            return Poll::Ready(Some(Err(err)));
+        }
+
+        scope_deadline(self.deadline, || self.inner.poll_next(cx))
+    }
+}
+
+impl<T: oio::BlockingRead> oio::BlockingRead for DeadlineWrapper<T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        check(self.deadline, ReadOperation::BlockingRead, &self.path)?;
+
+        scope_deadline(self.deadline, || self.inner.read(buf))
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        check(self.deadline, ReadOperation::BlockingSeek, &self.path)?;
+
+        scope_deadline(self.deadline, || self.inner.seek(pos))
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        if let Err(err) = check(self.deadline, ReadOperation::BlockingNext, &self.path) {
+            return Some(Err(err));
+        }
+
+        scope_deadline(self.deadline, || self.inner.next())
+    }
+}
+
+#[async_trait]
+impl<T: oio::Write> oio::Write for DeadlineWrapper<T> {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        check(self.deadline, WriteOperation::Write, &self.path)?;
+
+        DeadlineFuture::new(self.deadline, self.inner.write(bs)).await
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        check(self.deadline, WriteOperation::Append, &self.path)?;
+
+        DeadlineFuture::new(self.deadline, self.inner.append(bs)).await
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        check(self.deadline, WriteOperation::Close, &self.path)?;
+
+        DeadlineFuture::new(self.deadline, self.inner.close()).await
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        // Abort is used to clean up, don't block it by deadline.
+        self.inner.abort().await
+    }
+}
+
+impl<T: oio::BlockingWrite> oio::BlockingWrite for DeadlineWrapper<T> {
+    fn write(&mut self, bs: Bytes) -> Result<()> {
+        check(self.deadline, WriteOperation::BlockingWrite, &self.path)?;
+
+        scope_deadline(self.deadline, || self.inner.write(bs))
+    }
+
+    fn append(&mut self, bs: Bytes) -> Result<()> {
+        check(self.deadline, WriteOperation::BlockingAppend, &self.path)?;
+
+        scope_deadline(self.deadline, || self.inner.append(bs))
+    }
+
+    fn close(&mut self) -> Result<RpWrite> {
+        check(self.deadline, WriteOperation::BlockingClose, &self.path)?;
+
+        scope_deadline(self.deadline, || self.inner.close())
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        // Abort is used to clean up, don't block it by deadline.
+        self.inner.abort()
+    }
+}
+
+#[async_trait]
+impl<T: oio::Page> oio::Page for DeadlineWrapper<T> {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        check(self.deadline, PageOperation::Next, &self.path)?;
+
+        DeadlineFuture::new(self.deadline, self.inner.next()).await
+    }
+}
+
+impl<T: oio::BlockingPage> oio::BlockingPage for DeadlineWrapper<T> {
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        check(self.deadline, PageOperation::BlockingNext, &self.path)?;
+
+        scope_deadline(self.deadline, || self.inner.next())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::services::Memory;
+
+    #[tokio::test]
+    async fn test_deadline_exceeded() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        op.write("file", "Hello, World!").await.unwrap();
+
+        let err = op
+            .read_with("file", OpRead::new().with_deadline(Instant::now()))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(!err.is_temporary());
+
+        let err = op
+            .stat_with("file", OpStat::new().with_deadline(Instant::now()))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+
+        // Other calls on the same operator are not affected.
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let bs = op
+            .read_with("file", OpRead::new().with_deadline(deadline))
+            .await
+            .unwrap();
+        assert_eq!(bs, b"Hello, World!");
+        op.delete("file").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_deadline_on_reader() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        op.write("file", "Hello, World!").await.unwrap();
+
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let mut r = op
+            .reader_with("file", OpRead::new().with_deadline(deadline))
+            .await
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        let mut buf = vec![];
+        let res = futures::AsyncReadExt::read_to_end(&mut r, &mut buf).await;
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_blocking_deadline_exceeded() {
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .finish()
+            .blocking();
+        op.write("file", "Hello, World!").unwrap();
+
+        let err = op
+            .read_with("file", OpRead::new().with_deadline(Instant::now()))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+    }
+}
@@ -0,0 +1,468 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use async_compression::futures::bufread::GzipDecoder;
+use async_compression::futures::bufread::ZlibDecoder;
+use async_compression::futures::bufread::ZstdDecoder;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::io::BufReader;
+use futures::AsyncRead;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// The size of chunks yielded by `poll_next` of decompressed content.
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Decompress content on read based on the stored `Content-Encoding`.
+///
+/// # Decompress
+///
+/// Objects uploaded by other systems could be stored compressed with
+/// `Content-Encoding` set. DecompressLayer inspects the `Content-Encoding`
+/// returned by read and decodes the content while streaming, so callers
+/// will get the plain content.
+///
+/// Supported encodings are:
+///
+/// - `gzip` (and `x-gzip`)
+/// - `deflate`
+/// - `zstd`
+///
+/// Objects without `Content-Encoding` or with `identity` will be passed
+/// through unchanged.
+///
+/// # Notes
+///
+/// - Services that don't return `Content-Encoding` on read are not
+///   affected.
+/// - Compressed content can't be decoded partially, so range read or seek
+///   on compressed objects will return [`ErrorKind::Unsupported`].
+/// - Content length of compressed objects is unknown before decoding, so
+///   it will be removed from the returned metadata.
+/// - Blocking read on compressed objects is not supported yet.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::DecompressLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+/// use opendal::Scheme;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(DecompressLayer)
+///     .finish();
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct DecompressLayer;
+
+impl<A: Accessor> Layer<A> for DecompressLayer {
+    type LayeredAccessor = DecompressAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        DecompressAccessor { inner }
+    }
+}
+
+#[derive(Debug)]
+pub struct DecompressAccessor<A> {
+    inner: A,
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for DecompressAccessor<A> {
+    type Inner = A;
+    type Reader = DecompressReader<A::Reader>;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("DecompressLayer")
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let range = args.range();
+        let (rp, r) = self.inner.read(path, args).await?;
+
+        let encoding = match parse_encoding(rp.metadata()).map_err(|err| {
+            err.with_operation(Operation::Read)
+                .with_context("path", path)
+        })? {
+            Some(encoding) => encoding,
+            None => return Ok((rp, DecompressReader::new(Decoder::Plain(r)))),
+        };
+
+        if !range.is_full() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "range read on compressed content is not supported",
+            )
+            .with_operation(Operation::Read)
+            .with_context("path", path)
+            .with_context("range", range.to_string()));
+        }
+
+        // The size of decompressed content is unknown.
+        let meta = rp.into_metadata();
+        let bit = meta.bit() - Metakey::ContentLength - Metakey::ContentRange;
+        let rp = RpRead::with_metadata(meta.with_bit(bit));
+
+        let r = BufReader::new(ReadAdapter(r));
+        let decoder = match encoding {
+            Encoding::Gzip => {
+                let mut d = GzipDecoder::new(r);
+                // Concatenated gzip members are valid gzip content.
+                d.multiple_members(true);
+                Decoder::Gzip(d)
+            }
+            Encoding::Deflate => Decoder::Deflate(ZlibDecoder::new(r)),
+            Encoding::Zstd => Decoder::Zstd(ZstdDecoder::new(r)),
+        };
+
+        Ok((rp, DecompressReader::new(decoder)))
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let (rp, r) = self.inner.blocking_read(path, args)?;
+
+        match parse_encoding(rp.metadata())? {
+            None => Ok((rp, r)),
+            Some(_) => Err(Error::new(
+                ErrorKind::Unsupported,
+                "blocking read on compressed content is not supported",
+            )
+            .with_operation(Operation::BlockingRead)
+            .with_context("path", path)),
+        }
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.inner.write(path, args).await
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(path, args)
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        self.inner.scan(path, args).await
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        self.inner.blocking_scan(path, args)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Encoding {
+    Gzip,
+    Deflate,
+    Zstd,
+}
+
+/// Parse the encoding that needs to be decoded, returns `None` if the
+/// content is not encoded.
+fn parse_encoding(meta: &Metadata) -> Result<Option<Encoding>> {
+    if !meta.bit().contains(Metakey::ContentEncoding) {
+        return Ok(None);
+    }
+
+    let v = match meta.content_encoding() {
+        Some(v) => v.trim().to_ascii_lowercase(),
+        None => return Ok(None),
+    };
+
+    match v.as_str() {
+        "" | "identity" => Ok(None),
+        "gzip" | "x-gzip" => Ok(Some(Encoding::Gzip)),
+        "deflate" => Ok(Some(Encoding::Deflate)),
+        "zstd" => Ok(Some(Encoding::Zstd)),
+        _ => Err(
+            Error::new(ErrorKind::Unsupported, "content encoding is not supported")
+                .with_context("content_encoding", v),
+        ),
+    }
+}
+
+/// ReadAdapter adapts [`oio::Read`] into [`AsyncRead`] for decoders.
+struct ReadAdapter<R>(R);
+
+impl<R: oio::Read> AsyncRead for ReadAdapter<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().0.poll_read(cx, buf).map_err(io::Error::from)
+    }
+}
+
+type Decoded<R> = BufReader<ReadAdapter<R>>;
+
+enum Decoder<R> {
+    Plain(R),
+    Gzip(GzipDecoder<Decoded<R>>),
+    Deflate(ZlibDecoder<Decoded<R>>),
+    Zstd(ZstdDecoder<Decoded<R>>),
+}
+
+/// DecompressReader decodes content from inner reader while reading.
+pub struct DecompressReader<R> {
+    decoder: Decoder<R>,
+    buf: Vec<u8>,
+}
+
+impl<R> DecompressReader<R> {
+    fn new(decoder: Decoder<R>) -> Self {
+        Self {
+            decoder,
+            buf: Vec::new(),
+        }
+    }
+}
+
+impl<R: oio::Read> oio::Read for DecompressReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        let res = match &mut self.decoder {
+            Decoder::Plain(r) => return r.poll_read(cx, buf),
+            Decoder::Gzip(d) => Pin::new(d).poll_read(cx, buf),
+            Decoder::Deflate(d) => Pin::new(d).poll_read(cx, buf),
+            Decoder::Zstd(d) => Pin::new(d).poll_read(cx, buf),
+        };
+
+        res.map_err(new_decompress_error)
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
+        match &mut self.decoder {
+            Decoder::Plain(r) => r.poll_seek(cx, pos),
+            _ => Poll::Ready(Err(Error::new(
+                ErrorKind::Unsupported,
+                "seek on compressed content is not supported",
+            )
+            .with_operation(oio::ReadOperation::Seek))),
+        }
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        if let Decoder::Plain(r) = &mut self.decoder {
+            return r.poll_next(cx);
+        }
+
+        if self.buf.is_empty() {
+            self.buf = vec![0; DEFAULT_CHUNK_SIZE];
+        }
+
+        let mut buf = std::mem::take(&mut self.buf);
+        let res = self.poll_read(cx, &mut buf);
+        let res = match res {
+            Poll::Pending => {
+                self.buf = buf;
+                return Poll::Pending;
+            }
+            Poll::Ready(res) => res,
+        };
+
+        let res = match res {
+            Ok(0) => None,
+            Ok(n) => Some(Ok(Bytes::copy_from_slice(&buf[..n]))),
+            Err(err) => Some(Err(err)),
+        };
+        self.buf = buf;
+
+        Poll::Ready(res)
+    }
+}
+
+/// Errors returned by inner reader will be returned as is, others are
+/// errors while decoding.
+fn new_decompress_error(err: io::Error) -> Error {
+    if err.get_ref().map_or(false, |e| e.is::<Error>()) {
+        let inner = err.into_inner().expect("inner error must exist");
+        return *inner
+            .downcast::<Error>()
+            .expect("inner error must be Error");
+    }
+
+    Error::new(ErrorKind::Unexpected, "decompress content")
+        .with_operation(oio::ReadOperation::Read)
+        .set_source(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use async_compression::futures::bufread::GzipEncoder;
+    use async_compression::futures::bufread::ZlibEncoder;
+    use async_compression::futures::bufread::ZstdEncoder;
+    use futures::AsyncReadExt;
+    use futures::StreamExt;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::services::Http;
+
+    const CONTENT: &str = "Hello, World! Hello, World! Hello, World!";
+
+    async fn compress(encoding: &str, content: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        match encoding {
+            "gzip" => GzipEncoder::new(content).read_to_end(&mut buf).await,
+            "deflate" => ZlibEncoder::new(content).read_to_end(&mut buf).await,
+            "zstd" => ZstdEncoder::new(content).read_to_end(&mut buf).await,
+            _ => unreachable!(),
+        }
+        .expect("compress must succeed");
+        buf
+    }
+
+    async fn new_operator(server: &MockServer) -> Operator {
+        let mut builder = Http::default();
+        builder.endpoint(&server.uri());
+        Operator::new(builder)
+            .unwrap()
+            .layer(DecompressLayer)
+            .finish()
+    }
+
+    #[tokio::test]
+    async fn test_read_compressed() {
+        let _ = env_logger::try_init();
+
+        for encoding in ["gzip", "deflate", "zstd"] {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/file"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("content-encoding", encoding)
+                        .set_body_bytes(compress(encoding, CONTENT.as_bytes()).await),
+                )
+                .mount(&server)
+                .await;
+
+            let op = new_operator(&server).await;
+            let bs = op.read("file").await.unwrap();
+            assert_eq!(bs, CONTENT.as_bytes(), "{encoding} must be decoded");
+
+            let mut r = op.reader("file").await.unwrap();
+            let mut buf = Vec::new();
+            while let Some(bs) = r.next().await {
+                buf.extend_from_slice(&bs.unwrap());
+            }
+            assert_eq!(buf, CONTENT.as_bytes(), "{encoding} must be decoded");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_plain() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/file"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(CONTENT))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/identity"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-encoding", "identity")
+                    .set_body_string(CONTENT),
+            )
+            .mount(&server)
+            .await;
+
+        let op = new_operator(&server).await;
+        assert_eq!(op.read("file").await.unwrap(), CONTENT.as_bytes());
+        assert_eq!(op.read("identity").await.unwrap(), CONTENT.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_range_read_compressed() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/file"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-encoding", "gzip")
+                    .set_body_bytes(compress("gzip", CONTENT.as_bytes()).await),
+            )
+            .mount(&server)
+            .await;
+
+        let op = new_operator(&server).await;
+        let err = op.range_read("file", 0..4).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn test_parse_encoding() {
+        let cases = vec![
+            ("gzip", Some(Encoding::Gzip)),
+            ("x-gzip", Some(Encoding::Gzip)),
+            ("GZIP", Some(Encoding::Gzip)),
+            ("deflate", Some(Encoding::Deflate)),
+            ("zstd", Some(Encoding::Zstd)),
+            ("identity", None),
+        ];
+
+        for (input, expected) in cases {
+            let meta = Metadata::new(EntryMode::FILE).with_content_encoding(input.to_string());
+            assert_eq!(parse_encoding(&meta).unwrap(), expected, "{input}");
+        }
+
+        assert_eq!(
+            parse_encoding(&Metadata::new(EntryMode::FILE)).unwrap(),
+            None
+        );
+
+        let meta = Metadata::new(EntryMode::FILE).with_content_encoding("br".to_string());
+        assert_eq!(
+            parse_encoding(&meta).unwrap_err().kind(),
+            ErrorKind::Unsupported
+        );
+    }
+}
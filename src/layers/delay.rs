@@ -0,0 +1,344 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rand::Rng;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// Add a delay before every operation to simulate or smooth latency.
+///
+/// # Delay
+///
+/// The delay could be:
+///
+/// - a constant duration via [`DelayLayer::new`].
+/// - a uniform random duration in range via [`DelayLayer::uniform`].
+/// - a duration decided by the operation via [`DelayLayer::with_fn`].
+///
+/// DelayLayer only affects timing and never returns errors.
+///
+/// # Notes
+///
+/// - The delay is applied before calling the inner accessor. Async
+///   operations wait on a timer owned by the operation's future, so
+///   dropping the future cancels the timer as well and the inner operation
+///   will never be called.
+/// - Blocking operations will block current thread for the delay.
+/// - Only operations on accessor are delayed, IO on the returned reader,
+///   writer and pager are not affected.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anyhow::Result;
+/// use opendal::layers::DelayLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+/// use opendal::Scheme;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(DelayLayer::uniform(
+///         Duration::from_millis(10),
+///         Duration::from_millis(50),
+///     ))
+///     .finish();
+/// ```
+#[derive(Clone)]
+pub struct DelayLayer {
+    delay: Arc<dyn Fn(Operation) -> Duration + Send + Sync>,
+}
+
+impl Debug for DelayLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DelayLayer").finish_non_exhaustive()
+    }
+}
+
+impl DelayLayer {
+    /// Create a new delay layer that delays every operation by the same
+    /// duration.
+    pub fn new(delay: Duration) -> Self {
+        Self::with_fn(move |_| delay)
+    }
+
+    /// Create a new delay layer that delays every operation by a random
+    /// duration picked uniformly in `[min, max]`.
+    ///
+    /// # Panics
+    ///
+    /// Input min must not be larger than max.
+    pub fn uniform(min: Duration, max: Duration) -> Self {
+        assert!(min <= max, "min must not be larger than max");
+
+        Self::with_fn(move |_| rand::thread_rng().gen_range(min..=max))
+    }
+
+    /// Create a new delay layer that decides the delay by operation.
+    ///
+    /// Return `Duration::ZERO` to skip the delay of given operation.
+    pub fn with_fn(f: impl Fn(Operation) -> Duration + Send + Sync + 'static) -> Self {
+        Self { delay: Arc::new(f) }
+    }
+}
+
+impl<A: Accessor> Layer<A> for DelayLayer {
+    type LayeredAccessor = DelayAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        DelayAccessor {
+            inner,
+            delay: self.delay.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DelayAccessor<A> {
+    inner: A,
+    delay: Arc<dyn Fn(Operation) -> Duration + Send + Sync>,
+}
+
+impl<A: Debug> Debug for DelayAccessor<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DelayAccessor")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A> DelayAccessor<A> {
+    /// Wait for the delay of given operation.
+    ///
+    /// The sleep is owned by the returned future, dropping it will
+    /// deregister the timer.
+    async fn delay(&self, op: Operation) {
+        let dur = (self.delay)(op);
+        if !dur.is_zero() {
+            tokio::time::sleep(dur).await;
+        }
+    }
+
+    fn blocking_delay(&self, op: Operation) {
+        let dur = (self.delay)(op);
+        if !dur.is_zero() {
+            thread::sleep(dur);
+        }
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for DelayAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("DelayLayer")
+    }
+
+    async fn create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        self.delay(Operation::Create).await;
+        self.inner.create(path, args).await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.delay(Operation::Read).await;
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.delay(Operation::Write).await;
+        self.inner.write(path, args).await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.delay(Operation::Stat).await;
+        self.inner.stat(path, args).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.delay(Operation::Delete).await;
+        self.inner.delete(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.delay(Operation::List).await;
+        self.inner.list(path, args).await
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        self.delay(Operation::Scan).await;
+        self.inner.scan(path, args).await
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        self.delay(Operation::Batch).await;
+        self.inner.batch(args).await
+    }
+
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        self.delay(Operation::Restore).await;
+        self.inner.restore(path, args).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        path: &str,
+        args: OpAbortMultipart,
+    ) -> Result<RpAbortMultipart> {
+        self.delay(Operation::AbortMultipart).await;
+        self.inner.abort_multipart(path, args).await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.delay(Operation::Rename).await;
+        self.inner.rename(from, to, args).await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.delay(Operation::Copy).await;
+        self.inner.copy(from, to, args).await
+    }
+
+    async fn get_acl(&self, path: &str, args: OpGetAcl) -> Result<RpGetAcl> {
+        self.delay(Operation::GetAcl).await;
+        self.inner.get_acl(path, args).await
+    }
+
+    async fn set_acl(&self, path: &str, args: OpSetAcl) -> Result<RpSetAcl> {
+        self.delay(Operation::SetAcl).await;
+        self.inner.set_acl(path, args).await
+    }
+
+    async fn get_tags(&self, path: &str, args: OpGetTags) -> Result<RpGetTags> {
+        self.delay(Operation::GetTags).await;
+        self.inner.get_tags(path, args).await
+    }
+
+    async fn put_tags(&self, path: &str, args: OpPutTags) -> Result<RpPutTags> {
+        self.delay(Operation::PutTags).await;
+        self.inner.put_tags(path, args).await
+    }
+
+    fn blocking_create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        self.blocking_delay(Operation::BlockingCreate);
+        self.inner.blocking_create(path, args)
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.blocking_delay(Operation::BlockingRead);
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.blocking_delay(Operation::BlockingWrite);
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.blocking_delay(Operation::BlockingStat);
+        self.inner.blocking_stat(path, args)
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.blocking_delay(Operation::BlockingDelete);
+        self.inner.blocking_delete(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.blocking_delay(Operation::BlockingList);
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        self.blocking_delay(Operation::BlockingScan);
+        self.inner.blocking_scan(path, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::services::Memory;
+
+    #[tokio::test]
+    async fn test_constant_delay() {
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .layer(DelayLayer::new(Duration::from_millis(50)))
+            .finish();
+
+        let now = Instant::now();
+        op.write("file", "Hello, World!").await.unwrap();
+        assert!(now.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_delay_by_operation() {
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .layer(DelayLayer::with_fn(|op| match op {
+                Operation::Stat => Duration::from_secs(10),
+                _ => Duration::ZERO,
+            }))
+            .finish();
+
+        let now = Instant::now();
+        op.write("file", "Hello, World!").await.unwrap();
+        assert!(now.elapsed() < Duration::from_secs(10));
+
+        let res = tokio::time::timeout(Duration::from_millis(50), op.stat("file")).await;
+        assert!(res.is_err(), "stat should be delayed");
+    }
+
+    #[tokio::test]
+    async fn test_dropped_delay_skips_inner() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+        let delayed = op
+            .clone()
+            .layer(DelayLayer::new(Duration::from_millis(100)));
+
+        let res = tokio::time::timeout(
+            Duration::from_millis(10),
+            delayed.write("file", "Hello, World!"),
+        )
+        .await;
+        assert!(res.is_err(), "write should be cancelled");
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!op.is_exist("file").await.unwrap());
+    }
+}
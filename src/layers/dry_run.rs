@@ -0,0 +1,426 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use log::info;
+use parking_lot::Mutex;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+static DRY_RUN_TARGET: &str = "opendal::layers::dry_run";
+
+/// Add dry run for underlying storage services.
+///
+/// DryRunLayer will intercept all mutating operations (`create`, `write`,
+/// `delete` and batch deletes) and record them instead of sending them to
+/// the underlying services. Read only operations like `read`, `stat`,
+/// `list` and `scan` will be passed through so that the plan is computed
+/// against real data.
+///
+/// Recorded mutations are shared between all clones of the layer, so users
+/// can keep a clone of the layer and inspect them via [`DryRunLayer::mutations`]
+/// afterwards.
+///
+/// # Notes
+///
+/// - Mutations will be logged at `info` level with target `opendal::layers::dry_run`.
+/// - Data written to a dry run writer will be discarded. Mutations are
+///   recorded while the writer is created, so the size is not known.
+/// - Every path in batch deletes will be recorded as a `Delete`.
+/// - `rename` and `copy` will be recorded with the source path as
+///   [`DryRunMutation::path`] and the target path as [`DryRunMutation::target`].
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::DryRunLayer;
+/// use opendal::raw::Operation;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let layer = DryRunLayer::default();
+/// let op = Operator::new(services::Memory::default())?
+///     .layer(layer.clone())
+///     .finish();
+///
+/// op.write("path/to/file", "Hello, World!").await?;
+/// op.delete("path/to/file").await?;
+///
+/// let mutations = layer.mutations();
+/// assert_eq!(mutations.len(), 2);
+/// assert_eq!(mutations[0].operation(), Operation::Write);
+/// assert_eq!(mutations[0].path(), "path/to/file");
+/// assert_eq!(mutations[1].operation(), Operation::Delete);
+/// assert_eq!(mutations[1].path(), "path/to/file");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct DryRunLayer {
+    mutations: Arc<Mutex<Vec<DryRunMutation>>>,
+}
+
+impl DryRunLayer {
+    /// Get all recorded mutations in the order they happened.
+    pub fn mutations(&self) -> Vec<DryRunMutation> {
+        self.mutations.lock().clone()
+    }
+
+    /// Get the count of recorded mutations.
+    pub fn count(&self) -> usize {
+        self.mutations.lock().len()
+    }
+
+    /// Clear all recorded mutations.
+    pub fn clear(&self) {
+        self.mutations.lock().clear()
+    }
+}
+
+/// Mutation recorded by [`DryRunLayer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRunMutation {
+    operation: Operation,
+    path: String,
+    target: Option<String>,
+}
+
+impl DryRunMutation {
+    /// Get the operation of this mutation, like `Write` or `Rename`.
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Get the path of this mutation, which is the source path for
+    /// `rename` and `copy`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Get the target path of `rename` and `copy`, `None` for others.
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+}
+
+impl<A: Accessor> Layer<A> for DryRunLayer {
+    type LayeredAccessor = DryRunAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        DryRunAccessor {
+            inner,
+            mutations: self.mutations.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DryRunAccessor<A: Accessor> {
+    inner: A,
+    mutations: Arc<Mutex<Vec<DryRunMutation>>>,
+}
+
+impl<A: Accessor> DryRunAccessor<A> {
+    fn record(&self, op: Operation, path: &str) {
+        info!(
+            target: DRY_RUN_TARGET,
+            "service={} operation={} path={} -> skipped by dry run",
+            self.inner.info().scheme(),
+            op,
+            path
+        );
+
+        self.mutations.lock().push(DryRunMutation {
+            operation: op,
+            path: path.to_string(),
+            target: None,
+        });
+    }
+
+    fn record_pair(&self, op: Operation, from: &str, to: &str) {
+        info!(
+            target: DRY_RUN_TARGET,
+            "service={} operation={} from={} to={} -> skipped by dry run",
+            self.inner.info().scheme(),
+            op,
+            from,
+            to
+        );
+
+        self.mutations.lock().push(DryRunMutation {
+            operation: op,
+            path: from.to_string(),
+            target: Some(to.to_string()),
+        });
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for DryRunAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = DryRunWriter;
+    type BlockingWriter = DryRunWriter;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("DryRunLayer")
+    }
+
+    async fn create(&self, path: &str, _: OpCreate) -> Result<RpCreate> {
+        self.record(Operation::Create, path);
+
+        Ok(RpCreate::default())
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, _: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.record(Operation::Write, path);
+
+        Ok((RpWrite::new(), DryRunWriter))
+    }
+
+    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        self.record(Operation::Delete, path);
+
+        Ok(RpDelete::default())
+    }
+
+    async fn restore(&self, path: &str, _: OpRestore) -> Result<RpRestore> {
+        self.record(Operation::Restore, path);
+
+        Ok(RpRestore::default())
+    }
+
+    async fn abort_multipart(&self, path: &str, _: OpAbortMultipart) -> Result<RpAbortMultipart> {
+        self.record(Operation::AbortMultipart, path);
+
+        Ok(RpAbortMultipart::default())
+    }
+
+    async fn rename(&self, from: &str, to: &str, _: OpRename) -> Result<RpRename> {
+        self.record_pair(Operation::Rename, from, to);
+
+        Ok(RpRename::default())
+    }
+
+    async fn copy(&self, from: &str, to: &str, _: OpCopy) -> Result<RpCopy> {
+        self.record_pair(Operation::Copy, from, to);
+
+        Ok(RpCopy::default())
+    }
+
+    async fn get_acl(&self, path: &str, args: OpGetAcl) -> Result<RpGetAcl> {
+        self.inner.get_acl(path, args).await
+    }
+
+    async fn set_acl(&self, path: &str, _: OpSetAcl) -> Result<RpSetAcl> {
+        self.record(Operation::SetAcl, path);
+
+        Ok(RpSetAcl::default())
+    }
+
+    async fn get_tags(&self, path: &str, args: OpGetTags) -> Result<RpGetTags> {
+        self.inner.get_tags(path, args).await
+    }
+
+    async fn put_tags(&self, path: &str, _: OpPutTags) -> Result<RpPutTags> {
+        self.record(Operation::PutTags, path);
+
+        Ok(RpPutTags::default())
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        self.inner.scan(path, args).await
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        match args.into_operation() {
+            BatchOperations::Delete(ops) => {
+                let results = ops
+                    .into_iter()
+                    .map(|(path, _)| {
+                        self.record(Operation::Delete, &path);
+                        (path, Ok(RpDelete::default()))
+                    })
+                    .collect();
+
+                Ok(RpBatch::new(BatchedResults::Delete(results)))
+            }
+        }
+    }
+
+    fn blocking_create(&self, path: &str, _: OpCreate) -> Result<RpCreate> {
+        self.record(Operation::BlockingCreate, path);
+
+        Ok(RpCreate::default())
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, _: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.record(Operation::BlockingWrite, path);
+
+        Ok((RpWrite::new(), DryRunWriter))
+    }
+
+    fn blocking_delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        self.record(Operation::BlockingDelete, path);
+
+        Ok(RpDelete::default())
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        self.inner.blocking_scan(path, args)
+    }
+}
+
+/// DryRunWriter will discard all data written into it.
+pub struct DryRunWriter;
+
+#[async_trait]
+impl oio::Write for DryRunWriter {
+    async fn write(&mut self, _: Bytes) -> Result<()> {
+        Ok(())
+    }
+
+    async fn append(&mut self, _: Bytes) -> Result<()> {
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        Ok(RpWrite::new())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl oio::BlockingWrite for DryRunWriter {
+    fn write(&mut self, _: Bytes) -> Result<()> {
+        Ok(())
+    }
+
+    fn append(&mut self, _: Bytes) -> Result<()> {
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<RpWrite> {
+        Ok(RpWrite::new())
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::services::Memory;
+
+    #[tokio::test]
+    async fn test_dry_run() -> Result<()> {
+        let op = Operator::new(Memory::default())?.finish();
+        op.write("exist", "Hello, World!").await?;
+
+        let layer = DryRunLayer::default();
+        let dry = op.clone().layer(layer.clone());
+
+        // Reads are passed through.
+        assert_eq!(dry.read("exist").await?, b"Hello, World!");
+
+        dry.write("new", "Hello, World!").await?;
+        dry.delete("exist").await?;
+        dry.create_dir("dir/").await?;
+        dry.remove(vec!["exist".to_string(), "new".to_string()])
+            .await?;
+        dry.rename("exist", "renamed").await?;
+
+        let mutations: Vec<_> = layer
+            .mutations()
+            .into_iter()
+            .map(|m| (m.operation, m.path, m.target))
+            .collect();
+        assert_eq!(
+            mutations,
+            vec![
+                (Operation::Write, "new".to_string(), None),
+                (Operation::Delete, "exist".to_string(), None),
+                (Operation::Create, "dir/".to_string(), None),
+                (Operation::Delete, "exist".to_string(), None),
+                (Operation::Delete, "new".to_string(), None),
+                (
+                    Operation::Rename,
+                    "exist".to_string(),
+                    Some("renamed".to_string())
+                ),
+            ]
+        );
+
+        // Underlying services are not touched.
+        assert!(op.is_exist("exist").await?);
+        assert!(!op.is_exist("new").await?);
+
+        layer.clear();
+        assert_eq!(layer.count(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_copy() -> Result<()> {
+        let layer = DryRunLayer::default();
+        let acc = layer.layer(Memory::default().build()?);
+
+        Accessor::copy(&acc, "from", "to", OpCopy::new()).await?;
+
+        let mutations = layer.mutations();
+        assert_eq!(mutations.len(), 1);
+        assert_eq!(mutations[0].operation(), Operation::Copy);
+        assert_eq!(mutations[0].path(), "from");
+        assert_eq!(mutations[0].target(), Some("to"));
+        Ok(())
+    }
+}
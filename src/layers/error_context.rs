@@ -38,6 +38,10 @@ use crate::*;
 /// - `service`: The [`Scheme`] of underlying service.
 /// - `operation`: The [`Operation`] of this operation
 /// - `path`: The path of this operation
+///
+/// They could be accessed via [`Error::service`], [`Error::operation`] and
+/// [`Error::path`]. `rename` and `copy` carry `from` and `to` instead of
+/// `path`, please use [`Error::context_value`] for them.
 pub struct ErrorContextLayer;
 
 impl<A: Accessor> Layer<A> for ErrorContextLayer {
@@ -75,6 +79,10 @@ impl<A: Accessor> LayeredAccessor for ErrorContextAccessor<A> {
         &self.inner
     }
 
+    fn layer_name(&self) -> Option<&'static str> {
+        None
+    }
+
     fn metadata(&self) -> AccessorInfo {
         self.meta.clone()
     }
@@ -157,6 +165,100 @@ impl<A: Accessor> LayeredAccessor for ErrorContextAccessor<A> {
             .await
     }
 
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        self.inner
+            .restore(path, args)
+            .map_err(|err| {
+                err.with_operation(Operation::Restore)
+                    .with_context("service", self.meta.scheme())
+                    .with_context("path", path)
+            })
+            .await
+    }
+
+    async fn abort_multipart(
+        &self,
+        path: &str,
+        args: OpAbortMultipart,
+    ) -> Result<RpAbortMultipart> {
+        self.inner
+            .abort_multipart(path, args)
+            .map_err(|err| {
+                err.with_operation(Operation::AbortMultipart)
+                    .with_context("service", self.meta.scheme())
+                    .with_context("path", path)
+            })
+            .await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.inner
+            .rename(from, to, args)
+            .map_err(|err| {
+                err.with_operation(Operation::Rename)
+                    .with_context("service", self.meta.scheme())
+                    .with_context("from", from)
+                    .with_context("to", to)
+            })
+            .await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.inner
+            .copy(from, to, args)
+            .map_err(|err| {
+                err.with_operation(Operation::Copy)
+                    .with_context("service", self.meta.scheme())
+                    .with_context("from", from)
+                    .with_context("to", to)
+            })
+            .await
+    }
+
+    async fn get_acl(&self, path: &str, args: OpGetAcl) -> Result<RpGetAcl> {
+        self.inner
+            .get_acl(path, args)
+            .map_err(|err| {
+                err.with_operation(Operation::GetAcl)
+                    .with_context("service", self.meta.scheme())
+                    .with_context("path", path)
+            })
+            .await
+    }
+
+    async fn set_acl(&self, path: &str, args: OpSetAcl) -> Result<RpSetAcl> {
+        self.inner
+            .set_acl(path, args)
+            .map_err(|err| {
+                err.with_operation(Operation::SetAcl)
+                    .with_context("service", self.meta.scheme())
+                    .with_context("path", path)
+            })
+            .await
+    }
+
+    async fn get_tags(&self, path: &str, args: OpGetTags) -> Result<RpGetTags> {
+        self.inner
+            .get_tags(path, args)
+            .map_err(|err| {
+                err.with_operation(Operation::GetTags)
+                    .with_context("service", self.meta.scheme())
+                    .with_context("path", path)
+            })
+            .await
+    }
+
+    async fn put_tags(&self, path: &str, args: OpPutTags) -> Result<RpPutTags> {
+        self.inner
+            .put_tags(path, args)
+            .map_err(|err| {
+                err.with_operation(Operation::PutTags)
+                    .with_context("service", self.meta.scheme())
+                    .with_context("path", path)
+            })
+            .await
+    }
+
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
         self.inner
             .list(path, args)
@@ -417,13 +519,21 @@ impl<T: oio::Write> oio::Write for ErrorContextWrapper<T> {
         })
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn close(&mut self) -> Result<RpWrite> {
         self.inner.close().await.map_err(|err| {
             err.with_operation(WriteOperation::Close)
                 .with_context("service", self.scheme)
                 .with_context("path", &self.path)
         })
     }
+
+    async fn abort(&mut self) -> Result<()> {
+        self.inner.abort().await.map_err(|err| {
+            err.with_operation(WriteOperation::Abort)
+                .with_context("service", self.scheme)
+                .with_context("path", &self.path)
+        })
+    }
 }
 
 impl<T: oio::BlockingWrite> oio::BlockingWrite for ErrorContextWrapper<T> {
@@ -443,13 +553,21 @@ impl<T: oio::BlockingWrite> oio::BlockingWrite for ErrorContextWrapper<T> {
         })
     }
 
-    fn close(&mut self) -> Result<()> {
+    fn close(&mut self) -> Result<RpWrite> {
         self.inner.close().map_err(|err| {
             err.with_operation(WriteOperation::BlockingClose)
                 .with_context("service", self.scheme)
                 .with_context("path", &self.path)
         })
     }
+
+    fn abort(&mut self) -> Result<()> {
+        self.inner.abort().map_err(|err| {
+            err.with_operation(WriteOperation::BlockingAbort)
+                .with_context("service", self.scheme)
+                .with_context("path", &self.path)
+        })
+    }
 }
 
 #[async_trait::async_trait]
@@ -472,3 +590,26 @@ impl<T: oio::BlockingPage> oio::BlockingPage for ErrorContextWrapper<T> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Memory;
+
+    #[tokio::test]
+    async fn test_error_context_getters() {
+        let op = Operator::new(Memory::default()).unwrap().finish();
+
+        let err = op.stat("not_exist").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(err.operation(), Operation::Stat.into_static());
+        assert_eq!(err.service(), Some(Scheme::Memory.into_static()));
+        assert_eq!(err.path(), Some("not_exist"));
+        assert_eq!(err.status_code(), None);
+
+        let err = op.rename("not_exist", "file").await.unwrap_err();
+        assert_eq!(err.operation(), Operation::Rename.into_static());
+        assert_eq!(err.context_value("from"), Some("not_exist"));
+        assert_eq!(err.context_value("to"), Some("file"));
+    }
+}
@@ -12,11 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::fmt::Debug;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
 use std::mem;
+use std::path::Path;
+use std::sync::Arc;
 
 use async_trait::async_trait;
+use serde::Deserialize;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 
 use crate::ops::*;
 use crate::raw::*;
@@ -26,6 +34,25 @@ use crate::*;
 ///
 /// Especially useful for services without list capability like HTTP.
 ///
+/// # Index Format
+///
+/// Index could be loaded via [`ImmutableIndexLayer::from_reader`] or
+/// [`ImmutableIndexLayer::from_file`], every line is an entry:
+///
+/// - A plain key like `dir/file`, keys end with `/` are dirs.
+/// - A JSON object like `{"path": "dir/file", "size": 1024, "last_modified": "2023-01-01T00:00:00Z"}`,
+///   `size` and `last_modified` (in RFC 3339) are optional and will be
+///   returned as the metadata of listed entries.
+///
+/// Empty lines are ignored.
+///
+/// # Notes
+///
+/// Keys are sorted while building the layer, so `list` and `scan` only
+/// need to visit the entries under given path. Intermediate dirs like
+/// `dir/` of `dir/file` will be returned exactly once even if they are
+/// not in the index.
+///
 /// # Examples
 ///
 /// ```rust, no_run
@@ -44,9 +71,34 @@ use crate::*;
 ///     .layer(iil)
 ///     .finish();
 /// ```
+///
+/// Load index from file:
+///
+/// ```rust, no_run
+/// # use anyhow::Result;
+/// use opendal::layers::ImmutableIndexLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # fn main() -> Result<()> {
+/// let op = Operator::from_env::<services::Http>()?
+///     .layer(ImmutableIndexLayer::from_file("/path/to/index")?)
+///     .finish();
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Default, Debug, Clone)]
 pub struct ImmutableIndexLayer {
     vec: Vec<String>,
+    metas: HashMap<String, Metadata>,
+}
+
+/// The JSON format of index entry.
+#[derive(Deserialize)]
+struct IndexEntry {
+    path: String,
+    size: Option<u64>,
+    last_modified: Option<String>,
 }
 
 impl ImmutableIndexLayer {
@@ -62,6 +114,77 @@ impl ImmutableIndexLayer {
     {
         self.vec.extend(iter);
     }
+
+    /// Load index from given reader, read [`ImmutableIndexLayer`] for the
+    /// format.
+    pub fn from_reader(r: impl BufRead) -> Result<Self> {
+        let mut layer = Self::default();
+
+        for (idx, line) in r.lines().enumerate() {
+            let line = line.map_err(|err| {
+                Error::new(ErrorKind::Unexpected, "read index failed").set_source(err)
+            })?;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if !line.starts_with('{') {
+                layer.insert(line.to_string());
+                continue;
+            }
+
+            let (path, meta) = parse_index_entry(line)
+                .map_err(|err| err.with_context("line", (idx + 1).to_string()))?;
+            layer.metas.insert(path.clone(), meta);
+            layer.insert(path);
+        }
+
+        Ok(layer)
+    }
+
+    /// Load index from given file, read [`ImmutableIndexLayer`] for the
+    /// format.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let f = File::open(path).map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "open index file failed")
+                .with_context("path", path.to_string_lossy())
+                .set_source(err)
+        })?;
+
+        Self::from_reader(BufReader::new(f))
+            .map_err(|err| err.with_context("path", path.to_string_lossy()))
+    }
+}
+
+fn parse_index_entry(line: &str) -> Result<(String, Metadata)> {
+    let entry: IndexEntry = serde_json::from_str(line).map_err(|err| {
+        Error::new(ErrorKind::ConfigInvalid, "index entry is invalid").set_source(err)
+    })?;
+
+    let mode = if entry.path.ends_with('/') {
+        EntryMode::DIR
+    } else {
+        EntryMode::FILE
+    };
+    let mut meta = Metadata::new(mode);
+    if let Some(size) = entry.size {
+        meta = meta.with_content_length(size);
+    }
+    if let Some(v) = entry.last_modified {
+        let dt = OffsetDateTime::parse(&v, &Rfc3339).map_err(|err| {
+            Error::new(
+                ErrorKind::ConfigInvalid,
+                "index entry last_modified is invalid",
+            )
+            .with_context("last_modified", &v)
+            .set_source(err)
+        })?;
+        meta = meta.with_last_modified(dt);
+    }
+
+    Ok((entry.path, meta))
 }
 
 impl<A: Accessor> Layer<A> for ImmutableIndexLayer {
@@ -69,66 +192,95 @@ impl<A: Accessor> Layer<A> for ImmutableIndexLayer {
 
     fn layer(&self, inner: A) -> Self::LayeredAccessor {
         ImmutableIndexAccessor {
-            vec: self.vec.clone(),
+            index: Arc::new(Index::new(self.vec.clone(), self.metas.clone())),
             inner,
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct ImmutableIndexAccessor<A: Accessor> {
-    inner: A,
-    vec: Vec<String>,
+/// Index keeps keys sorted so that all keys under the same path are
+/// adjacent, and could be found via binary search.
+#[derive(Debug)]
+struct Index {
+    keys: Vec<String>,
+    metas: HashMap<String, Metadata>,
 }
 
-impl<A: Accessor> ImmutableIndexAccessor<A> {
+impl Index {
+    fn new(mut keys: Vec<String>, metas: HashMap<String, Metadata>) -> Self {
+        keys.sort_unstable();
+        keys.dedup();
+
+        Self { keys, metas }
+    }
+
+    /// Returns all keys start with given prefix.
+    fn range(&self, prefix: &str) -> &[String] {
+        let start = self.keys.partition_point(|k| k.as_str() < prefix);
+        let end = start + self.keys[start..].partition_point(|k| k.starts_with(prefix));
+
+        &self.keys[start..end]
+    }
+
     fn children_flat(&self, path: &str) -> Vec<String> {
-        self.vec
+        self.range(path)
             .iter()
-            .filter(|v| v.starts_with(path) && v.as_str() != path)
+            .filter(|v| v.as_str() != path)
             .cloned()
             .collect()
     }
 
     fn children_hierarchy(&self, path: &str) -> Vec<String> {
-        let mut res = HashSet::new();
+        let keys = self.range(path);
 
-        for i in self.vec.iter() {
-            // `/xyz` should not belong to `/abc`
-            if !i.starts_with(path) {
-                continue;
-            }
+        let mut res = Vec::new();
+        let mut idx = 0;
+        while idx < keys.len() {
+            let key = &keys[idx];
 
             // remove `/abc` if self
-            if i == path {
+            if key == path {
+                idx += 1;
                 continue;
             }
 
-            match i[path.len()..].find('/') {
+            match key[path.len()..].find('/') {
                 // File `/abc/def.csv` must belong to `/abc`
                 None => {
-                    res.insert(i.to_string());
+                    res.push(key.to_string());
+                    idx += 1;
                 }
-                Some(idx) => {
-                    // The index of first `/` after `/abc`.
-                    let dir_idx = idx + 1 + path.len();
-
-                    if dir_idx == i.len() {
-                        // Dir `/abc/def/` belongs to `/abc/`
-                        res.insert(i.to_string());
-                    } else {
-                        // File/Dir `/abc/def/xyz` doesn't belong to `/abc`.
-                        // But we need to list `/abc/def` out so that we can walk down.
-                        res.insert(i[..dir_idx].to_string());
-                    }
+                Some(i) => {
+                    // Dir `/abc/def/` belongs to `/abc/`, and File/Dir
+                    // `/abc/def/xyz` doesn't belong to `/abc` but we need
+                    // to list `/abc/def/` out so that we can walk down.
+                    let dir = &key[..path.len() + i + 1];
+                    res.push(dir.to_string());
+
+                    // Skip all keys under this dir.
+                    idx += keys[idx..].partition_point(|k| k.starts_with(dir));
                 }
             }
         }
 
-        res.into_iter().collect()
+        res
+    }
+
+    fn metadata(&self, path: &str) -> Metadata {
+        match self.metas.get(path) {
+            Some(meta) => meta.clone(),
+            None if path.ends_with('/') => Metadata::new(EntryMode::DIR),
+            None => Metadata::new(EntryMode::FILE),
+        }
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct ImmutableIndexAccessor<A: Accessor> {
+    inner: A,
+    index: Arc<Index>,
+}
+
 #[async_trait]
 impl<A: Accessor> LayeredAccessor for ImmutableIndexAccessor<A> {
     type Inner = A;
@@ -143,6 +295,10 @@ impl<A: Accessor> LayeredAccessor for ImmutableIndexAccessor<A> {
         &self.inner
     }
 
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("ImmutableIndexLayer")
+    }
+
     /// Add list capabilities for underlying storage services.
     fn metadata(&self) -> AccessorInfo {
         let mut meta = self.inner.info();
@@ -151,6 +307,12 @@ impl<A: Accessor> LayeredAccessor for ImmutableIndexAccessor<A> {
         );
         meta.set_hints(meta.hints());
 
+        let mut cap = meta.capability();
+        cap.list = true;
+        cap.list_with_delimiter = true;
+        cap.scan = true;
+        meta.set_capability(cap);
+
         meta
     }
 
@@ -166,7 +328,7 @@ impl<A: Accessor> LayeredAccessor for ImmutableIndexAccessor<A> {
 
         Ok((
             RpList::default(),
-            ImmutableDir::new(self.children_hierarchy(path)),
+            ImmutableDir::new(self.index.clone(), self.index.children_hierarchy(path)),
         ))
     }
 
@@ -178,7 +340,7 @@ impl<A: Accessor> LayeredAccessor for ImmutableIndexAccessor<A> {
 
         Ok((
             RpScan::default(),
-            ImmutableDir::new(self.children_flat(path)),
+            ImmutableDir::new(self.index.clone(), self.index.children_flat(path)),
         ))
     }
 
@@ -202,7 +364,7 @@ impl<A: Accessor> LayeredAccessor for ImmutableIndexAccessor<A> {
 
         Ok((
             RpList::default(),
-            ImmutableDir::new(self.children_hierarchy(path)),
+            ImmutableDir::new(self.index.clone(), self.index.children_hierarchy(path)),
         ))
     }
 
@@ -214,18 +376,19 @@ impl<A: Accessor> LayeredAccessor for ImmutableIndexAccessor<A> {
 
         Ok((
             RpScan::default(),
-            ImmutableDir::new(self.children_flat(path)),
+            ImmutableDir::new(self.index.clone(), self.index.children_flat(path)),
         ))
     }
 }
 
 pub struct ImmutableDir {
+    index: Arc<Index>,
     idx: Vec<String>,
 }
 
 impl ImmutableDir {
-    fn new(idx: Vec<String>) -> Self {
-        Self { idx }
+    fn new(index: Arc<Index>, idx: Vec<String>) -> Self {
+        Self { index, idx }
     }
 
     fn inner_next_page(&mut self) -> Option<Vec<oio::Entry>> {
@@ -238,12 +401,7 @@ impl ImmutableDir {
         Some(
             vs.into_iter()
                 .map(|v| {
-                    let mode = if v.ends_with('/') {
-                        EntryMode::DIR
-                    } else {
-                        EntryMode::FILE
-                    };
-                    let meta = Metadata::new(mode);
+                    let meta = self.index.metadata(&v);
                     oio::Entry::with(v, meta)
                 })
                 .collect(),
@@ -461,4 +619,76 @@ mod tests {
         assert_eq!(map["dataset/stateful/ontime_2009_200.csv"], EntryMode::FILE);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_from_reader() -> Result<()> {
+        let index = r#"
+file
+{"path": "dir/file", "size": 1024, "last_modified": "2023-01-01T00:00:00Z"}
+{"path": "dir/another"}
+"#;
+        let iil = ImmutableIndexLayer::from_reader(index.as_bytes())?;
+
+        let op = Operator::new(Http::from_iter(
+            vec![("endpoint".to_string(), "https://xuanwo.io".to_string())].into_iter(),
+        ))?
+        .layer(iil)
+        .finish();
+
+        let entries: Vec<_> = op.list("/").await?.try_collect().await?;
+        let paths: Vec<_> = entries.iter().map(|v| v.path()).collect();
+        assert_eq!(paths, ["dir/", "file"]);
+
+        let entries: Vec<_> = op.list("dir/").await?.try_collect().await?;
+        let paths: Vec<_> = entries.iter().map(|v| v.path()).collect();
+        assert_eq!(paths, ["dir/another", "dir/file"]);
+
+        let meta = op.metadata(&entries[1], Metakey::ContentLength).await?;
+        assert_eq!(meta.content_length(), 1024);
+        assert_eq!(
+            meta.last_modified(),
+            Some(OffsetDateTime::parse("2023-01-01T00:00:00Z", &Rfc3339)?)
+        );
+
+        let err = ImmutableIndexLayer::from_reader("file\n{\"size\": 1}".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_index() {
+        let keys: Vec<_> = (0..100_000)
+            .map(|i| format!("data/{}/{}/file_{i}", i / 1000, i / 100 % 10))
+            .rev()
+            .collect();
+        let index = Index::new(keys, HashMap::new());
+
+        let dirs = index.children_hierarchy("");
+        assert_eq!(dirs, ["data/"]);
+
+        let dirs = index.children_hierarchy("data/");
+        assert_eq!(dirs.len(), 100);
+        assert_eq!(dirs.iter().collect::<HashSet<_>>().len(), 100);
+        assert!(dirs.iter().all(|v| v.ends_with('/')));
+
+        let dirs = index.children_hierarchy("data/42/");
+        let expected: Vec<_> = (0..10).map(|i| format!("data/42/{i}/")).collect();
+        assert_eq!(dirs, expected);
+
+        let files = index.children_hierarchy("data/42/7/");
+        assert_eq!(files.len(), 100);
+        assert!(files
+            .iter()
+            .all(|v| v.starts_with("data/42/7/file_427") && !v.ends_with('/')));
+
+        assert_eq!(index.children_flat("data/42/").len(), 1000);
+
+        // Binary search only needs ~17 comparisons per lookup, scanning
+        // all keys for every lookup would take minutes here.
+        let now = std::time::Instant::now();
+        for _ in 0..100_000 {
+            assert_eq!(index.range("data/42/7/").len(), 100);
+        }
+        assert!(now.elapsed() < std::time::Duration::from_secs(5));
+    }
 }
@@ -175,6 +175,10 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
         &self.inner
     }
 
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("LoggingLayer")
+    }
+
     fn metadata(&self) -> AccessorInfo {
         debug!(
             target: LOGGING_TARGET,
@@ -399,6 +403,320 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             .await
     }
 
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        debug!(
+            target: LOGGING_TARGET,
+            "service={} operation={} path={} -> started",
+            self.scheme,
+            Operation::Restore,
+            path
+        );
+
+        self.inner
+            .restore(path, args)
+            .inspect(|v| match v {
+                Ok(_) => {
+                    debug!(
+                        target: LOGGING_TARGET,
+                        "service={} operation={} path={} -> finished",
+                        self.scheme,
+                        Operation::Restore,
+                        path
+                    );
+                }
+                Err(err) => {
+                    if let Some(lvl) = self.err_level(err) {
+                        log!(
+                            target: LOGGING_TARGET,
+                            lvl,
+                            "service={} operation={} path={} -> {}: {err:?}",
+                            self.scheme,
+                            Operation::Restore,
+                            path,
+                            self.err_status(err)
+                        );
+                    }
+                }
+            })
+            .await
+    }
+
+    async fn abort_multipart(
+        &self,
+        path: &str,
+        args: OpAbortMultipart,
+    ) -> Result<RpAbortMultipart> {
+        debug!(
+            target: LOGGING_TARGET,
+            "service={} operation={} path={} -> started",
+            self.scheme,
+            Operation::AbortMultipart,
+            path
+        );
+
+        self.inner
+            .abort_multipart(path, args)
+            .inspect(|v| match v {
+                Ok(_) => {
+                    debug!(
+                        target: LOGGING_TARGET,
+                        "service={} operation={} path={} -> finished",
+                        self.scheme,
+                        Operation::AbortMultipart,
+                        path
+                    );
+                }
+                Err(err) => {
+                    if let Some(lvl) = self.err_level(err) {
+                        log!(
+                            target: LOGGING_TARGET,
+                            lvl,
+                            "service={} operation={} path={} -> {}: {err:?}",
+                            self.scheme,
+                            Operation::AbortMultipart,
+                            path,
+                            self.err_status(err)
+                        );
+                    }
+                }
+            })
+            .await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        debug!(
+            target: LOGGING_TARGET,
+            "service={} operation={} from={} to={} -> started",
+            self.scheme,
+            Operation::Rename,
+            from,
+            to
+        );
+
+        self.inner
+            .rename(from, to, args)
+            .inspect(|v| match v {
+                Ok(_) => {
+                    debug!(
+                        target: LOGGING_TARGET,
+                        "service={} operation={} from={} to={} -> finished",
+                        self.scheme,
+                        Operation::Rename,
+                        from,
+                        to
+                    );
+                }
+                Err(err) => {
+                    if let Some(lvl) = self.err_level(err) {
+                        log!(
+                            target: LOGGING_TARGET,
+                            lvl,
+                            "service={} operation={} from={} to={} -> {}: {err:?}",
+                            self.scheme,
+                            Operation::Rename,
+                            from,
+                            to,
+                            self.err_status(err)
+                        );
+                    }
+                }
+            })
+            .await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        debug!(
+            target: LOGGING_TARGET,
+            "service={} operation={} from={} to={} -> started",
+            self.scheme,
+            Operation::Copy,
+            from,
+            to
+        );
+
+        self.inner
+            .copy(from, to, args)
+            .inspect(|v| match v {
+                Ok(_) => {
+                    debug!(
+                        target: LOGGING_TARGET,
+                        "service={} operation={} from={} to={} -> finished",
+                        self.scheme,
+                        Operation::Copy,
+                        from,
+                        to
+                    );
+                }
+                Err(err) => {
+                    if let Some(lvl) = self.err_level(err) {
+                        log!(
+                            target: LOGGING_TARGET,
+                            lvl,
+                            "service={} operation={} from={} to={} -> {}: {err:?}",
+                            self.scheme,
+                            Operation::Copy,
+                            from,
+                            to,
+                            self.err_status(err)
+                        );
+                    }
+                }
+            })
+            .await
+    }
+
+    async fn get_acl(&self, path: &str, args: OpGetAcl) -> Result<RpGetAcl> {
+        debug!(
+            target: LOGGING_TARGET,
+            "service={} operation={} path={} -> started",
+            self.scheme,
+            Operation::GetAcl,
+            path
+        );
+
+        self.inner
+            .get_acl(path, args)
+            .inspect(|v| match v {
+                Ok(_) => {
+                    debug!(
+                        target: LOGGING_TARGET,
+                        "service={} operation={} path={} -> finished",
+                        self.scheme,
+                        Operation::GetAcl,
+                        path
+                    );
+                }
+                Err(err) => {
+                    if let Some(lvl) = self.err_level(err) {
+                        log!(
+                            target: LOGGING_TARGET,
+                            lvl,
+                            "service={} operation={} path={} -> {}: {err:?}",
+                            self.scheme,
+                            Operation::GetAcl,
+                            path,
+                            self.err_status(err)
+                        );
+                    }
+                }
+            })
+            .await
+    }
+
+    async fn set_acl(&self, path: &str, args: OpSetAcl) -> Result<RpSetAcl> {
+        debug!(
+            target: LOGGING_TARGET,
+            "service={} operation={} path={} -> started",
+            self.scheme,
+            Operation::SetAcl,
+            path
+        );
+
+        self.inner
+            .set_acl(path, args)
+            .inspect(|v| match v {
+                Ok(_) => {
+                    debug!(
+                        target: LOGGING_TARGET,
+                        "service={} operation={} path={} -> finished",
+                        self.scheme,
+                        Operation::SetAcl,
+                        path
+                    );
+                }
+                Err(err) => {
+                    if let Some(lvl) = self.err_level(err) {
+                        log!(
+                            target: LOGGING_TARGET,
+                            lvl,
+                            "service={} operation={} path={} -> {}: {err:?}",
+                            self.scheme,
+                            Operation::SetAcl,
+                            path,
+                            self.err_status(err)
+                        );
+                    }
+                }
+            })
+            .await
+    }
+
+    async fn get_tags(&self, path: &str, args: OpGetTags) -> Result<RpGetTags> {
+        debug!(
+            target: LOGGING_TARGET,
+            "service={} operation={} path={} -> started",
+            self.scheme,
+            Operation::GetTags,
+            path
+        );
+
+        self.inner
+            .get_tags(path, args)
+            .inspect(|v| match v {
+                Ok(_) => {
+                    debug!(
+                        target: LOGGING_TARGET,
+                        "service={} operation={} path={} -> finished",
+                        self.scheme,
+                        Operation::GetTags,
+                        path
+                    );
+                }
+                Err(err) => {
+                    if let Some(lvl) = self.err_level(err) {
+                        log!(
+                            target: LOGGING_TARGET,
+                            lvl,
+                            "service={} operation={} path={} -> {}: {err:?}",
+                            self.scheme,
+                            Operation::GetTags,
+                            path,
+                            self.err_status(err)
+                        );
+                    }
+                }
+            })
+            .await
+    }
+
+    async fn put_tags(&self, path: &str, args: OpPutTags) -> Result<RpPutTags> {
+        debug!(
+            target: LOGGING_TARGET,
+            "service={} operation={} path={} -> started",
+            self.scheme,
+            Operation::PutTags,
+            path
+        );
+
+        self.inner
+            .put_tags(path, args)
+            .inspect(|v| match v {
+                Ok(_) => {
+                    debug!(
+                        target: LOGGING_TARGET,
+                        "service={} operation={} path={} -> finished",
+                        self.scheme,
+                        Operation::PutTags,
+                        path
+                    );
+                }
+                Err(err) => {
+                    if let Some(lvl) = self.err_level(err) {
+                        log!(
+                            target: LOGGING_TARGET,
+                            lvl,
+                            "service={} operation={} path={} -> {}: {err:?}",
+                            self.scheme,
+                            Operation::PutTags,
+                            path,
+                            self.err_status(err)
+                        );
+                    }
+                }
+            })
+            .await
+    }
+
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
         debug!(
             target: LOGGING_TARGET,
@@ -167,6 +167,29 @@ struct MetricsHandler {
     requests_total_batch: Counter,
     requests_duration_seconds_batch: Histogram,
 
+    requests_total_restore: Counter,
+    requests_duration_seconds_restore: Histogram,
+    requests_total_abort_multipart: Counter,
+    requests_duration_seconds_abort_multipart: Histogram,
+
+    requests_total_rename: Counter,
+    requests_duration_seconds_rename: Histogram,
+
+    requests_total_copy: Counter,
+    requests_duration_seconds_copy: Histogram,
+
+    requests_total_get_acl: Counter,
+    requests_duration_seconds_get_acl: Histogram,
+
+    requests_total_set_acl: Counter,
+    requests_duration_seconds_set_acl: Histogram,
+
+    requests_total_get_tags: Counter,
+    requests_duration_seconds_get_tags: Histogram,
+
+    requests_total_put_tags: Counter,
+    requests_duration_seconds_put_tags: Histogram,
+
     requests_total_blocking_create: Counter,
     requests_duration_seconds_blocking_create: Histogram,
 
@@ -317,6 +340,94 @@ impl MetricsHandler {
                 LABEL_OPERATION => Operation::Batch.into_static(),
             ),
 
+            requests_total_restore: register_counter!(
+                METRIC_REQUESTS_TOTAL,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::Restore.into_static(),
+            ),
+            requests_duration_seconds_restore: register_histogram!(
+                METRIC_REQUESTS_DURATION_SECONDS,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::Restore.into_static(),
+            ),
+
+            requests_total_abort_multipart: register_counter!(
+                METRIC_REQUESTS_TOTAL,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::AbortMultipart.into_static(),
+            ),
+            requests_duration_seconds_abort_multipart: register_histogram!(
+                METRIC_REQUESTS_DURATION_SECONDS,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::AbortMultipart.into_static(),
+            ),
+
+            requests_total_rename: register_counter!(
+                METRIC_REQUESTS_TOTAL,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::Rename.into_static(),
+            ),
+            requests_duration_seconds_rename: register_histogram!(
+                METRIC_REQUESTS_DURATION_SECONDS,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::Rename.into_static(),
+            ),
+
+            requests_total_copy: register_counter!(
+                METRIC_REQUESTS_TOTAL,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::Copy.into_static(),
+            ),
+            requests_duration_seconds_copy: register_histogram!(
+                METRIC_REQUESTS_DURATION_SECONDS,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::Copy.into_static(),
+            ),
+
+            requests_total_get_acl: register_counter!(
+                METRIC_REQUESTS_TOTAL,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::GetAcl.into_static(),
+            ),
+            requests_duration_seconds_get_acl: register_histogram!(
+                METRIC_REQUESTS_DURATION_SECONDS,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::GetAcl.into_static(),
+            ),
+
+            requests_total_set_acl: register_counter!(
+                METRIC_REQUESTS_TOTAL,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::SetAcl.into_static(),
+            ),
+            requests_duration_seconds_set_acl: register_histogram!(
+                METRIC_REQUESTS_DURATION_SECONDS,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::SetAcl.into_static(),
+            ),
+
+            requests_total_get_tags: register_counter!(
+                METRIC_REQUESTS_TOTAL,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::GetTags.into_static(),
+            ),
+            requests_duration_seconds_get_tags: register_histogram!(
+                METRIC_REQUESTS_DURATION_SECONDS,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::GetTags.into_static(),
+            ),
+
+            requests_total_put_tags: register_counter!(
+                METRIC_REQUESTS_TOTAL,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::PutTags.into_static(),
+            ),
+            requests_duration_seconds_put_tags: register_histogram!(
+                METRIC_REQUESTS_DURATION_SECONDS,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::PutTags.into_static(),
+            ),
+
             requests_total_blocking_create: register_counter!(
                 METRIC_REQUESTS_TOTAL,
                 LABEL_SERVICE => service,
@@ -447,6 +558,10 @@ impl<A: Accessor> LayeredAccessor for MetricsAccessor<A> {
         &self.inner
     }
 
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("MetricsLayer")
+    }
+
     fn metadata(&self) -> AccessorInfo {
         self.handle.requests_total_metadata.increment(1);
 
@@ -567,6 +682,164 @@ impl<A: Accessor> LayeredAccessor for MetricsAccessor<A> {
             .await
     }
 
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        self.handle.requests_total_restore.increment(1);
+
+        let start = Instant::now();
+
+        self.inner
+            .restore(path, args)
+            .inspect_ok(|_| {
+                let dur = start.elapsed().as_secs_f64();
+
+                self.handle.requests_duration_seconds_restore.record(dur);
+            })
+            .inspect_err(|e| {
+                self.handle
+                    .increment_errors_total(Operation::Restore, e.kind());
+            })
+            .await
+    }
+
+    async fn abort_multipart(
+        &self,
+        path: &str,
+        args: OpAbortMultipart,
+    ) -> Result<RpAbortMultipart> {
+        self.handle.requests_total_abort_multipart.increment(1);
+
+        let start = Instant::now();
+
+        self.inner
+            .abort_multipart(path, args)
+            .inspect_ok(|_| {
+                let dur = start.elapsed().as_secs_f64();
+
+                self.handle
+                    .requests_duration_seconds_abort_multipart
+                    .record(dur);
+            })
+            .inspect_err(|e| {
+                self.handle
+                    .increment_errors_total(Operation::AbortMultipart, e.kind());
+            })
+            .await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.handle.requests_total_rename.increment(1);
+
+        let start = Instant::now();
+
+        self.inner
+            .rename(from, to, args)
+            .inspect_ok(|_| {
+                let dur = start.elapsed().as_secs_f64();
+
+                self.handle.requests_duration_seconds_rename.record(dur);
+            })
+            .inspect_err(|e| {
+                self.handle
+                    .increment_errors_total(Operation::Rename, e.kind());
+            })
+            .await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.handle.requests_total_copy.increment(1);
+
+        let start = Instant::now();
+
+        self.inner
+            .copy(from, to, args)
+            .inspect_ok(|_| {
+                let dur = start.elapsed().as_secs_f64();
+
+                self.handle.requests_duration_seconds_copy.record(dur);
+            })
+            .inspect_err(|e| {
+                self.handle
+                    .increment_errors_total(Operation::Copy, e.kind());
+            })
+            .await
+    }
+
+    async fn get_acl(&self, path: &str, args: OpGetAcl) -> Result<RpGetAcl> {
+        self.handle.requests_total_get_acl.increment(1);
+
+        let start = Instant::now();
+
+        self.inner
+            .get_acl(path, args)
+            .inspect_ok(|_| {
+                let dur = start.elapsed().as_secs_f64();
+
+                self.handle.requests_duration_seconds_get_acl.record(dur);
+            })
+            .inspect_err(|e| {
+                self.handle
+                    .increment_errors_total(Operation::GetAcl, e.kind());
+            })
+            .await
+    }
+
+    async fn set_acl(&self, path: &str, args: OpSetAcl) -> Result<RpSetAcl> {
+        self.handle.requests_total_set_acl.increment(1);
+
+        let start = Instant::now();
+
+        self.inner
+            .set_acl(path, args)
+            .inspect_ok(|_| {
+                let dur = start.elapsed().as_secs_f64();
+
+                self.handle.requests_duration_seconds_set_acl.record(dur);
+            })
+            .inspect_err(|e| {
+                self.handle
+                    .increment_errors_total(Operation::SetAcl, e.kind());
+            })
+            .await
+    }
+
+    async fn get_tags(&self, path: &str, args: OpGetTags) -> Result<RpGetTags> {
+        self.handle.requests_total_get_tags.increment(1);
+
+        let start = Instant::now();
+
+        self.inner
+            .get_tags(path, args)
+            .inspect_ok(|_| {
+                let dur = start.elapsed().as_secs_f64();
+
+                self.handle.requests_duration_seconds_get_tags.record(dur);
+            })
+            .inspect_err(|e| {
+                self.handle
+                    .increment_errors_total(Operation::GetTags, e.kind());
+            })
+            .await
+    }
+
+    async fn put_tags(&self, path: &str, args: OpPutTags) -> Result<RpPutTags> {
+        self.handle.requests_total_put_tags.increment(1);
+
+        let start = Instant::now();
+
+        self.inner
+            .put_tags(path, args)
+            .inspect_ok(|_| {
+                let dur = start.elapsed().as_secs_f64();
+
+                self.handle.requests_duration_seconds_put_tags.record(dur);
+            })
+            .inspect_err(|e| {
+                self.handle
+                    .increment_errors_total(Operation::PutTags, e.kind());
+            })
+            .await
+    }
+
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
         self.handle.requests_total_list.increment(1);
 
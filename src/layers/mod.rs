@@ -17,25 +17,73 @@
 mod concurrent_limit;
 pub use concurrent_limit::ConcurrentLimitLayer;
 
+mod dry_run;
+pub use dry_run::DryRunLayer;
+pub use dry_run::DryRunMutation;
+
 mod immutable_index;
 pub use immutable_index::ImmutableIndexLayer;
 
 mod logging;
 pub use logging::LoggingLayer;
 
+#[cfg(feature = "layers-batch")]
+mod batch;
+#[cfg(feature = "layers-batch")]
+pub use batch::BatchLayer;
+
 #[cfg(feature = "layers-chaos")]
 mod chaos;
 #[cfg(feature = "layers-chaos")]
 pub use chaos::ChaosLayer;
 
+#[cfg(feature = "layers-decompress")]
+mod decompress;
+#[cfg(feature = "layers-decompress")]
+pub use decompress::DecompressLayer;
+
+#[cfg(feature = "layers-delay")]
+mod delay;
+#[cfg(feature = "layers-delay")]
+pub use delay::DelayLayer;
+
 #[cfg(feature = "layers-metrics")]
 mod metrics;
 #[cfg(feature = "layers-metrics")]
 pub use self::metrics::MetricsLayer;
 
+mod poison;
+pub use poison::PoisonLayer;
+pub use poison::PoisonListPolicy;
+
+mod prefix_index;
+pub use prefix_index::PrefixIndexLayer;
+
+mod progress;
+pub use progress::Progress;
+pub use progress::ProgressLayer;
+pub use progress::ProgressState;
+
+#[cfg(feature = "layers-redact-error")]
+mod redact_error;
+#[cfg(feature = "layers-redact-error")]
+pub use redact_error::RedactErrorLayer;
+
+mod reencode;
+pub use reencode::ReencodeLayer;
+
 mod retry;
 pub use self::retry::RetryLayer;
 
+mod stat_cache;
+pub use stat_cache::StatCacheLayer;
+
+mod subdir;
+pub use subdir::SubdirLayer;
+
+mod write_once;
+pub use write_once::WriteOnceLayer;
+
 #[cfg(feature = "layers-tracing")]
 mod tracing;
 #[cfg(feature = "layers-tracing")]
@@ -49,3 +97,6 @@ pub(crate) use error_context::ErrorContextLayer;
 
 mod complete;
 pub(crate) use complete::CompleteLayer;
+
+mod deadline;
+pub(crate) use deadline::DeadlineLayer;
@@ -0,0 +1,455 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::warn;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// Reject poisoned paths before they reach underlying storage services.
+///
+/// A path is poisoned if it contains:
+///
+/// - control characters, including the embedded NUL `\0`.
+/// - the replacement character `U+FFFD`, which means the path is decoded
+///   from invalid UTF-8 lossily.
+/// - non-ASCII characters, if [`PoisonLayer::allow_non_ascii`] is disabled.
+/// - characters added by [`PoisonLayer::deny_char`].
+///
+/// `create`, `write` and the targets of `copy` and `rename` on poisoned
+/// paths will fail with [`ErrorKind::Unexpected`], nothing will be sent.
+/// Paths with embedded NUL are rejected by [`check_path`] with
+/// [`ErrorKind::ConfigInvalid`] instead, the same as other operations.
+///
+/// Poisoned entries returned by `list` and `scan` are kept by default, use
+/// [`PoisonLayer::with_list_policy`] to warn about or skip them.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::PoisonLayer;
+/// use opendal::services;
+/// use opendal::ErrorKind;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let op = Operator::new(services::Memory::default())?
+///     .layer(PoisonLayer::new().allow_non_ascii(false))
+///     .finish();
+///
+/// op.write("data/file", "Hello, World!").await?;
+/// let err = op.write("data/fi\nle", "Hello, World!").await.unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::Unexpected);
+/// let err = op.write("data/文件", "Hello, World!").await.unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::Unexpected);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PoisonLayer {
+    allow_non_ascii: bool,
+    denied_chars: Vec<char>,
+    list_policy: PoisonListPolicy,
+}
+
+impl Default for PoisonLayer {
+    fn default() -> Self {
+        Self {
+            allow_non_ascii: true,
+            denied_chars: Vec::new(),
+            list_policy: PoisonListPolicy::Keep,
+        }
+    }
+}
+
+impl PoisonLayer {
+    /// Create a new PoisonLayer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow non-ASCII characters in paths or not.
+    ///
+    /// Default to `true`.
+    pub fn allow_non_ascii(mut self, allow: bool) -> Self {
+        self.allow_non_ascii = allow;
+        self
+    }
+
+    /// Reject paths containing given character.
+    ///
+    /// For example, `deny_char('\\')` to reject windows style separators.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `c` is `/`.
+    pub fn deny_char(mut self, c: char) -> Self {
+        assert_ne!(c, '/', "`/` can't be denied by PoisonLayer");
+
+        self.denied_chars.push(c);
+        self
+    }
+
+    /// Set the policy of poisoned entries returned by `list` and `scan`.
+    ///
+    /// Default to [`PoisonListPolicy::Keep`].
+    pub fn with_list_policy(mut self, policy: PoisonListPolicy) -> Self {
+        self.list_policy = policy;
+        self
+    }
+}
+
+/// PoisonListPolicy decides what to do with poisoned entries while listing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PoisonListPolicy {
+    /// Return poisoned entries as is.
+    Keep,
+    /// Return poisoned entries and log a warning for each of them.
+    Warn,
+    /// Skip poisoned entries silently.
+    Skip,
+}
+
+impl<A: Accessor> Layer<A> for PoisonLayer {
+    type LayeredAccessor = PoisonAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        PoisonAccessor {
+            inner,
+            checker: Arc::new(PoisonChecker {
+                allow_non_ascii: self.allow_non_ascii,
+                denied_chars: self.denied_chars.clone(),
+            }),
+            list_policy: self.list_policy,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PoisonChecker {
+    allow_non_ascii: bool,
+    denied_chars: Vec<char>,
+}
+
+impl PoisonChecker {
+    /// Returns the first poisoned character in path.
+    fn find_poisoned(&self, path: &str) -> Option<char> {
+        path.chars().find(|c| {
+            c.is_control()
+                || *c == char::REPLACEMENT_CHARACTER
+                || (!self.allow_non_ascii && !c.is_ascii())
+                || self.denied_chars.contains(c)
+        })
+    }
+
+    fn check(&self, op: Operation, path: &str) -> Result<()> {
+        check_path(path).map_err(|err| err.with_operation(op))?;
+
+        match self.find_poisoned(path) {
+            None => Ok(()),
+            Some(c) => Err(
+                Error::new(ErrorKind::Unexpected, "path contains poisoned character")
+                    .with_operation(op)
+                    .with_context("path", path.escape_debug().to_string())
+                    .with_context("character", c.escape_unicode().to_string()),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PoisonAccessor<A: Accessor> {
+    inner: A,
+    checker: Arc<PoisonChecker>,
+    list_policy: PoisonListPolicy,
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for PoisonAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = PoisonPager<A::Pager>;
+    type BlockingPager = PoisonPager<A::BlockingPager>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("PoisonLayer")
+    }
+
+    async fn create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        self.checker.check(Operation::Create, path)?;
+
+        self.inner.create(path, args).await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.checker.check(Operation::Write, path)?;
+
+        self.inner.write(path, args).await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.checker.check(Operation::Copy, to)?;
+
+        self.inner.copy(from, to, args).await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.checker.check(Operation::Rename, to)?;
+
+        self.inner.rename(from, to, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        let (rp, p) = self.inner.list(path, args).await?;
+
+        Ok((rp, PoisonPager::new(p, self)))
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        let (rp, p) = self.inner.scan(path, args).await?;
+
+        Ok((rp, PoisonPager::new(p, self)))
+    }
+
+    fn blocking_create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        self.checker.check(Operation::BlockingCreate, path)?;
+
+        self.inner.blocking_create(path, args)
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.checker.check(Operation::BlockingWrite, path)?;
+
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        let (rp, p) = self.inner.blocking_list(path, args)?;
+
+        Ok((rp, PoisonPager::new(p, self)))
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        let (rp, p) = self.inner.blocking_scan(path, args)?;
+
+        Ok((rp, PoisonPager::new(p, self)))
+    }
+}
+
+pub struct PoisonPager<P> {
+    inner: P,
+    checker: Arc<PoisonChecker>,
+    list_policy: PoisonListPolicy,
+}
+
+impl<P> PoisonPager<P> {
+    fn new<A: Accessor>(inner: P, acc: &PoisonAccessor<A>) -> Self {
+        Self {
+            inner,
+            checker: acc.checker.clone(),
+            list_policy: acc.list_policy,
+        }
+    }
+
+    fn filter_entries(&self, mut entries: Vec<oio::Entry>) -> Vec<oio::Entry> {
+        if self.list_policy == PoisonListPolicy::Keep {
+            return entries;
+        }
+
+        entries.retain(|entry| {
+            let c = match self.checker.find_poisoned(entry.path()) {
+                None => return true,
+                Some(c) => c,
+            };
+
+            match self.list_policy {
+                PoisonListPolicy::Warn => {
+                    warn!(
+                        target: "opendal::layers::poison",
+                        "listed path {:?} contains poisoned character {}",
+                        entry.path(),
+                        c.escape_unicode()
+                    );
+                    true
+                }
+                _ => false,
+            }
+        });
+        entries
+    }
+}
+
+#[async_trait]
+impl<P: oio::Page> oio::Page for PoisonPager<P> {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        // Skip pages that all entries have been filtered out.
+        loop {
+            match self.inner.next().await? {
+                None => return Ok(None),
+                Some(entries) => {
+                    let entries = self.filter_entries(entries);
+                    if !entries.is_empty() {
+                        return Ok(Some(entries));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<P: oio::BlockingPage> oio::BlockingPage for PoisonPager<P> {
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        // Skip pages that all entries have been filtered out.
+        loop {
+            match self.inner.next()? {
+                None => return Ok(None),
+                Some(entries) => {
+                    let entries = self.filter_entries(entries);
+                    if !entries.is_empty() {
+                        return Ok(Some(entries));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::services::Memory;
+
+    #[test]
+    fn test_find_poisoned() {
+        let checker = PoisonChecker {
+            allow_non_ascii: true,
+            denied_chars: vec!['\\'],
+        };
+
+        let cases = vec![
+            ("dir/file", None),
+            ("dir/文件", None),
+            ("dir/file\0", Some('\0')),
+            ("dir/\nfile", Some('\n')),
+            ("dir/\u{7f}", Some('\u{7f}')),
+            ("dir/\u{fffd}", Some('\u{fffd}')),
+            ("dir\\file", Some('\\')),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(checker.find_poisoned(path), expected, "{path:?}");
+        }
+
+        let checker = PoisonChecker {
+            allow_non_ascii: false,
+            denied_chars: vec![],
+        };
+        assert_eq!(checker.find_poisoned("dir/文件"), Some('文'));
+        assert_eq!(checker.find_poisoned("dir\\file"), None);
+    }
+
+    #[tokio::test]
+    async fn test_poison_write() -> Result<()> {
+        let op = Operator::new(Memory::default())?
+            .layer(PoisonLayer::new())
+            .finish();
+
+        op.write("file", "Hello, World!").await?;
+        let err = op.write("dir\t/file", "Hello, World!").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        let err = op.write("file\0", "Hello, World!").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let err = op.create_dir("dir\r/").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        let err = op.rename("file", "file\u{1b}").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+
+        // Nothing should be sent to the underlying service.
+        assert_eq!(op.scan("/").await?.try_collect::<Vec<_>>().await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_poison_list() -> Result<()> {
+        let op = Operator::new(Memory::default())?.finish();
+        op.write("dir/file", "Hello, World!").await?;
+        op.write("dir/file\u{7}", "Hello, World!").await?;
+
+        let list = |policy: PoisonListPolicy| {
+            let op = op
+                .clone()
+                .layer(PoisonLayer::new().with_list_policy(policy));
+            async move {
+                let mut paths: Vec<String> = op
+                    .list("dir/")
+                    .await?
+                    .map_ok(|e| e.path().to_string())
+                    .try_collect()
+                    .await?;
+                paths.sort();
+                Ok::<_, Error>(paths)
+            }
+        };
+
+        assert_eq!(
+            list(PoisonListPolicy::Keep).await?,
+            vec!["dir/file", "dir/file\u{7}"]
+        );
+        assert_eq!(
+            list(PoisonListPolicy::Warn).await?,
+            vec!["dir/file", "dir/file\u{7}"]
+        );
+        assert_eq!(list(PoisonListPolicy::Skip).await?, vec!["dir/file"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocking_poison_write() {
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .layer(PoisonLayer::new().allow_non_ascii(false))
+            .finish()
+            .blocking();
+
+        op.write("file", "Hello, World!").unwrap();
+        let err = op.write("文件", "Hello, World!").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+    }
+}
@@ -0,0 +1,777 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::mem::size_of;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use parking_lot::Mutex;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// Estimated memory used by a cached listing besides its names.
+const LISTING_OVERHEAD: usize = 128;
+
+/// Add a ttl based cache for `list` results, indexed by prefix.
+///
+/// # Notes
+///
+/// `list` on the same prefix within `ttl` will be served from the cache
+/// without visiting the underlying services.
+///
+/// - Only listings that have been consumed to the end will be cached.
+/// - Only paths of entries are cached, other metadata will be fetched via
+///   `stat` on demand.
+/// - `create`, `write`, `delete`, `rename`, `copy`, `restore` and batch
+///   deletes will invalidate the listings of all parent prefixes of
+///   affected paths, and all prefixes under them for dirs.
+/// - `list` with modified filters will always be sent to the underlying
+///   services.
+/// - The cache is bounded by `capacity` in bytes (64MiB by default), the
+///   least recently used listing will be evicted first.
+/// - Changes made by other processes will not be observed until the
+///   listing expired.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anyhow::Result;
+/// use opendal::layers::PrefixIndexLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(PrefixIndexLayer::new(Duration::from_secs(30)).with_capacity(16 * 1024 * 1024))
+///     .finish();
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrefixIndexLayer {
+    ttl: Duration,
+    capacity: usize,
+}
+
+impl PrefixIndexLayer {
+    /// Create a new PrefixIndexLayer that caches listings for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            capacity: 64 * 1024 * 1024,
+        }
+    }
+
+    /// Set the max bytes used by cached listings.
+    ///
+    /// Listings larger than `capacity` will not be cached. Setting
+    /// `capacity` to `0` disables the cache.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+}
+
+impl<A: Accessor> Layer<A> for PrefixIndexLayer {
+    type LayeredAccessor = PrefixIndexAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        PrefixIndexAccessor {
+            inner,
+            index: Arc::new(PrefixIndex::new(self.ttl, self.capacity)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PrefixIndexAccessor<A: Accessor> {
+    inner: A,
+    index: Arc<PrefixIndex>,
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for PrefixIndexAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = PrefixIndexWriter<A::Writer>;
+    type BlockingWriter = PrefixIndexWriter<A::BlockingWriter>;
+    type Pager = PrefixIndexPager<A::Pager>;
+    type BlockingPager = PrefixIndexPager<A::BlockingPager>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("PrefixIndexLayer")
+    }
+
+    async fn create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        let res = self.inner.create(path, args).await;
+        self.index.invalidate(path);
+        res
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.index.invalidate(path);
+        self.inner.write(path, args).await.map(|(rp, w)| {
+            (
+                rp,
+                PrefixIndexWriter::new(w, self.index.clone(), path.to_string()),
+            )
+        })
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let res = self.inner.delete(path, args).await;
+        self.index.invalidate(path);
+        res
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        if args.has_modified_filter() {
+            return self
+                .inner
+                .list(path, args)
+                .await
+                .map(|(rp, p)| (rp, PrefixIndexPager::uncached(p)));
+        }
+
+        let generation = match self.index.get(path) {
+            Ok(entries) => return Ok((RpList::default(), PrefixIndexPager::cached(entries))),
+            Err(generation) => generation,
+        };
+
+        self.inner.list(path, args).await.map(|(rp, p)| {
+            (
+                rp,
+                PrefixIndexPager::new(p, self.index.clone(), path, generation),
+            )
+        })
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        self.inner
+            .scan(path, args)
+            .await
+            .map(|(rp, p)| (rp, PrefixIndexPager::uncached(p)))
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let paths: Vec<String> = match args.operation() {
+            BatchOperations::Delete(ops) => ops.iter().map(|(p, _)| p.clone()).collect(),
+        };
+
+        let res = self.inner.batch(args).await;
+        for path in paths {
+            self.index.invalidate(&path);
+        }
+        res
+    }
+
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        let res = self.inner.restore(path, args).await;
+        self.index.invalidate(path);
+        res
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let res = self.inner.rename(from, to, args).await;
+        self.index.invalidate(from);
+        self.index.invalidate(to);
+        res
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let res = self.inner.copy(from, to, args).await;
+        self.index.invalidate(to);
+        res
+    }
+
+    fn blocking_create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        let res = self.inner.blocking_create(path, args);
+        self.index.invalidate(path);
+        res
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.index.invalidate(path);
+        self.inner.blocking_write(path, args).map(|(rp, w)| {
+            (
+                rp,
+                PrefixIndexWriter::new(w, self.index.clone(), path.to_string()),
+            )
+        })
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let res = self.inner.blocking_delete(path, args);
+        self.index.invalidate(path);
+        res
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        if args.has_modified_filter() {
+            return self
+                .inner
+                .blocking_list(path, args)
+                .map(|(rp, p)| (rp, PrefixIndexPager::uncached(p)));
+        }
+
+        let generation = match self.index.get(path) {
+            Ok(entries) => return Ok((RpList::default(), PrefixIndexPager::cached(entries))),
+            Err(generation) => generation,
+        };
+
+        self.inner.blocking_list(path, args).map(|(rp, p)| {
+            (
+                rp,
+                PrefixIndexPager::new(p, self.index.clone(), path, generation),
+            )
+        })
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        self.inner
+            .blocking_scan(path, args)
+            .map(|(rp, p)| (rp, PrefixIndexPager::uncached(p)))
+    }
+}
+
+/// Paths of entries under a prefix.
+///
+/// Names are stored relative to the prefix in one buffer so that large
+/// listings don't pay for an allocation per entry.
+#[derive(Debug, Default)]
+struct Listing {
+    names: String,
+    /// The end offsets of names in `names`.
+    ends: Vec<u32>,
+}
+
+impl Listing {
+    /// Push a name, returns `false` if the buffer is full.
+    fn push(&mut self, name: &str) -> bool {
+        let end = self.names.len() + name.len();
+        if end > u32::MAX as usize {
+            return false;
+        }
+
+        self.names.push_str(name);
+        self.ends.push(end as u32);
+        true
+    }
+
+    fn size(&self) -> usize {
+        self.names.len() + self.ends.len() * size_of::<u32>()
+    }
+
+    fn to_entries(&self, prefix: &str) -> Vec<oio::Entry> {
+        let mut start = 0;
+        self.ends
+            .iter()
+            .map(|end| {
+                let name = &self.names[start..*end as usize];
+                start = *end as usize;
+
+                let mode = if name.ends_with('/') {
+                    EntryMode::DIR
+                } else {
+                    EntryMode::FILE
+                };
+                oio::Entry::with(format!("{prefix}{name}"), Metadata::new(mode))
+            })
+            .collect()
+    }
+}
+
+/// A bounded LRU cache of listings, keyed by the listed prefix.
+#[derive(Debug)]
+struct PrefixIndex {
+    ttl: Duration,
+    capacity: usize,
+    inner: Mutex<LruMap>,
+}
+
+#[derive(Debug, Default)]
+struct LruMap {
+    entries: HashMap<String, CacheEntry>,
+    /// Prefixes ordered by their last access tick, the first one is the
+    /// least recently used.
+    order: BTreeMap<u64, String>,
+    tick: u64,
+    /// Bytes used by all cached listings.
+    size: usize,
+    /// Bumped on every invalidation, listings started before that could
+    /// be stale and will not be cached.
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    listing: Listing,
+    expire_at: Instant,
+    tick: u64,
+    size: usize,
+}
+
+impl PrefixIndex {
+    fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            inner: Mutex::new(LruMap::default()),
+        }
+    }
+
+    /// Get the cached entries of given prefix.
+    ///
+    /// Returns the current generation if cache missed.
+    fn get(&self, path: &str) -> std::result::Result<Vec<oio::Entry>, u64> {
+        let mut lru = self.inner.lock();
+        let lru = &mut *lru;
+
+        let expired = match lru.entries.get(path) {
+            None => return Err(lru.generation),
+            Some(entry) => entry.expire_at <= Instant::now(),
+        };
+        if expired {
+            lru.remove(path);
+            return Err(lru.generation);
+        }
+
+        lru.tick += 1;
+        let tick = lru.tick;
+        let entry = lru.entries.get_mut(path).expect("entry must exist");
+        lru.order.remove(&entry.tick);
+        lru.order.insert(tick, path.to_string());
+        entry.tick = tick;
+
+        let prefix = if path == "/" { "" } else { path };
+        Ok(entry.listing.to_entries(prefix))
+    }
+
+    /// Insert a complete listing if no invalidation happened since
+    /// `generation`.
+    fn insert(&self, path: &str, generation: u64, listing: Listing) {
+        let size = path.len() + listing.size() + LISTING_OVERHEAD;
+        if size > self.capacity {
+            return;
+        }
+
+        let mut lru = self.inner.lock();
+        if lru.generation != generation {
+            return;
+        }
+
+        lru.remove(path);
+        lru.tick += 1;
+        let tick = lru.tick;
+        lru.order.insert(tick, path.to_string());
+        lru.size += size;
+        lru.entries.insert(
+            path.to_string(),
+            CacheEntry {
+                listing,
+                expire_at: Instant::now() + self.ttl,
+                tick,
+                size,
+            },
+        );
+
+        while lru.size > self.capacity {
+            let tick = *lru
+                .order
+                .keys()
+                .next()
+                .expect("order must not be empty while size is not zero");
+            if let Some(evicted) = lru.order.remove(&tick) {
+                if let Some(entry) = lru.entries.remove(&evicted) {
+                    lru.size -= entry.size;
+                }
+            }
+        }
+    }
+
+    /// Invalidate listings that could be changed by mutations on given
+    /// path, which are listings of all its parents, and itself with all
+    /// its descendants if it's a dir.
+    ///
+    /// For example, writing `a/b/c` will invalidate `/`, `a/` and `a/b/`
+    /// since `a/` and `a/b/` could be created along with it. Deleting or
+    /// renaming `a/` will invalidate `a/b/` too.
+    fn invalidate(&self, path: &str) {
+        let mut lru = self.inner.lock();
+        lru.generation += 1;
+
+        lru.remove("/");
+        for (idx, _) in path.match_indices('/') {
+            lru.remove(&path[..=idx]);
+        }
+
+        if path.ends_with('/') {
+            let descendants: Vec<String> = lru
+                .entries
+                .keys()
+                .filter(|p| path == "/" || p.starts_with(path))
+                .cloned()
+                .collect();
+            for p in descendants {
+                lru.remove(&p);
+            }
+        }
+    }
+}
+
+impl LruMap {
+    fn remove(&mut self, path: &str) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.order.remove(&entry.tick);
+            self.size -= entry.size;
+        }
+    }
+}
+
+/// Pager that serves listings from the cache, or records the listing from
+/// underlying services into the cache once it's consumed to the end.
+pub struct PrefixIndexPager<P> {
+    inner: Option<P>,
+    cached: Option<Vec<oio::Entry>>,
+
+    index: Option<Arc<PrefixIndex>>,
+    path: String,
+    generation: u64,
+    /// `None` means the listing will not be cached.
+    listing: Option<Listing>,
+}
+
+impl<P> PrefixIndexPager<P> {
+    fn new(inner: P, index: Arc<PrefixIndex>, path: &str, generation: u64) -> Self {
+        Self {
+            inner: Some(inner),
+            cached: None,
+
+            index: Some(index),
+            path: path.to_string(),
+            generation,
+            listing: Some(Listing::default()),
+        }
+    }
+
+    fn uncached(inner: P) -> Self {
+        Self {
+            inner: Some(inner),
+            cached: None,
+
+            index: None,
+            path: String::new(),
+            generation: 0,
+            listing: None,
+        }
+    }
+
+    fn cached(entries: Vec<oio::Entry>) -> Self {
+        Self {
+            inner: None,
+            cached: Some(entries).filter(|v| !v.is_empty()),
+
+            index: None,
+            path: String::new(),
+            generation: 0,
+            listing: None,
+        }
+    }
+
+    /// Record entries of a page, `None` means all pages have been listed.
+    fn record(&mut self, page: Option<&Vec<oio::Entry>>) {
+        let entries = match page {
+            Some(entries) => entries,
+            None => {
+                if let (Some(index), Some(listing)) = (self.index.take(), self.listing.take()) {
+                    index.insert(&self.path, self.generation, listing);
+                }
+                return;
+            }
+        };
+
+        let listing = match &mut self.listing {
+            Some(listing) => listing,
+            None => return,
+        };
+        let prefix = if self.path == "/" { "" } else { &self.path };
+        for entry in entries {
+            let recorded = match entry.path().strip_prefix(prefix) {
+                Some(name) => listing.push(name),
+                None => false,
+            };
+            if !recorded {
+                self.listing = None;
+                return;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<P: oio::Page> oio::Page for PrefixIndexPager<P> {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let inner = match &mut self.inner {
+            Some(inner) => inner,
+            None => return Ok(self.cached.take()),
+        };
+
+        let page = inner.next().await?;
+        self.record(page.as_ref());
+        Ok(page)
+    }
+}
+
+impl<P: oio::BlockingPage> oio::BlockingPage for PrefixIndexPager<P> {
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let inner = match &mut self.inner {
+            Some(inner) => inner,
+            None => return Ok(self.cached.take()),
+        };
+
+        let page = inner.next()?;
+        self.record(page.as_ref());
+        Ok(page)
+    }
+}
+
+/// Writer that invalidates the listings after it's closed or aborted.
+pub struct PrefixIndexWriter<W> {
+    inner: W,
+    index: Arc<PrefixIndex>,
+    path: String,
+}
+
+impl<W> PrefixIndexWriter<W> {
+    fn new(inner: W, index: Arc<PrefixIndex>, path: String) -> Self {
+        Self { inner, index, path }
+    }
+}
+
+#[async_trait]
+impl<W: oio::Write> oio::Write for PrefixIndexWriter<W> {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.inner.write(bs).await
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.inner.append(bs).await
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        let res = self.inner.close().await;
+        self.index.invalidate(&self.path);
+        res
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        let res = self.inner.abort().await;
+        self.index.invalidate(&self.path);
+        res
+    }
+}
+
+impl<W: oio::BlockingWrite> oio::BlockingWrite for PrefixIndexWriter<W> {
+    fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.inner.write(bs)
+    }
+
+    fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.inner.append(bs)
+    }
+
+    fn close(&mut self) -> Result<RpWrite> {
+        let res = self.inner.close();
+        self.index.invalidate(&self.path);
+        res
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        let res = self.inner.abort();
+        self.index.invalidate(&self.path);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use anyhow::Result;
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::raw::tests::CountLayer;
+    use crate::services::Memory;
+
+    fn new_operator(layer: PrefixIndexLayer) -> (Operator, Arc<AtomicUsize>) {
+        let count = CountLayer::default();
+        let op = Operator::new(Memory::default())
+            .expect("must init")
+            .layer(count.clone())
+            .layer(layer)
+            .finish();
+        (op, count.lists)
+    }
+
+    async fn list_paths(op: &Operator, path: &str) -> Result<Vec<String>> {
+        let mut paths: Vec<String> = op
+            .list(path)
+            .await?
+            .map_ok(|e| e.path().to_string())
+            .try_collect()
+            .await?;
+        paths.sort();
+        Ok(paths)
+    }
+
+    #[tokio::test]
+    async fn test_list_cached() -> Result<()> {
+        let (op, lists) = new_operator(PrefixIndexLayer::new(Duration::from_secs(60)));
+
+        op.write("dir/a", vec![0; 16]).await?;
+        op.write("dir/sub/b", vec![0; 16]).await?;
+
+        assert_eq!(list_paths(&op, "dir/").await?, vec!["dir/a", "dir/sub/"]);
+        assert_eq!(list_paths(&op, "dir/").await?, vec!["dir/a", "dir/sub/"]);
+        assert_eq!(lists.load(Ordering::SeqCst), 1);
+
+        // Metadata of cached entries will be fetched on demand.
+        let entry = op
+            .list("dir/")
+            .await?
+            .try_next()
+            .await?
+            .expect("must exist");
+        let meta = op.metadata(&entry, Metakey::ContentLength).await?;
+        assert_eq!(meta.content_length(), 16);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_on_mutation() -> Result<()> {
+        let (op, lists) = new_operator(PrefixIndexLayer::new(Duration::from_secs(60)));
+
+        op.write("dir/a", vec![0; 16]).await?;
+        assert_eq!(list_paths(&op, "/").await?, vec!["dir/"]);
+        assert_eq!(list_paths(&op, "dir/").await?, vec!["dir/a"]);
+
+        // Writing into a new sub dir changes listings of all parents.
+        op.write("dir/sub/b", vec![0; 16]).await?;
+        assert_eq!(list_paths(&op, "dir/").await?, vec!["dir/a", "dir/sub/"]);
+        assert_eq!(lists.load(Ordering::SeqCst), 3);
+
+        // Listings of unrelated prefixes are kept.
+        op.write("other", vec![0; 16]).await?;
+        assert_eq!(list_paths(&op, "dir/").await?, vec!["dir/a", "dir/sub/"]);
+        assert_eq!(lists.load(Ordering::SeqCst), 3);
+
+        op.delete("dir/a").await?;
+        assert_eq!(list_paths(&op, "dir/").await?, vec!["dir/sub/"]);
+        assert_eq!(lists.load(Ordering::SeqCst), 4);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_expire() -> Result<()> {
+        let (op, lists) = new_operator(PrefixIndexLayer::new(Duration::from_millis(50)));
+
+        op.write("dir/a", vec![0; 16]).await?;
+        list_paths(&op, "dir/").await?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        list_paths(&op, "dir/").await?;
+        assert_eq!(lists.load(Ordering::SeqCst), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let listing = || {
+            let mut l = Listing::default();
+            assert!(l.push("file"));
+            assert!(l.push("dir/"));
+            l
+        };
+        let size = 2 + listing().size() + LISTING_OVERHEAD;
+        let index = PrefixIndex::new(Duration::from_secs(60), size * 2);
+
+        index.insert("a/", 0, listing());
+        index.insert("b/", 0, listing());
+        // Access `a/` so that `b/` becomes the least recently used.
+        assert!(index.get("a/").is_ok());
+        index.insert("c/", 0, listing());
+
+        let entries = index.get("a/").expect("must be cached");
+        let paths: Vec<_> = entries.iter().map(|e| e.path()).collect();
+        assert_eq!(paths, vec!["a/file", "a/dir/"]);
+        assert!(index.get("b/").is_err());
+        assert!(index.get("c/").is_ok());
+    }
+
+    #[test]
+    fn test_invalidate_descendants() {
+        let index = PrefixIndex::new(Duration::from_secs(60), 1024);
+
+        for path in ["/", "a/", "a/b/", "a/b/c/", "ab/"] {
+            index.insert(path, 0, Listing::default());
+        }
+
+        index.invalidate("a/b/");
+        assert!(index.get("/").is_err());
+        assert!(index.get("a/").is_err());
+        assert!(index.get("a/b/").is_err());
+        assert!(index.get("a/b/c/").is_err());
+        assert!(index.get("ab/").is_ok());
+
+        index.invalidate("/");
+        assert!(index.get("ab/").is_err());
+    }
+
+    #[test]
+    fn test_skip_stale_listing() {
+        let index = PrefixIndex::new(Duration::from_secs(60), 1024);
+
+        let generation = index.get("a/").unwrap_err();
+        // A mutation happened while listing.
+        index.invalidate("a/b");
+        index.insert("a/", generation, Listing::default());
+
+        assert!(index.get("a/").is_err());
+    }
+}
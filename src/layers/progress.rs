@@ -0,0 +1,552 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::io;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::ready;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// Report bytes transferred by readers and writers via callback.
+///
+/// ProgressLayer will invoke the user's callback with a [`Progress`] every
+/// time bytes flow through the returning `Reader` or `Writer`. Users can
+/// build progress bars on top of it.
+///
+/// # Notes
+///
+/// - The callback is called inside `poll_read` and `write`, so it must be
+///   cheap. Please don't block or do heavy work in it, send the event to
+///   another thread instead.
+/// - For reads, total comes from the content length returned by services.
+/// - For writes, total comes from [`OpWrite::content_length`], which is
+///   filled automatically by `Operator::write`.
+/// - A final event with [`ProgressState::Finished`] or [`ProgressState::Failed`]
+///   will be sent once the transfer completes or fails. Aborted writes are
+///   reported as failed.
+/// - Readers that are dropped before reaching EOF will not send a final event.
+/// - Seeking a reader moves bytes to the new position, so that bytes of
+///   readers always mean the position in content.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::ProgressLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let op = Operator::new(services::Memory::default())?
+///     .layer(ProgressLayer::new(|p| {
+///         println!(
+///             "{} {}: {}/{:?} {:?}",
+///             p.operation(),
+///             p.path(),
+///             p.bytes(),
+///             p.total(),
+///             p.state()
+///         )
+///     }))
+///     .finish();
+///
+/// op.write("path/to/file", "Hello, World!").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ProgressLayer {
+    callback: Arc<dyn Fn(Progress) + Send + Sync>,
+}
+
+impl Debug for ProgressLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressLayer").finish_non_exhaustive()
+    }
+}
+
+impl ProgressLayer {
+    /// Create a new ProgressLayer with given callback.
+    pub fn new(callback: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        Self {
+            callback: Arc::new(callback),
+        }
+    }
+}
+
+impl<A: Accessor> Layer<A> for ProgressLayer {
+    type LayeredAccessor = ProgressAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        ProgressAccessor {
+            inner,
+            callback: self.callback.clone(),
+        }
+    }
+}
+
+/// State of a [`Progress`] event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressState {
+    /// Bytes have been transferred and more are expected.
+    InProgress,
+    /// The transfer has been completed. This is the final event.
+    Finished,
+    /// The transfer has failed. This is the final event.
+    Failed,
+}
+
+/// Progress event sent by [`ProgressLayer`].
+#[derive(Debug, Clone)]
+pub struct Progress {
+    operation: Operation,
+    path: Arc<str>,
+    bytes: u64,
+    total: Option<u64>,
+    state: ProgressState,
+}
+
+impl Progress {
+    /// Get the operation of this progress, like `Read` or `BlockingWrite`.
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Get the path of this progress.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Get the bytes transferred so far.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Get the total bytes to transfer if known.
+    pub fn total(&self) -> Option<u64> {
+        self.total
+    }
+
+    /// Get the state of this progress.
+    pub fn state(&self) -> ProgressState {
+        self.state
+    }
+}
+
+#[derive(Clone)]
+pub struct ProgressAccessor<A: Accessor> {
+    inner: A,
+    callback: Arc<dyn Fn(Progress) + Send + Sync>,
+}
+
+impl<A: Accessor> Debug for ProgressAccessor<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressAccessor")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A: Accessor> ProgressAccessor<A> {
+    fn tracker(&self, operation: Operation, path: &str, total: Option<u64>) -> ProgressTracker {
+        ProgressTracker {
+            operation,
+            path: Arc::from(path),
+            bytes: 0,
+            total,
+            finished: false,
+            callback: self.callback.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for ProgressAccessor<A> {
+    type Inner = A;
+    type Reader = ProgressWrapper<A::Reader>;
+    type BlockingReader = ProgressWrapper<A::BlockingReader>;
+    type Writer = ProgressWrapper<A::Writer>;
+    type BlockingWriter = ProgressWrapper<A::BlockingWriter>;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("ProgressLayer")
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await.map(|(rp, r)| {
+            let total = rp.metadata().content_length_raw();
+            let tracker = self.tracker(Operation::Read, path, total);
+            (rp, ProgressWrapper::new(r, tracker))
+        })
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let total = args.content_length();
+        self.inner.write(path, args).await.map(|(rp, w)| {
+            let tracker = self.tracker(Operation::Write, path, total);
+            (rp, ProgressWrapper::new(w, tracker))
+        })
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        self.inner.scan(path, args).await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args).map(|(rp, r)| {
+            let total = rp.metadata().content_length_raw();
+            let tracker = self.tracker(Operation::BlockingRead, path, total);
+            (rp, ProgressWrapper::new(r, tracker))
+        })
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let total = args.content_length();
+        self.inner.blocking_write(path, args).map(|(rp, w)| {
+            let tracker = self.tracker(Operation::BlockingWrite, path, total);
+            (rp, ProgressWrapper::new(w, tracker))
+        })
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        self.inner.blocking_scan(path, args)
+    }
+}
+
+struct ProgressTracker {
+    operation: Operation,
+    path: Arc<str>,
+    bytes: u64,
+    total: Option<u64>,
+    finished: bool,
+    callback: Arc<dyn Fn(Progress) + Send + Sync>,
+}
+
+impl ProgressTracker {
+    fn emit(&mut self, state: ProgressState) {
+        // Only one final event should be sent.
+        if self.finished {
+            return;
+        }
+        if state != ProgressState::InProgress {
+            self.finished = true;
+        }
+
+        (self.callback)(Progress {
+            operation: self.operation,
+            path: self.path.clone(),
+            bytes: self.bytes,
+            total: self.total,
+            state,
+        })
+    }
+
+    /// Bytes have been written, the final event will be sent in `close`.
+    fn write(&mut self, n: usize) {
+        self.bytes += n as u64;
+        self.emit(ProgressState::InProgress)
+    }
+
+    /// Bytes have been read, reaching total means the read is finished.
+    fn read(&mut self, n: usize) {
+        self.bytes += n as u64;
+
+        match self.total {
+            Some(total) if self.bytes >= total => self.emit(ProgressState::Finished),
+            _ => self.emit(ProgressState::InProgress),
+        }
+    }
+
+    /// Reader has been seeked to `pos`.
+    fn seek(&mut self, pos: u64) {
+        self.bytes = pos;
+    }
+
+    fn close<T>(&mut self, res: &Result<T>) {
+        match res {
+            Ok(_) => self.emit(ProgressState::Finished),
+            Err(_) => self.emit(ProgressState::Failed),
+        }
+    }
+}
+
+pub struct ProgressWrapper<R> {
+    inner: R,
+    tracker: ProgressTracker,
+}
+
+impl<R> ProgressWrapper<R> {
+    fn new(inner: R, tracker: ProgressTracker) -> Self {
+        Self { inner, tracker }
+    }
+}
+
+impl<R: oio::Read> oio::Read for ProgressWrapper<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        let res = ready!(self.inner.poll_read(cx, buf));
+        match &res {
+            Ok(0) if !buf.is_empty() => self.tracker.emit(ProgressState::Finished),
+            Ok(n) => self.tracker.read(*n),
+            Err(_) => self.tracker.emit(ProgressState::Failed),
+        }
+        Poll::Ready(res)
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
+        let res = ready!(self.inner.poll_seek(cx, pos));
+        if let Ok(pos) = &res {
+            self.tracker.seek(*pos);
+        }
+        Poll::Ready(res)
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        let res = ready!(self.inner.poll_next(cx));
+        match &res {
+            Some(Ok(bs)) => self.tracker.read(bs.len()),
+            Some(Err(_)) => self.tracker.emit(ProgressState::Failed),
+            None => self.tracker.emit(ProgressState::Finished),
+        }
+        Poll::Ready(res)
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for ProgressWrapper<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let res = self.inner.read(buf);
+        match &res {
+            Ok(0) if !buf.is_empty() => self.tracker.emit(ProgressState::Finished),
+            Ok(n) => self.tracker.read(*n),
+            Err(_) => self.tracker.emit(ProgressState::Failed),
+        }
+        res
+    }
+
+    fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
+        let res = self.inner.seek(pos);
+        if let Ok(pos) = &res {
+            self.tracker.seek(*pos);
+        }
+        res
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        let res = self.inner.next();
+        match &res {
+            Some(Ok(bs)) => self.tracker.read(bs.len()),
+            Some(Err(_)) => self.tracker.emit(ProgressState::Failed),
+            None => self.tracker.emit(ProgressState::Finished),
+        }
+        res
+    }
+}
+
+#[async_trait]
+impl<R: oio::Write> oio::Write for ProgressWrapper<R> {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        let size = bs.len();
+        let res = self.inner.write(bs).await;
+        match &res {
+            Ok(_) => self.tracker.write(size),
+            Err(_) => self.tracker.emit(ProgressState::Failed),
+        }
+        res
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        let size = bs.len();
+        let res = self.inner.append(bs).await;
+        match &res {
+            Ok(_) => self.tracker.write(size),
+            Err(_) => self.tracker.emit(ProgressState::Failed),
+        }
+        res
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        let res = self.inner.close().await;
+        self.tracker.close(&res);
+        res
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        let res = self.inner.abort().await;
+        self.tracker.emit(ProgressState::Failed);
+        res
+    }
+}
+
+impl<R: oio::BlockingWrite> oio::BlockingWrite for ProgressWrapper<R> {
+    fn write(&mut self, bs: Bytes) -> Result<()> {
+        let size = bs.len();
+        let res = self.inner.write(bs);
+        match &res {
+            Ok(_) => self.tracker.write(size),
+            Err(_) => self.tracker.emit(ProgressState::Failed),
+        }
+        res
+    }
+
+    fn append(&mut self, bs: Bytes) -> Result<()> {
+        let size = bs.len();
+        let res = self.inner.append(bs);
+        match &res {
+            Ok(_) => self.tracker.write(size),
+            Err(_) => self.tracker.emit(ProgressState::Failed),
+        }
+        res
+    }
+
+    fn close(&mut self) -> Result<RpWrite> {
+        let res = self.inner.close();
+        self.tracker.close(&res);
+        res
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        let res = self.inner.abort();
+        self.tracker.emit(ProgressState::Failed);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use futures::AsyncReadExt;
+    use futures::AsyncSeekExt;
+    use parking_lot::Mutex;
+
+    use super::*;
+    use crate::services::Memory;
+
+    #[tokio::test]
+    async fn test_progress() -> Result<()> {
+        let events = Arc::new(Mutex::new(vec![]));
+        let layer = ProgressLayer::new({
+            let events = events.clone();
+            move |p| events.lock().push(p)
+        });
+        let op = Operator::new(Memory::default())?.layer(layer).finish();
+
+        op.write("test", vec![0; 1024]).await?;
+        {
+            let events = events.lock();
+            let last = events.last().expect("must have events");
+            assert_eq!(last.operation(), Operation::Write);
+            assert_eq!(last.path(), "test");
+            assert_eq!(last.bytes(), 1024);
+            assert_eq!(last.total(), Some(1024));
+            assert_eq!(last.state(), ProgressState::Finished);
+        }
+        events.lock().clear();
+
+        let bs = op.read("test").await?;
+        assert_eq!(bs.len(), 1024);
+        {
+            let events = events.lock();
+            let finished: Vec<_> = events
+                .iter()
+                .filter(|p| p.state() == ProgressState::Finished)
+                .collect();
+            assert_eq!(finished.len(), 1, "only one final event expected");
+            assert_eq!(finished[0].operation(), Operation::Read);
+            assert_eq!(finished[0].bytes(), 1024);
+            assert_eq!(finished[0].total(), Some(1024));
+            assert_eq!(
+                events.last().map(|p| p.state()),
+                Some(ProgressState::Finished)
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_progress_seek() -> Result<()> {
+        let events = Arc::new(Mutex::new(vec![]));
+        let layer = ProgressLayer::new({
+            let events = events.clone();
+            move |p| events.lock().push(p)
+        });
+        let op = Operator::new(Memory::default())?.layer(layer).finish();
+        op.write("test", vec![0; 1024]).await?;
+        events.lock().clear();
+
+        let mut r = op.reader("test").await?;
+        let mut buf = vec![0; 100];
+        r.read_exact(&mut buf).await?;
+        assert_eq!(events.lock().last().map(|p| p.bytes()), Some(100));
+
+        r.seek(io::SeekFrom::Start(1000)).await?;
+        let mut buf = vec![];
+        r.read_to_end(&mut buf).await?;
+        assert_eq!(buf.len(), 24);
+
+        let events = events.lock();
+        let finished: Vec<_> = events
+            .iter()
+            .filter(|p| p.state() == ProgressState::Finished)
+            .collect();
+        assert_eq!(finished.len(), 1, "only one final event expected");
+        assert_eq!(finished[0].bytes(), 1024);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_progress_failed() -> Result<()> {
+        let events = Arc::new(Mutex::new(vec![]));
+        let layer = ProgressLayer::new({
+            let events = events.clone();
+            move |p| events.lock().push(p)
+        });
+        let op = Operator::new(Memory::default())?.layer(layer).finish();
+
+        let mut w = op.writer("test").await?;
+        w.append(vec![0; 16]).await?;
+        w.abort().await?;
+
+        let events = events.lock();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].state(), ProgressState::InProgress);
+        assert_eq!(events[0].bytes(), 16);
+        assert_eq!(events[0].total(), None);
+        assert_eq!(events[1].state(), ProgressState::Failed);
+        Ok(())
+    }
+}
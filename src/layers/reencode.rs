@@ -0,0 +1,507 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+type Transform = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Rewrite paths between application and underlying storage services.
+///
+/// ReencodeLayer applies a `forward` transform to every path before
+/// sending it to underlying services, and applies the `inverse` transform
+/// to paths of listed entries, so that applications always see their own
+/// naming.
+///
+/// # Guarantee
+///
+/// Transforms must be bijective, otherwise different paths could be mapped
+/// to the same key and listings will be corrupted. ReencodeLayer checks
+/// every path at runtime:
+///
+/// - `inverse(forward(path))` must equal to `path` before sending it to
+///   underlying services.
+/// - `forward(inverse(key))` must equal to `key` for every listed entry.
+///
+/// Operations on paths that violate this will fail with
+/// [`ErrorKind::Unexpected`], nothing will be sent.
+///
+/// # Notes
+///
+/// - The root path `/` is never transformed.
+/// - Transforms must keep the trailing `/` of dirs.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::ReencodeLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let op = Operator::new(services::Memory::default())?.finish();
+/// let legacy = op.clone().layer(ReencodeLayer::lowercase());
+///
+/// legacy.write("DATA/FILE.TXT", "Hello, World!").await?;
+/// assert!(op.is_exist("data/file.txt").await?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ReencodeLayer {
+    forward: Transform,
+    inverse: Transform,
+}
+
+impl Debug for ReencodeLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReencodeLayer").finish_non_exhaustive()
+    }
+}
+
+impl ReencodeLayer {
+    /// Create a new ReencodeLayer with given transforms.
+    ///
+    /// `forward` maps application paths to keys of underlying services,
+    /// and `inverse` maps them back.
+    pub fn new(
+        forward: impl Fn(&str) -> String + Send + Sync + 'static,
+        inverse: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            forward: Arc::new(forward),
+            inverse: Arc::new(inverse),
+        }
+    }
+
+    /// Map uppercase application paths to lowercase keys.
+    ///
+    /// Application paths must be uppercase, paths like `Dir/File` can't be
+    /// restored and will be rejected.
+    pub fn lowercase() -> Self {
+        Self::new(|p| p.to_lowercase(), |p| p.to_uppercase())
+    }
+
+    /// Map `separator` in application paths to `/` in keys.
+    ///
+    /// For example, `dir\file` will be stored as `dir/file` with
+    /// `ReencodeLayer::separator('\\')`. The trailing `/` of dirs is kept
+    /// as is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `separator` is `/`.
+    pub fn separator(separator: char) -> Self {
+        assert_ne!(separator, '/', "separator of ReencodeLayer must not be `/`");
+
+        Self::new(
+            move |p| p.replace(separator, "/"),
+            move |p| match p.strip_suffix('/') {
+                Some(v) => format!("{}/", v.replace('/', &separator.to_string())),
+                None => p.replace('/', &separator.to_string()),
+            },
+        )
+    }
+}
+
+impl<A: Accessor> Layer<A> for ReencodeLayer {
+    type LayeredAccessor = ReencodeAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        ReencodeAccessor {
+            inner,
+            forward: self.forward.clone(),
+            inverse: self.inverse.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ReencodeAccessor<A: Accessor> {
+    inner: A,
+    forward: Transform,
+    inverse: Transform,
+}
+
+impl<A: Accessor> Debug for ReencodeAccessor<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReencodeAccessor")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A: Accessor> ReencodeAccessor<A> {
+    /// Build the key in underlying services.
+    fn encode(&self, path: &str) -> Result<String> {
+        if path == "/" {
+            return Ok(path.to_string());
+        }
+
+        let key = (self.forward)(path);
+        if (self.inverse)(&key) != path {
+            return Err(
+                Error::new(ErrorKind::Unexpected, "path can't be reencoded reversibly")
+                    .with_context("service", self.inner.info().scheme())
+                    .with_context("path", path)
+                    .with_context("key", key),
+            );
+        }
+
+        Ok(key)
+    }
+}
+
+/// Map key returned by underlying services back to application path.
+fn decode(forward: &Transform, inverse: &Transform, key: &str) -> Result<String> {
+    if key == "/" {
+        return Ok(key.to_string());
+    }
+
+    let path = inverse(key);
+    if forward(&path) != key {
+        return Err(
+            Error::new(ErrorKind::Unexpected, "key can't be reencoded reversibly")
+                .with_context("key", key)
+                .with_context("path", path),
+        );
+    }
+
+    Ok(path)
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for ReencodeAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = ReencodePager<A::Pager>;
+    type BlockingPager = ReencodePager<A::BlockingPager>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("ReencodeLayer")
+    }
+
+    async fn create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        self.inner.create(&self.encode(path)?, args).await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(&self.encode(path)?, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.inner.write(&self.encode(path)?, args).await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.inner.stat(&self.encode(path)?, args).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.inner.delete(&self.encode(path)?, args).await
+    }
+
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        self.inner.restore(&self.encode(path)?, args).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        path: &str,
+        args: OpAbortMultipart,
+    ) -> Result<RpAbortMultipart> {
+        self.inner.abort_multipart(&self.encode(path)?, args).await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.inner
+            .rename(&self.encode(from)?, &self.encode(to)?, args)
+            .await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.inner
+            .copy(&self.encode(from)?, &self.encode(to)?, args)
+            .await
+    }
+
+    async fn get_acl(&self, path: &str, args: OpGetAcl) -> Result<RpGetAcl> {
+        self.inner.get_acl(&self.encode(path)?, args).await
+    }
+
+    async fn set_acl(&self, path: &str, args: OpSetAcl) -> Result<RpSetAcl> {
+        self.inner.set_acl(&self.encode(path)?, args).await
+    }
+
+    async fn get_tags(&self, path: &str, args: OpGetTags) -> Result<RpGetTags> {
+        self.inner.get_tags(&self.encode(path)?, args).await
+    }
+
+    async fn put_tags(&self, path: &str, args: OpPutTags) -> Result<RpPutTags> {
+        self.inner.put_tags(&self.encode(path)?, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        let (rp, p) = self.inner.list(&self.encode(path)?, args).await?;
+
+        Ok((rp, ReencodePager::new(p, self)))
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        let (rp, p) = self.inner.scan(&self.encode(path)?, args).await?;
+
+        Ok((rp, ReencodePager::new(p, self)))
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        match args.into_operation() {
+            BatchOperations::Delete(ops) => {
+                let ops = ops
+                    .into_iter()
+                    .map(|(path, op)| Ok((self.encode(&path)?, op)))
+                    .collect::<Result<Vec<_>>>()?;
+
+                let rp = self
+                    .inner
+                    .batch(OpBatch::new(BatchOperations::Delete(ops)))
+                    .await?;
+
+                let BatchedResults::Delete(results) = rp.into_results();
+                let results = results
+                    .into_iter()
+                    .map(|(key, result)| {
+                        let path = decode(&self.forward, &self.inverse, &key)?;
+                        Ok((path, result))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                Ok(RpBatch::new(BatchedResults::Delete(results)))
+            }
+        }
+    }
+
+    fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.inner.presign(&self.encode(path)?, args)
+    }
+
+    fn blocking_create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        self.inner.blocking_create(&self.encode(path)?, args)
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(&self.encode(path)?, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(&self.encode(path)?, args)
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.inner.blocking_stat(&self.encode(path)?, args)
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.inner.blocking_delete(&self.encode(path)?, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        let (rp, p) = self.inner.blocking_list(&self.encode(path)?, args)?;
+
+        Ok((rp, ReencodePager::new(p, self)))
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        let (rp, p) = self.inner.blocking_scan(&self.encode(path)?, args)?;
+
+        Ok((rp, ReencodePager::new(p, self)))
+    }
+}
+
+pub struct ReencodePager<P> {
+    inner: P,
+    forward: Transform,
+    inverse: Transform,
+}
+
+impl<P> ReencodePager<P> {
+    fn new<A: Accessor>(inner: P, acc: &ReencodeAccessor<A>) -> Self {
+        Self {
+            inner,
+            forward: acc.forward.clone(),
+            inverse: acc.inverse.clone(),
+        }
+    }
+
+    fn decode_entries(&self, entries: Option<Vec<oio::Entry>>) -> Result<Option<Vec<oio::Entry>>> {
+        let entries = match entries {
+            Some(entries) => entries,
+            None => return Ok(None),
+        };
+
+        entries
+            .into_iter()
+            .map(|mut entry| {
+                let path = decode(&self.forward, &self.inverse, entry.path())?;
+                entry.set_path(&path);
+                Ok(entry)
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Some)
+    }
+}
+
+#[async_trait]
+impl<P: oio::Page> oio::Page for ReencodePager<P> {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let entries = self.inner.next().await?;
+
+        self.decode_entries(entries)
+    }
+}
+
+impl<P: oio::BlockingPage> oio::BlockingPage for ReencodePager<P> {
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let entries = self.inner.next()?;
+
+        self.decode_entries(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use anyhow::Result;
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::services::Memory;
+
+    #[test]
+    fn test_separator() {
+        let layer = ReencodeLayer::separator('\\');
+
+        // (path, key, restored path)
+        let cases = vec![
+            ("file", "file", "file"),
+            ("dir\\file", "dir/file", "dir\\file"),
+            ("dir\\sub/", "dir/sub/", "dir\\sub/"),
+            // Not invertible: `/` in the middle of path.
+            ("dir/file", "dir/file", "dir\\file"),
+        ];
+        for (path, key, restored) in cases {
+            assert_eq!((layer.forward)(path), key, "{path}");
+            assert_eq!((layer.inverse)(key), restored, "{path}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reencode_lowercase() -> Result<()> {
+        let op = Operator::new(Memory::default())?.finish();
+        let legacy = op.clone().layer(ReencodeLayer::lowercase());
+
+        legacy.write("DIR/A.TXT", "Hello, World!").await?;
+        legacy.write("DIR/SUB/B.TXT", "Hello, World!").await?;
+        assert_eq!(op.read("dir/a.txt").await?, b"Hello, World!");
+        assert_eq!(legacy.read("DIR/A.TXT").await?, b"Hello, World!");
+
+        let entries: HashSet<String> = legacy
+            .list("DIR/")
+            .await?
+            .map_ok(|e| e.path().to_string())
+            .try_collect()
+            .await?;
+        assert_eq!(
+            entries,
+            HashSet::from(["DIR/A.TXT".to_string(), "DIR/SUB/".to_string()])
+        );
+
+        let entries: HashSet<String> = legacy
+            .scan("/")
+            .await?
+            .map_ok(|e| e.path().to_string())
+            .try_collect()
+            .await?;
+        assert_eq!(
+            entries,
+            HashSet::from(["DIR/A.TXT".to_string(), "DIR/SUB/B.TXT".to_string()])
+        );
+
+        legacy.delete("DIR/A.TXT").await?;
+        assert!(!op.is_exist("dir/a.txt").await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reencode_separator() -> Result<()> {
+        let op = Operator::new(Memory::default())?.finish();
+        let legacy = op.clone().layer(ReencodeLayer::separator('\\'));
+
+        legacy.write("dir\\file", "Hello, World!").await?;
+        assert_eq!(op.read("dir/file").await?, b"Hello, World!");
+
+        let entries: Vec<String> = legacy
+            .scan("/")
+            .await?
+            .map_ok(|e| e.path().to_string())
+            .try_collect()
+            .await?;
+        assert_eq!(entries, vec!["dir\\file".to_string()]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reject_non_invertible_path() -> Result<()> {
+        let op = Operator::new(Memory::default())?.finish();
+        let legacy = op.clone().layer(ReencodeLayer::lowercase());
+
+        // `Mixed` will be stored as `mixed` but listed as `MIXED`.
+        let err = legacy.write("Mixed", "Hello, World!").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(!op.is_exist("mixed").await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reject_non_invertible_key() -> Result<()> {
+        let op = Operator::new(Memory::default())?.finish();
+        op.write("Mixed", "Hello, World!").await?;
+
+        let legacy = op.clone().layer(ReencodeLayer::lowercase());
+        let err = legacy
+            .scan("/")
+            .await?
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        Ok(())
+    }
+}
@@ -16,6 +16,7 @@ use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::io;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
 use std::time::Duration;
@@ -30,6 +31,7 @@ use bytes::Bytes;
 use futures::ready;
 use futures::FutureExt;
 use log::warn;
+use parking_lot::Mutex;
 
 use crate::ops::*;
 use crate::raw::oio::PageOperation;
@@ -46,6 +48,11 @@ use crate::*;
 /// returns true. If operation still failed, this layer will set error to
 /// `Persistent` which means error has been retried.
 ///
+/// If the error carries a [`Error::retry_after`] hint (for example, parsed
+/// from the `Retry-After` header of throttled responses), the hint will be
+/// used instead of the computed backoff, and it's still capped by
+/// `max_delay`.
+///
 /// `write` and `blocking_write` don't support retry so far, visit [this issue](https://github.com/datafuselabs/opendal/issues/1223) for more details.
 ///
 /// # Examples
@@ -62,8 +69,20 @@ use crate::*;
 ///     .layer(RetryLayer::new())
 ///     .finish();
 /// ```
-#[derive(Default)]
-pub struct RetryLayer(ExponentialBuilder);
+pub struct RetryLayer {
+    builder: ExponentialBuilder,
+    max_delay: Duration,
+}
+
+impl Default for RetryLayer {
+    fn default() -> Self {
+        Self {
+            builder: ExponentialBuilder::default(),
+            // Keep the same with the default max_delay of ExponentialBuilder.
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
 
 impl RetryLayer {
     /// Create a new retry layer.
@@ -89,7 +108,7 @@ impl RetryLayer {
     /// If jitter is enabled, ExponentialBackoff will add a random jitter in `[0, min_delay)
     /// to current delay.
     pub fn with_jitter(mut self) -> Self {
-        self.0 = self.0.with_jitter();
+        self.builder = self.builder.with_jitter();
         self
     }
 
@@ -99,21 +118,24 @@ impl RetryLayer {
     ///
     /// This function will panic if input factor smaller than `1.0`.
     pub fn with_factor(mut self, factor: f32) -> Self {
-        self.0 = self.0.with_factor(factor);
+        self.builder = self.builder.with_factor(factor);
         self
     }
 
     /// Set min_delay of current backoff.
     pub fn with_min_delay(mut self, min_delay: Duration) -> Self {
-        self.0 = self.0.with_min_delay(min_delay);
+        self.builder = self.builder.with_min_delay(min_delay);
         self
     }
 
     /// Set max_delay of current backoff.
     ///
     /// Delay will not increasing if current delay is larger than max_delay.
+    /// The `retry_after` hints returned by services are capped by max_delay
+    /// too.
     pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
-        self.0 = self.0.with_max_delay(max_delay);
+        self.builder = self.builder.with_max_delay(max_delay);
+        self.max_delay = max_delay;
         self
     }
 
@@ -121,7 +143,7 @@ impl RetryLayer {
     ///
     /// Backoff will return `None` if max times is reaching.
     pub fn with_max_times(mut self, max_times: usize) -> Self {
-        self.0 = self.0.with_max_times(max_times);
+        self.builder = self.builder.with_max_times(max_times);
         self
     }
 }
@@ -132,7 +154,7 @@ impl<A: Accessor> Layer<A> for RetryLayer {
     fn layer(&self, inner: A) -> Self::LayeredAccessor {
         RetryAccessor {
             inner,
-            builder: self.0.clone(),
+            builder: RetryBuilder::new(self.builder.clone(), self.max_delay),
         }
     }
 }
@@ -140,7 +162,78 @@ impl<A: Accessor> Layer<A> for RetryLayer {
 #[derive(Clone)]
 pub struct RetryAccessor<A: Accessor> {
     inner: A,
+    builder: RetryBuilder,
+}
+
+/// RetryBuilder builds [`RetryBackoff`] which honors the `retry_after`
+/// hints carried by errors.
+///
+/// The hint of the latest error is recorded by [`RetryBuilder::should_retry`]
+/// and consumed by the next backoff. Builders that cloned from each other
+/// share the same hint, use [`RetryBuilder::renew`] for every operation.
+#[derive(Clone, Debug)]
+struct RetryBuilder {
     builder: ExponentialBuilder,
+    max_delay: Duration,
+    hint: Arc<Mutex<Option<Duration>>>,
+}
+
+impl RetryBuilder {
+    fn new(builder: ExponentialBuilder, max_delay: Duration) -> Self {
+        Self {
+            builder,
+            max_delay,
+            hint: Arc::default(),
+        }
+    }
+
+    /// Create a new builder with the same config but a separate hint.
+    fn renew(&self) -> Self {
+        Self::new(self.builder.clone(), self.max_delay)
+    }
+
+    /// Record the `retry_after` hint of given error and check if it's
+    /// retryable.
+    fn should_retry(&self, err: &Error) -> bool {
+        *self.hint.lock() = err.retry_after();
+        err.is_temporary()
+    }
+}
+
+impl BackoffBuilder for RetryBuilder {
+    type Backoff = RetryBackoff;
+
+    fn build(&self) -> Self::Backoff {
+        RetryBackoff {
+            backoff: self.builder.build(),
+            max_delay: self.max_delay,
+            hint: self.hint.clone(),
+        }
+    }
+}
+
+/// RetryBackoff prefers the recorded `retry_after` hint (capped by
+/// `max_delay`) over the computed delay.
+///
+/// The computed backoff is still advanced, so `max_times` is respected.
+#[derive(Debug)]
+struct RetryBackoff {
+    backoff: ExponentialBackoff,
+    max_delay: Duration,
+    hint: Arc<Mutex<Option<Duration>>>,
+}
+
+impl Iterator for RetryBackoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let dur = self.backoff.next()?;
+
+        match self.hint.lock().take() {
+            Some(hint) => Some(hint.min(self.max_delay)),
+            None => Some(dur),
+        }
+    }
 }
 
 impl<A: Accessor> Debug for RetryAccessor<A> {
@@ -165,10 +258,15 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
         &self.inner
     }
 
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("RetryLayer")
+    }
+
     async fn create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        let backoff = self.builder.renew();
         { || self.inner.create(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -180,9 +278,10 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let backoff = self.builder.renew();
         { || self.inner.read(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -190,7 +289,7 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
                     Operation::Read, dur.as_secs_f64(), err)
             })
             .map(|v| {
-                v.map(|(rp, r)| (rp, RetryWrapper::new(r, path, self.builder.clone())))
+                v.map(|(rp, r)| (rp, RetryWrapper::new(r, path, self.builder.renew())))
                     .map_err(|e| e.set_persistent())
             })
             .await
@@ -200,9 +299,10 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
     ///
     /// Allowing users to retry the write request from upper logic.
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let backoff = self.builder.renew();
         { || self.inner.write(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -210,16 +310,17 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
                     Operation::Write, dur.as_secs_f64(), err)
             })
             .map(|v| {
-                v.map(|(rp, r)| (rp, RetryWrapper::new(r, path, self.builder.clone())))
+                v.map(|(rp, r)| (rp, RetryWrapper::new(r, path, self.builder.renew())))
                     .map_err(|e| e.set_persistent())
             })
             .await
     }
 
     async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let backoff = self.builder.renew();
         { || self.inner.stat(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -231,9 +332,10 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
     }
 
     async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let backoff = self.builder.renew();
         { || self.inner.delete(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -244,10 +346,135 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
             .await
     }
 
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        let backoff = self.builder.renew();
+        { || self.inner.restore(path, args.clone()) }
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
+            .notify(|err, dur| {
+                warn!(
+                    target: "opendal::service",
+                    "operation={} -> retry after {}s: error={:?}",
+                    Operation::Restore, dur.as_secs_f64(), err)
+            })
+            .map(|v| v.map_err(|e| e.set_persistent()))
+            .await
+    }
+
+    async fn abort_multipart(
+        &self,
+        path: &str,
+        args: OpAbortMultipart,
+    ) -> Result<RpAbortMultipart> {
+        let backoff = self.builder.renew();
+        { || self.inner.abort_multipart(path, args.clone()) }
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
+            .notify(|err, dur| {
+                warn!(
+                    target: "opendal::service",
+                    "operation={} -> retry after {}s: error={:?}",
+                    Operation::AbortMultipart, dur.as_secs_f64(), err)
+            })
+            .map(|v| v.map_err(|e| e.set_persistent()))
+            .await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let backoff = self.builder.renew();
+        { || self.inner.rename(from, to, args.clone()) }
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
+            .notify(|err, dur| {
+                warn!(
+                    target: "opendal::service",
+                    "operation={} -> retry after {}s: error={:?}",
+                    Operation::Rename, dur.as_secs_f64(), err)
+            })
+            .map(|v| v.map_err(|e| e.set_persistent()))
+            .await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let backoff = self.builder.renew();
+        { || self.inner.copy(from, to, args.clone()) }
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
+            .notify(|err, dur| {
+                warn!(
+                    target: "opendal::service",
+                    "operation={} -> retry after {}s: error={:?}",
+                    Operation::Copy, dur.as_secs_f64(), err)
+            })
+            .map(|v| v.map_err(|e| e.set_persistent()))
+            .await
+    }
+
+    async fn get_acl(&self, path: &str, args: OpGetAcl) -> Result<RpGetAcl> {
+        let backoff = self.builder.renew();
+        { || self.inner.get_acl(path, args.clone()) }
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
+            .notify(|err, dur| {
+                warn!(
+                    target: "opendal::service",
+                    "operation={} -> retry after {}s: error={:?}",
+                    Operation::GetAcl, dur.as_secs_f64(), err)
+            })
+            .map(|v| v.map_err(|e| e.set_persistent()))
+            .await
+    }
+
+    async fn set_acl(&self, path: &str, args: OpSetAcl) -> Result<RpSetAcl> {
+        let backoff = self.builder.renew();
+        { || self.inner.set_acl(path, args.clone()) }
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
+            .notify(|err, dur| {
+                warn!(
+                    target: "opendal::service",
+                    "operation={} -> retry after {}s: error={:?}",
+                    Operation::SetAcl, dur.as_secs_f64(), err)
+            })
+            .map(|v| v.map_err(|e| e.set_persistent()))
+            .await
+    }
+
+    async fn get_tags(&self, path: &str, args: OpGetTags) -> Result<RpGetTags> {
+        let backoff = self.builder.renew();
+        { || self.inner.get_tags(path, args.clone()) }
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
+            .notify(|err, dur| {
+                warn!(
+                    target: "opendal::service",
+                    "operation={} -> retry after {}s: error={:?}",
+                    Operation::GetTags, dur.as_secs_f64(), err)
+            })
+            .map(|v| v.map_err(|e| e.set_persistent()))
+            .await
+    }
+
+    async fn put_tags(&self, path: &str, args: OpPutTags) -> Result<RpPutTags> {
+        let backoff = self.builder.renew();
+        { || self.inner.put_tags(path, args.clone()) }
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
+            .notify(|err, dur| {
+                warn!(
+                    target: "opendal::service",
+                    "operation={} -> retry after {}s: error={:?}",
+                    Operation::PutTags, dur.as_secs_f64(), err)
+            })
+            .map(|v| v.map_err(|e| e.set_persistent()))
+            .await
+    }
+
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        let backoff = self.builder.renew();
         { || self.inner.list(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -256,7 +483,7 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
             })
             .map(|v| {
                 v.map(|(l, p)| {
-                    let pager = RetryWrapper::new(p, path, self.builder.clone());
+                    let pager = RetryWrapper::new(p, path, self.builder.renew());
                     (l, pager)
                 })
                 .map_err(|e| e.set_persistent())
@@ -265,9 +492,10 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
     }
 
     async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        let backoff = self.builder.renew();
         { || self.inner.scan(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -276,7 +504,7 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
             })
             .map(|v| {
                 v.map(|(l, p)| {
-                    let pager = RetryWrapper::new(p, path, self.builder.clone());
+                    let pager = RetryWrapper::new(p, path, self.builder.renew());
                     (l, pager)
                 })
                 .map_err(|e| e.set_persistent())
@@ -285,9 +513,10 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
     }
 
     async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let backoff = self.builder.renew();
         { || self.inner.batch(args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -299,9 +528,10 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
     }
 
     fn blocking_create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        let backoff = self.builder.renew();
         { || self.inner.blocking_create(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -313,9 +543,10 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
     }
 
     fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let backoff = self.builder.renew();
         { || self.inner.blocking_read(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -323,14 +554,15 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
                     Operation::BlockingRead, dur.as_secs_f64(), err)
             })
             .call()
-            .map(|(rp, r)| (rp, RetryWrapper::new(r, path, self.builder.clone())))
+            .map(|(rp, r)| (rp, RetryWrapper::new(r, path, self.builder.renew())))
             .map_err(|e| e.set_persistent())
     }
 
     fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let backoff = self.builder.renew();
         { || self.inner.blocking_write(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -338,14 +570,15 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
                     Operation::BlockingWrite, dur.as_secs_f64(), err)
             })
             .call()
-            .map(|(rp, r)| (rp, RetryWrapper::new(r, path, self.builder.clone())))
+            .map(|(rp, r)| (rp, RetryWrapper::new(r, path, self.builder.renew())))
             .map_err(|e| e.set_persistent())
     }
 
     fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let backoff = self.builder.renew();
         { || self.inner.blocking_stat(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -357,9 +590,10 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
     }
 
     fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let backoff = self.builder.renew();
         { || self.inner.blocking_delete(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -371,9 +605,10 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
     }
 
     fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        let backoff = self.builder.renew();
         { || self.inner.blocking_list(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -382,16 +617,17 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
             })
             .call()
             .map(|(rp, p)| {
-                let p = RetryWrapper::new(p, path, self.builder.clone());
+                let p = RetryWrapper::new(p, path, self.builder.renew());
                 (rp, p)
             })
             .map_err(|e| e.set_persistent())
     }
 
     fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        let backoff = self.builder.renew();
         { || self.inner.blocking_scan(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(|err, dur| {
                 warn!(
                     target: "opendal::service",
@@ -400,7 +636,7 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
             })
             .call()
             .map(|(rp, p)| {
-                let p = RetryWrapper::new(p, path, self.builder.clone());
+                let p = RetryWrapper::new(p, path, self.builder.renew());
                 (rp, p)
             })
             .map_err(|e| e.set_persistent())
@@ -410,13 +646,13 @@ impl<A: Accessor> LayeredAccessor for RetryAccessor<A> {
 pub struct RetryWrapper<R> {
     inner: R,
     path: String,
-    builder: ExponentialBuilder,
-    current_backoff: Option<ExponentialBackoff>,
+    builder: RetryBuilder,
+    current_backoff: Option<RetryBackoff>,
     sleep: Option<Pin<Box<tokio::time::Sleep>>>,
 }
 
 impl<R> RetryWrapper<R> {
-    fn new(inner: R, path: &str, backoff: ExponentialBuilder) -> Self {
+    fn new(inner: R, path: &str, backoff: RetryBuilder) -> Self {
         Self {
             inner,
             path: path.to_string(),
@@ -439,7 +675,7 @@ impl<R: oio::Read> oio::Read for RetryWrapper<R> {
                 self.current_backoff = None;
                 Poll::Ready(Ok(v))
             }
-            Err(err) if !err.is_temporary() => {
+            Err(err) if !self.builder.should_retry(&err) => {
                 self.current_backoff = None;
                 Poll::Ready(Err(err))
             }
@@ -481,7 +717,7 @@ impl<R: oio::Read> oio::Read for RetryWrapper<R> {
                 self.current_backoff = None;
                 Poll::Ready(Ok(v))
             }
-            Err(err) if !err.is_temporary() => {
+            Err(err) if !self.builder.should_retry(&err) => {
                 self.current_backoff = None;
                 Poll::Ready(Err(err))
             }
@@ -527,7 +763,7 @@ impl<R: oio::Read> oio::Read for RetryWrapper<R> {
                 self.current_backoff = None;
                 Poll::Ready(Some(Ok(v)))
             }
-            Some(Err(err)) if !err.is_temporary() => {
+            Some(Err(err)) if !self.builder.should_retry(&err) => {
                 self.current_backoff = None;
                 Poll::Ready(Some(Err(err)))
             }
@@ -561,9 +797,10 @@ impl<R: oio::Read> oio::Read for RetryWrapper<R> {
 
 impl<R: oio::BlockingRead> oio::BlockingRead for RetryWrapper<R> {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let backoff = self.builder.renew();
         { || self.inner.read(buf) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(move |err, dur| {
                 warn!(
                 target: "opendal::service",
@@ -575,9 +812,10 @@ impl<R: oio::BlockingRead> oio::BlockingRead for RetryWrapper<R> {
     }
 
     fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
+        let backoff = self.builder.renew();
         { || self.inner.seek(pos) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(move |err, dur| {
                 warn!(
                 target: "opendal::service",
@@ -589,9 +827,10 @@ impl<R: oio::BlockingRead> oio::BlockingRead for RetryWrapper<R> {
     }
 
     fn next(&mut self) -> Option<Result<Bytes>> {
+        let backoff = self.builder.renew();
         { || self.inner.next().transpose() }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(move |err, dur| {
                 warn!(
                 target: "opendal::service",
@@ -612,7 +851,7 @@ impl<R: oio::Write> oio::Write for RetryWrapper<R> {
         loop {
             match self.inner.write(bs.clone()).await {
                 Ok(v) => return Ok(v),
-                Err(e) if !e.is_temporary() => return Err(e),
+                Err(e) if !self.builder.should_retry(&e) => return Err(e),
                 Err(e) => match backoff.next() {
                     None => return Err(e),
                     Some(dur) => {
@@ -633,7 +872,7 @@ impl<R: oio::Write> oio::Write for RetryWrapper<R> {
         loop {
             match self.inner.append(bs.clone()).await {
                 Ok(v) => return Ok(v),
-                Err(e) if !e.is_temporary() => return Err(e),
+                Err(e) if !self.builder.should_retry(&e) => return Err(e),
                 Err(e) => match backoff.next() {
                     None => return Err(e),
                     Some(dur) => {
@@ -648,13 +887,13 @@ impl<R: oio::Write> oio::Write for RetryWrapper<R> {
         }
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn close(&mut self) -> Result<RpWrite> {
         let mut backoff = self.builder.build();
 
         loop {
             match self.inner.close().await {
                 Ok(v) => return Ok(v),
-                Err(e) if !e.is_temporary() => return Err(e),
+                Err(e) if !self.builder.should_retry(&e) => return Err(e),
                 Err(e) => match backoff.next() {
                     None => return Err(e),
                     Some(dur) => {
@@ -668,13 +907,35 @@ impl<R: oio::Write> oio::Write for RetryWrapper<R> {
             }
         }
     }
+
+    async fn abort(&mut self) -> Result<()> {
+        let mut backoff = self.builder.build();
+
+        loop {
+            match self.inner.abort().await {
+                Ok(v) => return Ok(v),
+                Err(e) if !self.builder.should_retry(&e) => return Err(e),
+                Err(e) => match backoff.next() {
+                    None => return Err(e),
+                    Some(dur) => {
+                        warn!(target: "opendal::service",
+                              "operation={} path={} -> pager retry after {}s: error={:?}",
+                              WriteOperation::Abort, self.path, dur.as_secs_f64(), e);
+                        tokio::time::sleep(dur).await;
+                        continue;
+                    }
+                },
+            }
+        }
+    }
 }
 
 impl<R: oio::BlockingWrite> oio::BlockingWrite for RetryWrapper<R> {
     fn write(&mut self, bs: Bytes) -> Result<()> {
+        let backoff = self.builder.renew();
         { || self.inner.write(bs.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(move |err, dur| {
                 warn!(
                 target: "opendal::service",
@@ -686,9 +947,10 @@ impl<R: oio::BlockingWrite> oio::BlockingWrite for RetryWrapper<R> {
     }
 
     fn append(&mut self, bs: Bytes) -> Result<()> {
+        let backoff = self.builder.renew();
         { || self.inner.append(bs.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(move |err, dur| {
                 warn!(
                 target: "opendal::service",
@@ -699,10 +961,11 @@ impl<R: oio::BlockingWrite> oio::BlockingWrite for RetryWrapper<R> {
             .map_err(|e| e.set_persistent())
     }
 
-    fn close(&mut self) -> Result<()> {
+    fn close(&mut self) -> Result<RpWrite> {
+        let backoff = self.builder.renew();
         { || self.inner.close() }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(move |err, dur| {
                 warn!(
                 target: "opendal::service",
@@ -712,6 +975,21 @@ impl<R: oio::BlockingWrite> oio::BlockingWrite for RetryWrapper<R> {
             .call()
             .map_err(|e| e.set_persistent())
     }
+
+    fn abort(&mut self) -> Result<()> {
+        let backoff = self.builder.renew();
+        { || self.inner.abort() }
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
+            .notify(move |err, dur| {
+                warn!(
+                target: "opendal::service",
+                "operation={} -> pager retry after {}s: error={:?}",
+               WriteOperation::BlockingAbort, dur.as_secs_f64(), err)
+            })
+            .call()
+            .map_err(|e| e.set_persistent())
+    }
 }
 
 #[async_trait]
@@ -722,7 +1000,7 @@ impl<P: oio::Page> oio::Page for RetryWrapper<P> {
         loop {
             match self.inner.next().await {
                 Ok(v) => return Ok(v),
-                Err(e) if !e.is_temporary() => return Err(e),
+                Err(e) if !self.builder.should_retry(&e) => return Err(e),
                 Err(e) => match backoff.next() {
                     None => return Err(e),
                     Some(dur) => {
@@ -740,9 +1018,10 @@ impl<P: oio::Page> oio::Page for RetryWrapper<P> {
 
 impl<P: oio::BlockingPage> oio::BlockingPage for RetryWrapper<P> {
     fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let backoff = self.builder.renew();
         { || self.inner.next() }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
+            .retry(&backoff)
+            .when(|e| backoff.should_retry(e))
             .notify(move |err, dur| {
                 warn!(
                 target: "opendal::service",
@@ -822,6 +1101,26 @@ mod tests {
             ))
         }
 
+        async fn stat(&self, _: &str, _: OpStat) -> Result<RpStat> {
+            let mut attempt = self.attempt.lock().unwrap();
+            *attempt += 1;
+
+            match *attempt {
+                1 => Err(
+                    Error::new(ErrorKind::RateLimited, "rate limited error from stat")
+                        .set_temporary()
+                        .set_retry_after(Duration::from_millis(100)),
+                ),
+                // The hint is too large, it must be capped by max delay.
+                2 => Err(
+                    Error::new(ErrorKind::RateLimited, "rate limited error from stat")
+                        .set_temporary()
+                        .set_retry_after(Duration::from_secs(3600)),
+                ),
+                _ => Ok(RpStat::new(Metadata::new(EntryMode::FILE))),
+            }
+        }
+
         async fn list(&self, _: &str, _: OpList) -> Result<(RpList, Self::Pager)> {
             let pager = MockPager::default();
             Ok((RpList::default(), pager))
@@ -968,4 +1267,70 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    #[tokio::test]
+    async fn test_retry_with_retry_after() {
+        let _ = env_logger::try_init();
+
+        let builder = MockBuilder::default();
+        let op = Operator::new(builder.clone())
+            .unwrap()
+            .layer(
+                RetryLayer::new()
+                    .with_min_delay(Duration::from_secs(10))
+                    .with_max_delay(Duration::from_millis(200)),
+            )
+            .finish();
+
+        let now = std::time::Instant::now();
+        op.stat("rate_limited").await.expect("stat must succeed");
+        let elapsed = now.elapsed();
+
+        assert_eq!(*builder.attempt.lock().unwrap(), 3);
+        // Waits 100ms and 200ms (capped) instead of the computed 10s.
+        assert!(elapsed >= Duration::from_millis(300), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(5), "{elapsed:?}");
+    }
+
+    #[test]
+    fn test_retry_backoff_prefers_hint() {
+        let builder = RetryBuilder::new(
+            ExponentialBuilder::default()
+                .with_min_delay(Duration::from_secs(1))
+                .with_max_times(3),
+            Duration::from_secs(10),
+        );
+        let mut backoff = builder.build();
+
+        let err = Error::new(ErrorKind::RateLimited, "slow down").set_temporary();
+        assert!(builder.should_retry(&err.set_retry_after(Duration::from_millis(1))));
+        assert_eq!(backoff.next(), Some(Duration::from_millis(1)));
+
+        // Errors without hints fallback to the computed backoff.
+        let err = Error::new(ErrorKind::Unexpected, "internal error").set_temporary();
+        assert!(builder.should_retry(&err));
+        assert_eq!(backoff.next(), Some(Duration::from_secs(2)));
+
+        // Hints are capped by max delay.
+        let err = Error::new(ErrorKind::RateLimited, "slow down").set_temporary();
+        assert!(builder.should_retry(&err.set_retry_after(Duration::from_secs(3600))));
+        assert_eq!(backoff.next(), Some(Duration::from_secs(10)));
+
+        // Max times is still respected.
+        assert!(builder.should_retry(
+            &Error::new(ErrorKind::RateLimited, "slow down")
+                .set_temporary()
+                .set_retry_after(Duration::from_millis(1))
+        ));
+        assert_eq!(backoff.next(), None);
+
+        // Renewed builders don't share hints.
+        let renewed = builder.renew();
+        assert!(builder.should_retry(
+            &Error::new(ErrorKind::RateLimited, "slow down")
+                .set_temporary()
+                .set_retry_after(Duration::from_millis(1))
+        ));
+        assert_eq!(renewed.build().next(), Some(Duration::from_secs(1)));
+    }
 }
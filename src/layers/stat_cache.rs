@@ -0,0 +1,797 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use parking_lot::Mutex;
+use time::OffsetDateTime;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// Add a ttl based cache for `stat` results.
+///
+/// # Notes
+///
+/// `stat` (and `is_exist` which is built upon `stat`) on the same path
+/// within `ttl` will be served from the cache without visiting the
+/// underlying services.
+///
+/// - `create`, `write`, `delete`, `rename`, `copy`, `restore` and batch deletes
+///   will invalidate the cache entries of affected paths, including all
+///   entries under them for dirs.
+/// - `NotFound` will only be cached if `with_negative_ttl` is set.
+/// - `stat` with tags will always be sent to the underlying services.
+/// - The cache is bounded by `capacity` (10000 by default), the least
+///   recently used entry will be evicted first.
+/// - Changes made by other processes will not be observed until the
+///   entry expired.
+/// - With `with_honor_cache_control`, `Cache-Control` and `Expires` of the
+///   returned metadata will decide the ttl instead, see
+///   [`StatCacheLayer::with_honor_cache_control`] for details.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anyhow::Result;
+/// use opendal::layers::StatCacheLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(
+///         StatCacheLayer::new(Duration::from_secs(5))
+///             .with_negative_ttl(Duration::from_secs(1))
+///             .with_honor_cache_control(true)
+///             .with_capacity(1024),
+///     )
+///     .finish();
+/// ```
+#[derive(Debug, Clone)]
+pub struct StatCacheLayer {
+    ttl: Duration,
+    negative_ttl: Option<Duration>,
+    honor_cache_control: bool,
+    capacity: usize,
+}
+
+impl StatCacheLayer {
+    /// Create a new StatCacheLayer that caches metadata for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            negative_ttl: None,
+            honor_cache_control: false,
+            capacity: 10000,
+        }
+    }
+
+    /// Cache `NotFound` of `stat` for `ttl`.
+    ///
+    /// It's useful for workloads that check non-existent paths frequently.
+    pub fn with_negative_ttl(mut self, ttl: Duration) -> Self {
+        self.negative_ttl = Some(ttl);
+        self
+    }
+
+    /// Use `Cache-Control` and `Expires` of the returned metadata as ttl.
+    ///
+    /// - `no-store` or `no-cache` in `Cache-Control` will bypass the cache.
+    /// - `max-age` in `Cache-Control` takes precedence over `Expires`.
+    /// - Falls back to the `ttl` passed to [`StatCacheLayer::new`] if
+    ///   neither of them is returned.
+    ///
+    /// Default to `false`. Only services that return http headers as
+    /// metadata like s3 and http will carry them.
+    pub fn with_honor_cache_control(mut self, v: bool) -> Self {
+        self.honor_cache_control = v;
+        self
+    }
+
+    /// Set the max number of cached paths.
+    ///
+    /// Setting `capacity` to `0` disables the cache.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+}
+
+impl<A: Accessor> Layer<A> for StatCacheLayer {
+    type LayeredAccessor = StatCacheAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        StatCacheAccessor {
+            inner,
+            cache: Arc::new(
+                StatCache::new(self.ttl, self.negative_ttl, self.capacity)
+                    .with_honor_cache_control(self.honor_cache_control),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatCacheAccessor<A: Accessor> {
+    inner: A,
+    cache: Arc<StatCache>,
+}
+
+impl<A: Accessor> StatCacheAccessor<A> {
+    /// Handle the result of `stat` from underlying services.
+    ///
+    /// `generation` must be fetched before `stat` is sent so that results
+    /// racing with mutations will not be cached.
+    fn handle_stat(&self, path: &str, generation: u64, res: &Result<RpStat>) {
+        match res {
+            Ok(rp) => self
+                .cache
+                .insert(path, generation, Some(rp.clone().into_metadata())),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                self.cache.insert(path, generation, None)
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+fn new_cached_not_found_error(op: Operation, path: &str) -> Error {
+    Error::new(ErrorKind::NotFound, "path not found (cached)")
+        .with_operation(op)
+        .with_context("path", path)
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for StatCacheAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = StatCacheWriter<A::Writer>;
+    type BlockingWriter = StatCacheWriter<A::BlockingWriter>;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("StatCacheLayer")
+    }
+
+    async fn create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        let res = self.inner.create(path, args).await;
+        self.cache.remove(path);
+        res
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.cache.remove(path);
+        self.inner.write(path, args).await.map(|(rp, w)| {
+            (
+                rp,
+                StatCacheWriter::new(w, self.cache.clone(), path.to_string()),
+            )
+        })
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        if args.tags() {
+            return self.inner.stat(path, args).await;
+        }
+
+        let generation = match self.cache.get(path) {
+            Ok(Some(meta)) => return Ok(RpStat::new(meta)),
+            Ok(None) => return Err(new_cached_not_found_error(Operation::Stat, path)),
+            Err(generation) => generation,
+        };
+
+        let res = self.inner.stat(path, args).await;
+        self.handle_stat(path, generation, &res);
+        res
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let res = self.inner.delete(path, args).await;
+        self.cache.remove(path);
+        res
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        self.inner.scan(path, args).await
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let paths: Vec<String> = match args.operation() {
+            BatchOperations::Delete(ops) => ops.iter().map(|(p, _)| p.clone()).collect(),
+        };
+
+        let res = self.inner.batch(args).await;
+        for path in paths {
+            self.cache.remove(&path);
+        }
+        res
+    }
+
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        let res = self.inner.restore(path, args).await;
+        self.cache.remove(path);
+        res
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let res = self.inner.rename(from, to, args).await;
+        self.cache.remove(from);
+        self.cache.remove(to);
+        res
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let res = self.inner.copy(from, to, args).await;
+        self.cache.remove(to);
+        res
+    }
+
+    fn blocking_create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        let res = self.inner.blocking_create(path, args);
+        self.cache.remove(path);
+        res
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.cache.remove(path);
+        self.inner.blocking_write(path, args).map(|(rp, w)| {
+            (
+                rp,
+                StatCacheWriter::new(w, self.cache.clone(), path.to_string()),
+            )
+        })
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        if args.tags() {
+            return self.inner.blocking_stat(path, args);
+        }
+
+        let generation = match self.cache.get(path) {
+            Ok(Some(meta)) => return Ok(RpStat::new(meta)),
+            Ok(None) => return Err(new_cached_not_found_error(Operation::BlockingStat, path)),
+            Err(generation) => generation,
+        };
+
+        let res = self.inner.blocking_stat(path, args);
+        self.handle_stat(path, generation, &res);
+        res
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let res = self.inner.blocking_delete(path, args);
+        self.cache.remove(path);
+        res
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        self.inner.blocking_scan(path, args)
+    }
+}
+
+/// A bounded LRU cache of `stat` results.
+#[derive(Debug)]
+struct StatCache {
+    ttl: Duration,
+    negative_ttl: Option<Duration>,
+    honor_cache_control: bool,
+    capacity: usize,
+    inner: Mutex<LruMap>,
+}
+
+#[derive(Debug, Default)]
+struct LruMap {
+    entries: HashMap<String, CacheEntry>,
+    /// Paths ordered by their last access tick, the first one is the
+    /// least recently used.
+    order: BTreeMap<u64, String>,
+    tick: u64,
+    /// Bumped on every invalidation, results of `stat` sent before that
+    /// could be stale and will not be cached.
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    /// `None` means the path is not found.
+    meta: Option<Metadata>,
+    expire_at: Instant,
+    tick: u64,
+}
+
+impl StatCache {
+    fn new(ttl: Duration, negative_ttl: Option<Duration>, capacity: usize) -> Self {
+        Self {
+            ttl,
+            negative_ttl,
+            honor_cache_control: false,
+            capacity,
+            inner: Mutex::new(LruMap::default()),
+        }
+    }
+
+    fn with_honor_cache_control(mut self, v: bool) -> Self {
+        self.honor_cache_control = v;
+        self
+    }
+
+    /// Returns the ttl of given metadata, or `None` if it must not be cached.
+    fn ttl_of(&self, meta: &Metadata) -> Option<Duration> {
+        if !self.honor_cache_control {
+            return Some(self.ttl);
+        }
+
+        match cache_policy(
+            meta.cache_control(),
+            meta.expires(),
+            OffsetDateTime::now_utc(),
+        ) {
+            CachePolicy::Bypass => None,
+            CachePolicy::Ttl(ttl) => Some(ttl),
+            CachePolicy::Default => Some(self.ttl),
+        }
+    }
+
+    /// Get the cached result of given path.
+    ///
+    /// Returns `Ok(None)` if the path is cached as not found, or the current
+    /// generation if cache missed.
+    fn get(&self, path: &str) -> std::result::Result<Option<Metadata>, u64> {
+        let mut lru = self.inner.lock();
+        let lru = &mut *lru;
+
+        let expired = match lru.entries.get(path) {
+            None => return Err(lru.generation),
+            Some(entry) => entry.expire_at <= Instant::now(),
+        };
+        if expired {
+            lru.remove(path);
+            return Err(lru.generation);
+        }
+
+        lru.tick += 1;
+        let tick = lru.tick;
+        let entry = lru.entries.get_mut(path).expect("entry must exist");
+        lru.order.remove(&entry.tick);
+        lru.order.insert(tick, path.to_string());
+        entry.tick = tick;
+
+        Ok(entry.meta.clone())
+    }
+
+    /// Insert result of `stat` if no invalidation happened since `generation`.
+    fn insert(&self, path: &str, generation: u64, meta: Option<Metadata>) {
+        let ttl = match (&meta, self.negative_ttl) {
+            (Some(meta), _) => match self.ttl_of(meta) {
+                Some(ttl) => ttl,
+                None => return,
+            },
+            (None, Some(ttl)) => ttl,
+            (None, None) => return,
+        };
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut lru = self.inner.lock();
+        if lru.generation != generation {
+            return;
+        }
+
+        lru.remove(path);
+        lru.tick += 1;
+        let tick = lru.tick;
+        lru.order.insert(tick, path.to_string());
+        lru.entries.insert(
+            path.to_string(),
+            CacheEntry {
+                meta,
+                expire_at: Instant::now() + ttl,
+                tick,
+            },
+        );
+
+        while lru.entries.len() > self.capacity {
+            let tick = *lru
+                .order
+                .keys()
+                .next()
+                .expect("order must not be empty while entries not empty");
+            if let Some(evicted) = lru.order.remove(&tick) {
+                lru.entries.remove(&evicted);
+            }
+        }
+    }
+
+    /// Invalidate the cache entry of given path.
+    ///
+    /// Entries under it will be invalidated too if it's a dir, since they
+    /// could be deleted or renamed along with it.
+    fn remove(&self, path: &str) {
+        let mut lru = self.inner.lock();
+        lru.generation += 1;
+
+        if !path.ends_with('/') {
+            lru.remove(path);
+            return;
+        }
+
+        let paths: Vec<String> = lru
+            .entries
+            .keys()
+            .filter(|p| path == "/" || p.starts_with(path))
+            .cloned()
+            .collect();
+        for p in paths {
+            lru.remove(&p);
+        }
+    }
+}
+
+/// CachePolicy is the caching decision declared by metadata.
+#[derive(Debug, PartialEq, Eq)]
+enum CachePolicy {
+    /// Don't cache this result.
+    Bypass,
+    /// Cache this result for given ttl.
+    Ttl(Duration),
+    /// Nothing declared, use the default ttl.
+    Default,
+}
+
+/// Decide the cache policy via `Cache-Control` and `Expires`.
+///
+/// Unknown directives of `Cache-Control` will be ignored.
+fn cache_policy(
+    cache_control: Option<&str>,
+    expires: Option<OffsetDateTime>,
+    now: OffsetDateTime,
+) -> CachePolicy {
+    let mut max_age = None;
+    for directive in cache_control.unwrap_or_default().split(',') {
+        let (name, value) = match directive.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+            None => (directive.trim(), None),
+        };
+
+        if name.eq_ignore_ascii_case("no-store") || name.eq_ignore_ascii_case("no-cache") {
+            return CachePolicy::Bypass;
+        }
+        if name.eq_ignore_ascii_case("max-age") {
+            if let Some(v) = value.and_then(|v| v.parse::<u64>().ok()) {
+                max_age = Some(Duration::from_secs(v));
+            }
+        }
+    }
+
+    let ttl = match (max_age, expires) {
+        (Some(ttl), _) => ttl,
+        (None, Some(expires)) => (expires - now).try_into().unwrap_or(Duration::ZERO),
+        (None, None) => return CachePolicy::Default,
+    };
+    if ttl.is_zero() {
+        CachePolicy::Bypass
+    } else {
+        CachePolicy::Ttl(ttl)
+    }
+}
+
+impl LruMap {
+    fn remove(&mut self, path: &str) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.order.remove(&entry.tick);
+        }
+    }
+}
+
+/// Writer that invalidates the cache entry after it's closed or aborted.
+pub struct StatCacheWriter<W> {
+    inner: W,
+    cache: Arc<StatCache>,
+    path: String,
+}
+
+impl<W> StatCacheWriter<W> {
+    fn new(inner: W, cache: Arc<StatCache>, path: String) -> Self {
+        Self { inner, cache, path }
+    }
+}
+
+#[async_trait]
+impl<W: oio::Write> oio::Write for StatCacheWriter<W> {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.inner.write(bs).await
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.inner.append(bs).await
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        let res = self.inner.close().await;
+        self.cache.remove(&self.path);
+        res
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        let res = self.inner.abort().await;
+        self.cache.remove(&self.path);
+        res
+    }
+}
+
+impl<W: oio::BlockingWrite> oio::BlockingWrite for StatCacheWriter<W> {
+    fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.inner.write(bs)
+    }
+
+    fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.inner.append(bs)
+    }
+
+    fn close(&mut self) -> Result<RpWrite> {
+        let res = self.inner.close();
+        self.cache.remove(&self.path);
+        res
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        let res = self.inner.abort();
+        self.cache.remove(&self.path);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use anyhow::Result;
+
+    use super::*;
+    use crate::raw::tests::CountLayer;
+    use crate::services::Memory;
+
+    fn new_operator(layer: StatCacheLayer) -> (Operator, Arc<AtomicUsize>) {
+        let count = CountLayer::default();
+        let op = Operator::new(Memory::default())
+            .expect("must init")
+            .layer(count.clone())
+            .layer(layer)
+            .finish();
+        (op, count.stats)
+    }
+
+    #[tokio::test]
+    async fn test_stat_cached() -> Result<()> {
+        let (op, stats) = new_operator(StatCacheLayer::new(Duration::from_secs(60)));
+
+        op.write("test", vec![0; 16]).await?;
+        assert_eq!(op.stat("test").await?.content_length(), 16);
+        assert_eq!(op.stat("test").await?.content_length(), 16);
+        assert!(op.is_exist("test").await?);
+        assert_eq!(stats.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_on_mutation() -> Result<()> {
+        let (op, stats) = new_operator(StatCacheLayer::new(Duration::from_secs(60)));
+
+        op.write("test", vec![0; 16]).await?;
+        assert_eq!(op.stat("test").await?.content_length(), 16);
+
+        op.write("test", vec![0; 32]).await?;
+        assert_eq!(op.stat("test").await?.content_length(), 32);
+        assert_eq!(stats.load(Ordering::SeqCst), 2);
+
+        op.delete("test").await?;
+        assert!(!op.is_exist("test").await?);
+        assert_eq!(stats.load(Ordering::SeqCst), 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_negative_cache() -> Result<()> {
+        let (op, stats) = new_operator(StatCacheLayer::new(Duration::from_secs(60)));
+        assert!(!op.is_exist("test").await?);
+        assert!(!op.is_exist("test").await?);
+        assert_eq!(stats.load(Ordering::SeqCst), 2, "not found is not cached");
+
+        let (op, stats) = new_operator(
+            StatCacheLayer::new(Duration::from_secs(60)).with_negative_ttl(Duration::from_secs(60)),
+        );
+        assert!(!op.is_exist("test").await?);
+        assert_eq!(
+            op.stat("test").await.unwrap_err().kind(),
+            ErrorKind::NotFound
+        );
+        assert_eq!(stats.load(Ordering::SeqCst), 1);
+
+        op.write("test", vec![0; 16]).await?;
+        assert!(op.is_exist("test").await?);
+        assert_eq!(stats.load(Ordering::SeqCst), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_expire() -> Result<()> {
+        let (op, stats) = new_operator(StatCacheLayer::new(Duration::from_millis(50)));
+
+        op.write("test", vec![0; 16]).await?;
+        op.stat("test").await?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        op.stat("test").await?;
+        assert_eq!(stats.load(Ordering::SeqCst), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let cache = StatCache::new(Duration::from_secs(60), None, 2);
+        let meta = Metadata::new(EntryMode::FILE);
+
+        cache.insert("a", 0, Some(meta.clone()));
+        cache.insert("b", 0, Some(meta.clone()));
+        // Access `a` so that `b` becomes the least recently used.
+        assert!(cache.get("a").is_ok());
+        cache.insert("c", 0, Some(meta));
+
+        assert!(cache.get("a").is_ok());
+        assert!(cache.get("b").is_err());
+        assert!(cache.get("c").is_ok());
+    }
+
+    #[test]
+    fn test_remove_dir() {
+        let cache = StatCache::new(Duration::from_secs(60), None, 16);
+        let meta = Metadata::new(EntryMode::FILE);
+
+        for path in ["dir/", "dir/a", "dir/sub/b", "dir2/c", "d"] {
+            cache.insert(path, 0, Some(meta.clone()));
+        }
+
+        cache.remove("dir/");
+        assert!(cache.get("dir/").is_err());
+        assert!(cache.get("dir/a").is_err());
+        assert!(cache.get("dir/sub/b").is_err());
+        assert!(cache.get("dir2/c").is_ok());
+        assert!(cache.get("d").is_ok());
+
+        cache.remove("/");
+        assert!(cache.get("dir2/c").is_err());
+        assert!(cache.get("d").is_err());
+    }
+
+    #[test]
+    fn test_cache_policy() {
+        let now = OffsetDateTime::now_utc();
+        let cases = vec![
+            ("none", None, None, CachePolicy::Default),
+            (
+                "max-age",
+                Some("public, max-age=60"),
+                None,
+                CachePolicy::Ttl(Duration::from_secs(60)),
+            ),
+            ("max-age zero", Some("max-age=0"), None, CachePolicy::Bypass),
+            ("no-store", Some("no-store"), None, CachePolicy::Bypass),
+            (
+                "no-cache",
+                Some("No-Cache, max-age=60"),
+                None,
+                CachePolicy::Bypass,
+            ),
+            (
+                "max-age over expires",
+                Some("max-age=60"),
+                Some(now + time::Duration::seconds(10)),
+                CachePolicy::Ttl(Duration::from_secs(60)),
+            ),
+            (
+                "expires",
+                Some("public"),
+                Some(now + time::Duration::seconds(10)),
+                CachePolicy::Ttl(Duration::from_secs(10)),
+            ),
+            (
+                "expired",
+                None,
+                Some(now - time::Duration::seconds(10)),
+                CachePolicy::Bypass,
+            ),
+        ];
+
+        for (name, cache_control, expires, expected) in cases {
+            assert_eq!(
+                cache_policy(cache_control, expires, now),
+                expected,
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_honor_cache_control() {
+        let cache =
+            StatCache::new(Duration::from_secs(60), None, 10).with_honor_cache_control(true);
+        let meta = Metadata::new(EntryMode::FILE).with_bit(Metakey::Complete);
+
+        cache.insert("default", 0, Some(meta.clone()));
+        cache.insert(
+            "no-store",
+            0,
+            Some(meta.clone().with_cache_control("no-store".to_string())),
+        );
+        cache.insert(
+            "expired",
+            0,
+            Some(meta.with_expires(OffsetDateTime::UNIX_EPOCH)),
+        );
+
+        assert!(cache.get("default").is_ok());
+        assert!(cache.get("no-store").is_err());
+        assert!(cache.get("expired").is_err());
+    }
+
+    #[test]
+    fn test_skip_stale_result() {
+        let cache = StatCache::new(Duration::from_secs(60), None, 2);
+
+        let generation = cache.get("a").unwrap_err();
+        // A mutation happened while `stat` is in flight.
+        cache.remove("a");
+        cache.insert("a", generation, Some(Metadata::new(EntryMode::FILE)));
+
+        assert!(cache.get("a").is_err());
+    }
+}
@@ -0,0 +1,427 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// Scope underlying storage services into a sub dir.
+///
+/// SubdirLayer will prepend the prefix to every path and strip it from
+/// returning entries, so that users can treat the prefix as root. For
+/// example, `read("a.txt")` will read `tenants/t1/a.txt` and `list("/")`
+/// will return `a.txt` instead of `tenants/t1/a.txt`.
+///
+/// # Notes
+///
+/// - Paths that can't pass [`check_path`], like those contain `..`, will be
+///   rejected with `ConfigInvalid` so that users can't escape from the prefix.
+/// - Errors returned from underlying services still carry the full path.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::SubdirLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let op = Operator::new(services::Memory::default())?.finish();
+/// let tenant = op.clone().layer(SubdirLayer::new("tenants/t1"));
+///
+/// tenant.write("a.txt", "Hello, World!").await?;
+/// assert!(op.is_exist("tenants/t1/a.txt").await?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SubdirLayer {
+    prefix: String,
+}
+
+impl SubdirLayer {
+    /// Create a new SubdirLayer with given prefix.
+    ///
+    /// Prefix will be normalized into style like `path/to/dir/`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if prefix can't pass [`check_path`], for example it contains `..`.
+    pub fn new(prefix: &str) -> Self {
+        let prefix = normalize_path(prefix);
+        if let Err(err) = check_path(&prefix) {
+            panic!("prefix of SubdirLayer is invalid: {err}")
+        }
+
+        let prefix = match prefix.as_str() {
+            "/" => String::new(),
+            v if v.ends_with('/') => v.to_string(),
+            v => format!("{v}/"),
+        };
+
+        Self { prefix }
+    }
+}
+
+impl<A: Accessor> Layer<A> for SubdirLayer {
+    type LayeredAccessor = SubdirAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        SubdirAccessor {
+            inner,
+            prefix: self.prefix.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SubdirAccessor<A: Accessor> {
+    inner: A,
+    prefix: String,
+}
+
+impl<A: Accessor> SubdirAccessor<A> {
+    /// Build the path in underlying services.
+    fn abs_path(&self, path: &str) -> Result<String> {
+        check_path(path).map_err(|err| {
+            err.with_context("service", self.inner.info().scheme())
+                .with_context("subdir", &self.prefix)
+        })?;
+
+        if path == "/" {
+            // The root of underlying services is still `/`.
+            if self.prefix.is_empty() {
+                return Ok(path.to_string());
+            }
+            return Ok(self.prefix.clone());
+        }
+
+        Ok(format!("{}{}", self.prefix, path))
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for SubdirAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = SubdirPager<A::Pager>;
+    type BlockingPager = SubdirPager<A::BlockingPager>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("SubdirLayer")
+    }
+
+    fn metadata(&self) -> AccessorInfo {
+        let mut meta = self.inner.info();
+        let root = format!("{}{}", meta.root(), self.prefix);
+        meta.set_root(&root);
+
+        meta
+    }
+
+    async fn create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        self.inner.create(&self.abs_path(path)?, args).await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(&self.abs_path(path)?, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.inner.write(&self.abs_path(path)?, args).await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.inner.stat(&self.abs_path(path)?, args).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.inner.delete(&self.abs_path(path)?, args).await
+    }
+
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        self.inner.restore(&self.abs_path(path)?, args).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        path: &str,
+        args: OpAbortMultipart,
+    ) -> Result<RpAbortMultipart> {
+        self.inner
+            .abort_multipart(&self.abs_path(path)?, args)
+            .await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.inner
+            .rename(&self.abs_path(from)?, &self.abs_path(to)?, args)
+            .await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.inner
+            .copy(&self.abs_path(from)?, &self.abs_path(to)?, args)
+            .await
+    }
+
+    async fn get_acl(&self, path: &str, args: OpGetAcl) -> Result<RpGetAcl> {
+        self.inner.get_acl(&self.abs_path(path)?, args).await
+    }
+
+    async fn set_acl(&self, path: &str, args: OpSetAcl) -> Result<RpSetAcl> {
+        self.inner.set_acl(&self.abs_path(path)?, args).await
+    }
+
+    async fn get_tags(&self, path: &str, args: OpGetTags) -> Result<RpGetTags> {
+        self.inner.get_tags(&self.abs_path(path)?, args).await
+    }
+
+    async fn put_tags(&self, path: &str, args: OpPutTags) -> Result<RpPutTags> {
+        self.inner.put_tags(&self.abs_path(path)?, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        let (rp, p) = self.inner.list(&self.abs_path(path)?, args).await?;
+
+        Ok((rp, SubdirPager::new(p, &self.prefix)))
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        let (rp, p) = self.inner.scan(&self.abs_path(path)?, args).await?;
+
+        Ok((rp, SubdirPager::new(p, &self.prefix)))
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        match args.into_operation() {
+            BatchOperations::Delete(ops) => {
+                let ops = ops
+                    .into_iter()
+                    .map(|(path, op)| Ok((self.abs_path(&path)?, op)))
+                    .collect::<Result<Vec<_>>>()?;
+
+                let rp = self
+                    .inner
+                    .batch(OpBatch::new(BatchOperations::Delete(ops)))
+                    .await?;
+
+                let BatchedResults::Delete(results) = rp.into_results();
+                let results = results
+                    .into_iter()
+                    .map(|(path, result)| (strip_prefix(&path, &self.prefix), result))
+                    .collect();
+
+                Ok(RpBatch::new(BatchedResults::Delete(results)))
+            }
+        }
+    }
+
+    fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.inner.presign(&self.abs_path(path)?, args)
+    }
+
+    fn blocking_create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        self.inner.blocking_create(&self.abs_path(path)?, args)
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(&self.abs_path(path)?, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(&self.abs_path(path)?, args)
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.inner.blocking_stat(&self.abs_path(path)?, args)
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.inner.blocking_delete(&self.abs_path(path)?, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        let (rp, p) = self.inner.blocking_list(&self.abs_path(path)?, args)?;
+
+        Ok((rp, SubdirPager::new(p, &self.prefix)))
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        let (rp, p) = self.inner.blocking_scan(&self.abs_path(path)?, args)?;
+
+        Ok((rp, SubdirPager::new(p, &self.prefix)))
+    }
+}
+
+/// Strip prefix from path returned by underlying services.
+fn strip_prefix(path: &str, prefix: &str) -> String {
+    match path.strip_prefix(prefix) {
+        Some("") => "/".to_string(),
+        Some(v) => v.to_string(),
+        None => path.to_string(),
+    }
+}
+
+pub struct SubdirPager<P> {
+    inner: P,
+    prefix: String,
+}
+
+impl<P> SubdirPager<P> {
+    fn new(inner: P, prefix: &str) -> Self {
+        Self {
+            inner,
+            prefix: prefix.to_string(),
+        }
+    }
+
+    fn strip_entries(&self, entries: Option<Vec<oio::Entry>>) -> Option<Vec<oio::Entry>> {
+        entries.map(|entries| {
+            entries
+                .into_iter()
+                .map(|mut entry| {
+                    let path = strip_prefix(entry.path(), &self.prefix);
+                    entry.set_path(&path);
+                    entry
+                })
+                .collect()
+        })
+    }
+}
+
+#[async_trait]
+impl<P: oio::Page> oio::Page for SubdirPager<P> {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let entries = self.inner.next().await?;
+
+        Ok(self.strip_entries(entries))
+    }
+}
+
+impl<P: oio::BlockingPage> oio::BlockingPage for SubdirPager<P> {
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let entries = self.inner.next()?;
+
+        Ok(self.strip_entries(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use anyhow::Result;
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::services::Memory;
+
+    #[test]
+    fn test_new() {
+        let cases = vec![
+            ("tenants/t1", "tenants/t1/"),
+            ("/tenants/t1/", "tenants/t1/"),
+            ("tenants//t1", "tenants/t1/"),
+            ("", ""),
+            ("/", ""),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(SubdirLayer::new(input).prefix, expected, "{input}");
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_with_parent_segment() {
+        SubdirLayer::new("tenants/../t1");
+    }
+
+    #[tokio::test]
+    async fn test_subdir() -> Result<()> {
+        let op = Operator::new(Memory::default())?.finish();
+        op.write("tenants/t2/b.txt", "t2").await?;
+
+        let t1 = op.clone().layer(SubdirLayer::new("tenants/t1"));
+        t1.write("a.txt", "Hello, World!").await?;
+        t1.write("dir/c.txt", "Hello, World!").await?;
+
+        assert_eq!(op.read("tenants/t1/a.txt").await?, b"Hello, World!");
+        assert_eq!(t1.read("a.txt").await?, b"Hello, World!");
+        assert_eq!(t1.stat("a.txt").await?.content_length(), 13);
+
+        let entries: HashSet<String> = t1
+            .list("/")
+            .await?
+            .map_ok(|e| e.path().to_string())
+            .try_collect()
+            .await?;
+        assert_eq!(
+            entries,
+            HashSet::from(["a.txt".to_string(), "dir/".to_string()])
+        );
+
+        let entries: HashSet<String> = t1
+            .scan("/")
+            .await?
+            .map_ok(|e| e.path().to_string())
+            .try_collect()
+            .await?;
+        assert_eq!(
+            entries,
+            HashSet::from(["a.txt".to_string(), "dir/c.txt".to_string()])
+        );
+
+        t1.remove(vec!["a.txt".to_string(), "dir/c.txt".to_string()])
+            .await?;
+        assert!(!op.is_exist("tenants/t1/a.txt").await?);
+        assert!(op.is_exist("tenants/t2/b.txt").await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subdir_escape() -> Result<()> {
+        let op = Operator::new(Memory::default())?.finish();
+        op.write("tenants/t2/b.txt", "t2").await?;
+
+        let t1 = op.layer(SubdirLayer::new("tenants/t1"));
+
+        let err = t1
+            .read("../t2/b.txt")
+            .await
+            .expect_err("escape must be rejected");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let err = t1
+            .write("dir/../../t2/b.txt", "t1")
+            .await
+            .expect_err("escape must be rejected");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+        Ok(())
+    }
+}
@@ -143,6 +143,10 @@ impl<A: Accessor> LayeredAccessor for TracingAccessor<A> {
         &self.inner
     }
 
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("TracingLayer")
+    }
+
     #[tracing::instrument(level = "debug")]
     fn metadata(&self) -> AccessorInfo {
         self.inner.info()
@@ -202,6 +206,50 @@ impl<A: Accessor> LayeredAccessor for TracingAccessor<A> {
         self.inner.batch(args).await
     }
 
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        self.inner.restore(path, args).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn abort_multipart(
+        &self,
+        path: &str,
+        args: OpAbortMultipart,
+    ) -> Result<RpAbortMultipart> {
+        self.inner.abort_multipart(path, args).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.inner.rename(from, to, args).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.inner.copy(from, to, args).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn get_acl(&self, path: &str, args: OpGetAcl) -> Result<RpGetAcl> {
+        self.inner.get_acl(path, args).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn set_acl(&self, path: &str, args: OpSetAcl) -> Result<RpSetAcl> {
+        self.inner.set_acl(path, args).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn get_tags(&self, path: &str, args: OpGetTags) -> Result<RpGetTags> {
+        self.inner.get_tags(path, args).await
+    }
+
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn put_tags(&self, path: &str, args: OpPutTags) -> Result<RpPutTags> {
+        self.inner.put_tags(path, args).await
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     fn blocking_create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
         self.inner.blocking_create(path, args)
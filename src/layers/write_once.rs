@@ -0,0 +1,382 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// Prevent overwriting existing files for underlying storage services.
+///
+/// WriteOnceLayer will check the target before `write`, `copy` and `rename`,
+/// and return an error with [`ErrorKind::AlreadyExists`] if it exists.
+///
+/// # Guarantee
+///
+/// - If the service supports `write_with_if_not_exists`, writes will be sent
+///   with [`OpWrite::with_if_not_exists`], so the check is atomic and
+///   concurrent writers can't overwrite each other.
+/// - Otherwise, the layer will `stat` the target before writing. This is
+///   best-effort only: the file could still be created by others between
+///   the `stat` and the `write`.
+/// - `copy` and `rename` always use `stat` since there is no conditional
+///   primitive for them.
+///
+/// # Notes
+///
+/// - `delete` is not affected.
+/// - Writes with [`OpWrite::with_append_existing`] extend the existing file
+///   instead of overwriting it, so they are not checked.
+/// - Paths added by [`WriteOnceLayer::allow_overwrite`] will bypass the check.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::WriteOnceLayer;
+/// use opendal::services;
+/// use opendal::ErrorKind;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let op = Operator::new(services::Memory::default())?
+///     .layer(WriteOnceLayer::new().allow_overwrite("_meta/"))
+///     .finish();
+///
+/// op.write("data/file", "Hello, World!").await?;
+/// let err = op.write("data/file", "Hello, World!").await.unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+///
+/// op.write("_meta/version", "1").await?;
+/// op.write("_meta/version", "2").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct WriteOnceLayer {
+    allowed: Vec<String>,
+}
+
+impl WriteOnceLayer {
+    /// Create a new WriteOnceLayer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow overwriting given path.
+    ///
+    /// If path ends with `/`, all files under it could be overwritten.
+    pub fn allow_overwrite(mut self, path: &str) -> Self {
+        self.allowed.push(normalize_path(path));
+        self
+    }
+}
+
+impl<A: Accessor> Layer<A> for WriteOnceLayer {
+    type LayeredAccessor = WriteOnceAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        let atomic = inner.info().capability().write_with_if_not_exists;
+
+        WriteOnceAccessor {
+            inner,
+            atomic,
+            allowed: Arc::new(self.allowed.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WriteOnceAccessor<A: Accessor> {
+    inner: A,
+    /// Whether the inner accessor supports `write_with_if_not_exists`.
+    atomic: bool,
+    allowed: Arc<Vec<String>>,
+}
+
+impl<A: Accessor> WriteOnceAccessor<A> {
+    fn is_overwritable(&self, path: &str) -> bool {
+        self.allowed.iter().any(|p| {
+            if p.ends_with('/') {
+                path.starts_with(p.as_str())
+            } else {
+                path == p
+            }
+        })
+    }
+
+    async fn check(&self, op: Operation, path: &str) -> Result<()> {
+        match self.inner.stat(path, OpStat::new()).await {
+            Ok(_) => Err(new_already_exists_error(op, path)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn blocking_check(&self, op: Operation, path: &str) -> Result<()> {
+        match self.inner.blocking_stat(path, OpStat::new()) {
+            Ok(_) => Err(new_already_exists_error(op, path)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+fn new_already_exists_error(op: Operation, path: &str) -> Error {
+    Error::new(
+        ErrorKind::AlreadyExists,
+        "path already exists and is not allowed to be overwritten",
+    )
+    .with_operation(op)
+    .with_context("path", path)
+}
+
+/// Convert the `ConditionNotMatch` returned by `if_not_exists` writes.
+fn map_condition_error(err: Error) -> Error {
+    if err.kind() == ErrorKind::ConditionNotMatch {
+        Error::new(
+            ErrorKind::AlreadyExists,
+            "path already exists and is not allowed to be overwritten",
+        )
+        .set_source(err)
+    } else {
+        err
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for WriteOnceAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = WriteOnceWriter<A::Writer>;
+    type BlockingWriter = WriteOnceWriter<A::BlockingWriter>;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn layer_name(&self) -> Option<&'static str> {
+        Some("WriteOnceLayer")
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let args = if args.append_existing() || self.is_overwritable(path) {
+            args
+        } else if self.atomic {
+            args.with_if_not_exists(true)
+        } else {
+            self.check(Operation::Write, path).await?;
+            args
+        };
+
+        self.inner
+            .write(path, args)
+            .await
+            .map(|(rp, w)| (rp, WriteOnceWriter::new(w)))
+            .map_err(map_condition_error)
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        if !self.is_overwritable(to) {
+            self.check(Operation::Copy, to).await?;
+        }
+
+        self.inner.copy(from, to, args).await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        if !self.is_overwritable(to) {
+            self.check(Operation::Rename, to).await?;
+        }
+
+        self.inner.rename(from, to, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        self.inner.scan(path, args).await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let args = if args.append_existing() || self.is_overwritable(path) {
+            args
+        } else if self.atomic {
+            args.with_if_not_exists(true)
+        } else {
+            self.blocking_check(Operation::BlockingWrite, path)?;
+            args
+        };
+
+        self.inner
+            .blocking_write(path, args)
+            .map(|(rp, w)| (rp, WriteOnceWriter::new(w)))
+            .map_err(map_condition_error)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        self.inner.blocking_scan(path, args)
+    }
+}
+
+/// WriteOnceWriter converts the error of `if_not_exists` writes into
+/// [`ErrorKind::AlreadyExists`], services could report them while closing.
+pub struct WriteOnceWriter<W> {
+    inner: W,
+}
+
+impl<W> WriteOnceWriter<W> {
+    fn new(inner: W) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl<W: oio::Write> oio::Write for WriteOnceWriter<W> {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.inner.write(bs).await.map_err(map_condition_error)
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.inner.append(bs).await.map_err(map_condition_error)
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        self.inner.close().await.map_err(map_condition_error)
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        self.inner.abort().await
+    }
+}
+
+impl<W: oio::BlockingWrite> oio::BlockingWrite for WriteOnceWriter<W> {
+    fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.inner.write(bs).map_err(map_condition_error)
+    }
+
+    fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.inner.append(bs).map_err(map_condition_error)
+    }
+
+    fn close(&mut self) -> Result<RpWrite> {
+        self.inner.close().map_err(map_condition_error)
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        self.inner.abort()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::services::Fs;
+    use crate::services::Memory;
+
+    #[tokio::test]
+    async fn test_write_once_with_stat() {
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .layer(WriteOnceLayer::new())
+            .finish();
+
+        op.write("file", "Hello, World!").await.unwrap();
+        let err = op.write("file", "Hello, World!").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+        // Delete is not affected.
+        op.delete("file").await.unwrap();
+        op.write("file", "Hello, World!").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_write_once_with_if_not_exists() {
+        let root = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let mut builder = Fs::default();
+        builder.root(&root.to_string_lossy());
+        let op = Operator::new(builder)
+            .unwrap()
+            .layer(WriteOnceLayer::new())
+            .finish();
+        assert!(op.info().capability().write_with_if_not_exists);
+
+        op.write("file", "Hello, World!").await.unwrap();
+        let err = op.write("file", "Hello, World!").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        assert_eq!(op.read("file").await.unwrap(), b"Hello, World!");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_write_once_allow_overwrite() {
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .layer(
+                WriteOnceLayer::new()
+                    .allow_overwrite("/version")
+                    .allow_overwrite("meta/"),
+            )
+            .finish();
+
+        for path in ["version", "meta/a", "meta/b/c"] {
+            op.write(path, "1").await.unwrap();
+            op.write(path, "2").await.unwrap();
+            assert_eq!(op.read(path).await.unwrap(), b"2");
+        }
+
+        op.write("meta", "1").await.unwrap();
+        let err = op.write("meta", "2").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+    }
+
+    #[test]
+    fn test_blocking_write_once() {
+        let op = Operator::new(Memory::default())
+            .unwrap()
+            .layer(WriteOnceLayer::new())
+            .finish()
+            .blocking();
+
+        op.write("file", "Hello, World!").unwrap();
+        let err = op.write("file", "Hello, World!").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+    }
+}
@@ -218,6 +218,155 @@ pub trait Accessor: Send + Sync + Debug + Unpin + 'static {
         ))
     }
 
+    /// Invoke the `restore` operation on the specified path.
+    ///
+    /// Require `restore` of [`Capability`]
+    ///
+    /// # Behavior
+    ///
+    /// - Initiate restoring an archived path so that it can be read again,
+    ///   it's ok to return before the restore finished.
+    /// - This API is optional, return [`ErrorKind::Unsupported`] if not supported.
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        let (_, _) = (path, args);
+
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "operation is not supported",
+        ))
+    }
+
+    /// Invoke the `abort_multipart` operation on the specified path.
+    ///
+    /// Require `abort_multipart` of [`Capability`]
+    ///
+    /// # Behavior
+    ///
+    /// - Abort the multipart upload (or upload session) of given path, all
+    ///   uploaded parts will be freed.
+    /// - The upload id is the session returned by [`RpWrite::session`].
+    /// - Aborting an upload that doesn't exist or has been aborted should
+    ///   return [`ErrorKind::NotFound`].
+    /// - This API is optional, return [`ErrorKind::Unsupported`] if not supported.
+    async fn abort_multipart(
+        &self,
+        path: &str,
+        args: OpAbortMultipart,
+    ) -> Result<RpAbortMultipart> {
+        let (_, _) = (path, args);
+
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "operation is not supported",
+        ))
+    }
+
+    /// Invoke the `rename` operation from the `from` path to the `to` path.
+    ///
+    /// Require `rename` of [`Capability`]
+    ///
+    /// # Behavior
+    ///
+    /// - `from` and `to` must be both files or both dirs.
+    /// - Rename a dir should move the whole subtree.
+    /// - This API is optional, return [`ErrorKind::Unsupported`] if not supported.
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let (_, _, _) = (from, to, args);
+
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "operation is not supported",
+        ))
+    }
+
+    /// Invoke the `copy` operation from the `from` path to the `to` path.
+    ///
+    /// Require `copy` of [`Capability`]
+    ///
+    /// # Behavior
+    ///
+    /// - `from` and `to` must be files.
+    /// - The copy should be done on the server side without transferring
+    ///   the content through the client.
+    /// - `to` should be overwritten if it exists.
+    /// - This API is optional, return [`ErrorKind::Unsupported`] if not supported.
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let (_, _, _) = (from, to, args);
+
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "operation is not supported",
+        ))
+    }
+
+    /// Invoke the `get_acl` operation on the specified path.
+    ///
+    /// Require `acl` of [`Capability`]
+    ///
+    /// # Behavior
+    ///
+    /// - This API is optional, return [`ErrorKind::Unsupported`] if not supported.
+    async fn get_acl(&self, path: &str, args: OpGetAcl) -> Result<RpGetAcl> {
+        let (_, _) = (path, args);
+
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "operation is not supported",
+        ))
+    }
+
+    /// Invoke the `set_acl` operation on the specified path.
+    ///
+    /// Require `acl` of [`Capability`]
+    ///
+    /// # Behavior
+    ///
+    /// - Fields that not set in [`Acl`] should be kept unchanged.
+    /// - This API is optional, return [`ErrorKind::Unsupported`] if not supported.
+    async fn set_acl(&self, path: &str, args: OpSetAcl) -> Result<RpSetAcl> {
+        let (_, _) = (path, args);
+
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "operation is not supported",
+        ))
+    }
+
+    /// Invoke the `get_tags` operation on the specified path.
+    ///
+    /// Require `tags` of [`Capability`]
+    ///
+    /// # Behavior
+    ///
+    /// - Return an empty map if the path exists but has no tags.
+    /// - This API is optional, return [`ErrorKind::Unsupported`] if not supported.
+    async fn get_tags(&self, path: &str, args: OpGetTags) -> Result<RpGetTags> {
+        let (_, _) = (path, args);
+
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "operation is not supported",
+        ))
+    }
+
+    /// Invoke the `put_tags` operation on the specified path.
+    ///
+    /// Require `tags` of [`Capability`]
+    ///
+    /// # Behavior
+    ///
+    /// - All existing tags should be replaced by the given tags.
+    /// - The content of the path should not be rewritten.
+    /// - This API is optional, return [`ErrorKind::Unsupported`] if not supported.
+    async fn put_tags(&self, path: &str, args: OpPutTags) -> Result<RpPutTags> {
+        let (_, _) = (path, args);
+
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "operation is not supported",
+        ))
+    }
+
     /// Invoke the `blocking_create` operation on the specified path.
     ///
     /// This operation is the blocking version of [`Accessor::create`]
@@ -336,6 +485,8 @@ impl Accessor for () {
             name: "dummy".to_string(),
             capabilities: None.into(),
             hints: None.into(),
+            capability: Capability::default(),
+            layers: vec![],
         }
     }
 }
@@ -382,6 +533,42 @@ impl<T: Accessor + ?Sized> Accessor for Arc<T> {
         self.as_ref().batch(args).await
     }
 
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        self.as_ref().restore(path, args).await
+    }
+
+    async fn abort_multipart(
+        &self,
+        path: &str,
+        args: OpAbortMultipart,
+    ) -> Result<RpAbortMultipart> {
+        self.as_ref().abort_multipart(path, args).await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.as_ref().rename(from, to, args).await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.as_ref().copy(from, to, args).await
+    }
+
+    async fn get_acl(&self, path: &str, args: OpGetAcl) -> Result<RpGetAcl> {
+        self.as_ref().get_acl(path, args).await
+    }
+
+    async fn set_acl(&self, path: &str, args: OpSetAcl) -> Result<RpSetAcl> {
+        self.as_ref().set_acl(path, args).await
+    }
+
+    async fn get_tags(&self, path: &str, args: OpGetTags) -> Result<RpGetTags> {
+        self.as_ref().get_tags(path, args).await
+    }
+
+    async fn put_tags(&self, path: &str, args: OpPutTags) -> Result<RpPutTags> {
+        self.as_ref().put_tags(path, args).await
+    }
+
     fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
         self.as_ref().presign(path, args)
     }
@@ -430,6 +617,8 @@ pub struct AccessorInfo {
     name: String,
     capabilities: FlagSet<AccessorCapability>,
     hints: FlagSet<AccessorHint>,
+    capability: Capability,
+    layers: Vec<&'static str>,
 }
 
 impl AccessorInfo {
@@ -497,6 +686,32 @@ impl AccessorInfo {
         self.hints = hints.into();
         self
     }
+
+    /// Get backend's [`Capability`].
+    pub fn capability(&self) -> Capability {
+        self.capability
+    }
+
+    /// Set [`Capability`] for backend.
+    pub fn set_capability(&mut self, capability: Capability) -> &mut Self {
+        self.capability = capability;
+        self
+    }
+
+    /// Names of layers applied on this backend, outermost first.
+    ///
+    /// The first layer in the list is the one that sees calls first and
+    /// returns results last. Internal layers like the error context layer
+    /// are not included.
+    pub fn layers(&self) -> &[&'static str] {
+        &self.layers
+    }
+
+    /// Record a new outermost layer on this backend.
+    pub fn push_layer(&mut self, name: &'static str) -> &mut Self {
+        self.layers.insert(0, name);
+        self
+    }
 }
 
 flags! {
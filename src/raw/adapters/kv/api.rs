@@ -18,6 +18,7 @@ use async_trait::async_trait;
 use flagset::FlagSet;
 
 use crate::raw::*;
+use crate::Capability;
 use crate::Error;
 use crate::ErrorKind;
 use crate::Result;
@@ -47,6 +48,16 @@ pub trait Adapter: Send + Sync + Debug + Unpin + 'static {
         .with_operation("kv::Adapter::blocking_get"))
     }
 
+    /// Get the length of value of a key.
+    ///
+    /// - return `Ok(None)` if this key is not exist.
+    ///
+    /// Services could implement this to avoid fetching the whole value
+    /// while `stat`.
+    async fn get_len(&self, path: &str) -> Result<Option<u64>> {
+        Ok(self.get(path).await?.map(|v| v.len() as u64))
+    }
+
     /// Set a key into service.
     async fn set(&self, path: &str, value: &[u8]) -> Result<()>;
 
@@ -147,6 +158,28 @@ impl From<Metadata> for AccessorInfo {
         am.set_scheme(m.scheme());
         am.set_capabilities(m.capabilities());
 
+        // kv services always read the whole value into memory, so range,
+        // seek and next are supported as long as read is supported.
+        let (read, write) = (
+            m.capabilities().contains(AccessorCapability::Read),
+            m.capabilities().contains(AccessorCapability::Write),
+        );
+        am.set_capability(Capability {
+            stat: read,
+            read,
+            read_can_seek: read,
+            read_can_next: read,
+            read_with_range: read,
+            read_with_suffix_range: read,
+            write,
+            write_can_append: write,
+            create_dir: write,
+            delete: write,
+            scan: m.capabilities().contains(AccessorCapability::Scan),
+            blocking: m.capabilities().contains(AccessorCapability::Blocking),
+            ..Default::default()
+        });
+
         am
     }
 }
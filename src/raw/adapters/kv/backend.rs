@@ -119,13 +119,19 @@ impl<S: Adapter> Accessor for Backend<S> {
     async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
         let p = build_abs_path(&self.root, path);
 
-        if p.is_empty() || p.ends_with('/') {
+        if p.is_empty() {
             Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+        } else if p.ends_with('/') {
+            // Dirs that only exist via their children will be completed
+            // by CompleteLayer.
+            match self.kv.get_len(&p).await? {
+                Some(_) => Ok(RpStat::new(Metadata::new(EntryMode::DIR))),
+                None => Err(Error::new(ErrorKind::NotFound, "kv doesn't have this path")),
+            }
         } else {
-            let bs = self.kv.get(&p).await?;
-            match bs {
-                Some(bs) => Ok(RpStat::new(
-                    Metadata::new(EntryMode::FILE).with_content_length(bs.len() as u64),
+            match self.kv.get_len(&p).await? {
+                Some(len) => Ok(RpStat::new(
+                    Metadata::new(EntryMode::FILE).with_content_length(len),
                 )),
                 None => Err(Error::new(ErrorKind::NotFound, "kv doesn't have this path")),
             }
@@ -135,8 +141,13 @@ impl<S: Adapter> Accessor for Backend<S> {
     fn blocking_stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
         let p = build_abs_path(&self.root, path);
 
-        if p.is_empty() || p.ends_with('/') {
+        if p.is_empty() {
             Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+        } else if p.ends_with('/') {
+            match self.kv.blocking_get(&p)? {
+                Some(_) => Ok(RpStat::new(Metadata::new(EntryMode::DIR))),
+                None => Err(Error::new(ErrorKind::NotFound, "kv doesn't have this path")),
+            }
         } else {
             let bs = self.kv.blocking_get(&p)?;
             match bs {
@@ -199,6 +210,7 @@ where
     }
 }
 
+/// Pager of kv service.
 pub struct KvPager {
     root: String,
     inner: Option<Vec<String>>,
@@ -255,6 +267,7 @@ impl oio::BlockingPage for KvPager {
     }
 }
 
+/// Writer of kv service.
 pub struct KvWriter<S> {
     kv: Arc<S>,
     path: String,
@@ -287,9 +300,15 @@ impl<S: Adapter> oio::Write for KvWriter<S> {
         Ok(())
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn close(&mut self) -> Result<RpWrite> {
         self.kv.set(&self.path, &self.buf).await?;
 
+        Ok(RpWrite::new())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        self.buf.clear();
+
         Ok(())
     }
 }
@@ -307,9 +326,15 @@ impl<S: Adapter> oio::BlockingWrite for KvWriter<S> {
         Ok(())
     }
 
-    fn close(&mut self) -> Result<()> {
+    fn close(&mut self) -> Result<RpWrite> {
         self.kv.blocking_set(&self.path, &self.buf)?;
 
+        Ok(RpWrite::new())
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        self.buf.clear();
+
         Ok(())
     }
 }
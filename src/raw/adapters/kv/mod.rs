@@ -28,3 +28,5 @@ pub use api::Metadata;
 
 mod backend;
 pub use backend::Backend;
+pub use backend::KvPager;
+pub use backend::KvWriter;
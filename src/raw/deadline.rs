@@ -0,0 +1,146 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Instant;
+
+use pin_project::pin_project;
+
+use crate::*;
+
+thread_local! {
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Restore the previous deadline even if the scope panics.
+struct DeadlineGuard(Option<Instant>);
+
+impl Drop for DeadlineGuard {
+    fn drop(&mut self) {
+        DEADLINE.with(|v| v.set(self.0));
+    }
+}
+
+/// Run `f` with given deadline as the current deadline.
+///
+/// The tighter one will be used if there is already a deadline in scope.
+pub fn scope_deadline<T>(deadline: Option<Instant>, f: impl FnOnce() -> T) -> T {
+    let prev = DEADLINE.with(|v| v.get());
+    let current = match (prev, deadline) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+
+    DEADLINE.with(|v| v.set(current));
+    let _guard = DeadlineGuard(prev);
+    f()
+}
+
+/// Get the deadline of current operation.
+///
+/// HTTP client uses it to set the timeout of requests.
+pub fn current_deadline() -> Option<Instant> {
+    DEADLINE.with(|v| v.get())
+}
+
+/// Return an error if given deadline has been reached.
+pub fn check_deadline(deadline: Option<Instant>) -> Result<()> {
+    match deadline {
+        Some(deadline) if Instant::now() >= deadline => Err(new_deadline_exceeded_error()),
+        _ => Ok(()),
+    }
+}
+
+/// Create a new error for operations that exceed their deadline.
+///
+/// The error is not temporary since retrying will not help.
+pub fn new_deadline_exceeded_error() -> Error {
+    Error::new(ErrorKind::Unexpected, "operation deadline exceeded")
+}
+
+/// DeadlineFuture polls the inner future with given deadline in scope.
+///
+/// The deadline is not checked here, callers should check it before
+/// starting the operation.
+#[pin_project]
+pub struct DeadlineFuture<F> {
+    deadline: Option<Instant>,
+    #[pin]
+    inner: F,
+}
+
+impl<F> DeadlineFuture<F> {
+    /// Create a new DeadlineFuture.
+    pub fn new(deadline: Option<Instant>, inner: F) -> Self {
+        Self { deadline, inner }
+    }
+}
+
+impl<F: Future> Future for DeadlineFuture<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let inner = this.inner;
+        scope_deadline(*this.deadline, || inner.poll(cx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_scope_deadline() {
+        let now = Instant::now();
+        let near = now + Duration::from_secs(1);
+        let far = now + Duration::from_secs(10);
+
+        assert_eq!(current_deadline(), None);
+        scope_deadline(Some(far), || {
+            assert_eq!(current_deadline(), Some(far));
+
+            // The tighter deadline wins.
+            scope_deadline(Some(near), || assert_eq!(current_deadline(), Some(near)));
+            scope_deadline(None, || assert_eq!(current_deadline(), Some(far)));
+
+            assert_eq!(current_deadline(), Some(far));
+        });
+        assert_eq!(current_deadline(), None);
+    }
+
+    #[tokio::test]
+    async fn test_deadline_future() {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let res = DeadlineFuture::new(Some(deadline), async { current_deadline() }).await;
+        assert_eq!(res, Some(deadline));
+        assert_eq!(current_deadline(), None);
+    }
+
+    #[test]
+    fn test_check_deadline() {
+        assert!(check_deadline(None).is_ok());
+        assert!(check_deadline(Some(Instant::now() + Duration::from_secs(10))).is_ok());
+
+        let err = check_deadline(Some(Instant::now())).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(!err.is_temporary());
+    }
+}
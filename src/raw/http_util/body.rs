@@ -17,6 +17,7 @@ use std::cmp::Ordering;
 use std::io;
 use std::io::Read;
 use std::io::Write;
+use std::ops::Range;
 use std::task::Context;
 use std::task::Poll;
 
@@ -26,10 +27,13 @@ use bytes::Bytes;
 use futures::ready;
 use futures::Stream;
 use futures::StreamExt;
+use http::Response;
+use http::StatusCode;
 
 use crate::raw::*;
 use crate::Error;
 use crate::ErrorKind;
+use crate::Metadata;
 use crate::Result;
 
 /// Body used in blocking HTTP requests.
@@ -130,6 +134,11 @@ pub struct IncomingAsyncBody {
     size: Option<u64>,
     consumed: u64,
     chunk: Option<Bytes>,
+    /// The window of the underlying stream that will be returned.
+    ///
+    /// Only set while server ignored the requested range and returns
+    /// the whole content instead.
+    window: Option<Range<u64>>,
 }
 
 impl IncomingAsyncBody {
@@ -140,9 +149,16 @@ impl IncomingAsyncBody {
             size,
             consumed: 0,
             chunk: None,
+            window: None,
         }
     }
 
+    /// Only return the given window of the underlying stream.
+    fn with_window(mut self, window: Range<u64>) -> Self {
+        self.window = Some(window);
+        self
+    }
+
     /// Consume the entire body.
     pub async fn consume(mut self) -> Result<()> {
         use oio::ReadExt;
@@ -246,21 +262,251 @@ impl oio::Read for IncomingAsyncBody {
             return Poll::Ready(Some(Ok(bs)));
         }
 
-        let res = match ready!(self.inner.poll_next_unpin(cx)) {
-            Some(Ok(bs)) => {
-                self.consumed += bs.len() as u64;
-                Some(Ok(bs))
+        loop {
+            // All data in window has been returned, there is no need to
+            // fetch the rest content.
+            if let Some(window) = &self.window {
+                if self.consumed >= window.end {
+                    return Poll::Ready(None);
+                }
             }
-            Some(Err(err)) => Some(Err(err)),
-            None => {
-                if let Some(size) = self.size {
-                    Self::check(size, self.consumed)?;
+
+            let res = match ready!(self.inner.poll_next_unpin(cx)) {
+                Some(Ok(bs)) => {
+                    let pos = self.consumed;
+                    self.consumed += bs.len() as u64;
+
+                    match &self.window {
+                        None => Some(Ok(bs)),
+                        Some(window) => {
+                            let start = min(window.start.saturating_sub(pos), bs.len() as u64);
+                            let end = min(window.end - pos, bs.len() as u64);
+                            if start == end {
+                                continue;
+                            }
+                            Some(Ok(bs.slice(start as usize..end as usize)))
+                        }
+                    }
                 }
+                Some(Err(err)) => Some(Err(err)),
+                None => {
+                    if let Some(size) = self.size {
+                        Self::check(size, self.consumed)?;
+                    }
+
+                    None
+                }
+            };
+
+            return Poll::Ready(res);
+        }
+    }
+}
 
-                None
+/// parse_into_read_response will parse the response of a ranged read into
+/// metadata and body.
+///
+/// # Notes
+///
+/// Misbehaving servers or proxies could return a range that differs from
+/// the requested one, so we will not trust the body blindly:
+///
+/// - For `206 Partial Content`, the returned `Content-Range` will be checked
+///   against the requested range. Mismatch will return an `Unexpected` error.
+/// - For `200 OK` while the range is not full, server ignored our `Range`
+///   header and returned the whole content. The body will be sliced to the
+///   requested range and metadata will be updated to match.
+pub fn parse_into_read_response(
+    path: &str,
+    range: BytesRange,
+    resp: Response<IncomingAsyncBody>,
+) -> Result<(Metadata, IncomingAsyncBody)> {
+    let status = resp.status();
+    let mut meta = parse_into_metadata(path, resp.headers())?;
+    let content_length = parse_content_length(resp.headers())?;
+    let body = resp.into_body();
+
+    match status {
+        StatusCode::PARTIAL_CONTENT => {
+            // Server doesn't tell us the returned range, we can't do
+            // anything about it.
+            let returned = match meta.content_range() {
+                Some(v) => v,
+                None => return Ok((meta, body)),
+            };
+
+            if !is_content_range_matched(range, returned) {
+                return Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "returned content range mismatches with requested range",
+                )
+                .with_operation("http_util::parse_into_read_response")
+                .with_context("path", path)
+                .with_context("requested", range.to_header())
+                .with_context("returned", returned.to_header()));
             }
-        };
 
-        Poll::Ready(res)
+            Ok((meta, body))
+        }
+        _ if range.is_full() => Ok((meta, body)),
+        _ => {
+            let total = match content_length {
+                Some(v) => v,
+                None => {
+                    return Err(Error::new(
+                        ErrorKind::Unexpected,
+                        "server ignored requested range without returning content length",
+                    )
+                    .with_operation("http_util::parse_into_read_response")
+                    .with_context("path", path)
+                    .with_context("requested", range.to_header()))
+                }
+            };
+
+            let start = match range.offset() {
+                Some(offset) => min(offset, total),
+                None => total.saturating_sub(range.size().unwrap_or_default()),
+            };
+            let end = match range.size() {
+                Some(size) if range.offset().is_some() => min(start + size, total),
+                _ => total,
+            };
+
+            meta.set_content_length(end - start);
+            if end > start {
+                meta.set_content_range(
+                    BytesContentRange::default()
+                        .with_range(start, end - 1)
+                        .with_size(total),
+                );
+            }
+
+            Ok((meta, body.with_window(start..end)))
+        }
+    }
+}
+
+/// Check if returned content range matches the requested range.
+///
+/// Server could return less data than requested if the range exceeds the
+/// end of content, but the start must be the same.
+fn is_content_range_matched(range: BytesRange, returned: BytesContentRange) -> bool {
+    let returned_range = match returned.range() {
+        Some(v) => v,
+        // Content range like `bytes */1024` doesn't carry range.
+        None => return false,
+    };
+
+    match (range.offset(), range.size()) {
+        (Some(offset), Some(size)) => {
+            returned_range.start == offset && returned_range.end <= offset + size
+        }
+        (Some(offset), None) => returned_range.start == offset,
+        (None, Some(size)) => match returned.size() {
+            Some(total) => {
+                returned_range.start == total.saturating_sub(size) && returned_range.end == total
+            }
+            None => returned_range.end - returned_range.start <= size,
+        },
+        (None, None) => returned_range.start == 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use super::*;
+
+    fn new_response(
+        status: StatusCode,
+        content_range: Option<&str>,
+        chunks: Vec<&'static str>,
+    ) -> Response<IncomingAsyncBody> {
+        let size: usize = chunks.iter().map(|v| v.len()).sum();
+        let s = stream::iter(chunks.into_iter().map(|v| Ok(Bytes::from(v))));
+
+        let mut builder = Response::builder()
+            .status(status)
+            .header(http::header::CONTENT_LENGTH, size);
+        if let Some(v) = content_range {
+            builder = builder.header(http::header::CONTENT_RANGE, v);
+        }
+        builder
+            .body(IncomingAsyncBody::new(Box::new(s), Some(size as u64)))
+            .expect("response must be valid")
+    }
+
+    #[tokio::test]
+    async fn test_parse_into_read_response_partial_content() -> Result<()> {
+        let cases = vec![
+            ("exact range", BytesRange::from(2..5), "bytes 2-4/10", true),
+            (
+                "truncated at end",
+                BytesRange::from(8..20),
+                "bytes 8-9/10",
+                true,
+            ),
+            ("open range", BytesRange::from(4..), "bytes 4-9/10", true),
+            (
+                "suffix range",
+                BytesRange::new(None, Some(3)),
+                "bytes 7-9/10",
+                true,
+            ),
+            ("wrong start", BytesRange::from(2..5), "bytes 0-2/10", false),
+            ("too long", BytesRange::from(2..5), "bytes 2-9/10", false),
+            (
+                "wrong suffix",
+                BytesRange::new(None, Some(3)),
+                "bytes 0-2/10",
+                false,
+            ),
+            ("no range", BytesRange::from(2..5), "bytes */10", false),
+        ];
+
+        for (name, range, content_range, ok) in cases {
+            let resp = new_response(
+                StatusCode::PARTIAL_CONTENT,
+                Some(content_range),
+                vec!["abc"],
+            );
+            let res = parse_into_read_response("path", range, resp);
+
+            match res {
+                Ok(_) => assert!(ok, "{name}: expect error but got ok"),
+                Err(err) => {
+                    assert!(!ok, "{name}: expect ok but got {err}");
+                    assert_eq!(err.kind(), ErrorKind::Unexpected, "{name}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_into_read_response_ignored_range() -> Result<()> {
+        let cases = vec![
+            ("range", BytesRange::from(2..7), "23456"),
+            ("open range", BytesRange::from(7..), "789"),
+            ("suffix range", BytesRange::new(None, Some(4)), "6789"),
+            ("exceed end", BytesRange::from(8..20), "89"),
+            ("full", BytesRange::from(..), "0123456789"),
+        ];
+
+        for (name, range, expected) in cases {
+            let resp = new_response(StatusCode::OK, None, vec!["012", "3", "", "456", "789"]);
+            let (meta, body) = parse_into_read_response("path", range, resp)?;
+
+            assert_eq!(
+                meta.content_length(),
+                expected.len() as u64,
+                "{name}: content length"
+            );
+            assert_eq!(body.bytes().await?, expected.as_bytes(), "{name}: content");
+        }
+
+        Ok(())
     }
 }
@@ -17,10 +17,16 @@ use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
 use futures::TryStreamExt;
+use http::HeaderMap;
+use http::HeaderValue;
+use http::Method;
 use http::Request;
 use http::Response;
+use http::Uri;
 use log::debug;
 use reqwest::redirect::Policy;
 use reqwest::ClientBuilder;
@@ -31,27 +37,185 @@ use super::dns::*;
 use super::parse_content_length;
 use super::AsyncBody;
 use super::Body;
+use crate::raw::current_deadline;
+use crate::raw::new_deadline_exceeded_error;
+use crate::raw::VERSION;
 use crate::Error;
 use crate::ErrorKind;
 use crate::Result;
 
-/// HttpClient that used across opendal.
-#[derive(Clone)]
-pub struct HttpClient {
-    async_client: reqwest::Client,
-    sync_client: ureq::Agent,
+/// HttpClientBuilder is used to build [`HttpClient`] with custom options
+/// like connection pool, timeout and http2.
+///
+/// All HTTP based services accept a customized client via their
+/// `http_client` option, so those options can be tuned per operator.
+///
+/// # Notes
+///
+/// Options that are not set will keep the defaults of underlying clients.
+///
+/// The max concurrent streams of a http2 connection is advertised by the
+/// server, please use [`ConcurrentLimitLayer`][crate::layers::ConcurrentLimitLayer]
+/// if we need to limit the concurrent requests on client side.
+#[derive(Default, Debug, Clone)]
+pub struct HttpClientBuilder {
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    http2_prior_knowledge: bool,
+    user_agent: Option<String>,
+    request_signer: Option<RequestSigner>,
 }
 
-/// We don't want users to know details about our clients.
-impl Debug for HttpClient {
+/// RequestSigner is the hook to compute extra headers for every request.
+///
+/// It accepts the method, uri and headers of the request, and returns the
+/// headers to attach.
+#[derive(Clone)]
+struct RequestSigner(Arc<SignFn>);
+
+type SignFn = dyn Fn(&Method, &Uri, &HeaderMap) -> Result<HeaderMap> + Send + Sync;
+
+impl Debug for RequestSigner {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("HttpClient").finish()
+        f.debug_struct("RequestSigner").finish_non_exhaustive()
     }
 }
 
-impl HttpClient {
-    /// Create a new http client.
-    pub fn new() -> Result<Self> {
+impl RequestSigner {
+    /// Attach headers returned by signer to the request.
+    fn sign(&self, parts: &mut http::request::Parts) -> Result<()> {
+        let headers = (self.0)(&parts.method, &parts.uri, &parts.headers)?;
+
+        let mut last_name = None;
+        for (name, value) in headers {
+            // HeaderMap yields `None` for the following values of the same name.
+            let name = match name {
+                Some(name) => {
+                    parts.headers.remove(&name);
+                    last_name = Some(name.clone());
+                    name
+                }
+                None => last_name.clone().expect("first header must have name"),
+            };
+            parts.headers.append(name, value);
+        }
+
+        Ok(())
+    }
+}
+
+impl HttpClientBuilder {
+    /// Set the max idle connections per host in pool.
+    ///
+    /// Default to no limit.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Set the timeout for idle connections in pool.
+    ///
+    /// Default to 90 seconds.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the timeout for connecting to the server.
+    ///
+    /// Default to no timeout.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Only use http2 for async requests without negotiation.
+    ///
+    /// Enable this only if the server is known to support http2. By
+    /// default, http2 will be used only if negotiated via TLS ALPN.
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// Set a custom user agent which will be appended to OpenDAL's default
+    /// user agent `opendal/<version>`.
+    ///
+    /// For example, `myapp/1.2.3` will send `opendal/<version> myapp/1.2.3`
+    /// on every request, so that requests can be identified in server's
+    /// access logs.
+    ///
+    /// The user agent is attached after services' native signing, and
+    /// requests that already carry a user agent will be kept as is.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        if !user_agent.is_empty() {
+            self.user_agent = Some(user_agent.to_string());
+        }
+        self
+    }
+
+    /// Set a request signer to attach extra headers to every request.
+    ///
+    /// The signer will be called with the method, uri and headers of the
+    /// request just before it's sent, which is **after** services' native
+    /// signing. Returned headers will be attached to the request and
+    /// replace existing headers with the same name, so please don't return
+    /// headers that have been signed like `authorization`.
+    ///
+    /// This is useful for gateways that require custom auth headers.
+    /// Errors returned by signer will be returned to users as is.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// use http::HeaderMap;
+    /// use http::HeaderValue;
+    /// use opendal::raw::HttpClient;
+    /// use opendal::services::S3;
+    ///
+    /// # fn main() -> Result<()> {
+    /// let client = HttpClient::builder()
+    ///     .request_signer(|method, uri, _| {
+    ///         let mut headers = HeaderMap::new();
+    ///         let token = format!("{method} {}", uri.path());
+    ///         headers.insert(
+    ///             "x-gateway-token",
+    ///             HeaderValue::from_str(&token).expect("must be valid header value"),
+    ///         );
+    ///         Ok(headers)
+    ///     })
+    ///     .build()?;
+    ///
+    /// let mut builder = S3::default();
+    /// builder.http_client(client);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request_signer(
+        mut self,
+        signer: impl Fn(&Method, &Uri, &HeaderMap) -> Result<HeaderMap> + Send + Sync + 'static,
+    ) -> Self {
+        self.request_signer = Some(RequestSigner(Arc::new(signer)));
+        self
+    }
+
+    /// Build the http client.
+    pub fn build(self) -> Result<HttpClient> {
+        let user_agent = match &self.user_agent {
+            Some(ua) => format!("opendal/{VERSION} {ua}"),
+            None => format!("opendal/{VERSION}"),
+        };
+        HeaderValue::from_str(&user_agent).map_err(|err| {
+            Error::new(
+                ErrorKind::ConfigInvalid,
+                "user agent is not valid header value",
+            )
+            .with_context("user_agent", &user_agent)
+            .set_source(err)
+        })?;
+
         let async_client = {
             let mut builder = ClientBuilder::new();
 
@@ -63,6 +227,21 @@ impl HttpClient {
             builder = builder.no_deflate();
             // Redirect will be handled by ourselves.
             builder = builder.redirect(Policy::none());
+            // Requests with user agent set by services will be kept.
+            builder = builder.user_agent(&user_agent);
+
+            if let Some(max) = self.pool_max_idle_per_host {
+                builder = builder.pool_max_idle_per_host(max);
+            }
+            if let Some(timeout) = self.pool_idle_timeout {
+                builder = builder.pool_idle_timeout(timeout);
+            }
+            if let Some(timeout) = self.connect_timeout {
+                builder = builder.connect_timeout(timeout);
+            }
+            if self.http2_prior_knowledge {
+                builder = builder.http2_prior_knowledge();
+            }
 
             #[cfg(feature = "trust-dns")]
             let builder = builder.dns_resolver(Arc::new(AsyncTrustDnsResolver::new().unwrap()));
@@ -87,7 +266,16 @@ impl HttpClient {
                 }
             }
 
-            let builder = builder.resolver(StdDnsResolver::default());
+            if let Some(max) = self.pool_max_idle_per_host {
+                builder = builder.max_idle_connections_per_host(max);
+            }
+            if let Some(timeout) = self.connect_timeout {
+                builder = builder.timeout_connect(timeout);
+            }
+
+            let builder = builder
+                .user_agent(&user_agent)
+                .resolver(StdDnsResolver::default());
 
             builder.build()
         };
@@ -95,8 +283,57 @@ impl HttpClient {
         Ok(HttpClient {
             async_client,
             sync_client,
+            request_signer: self.request_signer,
         })
     }
+}
+
+/// HttpClient that used across opendal.
+#[derive(Clone)]
+pub struct HttpClient {
+    async_client: reqwest::Client,
+    sync_client: ureq::Agent,
+    request_signer: Option<RequestSigner>,
+}
+
+/// We don't want users to know details about our clients.
+impl Debug for HttpClient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpClient").finish()
+    }
+}
+
+impl HttpClient {
+    /// Create a new http client with default options.
+    pub fn new() -> Result<Self> {
+        HttpClientBuilder::default().build()
+    }
+
+    /// Create a builder to build http client with custom options.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use anyhow::Result;
+    /// use opendal::raw::HttpClient;
+    /// use opendal::services::S3;
+    ///
+    /// # fn main() -> Result<()> {
+    /// let client = HttpClient::builder()
+    ///     .pool_max_idle_per_host(64)
+    ///     .pool_idle_timeout(Duration::from_secs(30))
+    ///     .connect_timeout(Duration::from_secs(5))
+    ///     .build()?;
+    ///
+    /// let mut builder = S3::default();
+    /// builder.http_client(client);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder() -> HttpClientBuilder {
+        HttpClientBuilder::default()
+    }
 
     /// Build a new http client from already built clients.
     ///
@@ -134,6 +371,7 @@ impl HttpClient {
         Self {
             async_client,
             sync_client,
+            request_signer: None,
         }
     }
 
@@ -149,7 +387,15 @@ impl HttpClient {
 
     /// Send a request in blocking way.
     pub fn send(&self, req: Request<Body>) -> Result<Response<Body>> {
-        let (parts, body) = req.into_parts();
+        let (mut parts, body) = req.into_parts();
+
+        if let Some(signer) = &self.request_signer {
+            signer
+                .sign(&mut parts)
+                .map_err(|err| err.with_operation("http_util::Client::send"))?;
+        }
+
+        let deadline = current_deadline();
 
         let mut ur = self
             .sync_client
@@ -157,12 +403,21 @@ impl HttpClient {
         for (k, v) in parts.headers.iter() {
             ur = ur.set(k.as_str(), v.to_str().expect("must be valid header"));
         }
+        if let Some(timeout) = remaining_timeout(deadline, "http_util::Client::send")? {
+            ur = ur.timeout(timeout);
+        }
 
         let resp = match ur.send(body) {
             Ok(resp) => resp,
             Err(err_resp) => match err_resp {
                 ureq::Error::Status(_code, resp) => resp,
                 ureq::Error::Transport(transport) => {
+                    if is_deadline_exceeded(deadline) {
+                        return Err(new_deadline_exceeded_error()
+                            .with_operation("http_util::Client::send")
+                            .set_source(transport));
+                    }
+
                     let is_temporary = matches!(
                         transport.kind(),
                         ureq::ErrorKind::Dns
@@ -198,8 +453,16 @@ impl HttpClient {
 
     /// Send a request in async way.
     pub async fn send_async(&self, req: Request<AsyncBody>) -> Result<Response<IncomingAsyncBody>> {
-        let is_head = req.method() == http::Method::HEAD;
-        let (parts, body) = req.into_parts();
+        let is_head = req.method() == Method::HEAD;
+        let (mut parts, body) = req.into_parts();
+
+        if let Some(signer) = &self.request_signer {
+            signer
+                .sign(&mut parts)
+                .map_err(|err| err.with_operation("http_util::Client::send_async"))?;
+        }
+
+        let deadline = current_deadline();
 
         let mut req_builder = self
             .async_client
@@ -209,6 +472,9 @@ impl HttpClient {
             )
             .version(parts.version)
             .headers(parts.headers);
+        if let Some(timeout) = remaining_timeout(deadline, "http_util::Client::send_async")? {
+            req_builder = req_builder.timeout(timeout);
+        }
 
         req_builder = if let AsyncBody::Multipart(field, r) = body {
             let mut form = reqwest::multipart::Form::new();
@@ -221,6 +487,12 @@ impl HttpClient {
         };
 
         let resp = req_builder.send().await.map_err(|err| {
+            if err.is_timeout() && is_deadline_exceeded(deadline) {
+                return new_deadline_exceeded_error()
+                    .with_operation("http_util::Client::send_async")
+                    .set_source(err);
+            }
+
             let is_temporary = !(
                 // Builder related error should not be retried.
                 err.is_builder() ||
@@ -259,7 +531,11 @@ impl HttpClient {
             hr = hr.header(k, v);
         }
 
-        let stream = resp.bytes_stream().map_err(|err| {
+        let stream = resp.bytes_stream().map_err(move |err| {
+            if err.is_timeout() && is_deadline_exceeded(deadline) {
+                return new_deadline_exceeded_error().set_source(err);
+            }
+
             // If stream returns a body related error, we can convert
             // it to interrupt so we can retry it.
             Error::new(ErrorKind::Unexpected, "read data from http stream")
@@ -274,3 +550,21 @@ impl HttpClient {
         Ok(resp)
     }
 }
+
+/// Convert the deadline of current operation into the timeout of request.
+fn remaining_timeout(deadline: Option<Instant>, op: &'static str) -> Result<Option<Duration>> {
+    let deadline = match deadline {
+        Some(deadline) => deadline,
+        None => return Ok(None),
+    };
+
+    match deadline.checked_duration_since(Instant::now()) {
+        Some(timeout) if !timeout.is_zero() => Ok(Some(timeout)),
+        _ => Err(new_deadline_exceeded_error().with_operation(op)),
+    }
+}
+
+/// Timeouts caused by deadline should not be retried.
+fn is_deadline_exceeded(deadline: Option<Instant>) -> bool {
+    matches!(deadline, Some(deadline) if Instant::now() >= deadline)
+}
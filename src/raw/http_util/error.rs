@@ -97,3 +97,120 @@ pub fn new_request_sign_error(err: anyhow::Error) -> Error {
         .with_operation("reqsign::Sign")
         .set_source(err)
 }
+
+/// Parse the status code of error response into error kind and whether
+/// it's retryable.
+///
+/// This is the shared mapping table for all http based services. Services
+/// should apply their own overrides for the error codes carried in the
+/// response (for example, s3's `InvalidAccessKeyId`) on top of it.
+///
+/// - `401 Unauthorized` and `403 Forbidden` will be mapped to
+///   `PermissionDenied` and never retried.
+/// - `408`, `500`, `502`, `503` and `504` will be retried.
+pub fn parse_error_status(status: StatusCode) -> (ErrorKind, bool) {
+    match status {
+        StatusCode::NOT_FOUND => (ErrorKind::NotFound, false),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => (ErrorKind::PermissionDenied, false),
+        StatusCode::PRECONDITION_FAILED => (ErrorKind::ConditionNotMatch, false),
+        StatusCode::TOO_MANY_REQUESTS => (ErrorKind::RateLimited, true),
+        StatusCode::REQUEST_TIMEOUT
+        | StatusCode::INTERNAL_SERVER_ERROR
+        | StatusCode::BAD_GATEWAY
+        | StatusCode::SERVICE_UNAVAILABLE
+        | StatusCode::GATEWAY_TIMEOUT => (ErrorKind::Unexpected, true),
+        _ => (ErrorKind::Unexpected, false),
+    }
+}
+
+/// Headers that services used to carry request id, checked in order.
+const REQUEST_ID_HEADERS: &[&str] = &[
+    "x-amz-request-id",
+    "x-ms-request-id",
+    "x-oss-request-id",
+    "x-obs-request-id",
+    "x-goog-request-id",
+    "x-guploader-uploadid",
+    "x-request-id",
+];
+
+/// Attach the context of an error response to given error.
+///
+/// Following context will be added, services should use this while
+/// building errors from responses to keep them consistent:
+///
+/// - `status`: the http status code, see [`Error::status_code`]
+/// - `request_id`: the request id returned by service if exists, see
+///   [`Error::request_id`]
+/// - `response`: the debug output of response parts
+pub fn with_error_response_context(err: Error, parts: &Parts) -> Error {
+    let mut err = err.with_context("status", parts.status.as_u16().to_string());
+
+    if let Some(v) = REQUEST_ID_HEADERS
+        .iter()
+        .find_map(|k| parts.headers.get(*k).and_then(|v| v.to_str().ok()))
+    {
+        err = err.with_context("request_id", v);
+    }
+
+    err.with_context("response", format!("{parts:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_error_status() {
+        let cases = vec![
+            (StatusCode::NOT_FOUND, ErrorKind::NotFound, false),
+            (StatusCode::UNAUTHORIZED, ErrorKind::PermissionDenied, false),
+            (StatusCode::FORBIDDEN, ErrorKind::PermissionDenied, false),
+            (
+                StatusCode::PRECONDITION_FAILED,
+                ErrorKind::ConditionNotMatch,
+                false,
+            ),
+            (StatusCode::TOO_MANY_REQUESTS, ErrorKind::RateLimited, true),
+            (StatusCode::REQUEST_TIMEOUT, ErrorKind::Unexpected, true),
+            (StatusCode::SERVICE_UNAVAILABLE, ErrorKind::Unexpected, true),
+            (StatusCode::BAD_REQUEST, ErrorKind::Unexpected, false),
+        ];
+
+        for (status, kind, retryable) in cases {
+            assert_eq!(
+                parse_error_status(status),
+                (kind, retryable),
+                "status: {status}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_with_error_response_context() {
+        let (parts, _) = Response::builder()
+            .status(StatusCode::FORBIDDEN)
+            .header("x-ms-request-id", "ms-request")
+            .header("x-request-id", "generic-request")
+            .body(())
+            .unwrap()
+            .into_parts();
+
+        let err = with_error_response_context(
+            Error::new(ErrorKind::PermissionDenied, "access denied"),
+            &parts,
+        );
+        assert_eq!(err.status_code(), Some(403));
+        assert_eq!(err.request_id(), Some("ms-request"));
+
+        let (parts, _) = Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(())
+            .unwrap()
+            .into_parts();
+
+        let err = with_error_response_context(Error::new(ErrorKind::NotFound, "not found"), &parts);
+        assert_eq!(err.status_code(), Some(404));
+        assert_eq!(err.request_id(), None);
+    }
+}
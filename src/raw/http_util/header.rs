@@ -12,16 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
 use base64::engine::general_purpose;
 use base64::Engine;
 use http::header::HeaderName;
+use http::header::CACHE_CONTROL;
 use http::header::CONTENT_DISPOSITION;
+use http::header::CONTENT_ENCODING;
 use http::header::CONTENT_LENGTH;
 use http::header::CONTENT_RANGE;
 use http::header::CONTENT_TYPE;
 use http::header::ETAG;
+use http::header::EXPIRES;
 use http::header::LAST_MODIFIED;
 use http::header::LOCATION;
+use http::header::RETRY_AFTER;
 use http::HeaderMap;
 use md5::Digest;
 use time::format_description::well_known::Rfc2822;
@@ -182,6 +188,91 @@ pub fn parse_content_disposition(headers: &HeaderMap) -> Result<Option<&str>> {
     }
 }
 
+/// Parse content encoding from header map.
+pub fn parse_content_encoding(headers: &HeaderMap) -> Result<Option<&str>> {
+    match headers.get(CONTENT_ENCODING) {
+        None => Ok(None),
+        Some(v) => Ok(Some(v.to_str().map_err(|e| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value has to be valid utf-8 string",
+            )
+            .with_operation("http_util::parse_content_encoding")
+            .set_source(e)
+        })?)),
+    }
+}
+
+/// Parse cache control from header map.
+pub fn parse_cache_control(headers: &HeaderMap) -> Result<Option<&str>> {
+    match headers.get(CACHE_CONTROL) {
+        None => Ok(None),
+        Some(v) => Ok(Some(v.to_str().map_err(|e| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value has to be valid utf-8 string",
+            )
+            .with_operation("http_util::parse_cache_control")
+            .set_source(e)
+        })?)),
+    }
+}
+
+/// Parse expires from header map.
+///
+/// Invalid values like `0` mean the response is already expired, they
+/// will be parsed as [`OffsetDateTime::UNIX_EPOCH`] instead of an error.
+pub fn parse_expires(headers: &HeaderMap) -> Result<Option<OffsetDateTime>> {
+    match headers.get(EXPIRES) {
+        None => Ok(None),
+        Some(v) => {
+            let t = v
+                .to_str()
+                .ok()
+                .and_then(|v| OffsetDateTime::parse(v, &Rfc2822).ok())
+                .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+
+            Ok(Some(t))
+        }
+    }
+}
+
+/// Parse retry after from header map.
+///
+/// `Retry-After` could be either the seconds to wait or a http date like
+/// `Wed, 21 Oct 2015 07:28:00 GMT`. Dates in the past will be treated as
+/// zero duration.
+pub fn parse_retry_after(headers: &HeaderMap) -> Result<Option<Duration>> {
+    let v = match headers.get(RETRY_AFTER) {
+        None => return Ok(None),
+        Some(v) => v.to_str().map_err(|e| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value is not valid utf-8 string",
+            )
+            .with_operation("http_util::parse_retry_after")
+            .set_source(e)
+        })?,
+    };
+
+    if let Ok(secs) = v.trim().parse::<u64>() {
+        return Ok(Some(Duration::from_secs(secs)));
+    }
+
+    let t = OffsetDateTime::parse(v, &Rfc2822).map_err(|e| {
+        Error::new(
+            ErrorKind::Unexpected,
+            "header value is neither seconds nor valid rfc2822 time",
+        )
+        .with_operation("http_util::parse_retry_after")
+        .with_context("value", v)
+        .set_source(e)
+    })?;
+    let dur = t - OffsetDateTime::now_utc();
+
+    Ok(Some(dur.try_into().unwrap_or(Duration::ZERO)))
+}
+
 /// parse_into_metadata will parse standards http headers into Metadata.
 ///
 /// # Notes
@@ -225,9 +316,48 @@ pub fn parse_into_metadata(path: &str, headers: &HeaderMap) -> Result<Metadata>
         m.set_content_disposition(v);
     }
 
+    if let Some(v) = parse_content_encoding(headers)? {
+        m.set_content_encoding(v);
+    }
+
+    if let Some(v) = parse_cache_control(headers)? {
+        m.set_cache_control(v);
+    }
+
+    if let Some(v) = parse_expires(headers)? {
+        m.set_expires(v);
+    }
+
     Ok(m)
 }
 
+/// parse_into_write_reply will parse the etag and version id of the written
+/// object into RpWrite.
+///
+/// Services use different headers for version id, like `x-amz-version-id`
+/// for s3, so it should be passed by caller.
+pub fn parse_into_write_reply(headers: &HeaderMap, version_header: &str) -> Result<RpWrite> {
+    let mut rp = RpWrite::new();
+
+    if let Some(v) = parse_etag(headers)? {
+        rp = rp.with_etag(v);
+    }
+
+    if let Some(v) = headers.get(version_header) {
+        let v = v.to_str().map_err(|e| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value is not valid utf-8 string",
+            )
+            .with_operation("http_util::parse_into_write_reply")
+            .set_source(e)
+        })?;
+        rp = rp.with_version(v);
+    }
+
+    Ok(rp)
+}
+
 /// format content md5 header by given input.
 pub fn format_content_md5(bs: &[u8]) -> String {
     let mut hasher = md5::Md5::new();
@@ -272,8 +402,30 @@ pub fn format_authorization_by_bearer(token: &str) -> Result<String> {
 
 #[cfg(test)]
 mod tests {
+    use http::HeaderValue;
+
     use super::*;
 
+    #[test]
+    fn test_parse_expires() {
+        let cases = vec![
+            (
+                "Wed, 21 Oct 2015 07:28:00 GMT",
+                OffsetDateTime::from_unix_timestamp(1445412480).unwrap(),
+            ),
+            ("0", OffsetDateTime::UNIX_EPOCH),
+            ("invalid", OffsetDateTime::UNIX_EPOCH),
+        ];
+
+        for (input, expected) in cases {
+            let mut headers = HeaderMap::new();
+            headers.insert(EXPIRES, HeaderValue::from_static(input));
+
+            assert_eq!(parse_expires(&headers).unwrap(), Some(expected), "{input}");
+        }
+        assert_eq!(parse_expires(&HeaderMap::new()).unwrap(), None);
+    }
+
     /// Test cases is from https://docs.aws.amazon.com/AmazonS3/latest/API/API_DeleteObjects.html
     #[test]
     fn test_format_content_md5() {
@@ -334,4 +486,37 @@ mod tests {
             assert_eq!(actual, expected)
         }
     }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let cases = vec![
+            (None, Some(None)),
+            (Some("120"), Some(Some(Duration::from_secs(120)))),
+            (Some(" 0 "), Some(Some(Duration::ZERO))),
+            // Dates in the past are treated as zero duration.
+            (
+                Some("Wed, 21 Oct 2015 07:28:00 GMT"),
+                Some(Some(Duration::ZERO)),
+            ),
+            (Some("-1"), None),
+            (Some("soon"), None),
+        ];
+
+        for (input, expected) in cases {
+            let mut headers = HeaderMap::new();
+            if let Some(v) = input {
+                headers.insert(RETRY_AFTER, v.parse().unwrap());
+            }
+
+            let actual = parse_retry_after(&headers).ok();
+            assert_eq!(actual, expected, "input: {input:?}")
+        }
+
+        // Dates in the future are converted into the duration to wait.
+        let t = OffsetDateTime::now_utc() + time::Duration::hours(1);
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, t.format(&Rfc2822).unwrap().parse().unwrap());
+        let dur = parse_retry_after(&headers).unwrap().unwrap();
+        assert!(dur > Duration::from_secs(3500) && dur <= Duration::from_secs(3600));
+    }
 }
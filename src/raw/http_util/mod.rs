@@ -21,8 +21,10 @@
 
 mod client;
 pub use client::HttpClient;
+pub use client::HttpClientBuilder;
 
 mod body;
+pub use body::parse_into_read_response;
 pub use body::AsyncBody;
 pub use body::Body;
 pub use body::IncomingAsyncBody;
@@ -31,25 +33,36 @@ mod header;
 pub use header::format_authorization_by_basic;
 pub use header::format_authorization_by_bearer;
 pub use header::format_content_md5;
+pub use header::parse_cache_control;
 pub use header::parse_content_disposition;
+pub use header::parse_content_encoding;
 pub use header::parse_content_length;
 pub use header::parse_content_md5;
 pub use header::parse_content_range;
 pub use header::parse_content_type;
 pub use header::parse_etag;
+pub use header::parse_expires;
 pub use header::parse_into_metadata;
+pub use header::parse_into_write_reply;
 pub use header::parse_last_modified;
 pub use header::parse_location;
+pub use header::parse_retry_after;
 
 mod uri;
+pub use uri::parse_endpoint;
 pub use uri::percent_encode_path;
 
 mod error;
 pub use error::new_request_build_error;
 pub use error::new_request_sign_error;
 pub use error::parse_error_response;
+pub use error::parse_error_status;
+pub use error::with_error_response_context;
 pub use error::ErrorResponse;
 
+mod oauth;
+pub use oauth::OAuth2TokenLoader;
+
 mod bytes_range;
 pub use bytes_range::BytesRange;
 
@@ -0,0 +1,365 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::lock::Mutex;
+use http::header;
+use http::Request;
+use http::Response;
+use http::StatusCode;
+use log::debug;
+use serde::Deserialize;
+use time::Duration;
+use time::OffsetDateTime;
+
+use super::format_authorization_by_bearer;
+use super::new_request_build_error;
+use super::parse_error_status;
+use super::parse_retry_after;
+use super::percent_encode_path;
+use super::with_error_response_context;
+use super::AsyncBody;
+use super::HttpClient;
+use super::IncomingAsyncBody;
+use crate::raw::new_json_deserialize_error;
+use crate::Error;
+use crate::ErrorKind;
+use crate::Result;
+
+/// Refresh the access token if it will be expired in this duration.
+const TOKEN_REFRESH_AHEAD: Duration = Duration::seconds(120);
+
+/// OAuth2TokenLoader loads the OAuth2 access token for services like
+/// `dropbox`, `gdrive` and `onedrive`.
+///
+/// - Access token given by users will be used as is and never expired.
+/// - Access token loaded via refresh token will be refreshed before it's
+///   expired. Refresh tokens rotated by the token endpoint are kept.
+///
+/// Only one refresh will be in flight at the same time: callers that meet
+/// an expiring token will wait for the refresh in flight and reuse its
+/// result instead of sending their own requests.
+#[derive(Clone)]
+pub struct OAuth2TokenLoader {
+    client: HttpClient,
+    refresher: Option<Arc<OAuth2Refresher>>,
+    token: Arc<Mutex<OAuth2Token>>,
+}
+
+impl Debug for OAuth2TokenLoader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuth2TokenLoader")
+            .field("refresher", &self.refresher)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Token endpoint and client credentials used to refresh access tokens.
+struct OAuth2Refresher {
+    token_endpoint: String,
+    client_id: String,
+    client_secret: Option<String>,
+}
+
+impl Debug for OAuth2Refresher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuth2Refresher")
+            .field("token_endpoint", &self.token_endpoint)
+            .field("client_id", &self.client_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The OAuth2 tokens and the expire time of access token.
+#[derive(Default)]
+struct OAuth2Token {
+    access_token: String,
+    refresh_token: Option<String>,
+    /// `None` means the token will never be expired.
+    expires_at: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize)]
+struct OAuth2TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: i64,
+}
+
+impl OAuth2TokenLoader {
+    /// Create a new loader that uses given access token as is.
+    pub fn new(client: HttpClient, access_token: &str) -> Self {
+        Self {
+            client,
+            refresher: None,
+            token: Arc::new(Mutex::new(OAuth2Token {
+                access_token: access_token.to_string(),
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Create a new loader that loads access tokens from `token_endpoint`
+    /// via the refresh token.
+    ///
+    /// `client_secret` is not required by public clients.
+    pub fn with_refresh_token(
+        client: HttpClient,
+        token_endpoint: &str,
+        refresh_token: &str,
+        client_id: &str,
+        client_secret: Option<&str>,
+    ) -> Self {
+        Self {
+            client,
+            refresher: Some(Arc::new(OAuth2Refresher {
+                token_endpoint: token_endpoint.to_string(),
+                client_id: client_id.to_string(),
+                client_secret: client_secret.map(|v| v.to_string()),
+            })),
+            token: Arc::new(Mutex::new(OAuth2Token {
+                access_token: String::new(),
+                refresh_token: Some(refresh_token.to_string()),
+                // Make sure the first call will load the access token.
+                expires_at: Some(OffsetDateTime::UNIX_EPOCH),
+            })),
+        }
+    }
+
+    /// Get the authorization header, the access token will be refreshed
+    /// if it's going to be expired.
+    pub async fn authorization(&self) -> Result<String> {
+        // The lock is held during refreshing, so that other callers will
+        // wait for the refresh in flight instead of sending their own.
+        let mut token = self.token.lock().await;
+
+        let expired = match token.expires_at {
+            None => false,
+            Some(at) => at <= OffsetDateTime::now_utc() + TOKEN_REFRESH_AHEAD,
+        };
+        if !expired {
+            return format_authorization_by_bearer(&token.access_token);
+        }
+
+        let (refresher, refresh_token) = match (&self.refresher, &token.refresh_token) {
+            (Some(refresher), Some(refresh_token)) => (refresher, refresh_token),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::PermissionDenied,
+                    "access token has been expired",
+                ))
+            }
+        };
+
+        *token = self.refresh(refresher, refresh_token).await?;
+        format_authorization_by_bearer(&token.access_token)
+    }
+
+    async fn refresh(
+        &self,
+        refresher: &OAuth2Refresher,
+        refresh_token: &str,
+    ) -> Result<OAuth2Token> {
+        debug!(
+            "refreshing oauth2 access token from {}",
+            refresher.token_endpoint
+        );
+
+        let mut body = format!(
+            "grant_type=refresh_token&refresh_token={}&client_id={}",
+            percent_encode_path(refresh_token),
+            percent_encode_path(&refresher.client_id),
+        );
+        if let Some(secret) = &refresher.client_secret {
+            body.push_str("&client_secret=");
+            body.push_str(&percent_encode_path(secret));
+        }
+
+        let req = Request::post(&refresher.token_endpoint)
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header(header::CONTENT_LENGTH, body.len())
+            .body(AsyncBody::Bytes(Bytes::from(body)))
+            .map_err(new_request_build_error)?;
+
+        let resp = self.client.send_async(req).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                let token: OAuth2TokenResponse =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+                Ok(OAuth2Token {
+                    access_token: token.access_token,
+                    refresh_token: Some(
+                        token
+                            .refresh_token
+                            .unwrap_or_else(|| refresh_token.to_string()),
+                    ),
+                    expires_at: Some(
+                        OffsetDateTime::now_utc() + Duration::seconds(token.expires_in),
+                    ),
+                })
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+}
+
+/// Parse error response of the token endpoint into Error.
+async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
+    let (parts, body) = resp.into_parts();
+    let bs = body.bytes().await?;
+
+    let (kind, retryable) = parse_error_status(parts.status);
+    let message = String::from_utf8_lossy(&bs);
+
+    let mut err = with_error_response_context(Error::new(kind, &message), &parts)
+        .with_operation("OAuth2TokenLoader::refresh");
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
+
+    if retryable {
+        err = err.set_temporary();
+    }
+
+    Ok(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future::join_all;
+    use serde_json::json;
+    use wiremock::matchers::body_string_contains;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_access_token() {
+        let loader = OAuth2TokenLoader::new(HttpClient::new().unwrap(), "token");
+
+        assert_eq!(loader.authorization().await.unwrap(), "Bearer token");
+    }
+
+    #[tokio::test]
+    async fn test_single_refresh_in_flight() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("refresh_token=refresh_token"))
+            .and(body_string_contains("client_secret=secret"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({
+                        "access_token": "access_token",
+                        "expires_in": 3600,
+                    }))
+                    .set_delay(std::time::Duration::from_millis(100)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let loader = OAuth2TokenLoader::with_refresh_token(
+            HttpClient::new().unwrap(),
+            &format!("{}/token", server.uri()),
+            "refresh_token",
+            "client_id",
+            Some("secret"),
+        );
+
+        let results = join_all((0..8).map(|_| loader.authorization())).await;
+        for res in results {
+            assert_eq!(res.unwrap(), "Bearer access_token");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rotated_token() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("refresh_token=old"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "access_token": "first",
+                "refresh_token": "new",
+                // Expires immediately so that the next call will refresh.
+                "expires_in": 0,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("refresh_token=new"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "access_token": "second",
+                "expires_in": 3600,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let loader = OAuth2TokenLoader::with_refresh_token(
+            HttpClient::new().unwrap(),
+            &format!("{}/token", server.uri()),
+            "old",
+            "client_id",
+            None,
+        );
+
+        assert_eq!(loader.authorization().await.unwrap(), "Bearer first");
+        assert_eq!(loader.authorization().await.unwrap(), "Bearer second");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_failed() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "error": "invalid_client",
+            })))
+            .mount(&server)
+            .await;
+
+        let loader = OAuth2TokenLoader::with_refresh_token(
+            HttpClient::new().unwrap(),
+            &format!("{}/token", server.uri()),
+            "refresh_token",
+            "client_id",
+            None,
+        );
+
+        let err = loader.authorization().await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+}
@@ -16,6 +16,8 @@ use percent_encoding::utf8_percent_encode;
 use percent_encoding::AsciiSet;
 use percent_encoding::NON_ALPHANUMERIC;
 
+use crate::*;
+
 /// PATH_ENCODE_SET is the encode set for http url path.
 ///
 /// This set follows [encodeURIComponent](https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Global_Objects/encodeURIComponent) which will encode all non-ASCII characters except `A-Z a-z 0-9 - _ . ! ~ * ' ( )`
@@ -44,6 +46,39 @@ pub fn percent_encode_path(path: &str) -> String {
     utf8_percent_encode(path, &PATH_ENCODE_SET).to_string()
 }
 
+/// parse_endpoint will parse and validate the endpoint of http based services.
+///
+/// Endpoint must be an absolute url with `http` or `https` scheme and a
+/// non-empty host, for example `https://s3.amazonaws.com`. Returns
+/// [`ErrorKind::ConfigInvalid`] so that typos in config could be found while
+/// building the operator instead of failing on the first request.
+pub fn parse_endpoint(endpoint: &str) -> Result<http::Uri> {
+    let uri = endpoint.parse::<http::Uri>().map_err(|e| {
+        Error::new(ErrorKind::ConfigInvalid, "endpoint is invalid")
+            .with_context("endpoint", endpoint)
+            .set_source(e)
+    })?;
+
+    match uri.scheme_str() {
+        Some("http") | Some("https") => {}
+        _ => {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "endpoint must start with http:// or https://",
+            )
+            .with_context("endpoint", endpoint))
+        }
+    }
+
+    match uri.host() {
+        Some(host) if !host.is_empty() => Ok(uri),
+        _ => Err(
+            Error::new(ErrorKind::ConfigInvalid, "endpoint doesn't have host")
+                .with_context("endpoint", endpoint),
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,4 +111,27 @@ mod tests {
             assert_eq!(actual, expected, "{name}");
         }
     }
+
+    #[test]
+    fn test_parse_endpoint() {
+        let cases = vec![
+            ("http", "http://127.0.0.1:9000", true),
+            ("https", "https://s3.amazonaws.com", true),
+            ("with path", "https://example.com/prefix", true),
+            ("no scheme", "s3.amazonaws.com", false),
+            ("invalid scheme", "htps://s3.amazonaws.com", false),
+            ("ftp scheme", "ftp://example.com", false),
+            ("space", "https://exa mple.com", false),
+            ("empty", "", false),
+        ];
+
+        for (name, input, valid) in cases {
+            let actual = parse_endpoint(input);
+
+            assert_eq!(actual.is_ok(), valid, "{name}");
+            if let Err(err) = actual {
+                assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "{name}");
+            }
+        }
+    }
 }
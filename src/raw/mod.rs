@@ -40,6 +40,7 @@ mod path;
 pub use path::build_abs_path;
 pub use path::build_rel_path;
 pub use path::build_rooted_abs_path;
+pub use path::check_path;
 pub use path::get_basename;
 pub use path::get_parent;
 pub use path::normalize_path;
@@ -52,6 +53,13 @@ pub use operation::Operation;
 mod version;
 pub use version::VERSION;
 
+mod deadline;
+pub use deadline::check_deadline;
+pub use deadline::current_deadline;
+pub use deadline::new_deadline_exceeded_error;
+pub use deadline::scope_deadline;
+pub use deadline::DeadlineFuture;
+
 mod rps;
 pub use rps::*;
 
@@ -63,3 +71,6 @@ pub use serde_util::*;
 
 // Expose as a pub mod to avoid confusing.
 pub mod adapters;
+
+#[cfg(test)]
+pub(crate) mod tests;
@@ -72,6 +72,11 @@ impl Entry {
         self.meta.mode()
     }
 
+    /// Get the metadata of entry.
+    pub fn metadata(&self) -> &Metadata {
+        &self.meta
+    }
+
     /// Consume self to convert into an Entry.
     ///
     /// NOTE: implement this by hand to avoid leaking raw entry to end-users.
@@ -0,0 +1,209 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// filter_modified_pager is used to make a pager only yield entries whose
+/// last modified is in the time window of given [`OpList`].
+///
+/// Entries are filtered page by page while the inner pager streams, so
+/// we don't need to collect all entries in memory.
+pub fn filter_modified_pager<A: Accessor, P>(
+    acc: A,
+    pager: P,
+    args: OpList,
+) -> FilterModifiedPager<A, P> {
+    FilterModifiedPager { acc, pager, args }
+}
+
+/// FilterModifiedPager will filter entries by their last modified.
+///
+/// # Note
+///
+/// Some services don't return last modified while listing. For such
+/// entries, FilterModifiedPager will:
+///
+/// - Send `stat` for files if [`OpList::stat_unknown_modified`] is enabled.
+/// - Keep or drop them according to [`OpList::include_unknown_modified`]
+///   if the last modified is still unknown.
+///
+/// Dirs will never be stated.
+pub struct FilterModifiedPager<A: Accessor, P> {
+    acc: A,
+    pager: P,
+    args: OpList,
+}
+
+impl<A: Accessor, P> FilterModifiedPager<A, P> {
+    /// Returns the known last modified of this entry.
+    fn last_modified(meta: &Metadata) -> Option<OffsetDateTime> {
+        let bit = meta.bit();
+        if bit.contains(Metakey::LastModified) || bit.contains(Metakey::Complete) {
+            meta.last_modified()
+        } else {
+            None
+        }
+    }
+
+    fn need_stat(&self, oe: &oio::Entry) -> bool {
+        self.args.stat_unknown_modified()
+            && !oe.mode().is_dir()
+            && Self::last_modified(oe.metadata()).is_none()
+    }
+}
+
+#[async_trait]
+impl<A, P> oio::Page for FilterModifiedPager<A, P>
+where
+    A: Accessor,
+    P: oio::Page,
+{
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        loop {
+            let entries = match self.pager.next().await? {
+                Some(entries) => entries,
+                None => return Ok(None),
+            };
+
+            let mut res = Vec::with_capacity(entries.len());
+            for oe in entries {
+                let oe = if self.need_stat(&oe) {
+                    let rp = self.acc.stat(oe.path(), OpStat::new()).await?;
+                    oio::Entry::with(oe.path().to_string(), rp.into_metadata())
+                } else {
+                    oe
+                };
+
+                if self.args.is_modified_in(Self::last_modified(oe.metadata())) {
+                    res.push(oe)
+                }
+            }
+
+            // Don't return empty pages to users.
+            if !res.is_empty() {
+                return Ok(Some(res));
+            }
+        }
+    }
+}
+
+impl<A, P> oio::BlockingPage for FilterModifiedPager<A, P>
+where
+    A: Accessor,
+    P: oio::BlockingPage,
+{
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        loop {
+            let entries = match self.pager.next()? {
+                Some(entries) => entries,
+                None => return Ok(None),
+            };
+
+            let mut res = Vec::with_capacity(entries.len());
+            for oe in entries {
+                let oe = if self.need_stat(&oe) {
+                    let rp = self.acc.blocking_stat(oe.path(), OpStat::new())?;
+                    oio::Entry::with(oe.path().to_string(), rp.into_metadata())
+                } else {
+                    oe
+                };
+
+                if self.args.is_modified_in(Self::last_modified(oe.metadata())) {
+                    res.push(oe)
+                }
+            }
+
+            // Don't return empty pages to users.
+            if !res.is_empty() {
+                return Ok(Some(res));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oio::BlockingPage;
+    use time::Duration;
+
+    use super::*;
+
+    struct MockPager {
+        pages: Vec<Vec<oio::Entry>>,
+    }
+
+    impl BlockingPage for MockPager {
+        fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+            if self.pages.is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(self.pages.remove(0)))
+        }
+    }
+
+    fn list(pager: MockPager, args: OpList) -> Result<Vec<String>> {
+        let mut pager = filter_modified_pager((), pager, args);
+
+        let mut paths = vec![];
+        while let Some(entries) = pager.next()? {
+            assert!(!entries.is_empty(), "empty page must not be returned");
+            paths.extend(entries.iter().map(|v| v.path().to_string()));
+        }
+        Ok(paths)
+    }
+
+    #[test]
+    fn test_filter_modified() -> Result<()> {
+        let _ = env_logger::try_init();
+
+        let now = OffsetDateTime::now_utc();
+        let entry = |path: &str, last_modified: Option<OffsetDateTime>| {
+            let mut meta = Metadata::new(EntryMode::FILE);
+            if let Some(t) = last_modified {
+                meta.set_last_modified(t);
+            }
+            oio::Entry::new(path, meta)
+        };
+        let pager = || MockPager {
+            pages: vec![
+                vec![
+                    entry("old", Some(now - Duration::days(2))),
+                    entry("unknown", None),
+                ],
+                vec![entry("older", Some(now - Duration::days(3)))],
+                vec![entry("new", Some(now))],
+            ],
+        };
+
+        let args = OpList::new().with_modified_after(now - Duration::days(1));
+        assert_eq!(list(pager(), args.clone())?, vec!["unknown", "new"]);
+        assert_eq!(
+            list(pager(), args.with_include_unknown_modified(false))?,
+            vec!["new"]
+        );
+
+        let args = OpList::new()
+            .with_modified_after(now - Duration::days(3))
+            .with_modified_before(now)
+            .with_include_unknown_modified(false);
+        assert_eq!(list(pager(), args)?, vec!["old"]);
+
+        Ok(())
+    }
+}
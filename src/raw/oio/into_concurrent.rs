@@ -0,0 +1,419 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::min;
+use std::collections::VecDeque;
+use std::io::SeekFrom;
+use std::task::Context;
+use std::task::Poll;
+
+use bytes::Buf;
+use bytes::Bytes;
+use bytes::BytesMut;
+use futures::future::BoxFuture;
+use futures::ready;
+use futures::stream::FuturesOrdered;
+use futures::StreamExt;
+use parking_lot::Mutex;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// into_concurrent_reader is used to read the range `[offset, offset + size)`
+/// of given path by sending at most `concurrent` ranged reads of
+/// `chunk_size` at the same time.
+///
+/// Chunks could be completed in any order, but they will always be yielded
+/// in order. Every chunk is an independent `read` on given accessor, so a
+/// failed chunk will be retried alone if a retry layer is present.
+///
+/// # Notes
+///
+/// - Any failed chunk will fail the whole read, the next read will restart
+///   from the failed chunk.
+/// - Seeking out of the buffered chunk will drop all ongoing reads.
+pub fn into_concurrent_reader(
+    acc: FusedAccessor,
+    path: &str,
+    op: OpRead,
+    offset: u64,
+    size: u64,
+    chunk_size: u64,
+    concurrent: usize,
+) -> IntoConcurrentReader {
+    IntoConcurrentReader {
+        acc,
+        path: path.to_string(),
+        op,
+        offset,
+        size,
+        chunk_size: chunk_size.max(1),
+        concurrent: concurrent.max(1),
+
+        cur: 0,
+        next: 0,
+        chunk: Bytes::new(),
+        ready: VecDeque::new(),
+        tasks: Mutex::new(FuturesOrdered::new()),
+    }
+}
+
+/// Read given range by concurrent chunks.
+pub struct IntoConcurrentReader {
+    acc: FusedAccessor,
+    path: String,
+    op: OpRead,
+
+    offset: u64,
+    size: u64,
+    chunk_size: u64,
+    concurrent: usize,
+
+    /// The position of the first byte in `chunk`, relative to `offset`.
+    cur: u64,
+    /// The position of the next chunk to send, relative to `offset`.
+    next: u64,
+    /// The chunk that is being consumed.
+    chunk: Bytes,
+    /// Chunks that have been read but not consumed yet, in order.
+    ready: VecDeque<Bytes>,
+    /// The lock is only used to make the reader `Sync`, it's always
+    /// accessed via `get_mut` under `&mut self`.
+    tasks: Mutex<FuturesOrdered<BoxFuture<'static, Result<Bytes>>>>,
+}
+
+impl IntoConcurrentReader {
+    /// Send chunk reads until `concurrent` chunks are running or ready.
+    fn fill_tasks(&mut self) {
+        while self.tasks.get_mut().len() + self.ready.len() < self.concurrent
+            && self.next < self.size
+        {
+            let offset = self.offset + self.next;
+            let size = min(self.chunk_size, self.size - self.next);
+            self.next += size;
+
+            let acc = self.acc.clone();
+            let path = self.path.clone();
+            let op = self
+                .op
+                .clone()
+                .with_range(BytesRange::new(Some(offset), Some(size)));
+
+            self.tasks
+                .get_mut()
+                .push_back(Box::pin(read_chunk(acc, path, op, offset, size)));
+        }
+    }
+
+    /// Drop all buffered and ongoing reads and restart from `pos`.
+    fn reset(&mut self, pos: u64) {
+        self.chunk = Bytes::new();
+        self.ready.clear();
+        *self.tasks.get_mut() = FuturesOrdered::new();
+        self.cur = pos;
+        self.next = pos;
+    }
+
+    /// Refill and poll running reads without waiting for them, so that
+    /// reads keep running while the current chunk is being consumed.
+    fn poll_tasks(&mut self, cx: &mut Context<'_>) -> Result<()> {
+        loop {
+            self.fill_tasks();
+
+            match self.tasks.get_mut().poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(bs))) => self.ready.push_back(bs),
+                Poll::Ready(Some(Err(err))) => return Err(err),
+                Poll::Ready(None) | Poll::Pending => return Ok(()),
+            }
+        }
+    }
+
+    fn poll_chunk(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<()>>> {
+        loop {
+            if let Err(err) = self.poll_tasks(cx) {
+                // Drop all ongoing reads so that the next read will
+                // restart from the first byte not consumed.
+                self.reset(self.cur);
+                return Poll::Ready(Some(Err(err)));
+            }
+
+            if !self.chunk.is_empty() {
+                return Poll::Ready(Some(Ok(())));
+            }
+
+            match self.ready.pop_front() {
+                // Poll again to refill the slot of this chunk.
+                Some(bs) => self.chunk = bs,
+                None if self.tasks.get_mut().is_empty() => return Poll::Ready(None),
+                None => return Poll::Pending,
+            }
+        }
+    }
+}
+
+async fn read_chunk(
+    acc: FusedAccessor,
+    path: String,
+    op: OpRead,
+    offset: u64,
+    size: u64,
+) -> Result<Bytes> {
+    let (_, mut r) = acc.read(&path, op).await?;
+
+    let mut buf = BytesMut::with_capacity(size as usize);
+    while let Some(bs) = oio::ReadExt::next(&mut r).await {
+        buf.extend_from_slice(&bs?);
+    }
+
+    if buf.len() as u64 != size {
+        return Err(Error::new(
+            ErrorKind::Unexpected,
+            "chunk returned content length mismatch with expected",
+        )
+        .with_operation(Operation::Read)
+        .with_context("path", &path)
+        .with_context(
+            "range",
+            BytesRange::new(Some(offset), Some(size)).to_string(),
+        )
+        .with_context("actual", buf.len().to_string()));
+    }
+
+    Ok(buf.freeze())
+}
+
+impl oio::Read for IntoConcurrentReader {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        match ready!(self.poll_chunk(cx)) {
+            Some(Ok(())) => {
+                let n = min(buf.len(), self.chunk.len());
+                buf[..n].copy_from_slice(&self.chunk.split_to(n));
+                self.cur += n as u64;
+                Poll::Ready(Ok(n))
+            }
+            Some(Err(err)) => Poll::Ready(Err(err)),
+            None => Poll::Ready(Ok(0)),
+        }
+    }
+
+    fn poll_seek(&mut self, _: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+        let (base, amt) = match pos {
+            SeekFrom::Start(n) => (0, n as i64),
+            SeekFrom::End(n) => (self.size as i64, n),
+            SeekFrom::Current(n) => (self.cur as i64, n),
+        };
+
+        let pos = match base.checked_add(amt) {
+            Some(n) if n >= 0 => n as u64,
+            _ => {
+                return Poll::Ready(Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "invalid seek to a negative or overflowing position",
+                )))
+            }
+        };
+
+        // Reuse the buffered chunk if possible.
+        if pos >= self.cur && pos - self.cur < self.chunk.len() as u64 {
+            self.chunk.advance((pos - self.cur) as usize);
+            self.cur = pos;
+        } else {
+            self.reset(pos);
+        }
+
+        Poll::Ready(Ok(pos))
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        match ready!(self.poll_chunk(cx)) {
+            Some(Ok(())) => {
+                let bs = std::mem::take(&mut self.chunk);
+                self.cur += bs.len() as u64;
+                Poll::Ready(Some(Ok(bs)))
+            }
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use futures::AsyncReadExt;
+    use futures::AsyncSeekExt;
+    use rand::prelude::*;
+
+    use super::*;
+    use crate::layers::RetryLayer;
+    use crate::services::Memory;
+
+    fn gen_bytes(size: usize) -> Vec<u8> {
+        let mut content = vec![0; size];
+        thread_rng().fill_bytes(&mut content);
+        content
+    }
+
+    /// MockService will fail the read of chunk at `fail_at` once.
+    #[derive(Debug, Clone, Default)]
+    struct MockService {
+        data: Bytes,
+        fail_at: Arc<Mutex<Option<u64>>>,
+        reads: Arc<Mutex<Vec<u64>>>,
+    }
+
+    #[async_trait]
+    impl Accessor for MockService {
+        type Reader = oio::Cursor;
+        type BlockingReader = ();
+        type Writer = ();
+        type BlockingWriter = ();
+        type Pager = ();
+        type BlockingPager = ();
+
+        fn info(&self) -> AccessorInfo {
+            let mut am = AccessorInfo::default();
+            am.set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_seek: true,
+                read_can_next: true,
+                read_with_range: true,
+                ..Default::default()
+            });
+
+            am
+        }
+
+        async fn stat(&self, _: &str, _: OpStat) -> Result<RpStat> {
+            Ok(RpStat::new(
+                Metadata::new(EntryMode::FILE).with_content_length(self.data.len() as u64),
+            ))
+        }
+
+        async fn read(&self, _: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+            let offset = args.range().offset().unwrap_or_default();
+            self.reads.lock().unwrap().push(offset);
+
+            let mut fail_at = self.fail_at.lock().unwrap();
+            if *fail_at == Some(offset) {
+                *fail_at = None;
+                return Err(Error::new(ErrorKind::Unexpected, "mock chunk failed").set_temporary());
+            }
+
+            let bs = args.range().apply_on_bytes(self.data.clone());
+            Ok((RpRead::new(bs.len() as u64), oio::Cursor::from(bs.to_vec())))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_by_chunks() -> anyhow::Result<()> {
+        let op = Operator::new(Memory::default())?
+            .finish()
+            .with_read_chunk(1000, 4);
+
+        let content = gen_bytes(10 * 1000 + 123);
+        op.write("file", content.clone()).await?;
+
+        assert_eq!(op.read("file").await?, content);
+        assert_eq!(
+            op.range_read("file", 1500..7777).await?,
+            content[1500..7777]
+        );
+        assert_eq!(op.range_read("file", 9000..).await?, content[9000..]);
+
+        // Small reads don't need to be split.
+        assert_eq!(op.range_read("file", 10..20).await?, content[10..20]);
+
+        let mut r = op.reader("file").await?;
+        r.seek(SeekFrom::Start(4321)).await?;
+        let mut buf = vec![0; 2000];
+        r.read_exact(&mut buf).await?;
+        assert_eq!(buf, content[4321..6321]);
+        assert_eq!(r.offset(), 6321);
+
+        let mut r = op.range_reader("file", 3000..).await?;
+        let mut buf = Vec::new();
+        r.read_to_end(&mut buf).await?;
+        assert_eq!(buf, content[3000..]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_by_chunks_failed() -> anyhow::Result<()> {
+        let content = gen_bytes(10 * 1000);
+        let srv = MockService {
+            data: Bytes::from(content.clone()),
+            ..Default::default()
+        };
+
+        let op = OperatorBuilder::new(srv.clone())
+            .finish()
+            .with_read_chunk(1000, 4);
+        *srv.fail_at.lock().unwrap() = Some(5000);
+        assert!(op.read("file").await.is_err());
+
+        let op = OperatorBuilder::new(srv.clone())
+            .layer(RetryLayer::new().with_min_delay(Duration::from_millis(1)))
+            .finish()
+            .with_read_chunk(1000, 4);
+        *srv.fail_at.lock().unwrap() = Some(5000);
+        srv.reads.lock().unwrap().clear();
+        assert_eq!(op.read("file").await?, content);
+
+        // Only the failed chunk has been retried.
+        let reads = srv.reads.lock().unwrap();
+        assert_eq!(reads.len(), 11);
+        assert_eq!(reads.iter().filter(|v| **v == 5000).count(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_by_chunks_refill() -> anyhow::Result<()> {
+        let content = gen_bytes(10 * 1000);
+        let srv = MockService {
+            data: Bytes::from(content.clone()),
+            ..Default::default()
+        };
+
+        let op = OperatorBuilder::new(srv.clone())
+            .finish()
+            .with_read_chunk(1000, 2);
+        let mut r = op.reader("file").await?;
+
+        let mut buf = vec![0; 10];
+        r.read_exact(&mut buf).await?;
+        assert_eq!(buf, content[..10]);
+
+        // The slot of the first chunk has been refilled before it's consumed.
+        assert_eq!(*srv.reads.lock().unwrap(), vec![0, 1000, 2000]);
+
+        let mut buf = Vec::new();
+        r.read_to_end(&mut buf).await?;
+        assert_eq!(buf, content[10..]);
+
+        Ok(())
+    }
+}
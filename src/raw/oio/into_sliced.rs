@@ -0,0 +1,228 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::min;
+use std::io::SeekFrom;
+use std::task::Context;
+use std::task::Poll;
+
+use bytes::Bytes;
+use futures::ready;
+
+use crate::raw::*;
+use crate::*;
+
+/// into_sliced_reader is used to make [`oio::Read`] or [`oio::BlockingRead`]
+/// only yield the first `size` bytes.
+///
+/// The underlying reader will be dropped as soon as `size` bytes have been
+/// read, so connections will be closed without downloading the rest.
+pub fn into_sliced_reader<R>(r: R, size: u64) -> IntoSlicedReader<R> {
+    let mut sr = IntoSlicedReader {
+        r: Some(r),
+        remaining: size,
+    };
+    sr.try_finish();
+    sr
+}
+
+/// Make given read only yield the first `size` bytes.
+pub struct IntoSlicedReader<R> {
+    /// `None` means the limit has been reached and inner reader dropped.
+    r: Option<R>,
+    remaining: u64,
+}
+
+impl<R> IntoSlicedReader<R> {
+    /// Drop inner reader if the limit has been reached.
+    fn try_finish(&mut self) {
+        if self.remaining == 0 {
+            self.r = None;
+        }
+    }
+
+    fn advance(&mut self, n: usize) {
+        self.remaining -= n as u64;
+        self.try_finish();
+    }
+}
+
+impl<R: oio::Read> oio::Read for IntoSlicedReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        let size = min(buf.len() as u64, self.remaining) as usize;
+        let r = match &mut self.r {
+            Some(r) => r,
+            None => return Poll::Ready(Ok(0)),
+        };
+
+        let n = ready!(r.poll_read(cx, &mut buf[..size]))?;
+        self.advance(n);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_seek(&mut self, _: &mut Context<'_>, _: SeekFrom) -> Poll<Result<u64>> {
+        Poll::Ready(Err(Error::new(
+            ErrorKind::Unsupported,
+            "sliced reader doesn't support seeking",
+        )))
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        let r = match &mut self.r {
+            Some(r) => r,
+            None => return Poll::Ready(None),
+        };
+
+        match ready!(r.poll_next(cx)) {
+            Some(Ok(mut bs)) => {
+                if bs.len() as u64 > self.remaining {
+                    bs.truncate(self.remaining as usize);
+                }
+                self.advance(bs.len());
+                Poll::Ready(Some(Ok(bs)))
+            }
+            v => Poll::Ready(v),
+        }
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for IntoSlicedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let size = min(buf.len() as u64, self.remaining) as usize;
+        let r = match &mut self.r {
+            Some(r) => r,
+            None => return Ok(0),
+        };
+
+        let n = r.read(&mut buf[..size])?;
+        self.advance(n);
+        Ok(n)
+    }
+
+    fn seek(&mut self, _: SeekFrom) -> Result<u64> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "sliced reader doesn't support seeking",
+        ))
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        let r = self.r.as_mut()?;
+
+        match r.next() {
+            Some(Ok(mut bs)) => {
+                if bs.len() as u64 > self.remaining {
+                    bs.truncate(self.remaining as usize);
+                }
+                self.advance(bs.len());
+                Some(Ok(bs))
+            }
+            v => v,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use bytes::BufMut;
+    use bytes::BytesMut;
+
+    use super::*;
+
+    /// A reader that records whether it has been dropped.
+    struct DropReader {
+        r: oio::Cursor,
+        dropped: Arc<AtomicBool>,
+    }
+
+    impl Drop for DropReader {
+        fn drop(&mut self) {
+            self.dropped.store(true, Ordering::SeqCst)
+        }
+    }
+
+    impl oio::Read for DropReader {
+        fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+            self.r.poll_read(cx, buf)
+        }
+
+        fn poll_seek(&mut self, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+            self.r.poll_seek(cx, pos)
+        }
+
+        fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+            self.r.poll_next(cx)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_into_sliced() {
+        use oio::ReadExt;
+
+        let dropped = Arc::new(AtomicBool::new(false));
+        let r = DropReader {
+            r: oio::Cursor::from(vec![1; 4096]),
+            dropped: dropped.clone(),
+        };
+        let mut r = into_sliced_reader(Box::new(r) as oio::Reader, 1024);
+
+        let mut bs = BytesMut::new();
+        while let Some(b) = r.next().await {
+            let b = b.expect("read must success");
+            bs.put_slice(&b);
+        }
+        assert_eq!(bs.len(), 1024);
+        assert!(dropped.load(Ordering::SeqCst), "inner must be dropped");
+
+        let mut buf = vec![0; 16];
+        let n = r.read(&mut buf).await.expect("read must success");
+        assert_eq!(n, 0);
+    }
+
+    #[tokio::test]
+    async fn test_into_sliced_larger_than_content() {
+        use oio::ReadExt;
+
+        let r = oio::Cursor::from(vec![1; 1024]);
+        let mut r = into_sliced_reader(Box::new(r) as oio::Reader, 4096);
+
+        let mut buf = vec![0; 8192];
+        let mut total = 0;
+        loop {
+            let n = r.read(&mut buf).await.expect("read must success");
+            if n == 0 {
+                break;
+            }
+            total += n;
+        }
+        assert_eq!(total, 1024);
+    }
+
+    #[test]
+    fn test_into_sliced_blocking() {
+        use oio::BlockingRead;
+
+        let r = oio::Cursor::from(vec![1; 4096]);
+        let mut r = into_sliced_reader(Box::new(r) as oio::BlockingReader, 1000);
+
+        let mut buf = vec![0; 600];
+        assert_eq!(r.read(&mut buf).expect("read must success"), 600);
+        assert_eq!(r.read(&mut buf).expect("read must success"), 400);
+        assert_eq!(r.read(&mut buf).expect("read must success"), 0);
+    }
+}
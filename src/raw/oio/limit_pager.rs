@@ -0,0 +1,129 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+
+use crate::raw::*;
+use crate::*;
+
+/// limit_pager is used to make a pager yield at most `limit` entries in
+/// total.
+pub fn limit_pager<P>(pager: P, limit: usize) -> LimitPager<P> {
+    LimitPager {
+        pager,
+        remaining: limit,
+    }
+}
+
+/// LimitPager will stop yielding entries once the limit has been reached.
+///
+/// The last page will be truncated if needed, and the inner pager will not
+/// be polled anymore after that, so no extra page requests will be sent.
+pub struct LimitPager<P> {
+    pager: P,
+    remaining: usize,
+}
+
+impl<P> LimitPager<P> {
+    fn truncate(&mut self, mut entries: Vec<oio::Entry>) -> Vec<oio::Entry> {
+        entries.truncate(self.remaining);
+        self.remaining -= entries.len();
+        entries
+    }
+}
+
+#[async_trait]
+impl<P: oio::Page> oio::Page for LimitPager<P> {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        Ok(self.pager.next().await?.map(|v| self.truncate(v)))
+    }
+}
+
+impl<P: oio::BlockingPage> oio::BlockingPage for LimitPager<P> {
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        Ok(self.pager.next()?.map(|v| self.truncate(v)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oio::BlockingPage;
+
+    use super::*;
+
+    struct MockPager {
+        pages: Vec<Vec<oio::Entry>>,
+        fetched: usize,
+    }
+
+    impl BlockingPage for MockPager {
+        fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+            self.fetched += 1;
+            if self.pages.is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(self.pages.remove(0)))
+        }
+    }
+
+    fn mock_pager() -> MockPager {
+        let entry = |path: &str| oio::Entry::new(path, Metadata::new(EntryMode::FILE));
+
+        MockPager {
+            pages: vec![
+                vec![entry("a"), entry("b")],
+                vec![entry("c"), entry("d")],
+                vec![entry("e")],
+            ],
+            fetched: 0,
+        }
+    }
+
+    fn list(pager: &mut LimitPager<MockPager>) -> Result<Vec<String>> {
+        let mut paths = vec![];
+        while let Some(entries) = pager.next()? {
+            paths.extend(entries.iter().map(|v| v.path().to_string()));
+        }
+        Ok(paths)
+    }
+
+    #[test]
+    fn test_limit_pager() -> Result<()> {
+        let mut pager = limit_pager(mock_pager(), 3);
+        assert_eq!(list(&mut pager)?, vec!["a", "b", "c"]);
+        // The third page must not be fetched.
+        assert_eq!(pager.pager.fetched, 2);
+
+        let mut pager = limit_pager(mock_pager(), 2);
+        assert_eq!(list(&mut pager)?, vec!["a", "b"]);
+        assert_eq!(pager.pager.fetched, 1);
+
+        let mut pager = limit_pager(mock_pager(), 0);
+        assert!(list(&mut pager)?.is_empty());
+        assert_eq!(pager.pager.fetched, 0);
+
+        let mut pager = limit_pager(mock_pager(), 10);
+        assert_eq!(list(&mut pager)?, vec!["a", "b", "c", "d", "e"]);
+
+        Ok(())
+    }
+}
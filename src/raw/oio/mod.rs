@@ -38,6 +38,11 @@ pub use write::Write;
 pub use write::WriteOperation;
 pub use write::Writer;
 
+mod multipart_upload_write;
+pub use multipart_upload_write::MultipartUploadPart;
+pub use multipart_upload_write::MultipartUploadWrite;
+pub use multipart_upload_write::MultipartUploadWriter;
+
 mod cursor;
 pub use cursor::Cursor;
 
@@ -45,6 +50,14 @@ mod into_streamable;
 pub use into_streamable::into_streamable_reader;
 pub use into_streamable::IntoStreamableReader;
 
+mod into_sliced;
+pub use into_sliced::into_sliced_reader;
+pub use into_sliced::IntoSlicedReader;
+
+mod into_concurrent;
+pub use into_concurrent::into_concurrent_reader;
+pub use into_concurrent::IntoConcurrentReader;
+
 mod entry;
 pub use entry::Entry;
 
@@ -62,3 +75,15 @@ pub use to_flat_pager::ToFlatPager;
 mod to_hierarchy_pager;
 pub use to_hierarchy_pager::to_hierarchy_pager;
 pub use to_hierarchy_pager::ToHierarchyPager;
+
+mod to_walk_pager;
+pub use to_walk_pager::to_walk_pager;
+pub use to_walk_pager::WalkPager;
+
+mod filter_modified_pager;
+pub use filter_modified_pager::filter_modified_pager;
+pub use filter_modified_pager::FilterModifiedPager;
+
+mod limit_pager;
+pub use limit_pager::limit_pager;
+pub use limit_pager::LimitPager;
@@ -0,0 +1,533 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::mem;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use bytes::BytesMut;
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use log::debug;
+use parking_lot::Mutex;
+
+use crate::raw::*;
+use crate::*;
+
+/// MultipartUploadWrite is used to implement [`Write`] based on multipart
+/// uploads, which is supported by most S3 alike services.
+///
+/// Services only need to implement the requests, [`MultipartUploadWriter`]
+/// will take care of buffering, part numbers, concurrency and abort.
+///
+/// [`Write`]: oio::Write
+#[async_trait]
+pub trait MultipartUploadWrite: Send + Sync + Unpin + 'static {
+    /// Upload the whole content in one request.
+    ///
+    /// This is used by [`Write::write`] and by `close` if all appended
+    /// content fits in a single part.
+    ///
+    /// Returns the etag and version of the written object if services
+    /// return them.
+    ///
+    /// [`Write::write`]: oio::Write::write
+    async fn write_once(&self, size: u64, body: AsyncBody) -> Result<RpWrite>;
+
+    /// Initiate a new multipart upload and return its upload id.
+    async fn initiate_part(&self) -> Result<String>;
+
+    /// Upload a part with given part number, part number starts from 1.
+    ///
+    /// Parts could be uploaded concurrently, so they could be finished
+    /// out of order.
+    async fn write_part(
+        &self,
+        upload_id: &str,
+        part_number: usize,
+        size: u64,
+        body: AsyncBody,
+    ) -> Result<MultipartUploadPart>;
+
+    /// Complete the multipart upload with all uploaded parts.
+    ///
+    /// Parts are sorted by part number. Services should format the etag
+    /// in the way they expected while building the request.
+    ///
+    /// Returns the etag and version of the written object if services
+    /// return them.
+    async fn complete_part(
+        &self,
+        upload_id: &str,
+        parts: &[MultipartUploadPart],
+    ) -> Result<RpWrite>;
+
+    /// Abort the multipart upload, all uploaded parts will be freed.
+    async fn abort_part(&self, upload_id: &str) -> Result<()>;
+}
+
+/// The part that has been uploaded by [`MultipartUploadWrite::write_part`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultipartUploadPart {
+    /// The number of this part, starts from 1.
+    pub part_number: usize,
+    /// The etag returned by service, it's kept as is (quotes included).
+    pub etag: String,
+    /// The checksum of this part, only set by services that verify the
+    /// checksum of every part while completing the upload.
+    pub checksum: Option<String>,
+}
+
+impl MultipartUploadPart {
+    /// Create a new part.
+    pub fn new(part_number: usize, etag: impl Into<String>) -> Self {
+        Self {
+            part_number,
+            etag: etag.into(),
+            checksum: None,
+        }
+    }
+
+    /// Set the checksum of this part.
+    pub fn with_checksum(mut self, checksum: impl Into<String>) -> Self {
+        self.checksum = Some(checksum.into());
+        self
+    }
+}
+
+type WritePartFuture = BoxFuture<'static, Result<MultipartUploadPart>>;
+
+/// MultipartUploadWriter implements [`Write`] via [`MultipartUploadWrite`].
+///
+/// - Appended bytes will be buffered until `part_size` is reached, every
+///   append will be uploaded as a part if `part_size` is not set.
+/// - At most `concurrency` parts will be uploaded at the same time, `append`
+///   will wait for running parts before buffering more bytes. So at most
+///   `part_size * concurrency` bytes will be kept in memory.
+/// - Parts will be sorted by part number before completing the upload.
+/// - The multipart upload is initiated lazily, content that smaller than
+///   `part_size` will be uploaded via [`MultipartUploadWrite::write_once`]
+///   while closing.
+/// - The multipart upload will be aborted in background if the writer is
+///   dropped without `close` or `abort`.
+///
+/// [`Write`]: oio::Write
+pub struct MultipartUploadWriter<W: MultipartUploadWrite> {
+    inner: Arc<W>,
+    part_size: Option<usize>,
+    concurrency: usize,
+
+    upload_id: Option<String>,
+    /// The part number of next part, starts from 1.
+    next_part_number: usize,
+    parts: Vec<MultipartUploadPart>,
+    /// Parts that are uploading.
+    ///
+    /// The lock is only used to make the writer `Sync`, it's always
+    /// accessed via `get_mut` under `&mut self`.
+    futs: Mutex<FuturesUnordered<WritePartFuture>>,
+    /// Bytes that not uploaded yet.
+    buf: BytesMut,
+    /// The reply of `write`, the content has been uploaded at once so
+    /// there is nothing to upload while closing.
+    written: Option<RpWrite>,
+}
+
+impl<W: MultipartUploadWrite> MultipartUploadWriter<W> {
+    /// Create a new MultipartUploadWriter.
+    pub fn new(inner: W, part_size: Option<usize>) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            part_size,
+            concurrency: 1,
+
+            upload_id: None,
+            next_part_number: 1,
+            parts: vec![],
+            futs: Mutex::new(FuturesUnordered::new()),
+            buf: BytesMut::new(),
+            written: None,
+        }
+    }
+
+    /// Set the max number of parts that could be uploaded at the same time.
+    ///
+    /// Default to `1` which means parts will be uploaded one by one.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Use an upload that has been initiated already.
+    ///
+    /// Services could initiate the upload while creating the writer so
+    /// that the upload id can be returned as session. At least one part
+    /// will be uploaded for this upload while closing.
+    pub fn with_upload_id(mut self, upload_id: String) -> Self {
+        self.upload_id = Some(upload_id);
+        self
+    }
+
+    /// Start uploading given bytes as the next part, initiate the multipart
+    /// upload if it's not started yet.
+    ///
+    /// Wait for running parts if there are already `concurrency` parts
+    /// uploading.
+    async fn upload_part(&mut self, bs: Bytes) -> Result<()> {
+        let upload_id = match &self.upload_id {
+            Some(upload_id) => upload_id.clone(),
+            None => {
+                let upload_id = self.inner.initiate_part().await?;
+                self.upload_id = Some(upload_id.clone());
+                upload_id
+            }
+        };
+
+        let part_number = self.next_part_number;
+        self.next_part_number += 1;
+
+        let inner = self.inner.clone();
+        self.futs.get_mut().push(Box::pin(async move {
+            inner
+                .write_part(
+                    &upload_id,
+                    part_number,
+                    bs.len() as u64,
+                    AsyncBody::Bytes(bs),
+                )
+                .await
+        }));
+
+        while self.futs.get_mut().len() >= self.concurrency {
+            self.poll_part().await?;
+        }
+        Ok(())
+    }
+
+    /// Wait for one of the running parts to finish.
+    async fn poll_part(&mut self) -> Result<()> {
+        if let Some(part) = self.futs.get_mut().next().await {
+            self.parts.push(part?);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<W: MultipartUploadWrite> oio::Write for MultipartUploadWriter<W> {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        let rp = self
+            .inner
+            .write_once(bs.len() as u64, AsyncBody::Bytes(bs))
+            .await?;
+        self.written = Some(rp);
+        Ok(())
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        let part_size = match self.part_size {
+            Some(part_size) => part_size,
+            // Upload every append as a part if part size is not set.
+            None => return self.upload_part(bs).await,
+        };
+
+        let mut bs = bs;
+        while !bs.is_empty() {
+            let n = (part_size - self.buf.len()).min(bs.len());
+            // Avoid copying if there are no buffered bytes.
+            let part = if self.buf.is_empty() && n == part_size {
+                bs.split_to(n)
+            } else {
+                self.buf.extend_from_slice(&bs.split_to(n));
+                if self.buf.len() < part_size {
+                    continue;
+                }
+                mem::take(&mut self.buf).freeze()
+            };
+
+            self.upload_part(part).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        // The content has been uploaded by `write` already.
+        if let Some(rp) = self.written.take() {
+            return Ok(rp);
+        }
+
+        let bs = mem::take(&mut self.buf).freeze();
+
+        let upload_id = match self.upload_id.clone() {
+            Some(upload_id) => upload_id,
+            // No parts uploaded, upload the buffered content at once.
+            None => {
+                return self
+                    .inner
+                    .write_once(bs.len() as u64, AsyncBody::Bytes(bs))
+                    .await;
+            }
+        };
+
+        // Upload the last part, services require at least one part.
+        if !bs.is_empty() || self.next_part_number == 1 {
+            self.upload_part(bs).await?;
+        }
+        while !self.futs.get_mut().is_empty() {
+            self.poll_part().await?;
+        }
+
+        // Parts could be finished out of order.
+        self.parts.sort_by_key(|part| part.part_number);
+        let rp = self.inner.complete_part(&upload_id, &self.parts).await?;
+        self.upload_id = None;
+        self.parts.clear();
+
+        Ok(rp)
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        // Cancel all running parts before aborting.
+        *self.futs.get_mut() = FuturesUnordered::new();
+        self.buf.clear();
+        self.parts.clear();
+
+        let upload_id = match self.upload_id.take() {
+            Some(upload_id) => upload_id,
+            None => return Ok(()),
+        };
+
+        self.inner.abort_part(&upload_id).await
+    }
+}
+
+impl<W: MultipartUploadWrite> Drop for MultipartUploadWriter<W> {
+    fn drop(&mut self) {
+        let upload_id = match self.upload_id.take() {
+            Some(upload_id) => upload_id,
+            None => return,
+        };
+
+        // Abort the unfinished upload to avoid leaking parts on the service.
+        let inner = self.inner.clone();
+        if let Ok(rt) = tokio::runtime::Handle::try_current() {
+            rt.spawn(async move {
+                if let Err(err) = inner.abort_part(&upload_id).await {
+                    debug!("abort multipart upload {upload_id} failed: {err:?}");
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oio::Write;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MockState {
+        write_once: Vec<u64>,
+        initiated: usize,
+        in_flight: usize,
+        max_in_flight: usize,
+        /// Part numbers in the order they finished.
+        finished: Vec<usize>,
+        completed: Option<Vec<MultipartUploadPart>>,
+        aborted: Vec<String>,
+    }
+
+    /// Fake multipart upload that finishes parts with larger part numbers
+    /// earlier, and records all requests.
+    #[derive(Clone, Default)]
+    struct MockUpload {
+        state: Arc<Mutex<MockState>>,
+    }
+
+    #[async_trait]
+    impl MultipartUploadWrite for MockUpload {
+        async fn write_once(&self, size: u64, _: AsyncBody) -> Result<RpWrite> {
+            self.state.lock().write_once.push(size);
+            Ok(RpWrite::new())
+        }
+
+        async fn initiate_part(&self) -> Result<String> {
+            self.state.lock().initiated += 1;
+            Ok("upload_id".to_string())
+        }
+
+        async fn write_part(
+            &self,
+            upload_id: &str,
+            part_number: usize,
+            size: u64,
+            _: AsyncBody,
+        ) -> Result<MultipartUploadPart> {
+            assert_eq!(upload_id, "upload_id");
+            assert!(size > 0, "empty part must not be uploaded");
+
+            {
+                let mut state = self.state.lock();
+                state.in_flight += 1;
+                state.max_in_flight = state.max_in_flight.max(state.in_flight);
+            }
+            // Parts with smaller part numbers will take longer to finish.
+            for _ in 0..(16 - part_number.min(16)) {
+                tokio::task::yield_now().await;
+            }
+            {
+                let mut state = self.state.lock();
+                state.in_flight -= 1;
+                state.finished.push(part_number);
+            }
+
+            Ok(MultipartUploadPart::new(
+                part_number,
+                format!("etag-{part_number}"),
+            ))
+        }
+
+        async fn complete_part(
+            &self,
+            upload_id: &str,
+            parts: &[MultipartUploadPart],
+        ) -> Result<RpWrite> {
+            assert_eq!(upload_id, "upload_id");
+            self.state.lock().completed = Some(parts.to_vec());
+            Ok(RpWrite::new())
+        }
+
+        async fn abort_part(&self, upload_id: &str) -> Result<()> {
+            self.state.lock().aborted.push(upload_id.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parts_sorted_and_concurrency() {
+        let upload = MockUpload::default();
+        let mut w = MultipartUploadWriter::new(upload.clone(), Some(4)).with_concurrency(3);
+
+        for _ in 0..6 {
+            w.append(Bytes::from("abcd")).await.unwrap();
+        }
+        w.close().await.unwrap();
+
+        let state = upload.state.lock();
+        assert_eq!(state.initiated, 1);
+        assert_eq!(state.max_in_flight, 3);
+        assert_ne!(
+            state.finished,
+            vec![1, 2, 3, 4, 5, 6],
+            "must be out of order"
+        );
+        let completed: Vec<_> = state
+            .completed
+            .as_ref()
+            .expect("upload must be completed")
+            .iter()
+            .map(|part| part.part_number)
+            .collect();
+        assert_eq!(completed, vec![1, 2, 3, 4, 5, 6]);
+        assert!(state.aborted.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_close_after_flushed() {
+        let upload = MockUpload::default();
+        let mut w = MultipartUploadWriter::new(upload.clone(), Some(4));
+
+        w.append(Bytes::from("abcdefgh")).await.unwrap();
+        w.close().await.unwrap();
+
+        let state = upload.state.lock();
+        assert_eq!(state.finished, vec![1, 2]);
+        assert_eq!(state.completed.as_ref().map(|v| v.len()), Some(2));
+        assert!(state.write_once.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_close_small_content() {
+        let upload = MockUpload::default();
+        let mut w = MultipartUploadWriter::new(upload.clone(), Some(4));
+
+        w.append(Bytes::from("abc")).await.unwrap();
+        w.close().await.unwrap();
+
+        let state = upload.state.lock();
+        assert_eq!(state.write_once, vec![3]);
+        assert_eq!(state.initiated, 0);
+        assert!(state.completed.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_write_then_close() {
+        let upload = MockUpload::default();
+        let mut w = MultipartUploadWriter::new(upload.clone(), Some(4));
+
+        w.write(Bytes::from("abcdefgh")).await.unwrap();
+        w.close().await.unwrap();
+
+        let state = upload.state.lock();
+        assert_eq!(state.write_once, vec![8]);
+        assert_eq!(state.initiated, 0);
+        assert!(state.finished.is_empty());
+        assert!(state.completed.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_abort() {
+        let upload = MockUpload::default();
+        let mut w = MultipartUploadWriter::new(upload.clone(), Some(4));
+
+        w.append(Bytes::from("abcdefgh")).await.unwrap();
+        w.abort().await.unwrap();
+        drop(w);
+
+        let state = upload.state.lock();
+        assert_eq!(state.aborted, vec!["upload_id".to_string()]);
+        assert!(state.completed.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_abort_on_drop() {
+        let upload = MockUpload::default();
+        let mut w = MultipartUploadWriter::new(upload.clone(), Some(4));
+
+        w.append(Bytes::from("abcdefgh")).await.unwrap();
+        drop(w);
+
+        // Abort is spawned in background, wait for it to run.
+        for _ in 0..16 {
+            if !upload.state.lock().aborted.is_empty() {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(upload.state.lock().aborted, vec!["upload_id".to_string()]);
+
+        // Closed writers must not be aborted.
+        let upload = MockUpload::default();
+        let mut w = MultipartUploadWriter::new(upload.clone(), Some(4));
+        w.append(Bytes::from("abcdefgh")).await.unwrap();
+        w.close().await.unwrap();
+        drop(w);
+        tokio::task::yield_now().await;
+        assert!(upload.state.lock().aborted.is_empty());
+    }
+}
@@ -0,0 +1,327 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+
+use async_trait::async_trait;
+
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// to_walk_pager is used to walk all entries under given path.
+///
+/// Services' native `scan` will be used if supported, otherwise we will
+/// walk the dir tree via `list` recursively.
+pub fn to_walk_pager<A: Accessor, P>(acc: A, path: &str, args: OpWalk) -> WalkPager<A, P> {
+    let scan = acc.info().capabilities().contains(AccessorCapability::Scan);
+
+    WalkPager {
+        acc,
+        path: path.to_string(),
+        args,
+        scan,
+        dirs: VecDeque::from([(path.to_string(), 1)]),
+        pager: None,
+    }
+}
+
+/// WalkPager will walk dir in top down way.
+///
+/// - For services that support `scan`, all entries will be returned by
+///   `scan` and filtered by their depth.
+/// - For others, dirs will be listed one by one, and dirs deeper than
+///   `max_depth` will never be listed.
+///
+/// # Note
+///
+/// The walking path itself will not be returned, and there is no guarantee
+/// about the order of returning entries.
+pub struct WalkPager<A: Accessor, P> {
+    acc: A,
+    path: String,
+    args: OpWalk,
+    /// Use `scan` instead of `list` or not.
+    scan: bool,
+
+    /// Dirs waiting to be listed along with the depth of their children.
+    dirs: VecDeque<(String, usize)>,
+    /// Current pager along with the depth of its entries.
+    pager: Option<(P, usize)>,
+}
+
+impl<A: Accessor, P> WalkPager<A, P> {
+    /// Get the depth of given path relative to walking path.
+    fn depth(&self, path: &str) -> usize {
+        let rel = if self.path == "/" {
+            path
+        } else {
+            path.strip_prefix(self.path.as_str()).unwrap_or(path)
+        };
+
+        rel.trim_end_matches('/').split('/').count()
+    }
+
+    /// Filter entries and push dirs that need to be listed.
+    fn handle(&mut self, entries: Vec<oio::Entry>, depth: usize) -> Vec<oio::Entry> {
+        let mut res = Vec::with_capacity(entries.len());
+        for oe in entries {
+            if oe.path() == self.path {
+                continue;
+            }
+
+            let depth = if self.scan {
+                self.depth(oe.path())
+            } else {
+                depth
+            };
+            if matches!(self.args.max_depth(), Some(max_depth) if depth > max_depth) {
+                continue;
+            }
+
+            if oe.mode().is_dir() {
+                if !self.scan && self.args.max_depth().map_or(true, |v| depth < v) {
+                    self.dirs.push_back((oe.path().to_string(), depth + 1));
+                }
+                if !self.args.include_dirs() {
+                    continue;
+                }
+            }
+
+            res.push(oe)
+        }
+        res
+    }
+}
+
+#[async_trait]
+impl<A, P> oio::Page for WalkPager<A, P>
+where
+    A: Accessor<Pager = P>,
+    P: oio::Page,
+{
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        loop {
+            let (mut pager, depth) = match self.pager.take() {
+                Some(v) => v,
+                None => match self.dirs.pop_front() {
+                    Some((dir, depth)) => {
+                        let pager = if self.scan {
+                            self.acc.scan(&dir, OpScan::new()).await?.1
+                        } else {
+                            self.acc.list(&dir, OpList::new()).await?.1
+                        };
+                        (pager, depth)
+                    }
+                    None => return Ok(None),
+                },
+            };
+
+            let entries = match pager.next().await? {
+                Some(entries) => entries,
+                None => continue,
+            };
+            self.pager = Some((pager, depth));
+
+            let res = self.handle(entries, depth);
+            // Don't return empty pages to users.
+            if !res.is_empty() {
+                return Ok(Some(res));
+            }
+        }
+    }
+}
+
+impl<A, P> oio::BlockingPage for WalkPager<A, P>
+where
+    A: Accessor<BlockingPager = P>,
+    P: oio::BlockingPage,
+{
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        loop {
+            let (mut pager, depth) = match self.pager.take() {
+                Some(v) => v,
+                None => match self.dirs.pop_front() {
+                    Some((dir, depth)) => {
+                        let pager = if self.scan {
+                            self.acc.blocking_scan(&dir, OpScan::new())?.1
+                        } else {
+                            self.acc.blocking_list(&dir, OpList::new())?.1
+                        };
+                        (pager, depth)
+                    }
+                    None => return Ok(None),
+                },
+            };
+
+            let entries = match pager.next()? {
+                Some(entries) => entries,
+                None => continue,
+            };
+            self.pager = Some((pager, depth));
+
+            let res = self.handle(entries, depth);
+            // Don't return empty pages to users.
+            if !res.is_empty() {
+                return Ok(Some(res));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::collections::HashSet;
+
+    use log::debug;
+    use oio::BlockingPage;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct MockService {
+        scan: bool,
+        map: HashMap<&'static str, Vec<&'static str>>,
+    }
+
+    impl MockService {
+        fn new(scan: bool) -> Self {
+            let mut map = HashMap::default();
+            if scan {
+                map.insert("x/", vec!["x/a", "x/y/", "x/y/b", "x/y/z/", "x/y/z/c"]);
+            } else {
+                map.insert("x/", vec!["x/a", "x/y/"]);
+                map.insert("x/y/", vec!["x/y/b", "x/y/z/"]);
+                map.insert("x/y/z/", vec!["x/y/z/c"]);
+            }
+
+            Self { scan, map }
+        }
+
+        fn get(&self, path: &str) -> MockPager {
+            let inner = self.map.get(path).expect("must have value").to_vec();
+
+            MockPager { inner, done: false }
+        }
+    }
+
+    #[async_trait]
+    impl Accessor for MockService {
+        type Reader = ();
+        type BlockingReader = ();
+        type Writer = ();
+        type BlockingWriter = ();
+        type Pager = ();
+        type BlockingPager = MockPager;
+
+        fn info(&self) -> AccessorInfo {
+            let mut am = AccessorInfo::default();
+            if self.scan {
+                am.set_capabilities(AccessorCapability::Scan);
+            } else {
+                am.set_capabilities(AccessorCapability::List);
+            }
+
+            am
+        }
+
+        fn blocking_list(&self, path: &str, _: OpList) -> Result<(RpList, Self::BlockingPager)> {
+            debug!("visit path: {path}");
+            assert!(!self.scan, "list must not be called");
+            Ok((RpList::default(), self.get(path)))
+        }
+
+        fn blocking_scan(&self, path: &str, _: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+            debug!("visit path: {path}");
+            assert!(self.scan, "scan must not be called");
+            Ok((RpScan::default(), self.get(path)))
+        }
+    }
+
+    struct MockPager {
+        inner: Vec<&'static str>,
+        done: bool,
+    }
+
+    impl BlockingPage for MockPager {
+        fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+            if self.done {
+                return Ok(None);
+            }
+            self.done = true;
+
+            let entries = self
+                .inner
+                .iter()
+                .map(|path| {
+                    if path.ends_with('/') {
+                        oio::Entry::new(path, Metadata::new(EntryMode::DIR))
+                    } else {
+                        oio::Entry::new(path, Metadata::new(EntryMode::FILE))
+                    }
+                })
+                .collect();
+
+            Ok(Some(entries))
+        }
+    }
+
+    fn walk(scan: bool, args: OpWalk) -> Result<HashSet<String>> {
+        let mut pager = to_walk_pager(MockService::new(scan), "x/", args);
+
+        let mut paths = HashSet::new();
+        while let Some(e) = pager.next()? {
+            paths.extend(e.into_iter().map(|v| v.path().to_string()))
+        }
+        Ok(paths)
+    }
+
+    #[test]
+    fn test_blocking_walk() -> Result<()> {
+        let _ = env_logger::try_init();
+
+        let cases = vec![
+            (
+                "walk all",
+                OpWalk::new(),
+                vec!["x/a", "x/y/", "x/y/b", "x/y/z/", "x/y/z/c"],
+            ),
+            (
+                "walk without dirs",
+                OpWalk::new().with_include_dirs(false),
+                vec!["x/a", "x/y/b", "x/y/z/c"],
+            ),
+            (
+                "walk with depth 1",
+                OpWalk::new().with_max_depth(1),
+                vec!["x/a", "x/y/"],
+            ),
+            (
+                "walk with depth 2",
+                OpWalk::new().with_max_depth(2),
+                vec!["x/a", "x/y/", "x/y/b", "x/y/z/"],
+            ),
+        ];
+
+        for scan in [false, true] {
+            for (name, args, expected) in &cases {
+                let expected: HashSet<String> = expected.iter().map(|v| v.to_string()).collect();
+                assert_eq!(walk(scan, args.clone())?, expected, "{name}, scan: {scan}");
+            }
+        }
+
+        Ok(())
+    }
+}
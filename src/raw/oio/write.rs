@@ -18,6 +18,7 @@ use std::fmt::Formatter;
 use async_trait::async_trait;
 use bytes::Bytes;
 
+use crate::raw::*;
 use crate::*;
 
 /// WriteOperation is the name for APIs of Writer.
@@ -30,12 +31,16 @@ pub enum WriteOperation {
     Append,
     /// Operation for [`Write::close`]
     Close,
+    /// Operation for [`Write::abort`]
+    Abort,
     /// Operation for [`BlockingWrite::write`]
     BlockingWrite,
     /// Operation for [`BlockingWrite::append`]
     BlockingAppend,
     /// Operation for [`BlockingWrite::close`]
     BlockingClose,
+    /// Operation for [`BlockingWrite::abort`]
+    BlockingAbort,
 }
 
 impl WriteOperation {
@@ -59,9 +64,11 @@ impl From<WriteOperation> for &'static str {
             Write => "Writer::write",
             Append => "Writer::append",
             Close => "Writer::close",
+            Abort => "Writer::abort",
             BlockingWrite => "BlockingWriter::write",
             BlockingAppend => "BlockingWriter::append",
             BlockingClose => "BlockingWriter::close",
+            BlockingAbort => "BlockingWriter::abort",
         }
     }
 }
@@ -85,7 +92,17 @@ pub trait Write: Unpin + Send + Sync {
     async fn append(&mut self, bs: Bytes) -> Result<()>;
 
     /// Close the writer and make sure all data has been flushed.
-    async fn close(&mut self) -> Result<()>;
+    ///
+    /// Returns the etag and version of the written object if services
+    /// return them while writing.
+    async fn close(&mut self) -> Result<RpWrite>;
+
+    /// Abort the pending writer.
+    ///
+    /// All data appended so far will be discarded and the target file
+    /// will not be created. Services that buffer nothing before `close`
+    /// can simply return `Ok(())`.
+    async fn abort(&mut self) -> Result<()>;
 }
 
 #[async_trait]
@@ -105,12 +122,19 @@ impl Write for () {
         ))
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn close(&mut self) -> Result<RpWrite> {
         Err(Error::new(
             ErrorKind::Unsupported,
             "output writer doesn't support close",
         ))
     }
+
+    async fn abort(&mut self) -> Result<()> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "output writer doesn't support abort",
+        ))
+    }
 }
 
 /// `Box<dyn Write>` won't implement `Write` automatically. To make Writer
@@ -125,9 +149,13 @@ impl<T: Write + ?Sized> Write for Box<T> {
         (**self).append(bs).await
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn close(&mut self) -> Result<RpWrite> {
         (**self).close().await
     }
+
+    async fn abort(&mut self) -> Result<()> {
+        (**self).abort().await
+    }
 }
 
 /// BlockingWriter is a type erased [`BlockingWrite`]
@@ -142,7 +170,17 @@ pub trait BlockingWrite: Send + Sync + 'static {
     fn append(&mut self, bs: Bytes) -> Result<()>;
 
     /// Close the writer and make sure all data has been flushed.
-    fn close(&mut self) -> Result<()>;
+    ///
+    /// Returns the etag and version of the written object if services
+    /// return them while writing.
+    fn close(&mut self) -> Result<RpWrite>;
+
+    /// Abort the pending writer.
+    ///
+    /// All data appended so far will be discarded and the target file
+    /// will not be created. Services that buffer nothing before `close`
+    /// can simply return `Ok(())`.
+    fn abort(&mut self) -> Result<()>;
 }
 
 impl BlockingWrite for () {
@@ -161,12 +199,19 @@ impl BlockingWrite for () {
         ))
     }
 
-    fn close(&mut self) -> Result<()> {
+    fn close(&mut self) -> Result<RpWrite> {
         Err(Error::new(
             ErrorKind::Unsupported,
             "output writer doesn't support close",
         ))
     }
+
+    fn abort(&mut self) -> Result<()> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "output writer doesn't support abort",
+        ))
+    }
 }
 
 /// `Box<dyn BlockingWrite>` won't implement `BlockingWrite` automatically.
@@ -180,7 +225,11 @@ impl<T: BlockingWrite + ?Sized> BlockingWrite for Box<T> {
         (**self).append(bs)
     }
 
-    fn close(&mut self) -> Result<()> {
+    fn close(&mut self) -> Result<RpWrite> {
         (**self).close()
     }
+
+    fn abort(&mut self) -> Result<()> {
+        (**self).abort()
+    }
 }
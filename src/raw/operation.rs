@@ -39,6 +39,22 @@ pub enum Operation {
     Batch,
     /// Operation for [`crate::raw::Accessor::presign`]
     Presign,
+    /// Operation for [`crate::raw::Accessor::restore`]
+    Restore,
+    /// Operation for [`crate::raw::Accessor::abort_multipart`]
+    AbortMultipart,
+    /// Operation for [`crate::raw::Accessor::rename`]
+    Rename,
+    /// Operation for [`crate::raw::Accessor::copy`]
+    Copy,
+    /// Operation for [`crate::raw::Accessor::get_acl`]
+    GetAcl,
+    /// Operation for [`crate::raw::Accessor::set_acl`]
+    SetAcl,
+    /// Operation for [`crate::raw::Accessor::get_tags`]
+    GetTags,
+    /// Operation for [`crate::raw::Accessor::put_tags`]
+    PutTags,
     /// Operation for [`crate::raw::Accessor::blocking_create`]
     BlockingCreate,
     /// Operation for [`crate::raw::Accessor::blocking_read`]
@@ -87,6 +103,14 @@ impl From<Operation> for &'static str {
             Operation::Scan => "scan",
             Operation::Presign => "presign",
             Operation::Batch => "batch",
+            Operation::Restore => "restore",
+            Operation::AbortMultipart => "abort_multipart",
+            Operation::Rename => "rename",
+            Operation::Copy => "copy",
+            Operation::GetAcl => "get_acl",
+            Operation::SetAcl => "set_acl",
+            Operation::GetTags => "get_tags",
+            Operation::PutTags => "put_tags",
             Operation::BlockingCreate => "blocking_create",
             Operation::BlockingRead => "blocking_read",
             Operation::BlockingWrite => "blocking_write",
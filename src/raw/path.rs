@@ -13,6 +13,9 @@
 // limitations under the License.
 
 use crate::EntryMode;
+use crate::Error;
+use crate::ErrorKind;
+use crate::Result;
 
 /// build_abs_path will build an absolute path with root.
 ///
@@ -89,7 +92,11 @@ pub fn build_rel_path(root: &str, path: &str) -> String {
 /// - All whitespace will be trimmed: ` abc/def ` => `abc/def`
 /// - All leading / will be trimmed: `///abc` => `abc`
 /// - Internal // will be replaced by /: `abc///def` => `abc/def`
+/// - All `.` segments will be removed: `./abc/./def` => `abc/def`
+/// - Path ends with `.` segment is a dir path: `abc/.` => `abc/`
 /// - Empty path will be `/`: `` => `/`
+///
+/// `..` segments will be kept as is, use [`check_path`] to reject them.
 pub fn normalize_path(path: &str) -> String {
     // - all whitespace has been trimmed.
     // - all leading `/` has been trimmed.
@@ -100,14 +107,20 @@ pub fn normalize_path(path: &str) -> String {
         return "/".to_string();
     }
 
-    let has_trailing = path.ends_with('/');
+    // Path ends with `.` segment like `abc/.` is a dir path too.
+    let has_trailing = path.ends_with('/') || path == "." || path.ends_with("/.");
 
     let mut p = path
         .split('/')
-        .filter(|v| !v.is_empty())
+        .filter(|v| !v.is_empty() && *v != ".")
         .collect::<Vec<&str>>()
         .join("/");
 
+    // Path like `./` only contains current dir.
+    if p.is_empty() {
+        return "/".to_string();
+    }
+
     // Append trailing back if input path is endswith `/`.
     if has_trailing {
         p.push('/');
@@ -116,6 +129,29 @@ pub fn normalize_path(path: &str) -> String {
     p
 }
 
+/// Check if given path is safe to be sent to services.
+///
+/// # Rules
+///
+/// - Path must not contain `..` segments: `abc/../def` is rejected since
+///   services resolve it differently, and some of them allow escaping
+///   from root.
+/// - Path must not contain embedded NUL: `abc\0def` is rejected.
+///
+/// Input path should be normalized by [`normalize_path`] first.
+pub fn check_path(path: &str) -> Result<()> {
+    let reason = if path.contains('\0') {
+        "path contains NUL character"
+    } else if path.split('/').any(|v| v == "..") {
+        "path contains `..` segment"
+    } else {
+        return Ok(());
+    };
+
+    Err(Error::new(ErrorKind::ConfigInvalid, reason)
+        .with_context("path", path.escape_debug().to_string()))
+}
+
 /// Make sure root is normalized to style like `/abc/def/`.
 ///
 /// # Normalize Rules
@@ -238,6 +274,13 @@ mod tests {
             ("file path contains ///", "abc///def", "abc/def"),
             ("dir path contains ///", "abc///def///", "abc/def/"),
             ("file with whitespace", "abc/def   ", "abc/def"),
+            ("current dir", ".", "/"),
+            ("current dir path", "./", "/"),
+            ("file path starts with ./", "./abc", "abc"),
+            ("dir path contains /./", "abc/./def/./", "abc/def/"),
+            ("dir path ends with /.", "abc/.", "abc/"),
+            ("file name starts with .", ".abc/.def", ".abc/.def"),
+            ("parent dir is kept", "abc/../def", "abc/../def"),
         ];
 
         for (name, input, expect) in cases {
@@ -245,6 +288,75 @@ mod tests {
         }
     }
 
+    /// Normalized path must be stable and well formed for any input.
+    #[test]
+    fn test_normalize_path_properties() {
+        let segments = ["", ".", "..", "a", "b.c", "..d"];
+
+        // Build all paths with up to 3 segments, with and without leading
+        // and trailing `/`.
+        let mut inputs = vec![String::new()];
+        for _ in 0..3 {
+            let mut next = inputs.clone();
+            for input in &inputs {
+                for seg in segments {
+                    next.push(format!("{input}/{seg}"));
+                    next.push(format!("{input}{seg}/"));
+                }
+            }
+            inputs = next;
+        }
+
+        for input in inputs {
+            let p = normalize_path(&input);
+
+            assert_eq!(
+                normalize_path(&p),
+                p,
+                "normalize must be idempotent: {input:?}"
+            );
+            assert!(!p.is_empty(), "{input:?}");
+            if p != "/" {
+                assert!(!p.starts_with('/'), "{input:?} => {p:?}");
+                assert!(!p.contains("//"), "{input:?} => {p:?}");
+                assert!(!p.split('/').any(|v| v == "."), "{input:?} => {p:?}");
+            }
+            let input_is_dir = input.trim().ends_with('/') || input.trim().ends_with("/.");
+            assert_eq!(
+                p.ends_with('/'),
+                p == "/" || input_is_dir,
+                "{input:?} => {p:?}"
+            );
+            assert_eq!(
+                check_path(&p).is_err(),
+                p.split('/').any(|v| v == ".."),
+                "{input:?} => {p:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_path() {
+        let cases = vec![
+            ("file path", "abc/def", true),
+            ("dir path", "abc/def/", true),
+            ("root path", "/", true),
+            ("name contains ..", "abc..def/..ghi", true),
+            ("parent dir", "../abc", false),
+            ("parent dir in middle", "abc/../def", false),
+            ("parent dir at end", "abc/..", false),
+            ("embedded NUL", "abc\0def", false),
+        ];
+
+        for (name, input, expect) in cases {
+            let res = check_path(input);
+            assert_eq!(res.is_ok(), expect, "{name}");
+            if let Err(err) = res {
+                assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "{name}");
+            }
+        }
+    }
+
     #[test]
     fn test_normalize_root() {
         let cases = vec![
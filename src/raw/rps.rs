@@ -12,8 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+
 use http::Request;
 
+use crate::raw::BytesContentRange;
 use crate::*;
 
 /// Reply for `create` operation
@@ -116,6 +119,32 @@ impl RpRead {
         RpRead { meta }
     }
 
+    /// Get the resolved absolute offset of returned content.
+    ///
+    /// Returns `None` if services didn't report the content range, for
+    /// example, while reading the whole object.
+    pub fn offset(&self) -> Option<u64> {
+        self.content_range()
+            .and_then(|v| v.range())
+            .map(|v| v.start)
+    }
+
+    /// Get the total size of the object.
+    ///
+    /// Returns `None` if services didn't report the content range.
+    pub fn total_size(&self) -> Option<u64> {
+        self.content_range().and_then(|v| v.size())
+    }
+
+    /// Get the content range returned by services.
+    pub(crate) fn content_range(&self) -> Option<BytesContentRange> {
+        if self.meta.bit().contains(Metakey::ContentRange) {
+            self.meta.content_range()
+        } else {
+            None
+        }
+    }
+
     /// Get a ref of metadata.
     pub fn metadata(&self) -> &Metadata {
         &self.meta
@@ -127,6 +156,76 @@ impl RpRead {
     }
 }
 
+/// Reply for `restore` operation.
+#[derive(Debug, Clone, Default)]
+pub struct RpRestore {}
+
+/// Reply for `abort_multipart` operation.
+#[derive(Debug, Clone, Default)]
+pub struct RpAbortMultipart {}
+
+/// Reply for `rename` operation.
+#[derive(Debug, Clone, Default)]
+pub struct RpRename {}
+
+/// Reply for `copy` operation.
+#[derive(Debug, Clone, Default)]
+pub struct RpCopy {}
+
+/// Reply for `get_acl` operation.
+#[derive(Debug, Clone)]
+pub struct RpGetAcl {
+    acl: Acl,
+}
+
+impl RpGetAcl {
+    /// Create a new reply for get_acl.
+    pub fn new(acl: Acl) -> Self {
+        RpGetAcl { acl }
+    }
+
+    /// Get a ref of acl.
+    pub fn acl(&self) -> &Acl {
+        &self.acl
+    }
+
+    /// Consume reply to get the acl.
+    pub fn into_acl(self) -> Acl {
+        self.acl
+    }
+}
+
+/// Reply for `set_acl` operation.
+#[derive(Debug, Clone, Default)]
+pub struct RpSetAcl {}
+
+/// Reply for `get_tags` operation.
+#[derive(Debug, Clone)]
+pub struct RpGetTags {
+    tags: HashMap<String, String>,
+}
+
+impl RpGetTags {
+    /// Create a new reply for get_tags.
+    pub fn new(tags: HashMap<String, String>) -> Self {
+        RpGetTags { tags }
+    }
+
+    /// Get a ref of tags.
+    pub fn tags(&self) -> &HashMap<String, String> {
+        &self.tags
+    }
+
+    /// Consume reply to get the tags.
+    pub fn into_tags(self) -> HashMap<String, String> {
+        self.tags
+    }
+}
+
+/// Reply for `put_tags` operation.
+#[derive(Debug, Clone, Default)]
+pub struct RpPutTags {}
+
 /// Reply for `batch` operation.
 pub struct RpBatch {
     results: BatchedResults,
@@ -215,12 +314,73 @@ impl RpStat {
 
 /// Reply for `write` operation.
 #[derive(Debug, Clone, Default)]
-pub struct RpWrite {}
+pub struct RpWrite {
+    session: Option<String>,
+    offset: u64,
+    etag: Option<String>,
+    version: Option<String>,
+}
 
 impl RpWrite {
     /// Create a new reply for write.
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Get the upload session of this write.
+    ///
+    /// The session could be passed to [`OpWrite::with_session`] to resume
+    /// this write later, or passed to [`OpAbortMultipart::new`] to abort it.
+    ///
+    /// [`OpWrite::with_session`]: crate::ops::OpWrite::with_session
+    /// [`OpAbortMultipart::new`]: crate::ops::OpAbortMultipart::new
+    pub fn session(&self) -> Option<&str> {
+        self.session.as_deref()
+    }
+
+    /// Set the upload session of this write.
+    pub fn with_session(mut self, session: &str) -> Self {
+        self.session = Some(session.to_string());
+        self
+    }
+
+    /// Get the size of bytes that have been committed by the session.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Set the size of bytes that have been committed by the session.
+    pub fn with_offset(mut self, offset: u64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Get the etag of the written object.
+    ///
+    /// Only available in the reply returned by `close`, and will be `None`
+    /// if services don't return it while writing.
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    /// Set the etag of the written object.
+    pub fn with_etag(mut self, etag: &str) -> Self {
+        self.etag = Some(etag.to_string());
+        self
+    }
+
+    /// Get the version id of the written object.
+    ///
+    /// Only available in the reply returned by `close` for services that
+    /// enable versioning.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Set the version id of the written object.
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_string());
+        self
     }
 }
 
@@ -270,4 +430,23 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_rp_read_range() {
+        let rp = RpRead::new(1024);
+        assert_eq!(rp.offset(), None);
+        assert_eq!(rp.total_size(), None);
+
+        let rp = RpRead::with_metadata(
+            Metadata::new(EntryMode::FILE)
+                .with_content_length(8)
+                .with_content_range(
+                    BytesContentRange::default()
+                        .with_range(1016, 1023)
+                        .with_size(1024),
+                ),
+        );
+        assert_eq!(rp.offset(), Some(1016));
+        assert_eq!(rp.total_size(), Some(1024));
+    }
 }
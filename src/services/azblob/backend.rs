@@ -20,14 +20,21 @@ use std::mem;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use bytes::Buf;
+use bytes::Bytes;
 use http::header::HeaderName;
 use http::header::CONTENT_LENGTH;
 use http::header::CONTENT_TYPE;
+use http::header::IF_MATCH;
+use http::header::IF_NONE_MATCH;
+use http::HeaderMap;
 use http::Request;
 use http::Response;
 use http::StatusCode;
 use log::debug;
 use reqsign::AzureStorageSigner;
+use serde::Deserialize;
+use serde::Serialize;
 
 use super::error::parse_error;
 use super::pager::AzblobPager;
@@ -38,6 +45,16 @@ use crate::types::Metadata;
 use crate::*;
 
 const X_MS_BLOB_TYPE: &str = "x-ms-blob-type";
+const X_MS_BLOB_CONTENT_TYPE: &str = "x-ms-blob-content-type";
+const X_MS_ACCESS_TIER: &str = "x-ms-access-tier";
+const X_MS_VERSION: &str = "x-ms-version";
+pub(super) const X_MS_VERSION_ID: &str = "x-ms-version-id";
+
+/// Blob tags are only available since `2019-12-12`.
+const BLOB_TAGS_VERSION: &str = "2019-12-12";
+
+/// Azure allows at most 4000 MiB in one block.
+const MAX_WRITE_BLOCK_SIZE: usize = 4000 * 1024 * 1024;
 
 /// Azure Storage Blob services support.
 ///
@@ -49,7 +66,7 @@ const X_MS_BLOB_TYPE: &str = "x-ms-blob-type";
 /// - [x] write
 /// - [x] list
 /// - [x] scan
-/// - [ ] presign
+/// - [x] presign (only with `sas_token`)
 /// - [ ] blocking
 ///
 /// # Configuration
@@ -59,6 +76,10 @@ const X_MS_BLOB_TYPE: &str = "x-ms-blob-type";
 /// - `endpoint`: Set the endpoint for backend.
 /// - `account_name`: Set the account_name for backend.
 /// - `account_key`: Set the account_key for backend.
+/// - `sas_token`: Set the sas_token for backend.
+/// - `write_block_size`: Set the block size of staged block uploads.
+/// - `write_concurrency`: Set the max concurrent block uploads of staged block uploads.
+/// - `access_tier`: Set the access tier of written blobs, available values are `Hot`, `Cool` and `Archive`.
 ///
 /// Refer to public API docs for more information.
 ///
@@ -122,6 +143,9 @@ pub struct AzblobBuilder {
     account_name: Option<String>,
     account_key: Option<String>,
     sas_token: Option<String>,
+    write_block_size: Option<usize>,
+    write_concurrency: Option<usize>,
+    access_tier: Option<String>,
     http_client: Option<HttpClient>,
 }
 
@@ -142,6 +166,9 @@ impl Debug for AzblobBuilder {
         if self.sas_token.is_some() {
             ds.field("sas_token", &"<redacted>");
         }
+        ds.field("write_block_size", &self.write_block_size);
+        ds.field("write_concurrency", &self.write_concurrency);
+        ds.field("access_tier", &self.access_tier);
 
         ds.finish()
     }
@@ -207,12 +234,15 @@ impl AzblobBuilder {
 
     /// Set sas_token of this backend.
     ///
-    /// - If sas_token is set, we will take user's input first.
-    /// - If not, we will try to load it from environment.
+    /// Both account SAS and container SAS are supported. If sas_token is
+    /// set, the token will be appended into the query of every request
+    /// instead of signing with shared key, so `account_key` must not be set.
     ///
     /// See [Grant limited access to Azure Storage resources using shared access signatures (SAS)](https://learn.microsoft.com/en-us/azure/storage/common/storage-sas-overview)
     /// for more info.
     pub fn sas_token(&mut self, sas_token: &str) -> &mut Self {
+        // Allow users to input sas token copied from url like `?sv=...`.
+        let sas_token = sas_token.trim_start_matches('?');
         if !sas_token.is_empty() {
             self.sas_token = Some(sas_token.to_string());
         }
@@ -220,6 +250,47 @@ impl AzblobBuilder {
         self
     }
 
+    /// Set the block size of staged block uploads.
+    ///
+    /// Bytes appended by writer will be buffered until `write_block_size`
+    /// is reached and then uploaded as one block via `Put Block`. All
+    /// blocks will be committed via `Put Block List` while closing.
+    ///
+    /// Azure allows at most 4000 MiB in one block, building will fail with
+    /// [`ErrorKind::ConfigInvalid`] if given size is zero or larger.
+    ///
+    /// If not set, every append will be uploaded as one block directly.
+    pub fn write_block_size(&mut self, size: usize) -> &mut Self {
+        self.write_block_size = Some(size);
+        self
+    }
+
+    /// Set the max concurrent block uploads of staged block uploads.
+    ///
+    /// Blocks will be uploaded in parallel while users keep appending,
+    /// at most `write_block_size * write_concurrency` bytes will be kept
+    /// in memory.
+    ///
+    /// Default to `1` which means blocks will be uploaded one by one.
+    pub fn write_concurrency(&mut self, concurrency: usize) -> &mut Self {
+        self.write_concurrency = Some(concurrency);
+        self
+    }
+
+    /// Set the access tier of written blobs.
+    ///
+    /// Available values are `Hot`, `Cool` and `Archive`. The tier will be
+    /// sent via `x-ms-access-tier` while writing, and returned as
+    /// [`Metadata::storage_class`] while stating.
+    ///
+    /// If not set, the default access tier of the account will be used.
+    pub fn access_tier(&mut self, tier: &str) -> &mut Self {
+        if !tier.is_empty() {
+            self.access_tier = Some(tier.to_string());
+        }
+        self
+    }
+
     /// Specify the http client that used by this service.
     ///
     /// # Notes
@@ -329,6 +400,13 @@ impl Builder for AzblobBuilder {
         map.get("account_name").map(|v| builder.account_name(v));
         map.get("account_key").map(|v| builder.account_key(v));
         map.get("sas_token").map(|v| builder.sas_token(v));
+        map.get("write_block_size")
+            .and_then(|v| v.parse::<usize>().ok())
+            .map(|v| builder.write_block_size(v));
+        map.get("write_concurrency")
+            .and_then(|v| v.parse::<usize>().ok())
+            .map(|v| builder.write_concurrency(v));
+        map.get("access_tier").map(|v| builder.access_tier(v));
 
         builder
     }
@@ -354,6 +432,10 @@ impl Builder for AzblobBuilder {
                 .with_operation("Builder::build")
                 .with_context("service", Scheme::Azblob)),
         }?;
+        parse_endpoint(&endpoint).map_err(|e| {
+            e.with_operation("Builder::build")
+                .with_context("service", Scheme::Azblob)
+        })?;
         debug!("backend use endpoint {}", &container);
 
         let client = if let Some(client) = self.http_client.take() {
@@ -365,10 +447,53 @@ impl Builder for AzblobBuilder {
             })?
         };
 
+        if self.sas_token.is_some() && self.account_key.is_some() {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "sas_token and account_key can't be set at the same time",
+            )
+            .with_operation("Builder::build")
+            .with_context("service", Scheme::Azblob));
+        }
+
+        if let Some(size) = self.write_block_size {
+            if size == 0 || size > MAX_WRITE_BLOCK_SIZE {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "write_block_size must be between 1 byte and 4000 MiB",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Azblob)
+                .with_context("write_block_size", size.to_string()));
+            }
+        }
+        let write_concurrency = self.write_concurrency.unwrap_or(1);
+        if write_concurrency == 0 {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "write_concurrency must be greater than 0",
+            )
+            .with_operation("Builder::build")
+            .with_context("service", Scheme::Azblob));
+        }
+        let access_tier = match self.access_tier.as_deref() {
+            None => None,
+            Some(v) => Some(
+                ["Hot", "Cool", "Archive"]
+                    .into_iter()
+                    .find(|tier| tier.eq_ignore_ascii_case(v))
+                    .ok_or_else(|| {
+                        Error::new(ErrorKind::ConfigInvalid, "access_tier value is invalid")
+                            .with_operation("Builder::build")
+                            .with_context("service", Scheme::Azblob)
+                            .with_context("access_tier", v)
+                    })?
+                    .to_string(),
+            ),
+        };
+
         let mut signer_builder = AzureStorageSigner::builder();
-        if let Some(sas_token) = &self.sas_token {
-            signer_builder.security_token(sas_token);
-        } else if let (Some(name), Some(key)) = (&self.account_name, &self.account_key) {
+        if let (Some(name), Some(key)) = (&self.account_name, &self.account_key) {
             signer_builder.account_name(name).account_key(key);
         }
 
@@ -386,6 +511,10 @@ impl Builder for AzblobBuilder {
             root,
             endpoint,
             signer: Arc::new(signer),
+            sas_token: self.sas_token.take(),
+            write_block_size: self.write_block_size,
+            write_concurrency,
+            access_tier,
             container: self.container.clone(),
             client,
             _account_name: mem::take(&mut self.account_name).unwrap_or_default(),
@@ -402,6 +531,11 @@ pub struct AzblobBackend {
     root: String, // root will be "/" or /abc/
     endpoint: String,
     pub signer: Arc<AzureStorageSigner>,
+    /// Requests will be authorized by sas token instead of signer if set.
+    sas_token: Option<String>,
+    pub(super) write_block_size: Option<usize>,
+    pub(super) write_concurrency: usize,
+    access_tier: Option<String>,
     _account_name: String,
 }
 
@@ -409,7 +543,7 @@ pub struct AzblobBackend {
 impl Accessor for AzblobBackend {
     type Reader = IncomingAsyncBody;
     type BlockingReader = ();
-    type Writer = AzblobWriter;
+    type Writer = oio::MultipartUploadWriter<AzblobWriter>;
     type BlockingWriter = ();
     type Pager = AzblobPager;
     type BlockingPager = ();
@@ -418,11 +552,42 @@ impl Accessor for AzblobBackend {
         use AccessorCapability::*;
         use AccessorHint::*;
 
+        // Presign is only supported with sas token.
+        let presign = self.sas_token.is_some();
+        let mut capabilities = Read | Write | List | Scan;
+        if presign {
+            capabilities |= Presign;
+        }
+
         let mut am = AccessorInfo::default();
         am.set_scheme(Scheme::Azblob)
             .set_root(&self.root)
             .set_name(&self.container)
-            .set_capabilities(Read | Write | List | Scan)
+            .set_capabilities(capabilities)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                write: true,
+                write_can_append: true,
+                write_multi: true,
+                write_with_content_type: true,
+                write_with_if_not_exists: true,
+                write_with_if_match: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                scan: true,
+                presign,
+                presign_read: presign,
+                presign_stat: presign,
+                presign_write: presign,
+                tags: true,
+                ..Default::default()
+            })
             .set_hints(ReadStreamable);
 
         am
@@ -431,7 +596,7 @@ impl Accessor for AzblobBackend {
     async fn create(&self, path: &str, _: OpCreate) -> Result<RpCreate> {
         let mut req = self.azblob_put_blob_request(path, Some(0), None, AsyncBody::Empty)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        self.sign(&mut req)?;
 
         let resp = self.client.send_async(req).await?;
 
@@ -447,31 +612,27 @@ impl Accessor for AzblobBackend {
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        let resp = self.azblob_get_blob(path, args.range()).await?;
+        let range = args.range();
+        let resp = self.azblob_get_blob(path, range).await?;
 
         let status = resp.status();
 
         match status {
             StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
-                let meta = parse_into_metadata(path, resp.headers())?;
-
-                Ok((RpRead::with_metadata(meta), resp.into_body()))
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
             }
             _ => Err(parse_error(resp).await?),
         }
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        if args.append() {
-            return Err(Error::new(
-                ErrorKind::Unsupported,
-                "append write is not supported",
-            ));
-        }
+        let w = AzblobWriter::new(self.clone(), args, path.to_string());
 
         Ok((
             RpWrite::default(),
-            AzblobWriter::new(self.clone(), args, path.to_string()),
+            oio::MultipartUploadWriter::new(w, self.write_block_size)
+                .with_concurrency(self.write_concurrency),
         ))
     }
 
@@ -486,7 +647,13 @@ impl Accessor for AzblobBackend {
         let status = resp.status();
 
         match status {
-            StatusCode::OK => parse_into_metadata(path, resp.headers()).map(RpStat::new),
+            StatusCode::OK => {
+                let mut meta = parse_into_metadata(path, resp.headers())?;
+                if let Some(tier) = parse_access_tier(resp.headers())? {
+                    meta.set_storage_class(tier);
+                }
+                Ok(RpStat::new(meta))
+            }
             StatusCode::NOT_FOUND if path.ends_with('/') => {
                 Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
             }
@@ -505,6 +672,45 @@ impl Accessor for AzblobBackend {
         }
     }
 
+    async fn get_tags(&self, path: &str, _: OpGetTags) -> Result<RpGetTags> {
+        let resp = self.azblob_get_blob_tags(path).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let result: BlobTags =
+                    quick_xml::de::from_reader(bs.reader()).map_err(new_xml_deserialize_error)?;
+
+                Ok(RpGetTags::new(
+                    result
+                        .tag_set
+                        .tag
+                        .into_iter()
+                        .map(|t| (t.key, t.value))
+                        .collect(),
+                ))
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn put_tags(&self, path: &str, args: OpPutTags) -> Result<RpPutTags> {
+        let resp = self.azblob_set_blob_tags(path, args.tags()).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::NO_CONTENT | StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(RpPutTags::default())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
         let op = AzblobPager::new(
             Arc::new(self.clone()),
@@ -528,14 +734,64 @@ impl Accessor for AzblobBackend {
 
         Ok((RpScan::default(), op))
     }
+
+    fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        if self.sas_token.is_none() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "presign is only supported with sas_token",
+            ));
+        }
+
+        // We will not send this request out, just for signing.
+        let mut req = match args.operation() {
+            PresignOperation::Stat(_) => self.azblob_get_blob_properties_request(path)?,
+            PresignOperation::Read(v) => self.azblob_get_blob_request(path, v.range())?,
+            PresignOperation::Write(_) => {
+                self.azblob_put_blob_request(path, None, None, AsyncBody::Empty)?
+            }
+        };
+
+        // The sas token carries its own expiry, so the presigned request
+        // will expire no later than the sas token whatever `expire` is.
+        self.sign(&mut req)?;
+
+        // We don't need this request anymore, consume it directly.
+        let (parts, _) = req.into_parts();
+
+        Ok(RpPresign::new(PresignedRequest::new(
+            parts.method,
+            parts.uri,
+            parts.headers,
+        )))
+    }
 }
 
 impl AzblobBackend {
-    async fn azblob_get_blob(
-        &self,
-        path: &str,
-        range: BytesRange,
-    ) -> Result<Response<IncomingAsyncBody>> {
+    /// Authorize the request.
+    ///
+    /// The sas token will be appended into the query of request if it's
+    /// set, otherwise the request will be signed by signer.
+    pub fn sign(&self, req: &mut Request<AsyncBody>) -> Result<()> {
+        let token = match &self.sas_token {
+            Some(token) => token,
+            None => return self.signer.sign(req).map_err(new_request_sign_error),
+        };
+
+        let sep = if req.uri().query().is_some() {
+            '&'
+        } else {
+            '?'
+        };
+        let uri = format!("{}{sep}{token}", req.uri());
+        *req.uri_mut() = uri
+            .parse()
+            .map_err(|e: http::uri::InvalidUri| new_request_build_error(e.into()))?;
+
+        Ok(())
+    }
+
+    fn azblob_get_blob_request(&self, path: &str, range: BytesRange) -> Result<Request<AsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
         let url = format!(
@@ -561,11 +817,21 @@ impl AzblobBackend {
             req = req.header(http::header::RANGE, range.to_header());
         }
 
-        let mut req = req
+        let req = req
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        Ok(req)
+    }
+
+    async fn azblob_get_blob(
+        &self,
+        path: &str,
+        range: BytesRange,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let mut req = self.azblob_get_blob_request(path, range)?;
+
+        self.sign(&mut req)?;
 
         self.client.send_async(req).await
     }
@@ -598,13 +864,101 @@ impl AzblobBackend {
 
         req = req.header(HeaderName::from_static(X_MS_BLOB_TYPE), "BlockBlob");
 
+        if let Some(tier) = &self.access_tier {
+            req = req.header(HeaderName::from_static(X_MS_ACCESS_TIER), tier)
+        }
+
         // Set body
         let req = req.body(body).map_err(new_request_build_error)?;
 
         Ok(req)
     }
 
-    async fn azblob_get_blob_properties(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+    /// Build the request to stage a block of block blob.
+    ///
+    /// ref: https://learn.microsoft.com/en-us/rest/api/storageservices/put-block
+    pub fn azblob_put_block_request(
+        &self,
+        path: &str,
+        block_id: &str,
+        size: Option<usize>,
+        body: AsyncBody,
+    ) -> Result<Request<AsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!(
+            "{}/{}/{}?comp=block&blockid={}",
+            self.endpoint,
+            self.container,
+            percent_encode_path(&p),
+            percent_encode_path(block_id)
+        );
+
+        let mut req = Request::put(&url);
+
+        if let Some(size) = size {
+            req = req.header(CONTENT_LENGTH, size)
+        }
+
+        // Set body
+        let req = req.body(body).map_err(new_request_build_error)?;
+
+        Ok(req)
+    }
+
+    /// Commit staged blocks as the content of block blob.
+    ///
+    /// ref: https://learn.microsoft.com/en-us/rest/api/storageservices/put-block-list
+    pub async fn azblob_put_block_list(
+        &self,
+        path: &str,
+        block_ids: &[String],
+        args: &OpWrite,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!(
+            "{}/{}/{}?comp=blocklist",
+            self.endpoint,
+            self.container,
+            percent_encode_path(&p)
+        );
+
+        let mut req = Request::put(&url);
+
+        if let Some(ty) = args.content_type() {
+            req = req.header(HeaderName::from_static(X_MS_BLOB_CONTENT_TYPE), ty)
+        }
+        if let Some(tier) = &self.access_tier {
+            req = req.header(HeaderName::from_static(X_MS_ACCESS_TIER), tier)
+        }
+        // Only commit the blob if it doesn't exist.
+        if args.if_not_exists() {
+            req = req.header(IF_NONE_MATCH, "*");
+        }
+        // Only commit the blob if its etag matches.
+        if let Some(etag) = args.if_match() {
+            req = req.header(IF_MATCH, etag);
+        }
+
+        let content = quick_xml::se::to_string(&PutBlockListRequest {
+            latest: block_ids.to_vec(),
+        })
+        .map_err(new_xml_deserialize_error)?;
+        // Make sure content length has been set to avoid put with chunked encoding.
+        let req = req.header(CONTENT_LENGTH, content.len());
+        let req = req.header(CONTENT_TYPE, "application/xml");
+
+        let mut req = req
+            .body(AsyncBody::Bytes(Bytes::from(content)))
+            .map_err(new_request_build_error)?;
+
+        self.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+
+    fn azblob_get_blob_properties_request(&self, path: &str) -> Result<Request<AsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
         let url = format!(
@@ -616,11 +970,17 @@ impl AzblobBackend {
 
         let req = Request::head(&url);
 
-        let mut req = req
+        let req = req
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        Ok(req)
+    }
+
+    async fn azblob_get_blob_properties(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let mut req = self.azblob_get_blob_properties_request(path)?;
+
+        self.sign(&mut req)?;
 
         self.client.send_async(req).await
     }
@@ -641,7 +1001,72 @@ impl AzblobBackend {
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        self.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+
+    /// ref: https://learn.microsoft.com/en-us/rest/api/storageservices/get-blob-tags
+    async fn azblob_get_blob_tags(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!(
+            "{}/{}/{}?comp=tags",
+            self.endpoint,
+            self.container,
+            percent_encode_path(&p)
+        );
+
+        let mut req = Request::get(&url)
+            .header(X_MS_VERSION, BLOB_TAGS_VERSION)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+
+    /// ref: https://learn.microsoft.com/en-us/rest/api/storageservices/set-blob-tags
+    async fn azblob_set_blob_tags(
+        &self,
+        path: &str,
+        tags: &HashMap<String, String>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!(
+            "{}/{}/{}?comp=tags",
+            self.endpoint,
+            self.container,
+            percent_encode_path(&p)
+        );
+
+        let mut tags: Vec<_> = tags.iter().collect();
+        tags.sort();
+
+        let content = quick_xml::se::to_string(&BlobTags {
+            tag_set: BlobTagSet {
+                tag: tags
+                    .into_iter()
+                    .map(|(k, v)| BlobTag {
+                        key: k.to_string(),
+                        value: v.to_string(),
+                    })
+                    .collect(),
+            },
+        })
+        .map_err(new_xml_deserialize_error)?;
+
+        let mut req = Request::put(&url)
+            .header(X_MS_VERSION, BLOB_TAGS_VERSION)
+            // Make sure content length has been set to avoid put with chunked encoding.
+            .header(CONTENT_LENGTH, content.len())
+            .header(CONTENT_TYPE, "application/xml")
+            .body(AsyncBody::Bytes(Bytes::from(content)))
+            .map_err(new_request_build_error)?;
+
+        self.sign(&mut req)?;
 
         self.client.send_async(req).await
     }
@@ -677,15 +1102,70 @@ impl AzblobBackend {
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        self.sign(&mut req)?;
 
         self.client.send_async(req).await
     }
 }
 
+/// Parse access tier from `x-ms-access-tier` header.
+fn parse_access_tier(headers: &HeaderMap) -> Result<Option<&str>> {
+    match headers.get(X_MS_ACCESS_TIER) {
+        None => Ok(None),
+        Some(v) => v.to_str().map(Some).map_err(|e| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value is not valid utf-8 string",
+            )
+            .with_operation("azblob::parse_access_tier")
+            .set_source(e)
+        }),
+    }
+}
+
+/// Request of PutBlockList
+#[derive(Default, Debug, Serialize)]
+#[serde(default, rename = "BlockList", rename_all = "PascalCase")]
+struct PutBlockListRequest {
+    latest: Vec<String>,
+}
+
+/// Result of GetBlobTags and request of SetBlobTags.
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(default, rename = "Tags", rename_all = "PascalCase")]
+struct BlobTags {
+    tag_set: BlobTagSet,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct BlobTagSet {
+    tag: Vec<BlobTag>,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct BlobTag {
+    key: String,
+    value: String,
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
+    use http::header::AUTHORIZATION;
+    use time::Duration;
+
     use super::AzblobBuilder;
+    use crate::ops::OpPresign;
+    use crate::ops::OpRead;
+    use crate::raw::*;
+    use crate::Builder;
+    use crate::ErrorKind;
+    use crate::Operator;
+
+    const SAS_TOKEN: &str = "sv=2021-01-01&ss=b&srt=c&sp=rwdlaciytfx&se=2022-01-01T11:00:14Z&st=2022-01-02T03:00:14Z&spr=https&sig=KEllk4N8f7rJfLjQCmikL2fRVt%2B%2Bl73UBkbgH%2FK3VGE%3D";
 
     #[test]
     fn test_builder_from_connection_string() {
@@ -764,4 +1244,340 @@ SharedAccessSignature=sv=2021-01-01&ss=b&srt=c&sp=rwdlaciytfx&se=2022-01-01T11:0
         assert_eq!(builder.account_name, None);
         assert_eq!(builder.account_key, None);
     }
+
+    #[test]
+    fn test_build_with_invalid_endpoint() {
+        for endpoint in ["127.0.0.1:10000/devstoreaccount1", "htp://127.0.0.1:10000"] {
+            let mut builder = AzblobBuilder::default();
+            builder
+                .container("test")
+                .endpoint(endpoint)
+                .account_name("devstoreaccount1")
+                .account_key("account-key");
+
+            let err = builder
+                .build()
+                .expect_err("build with invalid endpoint must fail");
+            assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "{endpoint}");
+        }
+    }
+
+    #[test]
+    fn test_build_with_sas_token_and_account_key() {
+        let mut builder = AzblobBuilder::default();
+        builder
+            .container("test")
+            .endpoint("http://127.0.0.1:10000/devstoreaccount1")
+            .account_name("devstoreaccount1")
+            .account_key("account-key")
+            .sas_token(SAS_TOKEN);
+
+        let err = builder
+            .build()
+            .expect_err("build with both sas_token and account_key must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[test]
+    fn test_sign_with_sas_token() {
+        let mut builder = AzblobBuilder::default();
+        builder
+            .container("test")
+            .endpoint("http://127.0.0.1:10000/devstoreaccount1")
+            .sas_token(&format!("?{SAS_TOKEN}"));
+        let backend = builder.build().expect("build must succeed");
+
+        let mut req = backend
+            .azblob_put_blob_request("path/to/file", Some(0), None, AsyncBody::Empty)
+            .expect("build request must succeed");
+        backend.sign(&mut req).expect("sign must succeed");
+        assert_eq!(
+            req.uri().to_string(),
+            format!("http://127.0.0.1:10000/devstoreaccount1/test/path/to/file?{SAS_TOKEN}")
+        );
+        assert!(req.headers().get(AUTHORIZATION).is_none());
+
+        // The token should be appended after existing query.
+        let mut req = http::Request::get(
+            "http://127.0.0.1:10000/devstoreaccount1/test?restype=container&comp=list",
+        )
+        .body(AsyncBody::Empty)
+        .expect("build request must succeed");
+        backend.sign(&mut req).expect("sign must succeed");
+        assert_eq!(
+            req.uri().to_string(),
+            format!("http://127.0.0.1:10000/devstoreaccount1/test?restype=container&comp=list&{SAS_TOKEN}")
+        );
+        assert!(req.headers().get(AUTHORIZATION).is_none());
+    }
+
+    #[test]
+    fn test_presign_with_sas_token() {
+        let mut builder = AzblobBuilder::default();
+        builder
+            .container("test")
+            .endpoint("http://127.0.0.1:10000/devstoreaccount1")
+            .sas_token(SAS_TOKEN);
+        let backend = builder.build().expect("build must succeed");
+        assert!(backend.info().capability().presign_read);
+
+        let rp = backend
+            .presign(
+                "path/to/file",
+                OpPresign::new(OpRead::new(), Duration::hours(1)),
+            )
+            .expect("presign must succeed");
+        let req = rp.into_presigned_request();
+        assert_eq!(req.method(), &http::Method::GET);
+        assert_eq!(
+            req.uri().to_string(),
+            format!("http://127.0.0.1:10000/devstoreaccount1/test/path/to/file?{SAS_TOKEN}")
+        );
+        assert!(req.header().get(AUTHORIZATION).is_none());
+    }
+
+    #[test]
+    fn test_presign_without_sas_token() {
+        let mut builder = AzblobBuilder::default();
+        builder
+            .container("test")
+            .endpoint("http://127.0.0.1:10000/devstoreaccount1")
+            .account_name("devstoreaccount1")
+            .account_key("account-key");
+        let backend = builder.build().expect("build must succeed");
+        assert!(!backend.info().capability().presign);
+
+        let err = backend
+            .presign(
+                "path/to/file",
+                OpPresign::new(OpRead::new(), Duration::hours(1)),
+            )
+            .expect_err("presign must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn test_build_with_invalid_write_options() {
+        type Case = (&'static str, fn(&mut AzblobBuilder));
+        let cases: Vec<Case> = vec![
+            ("write_block_size is zero", |b| {
+                b.write_block_size(0);
+            }),
+            ("write_block_size too large", |b| {
+                b.write_block_size(4001 * 1024 * 1024);
+            }),
+            ("write_concurrency is zero", |b| {
+                b.write_concurrency(0);
+            }),
+            ("access_tier is invalid", |b| {
+                b.access_tier("Frozen");
+            }),
+        ];
+
+        for (name, f) in cases {
+            let mut builder = AzblobBuilder::default();
+            builder
+                .container("test")
+                .endpoint("http://127.0.0.1:10000/devstoreaccount1")
+                .sas_token(SAS_TOKEN);
+            f(&mut builder);
+
+            let err = builder.build().expect_err(name);
+            assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_serialize_put_block_list_request() {
+        let req = super::PutBlockListRequest {
+            latest: vec!["YmxvY2stMQ==".to_string(), "YmxvY2stMg==".to_string()],
+        };
+
+        let actual = quick_xml::se::to_string(&req).expect("must succeed");
+        assert_eq!(
+            actual,
+            "<BlockList><Latest>YmxvY2stMQ==</Latest><Latest>YmxvY2stMg==</Latest></BlockList>"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_staged_block_upload() {
+        use base64::prelude::BASE64_STANDARD;
+        use base64::Engine;
+        use wiremock::matchers::header;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::matchers::query_param;
+        use wiremock::Match;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::Request;
+        use wiremock::ResponseTemplate;
+
+        /// Match block list that contains `n` blocks of the same writer in order.
+        struct BlockListMatcher(usize);
+
+        impl Match for BlockListMatcher {
+            fn matches(&self, req: &Request) -> bool {
+                let body = String::from_utf8_lossy(&req.body);
+                let ids: Vec<String> = body
+                    .split("<Latest>")
+                    .skip(1)
+                    .filter_map(|v| v.split("</Latest>").next())
+                    .filter_map(|v| BASE64_STANDARD.decode(v).ok())
+                    .map(|v| String::from_utf8_lossy(&v).to_string())
+                    .collect();
+                if ids.len() != self.0 {
+                    return false;
+                }
+
+                let prefix = ids[0].trim_end_matches("000001");
+                ids.iter()
+                    .enumerate()
+                    .all(|(idx, id)| id == &format!("{prefix}{:06}", idx + 1))
+            }
+        }
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/test/file"))
+            .and(query_param("comp", "block"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(3)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/test/file"))
+            .and(query_param("comp", "blocklist"))
+            .and(header("x-ms-access-tier", "Cool"))
+            .and(BlockListMatcher(3))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = AzblobBuilder::default();
+        builder
+            .container("test")
+            .endpoint(&mock_server.uri())
+            .sas_token(SAS_TOKEN)
+            .write_block_size(1024)
+            .write_concurrency(4)
+            .access_tier("cool");
+        let op = Operator::new(builder).unwrap().finish();
+
+        let mut w = op.writer("file").await.expect("writer must be created");
+        // Append in chunks that not aligned with block size.
+        for _ in 0..4 {
+            w.append(vec![0; 700]).await.expect("append must succeed");
+        }
+        w.close().await.expect("close must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_write_and_stat_with_access_tier() {
+        use wiremock::matchers::header;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/test/file"))
+            .and(header("x-ms-access-tier", "Archive"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path("/test/file"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-length", "1024")
+                    .insert_header("x-ms-access-tier", "Archive"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = AzblobBuilder::default();
+        builder
+            .container("test")
+            .endpoint(&mock_server.uri())
+            .sas_token(SAS_TOKEN)
+            .access_tier("Archive");
+        let op = Operator::new(builder).unwrap().finish();
+
+        op.write("file", vec![0; 1024])
+            .await
+            .expect("write must succeed");
+
+        let meta = op.stat("file").await.expect("stat must succeed");
+        assert_eq!(meta.storage_class(), Some("Archive"));
+    }
+
+    #[tokio::test]
+    async fn test_get_and_put_tags() {
+        use wiremock::matchers::body_string;
+        use wiremock::matchers::header;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::matchers::query_param;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/test/file"))
+            .and(query_param("comp", "tags"))
+            .and(header("x-ms-version", "2019-12-12"))
+            .and(body_string(
+                "<Tags><TagSet><Tag><Key>env</Key><Value>prod</Value></Tag><Tag><Key>team</Key><Value>data</Value></Tag></TagSet></Tags>",
+            ))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/test/file"))
+            .and(query_param("comp", "tags"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8"?>
+<Tags>
+  <TagSet>
+    <Tag><Key>env</Key><Value>prod</Value></Tag>
+    <Tag><Key>team</Key><Value>data</Value></Tag>
+  </TagSet>
+</Tags>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = AzblobBuilder::default();
+        builder
+            .container("test")
+            .endpoint(&mock_server.uri())
+            .sas_token(SAS_TOKEN);
+        let op = Operator::new(builder).unwrap().finish();
+
+        let tags = HashMap::from([
+            ("env".to_string(), "prod".to_string()),
+            ("team".to_string(), "data".to_string()),
+        ]);
+        op.put_tags("file", tags.clone())
+            .await
+            .expect("put tags must succeed");
+
+        let actual = op.get_tags("file").await.expect("get tags must succeed");
+        assert_eq!(actual, tags);
+    }
 }
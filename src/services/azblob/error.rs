@@ -62,20 +62,37 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
     let (parts, body) = resp.into_parts();
     let bs = body.bytes().await?;
 
-    let (kind, retryable) = match parts.status {
-        StatusCode::NOT_FOUND => (ErrorKind::NotFound, false),
-        StatusCode::FORBIDDEN => (ErrorKind::PermissionDenied, false),
-        StatusCode::INTERNAL_SERVER_ERROR
-        | StatusCode::BAD_GATEWAY
-        | StatusCode::SERVICE_UNAVAILABLE
-        | StatusCode::GATEWAY_TIMEOUT => (ErrorKind::Unexpected, true),
-        _ => (ErrorKind::Unexpected, false),
-    };
+    let (mut kind, mut retryable) = parse_error_status(parts.status);
 
     let mut message = match de::from_reader::<_, AzblobError>(bs.clone().reader()) {
         Ok(azblob_err) => format!("{azblob_err:?}"),
         Err(_) => String::from_utf8_lossy(&bs).into_owned(),
     };
+
+    // All possible error code: <https://learn.microsoft.com/en-us/rest/api/storageservices/blob-service-error-codes>
+    let code = parts
+        .headers
+        .get("x-ms-error-code")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    (kind, retryable) = match (parts.status, code) {
+        // Azblob returns `409 BlobAlreadyExists` for `If-None-Match: *` while
+        // the blob already exists.
+        (StatusCode::CONFLICT, "BlobAlreadyExists") => (ErrorKind::ConditionNotMatch, false),
+        // Azblob returns `503 ServerBusy` while throttling requests.
+        (StatusCode::SERVICE_UNAVAILABLE, "ServerBusy") => (ErrorKind::RateLimited, true),
+        // Signature or account key is not valid, users need to fix their config.
+        (_, "AuthenticationFailed" | "InvalidAuthenticationInfo") => {
+            (ErrorKind::ConfigInvalid, false)
+        }
+        (
+            _,
+            "AuthorizationFailure"
+            | "AuthorizationPermissionMismatch"
+            | "InsufficientAccountPermissions",
+        ) => (ErrorKind::PermissionDenied, false),
+        _ => (kind, retryable),
+    };
     // If there is no body here, fill with error code.
     if message.is_empty() {
         if let Some(v) = parts.headers.get("x-ms-error-code") {
@@ -91,7 +108,11 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
         }
     }
 
-    let mut err = Error::new(kind, &message).with_context("response", format!("{parts:?}"));
+    let mut err = with_error_response_context(Error::new(kind, &message), &parts);
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
 
     if retryable {
         err = err.set_temporary();
@@ -102,6 +123,11 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use futures::stream;
+    use http::header::RETRY_AFTER;
+
     use super::*;
 
     #[test]
@@ -153,4 +179,56 @@ mod tests {
         );
         assert_eq!(out.reason, "invalid receipt format");
     }
+
+    #[tokio::test]
+    async fn test_parse_error_server_busy() -> Result<()> {
+        let body = IncomingAsyncBody::new(Box::new(stream::empty()), None);
+        let resp = Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .header("x-ms-error-code", "ServerBusy")
+            .header(RETRY_AFTER, "5")
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::RateLimited);
+        assert!(err.is_temporary());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(5)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_auth() -> Result<()> {
+        let cases = vec![
+            ("AuthenticationFailed", ErrorKind::ConfigInvalid),
+            ("AuthorizationFailure", ErrorKind::PermissionDenied),
+            (
+                "AuthorizationPermissionMismatch",
+                ErrorKind::PermissionDenied,
+            ),
+        ];
+
+        for (code, kind) in cases {
+            let bs = bytes::Bytes::from(format!(
+                r#"<?xml version="1.0" encoding="utf-8"?>
+<Error>
+  <Code>{code}</Code>
+  <Message>some message</Message>
+</Error>"#
+            ));
+            let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+            let resp = Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .header("x-ms-error-code", code)
+                .body(body)
+                .unwrap();
+
+            let err = parse_error(resp).await?;
+            assert_eq!(err.kind(), kind, "code: {code}");
+            assert!(!err.is_temporary(), "code: {code}");
+        }
+
+        Ok(())
+    }
 }
@@ -13,15 +13,31 @@
 // limitations under the License.
 
 use async_trait::async_trait;
-use bytes::Bytes;
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use http::header::IF_MATCH;
+use http::header::IF_NONE_MATCH;
+use http::HeaderValue;
 use http::StatusCode;
 
 use super::backend::AzblobBackend;
+use super::backend::X_MS_VERSION_ID;
 use super::error::parse_error;
 use crate::ops::OpWrite;
 use crate::raw::*;
 use crate::*;
 
+/// AzblobWriter uploads appended bytes as staged blocks of a block blob,
+/// buffering and concurrent block uploads are handled by
+/// [`oio::MultipartUploadWriter`].
+///
+/// Azblob doesn't have multipart uploads, so they are mapped to blocks:
+///
+/// - The upload id is a random prefix of block ids, unique for every
+///   writer so that blocks staged by concurrent writers of the same blob
+///   will not be mixed.
+/// - The etag of a part is its block id.
+/// - All blocks will be committed in the appended order while completing.
 pub struct AzblobWriter {
     backend: AzblobBackend,
 
@@ -35,20 +51,37 @@ impl AzblobWriter {
     }
 }
 
+/// Generate the base64 encoded block id.
+///
+/// Azure requires all block ids of a blob to have the same length, so the
+/// index will be padded with zeros.
+pub(super) fn format_block_id(prefix: &str, idx: usize) -> String {
+    BASE64_STANDARD.encode(format!("{prefix}-{idx:06}"))
+}
+
 #[async_trait]
-impl oio::Write for AzblobWriter {
-    async fn write(&mut self, bs: Bytes) -> Result<()> {
+impl oio::MultipartUploadWrite for AzblobWriter {
+    async fn write_once(&self, size: u64, body: AsyncBody) -> Result<RpWrite> {
         let mut req = self.backend.azblob_put_blob_request(
             &self.path,
-            Some(bs.len()),
+            Some(size as usize),
             self.op.content_type(),
-            AsyncBody::Bytes(bs),
+            body,
         )?;
 
-        self.backend
-            .signer
-            .sign(&mut req)
-            .map_err(new_request_sign_error)?;
+        // Only put the blob if it doesn't exist.
+        if self.op.if_not_exists() {
+            req.headers_mut()
+                .insert(IF_NONE_MATCH, HeaderValue::from_static("*"));
+        }
+        // Only put the blob if its etag matches.
+        if let Some(etag) = self.op.if_match() {
+            let etag =
+                HeaderValue::from_str(etag).map_err(|e| new_request_build_error(e.into()))?;
+            req.headers_mut().insert(IF_MATCH, etag);
+        }
+
+        self.backend.sign(&mut req)?;
 
         let resp = self.backend.client.send_async(req).await?;
 
@@ -56,23 +89,74 @@ impl oio::Write for AzblobWriter {
 
         match status {
             StatusCode::CREATED | StatusCode::OK => {
+                let rp = parse_into_write_reply(resp.headers(), X_MS_VERSION_ID)?;
+                resp.into_body().consume().await?;
+                Ok(rp)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn initiate_part(&self) -> Result<String> {
+        // Blocks are staged to the blob directly, there is nothing to
+        // initiate.
+        Ok(uuid::Uuid::new_v4().to_string())
+    }
+
+    async fn write_part(
+        &self,
+        upload_id: &str,
+        part_number: usize,
+        size: u64,
+        body: AsyncBody,
+    ) -> Result<oio::MultipartUploadPart> {
+        let block_id = format_block_id(upload_id, part_number);
+
+        let mut req = self.backend.azblob_put_block_request(
+            &self.path,
+            &block_id,
+            Some(size as usize),
+            body,
+        )?;
+
+        self.backend.sign(&mut req)?;
+
+        let resp = self.backend.client.send_async(req).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::CREATED => {
                 resp.into_body().consume().await?;
-                Ok(())
+                Ok(oio::MultipartUploadPart::new(part_number, block_id))
             }
             _ => Err(parse_error(resp).await?),
         }
     }
 
-    async fn append(&mut self, bs: Bytes) -> Result<()> {
-        let _ = bs;
+    async fn complete_part(&self, _: &str, parts: &[oio::MultipartUploadPart]) -> Result<RpWrite> {
+        let block_ids: Vec<String> = parts.iter().map(|part| part.etag.clone()).collect();
+
+        let resp = self
+            .backend
+            .azblob_put_block_list(&self.path, &block_ids, &self.op)
+            .await?;
 
-        Err(Error::new(
-            ErrorKind::Unsupported,
-            "output writer doesn't support append",
-        ))
+        let status = resp.status();
+
+        match status {
+            StatusCode::CREATED | StatusCode::OK => {
+                let rp = parse_into_write_reply(resp.headers(), X_MS_VERSION_ID)?;
+                resp.into_body().consume().await?;
+                Ok(rp)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn abort_part(&self, _: &str) -> Result<()> {
+        // Azblob doesn't have an API to discard staged blocks, blocks
+        // that not committed will be garbage collected after a week.
         Ok(())
     }
 }
@@ -23,6 +23,7 @@ use async_trait::async_trait;
 use http::header::CONTENT_DISPOSITION;
 use http::header::CONTENT_LENGTH;
 use http::header::CONTENT_TYPE;
+use http::HeaderMap;
 use http::Request;
 use http::Response;
 use http::StatusCode;
@@ -30,12 +31,18 @@ use log::debug;
 use reqsign::AzureStorageSigner;
 
 use super::error::parse_error;
+use super::error::parse_hns_error;
 use super::pager::AzdfsPager;
 use super::writer::AzdfsWriter;
 use crate::ops::*;
 use crate::raw::*;
 use crate::*;
 
+const X_MS_RENAME_SOURCE: &str = "x-ms-rename-source";
+const X_MS_OWNER: &str = "x-ms-owner";
+const X_MS_GROUP: &str = "x-ms-group";
+const X_MS_PERMISSIONS: &str = "x-ms-permissions";
+
 /// Azure Data Lake Storage Gen2 Support.
 ///
 /// As known as `abfs`, `azdfs` or `azdls`.
@@ -50,9 +57,20 @@ use crate::*;
 /// - [x] write
 /// - [x] list
 /// - [ ] ~~scan~~
+/// - [x] rename
+/// - [x] acl
 /// - [ ] presign
 /// - [ ] blocking
 ///
+/// # Hierarchical Namespace
+///
+/// `rename` and `acl` are served by the Data Lake REST API:
+///
+/// - With hierarchical namespace (HNS) enabled, rename a dir will move the
+///   whole subtree atomically.
+/// - Without HNS, the storage account doesn't support POSIX acl, `get_acl`
+///   and `set_acl` will return [`ErrorKind::Unsupported`].
+///
 /// # Configuration
 ///
 /// - `root`: Set the work dir for backend.
@@ -307,6 +325,22 @@ impl Accessor for AzdfsBackend {
             .set_capabilities(
                 AccessorCapability::Read | AccessorCapability::Write | AccessorCapability::List,
             )
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                write: true,
+                write_with_content_type: true,
+                create_dir: true,
+                delete: true,
+                rename: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                acl: true,
+                ..Default::default()
+            })
             .set_hints(AccessorHint::ReadStreamable);
 
         am
@@ -337,14 +371,15 @@ impl Accessor for AzdfsBackend {
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        let resp = self.azdfs_read(path, args.range()).await?;
+        let range = args.range();
+        let resp = self.azdfs_read(path, range).await?;
 
         let status = resp.status();
 
         match status {
             StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
-                let meta = parse_into_metadata(path, resp.headers())?;
-                Ok((RpRead::with_metadata(meta), resp.into_body()))
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
             }
             _ => Err(parse_error(resp).await?),
         }
@@ -404,6 +439,64 @@ impl Accessor for AzdfsBackend {
 
         Ok((RpList::default(), op))
     }
+
+    async fn rename(&self, from: &str, to: &str, _: OpRename) -> Result<RpRename> {
+        self.azdfs_ensure_parent_path(to).await?;
+
+        let resp = self.azdfs_rename(from, to).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::CREATED => {
+                resp.into_body().consume().await?;
+                Ok(RpRename::default())
+            }
+            // Rename requires hierarchical namespace.
+            _ => Err(parse_hns_error(resp).await?),
+        }
+    }
+
+    async fn get_acl(&self, path: &str, _: OpGetAcl) -> Result<RpGetAcl> {
+        let resp = self.azdfs_get_access_control(path).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let headers = resp.headers();
+
+                let mut acl = Acl::new();
+                if let Some(v) = parse_header_to_str(headers, X_MS_OWNER)? {
+                    acl = acl.with_owner(v);
+                }
+                if let Some(v) = parse_header_to_str(headers, X_MS_GROUP)? {
+                    acl = acl.with_group(v);
+                }
+                if let Some(v) = parse_header_to_str(headers, X_MS_PERMISSIONS)? {
+                    acl = acl.with_permissions(v);
+                }
+
+                resp.into_body().consume().await?;
+                Ok(RpGetAcl::new(acl))
+            }
+            _ => Err(parse_hns_error(resp).await?),
+        }
+    }
+
+    async fn set_acl(&self, path: &str, args: OpSetAcl) -> Result<RpSetAcl> {
+        let resp = self.azdfs_set_access_control(path, args.acl()).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(RpSetAcl::default())
+            }
+            _ => Err(parse_hns_error(resp).await?),
+        }
+    }
 }
 
 impl AzdfsBackend {
@@ -563,6 +656,134 @@ impl AzdfsBackend {
         self.client.send_async(req).await
     }
 
+    /// Create the parent dir of given path if it doesn't exist, the
+    /// destination of rename must have an existing parent.
+    async fn azdfs_ensure_parent_path(&self, path: &str) -> Result<()> {
+        let parent = get_parent(path);
+        if parent == "/" {
+            return Ok(());
+        }
+
+        let resp = self.azdfs_get_properties(parent).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            StatusCode::NOT_FOUND => {
+                resp.into_body().consume().await?;
+
+                let mut req =
+                    self.azdfs_create_request(parent, "directory", None, None, AsyncBody::Empty)?;
+
+                self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+
+                let resp = self.client.send_async(req).await?;
+
+                match resp.status() {
+                    StatusCode::CREATED | StatusCode::OK => {
+                        resp.into_body().consume().await?;
+                        Ok(())
+                    }
+                    _ => Err(parse_error(resp).await?),
+                }
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// ref: https://learn.microsoft.com/en-us/rest/api/storageservices/datalakestoragegen2/path/create
+    async fn azdfs_rename(&self, from: &str, to: &str) -> Result<Response<IncomingAsyncBody>> {
+        let source = build_abs_path(&self.root, from)
+            .trim_end_matches('/')
+            .to_string();
+        let target = build_abs_path(&self.root, to)
+            .trim_end_matches('/')
+            .to_string();
+
+        let url = format!(
+            "{}/{}/{}",
+            self.endpoint,
+            self.filesystem,
+            percent_encode_path(&target)
+        );
+
+        let mut req = Request::put(&url)
+            .header(
+                X_MS_RENAME_SOURCE,
+                format!("/{}/{}", self.filesystem, percent_encode_path(&source)),
+            )
+            .header(CONTENT_LENGTH, 0)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+
+        self.client.send_async(req).await
+    }
+
+    /// ref: https://learn.microsoft.com/en-us/rest/api/storageservices/datalakestoragegen2/path/get-properties
+    async fn azdfs_get_access_control(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path)
+            .trim_end_matches('/')
+            .to_string();
+
+        let url = format!(
+            "{}/{}/{}?action=getAccessControl",
+            self.endpoint,
+            self.filesystem,
+            percent_encode_path(&p)
+        );
+
+        let mut req = Request::head(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+
+        self.client.send_async(req).await
+    }
+
+    /// ref: https://learn.microsoft.com/en-us/rest/api/storageservices/datalakestoragegen2/path/update
+    async fn azdfs_set_access_control(
+        &self,
+        path: &str,
+        acl: &Acl,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path)
+            .trim_end_matches('/')
+            .to_string();
+
+        let url = format!(
+            "{}/{}/{}?action=setAccessControl",
+            self.endpoint,
+            self.filesystem,
+            percent_encode_path(&p)
+        );
+
+        let mut req = Request::patch(&url);
+
+        if let Some(v) = acl.owner() {
+            req = req.header(X_MS_OWNER, v);
+        }
+        if let Some(v) = acl.group() {
+            req = req.header(X_MS_GROUP, v);
+        }
+        if let Some(v) = acl.permissions() {
+            req = req.header(X_MS_PERMISSIONS, v);
+        }
+
+        let mut req = req
+            .header(CONTENT_LENGTH, 0)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+
+        self.client.send_async(req).await
+    }
+
     pub(crate) async fn azdfs_list(
         &self,
         path: &str,
@@ -597,3 +818,166 @@ impl AzdfsBackend {
         self.client.send_async(req).await
     }
 }
+
+/// Parse the value of given header name into str.
+fn parse_header_to_str<'a>(headers: &'a HeaderMap, name: &str) -> Result<Option<&'a str>> {
+    match headers.get(name) {
+        None => Ok(None),
+        Some(v) => Ok(Some(v.to_str().map_err(|e| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value is not valid utf-8 string",
+            )
+            .with_operation("azdfs::parse_header_to_str")
+            .with_context("header", name)
+            .set_source(e)
+        })?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    fn new_operator(endpoint: &str) -> Operator {
+        let mut builder = AzdfsBuilder::default();
+        builder
+            .filesystem("test")
+            .endpoint(endpoint)
+            .account_name("account")
+            .account_key("YWNjb3VudF9rZXk=");
+
+        Operator::new(builder).unwrap().finish()
+    }
+
+    #[tokio::test]
+    async fn test_rename() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/test/to"))
+            .and(query_param("action", "getStatus"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/test/to"))
+            .and(query_param("resource", "directory"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/test/to/dir"))
+            .and(header(X_MS_RENAME_SOURCE, "/test/from/dir"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_operator(&mock_server.uri());
+
+        op.rename("from/dir/", "to/dir/")
+            .await
+            .expect("rename must succeed");
+
+        let err = op
+            .rename("from/dir/", "to/file")
+            .await
+            .expect_err("rename dir to file must fail");
+        assert_eq!(err.kind(), ErrorKind::NotADirectory);
+    }
+
+    #[tokio::test]
+    async fn test_get_and_set_acl() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/test/file"))
+            .and(query_param("action", "getAccessControl"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header(X_MS_OWNER, "alice")
+                    .insert_header(X_MS_GROUP, "staff")
+                    .insert_header(X_MS_PERMISSIONS, "rwxr-x---+"),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/test/file"))
+            .and(query_param("action", "setAccessControl"))
+            .and(header(X_MS_PERMISSIONS, "0750"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_operator(&mock_server.uri());
+
+        let acl = op.get_acl("file").await.expect("get_acl must succeed");
+        assert_eq!(acl.owner(), Some("alice"));
+        assert_eq!(acl.group(), Some("staff"));
+        assert_eq!(acl.permissions(), Some("rwxr-x---+"));
+
+        op.set_acl("file", Acl::new().with_permissions("0750"))
+            .await
+            .expect("set_acl must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_acl_without_hns() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/test/file"))
+            .and(query_param("action", "getAccessControl"))
+            .respond_with(ResponseTemplate::new(400))
+            .mount(&mock_server)
+            .await;
+
+        let op = new_operator(&mock_server.uri());
+
+        let err = op.get_acl("file").await.expect_err("get_acl must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    #[tokio::test]
+    async fn test_rename_without_hns() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/test/to"))
+            .and(query_param("action", "getStatus"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/test/to/file"))
+            .and(header(X_MS_RENAME_SOURCE, "/test/from/file"))
+            .respond_with(ResponseTemplate::new(400))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = new_operator(&mock_server.uri());
+
+        let err = op
+            .rename("from/file", "to/file")
+            .await
+            .expect_err("rename must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+}
@@ -62,20 +62,26 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
     let (parts, body) = resp.into_parts();
     let bs = body.bytes().await?;
 
-    let (kind, retryable) = match parts.status {
-        StatusCode::NOT_FOUND => (ErrorKind::NotFound, false),
-        StatusCode::FORBIDDEN => (ErrorKind::PermissionDenied, false),
-        StatusCode::INTERNAL_SERVER_ERROR
-        | StatusCode::BAD_GATEWAY
-        | StatusCode::SERVICE_UNAVAILABLE
-        | StatusCode::GATEWAY_TIMEOUT => (ErrorKind::Unexpected, true),
-        _ => (ErrorKind::Unexpected, false),
-    };
+    let (mut kind, mut retryable) = parse_error_status(parts.status);
 
     let mut message = match de::from_reader::<_, AzdfsError>(bs.clone().reader()) {
         Ok(azblob_err) => format!("{azblob_err:?}"),
         Err(_) => String::from_utf8_lossy(&bs).into_owned(),
     };
+
+    let code = parts
+        .headers
+        .get("x-ms-error-code")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    (kind, retryable) = match code {
+        // Signature or account key is not valid, users need to fix their config.
+        "AuthenticationFailed" | "InvalidAuthenticationInfo" => (ErrorKind::ConfigInvalid, false),
+        "AuthorizationFailure"
+        | "AuthorizationPermissionMismatch"
+        | "InsufficientAccountPermissions" => (ErrorKind::PermissionDenied, false),
+        _ => (kind, retryable),
+    };
     // If there is no body here, fill with error code.
     if message.is_empty() {
         if let Some(v) = parts.headers.get("x-ms-error-code") {
@@ -91,7 +97,11 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
         }
     }
 
-    let mut err = Error::new(kind, &message).with_context("response", format!("{parts:?}"));
+    let mut err = with_error_response_context(Error::new(kind, &message), &parts);
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
 
     if retryable {
         err = err.set_temporary();
@@ -99,3 +109,24 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
 
     Ok(err)
 }
+
+/// Parse error response of operations that require hierarchical namespace.
+///
+/// Storage accounts without hierarchical namespace enabled will return
+/// `400 Bad Request` for them, we will convert it into `Unsupported`.
+pub async fn parse_hns_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
+    if resp.status() != StatusCode::BAD_REQUEST {
+        return parse_error(resp).await;
+    }
+
+    let (parts, body) = resp.into_parts();
+    body.consume().await?;
+
+    Ok(with_error_response_context(
+        Error::new(
+            ErrorKind::Unsupported,
+            "operation requires hierarchical namespace enabled on the storage account",
+        ),
+        &parts,
+    ))
+}
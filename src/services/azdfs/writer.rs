@@ -97,7 +97,11 @@ impl oio::Write for AzdfsWriter {
         ))
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn close(&mut self) -> Result<RpWrite> {
+        Ok(RpWrite::new())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
         Ok(())
     }
 }
@@ -0,0 +1,873 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::header;
+use http::Request;
+use http::Response;
+use http::StatusCode;
+use log::debug;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde_json::json;
+use time::OffsetDateTime;
+
+use super::error::parse_b2_error_code;
+use super::error::parse_error;
+use super::pager::B2Pager;
+use super::writer::B2Writer;
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+const DEFAULT_B2_ENDPOINT: &str = "https://api.backblazeb2.com";
+
+/// Parts of large files must be at least 5MB except the last one.
+const MIN_WRITE_PART_SIZE: usize = 5 * 1000 * 1000;
+/// Parts of large files can't be larger than 5GB.
+const MAX_WRITE_PART_SIZE: u64 = 5 * 1000 * 1000 * 1000;
+/// The recommended part size returned by B2.
+const DEFAULT_WRITE_PART_SIZE: usize = 100 * 1000 * 1000;
+
+/// [Backblaze B2](https://www.backblaze.com/b2/cloud-storage.html) services
+/// support via the native API.
+///
+/// # Capabilities
+///
+/// This service can be used to:
+///
+/// - [x] read
+/// - [x] write
+/// - [x] list
+/// - [ ] ~~scan~~
+/// - [ ] ~~presign~~
+/// - [ ] blocking
+///
+/// # Configuration
+///
+/// - `root`: Set the work directory for backend
+/// - `bucket`: Set the name of bucket
+/// - `bucket_id`: Set the id of bucket
+/// - `application_key_id`: Set the id of application key
+/// - `application_key`: Set the application key
+/// - `write_part_size`: Part size of large files, default to 100MB
+///
+/// You can refer to [`B2Builder`]'s docs for more information
+///
+/// # Notes
+///
+/// - Files larger than `write_part_size` will be uploaded via the large file
+///   API.
+/// - `delete` will delete all versions of the file.
+///
+/// # Example
+///
+/// ## Via Builder
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::services::B2;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     // create backend builder
+///     let mut builder = B2::default();
+///
+///     builder
+///         .root("/path/to/dir")
+///         .bucket("opendal")
+///         .bucket_id("4a48fe8875c6214145260818")
+///         .application_key_id("application_key_id")
+///         .application_key("application_key");
+///
+///     let op: Operator = Operator::new(builder)?.finish();
+///     Ok(())
+/// }
+/// ```
+#[derive(Default)]
+pub struct B2Builder {
+    root: Option<String>,
+    endpoint: Option<String>,
+    bucket: String,
+    bucket_id: String,
+
+    application_key_id: Option<String>,
+    application_key: Option<String>,
+
+    write_part_size: Option<usize>,
+    http_client: Option<HttpClient>,
+}
+
+impl Debug for B2Builder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("Builder");
+
+        ds.field("root", &self.root)
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("bucket_id", &self.bucket_id)
+            .field("application_key_id", &self.application_key_id)
+            .field("write_part_size", &self.write_part_size);
+        if self.application_key.is_some() {
+            ds.field("application_key", &"<redacted>");
+        }
+        ds.finish()
+    }
+}
+
+impl B2Builder {
+    /// Set root path of B2.
+    pub fn root(&mut self, root: &str) -> &mut Self {
+        if !root.is_empty() {
+            self.root = Some(root.to_string())
+        }
+
+        self
+    }
+
+    /// Set the endpoint to authorize account.
+    ///
+    /// Default to `https://api.backblazeb2.com`.
+    pub fn endpoint(&mut self, endpoint: &str) -> &mut Self {
+        if !endpoint.is_empty() {
+            self.endpoint = Some(endpoint.to_string())
+        }
+
+        self
+    }
+
+    /// Set the name of bucket, which is used to download files.
+    pub fn bucket(&mut self, bucket: &str) -> &mut Self {
+        self.bucket = bucket.to_string();
+        self
+    }
+
+    /// Set the id of bucket, which is used to upload and list files.
+    pub fn bucket_id(&mut self, bucket_id: &str) -> &mut Self {
+        self.bucket_id = bucket_id.to_string();
+        self
+    }
+
+    /// Set the id of application key.
+    pub fn application_key_id(&mut self, key_id: &str) -> &mut Self {
+        if !key_id.is_empty() {
+            self.application_key_id = Some(key_id.to_string())
+        }
+
+        self
+    }
+
+    /// Set the application key.
+    pub fn application_key(&mut self, key: &str) -> &mut Self {
+        if !key.is_empty() {
+            self.application_key = Some(key.to_string())
+        }
+
+        self
+    }
+
+    /// Set the part size of large files that used by writers.
+    ///
+    /// Appended bytes will be uploaded as parts of a large file once they
+    /// exceed this size. The size must be between 5MB and 5GB, building
+    /// will fail with [`ErrorKind::ConfigInvalid`] for others.
+    ///
+    /// Default to 100MB.
+    pub fn write_part_size(&mut self, size: usize) -> &mut Self {
+        self.write_part_size = Some(size);
+        self
+    }
+
+    /// Specify the http client that used by this service.
+    ///
+    /// # Notes
+    ///
+    /// This API is part of OpenDAL's Raw API. `HttpClient` could be changed
+    /// during minor updates.
+    pub fn http_client(&mut self, client: HttpClient) -> &mut Self {
+        self.http_client = Some(client);
+        self
+    }
+}
+
+impl Builder for B2Builder {
+    const SCHEME: Scheme = Scheme::B2;
+    type Accessor = B2Backend;
+
+    fn from_map(map: HashMap<String, String>) -> Self {
+        let mut builder = B2Builder::default();
+
+        map.get("root").map(|v| builder.root(v));
+        map.get("endpoint").map(|v| builder.endpoint(v));
+        map.get("bucket").map(|v| builder.bucket(v));
+        map.get("bucket_id").map(|v| builder.bucket_id(v));
+        map.get("application_key_id")
+            .map(|v| builder.application_key_id(v));
+        map.get("application_key")
+            .map(|v| builder.application_key(v));
+        map.get("write_part_size")
+            .and_then(|v| v.parse::<usize>().ok())
+            .map(|v| builder.write_part_size(v));
+
+        builder
+    }
+
+    fn build(&mut self) -> Result<Self::Accessor> {
+        debug!("backend build started: {:?}", self);
+
+        let root = normalize_root(&self.root.take().unwrap_or_default());
+        debug!("backend use root {}", root);
+
+        let endpoint = self
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| DEFAULT_B2_ENDPOINT.to_string());
+        parse_endpoint(&endpoint).map_err(|e| {
+            e.with_operation("Builder::build")
+                .with_context("service", Scheme::B2)
+        })?;
+        let endpoint = endpoint.trim_end_matches('/').to_string();
+        debug!("backend use endpoint {}", endpoint);
+
+        for (k, v) in [("bucket", &self.bucket), ("bucket_id", &self.bucket_id)] {
+            if v.is_empty() {
+                return Err(
+                    Error::new(ErrorKind::ConfigInvalid, &format!("{k} is empty"))
+                        .with_operation("Builder::build")
+                        .with_context("service", Scheme::B2),
+                );
+            }
+        }
+
+        let (key_id, key) = match (&self.application_key_id, &self.application_key) {
+            (Some(key_id), Some(key)) => (key_id.clone(), key.clone()),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "application_key_id and application_key are required",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::B2))
+            }
+        };
+
+        let write_part_size = self.write_part_size.unwrap_or(DEFAULT_WRITE_PART_SIZE);
+        if write_part_size < MIN_WRITE_PART_SIZE || write_part_size as u64 > MAX_WRITE_PART_SIZE {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "write_part_size must be between 5MB and 5GB",
+            )
+            .with_operation("Builder::build")
+            .with_context("service", Scheme::B2)
+            .with_context("write_part_size", write_part_size.to_string()));
+        }
+
+        let client = if let Some(client) = self.http_client.take() {
+            client
+        } else {
+            HttpClient::new().map_err(|err| {
+                err.with_operation("Builder::build")
+                    .with_context("service", Scheme::B2)
+            })?
+        };
+
+        debug!("backend build finished: {:?}", self);
+        Ok(B2Backend {
+            root,
+            endpoint,
+            bucket: self.bucket.clone(),
+            bucket_id: self.bucket_id.clone(),
+            application_key_id: key_id,
+            application_key: key,
+            client,
+            write_part_size,
+
+            authorization: Arc::new(Mutex::new(None)),
+        })
+    }
+}
+
+/// Result of `b2_authorize_account`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct B2Authorization {
+    pub authorization_token: String,
+    pub api_url: String,
+    pub download_url: String,
+}
+
+/// File and folder returned by `b2_list_file_names`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(super) struct B2File {
+    pub file_name: String,
+    pub file_id: Option<String>,
+    /// One of `upload`, `folder`, `hide` and `start`.
+    pub action: String,
+    pub content_length: u64,
+    pub content_type: Option<String>,
+    /// Milliseconds since the epoch.
+    pub upload_timestamp: i64,
+}
+
+impl B2File {
+    pub fn is_folder(&self) -> bool {
+        self.action == "folder" || self.file_name.ends_with('/')
+    }
+
+    pub fn parse_into_metadata(&self) -> Result<Metadata> {
+        if self.is_folder() {
+            return Ok(Metadata::new(EntryMode::DIR));
+        }
+
+        let mut meta = Metadata::new(EntryMode::FILE);
+        meta.set_content_length(self.content_length);
+        if let Some(v) = &self.content_type {
+            meta.set_content_type(v);
+        }
+        // B2 doesn't have etags, the file id is unique for every version.
+        if let Some(v) = &self.file_id {
+            meta.set_etag(v);
+        }
+        let dt =
+            OffsetDateTime::from_unix_timestamp_nanos(self.upload_timestamp as i128 * 1_000_000)
+                .map_err(|e| {
+                    Error::new(ErrorKind::Unexpected, "parse upload timestamp").set_source(e)
+                })?;
+        meta.set_last_modified(dt);
+        Ok(meta)
+    }
+}
+
+/// Result of `b2_list_file_names` and `b2_list_file_versions`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(super) struct B2ListFilesResult {
+    pub files: Vec<B2File>,
+    pub next_file_name: Option<String>,
+    pub next_file_id: Option<String>,
+}
+
+/// Backend for B2 services.
+#[derive(Clone)]
+pub struct B2Backend {
+    root: String,
+    endpoint: String,
+    bucket: String,
+    pub(super) bucket_id: String,
+    application_key_id: String,
+    application_key: String,
+    pub(super) client: HttpClient,
+    pub(super) write_part_size: usize,
+
+    /// Authorization will be refreshed once expired.
+    authorization: Arc<Mutex<Option<B2Authorization>>>,
+}
+
+impl Debug for B2Backend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Backend")
+            .field("root", &self.root)
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("bucket_id", &self.bucket_id)
+            .field("write_part_size", &self.write_part_size)
+            .field("client", &self.client)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Accessor for B2Backend {
+    type Reader = IncomingAsyncBody;
+    type BlockingReader = ();
+    type Writer = B2Writer;
+    type BlockingWriter = ();
+    type Pager = B2Pager;
+    type BlockingPager = ();
+
+    fn info(&self) -> AccessorInfo {
+        use AccessorCapability::*;
+        use AccessorHint::*;
+
+        let mut am = AccessorInfo::default();
+        am.set_scheme(Scheme::B2)
+            .set_root(&self.root)
+            .set_name(&self.bucket)
+            .set_capabilities(Read | Write | List)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                write_with_content_type: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_delimiter: true,
+                ..Default::default()
+            })
+            .set_hints(ReadStreamable);
+        am
+    }
+
+    async fn create(&self, path: &str, _: OpCreate) -> Result<RpCreate> {
+        // B2 doesn't have dirs, create an empty file ending with `/` instead.
+        let mut w = B2Writer::new(self.clone(), self.b2_path(path), OpWrite::new());
+        oio::Write::write(&mut w, Bytes::new()).await?;
+
+        Ok(RpCreate::default())
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let range = args.range();
+        let resp = self.b2_download_file_by_name(path, range).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let p = self.b2_path(path);
+
+        Ok((RpWrite::default(), B2Writer::new(self.clone(), p, args)))
+    }
+
+    async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+        // Stat root always returns a DIR.
+        if path == "/" {
+            return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
+        }
+
+        let p = self.b2_path(path);
+        // Files are found by starting from its name, and dirs are found by
+        // any files under it.
+        let output = if path.ends_with('/') {
+            self.b2_list_file_names(&p, None, Some("/"), 1).await?
+        } else {
+            self.b2_list_file_names(&p, Some(&p), None, 1).await?
+        };
+
+        match output.files.first() {
+            Some(file) if path.ends_with('/') => {
+                debug!("dir {path} is found by {}", file.file_name);
+                Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+            }
+            Some(file) if file.file_name == p => file.parse_into_metadata().map(RpStat::new),
+            _ => {
+                Err(Error::new(ErrorKind::NotFound, "file is not found").with_context("path", path))
+            }
+        }
+    }
+
+    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        let p = self.b2_path(path);
+
+        // Delete all versions of the file, including hide markers.
+        let mut start_file_id = None;
+        loop {
+            let output = self
+                .b2_list_file_versions(&p, start_file_id.as_deref())
+                .await?;
+
+            for file in output.files.iter().filter(|v| v.file_name == p) {
+                let file_id = match &file.file_id {
+                    Some(v) => v,
+                    None => continue,
+                };
+                self.b2_delete_file_version(&p, file_id).await?;
+            }
+
+            match (output.next_file_name, output.next_file_id) {
+                (Some(name), Some(id)) if name == p => start_file_id = Some(id),
+                _ => break,
+            }
+        }
+
+        Ok(RpDelete::default())
+    }
+
+    async fn list(&self, path: &str, _: OpList) -> Result<(RpList, Self::Pager)> {
+        let p = self.b2_path(path);
+
+        Ok((RpList::default(), B2Pager::new(self.clone(), &self.root, p)))
+    }
+}
+
+impl B2Backend {
+    /// Build the file name that used by B2, which doesn't start with `/`.
+    pub(super) fn b2_path(&self, path: &str) -> String {
+        build_abs_path(&self.root, path)
+    }
+
+    /// Get the authorization of account, the account will be authorized
+    /// again if there is no valid authorization.
+    async fn authorization(&self) -> Result<B2Authorization> {
+        if let Some(auth) = self.authorization.lock().clone() {
+            return Ok(auth);
+        }
+
+        let auth = self.b2_authorize_account().await?;
+        *self.authorization.lock() = Some(auth.clone());
+
+        Ok(auth)
+    }
+
+    async fn b2_authorize_account(&self) -> Result<B2Authorization> {
+        let url = format!("{}/b2api/v2/b2_authorize_account", self.endpoint);
+
+        let req = Request::get(&url)
+            .header(
+                header::AUTHORIZATION,
+                format_authorization_by_basic(&self.application_key_id, &self.application_key)?,
+            )
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        let resp = self.client.send_async(req).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                serde_json::from_slice(&bs).map_err(new_json_deserialize_error)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Send requests built by `build` with the account authorization.
+    ///
+    /// Authorization tokens are valid for at most 24 hours, so the account
+    /// will be authorized again and the request will be retried once if
+    /// the token is expired.
+    async fn b2_send<F>(&self, build: F) -> Result<Response<IncomingAsyncBody>>
+    where
+        F: Fn(&B2Authorization) -> Result<Request<AsyncBody>>,
+    {
+        let auth = self.authorization().await?;
+        let resp = self.client.send_async(build(&auth)?).await?;
+        if resp.status() != StatusCode::UNAUTHORIZED {
+            return Ok(resp);
+        }
+
+        let (parts, body) = resp.into_parts();
+        let bs = body.bytes().await?;
+        let code = parse_b2_error_code(&bs);
+        if !matches!(
+            code.as_deref(),
+            Some("expired_auth_token") | Some("bad_auth_token")
+        ) {
+            return Ok(Response::from_parts(
+                parts,
+                IncomingAsyncBody::new(Box::new(futures::stream::iter(vec![Ok(bs)])), None),
+            ));
+        }
+
+        debug!("authorization token is expired, authorize account again");
+        self.authorization.lock().take();
+        let auth = self.authorization().await?;
+        self.client.send_async(build(&auth)?).await
+    }
+
+    /// Call APIs which take arguments as JSON body.
+    pub(super) async fn b2_api(
+        &self,
+        name: &str,
+        args: serde_json::Value,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let body = Bytes::from(args.to_string());
+
+        self.b2_send(|auth| {
+            Request::post(format!("{}/b2api/v2/{name}", auth.api_url))
+                .header(header::AUTHORIZATION, &auth.authorization_token)
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::CONTENT_LENGTH, body.len())
+                .body(AsyncBody::Bytes(body.clone()))
+                .map_err(new_request_build_error)
+        })
+        .await
+    }
+
+    async fn b2_download_file_by_name(
+        &self,
+        path: &str,
+        range: BytesRange,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = percent_encode_path(&self.b2_path(path));
+
+        self.b2_send(|auth| {
+            let mut req = Request::get(format!("{}/file/{}/{p}", auth.download_url, self.bucket))
+                .header(header::AUTHORIZATION, &auth.authorization_token);
+            if !range.is_full() {
+                req = req.header(header::RANGE, range.to_header());
+            }
+
+            req.body(AsyncBody::Empty).map_err(new_request_build_error)
+        })
+        .await
+    }
+
+    pub(super) async fn b2_list_file_names(
+        &self,
+        prefix: &str,
+        start_file_name: Option<&str>,
+        delimiter: Option<&str>,
+        max_file_count: usize,
+    ) -> Result<B2ListFilesResult> {
+        let mut args = json!({
+            "bucketId": self.bucket_id,
+            "prefix": prefix,
+            "maxFileCount": max_file_count,
+        });
+        if let Some(v) = start_file_name {
+            args["startFileName"] = json!(v);
+        }
+        if let Some(v) = delimiter {
+            args["delimiter"] = json!(v);
+        }
+
+        let resp = self.b2_api("b2_list_file_names", args).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                serde_json::from_slice(&bs).map_err(new_json_deserialize_error)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn b2_list_file_versions(
+        &self,
+        path: &str,
+        start_file_id: Option<&str>,
+    ) -> Result<B2ListFilesResult> {
+        let mut args = json!({
+            "bucketId": self.bucket_id,
+            "prefix": path,
+            "startFileName": path,
+            "maxFileCount": 100,
+        });
+        if let Some(v) = start_file_id {
+            args["startFileId"] = json!(v);
+        }
+
+        let resp = self.b2_api("b2_list_file_versions", args).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                serde_json::from_slice(&bs).map_err(new_json_deserialize_error)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn b2_delete_file_version(&self, path: &str, file_id: &str) -> Result<()> {
+        let resp = self
+            .b2_api(
+                "b2_delete_file_version",
+                json!({ "fileName": path, "fileId": file_id }),
+            )
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => {
+                let err = parse_error(resp).await?;
+                // The version has been deleted by others.
+                if err.kind() == ErrorKind::NotFound {
+                    Ok(())
+                } else {
+                    Err(err)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::body_partial_json;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    async fn mock_authorize_account(server: &MockServer, token: &str) {
+        Mock::given(method("GET"))
+            .and(path("/b2api/v2/b2_authorize_account"))
+            .and(header("authorization", "Basic a2V5X2lkOmtleQ=="))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "accountId": "account_id",
+                "authorizationToken": token,
+                "apiUrl": server.uri(),
+                "downloadUrl": server.uri(),
+                "recommendedPartSize": 100000000,
+                "absoluteMinimumPartSize": 5000000,
+                "allowed": { "bucketId": "bucket_id", "bucketName": "bucket", "capabilities": ["listFiles"] }
+            })))
+            .up_to_n_times(1)
+            .mount(server)
+            .await;
+    }
+
+    fn new_backend(server: &MockServer) -> B2Backend {
+        let mut builder = B2Builder::default();
+        builder
+            .endpoint(&server.uri())
+            .bucket("bucket")
+            .bucket_id("bucket_id")
+            .application_key_id("key_id")
+            .application_key("key")
+            .write_part_size(MIN_WRITE_PART_SIZE);
+        builder.build().expect("build must succeed")
+    }
+
+    #[test]
+    fn test_build_invalid() {
+        let mut builder = B2Builder::default();
+        builder.bucket("bucket").bucket_id("bucket_id");
+        let err = builder.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let mut builder = B2Builder::default();
+        builder
+            .bucket("bucket")
+            .bucket_id("bucket_id")
+            .application_key_id("key_id")
+            .application_key("key")
+            .write_part_size(1024);
+        let err = builder.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[tokio::test]
+    async fn test_authorize_again_on_expired_token() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        mock_authorize_account(&server, "token_1").await;
+        mock_authorize_account(&server, "token_2").await;
+        Mock::given(method("POST"))
+            .and(path("/b2api/v2/b2_list_file_names"))
+            .and(header("authorization", "token_1"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "status": 401,
+                "code": "expired_auth_token",
+                "message": "Authorization token has expired"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/b2api/v2/b2_list_file_names"))
+            .and(header("authorization", "token_2"))
+            .and(body_partial_json(json!({
+                "bucketId": "bucket_id",
+                "prefix": "dir/file",
+                "startFileName": "dir/file",
+                "maxFileCount": 1
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "files": [{
+                    "accountId": "account_id",
+                    "action": "upload",
+                    "bucketId": "bucket_id",
+                    "contentLength": 7,
+                    "contentSha1": "dc724af18fbdd4e59189f5fe768a5f8311527050",
+                    "contentType": "text/plain",
+                    "fileId": "4_z27c88f1d182b150646ff0b16_f1004ba650fe24e6b_d20180809_m012513_c100_v0009990_t0000",
+                    "fileInfo": {},
+                    "fileName": "dir/file",
+                    "uploadTimestamp": 1533777913000i64
+                }],
+                "nextFileName": "dir/file\u{0}"
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server);
+
+        let meta = backend
+            .stat("dir/file", OpStat::new())
+            .await
+            .expect("stat must succeed")
+            .into_metadata();
+        assert_eq!(meta.mode(), EntryMode::FILE);
+        assert_eq!(meta.content_length(), 7);
+        assert_eq!(meta.content_type(), Some("text/plain"));
+        assert_eq!(
+            meta.last_modified(),
+            Some(OffsetDateTime::from_unix_timestamp(1533777913).unwrap())
+        );
+
+        // The refreshed token will be reused.
+        backend
+            .stat("dir/file", OpStat::new())
+            .await
+            .expect("stat must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_not_retried() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        mock_authorize_account(&server, "token").await;
+        Mock::given(method("POST"))
+            .and(path("/b2api/v2/b2_list_file_names"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "status": 401,
+                "code": "unauthorized",
+                "message": "application key lacks listFiles capability"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server);
+
+        let err = backend
+            .stat("dir/file", OpStat::new())
+            .await
+            .expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use http::Response;
+use http::StatusCode;
+use serde::Deserialize;
+use serde_json::de;
+
+use crate::raw::*;
+use crate::Error;
+use crate::ErrorKind;
+use crate::Result;
+
+/// B2 returns errors like:
+///
+/// ```json
+/// {
+///   "status": 400,
+///   "code": "invalid_bucket_name",
+///   "message": "bucket name is too short"
+/// }
+/// ```
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+struct B2Error {
+    status: u16,
+    code: String,
+    message: String,
+}
+
+/// Parse error response into Error.
+pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
+    let (parts, body) = resp.into_parts();
+    let bs = body.bytes().await?;
+
+    let b2_err = de::from_slice::<B2Error>(&bs).ok();
+    let code = b2_err.as_ref().map(|v| v.code.as_str()).unwrap_or_default();
+
+    let (kind, retryable) = match (parts.status, code) {
+        (StatusCode::NOT_FOUND, _) => (ErrorKind::NotFound, false),
+        // Deleting a not exist file version returns `400 Bad Request`.
+        (StatusCode::BAD_REQUEST, "file_not_present") => (ErrorKind::NotFound, false),
+        (StatusCode::UNAUTHORIZED, _) => (ErrorKind::PermissionDenied, false),
+        // Usage caps of account are exceeded.
+        (StatusCode::FORBIDDEN, "cap_exceeded" | "transaction_cap_exceeded") => {
+            (ErrorKind::RateLimited, false)
+        }
+        (StatusCode::FORBIDDEN, _) => (ErrorKind::PermissionDenied, false),
+        (StatusCode::TOO_MANY_REQUESTS, _) => (ErrorKind::RateLimited, true),
+        (StatusCode::REQUEST_TIMEOUT, _)
+        | (StatusCode::INTERNAL_SERVER_ERROR, _)
+        | (StatusCode::BAD_GATEWAY, _)
+        | (StatusCode::SERVICE_UNAVAILABLE, _)
+        | (StatusCode::GATEWAY_TIMEOUT, _) => (ErrorKind::Unexpected, true),
+        _ => (ErrorKind::Unexpected, false),
+    };
+
+    let message = match b2_err {
+        Some(b2_err) => format!("{b2_err:?}"),
+        None => String::from_utf8_lossy(&bs).into_owned(),
+    };
+
+    let mut err = Error::new(kind, &message);
+
+    // Rate limited responses carry the seconds to wait in `Retry-After`.
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
+
+    err = with_error_response_context(err, &parts);
+
+    if retryable {
+        err = err.set_temporary();
+    }
+
+    Ok(err)
+}
+
+/// Parse the error code like `expired_auth_token` from error response body.
+pub fn parse_b2_error_code(bs: &[u8]) -> Option<String> {
+    de::from_slice::<B2Error>(bs).ok().map(|v| v.code)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::stream;
+    use http::header;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_parse_error() -> Result<()> {
+        let cases = vec![
+            (
+                StatusCode::BAD_REQUEST,
+                r#"{"status": 400, "code": "file_not_present", "message": "File not present: dir/file"}"#,
+                ErrorKind::NotFound,
+                false,
+            ),
+            (
+                StatusCode::BAD_REQUEST,
+                r#"{"status": 400, "code": "bad_request", "message": "fileName is required"}"#,
+                ErrorKind::Unexpected,
+                false,
+            ),
+            (
+                StatusCode::FORBIDDEN,
+                r#"{"status": 403, "code": "transaction_cap_exceeded", "message": "Transaction cap exceeded"}"#,
+                ErrorKind::RateLimited,
+                false,
+            ),
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                r#"{"status": 503, "code": "service_unavailable", "message": "c001_v0001108_t0044 is too busy"}"#,
+                ErrorKind::Unexpected,
+                true,
+            ),
+        ];
+
+        for (status, content, kind, temporary) in cases {
+            let bs = bytes::Bytes::from(content);
+            let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+            let resp = Response::builder().status(status).body(body).unwrap();
+
+            let err = parse_error(resp).await?;
+            assert_eq!(err.kind(), kind, "{content}");
+            assert_eq!(err.is_temporary(), temporary, "{content}");
+        }
+
+        let bs = bytes::Bytes::from(
+            r#"{"status": 429, "code": "too_many_requests", "message": "Too many requests"}"#,
+        );
+        let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+        let resp = Response::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .header(header::RETRY_AFTER, "7")
+            .body(body)
+            .unwrap();
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::RateLimited);
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(7)));
+
+        assert_eq!(
+            parse_b2_error_code(br#"{"status": 401, "code": "expired_auth_token", "message": ""}"#)
+                .as_deref(),
+            Some("expired_auth_token")
+        );
+
+        Ok(())
+    }
+}
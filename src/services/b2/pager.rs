@@ -0,0 +1,196 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+
+use super::backend::B2Backend;
+use crate::raw::*;
+use crate::*;
+
+/// The max number of files returned by one `b2_list_file_names` call
+/// without being charged as multiple transactions.
+const MAX_FILE_COUNT: usize = 1000;
+
+/// B2Pager lists files under a prefix page by page via
+/// `b2_list_file_names`.
+///
+/// Pages are chained by `nextFileName`, which is the file name to start
+/// the next page with.
+pub struct B2Pager {
+    backend: B2Backend,
+    root: String,
+    path: String,
+
+    next_file_name: Option<String>,
+    done: bool,
+}
+
+impl B2Pager {
+    pub fn new(backend: B2Backend, root: &str, path: String) -> Self {
+        Self {
+            backend,
+            root: root.to_string(),
+            path,
+
+            next_file_name: None,
+            done: false,
+        }
+    }
+}
+
+#[async_trait]
+impl oio::Page for B2Pager {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let output = self
+            .backend
+            .b2_list_file_names(
+                &self.path,
+                self.next_file_name.as_deref(),
+                Some("/"),
+                MAX_FILE_COUNT,
+            )
+            .await?;
+
+        self.done = output.next_file_name.is_none();
+        self.next_file_name = output.next_file_name;
+
+        let mut entries = Vec::with_capacity(output.files.len());
+        for file in output.files {
+            // B2 could return the dir itself or placeholders created by
+            // `create_dir` which end with `/`, they should be ignored.
+            if file.file_name == self.path
+                || (file.action != "folder" && file.file_name.ends_with('/'))
+            {
+                continue;
+            }
+
+            entries.push(oio::Entry::new(
+                &build_rel_path(&self.root, &file.file_name),
+                file.parse_into_metadata()?,
+            ));
+        }
+
+        Ok(Some(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oio::Page;
+    use serde_json::json;
+    use wiremock::matchers::body_json;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::services::B2;
+
+    #[tokio::test]
+    async fn test_list_with_next_file_name() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/b2api/v2/b2_authorize_account"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "authorizationToken": "token",
+                "apiUrl": server.uri(),
+                "downloadUrl": server.uri()
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/b2api/v2/b2_list_file_names"))
+            .and(body_json(json!({
+                "bucketId": "bucket_id",
+                "prefix": "root/dir/",
+                "delimiter": "/",
+                "maxFileCount": 1000
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "files": [
+                    { "action": "upload", "fileName": "root/dir/", "contentLength": 0, "uploadTimestamp": 0 },
+                    { "action": "folder", "fileName": "root/dir/a/", "contentLength": 0, "uploadTimestamp": 0 },
+                    {
+                        "action": "upload",
+                        "fileName": "root/dir/b",
+                        "fileId": "id_b",
+                        "contentLength": 1,
+                        "contentType": "text/plain",
+                        "uploadTimestamp": 1533777913000i64
+                    }
+                ],
+                "nextFileName": "root/dir/b\u{0}"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/b2api/v2/b2_list_file_names"))
+            .and(body_json(json!({
+                "bucketId": "bucket_id",
+                "prefix": "root/dir/",
+                "startFileName": "root/dir/b\u{0}",
+                "delimiter": "/",
+                "maxFileCount": 1000
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "files": [
+                    {
+                        "action": "upload",
+                        "fileName": "root/dir/c",
+                        "fileId": "id_c",
+                        "contentLength": 4,
+                        "uploadTimestamp": 1533777913000i64
+                    }
+                ],
+                "nextFileName": null
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut builder = B2::default();
+        builder
+            .root("/root/")
+            .endpoint(&server.uri())
+            .bucket("bucket")
+            .bucket_id("bucket_id")
+            .application_key_id("key_id")
+            .application_key("key");
+        let backend = builder.build().expect("build must succeed");
+
+        let mut pager = B2Pager::new(backend, "/root/", "root/dir/".to_string());
+
+        let entries = pager.next().await.unwrap().expect("first page must exist");
+        let paths: Vec<_> = entries.iter().map(|e| e.path().to_string()).collect();
+        assert_eq!(paths, vec!["dir/a/", "dir/b"]);
+        assert_eq!(entries[1].metadata().etag(), Some("id_b"));
+
+        let entries = pager.next().await.unwrap().expect("second page must exist");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path(), "dir/c");
+        assert_eq!(entries[0].metadata().content_length(), 4);
+
+        assert!(pager.next().await.unwrap().is_none());
+    }
+}
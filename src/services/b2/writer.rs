@@ -0,0 +1,520 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use bytes::BytesMut;
+use http::header;
+use http::Request;
+use http::StatusCode;
+use log::debug;
+use serde::Deserialize;
+use serde_json::json;
+use sha1::Digest;
+use sha1::Sha1;
+
+use super::backend::B2Backend;
+use super::error::parse_error;
+use crate::ops::OpWrite;
+use crate::raw::*;
+use crate::*;
+
+/// Upload urls could be busy or expired, a new one should be fetched for
+/// retrying. We will give up after so many attempts.
+const MAX_UPLOAD_ATTEMPTS: usize = 5;
+
+/// The url and token to upload files or parts.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct B2UploadUrl {
+    upload_url: String,
+    authorization_token: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct B2StartLargeFileResult {
+    file_id: String,
+}
+
+/// B2Writer uploads small files via `b2_upload_file`, and larger files via
+/// the [large file API](https://www.backblaze.com/b2/docs/large_files.html).
+///
+/// B2 expects clients to fetch a new upload url if the current one is busy
+/// or expired, so uploads will be retried with new urls on `503 Service
+/// Unavailable`, `408 Request Timeout`, `401 Unauthorized` and temporary
+/// errors like broken connections.
+pub struct B2Writer {
+    backend: B2Backend,
+    path: String,
+    op: OpWrite,
+
+    /// The url to upload file, fetched lazily and reused.
+    upload_url: Option<B2UploadUrl>,
+
+    /// The id of large file, started lazily.
+    file_id: Option<String>,
+    /// The url to upload parts of large file.
+    part_url: Option<B2UploadUrl>,
+    /// Sha1 of uploaded parts.
+    part_sha1s: Vec<String>,
+
+    buf: BytesMut,
+    /// The upload has been finished or cancelled.
+    closed: bool,
+}
+
+impl B2Writer {
+    pub fn new(backend: B2Backend, path: String, op: OpWrite) -> Self {
+        B2Writer {
+            backend,
+            path,
+            op,
+
+            upload_url: None,
+
+            file_id: None,
+            part_url: None,
+            part_sha1s: Vec::new(),
+
+            buf: BytesMut::new(),
+            closed: false,
+        }
+    }
+
+    fn content_type(&self) -> &str {
+        self.op.content_type().unwrap_or("b2/x-auto")
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+
+        let total = self.part_sha1s.len() as u64 * self.backend.write_part_size as u64
+            + self.buf.len() as u64;
+        if let Some(expected) = self.op.content_length() {
+            if expected != total {
+                return Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "written size is not equal to the content length",
+                )
+                .with_context("path", &self.path)
+                .with_context("expected", expected.to_string())
+                .with_context("actual", total.to_string()));
+            }
+        }
+
+        if self.file_id.is_none() && self.buf.len() <= self.backend.write_part_size {
+            let bs = self.buf.split().freeze();
+            self.upload(bs, None).await?;
+            self.closed = true;
+            return Ok(());
+        }
+
+        // Upload all bytes except the last part.
+        while self.buf.len() > self.backend.write_part_size {
+            self.upload_part().await?;
+        }
+        // Large files must have at least two parts, which is guaranteed
+        // since the last part is always kept in buffer while appending.
+        self.upload_part().await?;
+
+        self.finish_large_file().await?;
+        self.closed = true;
+        Ok(())
+    }
+
+    /// Upload the leading part of buffered bytes, the large file will be
+    /// started with the first part.
+    async fn upload_part(&mut self) -> Result<()> {
+        if self.file_id.is_none() {
+            self.file_id = Some(self.start_large_file().await?);
+        }
+
+        let size = std::cmp::min(self.buf.len(), self.backend.write_part_size);
+        let bs = self.buf.split_to(size).freeze();
+        let part_number = self.part_sha1s.len() + 1;
+
+        let sha1 = self.upload(bs, Some(part_number)).await?;
+        self.part_sha1s.push(sha1);
+        Ok(())
+    }
+
+    /// Upload bytes as the whole file or a part of large file, returns
+    /// the sha1 of bytes.
+    async fn upload(&mut self, bs: Bytes, part_number: Option<usize>) -> Result<String> {
+        let sha1 = format!("{:x}", Sha1::digest(&bs));
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            let url = match part_number {
+                None => self.upload_url().await?,
+                Some(_) => self.part_url().await?,
+            };
+
+            let mut req = Request::post(&url.upload_url)
+                .header(header::AUTHORIZATION, &url.authorization_token)
+                .header(header::CONTENT_LENGTH, bs.len())
+                .header("X-Bz-Content-Sha1", &sha1);
+            req = match part_number {
+                None => req
+                    .header("X-Bz-File-Name", percent_encode_path(&self.path))
+                    .header(header::CONTENT_TYPE, self.content_type()),
+                Some(n) => req.header("X-Bz-Part-Number", n),
+            };
+            let req = req
+                .body(AsyncBody::Bytes(bs.clone()))
+                .map_err(new_request_build_error)?;
+
+            let err = match self.backend.client.send_async(req).await {
+                Ok(resp) => match resp.status() {
+                    StatusCode::OK => {
+                        resp.into_body().consume().await?;
+                        return Ok(sha1);
+                    }
+                    StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::REQUEST_TIMEOUT
+                    | StatusCode::UNAUTHORIZED => parse_error(resp).await?.set_temporary(),
+                    _ => return Err(parse_error(resp).await?),
+                },
+                Err(err) if err.is_temporary() => err,
+                Err(err) => return Err(err),
+            };
+
+            if attempt >= MAX_UPLOAD_ATTEMPTS {
+                return Err(err.with_context("attempts", attempt.to_string()));
+            }
+            debug!(
+                "upload url of {} is not usable, fetch a new one: {err}",
+                self.path
+            );
+            match part_number {
+                None => self.upload_url = None,
+                Some(_) => self.part_url = None,
+            }
+        }
+    }
+
+    async fn upload_url(&mut self) -> Result<B2UploadUrl> {
+        if let Some(url) = &self.upload_url {
+            return Ok(url.clone());
+        }
+
+        let resp = self
+            .backend
+            .b2_api(
+                "b2_get_upload_url",
+                json!({ "bucketId": self.backend.bucket_id }),
+            )
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                let url: B2UploadUrl =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+                self.upload_url = Some(url.clone());
+                Ok(url)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn part_url(&mut self) -> Result<B2UploadUrl> {
+        if let Some(url) = &self.part_url {
+            return Ok(url.clone());
+        }
+
+        let file_id = self
+            .file_id
+            .clone()
+            .expect("file id must be valid while uploading parts");
+        let resp = self
+            .backend
+            .b2_api("b2_get_upload_part_url", json!({ "fileId": file_id }))
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                let url: B2UploadUrl =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+                self.part_url = Some(url.clone());
+                Ok(url)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn start_large_file(&self) -> Result<String> {
+        let resp = self
+            .backend
+            .b2_api(
+                "b2_start_large_file",
+                json!({
+                    "bucketId": self.backend.bucket_id,
+                    "fileName": self.path,
+                    "contentType": self.content_type(),
+                }),
+            )
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                let result: B2StartLargeFileResult =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+                Ok(result.file_id)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn finish_large_file(&self) -> Result<()> {
+        let file_id = self
+            .file_id
+            .clone()
+            .expect("file id must be valid while finishing large file");
+        let resp = self
+            .backend
+            .b2_api(
+                "b2_finish_large_file",
+                json!({ "fileId": file_id, "partSha1Array": self.part_sha1s }),
+            )
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+}
+
+#[async_trait]
+impl oio::Write for B2Writer {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.buf.extend_from_slice(&bs);
+        self.finish().await
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.buf.extend_from_slice(&bs);
+
+        // Always keep the last part in buffer so that small files could be
+        // uploaded in one request.
+        while self.buf.len() > self.backend.write_part_size {
+            self.upload_part().await?;
+        }
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        self.finish().await?;
+        Ok(RpWrite::new())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        self.buf.clear();
+        if self.closed {
+            return Ok(());
+        }
+        self.closed = true;
+
+        let file_id = match self.file_id.take() {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        let resp = self
+            .backend
+            .b2_api("b2_cancel_large_file", json!({ "fileId": file_id }))
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oio::Write;
+    use wiremock::matchers::body_partial_json;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::services::B2;
+
+    const PART_SIZE: usize = 5 * 1000 * 1000;
+
+    async fn new_backend(server: &MockServer) -> B2Backend {
+        Mock::given(method("GET"))
+            .and(path("/b2api/v2/b2_authorize_account"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "authorizationToken": "token",
+                "apiUrl": server.uri(),
+                "downloadUrl": server.uri()
+            })))
+            .expect(1)
+            .mount(server)
+            .await;
+
+        let mut builder = B2::default();
+        builder
+            .endpoint(&server.uri())
+            .bucket("bucket")
+            .bucket_id("bucket_id")
+            .application_key_id("key_id")
+            .application_key("key")
+            .write_part_size(PART_SIZE);
+        builder.build().expect("build must succeed")
+    }
+
+    #[tokio::test]
+    async fn test_upload_url_rotation() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        let backend = new_backend(&server).await;
+        for i in 1..=2 {
+            Mock::given(method("POST"))
+                .and(path("/b2api/v2/b2_get_upload_url"))
+                .and(body_partial_json(json!({ "bucketId": "bucket_id" })))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "bucketId": "bucket_id",
+                    "uploadUrl": format!("{}/upload/{i}", server.uri()),
+                    "authorizationToken": format!("upload_token_{i}")
+                })))
+                .up_to_n_times(1)
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        // The first upload url is too busy.
+        Mock::given(method("POST"))
+            .and(path("/upload/1"))
+            .and(header("authorization", "upload_token_1"))
+            .respond_with(ResponseTemplate::new(503).set_body_json(json!({
+                "status": 503,
+                "code": "service_unavailable",
+                "message": "c001_v0001108_t0044 is too busy"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/upload/2"))
+            .and(header("authorization", "upload_token_2"))
+            .and(header("X-Bz-File-Name", "dir/file%20name"))
+            .and(header("content-type", "text/plain"))
+            .and(header(
+                "X-Bz-Content-Sha1",
+                "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "fileId": "id",
+                "fileName": "dir/file name",
+                "contentLength": 5
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let op = OpWrite::new().with_content_type("text/plain");
+        let mut w = B2Writer::new(backend, "dir/file name".to_string(), op);
+        w.write(Bytes::from("hello"))
+            .await
+            .expect("write must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_upload_large_file() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        let backend = new_backend(&server).await;
+        Mock::given(method("POST"))
+            .and(path("/b2api/v2/b2_start_large_file"))
+            .and(body_partial_json(json!({
+                "bucketId": "bucket_id",
+                "fileName": "file",
+                "contentType": "b2/x-auto"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "fileId": "file_id" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/b2api/v2/b2_get_upload_part_url"))
+            .and(body_partial_json(json!({ "fileId": "file_id" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "fileId": "file_id",
+                "uploadUrl": format!("{}/upload_part", server.uri()),
+                "authorizationToken": "part_token"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        for n in 1..=2 {
+            Mock::given(method("POST"))
+                .and(path("/upload_part"))
+                .and(header("authorization", "part_token"))
+                .and(header("X-Bz-Part-Number", n.to_string().as_str()))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let sha1_1 = format!("{:x}", Sha1::digest(vec![1; PART_SIZE]));
+        let sha1_2 = format!("{:x}", Sha1::digest(vec![2; 100]));
+        Mock::given(method("POST"))
+            .and(path("/b2api/v2/b2_finish_large_file"))
+            .and(body_partial_json(json!({
+                "fileId": "file_id",
+                "partSha1Array": [sha1_1, sha1_2]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "fileId": "file_id" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut w = B2Writer::new(backend, "file".to_string(), OpWrite::new());
+        w.append(Bytes::from(vec![1; PART_SIZE]))
+            .await
+            .expect("append must succeed");
+        w.append(Bytes::from(vec![2; 100]))
+            .await
+            .expect("append must succeed");
+        assert_eq!(w.part_sha1s, vec![sha1_1]);
+
+        w.close().await.expect("close must succeed");
+        assert!(w.closed);
+    }
+}
@@ -0,0 +1,616 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use log::debug;
+use log::warn;
+use parking_lot::Mutex;
+use percent_encoding::percent_decode_str;
+use percent_encoding::utf8_percent_encode;
+use percent_encoding::NON_ALPHANUMERIC;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::fs;
+use uuid::Uuid;
+
+use super::super::fs::error::parse_io_error;
+use crate::raw::adapters::kv;
+use crate::raw::*;
+use crate::*;
+
+/// Local file system with size budget, designed to be used as a bounded
+/// local disk cache.
+///
+/// # Capabilities
+///
+/// This service can be used to:
+///
+/// - [x] read
+/// - [x] write
+/// - [ ] ~~list~~
+/// - [x] scan
+/// - [ ] ~~presign~~
+/// - [x] blocking
+///
+/// # Configuration
+///
+/// - `root`: Set the work dir for backend.
+/// - `max_bytes`: Set the max total bytes of all entries.
+///
+/// # Layout
+///
+/// - Entries are stored as files under `{root}/data`, file names are the
+///   percent encoded paths.
+/// - The index is stored at `{root}/index`, which records the size and the
+///   access order of every entry.
+///
+/// # Eviction
+///
+/// The directory is unbounded by default. If `max_bytes` is set, the least
+/// recently read entries will be deleted after writing to keep the usage
+/// under budget. Access order is tracked in the index instead of `atime`,
+/// which is often disabled or lazily updated.
+///
+/// Reads only update the index in memory, which will be persisted with the
+/// next write, delete or while the backend is dropped.
+///
+/// # Crash Safety
+///
+/// The index is rebuilt by scanning `{root}/data` while building, so it's
+/// fine to lose or delete the index file. Entries that not recorded in the
+/// index will be treated as the least recently read.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::services::CacheFs;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let mut builder = CacheFs::default();
+///     builder.root("/tmp/opendal/cache").max_bytes(1024 * 1024 * 1024);
+///
+///     let op: Operator = Operator::new(builder)?.finish();
+///     Ok(())
+/// }
+/// ```
+#[derive(Default, Debug)]
+pub struct CacheFsBuilder {
+    root: Option<PathBuf>,
+    max_bytes: Option<u64>,
+}
+
+impl CacheFsBuilder {
+    /// Set root for backend.
+    pub fn root(&mut self, root: &str) -> &mut Self {
+        self.root = if root.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(root))
+        };
+
+        self
+    }
+
+    /// Set the max total bytes of all entries.
+    ///
+    /// Writing content that larger than `max_bytes` will return an error.
+    pub fn max_bytes(&mut self, max_bytes: u64) -> &mut Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+}
+
+impl Builder for CacheFsBuilder {
+    const SCHEME: Scheme = Scheme::CacheFs;
+    type Accessor = CacheFsBackend;
+
+    fn from_map(map: HashMap<String, String>) -> Self {
+        let mut builder = CacheFsBuilder::default();
+
+        map.get("root").map(|v| builder.root(v));
+        map.get("max_bytes")
+            .map(|v| v.parse::<u64>().map(|v| builder.max_bytes(v)));
+
+        builder
+    }
+
+    fn build(&mut self) -> Result<Self::Accessor> {
+        debug!("backend build started: {:?}", &self);
+
+        let root = match self.root.take() {
+            Some(root) => Ok(root),
+            None => Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "root is not specified",
+            )),
+        }?;
+        debug!("backend use root {}", root.to_string_lossy());
+
+        for dir in [root.join("data"), root.join("tmp")] {
+            std::fs::create_dir_all(&dir).map_err(|e| {
+                Error::new(ErrorKind::Unexpected, "create cache dir failed")
+                    .with_operation("Builder::build")
+                    .with_context("dir", dir.to_string_lossy())
+                    .set_source(e)
+            })?;
+        }
+
+        let mut index = Index::load(&root).map_err(|e| {
+            parse_io_error(e)
+                .with_operation("Builder::build")
+                .with_context("root", root.to_string_lossy())
+        })?;
+        index.max_bytes = self.max_bytes;
+
+        let adapter = Adapter {
+            root,
+            index: Mutex::new(index),
+        };
+        {
+            // The existing entries could exceed the budget.
+            let mut index = adapter.index.lock();
+            adapter.evict(&mut index, None)?;
+        }
+
+        Ok(CacheFsBackend::new(adapter))
+    }
+}
+
+/// Backend is used to serve `Accessor` support for cache fs.
+pub type CacheFsBackend = kv::Backend<Adapter>;
+
+/// The persisted format of index.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexFile {
+    /// Entries in access order, the first one is the least recently read.
+    entries: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexEntry {
+    path: String,
+    size: u64,
+}
+
+#[derive(Debug, Default)]
+struct Index {
+    entries: HashMap<String, Entry>,
+    /// Access order of entries, smaller tick means less recently read.
+    lru: BTreeMap<u64, String>,
+    tick: u64,
+    total_bytes: u64,
+    /// Whether there are changes not persisted yet.
+    dirty: bool,
+
+    max_bytes: Option<u64>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    size: u64,
+    tick: u64,
+}
+
+impl Index {
+    /// Load index by scanning the data dir.
+    ///
+    /// The persisted index only provides the access order, entries are
+    /// always decided by files in the data dir.
+    fn load(root: &Path) -> io::Result<Self> {
+        let mut files = Vec::new();
+        for de in std::fs::read_dir(root.join("data"))? {
+            let de = de?;
+            let meta = de.metadata()?;
+            if !meta.is_file() {
+                continue;
+            }
+            let path = match de.file_name().to_str().and_then(decode_path) {
+                Some(path) => path,
+                None => {
+                    warn!("cache fs found unexpected file {:?}", de.path());
+                    continue;
+                }
+            };
+            let mtime = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            files.push((path, meta.len(), mtime));
+        }
+
+        let persisted = match std::fs::read(root.join("index")) {
+            Ok(bs) => match serde_json::from_slice::<IndexFile>(&bs) {
+                Ok(v) => v,
+                Err(err) => {
+                    warn!("cache fs index is broken, rebuild it: {err:?}");
+                    IndexFile::default()
+                }
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                debug!("cache fs index is not found, rebuild it");
+                IndexFile::default()
+            }
+            Err(err) => return Err(err),
+        };
+        let order: HashMap<String, usize> = persisted
+            .entries
+            .into_iter()
+            .enumerate()
+            .map(|(idx, entry)| (entry.path, idx))
+            .collect();
+
+        // Files that not recorded come first in modified order, then
+        // others in the persisted access order.
+        files.sort_by_key(|(path, _, mtime)| (order.get(path).map(|v| v + 1), *mtime));
+
+        let mut index = Index::default();
+        for (path, size, _) in files {
+            index.insert(path, size);
+        }
+        index.dirty = true;
+        index.persist(root)?;
+
+        Ok(index)
+    }
+
+    /// Persist the index into `{root}/index` atomically.
+    fn persist(&mut self, root: &Path) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let file = IndexFile {
+            entries: self
+                .lru
+                .values()
+                .map(|path| IndexEntry {
+                    path: path.clone(),
+                    size: self.entries[path].size,
+                })
+                .collect(),
+        };
+        let bs = serde_json::to_vec(&file)?;
+
+        let tmp = root.join("tmp").join(format!("index.{}", Uuid::new_v4()));
+        std::fs::write(&tmp, bs)?;
+        std::fs::rename(&tmp, root.join("index"))?;
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn touch(&mut self, path: &str) {
+        let tick = self.next_tick();
+        if let Some(entry) = self.entries.get_mut(path) {
+            self.lru.remove(&entry.tick);
+            self.lru.insert(tick, path.to_string());
+            entry.tick = tick;
+            self.dirty = true;
+        }
+    }
+
+    fn insert(&mut self, path: String, size: u64) {
+        self.remove(&path);
+
+        let tick = self.next_tick();
+        self.total_bytes += size;
+        self.lru.insert(tick, path.clone());
+        self.entries.insert(path, Entry { size, tick });
+        self.dirty = true;
+    }
+
+    fn remove(&mut self, path: &str) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.total_bytes -= entry.size;
+            self.lru.remove(&entry.tick);
+            self.dirty = true;
+        }
+    }
+
+    /// Pick the least recently read entry except `keep` while the usage is
+    /// over budget.
+    fn next_victim(&self, keep: Option<&str>) -> Option<String> {
+        if !matches!(self.max_bytes, Some(v) if self.total_bytes > v) {
+            return None;
+        }
+
+        self.lru
+            .values()
+            .find(|path| Some(path.as_str()) != keep)
+            .cloned()
+    }
+}
+
+fn encode_path(path: &str) -> String {
+    utf8_percent_encode(path, NON_ALPHANUMERIC).to_string()
+}
+
+fn decode_path(name: &str) -> Option<String> {
+    percent_decode_str(name)
+        .decode_utf8()
+        .ok()
+        .map(|v| v.to_string())
+}
+
+#[derive(Debug)]
+pub struct Adapter {
+    root: PathBuf,
+    index: Mutex<Index>,
+}
+
+impl Adapter {
+    fn data_path(&self, path: &str) -> PathBuf {
+        self.root.join("data").join(encode_path(path))
+    }
+
+    fn tmp_path(&self) -> PathBuf {
+        self.root.join("tmp").join(Uuid::new_v4().to_string())
+    }
+
+    fn check_size(&self, size: u64) -> Result<()> {
+        if matches!(self.index.lock().max_bytes, Some(v) if size > v) {
+            return Err(
+                Error::new(ErrorKind::Unexpected, "content is larger than max bytes")
+                    .with_context("size", size.to_string()),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Update the index after `path` has been written or deleted.
+    fn blocking_update(&self, path: &str, size: Option<u64>) -> Result<()> {
+        let mut index = self.index.lock();
+        match size {
+            Some(size) => index.insert(path.to_string(), size),
+            None => index.remove(path),
+        }
+
+        self.evict(&mut index, Some(path))
+    }
+
+    /// Delete least recently read entries until the usage is under budget,
+    /// and persist the index.
+    ///
+    /// Files are deleted with the index locked, so they will not race with
+    /// concurrent writes.
+    fn evict(&self, index: &mut Index, keep: Option<&str>) -> Result<()> {
+        while let Some(victim) = index.next_victim(keep) {
+            debug!("cache fs evict entry {victim}");
+            match std::fs::remove_file(self.data_path(&victim)) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                    return Err(parse_io_error(err).with_context("path", &victim))
+                }
+                _ => index.remove(&victim),
+            }
+        }
+
+        index.persist(&self.root).map_err(parse_io_error)
+    }
+}
+
+impl Drop for Adapter {
+    fn drop(&mut self) {
+        if let Err(err) = self.index.lock().persist(&self.root) {
+            warn!("cache fs persist index failed: {err:?}");
+        }
+    }
+}
+
+#[async_trait]
+impl kv::Adapter for Adapter {
+    fn metadata(&self) -> kv::Metadata {
+        kv::Metadata::new(
+            Scheme::CacheFs,
+            &self.root.to_string_lossy(),
+            AccessorCapability::Read
+                | AccessorCapability::Write
+                | AccessorCapability::Scan
+                | AccessorCapability::Blocking,
+        )
+    }
+
+    async fn get(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        match fs::read(self.data_path(path)).await {
+            Ok(bs) => {
+                self.index.lock().touch(path);
+                Ok(Some(bs))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(parse_io_error(err)),
+        }
+    }
+
+    fn blocking_get(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        match std::fs::read(self.data_path(path)) {
+            Ok(bs) => {
+                self.index.lock().touch(path);
+                Ok(Some(bs))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(parse_io_error(err)),
+        }
+    }
+
+    async fn get_len(&self, path: &str) -> Result<Option<u64>> {
+        Ok(self.index.lock().entries.get(path).map(|v| v.size))
+    }
+
+    async fn set(&self, path: &str, value: &[u8]) -> Result<()> {
+        self.check_size(value.len() as u64)?;
+
+        // Write into tmp first so that readers will never see partial files.
+        let tmp = self.tmp_path();
+        fs::write(&tmp, value).await.map_err(parse_io_error)?;
+        fs::rename(&tmp, self.data_path(path))
+            .await
+            .map_err(parse_io_error)?;
+
+        self.blocking_update(path, Some(value.len() as u64))
+    }
+
+    fn blocking_set(&self, path: &str, value: &[u8]) -> Result<()> {
+        self.check_size(value.len() as u64)?;
+
+        let tmp = self.tmp_path();
+        std::fs::write(&tmp, value).map_err(parse_io_error)?;
+        std::fs::rename(&tmp, self.data_path(path)).map_err(parse_io_error)?;
+
+        self.blocking_update(path, Some(value.len() as u64))
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        match fs::remove_file(self.data_path(path)).await {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(parse_io_error(err)),
+            _ => {}
+        }
+
+        self.blocking_update(path, None)
+    }
+
+    fn blocking_delete(&self, path: &str) -> Result<()> {
+        match std::fs::remove_file(self.data_path(path)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(parse_io_error(err)),
+            _ => {}
+        }
+
+        self.blocking_update(path, None)
+    }
+
+    async fn scan(&self, path: &str) -> Result<Vec<String>> {
+        self.blocking_scan(path)
+    }
+
+    fn blocking_scan(&self, path: &str) -> Result<Vec<String>> {
+        let mut keys: Vec<_> = self
+            .index
+            .lock()
+            .entries
+            .keys()
+            .filter(|k| k.starts_with(path))
+            .cloned()
+            .collect();
+        keys.sort();
+
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_operator(root: &Path, max_bytes: u64) -> Operator {
+        let mut builder = CacheFsBuilder::default();
+        builder.root(&root.to_string_lossy()).max_bytes(max_bytes);
+        Operator::new(builder).unwrap().finish()
+    }
+
+    #[tokio::test]
+    async fn test_evict_least_recently_read() {
+        let root = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let op = new_operator(&root, 300);
+
+        for path in ["a", "b", "c"] {
+            op.write(path, vec![0; 100]).await.unwrap();
+        }
+        // Read `a` so that `b` becomes the least recently read.
+        op.read("a").await.unwrap();
+
+        op.write("dir/d", vec![0; 100]).await.unwrap();
+        let err = op.stat("b").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert!(!root.join("data").join("b").exists());
+        for path in ["a", "c", "dir/d"] {
+            assert_eq!(op.stat(path).await.unwrap().content_length(), 100);
+        }
+
+        // Overwriting could evict multiple entries.
+        op.write("c", vec![0; 250]).await.unwrap();
+        assert_eq!(op.read("c").await.unwrap().len(), 250);
+        for path in ["a", "dir/d"] {
+            let err = op.stat(path).await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::NotFound);
+        }
+
+        let err = op.write("e", vec![0; 301]).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_index() {
+        let root = std::env::temp_dir().join(Uuid::new_v4().to_string());
+
+        let op = new_operator(&root, 300);
+        for path in ["a", "b", "c"] {
+            op.write(path, vec![0; 100]).await.unwrap();
+        }
+        op.read("a").await.unwrap();
+        drop(op);
+
+        // The access order is kept while index exists.
+        let op = new_operator(&root, 300);
+        op.write("d", vec![0; 100]).await.unwrap();
+        assert!(op.stat("a").await.is_ok());
+        assert!(op.stat("b").await.is_err());
+        drop(op);
+
+        std::fs::remove_file(root.join("index")).unwrap();
+
+        // Smaller budget will evict entries while building.
+        let op = new_operator(&root, 200);
+        assert!(root.join("index").exists());
+        let mut remaining = Vec::new();
+        for path in ["a", "c", "d"] {
+            if op.stat(path).await.is_ok() {
+                assert_eq!(op.read(path).await.unwrap().len(), 100);
+                remaining.push(path);
+            }
+        }
+        assert_eq!(remaining.len(), 2);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_blocking_evict() {
+        let root = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let op = new_operator(&root, 200).blocking();
+
+        op.write("a", vec![0; 100]).unwrap();
+        op.write("b", vec![0; 100]).unwrap();
+        op.read("a").unwrap();
+        op.write("c", vec![0; 100]).unwrap();
+
+        assert!(op.stat("a").is_ok());
+        assert!(op.stat("b").is_err());
+        assert!(op.stat("c").is_ok());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,994 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::header::CONTENT_LENGTH;
+use http::header::CONTENT_TYPE;
+use http::Request;
+use http::Response;
+use http::StatusCode;
+use http::Uri;
+use log::debug;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::error::parse_error;
+use super::pager::CosPager;
+use super::signer::CosSigner;
+use super::writer::CosWriter;
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// Tencent Cloud COS services support.
+///
+/// # Capabilities
+///
+/// This service can be used to:
+///
+/// - [x] read
+/// - [x] write
+/// - [x] list
+/// - [x] scan
+/// - [ ] presign
+/// - [ ] blocking
+///
+/// # Configuration
+///
+/// - `root`: Set the work directory for backend
+/// - `bucket`: Set the container name for backend
+/// - `endpoint`: Customizable endpoint setting
+/// - `secret_id`: Set the secret_id for backend.
+/// - `secret_key`: Set the secret_key for backend.
+/// - `write_part_size`: Set the part size of multipart upload.
+///
+/// You can refer to [`CosBuilder`]'s docs for more information
+///
+/// # Multipart Upload
+///
+/// `Writer::append` is implemented via multipart upload. Appended bytes
+/// will be uploaded as parts of `write_part_size`, the upload will be
+/// aborted if the writer is aborted or dropped before `close`.
+///
+/// # Example
+///
+/// ## Via Builder
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::services::Cos;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     // create backend builder
+///     let mut builder = Cos::default();
+///
+///     // set the storage bucket for OpenDAL
+///     builder.bucket("examplebucket-1250000000");
+///     // set the endpoint of the region
+///     builder.endpoint("https://cos.ap-beijing.myqcloud.com");
+///     // Set the secret_id and secret_key.
+///     //
+///     // If credential not set, OpenDAL will send request without signing
+///     // like anonymous user.
+///     builder.secret_id("secret_id");
+///     builder.secret_key("secret_key");
+///
+///     let op: Operator = Operator::new(builder)?.finish();
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Default, Clone)]
+pub struct CosBuilder {
+    root: Option<String>,
+    endpoint: Option<String>,
+    secret_id: Option<String>,
+    secret_key: Option<String>,
+    bucket: Option<String>,
+    write_part_size: Option<usize>,
+    http_client: Option<HttpClient>,
+}
+
+impl Debug for CosBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Builder")
+            .field("root", &self.root)
+            .field("endpoint", &self.endpoint)
+            .field("secret_id", &"<redacted>")
+            .field("secret_key", &"<redacted>")
+            .field("bucket", &self.bucket)
+            .field("write_part_size", &self.write_part_size)
+            .finish()
+    }
+}
+
+impl CosBuilder {
+    /// Set root of this backend.
+    ///
+    /// All operations will happen under this root.
+    pub fn root(&mut self, root: &str) -> &mut Self {
+        if !root.is_empty() {
+            self.root = Some(root.to_string())
+        }
+
+        self
+    }
+
+    /// Set endpoint of this backend.
+    ///
+    /// Both tencent cloud default domain and user domain endpoints are allowed.
+    /// Please DO NOT add the bucket name to the endpoint.
+    ///
+    /// - `https://cos.ap-beijing.myqcloud.com`
+    /// - `cos.ap-beijing.myqcloud.com` (https by default)
+    /// - `https://custom.cos.com` (port should not be set)
+    pub fn endpoint(&mut self, endpoint: &str) -> &mut Self {
+        if !endpoint.is_empty() {
+            self.endpoint = Some(endpoint.trim_end_matches('/').to_string());
+        }
+
+        self
+    }
+
+    /// Set secret_id of this backend.
+    pub fn secret_id(&mut self, secret_id: &str) -> &mut Self {
+        if !secret_id.is_empty() {
+            self.secret_id = Some(secret_id.to_string());
+        }
+
+        self
+    }
+
+    /// Set secret_key of this backend.
+    pub fn secret_key(&mut self, secret_key: &str) -> &mut Self {
+        if !secret_key.is_empty() {
+            self.secret_key = Some(secret_key.to_string());
+        }
+
+        self
+    }
+
+    /// Set bucket of this backend.
+    /// The param is required.
+    ///
+    /// COS bucket name contains the APPID, like `examplebucket-1250000000`.
+    pub fn bucket(&mut self, bucket: &str) -> &mut Self {
+        if !bucket.is_empty() {
+            self.bucket = Some(bucket.to_string());
+        }
+
+        self
+    }
+
+    /// Set the part size of multipart upload.
+    ///
+    /// Bytes appended by writer will be buffered until `write_part_size`
+    /// is reached and then uploaded as one part. The last part could be
+    /// smaller than it.
+    ///
+    /// COS requires part size to be at least 1 MiB, building will fail
+    /// with [`ErrorKind::ConfigInvalid`] if given size is smaller.
+    ///
+    /// If not set, every append will be uploaded as one part directly.
+    pub fn write_part_size(&mut self, size: usize) -> &mut Self {
+        self.write_part_size = Some(size);
+        self
+    }
+
+    /// Specify the http client that used by this service.
+    ///
+    /// # Notes
+    ///
+    /// This API is part of OpenDAL's Raw API. `HttpClient` could be changed
+    /// during minor updates.
+    pub fn http_client(&mut self, client: HttpClient) -> &mut Self {
+        self.http_client = Some(client);
+        self
+    }
+}
+
+impl Builder for CosBuilder {
+    const SCHEME: Scheme = Scheme::Cos;
+    type Accessor = CosBackend;
+
+    fn from_map(map: HashMap<String, String>) -> Self {
+        let mut builder = CosBuilder::default();
+
+        map.get("root").map(|v| builder.root(v));
+        map.get("bucket").map(|v| builder.bucket(v));
+        map.get("endpoint").map(|v| builder.endpoint(v));
+        map.get("secret_id").map(|v| builder.secret_id(v));
+        map.get("secret_key").map(|v| builder.secret_key(v));
+        map.get("write_part_size")
+            .and_then(|v| v.parse().ok())
+            .map(|v| builder.write_part_size(v));
+
+        builder
+    }
+
+    fn build(&mut self) -> Result<Self::Accessor> {
+        debug!("backend build started: {:?}", &self);
+
+        let root = normalize_root(&self.root.take().unwrap_or_default());
+        debug!("backend use root {}", root);
+
+        let bucket = match &self.bucket {
+            Some(bucket) => Ok(bucket.to_string()),
+            None => Err(Error::new(ErrorKind::ConfigInvalid, "bucket is empty")
+                .with_context("service", Scheme::Cos)),
+        }?;
+        debug!("backend use bucket {}", &bucket);
+
+        if let Some(size) = self.write_part_size {
+            if size < MIN_WRITE_PART_SIZE {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "write_part_size must be at least 1 MiB",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Cos)
+                .with_context("write_part_size", size.to_string()));
+            }
+        }
+
+        let uri = match &self.endpoint {
+            Some(endpoint) => endpoint.parse::<Uri>().map_err(|err| {
+                Error::new(ErrorKind::ConfigInvalid, "endpoint is invalid")
+                    .with_context("service", Scheme::Cos)
+                    .set_source(err)
+            }),
+            None => Err(Error::new(ErrorKind::ConfigInvalid, "endpoint is empty")
+                .with_context("service", Scheme::Cos)),
+        }?;
+
+        let scheme = match uri.scheme_str() {
+            Some(scheme) => scheme.to_string(),
+            None => "https".to_string(),
+        };
+
+        // Use virtual-hosted-style for the default domain, like
+        // `examplebucket-1250000000.cos.ap-beijing.myqcloud.com`.
+        let endpoint = {
+            let host = uri.host().unwrap_or_default().to_string();
+            if host.starts_with("cos.") && host.ends_with(".myqcloud.com") {
+                format!("{bucket}.{host}")
+            } else {
+                host
+            }
+        };
+        debug!("backend use endpoint {}", &endpoint);
+
+        let client = if let Some(client) = self.http_client.take() {
+            client
+        } else {
+            HttpClient::new().map_err(|err| {
+                err.with_operation("Builder::build")
+                    .with_context("service", Scheme::Cos)
+            })?
+        };
+
+        let signer = CosSigner::new(self.secret_id.as_deref(), self.secret_key.as_deref());
+
+        debug!("backend build finished: {:?}", &self);
+        Ok(CosBackend {
+            client,
+            root,
+            endpoint: format!("{}://{}", &scheme, &endpoint),
+            signer: Arc::new(signer),
+            bucket,
+            write_part_size: self.write_part_size,
+        })
+    }
+}
+
+/// The minimum part size of multipart upload, except the last part.
+const MIN_WRITE_PART_SIZE: usize = 1024 * 1024;
+
+pub(super) const X_COS_VERSION_ID: &str = "x-cos-version-id";
+
+/// Backend for Tencent Cloud COS services.
+#[derive(Debug, Clone)]
+pub struct CosBackend {
+    pub client: HttpClient,
+    root: String,
+    endpoint: String,
+    pub signer: Arc<CosSigner>,
+    bucket: String,
+    /// Part size of multipart upload, `None` means every append is a part.
+    pub(super) write_part_size: Option<usize>,
+}
+
+#[async_trait]
+impl Accessor for CosBackend {
+    type Reader = IncomingAsyncBody;
+    type BlockingReader = ();
+    type Writer = oio::MultipartUploadWriter<CosWriter>;
+    type BlockingWriter = ();
+    type Pager = CosPager;
+    type BlockingPager = ();
+
+    fn info(&self) -> AccessorInfo {
+        use AccessorCapability::*;
+        use AccessorHint::*;
+
+        let mut am = AccessorInfo::default();
+        am.set_scheme(Scheme::Cos)
+            .set_root(&self.root)
+            .set_name(&self.bucket)
+            .set_capabilities(Read | Write | List | Scan)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                write_with_content_type: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                scan: true,
+                ..Default::default()
+            })
+            .set_hints(ReadStreamable);
+
+        am
+    }
+
+    async fn create(&self, path: &str, _: OpCreate) -> Result<RpCreate> {
+        let mut req = self.cos_put_object_request(path, Some(0), None, AsyncBody::Empty)?;
+
+        self.signer.sign(&mut req)?;
+
+        let resp = self.client.send_async(req).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::CREATED | StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(RpCreate::default())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let range = args.range();
+        let resp = self.cos_get_object(path, range).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let w = CosWriter::new(self.clone(), args, path.to_string());
+
+        Ok((
+            RpWrite::default(),
+            oio::MultipartUploadWriter::new(w, self.write_part_size),
+        ))
+    }
+
+    async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+        // Stat root always returns a DIR.
+        if path == "/" {
+            return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
+        }
+
+        let resp = self.cos_get_head_object(path).await?;
+
+        let status = resp.status();
+
+        // The response is very similar to azblob.
+        match status {
+            StatusCode::OK => parse_into_metadata(path, resp.headers()).map(RpStat::new),
+            StatusCode::NOT_FOUND if path.ends_with('/') => {
+                Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        let resp = self.cos_delete_object(path).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::NO_CONTENT | StatusCode::ACCEPTED | StatusCode::NOT_FOUND => {
+                Ok(RpDelete::default())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        Ok((
+            RpList::default(),
+            CosPager::new(Arc::new(self.clone()), &self.root, path, "/", args.limit()),
+        ))
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        Ok((
+            RpScan::default(),
+            CosPager::new(Arc::new(self.clone()), &self.root, path, "", args.limit()),
+        ))
+    }
+}
+
+impl CosBackend {
+    async fn cos_get_object(
+        &self,
+        path: &str,
+        range: BytesRange,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!("{}/{}", self.endpoint, percent_encode_path(&p));
+
+        let mut req = Request::get(&url);
+
+        if !range.is_full() {
+            req = req.header(http::header::RANGE, range.to_header())
+        }
+
+        let mut req = req
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+
+    pub fn cos_put_object_request(
+        &self,
+        path: &str,
+        size: Option<usize>,
+        content_type: Option<&str>,
+        body: AsyncBody,
+    ) -> Result<Request<AsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!("{}/{}", self.endpoint, percent_encode_path(&p));
+
+        let mut req = Request::put(&url);
+
+        if let Some(size) = size {
+            req = req.header(CONTENT_LENGTH, size)
+        }
+
+        if let Some(mime) = content_type {
+            req = req.header(CONTENT_TYPE, mime)
+        }
+
+        let req = req.body(body).map_err(new_request_build_error)?;
+
+        Ok(req)
+    }
+
+    async fn cos_get_head_object(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!("{}/{}", self.endpoint, percent_encode_path(&p));
+
+        let req = Request::head(&url);
+
+        let mut req = req
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+
+    async fn cos_delete_object(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!("{}/{}", self.endpoint, percent_encode_path(&p));
+
+        let req = Request::delete(&url);
+
+        let mut req = req
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+
+    pub(super) async fn cos_initiate_multipart_upload(
+        &self,
+        path: &str,
+        content_type: Option<&str>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!("{}/{}?uploads", self.endpoint, percent_encode_path(&p));
+
+        let mut req = Request::post(&url);
+
+        if let Some(mime) = content_type {
+            req = req.header(CONTENT_TYPE, mime)
+        }
+
+        let mut req = req
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+
+    pub(super) async fn cos_upload_part(
+        &self,
+        path: &str,
+        upload_id: &str,
+        part_number: usize,
+        size: u64,
+        body: AsyncBody,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!(
+            "{}/{}?partNumber={}&uploadId={}",
+            self.endpoint,
+            percent_encode_path(&p),
+            part_number,
+            upload_id
+        );
+
+        let mut req = Request::put(&url)
+            .header(CONTENT_LENGTH, size)
+            .body(body)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+
+    pub(super) async fn cos_complete_multipart_upload(
+        &self,
+        path: &str,
+        upload_id: &str,
+        parts: &[oio::MultipartUploadPart],
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!(
+            "{}/{}?uploadId={}",
+            self.endpoint,
+            percent_encode_path(&p),
+            upload_id
+        );
+
+        let content = build_complete_multipart_upload_body(parts)?;
+
+        // Make sure content length has been set to avoid post with chunked encoding.
+        let mut req = Request::post(&url)
+            .header(CONTENT_LENGTH, content.len())
+            .header(CONTENT_TYPE, "application/xml")
+            .body(AsyncBody::Bytes(Bytes::from(content)))
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+
+    /// Abort an on-going multipart upload.
+    ///
+    /// All parts uploaded will be freed by COS after this call.
+    pub(super) async fn cos_abort_multipart_upload(
+        &self,
+        path: &str,
+        upload_id: &str,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!(
+            "{}/{}?uploadId={}",
+            self.endpoint,
+            percent_encode_path(&p),
+            upload_id
+        );
+
+        let mut req = Request::delete(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+
+    pub(crate) async fn cos_list_objects(
+        &self,
+        path: &str,
+        next_marker: &str,
+        delimiter: &str,
+        limit: Option<usize>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let mut queries = vec![];
+        if !path.is_empty() {
+            queries.push(format!("prefix={}", percent_encode_path(&p)));
+        }
+        if !delimiter.is_empty() {
+            queries.push(format!("delimiter={delimiter}"));
+        }
+        if let Some(limit) = limit {
+            queries.push(format!("max-keys={limit}"));
+        }
+        if !next_marker.is_empty() {
+            queries.push(format!("marker={next_marker}"));
+        }
+
+        let url = if queries.is_empty() {
+            self.endpoint.to_string()
+        } else {
+            format!("{}?{}", self.endpoint, queries.join("&"))
+        };
+
+        let mut req = Request::get(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+}
+
+/// Result of InitiateMultipartUpload
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub(super) struct InitiateMultipartUploadResult {
+    pub upload_id: String,
+}
+
+/// Result of CompleteMultipartUpload
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub(super) struct CompleteMultipartUploadResult {
+    #[serde(rename = "ETag")]
+    pub etag: String,
+}
+
+/// Request of CompleteMultipartUpload
+#[derive(Default, Debug, Serialize)]
+#[serde(default, rename = "CompleteMultipartUpload", rename_all = "PascalCase")]
+struct CompleteMultipartUploadRequest {
+    part: Vec<CompleteMultipartUploadRequestPart>,
+}
+
+#[derive(Default, Debug, Serialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct CompleteMultipartUploadRequestPart {
+    #[serde(rename = "PartNumber")]
+    part_number: usize,
+    #[serde(rename = "ETag")]
+    etag: String,
+}
+
+/// Build the body of CompleteMultipartUpload.
+///
+/// COS expects the etag in `CompleteMultipartUpload` to be the same as
+/// the quoted value returned by `UploadPart`, so quotes will be added if
+/// they are missing.
+fn build_complete_multipart_upload_body(parts: &[oio::MultipartUploadPart]) -> Result<String> {
+    quick_xml::se::to_string(&CompleteMultipartUploadRequest {
+        part: parts
+            .iter()
+            .map(|part| CompleteMultipartUploadRequestPart {
+                part_number: part.part_number,
+                etag: format!("\"{}\"", part.etag.trim_matches('"')),
+            })
+            .collect(),
+    })
+    .map_err(new_xml_deserialize_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use wiremock::matchers::body_string;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    fn new_backend(endpoint: &str, write_part_size: Option<usize>) -> CosBackend {
+        let signer = CosSigner::new(Some("secret_id"), Some("secret_key"));
+
+        CosBackend {
+            client: HttpClient::new().unwrap(),
+            root: "/".to_string(),
+            endpoint: endpoint.to_string(),
+            signer: Arc::new(signer),
+            bucket: "test".to_string(),
+            write_part_size,
+        }
+    }
+
+    async fn mount_initiate(mock_server: &MockServer) {
+        Mock::given(method("POST"))
+            .and(path("/file"))
+            .and(query_param("uploads", ""))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<InitiateMultipartUploadResult><Bucket>test</Bucket><Key>file</Key><UploadId>upload-id</UploadId></InitiateMultipartUploadResult>"#,
+            ))
+            .expect(1)
+            .mount(mock_server)
+            .await;
+    }
+
+    #[test]
+    fn test_build_complete_multipart_upload_body() {
+        let body = build_complete_multipart_upload_body(&[
+            oio::MultipartUploadPart::new(1, r#""etag-1""#),
+            oio::MultipartUploadPart::new(2, "etag-2"),
+        ])
+        .expect("must succeed");
+
+        assert_eq!(
+            body,
+            "<CompleteMultipartUpload>\
+             <Part><PartNumber>1</PartNumber><ETag>&quot;etag-1&quot;</ETag></Part>\
+             <Part><PartNumber>2</PartNumber><ETag>&quot;etag-2&quot;</ETag></Part>\
+             </CompleteMultipartUpload>"
+        );
+    }
+
+    #[test]
+    fn test_write_part_size_too_small() {
+        let mut builder = CosBuilder::default();
+        builder
+            .bucket("test-1250000000")
+            .endpoint("https://cos.ap-beijing.myqcloud.com")
+            .write_part_size(100 * 1024);
+
+        let err = builder.build().expect_err("must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        mount_initiate(&mock_server).await;
+        for (part_number, size) in [(1, 100 * 1024), (2, 100 * 1024), (3, 50 * 1024)] {
+            Mock::given(method("PUT"))
+                .and(path("/file"))
+                .and(query_param("uploadId", "upload-id"))
+                .and(query_param("partNumber", part_number.to_string()))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("ETag", format!("\"etag-{size}\"").as_str()),
+                )
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .and(body_string(
+                "<CompleteMultipartUpload>\
+                 <Part><PartNumber>1</PartNumber><ETag>&quot;etag-102400&quot;</ETag></Part>\
+                 <Part><PartNumber>2</PartNumber><ETag>&quot;etag-102400&quot;</ETag></Part>\
+                 <Part><PartNumber>3</PartNumber><ETag>&quot;etag-51200&quot;</ETag></Part>\
+                 </CompleteMultipartUpload>",
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_backend(&mock_server.uri(), Some(100 * 1024));
+        let (_, mut w) = backend
+            .write("file", OpWrite::new().with_append())
+            .await
+            .expect("writer must be created");
+        for _ in 0..5 {
+            oio::Write::append(&mut w, Bytes::from(vec![0; 50 * 1024]))
+                .await
+                .expect("append must succeed");
+        }
+        oio::Write::close(&mut w).await.expect("close must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_small_content() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/file"))
+            .and(body_string("Hello, World!"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_backend(&mock_server.uri(), Some(100 * 1024));
+        let (_, mut w) = backend
+            .write("file", OpWrite::new().with_append())
+            .await
+            .expect("writer must be created");
+        oio::Write::append(&mut w, Bytes::from("Hello, World!"))
+            .await
+            .expect("append must succeed");
+        oio::Write::close(&mut w).await.expect("close must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_write_read() {
+        use std::sync::Mutex;
+
+        use wiremock::Request;
+        use wiremock::Respond;
+
+        /// Store the content of PutObject and return it for GetObject.
+        #[derive(Clone, Default)]
+        struct ObjectResponder(Arc<Mutex<Vec<u8>>>);
+
+        impl Respond for ObjectResponder {
+            fn respond(&self, req: &Request) -> ResponseTemplate {
+                let mut content = self.0.lock().unwrap();
+                if req.method == wiremock::http::Method::Put {
+                    *content = req.body.clone();
+                    ResponseTemplate::new(200)
+                } else {
+                    ResponseTemplate::new(200).set_body_bytes(content.clone())
+                }
+            }
+        }
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        let responder = ObjectResponder::default();
+        Mock::given(method("PUT"))
+            .and(path("/file"))
+            .respond_with(responder.clone())
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/file"))
+            .respond_with(responder)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = OperatorBuilder::new(new_backend(&mock_server.uri(), None)).finish();
+        op.write("file", "Hello, World!")
+            .await
+            .expect("write must succeed");
+
+        let bs = op.read("file").await.expect("read must succeed");
+        assert_eq!(bs, b"Hello, World!");
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_abort() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        mount_initiate(&mock_server).await;
+        Mock::given(method("PUT"))
+            .and(path("/file"))
+            .respond_with(ResponseTemplate::new(200).insert_header("ETag", "\"etag\""))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_backend(&mock_server.uri(), None);
+        let (_, mut w) = backend
+            .write("file", OpWrite::new().with_append())
+            .await
+            .expect("writer must be created");
+        oio::Write::append(&mut w, Bytes::from("Hello, World!"))
+            .await
+            .expect("append must succeed");
+        oio::Write::abort(&mut w).await.expect("abort must succeed");
+
+        // Aborted writer will not abort again while dropping.
+        drop(w);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_abort_on_drop() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        mount_initiate(&mock_server).await;
+        Mock::given(method("PUT"))
+            .and(path("/file"))
+            .respond_with(ResponseTemplate::new(200).insert_header("ETag", "\"etag\""))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_backend(&mock_server.uri(), None);
+        let (_, mut w) = backend
+            .write("file", OpWrite::new().with_append())
+            .await
+            .expect("writer must be created");
+        oio::Write::append(&mut w, Bytes::from("Hello, World!"))
+            .await
+            .expect("append must succeed");
+
+        drop(w);
+        // Wait for the background abort.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
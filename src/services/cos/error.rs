@@ -0,0 +1,137 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bytes::Buf;
+use http::Response;
+use quick_xml::de;
+use serde::Deserialize;
+
+use crate::raw::*;
+use crate::Error;
+use crate::ErrorKind;
+use crate::Result;
+
+/// CosError is the error returned by cos service.
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct CosError {
+    code: String,
+    message: String,
+    resource: String,
+    request_id: String,
+    trace_id: String,
+}
+
+/// Parse error response into Error.
+pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
+    let (parts, body) = resp.into_parts();
+    let bs = body.bytes().await?;
+
+    let (mut kind, mut retryable) = parse_error_status(parts.status);
+
+    let (message, cos_err) = de::from_reader::<_, CosError>(bs.clone().reader())
+        .map(|cos_err| (format!("{cos_err:?}"), Some(cos_err)))
+        .unwrap_or_else(|_| (String::from_utf8_lossy(&bs).into_owned(), None));
+
+    if let Some(cos_err) = cos_err {
+        (kind, retryable) = match cos_err.code.as_str() {
+            // Credentials are not valid, users need to fix their config.
+            "InvalidAccessKeyId" | "SignatureDoesNotMatch" | "InvalidSecurityToken" => {
+                (ErrorKind::ConfigInvalid, false)
+            }
+            "AccessDenied" => (ErrorKind::PermissionDenied, false),
+            "NoSuchBucket" => (ErrorKind::NotFound, false),
+            _ => (kind, retryable),
+        }
+    }
+
+    let mut err = with_error_response_context(Error::new(kind, &message), &parts);
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
+
+    if retryable {
+        err = err.set_temporary();
+    }
+
+    Ok(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+    use http::StatusCode;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_error() {
+        let bs = bytes::Bytes::from(
+            r#"
+<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+<Code>NoSuchKey</Code>
+<Message>The specified key does not exist.</Message>
+<Resource>examplebucket-1250000000.cos.ap-beijing.myqcloud.com/object</Resource>
+<RequestId>NWE3YjQ1ZjNfOTYwYTBiMDlfZDg0OF8xMjM0</RequestId>
+<TraceId>OGVmYzZiMmQzYjA2OWNhODk0NTRkMTBiOWVmMDAxODc0OWRkZjk0ZDM1NmI1M2E2MTRlY2MzZDhmNmI5MWI1OTBjYzE2MjAxN2M1MzJiOTdkZjMxMDVlYTZjN2FiMmI0</TraceId>
+</Error>
+"#,
+        );
+
+        let out: CosError = de::from_reader(bs.reader()).expect("must success");
+        println!("{out:?}");
+
+        assert_eq!(out.code, "NoSuchKey");
+        assert_eq!(out.message, "The specified key does not exist.");
+        assert_eq!(
+            out.resource,
+            "examplebucket-1250000000.cos.ap-beijing.myqcloud.com/object"
+        );
+        assert_eq!(out.request_id, "NWE3YjQ1ZjNfOTYwYTBiMDlfZDg0OF8xMjM0");
+        assert!(out.trace_id.starts_with("OGVmYzZiMmQzYjA2OWNh"));
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_auth() -> Result<()> {
+        let cases = vec![
+            ("InvalidAccessKeyId", ErrorKind::ConfigInvalid),
+            ("SignatureDoesNotMatch", ErrorKind::ConfigInvalid),
+            ("AccessDenied", ErrorKind::PermissionDenied),
+        ];
+
+        for (code, kind) in cases {
+            let bs = bytes::Bytes::from(format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>{code}</Code>
+  <Message>some message</Message>
+  <RequestId>1D842BC54255</RequestId>
+</Error>"#
+            ));
+            let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+            let resp = Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .body(body)
+                .unwrap();
+
+            let err = parse_error(resp).await?;
+            assert_eq!(err.kind(), kind, "code: {code}");
+            assert!(!err.is_temporary(), "code: {code}");
+        }
+
+        Ok(())
+    }
+}
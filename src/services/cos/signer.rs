@@ -0,0 +1,284 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+
+use hmac::Hmac;
+use hmac::Mac;
+use http::header::AUTHORIZATION;
+use http::HeaderMap;
+use http::HeaderValue;
+use http::Method;
+use http::Request;
+use http::Uri;
+use percent_encoding::percent_decode_str;
+use percent_encoding::utf8_percent_encode;
+use percent_encoding::AsciiSet;
+use percent_encoding::NON_ALPHANUMERIC;
+use sha1::Digest;
+use sha1::Sha1;
+use time::Duration;
+use time::OffsetDateTime;
+
+use crate::raw::*;
+use crate::*;
+
+/// Signature will be valid for this duration after signing.
+const SIGN_EXPIRE: Duration = Duration::hours(1);
+
+/// Headers that need to be signed, all `x-cos-` headers will be signed too.
+const SIGNED_HEADERS: &[&str] = &["content-length", "content-md5", "content-type"];
+
+/// Characters except the unreserved ones in RFC 3986 will be encoded.
+static SIGN_ENCODE_SET: AsciiSet = NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// CosSigner signs requests with COS signature.
+///
+/// ref: <https://www.tencentcloud.com/document/product/436/7778>
+#[derive(Default, Clone)]
+pub struct CosSigner {
+    secret_id: Option<String>,
+    secret_key: Option<String>,
+}
+
+impl Debug for CosSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CosSigner")
+            .field("secret_id", &self.secret_id)
+            .field("secret_key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl CosSigner {
+    /// Requests will be sent without signing like anonymous user if
+    /// credential is not set.
+    pub fn new(secret_id: Option<&str>, secret_key: Option<&str>) -> Self {
+        Self {
+            secret_id: secret_id.map(|v| v.to_string()),
+            secret_key: secret_key.map(|v| v.to_string()),
+        }
+    }
+
+    /// Sign the request via the `Authorization` header.
+    pub fn sign(&self, req: &mut Request<AsyncBody>) -> Result<()> {
+        self.sign_at(req, OffsetDateTime::now_utc())
+    }
+
+    fn sign_at(&self, req: &mut Request<AsyncBody>, now: OffsetDateTime) -> Result<()> {
+        let (secret_id, secret_key) = match (&self.secret_id, &self.secret_key) {
+            (Some(secret_id), Some(secret_key)) => (secret_id, secret_key),
+            _ => return Ok(()),
+        };
+
+        let key_time = format!(
+            "{};{}",
+            now.unix_timestamp(),
+            (now + SIGN_EXPIRE).unix_timestamp()
+        );
+
+        let (param_list, params) = build_params(req.uri());
+        let (header_list, headers) = build_headers(req.headers());
+        let http_string = build_http_string(req.method(), req.uri(), &params, &headers);
+        let string_to_sign = format!("sha1\n{key_time}\n{}\n", sha1_hex(&http_string));
+
+        let sign_key = hmac_sha1_hex(secret_key.as_bytes(), &key_time);
+        let signature = hmac_sha1_hex(sign_key.as_bytes(), &string_to_sign);
+
+        let auth = format!(
+            "q-sign-algorithm=sha1&q-ak={secret_id}&q-sign-time={key_time}&q-key-time={key_time}&q-header-list={header_list}&q-url-param-list={param_list}&q-signature={signature}"
+        );
+        let auth = HeaderValue::from_str(&auth).map_err(|e| {
+            Error::new(ErrorKind::Unexpected, "header value is invalid")
+                .with_operation("cos::CosSigner")
+                .set_source(e)
+        })?;
+        req.headers_mut().insert(AUTHORIZATION, auth);
+
+        Ok(())
+    }
+}
+
+fn sign_encode(s: &str) -> String {
+    utf8_percent_encode(s, &SIGN_ENCODE_SET).to_string()
+}
+
+/// Build the sorted key list and key-value pairs from given pairs.
+///
+/// Both keys and values will be encoded, keys will be in lowercase.
+fn build_sorted_pairs(pairs: impl Iterator<Item = (String, String)>) -> (String, String) {
+    let mut pairs: Vec<(String, String)> = pairs
+        .map(|(k, v)| (sign_encode(&k).to_lowercase(), sign_encode(&v)))
+        .collect();
+    pairs.sort();
+
+    let keys = pairs
+        .iter()
+        .map(|(k, _)| k.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let kvs = pairs
+        .iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    (keys, kvs)
+}
+
+/// Build `UrlParamList` and `HttpParameters` from the query.
+fn build_params(uri: &Uri) -> (String, String) {
+    build_sorted_pairs(
+        uri.query()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|kv| !kv.is_empty())
+            .map(|kv| {
+                let (k, v) = kv.split_once('=').unwrap_or((kv, ""));
+                (
+                    percent_decode_str(k).decode_utf8_lossy().to_string(),
+                    percent_decode_str(v).decode_utf8_lossy().to_string(),
+                )
+            }),
+    )
+}
+
+/// Build `HeaderList` and `HttpHeaders` from the headers to sign.
+fn build_headers(headers: &HeaderMap) -> (String, String) {
+    build_sorted_pairs(
+        headers
+            .iter()
+            .filter(|(k, _)| {
+                SIGNED_HEADERS.contains(&k.as_str()) || k.as_str().starts_with("x-cos-")
+            })
+            .map(|(k, v)| {
+                (
+                    k.as_str().to_string(),
+                    v.to_str().unwrap_or_default().trim().to_string(),
+                )
+            }),
+    )
+}
+
+/// Build the http string:
+///
+/// ```text
+/// HttpMethod + "\n"
+/// + UriPathname + "\n"
+/// + HttpParameters + "\n"
+/// + HttpHeaders + "\n"
+/// ```
+fn build_http_string(method: &Method, uri: &Uri, params: &str, headers: &str) -> String {
+    let path = percent_decode_str(uri.path()).decode_utf8_lossy();
+
+    format!(
+        "{}\n{path}\n{params}\n{headers}\n",
+        method.as_str().to_lowercase()
+    )
+}
+
+fn hex(bs: &[u8]) -> String {
+    bs.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn sha1_hex(s: &str) -> String {
+    hex(&Sha1::digest(s.as_bytes()))
+}
+
+fn hmac_sha1_hex(key: &[u8], s: &str) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("hmac accepts any key size");
+    mac.update(s.as_bytes());
+
+    hex(&mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::CONTENT_TYPE;
+
+    use super::*;
+
+    #[test]
+    fn test_build_http_string() {
+        let req = Request::put(
+            "https://examplebucket-1250000000.cos.ap-beijing.myqcloud.com/exampleobject%28%E8%85%BE%E8%AE%AF%E4%BA%91%29?partNumber=1&uploadId=upload%2Did",
+        )
+        .header(CONTENT_TYPE, "text/plain")
+        .header("x-cos-acl", "private")
+        .header("x-custom", "ignored")
+        .body(AsyncBody::Empty)
+        .unwrap();
+
+        let (param_list, params) = build_params(req.uri());
+        assert_eq!(param_list, "partnumber;uploadid");
+        assert_eq!(params, "partnumber=1&uploadid=upload-id");
+
+        let (header_list, headers) = build_headers(req.headers());
+        assert_eq!(header_list, "content-type;x-cos-acl");
+        assert_eq!(headers, "content-type=text%2Fplain&x-cos-acl=private");
+
+        assert_eq!(
+            build_http_string(req.method(), req.uri(), &params, &headers),
+            "put\n/exampleobject(腾讯云)\npartnumber=1&uploadid=upload-id\ncontent-type=text%2Fplain&x-cos-acl=private\n"
+        );
+    }
+
+    #[test]
+    fn test_sign() {
+        let signer = CosSigner::new(Some("secret_id"), Some("secret_key"));
+        let now = OffsetDateTime::from_unix_timestamp(1669459385).unwrap();
+
+        let mut req = Request::get("https://bucket.cos.ap-beijing.myqcloud.com/file?uploads")
+            .body(AsyncBody::Empty)
+            .unwrap();
+        signer.sign_at(&mut req, now).unwrap();
+
+        let http_string = "get\n/file\nuploads=\n\n";
+        let string_to_sign = format!("sha1\n1669459385;1669462985\n{}\n", sha1_hex(http_string));
+        let signature = hmac_sha1_hex(
+            hmac_sha1_hex(b"secret_key", "1669459385;1669462985").as_bytes(),
+            &string_to_sign,
+        );
+        assert_eq!(
+            req.headers()[AUTHORIZATION].to_str().unwrap(),
+            format!("q-sign-algorithm=sha1&q-ak=secret_id&q-sign-time=1669459385;1669462985&q-key-time=1669459385;1669462985&q-header-list=&q-url-param-list=uploads&q-signature={signature}")
+        );
+    }
+
+    #[test]
+    fn test_sign_anonymous() {
+        let signer = CosSigner::new(None, None);
+
+        let mut req = Request::get("https://bucket.cos.ap-beijing.myqcloud.com/file")
+            .body(AsyncBody::Empty)
+            .unwrap();
+        signer.sign(&mut req).unwrap();
+
+        assert!(req.headers().get(AUTHORIZATION).is_none());
+    }
+
+    #[test]
+    fn test_hmac_sha1_hex() {
+        // Test vector from RFC 2202.
+        assert_eq!(
+            hmac_sha1_hex(b"Jefe", "what do ya want for nothing?"),
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"
+        );
+    }
+}
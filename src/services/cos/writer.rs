@@ -0,0 +1,169 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use bytes::Buf;
+use http::StatusCode;
+
+use super::backend::CompleteMultipartUploadResult;
+use super::backend::CosBackend;
+use super::backend::InitiateMultipartUploadResult;
+use super::backend::X_COS_VERSION_ID;
+use super::error::parse_error;
+use crate::ops::OpWrite;
+use crate::raw::*;
+use crate::*;
+
+pub struct CosWriter {
+    backend: CosBackend,
+
+    op: OpWrite,
+    path: String,
+}
+
+impl CosWriter {
+    pub fn new(backend: CosBackend, op: OpWrite, path: String) -> Self {
+        CosWriter { backend, op, path }
+    }
+}
+
+#[async_trait]
+impl oio::MultipartUploadWrite for CosWriter {
+    async fn write_once(&self, size: u64, body: AsyncBody) -> Result<RpWrite> {
+        let mut req = self.backend.cos_put_object_request(
+            &self.path,
+            Some(size as usize),
+            self.op.content_type(),
+            body,
+        )?;
+
+        self.backend.signer.sign(&mut req)?;
+
+        let resp = self.backend.client.send_async(req).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::CREATED | StatusCode::OK => {
+                let rp = parse_into_write_reply(resp.headers(), X_COS_VERSION_ID)?;
+                resp.into_body().consume().await?;
+                Ok(rp)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn initiate_part(&self) -> Result<String> {
+        let resp = self
+            .backend
+            .cos_initiate_multipart_upload(&self.path, self.op.content_type())
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let result: InitiateMultipartUploadResult =
+                    quick_xml::de::from_reader(bs.reader()).map_err(new_xml_deserialize_error)?;
+
+                Ok(result.upload_id)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn write_part(
+        &self,
+        upload_id: &str,
+        part_number: usize,
+        size: u64,
+        body: AsyncBody,
+    ) -> Result<oio::MultipartUploadPart> {
+        let resp = self
+            .backend
+            .cos_upload_part(&self.path, upload_id, part_number, size, body)
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let etag = parse_etag(resp.headers())?
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::Unexpected,
+                            "ETag not present in returning response",
+                        )
+                    })?
+                    .to_string();
+
+                resp.into_body().consume().await?;
+
+                Ok(oio::MultipartUploadPart::new(part_number, etag))
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn complete_part(
+        &self,
+        upload_id: &str,
+        parts: &[oio::MultipartUploadPart],
+    ) -> Result<RpWrite> {
+        let resp = self
+            .backend
+            .cos_complete_multipart_upload(&self.path, upload_id, parts)
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let mut rp = parse_into_write_reply(resp.headers(), X_COS_VERSION_ID)?;
+                let bs = resp.into_body().bytes().await?;
+
+                // The etag of multipart upload is only returned in body.
+                if !bs.is_empty() {
+                    let result: CompleteMultipartUploadResult =
+                        quick_xml::de::from_reader(bs.reader())
+                            .map_err(new_xml_deserialize_error)?;
+                    if !result.etag.is_empty() {
+                        rp = rp.with_etag(&result.etag);
+                    }
+                }
+
+                Ok(rp)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn abort_part(&self, upload_id: &str) -> Result<()> {
+        let resp = self
+            .backend
+            .cos_abort_multipart_upload(&self.path, upload_id)
+            .await?;
+
+        match resp.status() {
+            // COS returns code 204 if abort succeeds.
+            StatusCode::NO_CONTENT => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+}
@@ -0,0 +1,840 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Write;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::header;
+use http::Request;
+use http::Response;
+use http::StatusCode;
+use log::debug;
+use serde::Deserialize;
+use serde_json::json;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use super::error::parse_error;
+use super::error::parse_error_with_summary;
+use super::pager::DropboxPager;
+use super::writer::DropboxWriter;
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+const DEFAULT_DROPBOX_API_ENDPOINT: &str = "https://api.dropboxapi.com";
+const DEFAULT_DROPBOX_CONTENT_ENDPOINT: &str = "https://content.dropboxapi.com";
+
+/// Header that carries arguments of content endpoints.
+const DROPBOX_API_ARG: &str = "Dropbox-API-Arg";
+
+/// Single request of upload and upload session can't exceed 150MiB.
+const MAX_WRITE_CHUNK_SIZE: usize = 150 * 1024 * 1024;
+/// The default chunk size of upload session.
+const DEFAULT_WRITE_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// [Dropbox](https://www.dropbox.com/) services support.
+///
+/// # Capabilities
+///
+/// This service can be used to:
+///
+/// - [x] read
+/// - [x] write
+/// - [x] list
+/// - [ ] ~~scan~~
+/// - [ ] ~~presign~~
+/// - [ ] blocking
+///
+/// # Configuration
+///
+/// - `root`: Set the work directory for backend
+/// - `access_token`: Set the OAuth2 access token
+/// - `refresh_token`: Set the OAuth2 refresh token
+/// - `client_id`: Set the app key that used to refresh token
+/// - `client_secret`: Set the app secret that used to refresh token
+/// - `write_chunk_size`: Chunk size of upload sessions, default to 8MiB
+///
+/// You can refer to [`DropboxBuilder`]'s docs for more information
+///
+/// # Notes
+///
+/// Paths in Dropbox are case-insensitive, `dir/File` and `dir/file` are the
+/// same file. Listed entries keep the case that they are created with.
+///
+/// Dropbox returns `429 Too Many Requests` with a `Retry-After` header while
+/// rate limited, they will be returned as temporary errors with kind
+/// [`ErrorKind::RateLimited`] so that [`RetryLayer`][crate::layers::RetryLayer]
+/// can retry them.
+///
+/// # Example
+///
+/// ## Via Builder
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::services::Dropbox;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     // create backend builder
+///     let mut builder = Dropbox::default();
+///
+///     builder
+///         .root("/path/to/dir")
+///         .refresh_token("refresh_token")
+///         .client_id("app_key")
+///         .client_secret("app_secret");
+///
+///     let op: Operator = Operator::new(builder)?.finish();
+///     Ok(())
+/// }
+/// ```
+#[derive(Default)]
+pub struct DropboxBuilder {
+    root: Option<String>,
+    api_endpoint: Option<String>,
+    content_endpoint: Option<String>,
+
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+
+    write_chunk_size: Option<usize>,
+    http_client: Option<HttpClient>,
+}
+
+impl Debug for DropboxBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("Builder");
+
+        ds.field("root", &self.root)
+            .field("api_endpoint", &self.api_endpoint)
+            .field("content_endpoint", &self.content_endpoint)
+            .field("client_id", &self.client_id)
+            .field("write_chunk_size", &self.write_chunk_size);
+        if self.access_token.is_some() {
+            ds.field("access_token", &"<redacted>");
+        }
+        if self.refresh_token.is_some() {
+            ds.field("refresh_token", &"<redacted>");
+        }
+        if self.client_secret.is_some() {
+            ds.field("client_secret", &"<redacted>");
+        }
+        ds.finish()
+    }
+}
+
+impl DropboxBuilder {
+    /// Set root path of Dropbox.
+    pub fn root(&mut self, root: &str) -> &mut Self {
+        if !root.is_empty() {
+            self.root = Some(root.to_string())
+        }
+
+        self
+    }
+
+    /// Set the endpoint of RPC endpoints like `files/get_metadata`.
+    ///
+    /// Default to `https://api.dropboxapi.com`.
+    pub fn api_endpoint(&mut self, endpoint: &str) -> &mut Self {
+        if !endpoint.is_empty() {
+            self.api_endpoint = Some(endpoint.to_string())
+        }
+
+        self
+    }
+
+    /// Set the endpoint of content endpoints like `files/download`.
+    ///
+    /// Default to `https://content.dropboxapi.com`.
+    pub fn content_endpoint(&mut self, endpoint: &str) -> &mut Self {
+        if !endpoint.is_empty() {
+            self.content_endpoint = Some(endpoint.to_string())
+        }
+
+        self
+    }
+
+    /// Set the OAuth2 access token.
+    ///
+    /// The token will be used as is. Please set `refresh_token` instead for
+    /// long running services since access tokens are short-lived.
+    pub fn access_token(&mut self, token: &str) -> &mut Self {
+        if !token.is_empty() {
+            self.access_token = Some(token.to_string())
+        }
+
+        self
+    }
+
+    /// Set the OAuth2 refresh token.
+    ///
+    /// `client_id` and `client_secret` are required to refresh the access
+    /// token.
+    pub fn refresh_token(&mut self, token: &str) -> &mut Self {
+        if !token.is_empty() {
+            self.refresh_token = Some(token.to_string())
+        }
+
+        self
+    }
+
+    /// Set the app key of Dropbox app.
+    pub fn client_id(&mut self, client_id: &str) -> &mut Self {
+        if !client_id.is_empty() {
+            self.client_id = Some(client_id.to_string())
+        }
+
+        self
+    }
+
+    /// Set the app secret of Dropbox app.
+    pub fn client_secret(&mut self, client_secret: &str) -> &mut Self {
+        if !client_secret.is_empty() {
+            self.client_secret = Some(client_secret.to_string())
+        }
+
+        self
+    }
+
+    /// Set the chunk size of upload sessions that used by writers.
+    ///
+    /// Appended bytes will be buffered and uploaded in chunks of this size.
+    /// The size must not be larger than 150MiB, building will fail with
+    /// [`ErrorKind::ConfigInvalid`] for others.
+    ///
+    /// Default to 8MiB.
+    pub fn write_chunk_size(&mut self, size: usize) -> &mut Self {
+        self.write_chunk_size = Some(size);
+        self
+    }
+
+    /// Specify the http client that used by this service.
+    ///
+    /// # Notes
+    ///
+    /// This API is part of OpenDAL's Raw API. `HttpClient` could be changed
+    /// during minor updates.
+    pub fn http_client(&mut self, client: HttpClient) -> &mut Self {
+        self.http_client = Some(client);
+        self
+    }
+}
+
+impl Builder for DropboxBuilder {
+    const SCHEME: Scheme = Scheme::Dropbox;
+    type Accessor = DropboxBackend;
+
+    fn from_map(map: HashMap<String, String>) -> Self {
+        let mut builder = DropboxBuilder::default();
+
+        map.get("root").map(|v| builder.root(v));
+        map.get("api_endpoint").map(|v| builder.api_endpoint(v));
+        map.get("content_endpoint")
+            .map(|v| builder.content_endpoint(v));
+        map.get("access_token").map(|v| builder.access_token(v));
+        map.get("refresh_token").map(|v| builder.refresh_token(v));
+        map.get("client_id").map(|v| builder.client_id(v));
+        map.get("client_secret").map(|v| builder.client_secret(v));
+        map.get("write_chunk_size")
+            .and_then(|v| v.parse::<usize>().ok())
+            .map(|v| builder.write_chunk_size(v));
+
+        builder
+    }
+
+    fn build(&mut self) -> Result<Self::Accessor> {
+        debug!("backend build started: {:?}", self);
+
+        let root = normalize_root(&self.root.take().unwrap_or_default());
+        debug!("backend use root {}", root);
+
+        let mut endpoints = Vec::with_capacity(2);
+        for (endpoint, default) in [
+            (&self.api_endpoint, DEFAULT_DROPBOX_API_ENDPOINT),
+            (&self.content_endpoint, DEFAULT_DROPBOX_CONTENT_ENDPOINT),
+        ] {
+            let endpoint = endpoint.clone().unwrap_or_else(|| default.to_string());
+            parse_endpoint(&endpoint).map_err(|e| {
+                e.with_operation("Builder::build")
+                    .with_context("service", Scheme::Dropbox)
+            })?;
+            endpoints.push(endpoint.trim_end_matches('/').to_string());
+        }
+        let content_endpoint = endpoints.pop().expect("content endpoint must exist");
+        let api_endpoint = endpoints.pop().expect("api endpoint must exist");
+        debug!("backend use endpoints: {api_endpoint}, {content_endpoint}");
+
+        let write_chunk_size = self.write_chunk_size.unwrap_or(DEFAULT_WRITE_CHUNK_SIZE);
+        if write_chunk_size == 0 || write_chunk_size > MAX_WRITE_CHUNK_SIZE {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "write_chunk_size must not be larger than 150MiB",
+            )
+            .with_operation("Builder::build")
+            .with_context("service", Scheme::Dropbox)
+            .with_context("write_chunk_size", write_chunk_size.to_string()));
+        }
+
+        let client = if let Some(client) = self.http_client.take() {
+            client
+        } else {
+            HttpClient::new().map_err(|err| {
+                err.with_operation("Builder::build")
+                    .with_context("service", Scheme::Dropbox)
+            })?
+        };
+
+        let token_loader = match (
+            &self.access_token,
+            &self.refresh_token,
+            &self.client_id,
+            &self.client_secret,
+        ) {
+            // Access token given by user is used as is and never expired.
+            (Some(access_token), _, _, _) => OAuth2TokenLoader::new(client.clone(), access_token),
+            (None, Some(refresh_token), Some(client_id), Some(client_secret)) => {
+                OAuth2TokenLoader::with_refresh_token(
+                    client.clone(),
+                    &format!("{api_endpoint}/oauth2/token"),
+                    refresh_token,
+                    client_id,
+                    Some(client_secret),
+                )
+            }
+            (None, Some(_), _, _) => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "client_id and client_secret are required to refresh token",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Dropbox))
+            }
+            (None, None, _, _) => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "access_token or refresh_token is required",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Dropbox))
+            }
+        };
+
+        debug!("backend build finished: {:?}", self);
+        Ok(DropboxBackend {
+            root,
+            api_endpoint,
+            content_endpoint,
+            client,
+            write_chunk_size,
+
+            token_loader,
+        })
+    }
+}
+
+/// Metadata of files and folders returned by Dropbox.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(super) struct DropboxMetadata {
+    /// One of `file`, `folder` and `deleted`.
+    #[serde(rename = ".tag")]
+    pub tag: String,
+    pub name: String,
+    pub size: u64,
+    pub server_modified: Option<String>,
+    pub content_hash: Option<String>,
+}
+
+impl DropboxMetadata {
+    pub fn is_folder(&self) -> bool {
+        self.tag == "folder"
+    }
+
+    pub fn parse_into_metadata(&self) -> Result<Metadata> {
+        if self.is_folder() {
+            return Ok(Metadata::new(EntryMode::DIR));
+        }
+
+        let mut meta = Metadata::new(EntryMode::FILE);
+        meta.set_content_length(self.size);
+        if let Some(v) = &self.content_hash {
+            meta.set_etag(v);
+        }
+        if let Some(v) = &self.server_modified {
+            let dt = OffsetDateTime::parse(v, &Rfc3339).map_err(|e| {
+                Error::new(ErrorKind::Unexpected, "parse server modified as rfc3339").set_source(e)
+            })?;
+            meta.set_last_modified(dt);
+        }
+        Ok(meta)
+    }
+}
+
+/// Result of `files/list_folder` and `files/list_folder/continue`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(super) struct DropboxListFolderResult {
+    pub entries: Vec<DropboxMetadata>,
+    pub cursor: String,
+    pub has_more: bool,
+}
+
+/// Backend for Dropbox services.
+#[derive(Clone)]
+pub struct DropboxBackend {
+    root: String,
+    api_endpoint: String,
+    content_endpoint: String,
+    pub(super) client: HttpClient,
+    pub(super) write_chunk_size: usize,
+
+    token_loader: OAuth2TokenLoader,
+}
+
+impl Debug for DropboxBackend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Backend")
+            .field("root", &self.root)
+            .field("api_endpoint", &self.api_endpoint)
+            .field("content_endpoint", &self.content_endpoint)
+            .field("write_chunk_size", &self.write_chunk_size)
+            .field("client", &self.client)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Accessor for DropboxBackend {
+    type Reader = IncomingAsyncBody;
+    type BlockingReader = ();
+    type Writer = DropboxWriter;
+    type BlockingWriter = ();
+    type Pager = DropboxPager;
+    type BlockingPager = ();
+
+    fn info(&self) -> AccessorInfo {
+        use AccessorCapability::*;
+        use AccessorHint::*;
+
+        let mut am = AccessorInfo::default();
+        am.set_scheme(Scheme::Dropbox)
+            .set_root(&self.root)
+            .set_capabilities(Read | Write | List)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                write_with_if_not_exists: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_delimiter: true,
+                ..Default::default()
+            })
+            .set_hints(ReadStreamable);
+        am
+    }
+
+    async fn create(&self, path: &str, _: OpCreate) -> Result<RpCreate> {
+        let p = self.dropbox_path(path);
+        // Root always exists.
+        if p.is_empty() {
+            return Ok(RpCreate::default());
+        }
+
+        // Missing parents will be created by Dropbox.
+        let resp = self
+            .dropbox_rpc(
+                "files/create_folder_v2",
+                json!({ "path": p, "autorename": false }),
+            )
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(RpCreate::default())
+            }
+            _ => {
+                let (summary, err) = parse_error_with_summary(resp).await?;
+                if summary.starts_with("path/conflict/folder") {
+                    Ok(RpCreate::default())
+                } else if summary.starts_with("path/conflict") {
+                    Err(
+                        Error::new(ErrorKind::NotADirectory, "path exists but is not a folder")
+                            .with_context("path", path)
+                            .set_source(err),
+                    )
+                } else {
+                    Err(err)
+                }
+            }
+        }
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let range = args.range();
+        let resp = self.dropbox_download(path, range).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let p = self.dropbox_path(path);
+
+        Ok((
+            RpWrite::default(),
+            DropboxWriter::new(self.clone(), p, args),
+        ))
+    }
+
+    async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+        // Dropbox doesn't support getting metadata of root.
+        if path == "/" {
+            return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
+        }
+
+        let p = self.dropbox_path(path);
+        let resp = self
+            .dropbox_rpc("files/get_metadata", json!({ "path": p }))
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                let meta: DropboxMetadata =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+                // Folders and files are told apart by the tailing `/`.
+                if meta.is_folder() != path.ends_with('/') {
+                    return Err(Error::new(ErrorKind::NotFound, "file is not found")
+                        .with_context("path", path));
+                }
+
+                meta.parse_into_metadata().map(RpStat::new)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        let p = self.dropbox_path(path);
+        let resp = self
+            .dropbox_rpc("files/delete_v2", json!({ "path": p }))
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(RpDelete::default())
+            }
+            _ => {
+                let err = parse_error(resp).await?;
+                if err.kind() == ErrorKind::NotFound {
+                    Ok(RpDelete::default())
+                } else {
+                    Err(err)
+                }
+            }
+        }
+    }
+
+    async fn list(&self, path: &str, _: OpList) -> Result<(RpList, Self::Pager)> {
+        let p = self.dropbox_path(path);
+
+        Ok((RpList::default(), DropboxPager::new(self.clone(), path, p)))
+    }
+}
+
+impl DropboxBackend {
+    /// Build the path that used by Dropbox API.
+    ///
+    /// Dropbox requires paths to start with `/` without the tailing `/`,
+    /// and uses an empty string for the root.
+    pub(super) fn dropbox_path(&self, path: &str) -> String {
+        let p = build_abs_path(&self.root, path);
+        let p = p.trim_end_matches('/');
+
+        if p.is_empty() {
+            String::new()
+        } else {
+            format!("/{p}")
+        }
+    }
+
+    /// Call RPC endpoints which take arguments as JSON body.
+    pub(super) async fn dropbox_rpc(
+        &self,
+        endpoint: &str,
+        args: serde_json::Value,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!("{}/2/{endpoint}", self.api_endpoint);
+        let body = args.to_string();
+
+        let req = Request::post(&url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_LENGTH, body.len());
+
+        self.dropbox_send(req, AsyncBody::Bytes(Bytes::from(body)))
+            .await
+    }
+
+    /// Call content upload endpoints which take arguments in the
+    /// `Dropbox-API-Arg` header and content as body.
+    pub(super) async fn dropbox_upload(
+        &self,
+        endpoint: &str,
+        args: serde_json::Value,
+        bs: Bytes,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!("{}/2/{endpoint}", self.content_endpoint);
+
+        let req = Request::post(&url)
+            .header(DROPBOX_API_ARG, encode_api_arg(&args))
+            .header(header::CONTENT_TYPE, "application/octet-stream")
+            .header(header::CONTENT_LENGTH, bs.len());
+
+        self.dropbox_send(req, AsyncBody::Bytes(bs)).await
+    }
+
+    async fn dropbox_download(
+        &self,
+        path: &str,
+        range: BytesRange,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!("{}/2/files/download", self.content_endpoint);
+        let args = json!({ "path": self.dropbox_path(path) });
+
+        let mut req = Request::post(&url).header(DROPBOX_API_ARG, encode_api_arg(&args));
+        if !range.is_full() {
+            req = req.header(header::RANGE, range.to_header());
+        }
+
+        self.dropbox_send(req, AsyncBody::Empty).await
+    }
+
+    async fn dropbox_send(
+        &self,
+        req: http::request::Builder,
+        body: AsyncBody,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let req = req
+            .header(
+                header::AUTHORIZATION,
+                self.token_loader.authorization().await?,
+            )
+            .body(body)
+            .map_err(new_request_build_error)?;
+
+        self.client.send_async(req).await
+    }
+}
+
+/// Encode arguments into `Dropbox-API-Arg` header.
+///
+/// Header values must be ASCII, so non-ASCII characters are escaped as
+/// `\uXXXX` just like what JSON allows.
+///
+/// ref: <https://www.dropbox.com/developers/reference/json-encoding>
+fn encode_api_arg(args: &serde_json::Value) -> String {
+    let s = args.to_string();
+
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c.is_ascii() {
+            out.push(c);
+        } else {
+            let mut buf = [0; 2];
+            for v in c.encode_utf16(&mut buf) {
+                write!(out, "\\u{v:04x}").expect("write into string must succeed");
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::body_json;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    fn new_backend(endpoint: &str, root: &str) -> DropboxBackend {
+        let mut builder = DropboxBuilder::default();
+        builder
+            .root(root)
+            .api_endpoint(endpoint)
+            .content_endpoint(endpoint)
+            .access_token("token");
+        builder.build().expect("build must succeed")
+    }
+
+    #[test]
+    fn test_build_without_token() {
+        let mut builder = DropboxBuilder::default();
+        let err = builder.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let mut builder = DropboxBuilder::default();
+        builder.refresh_token("refresh_token").client_id("app_key");
+        let err = builder.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[test]
+    fn test_dropbox_path() {
+        let backend = new_backend("https://api.dropboxapi.com", "/");
+        assert_eq!(backend.dropbox_path("/"), "");
+        assert_eq!(backend.dropbox_path("dir/"), "/dir");
+        assert_eq!(backend.dropbox_path("dir/file"), "/dir/file");
+
+        let backend = new_backend("https://api.dropboxapi.com", "/root/");
+        assert_eq!(backend.dropbox_path("/"), "/root");
+        assert_eq!(backend.dropbox_path("dir/"), "/root/dir");
+    }
+
+    #[test]
+    fn test_encode_api_arg() {
+        assert_eq!(
+            encode_api_arg(&json!({ "path": "/a b" })),
+            r#"{"path":"/a b"}"#
+        );
+        assert_eq!(
+            encode_api_arg(&json!({ "path": "/文件" })),
+            r#"{"path":"/\u6587\u4ef6"}"#
+        );
+        // Characters out of BMP are escaped as surrogate pairs.
+        assert_eq!(
+            encode_api_arg(&json!({ "path": "/😀" })),
+            r#"{"path":"/\ud83d\ude00"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stat() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/2/files/get_metadata"))
+            .and(header("authorization", "Bearer token"))
+            .and(body_json(json!({ "path": "/dir/file" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                ".tag": "file",
+                "name": "file",
+                "id": "id:a4ayc_80_OEAAAAAAAAAXw",
+                "client_modified": "2015-05-12T15:50:38Z",
+                "server_modified": "2015-05-12T15:50:38Z",
+                "rev": "a1c10ce0dd78",
+                "size": 7212,
+                "path_lower": "/dir/file",
+                "path_display": "/dir/file",
+                "content_hash": "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server.uri(), "/");
+
+        let meta = backend
+            .stat("dir/file", OpStat::new())
+            .await
+            .expect("stat must succeed")
+            .into_metadata();
+        assert_eq!(meta.mode(), EntryMode::FILE);
+        assert_eq!(meta.content_length(), 7212);
+        assert_eq!(
+            meta.etag(),
+            Some("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+
+        let err = backend
+            .stat("dir/file/", OpStat::new())
+            .await
+            .expect_err("stat file as dir must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_create_dir_conflict() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/2/files/create_folder_v2"))
+            .and(body_json(json!({ "path": "/dir", "autorename": false })))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error_summary": "path/conflict/folder/..",
+                "error": { ".tag": "path", "path": { ".tag": "conflict", "conflict": { ".tag": "folder" } } }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/2/files/create_folder_v2"))
+            .and(body_json(json!({ "path": "/file", "autorename": false })))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error_summary": "path/conflict/file/..",
+                "error": { ".tag": "path", "path": { ".tag": "conflict", "conflict": { ".tag": "file" } } }
+            })))
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server.uri(), "/");
+
+        backend
+            .create("dir/", OpCreate::new(EntryMode::DIR))
+            .await
+            .expect("create existing folder must succeed");
+        let err = backend
+            .create("file/", OpCreate::new(EntryMode::DIR))
+            .await
+            .expect_err("create on existing file must fail");
+        assert_eq!(err.kind(), ErrorKind::NotADirectory);
+    }
+}
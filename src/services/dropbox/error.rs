@@ -0,0 +1,202 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use http::Response;
+use http::StatusCode;
+use serde::Deserialize;
+use serde_json::de;
+
+use crate::raw::*;
+use crate::Error;
+use crate::ErrorKind;
+use crate::Result;
+
+/// Dropbox returns errors like:
+///
+/// ```json
+/// {
+///   "error_summary": "path/not_found/..",
+///   "error": { ".tag": "path", "path": { ".tag": "not_found" } }
+/// }
+/// ```
+#[derive(Default, Debug, Deserialize)]
+#[serde(default)]
+struct DropboxErrorResponse {
+    error_summary: String,
+}
+
+/// Parse error response into Error.
+pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
+    parse_error_with_summary(resp).await.map(|(_, err)| err)
+}
+
+/// Parse error response into Error along with the `error_summary` like
+/// `path/conflict/folder/..`, which is required to tell errors of the same
+/// status apart.
+pub async fn parse_error_with_summary(
+    resp: Response<IncomingAsyncBody>,
+) -> Result<(String, Error)> {
+    let (parts, body) = resp.into_parts();
+    let bs = body.bytes().await?;
+
+    let dropbox_err = de::from_slice::<DropboxErrorResponse>(&bs).ok();
+    let summary = dropbox_err
+        .as_ref()
+        .map(|v| v.error_summary.clone())
+        .unwrap_or_default();
+
+    let (kind, retryable) = match parts.status {
+        // Endpoint specific errors are returned as `409 Conflict`.
+        StatusCode::CONFLICT => parse_error_summary(&summary),
+        v => parse_error_status(v),
+    };
+
+    let message = match dropbox_err {
+        Some(dropbox_err) => format!("{dropbox_err:?}"),
+        None => String::from_utf8_lossy(&bs).into_owned(),
+    };
+
+    let mut err = Error::new(kind, &message);
+
+    // Rate limited responses carry the seconds to wait in `Retry-After`.
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
+
+    err = with_error_response_context(err, &parts);
+
+    if retryable {
+        err = err.set_temporary();
+    }
+
+    Ok((summary, err))
+}
+
+/// Parse error response of writes into Error.
+///
+/// Writes with `if_not_exists` are committed with mode `add`, the returning
+/// conflict means the condition is not match.
+pub async fn parse_write_error(
+    resp: Response<IncomingAsyncBody>,
+    if_not_exists: bool,
+) -> Result<Error> {
+    let err = parse_error(resp).await?;
+
+    if if_not_exists && err.kind() == ErrorKind::AlreadyExists {
+        Ok(Error::new(ErrorKind::ConditionNotMatch, "path already exists").set_source(err))
+    } else {
+        Ok(err)
+    }
+}
+
+/// Classify `409 Conflict` errors by the `error_summary`.
+///
+/// The summary is formed by tags of the error like
+/// `path_lookup/not_found/...` or `to/conflict/file/...`.
+fn parse_error_summary(summary: &str) -> (ErrorKind, bool) {
+    let tags: Vec<&str> = summary.split('/').collect();
+
+    if tags.contains(&"not_found") {
+        (ErrorKind::NotFound, false)
+    } else if tags.contains(&"conflict") {
+        (ErrorKind::AlreadyExists, false)
+    } else if tags.contains(&"too_many_write_operations") || tags.contains(&"too_many_requests") {
+        (ErrorKind::RateLimited, true)
+    } else if tags.contains(&"no_write_permission") {
+        (ErrorKind::PermissionDenied, false)
+    } else {
+        (ErrorKind::Unexpected, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::stream;
+    use http::header;
+
+    use super::*;
+
+    fn new_response(status: StatusCode, body: &'static str) -> Response<IncomingAsyncBody> {
+        let bs = bytes::Bytes::from(body);
+        let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+        Response::builder().status(status).body(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_parse_error() -> Result<()> {
+        let cases = vec![
+            (
+                StatusCode::CONFLICT,
+                r#"{"error_summary": "path/not_found/..", "error": {".tag": "path", "path": {".tag": "not_found"}}}"#,
+                ErrorKind::NotFound,
+                false,
+            ),
+            (
+                StatusCode::CONFLICT,
+                r#"{"error_summary": "path/conflict/file/..", "error": {".tag": "path", "path": {".tag": "conflict", "conflict": {".tag": "file"}}}}"#,
+                ErrorKind::AlreadyExists,
+                false,
+            ),
+            (
+                StatusCode::CONFLICT,
+                r#"{"error_summary": "path/too_many_write_operations/..", "error": {".tag": "path", "path": {".tag": "too_many_write_operations"}}}"#,
+                ErrorKind::RateLimited,
+                true,
+            ),
+            (
+                StatusCode::UNAUTHORIZED,
+                r#"{"error_summary": "expired_access_token/..", "error": {".tag": "expired_access_token"}}"#,
+                ErrorKind::PermissionDenied,
+                false,
+            ),
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal Server Error",
+                ErrorKind::Unexpected,
+                true,
+            ),
+        ];
+
+        for (status, body, kind, temporary) in cases {
+            let (_, err) = parse_error_with_summary(new_response(status, body)).await?;
+            assert_eq!(err.kind(), kind, "{body}");
+            assert_eq!(err.is_temporary(), temporary, "{body}");
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_rate_limited() -> Result<()> {
+        let bs = bytes::Bytes::from(
+            r#"{"error_summary": "too_many_requests/..", "error": {"reason": {".tag": "too_many_requests"}, "retry_after": 300}}"#,
+        );
+        let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+        let resp = Response::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .header(header::RETRY_AFTER, "300")
+            .body(body)
+            .unwrap();
+
+        let (summary, err) = parse_error_with_summary(resp).await?;
+        assert_eq!(summary, "too_many_requests/..");
+        assert_eq!(err.kind(), ErrorKind::RateLimited);
+        assert!(err.is_temporary());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(300)));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use http::StatusCode;
+use serde_json::json;
+
+use super::backend::DropboxBackend;
+use super::backend::DropboxListFolderResult;
+use super::error::parse_error;
+use crate::raw::*;
+use crate::*;
+
+/// DropboxPager lists the children of a folder page by page.
+///
+/// The first page is fetched by `files/list_folder`, and the following
+/// pages are fetched by `files/list_folder/continue` with the returned
+/// cursor.
+pub struct DropboxPager {
+    backend: DropboxBackend,
+    path: String,
+    dropbox_path: String,
+
+    cursor: Option<String>,
+    done: bool,
+}
+
+impl DropboxPager {
+    pub fn new(backend: DropboxBackend, path: &str, dropbox_path: String) -> Self {
+        Self {
+            backend,
+            path: path.to_string(),
+            dropbox_path,
+
+            cursor: None,
+            done: false,
+        }
+    }
+}
+
+#[async_trait]
+impl oio::Page for DropboxPager {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let resp = match &self.cursor {
+            None => {
+                self.backend
+                    .dropbox_rpc(
+                        "files/list_folder",
+                        json!({ "path": self.dropbox_path, "recursive": false }),
+                    )
+                    .await?
+            }
+            Some(cursor) => {
+                self.backend
+                    .dropbox_rpc("files/list_folder/continue", json!({ "cursor": cursor }))
+                    .await?
+            }
+        };
+
+        if resp.status() != StatusCode::OK {
+            let err = parse_error(resp).await?;
+            // Listing a not exist folder returns nothing.
+            if err.kind() == ErrorKind::NotFound {
+                self.done = true;
+                return Ok(None);
+            }
+            return Err(err);
+        }
+        let bs = resp.into_body().bytes().await?;
+
+        let output: DropboxListFolderResult =
+            serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+        self.done = !output.has_more;
+        self.cursor = Some(output.cursor);
+
+        let mut entries = Vec::with_capacity(output.entries.len());
+        for item in output.entries {
+            let name = match item.tag.as_str() {
+                "folder" => format!("{}/", item.name),
+                "file" => item.name.clone(),
+                // Entries like `deleted` should be ignored.
+                _ => continue,
+            };
+
+            let path = if self.path == "/" {
+                name
+            } else {
+                format!("{}{}", self.path, name)
+            };
+
+            entries.push(oio::Entry::new(&path, item.parse_into_metadata()?));
+        }
+
+        Ok(Some(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oio::Page;
+    use wiremock::matchers::body_json;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::services::Dropbox;
+
+    #[tokio::test]
+    async fn test_list_with_cursor() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/2/files/list_folder"))
+            .and(body_json(json!({ "path": "/dir", "recursive": false })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "entries": [
+                    {
+                        ".tag": "folder",
+                        "name": "a",
+                        "id": "id:a",
+                        "path_lower": "/dir/a",
+                        "path_display": "/dir/a"
+                    },
+                    {
+                        ".tag": "file",
+                        "name": "B",
+                        "id": "id:b",
+                        "path_lower": "/dir/b",
+                        "path_display": "/dir/B",
+                        "size": 1,
+                        "rev": "015f9c7e0dc3",
+                        "client_modified": "2023-03-01T10:00:00Z",
+                        "server_modified": "2023-03-01T10:00:00Z"
+                    }
+                ],
+                "cursor": "cursor_1",
+                "has_more": true
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/2/files/list_folder/continue"))
+            .and(body_json(json!({ "cursor": "cursor_1" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "entries": [
+                    { ".tag": "deleted", "name": "d", "path_lower": "/dir/d" },
+                    {
+                        ".tag": "file",
+                        "name": "c",
+                        "id": "id:c",
+                        "size": 4,
+                        "server_modified": "2023-03-01T10:00:00Z",
+                        "content_hash": "hash_c"
+                    }
+                ],
+                "cursor": "cursor_2",
+                "has_more": false
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut builder = Dropbox::default();
+        builder
+            .api_endpoint(&server.uri())
+            .content_endpoint(&server.uri())
+            .access_token("token");
+        let backend = builder.build().expect("build must succeed");
+
+        let mut pager = DropboxPager::new(backend, "dir/", "/dir".to_string());
+
+        let entries = pager.next().await.unwrap().expect("first page must exist");
+        let paths: Vec<_> = entries.iter().map(|e| e.path().to_string()).collect();
+        assert_eq!(paths, vec!["dir/a/", "dir/B"]);
+
+        let entries = pager.next().await.unwrap().expect("second page must exist");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path(), "dir/c");
+        let meta = entries[0].metadata();
+        assert_eq!(meta.content_length(), 4);
+        assert_eq!(meta.etag(), Some("hash_c"));
+
+        assert!(pager.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_root() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        // Root is listed with the empty path.
+        Mock::given(method("POST"))
+            .and(path("/2/files/list_folder"))
+            .and(body_json(json!({ "path": "", "recursive": false })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "entries": [{ ".tag": "file", "name": "a", "size": 1 }],
+                "cursor": "cursor_1",
+                "has_more": false
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/2/files/list_folder"))
+            .and(body_json(
+                json!({ "path": "/not_exist", "recursive": false }),
+            ))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error_summary": "path/not_found/..",
+                "error": { ".tag": "path", "path": { ".tag": "not_found" } }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut builder = Dropbox::default();
+        builder
+            .api_endpoint(&server.uri())
+            .content_endpoint(&server.uri())
+            .access_token("token");
+        let backend = builder.build().expect("build must succeed");
+
+        let mut pager = DropboxPager::new(backend.clone(), "/", backend.dropbox_path("/"));
+        let entries = pager.next().await.unwrap().expect("page must exist");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path(), "a");
+        assert!(pager.next().await.unwrap().is_none());
+
+        let mut pager = DropboxPager::new(
+            backend.clone(),
+            "not_exist/",
+            backend.dropbox_path("not_exist/"),
+        );
+        assert!(pager.next().await.unwrap().is_none());
+    }
+}
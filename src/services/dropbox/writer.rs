@@ -0,0 +1,473 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp;
+
+use async_trait::async_trait;
+use bytes::Buf;
+use bytes::Bytes;
+use bytes::BytesMut;
+use http::Response;
+use http::StatusCode;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::backend::DropboxBackend;
+use super::error::parse_write_error;
+use crate::ops::OpWrite;
+use crate::raw::*;
+use crate::*;
+
+/// DropboxWriter uploads small files in one request via `files/upload`, and
+/// larger files via [upload sessions](https://www.dropbox.com/developers/documentation/http/documentation#files-upload_session-start).
+///
+/// Upload sessions don't need the total size in advance, so appended bytes
+/// will be uploaded once a full chunk has been buffered.
+pub struct DropboxWriter {
+    backend: DropboxBackend,
+    path: String,
+    op: OpWrite,
+
+    /// The id of upload session, created lazily.
+    session_id: Option<String>,
+    /// Bytes that not persisted by the session yet.
+    buf: BytesMut,
+    /// Size of persisted bytes, also the offset of `buf` in the file.
+    offset: u64,
+    /// The upload has been finished or cancelled.
+    closed: bool,
+}
+
+#[derive(Deserialize)]
+struct DropboxUploadSessionStartResult {
+    session_id: String,
+}
+
+/// The `upload_session/append_v2` and `upload_session/finish` return
+/// `incorrect_offset` with the correct offset if the chunk doesn't follow
+/// the persisted bytes.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct DropboxUploadSessionError {
+    error: DropboxUploadSessionLookupError,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct DropboxUploadSessionLookupError {
+    #[serde(rename = ".tag")]
+    tag: String,
+    correct_offset: Option<u64>,
+    lookup_failed: Option<Box<DropboxUploadSessionLookupError>>,
+}
+
+impl DropboxWriter {
+    pub fn new(backend: DropboxBackend, path: String, op: OpWrite) -> Self {
+        DropboxWriter {
+            backend,
+            path,
+            op,
+
+            session_id: None,
+            buf: BytesMut::new(),
+            offset: 0,
+            closed: false,
+        }
+    }
+
+    /// The mode of commit, `add` will fail if the file already exists.
+    fn commit_info(&self) -> serde_json::Value {
+        let mode = if self.op.if_not_exists() {
+            "add"
+        } else {
+            "overwrite"
+        };
+
+        json!({
+            "path": self.path,
+            "mode": mode,
+            "autorename": false,
+            "mute": true,
+        })
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+
+        let total = self.offset + self.buf.len() as u64;
+        if let Some(expected) = self.op.content_length() {
+            if expected != total {
+                return Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "written size is not equal to the content length",
+                )
+                .with_context("path", &self.path)
+                .with_context("expected", expected.to_string())
+                .with_context("actual", total.to_string()));
+            }
+        }
+
+        if self.session_id.is_none() && self.buf.len() <= self.backend.write_chunk_size {
+            return self.upload_simple().await;
+        }
+
+        // Upload all bytes except the last chunk, which will be carried by
+        // `upload_session/finish`.
+        while self.buf.len() > self.backend.write_chunk_size {
+            self.upload_chunk().await?;
+        }
+
+        while !self.closed {
+            let offset = self.offset;
+            self.upload_session_finish().await?;
+
+            if !self.closed && self.offset == offset {
+                return Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "upload session doesn't make progress while finishing",
+                )
+                .with_context("offset", offset.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    async fn upload_simple(&mut self) -> Result<()> {
+        let bs = self.buf.split().freeze();
+        let resp = self
+            .backend
+            .dropbox_upload("files/upload", self.commit_info(), bs)
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                self.closed = true;
+                Ok(())
+            }
+            _ => Err(parse_write_error(resp, self.op.if_not_exists()).await?),
+        }
+    }
+
+    /// Upload the leading chunk of buffered bytes into the upload session,
+    /// the session will be started with the first chunk.
+    async fn upload_chunk(&mut self) -> Result<()> {
+        let size = cmp::min(self.buf.len(), self.backend.write_chunk_size);
+        let bs = Bytes::copy_from_slice(&self.buf[..size]);
+
+        let session_id = match &self.session_id {
+            Some(v) => v.clone(),
+            None => {
+                let resp = self
+                    .backend
+                    .dropbox_upload("files/upload_session/start", json!({ "close": false }), bs)
+                    .await?;
+
+                return match resp.status() {
+                    StatusCode::OK => {
+                        let bs = resp.into_body().bytes().await?;
+                        let result: DropboxUploadSessionStartResult =
+                            serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+                        self.session_id = Some(result.session_id);
+                        self.advance(self.offset + size as u64)
+                    }
+                    _ => Err(parse_write_error(resp, self.op.if_not_exists()).await?),
+                };
+            }
+        };
+
+        let args = json!({
+            "cursor": { "session_id": session_id, "offset": self.offset },
+            "close": false,
+        });
+        let resp = self
+            .backend
+            .dropbox_upload("files/upload_session/append_v2", args, bs)
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                self.advance(self.offset + size as u64)
+            }
+            _ => self.handle_session_error(resp).await,
+        }
+    }
+
+    /// Upload the remaining bytes and commit the upload session.
+    async fn upload_session_finish(&mut self) -> Result<()> {
+        let session_id = self
+            .session_id
+            .clone()
+            .expect("session id must be valid while finishing upload session");
+
+        let args = json!({
+            "cursor": { "session_id": session_id, "offset": self.offset },
+            "commit": self.commit_info(),
+        });
+        let bs = Bytes::copy_from_slice(&self.buf);
+        let resp = self
+            .backend
+            .dropbox_upload("files/upload_session/finish", args, bs)
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+
+                self.offset += self.buf.len() as u64;
+                self.buf.clear();
+                self.closed = true;
+                Ok(())
+            }
+            _ => self.handle_session_error(resp).await,
+        }
+    }
+
+    /// Resume from the correct offset if the session reports
+    /// `incorrect_offset`, which happens while a retried chunk has been
+    /// persisted already.
+    async fn handle_session_error(&mut self, resp: Response<IncomingAsyncBody>) -> Result<()> {
+        let (parts, body) = resp.into_parts();
+        let bs = body.bytes().await?;
+
+        if parts.status == StatusCode::CONFLICT {
+            if let Some(offset) = parse_correct_offset(&bs) {
+                return self.advance(offset);
+            }
+        }
+
+        let resp = Response::from_parts(
+            parts,
+            IncomingAsyncBody::new(Box::new(futures::stream::iter(vec![Ok(bs)])), None),
+        );
+        Err(parse_write_error(resp, self.op.if_not_exists()).await?)
+    }
+
+    /// Drop the persisted bytes from buffer.
+    fn advance(&mut self, persisted: u64) -> Result<()> {
+        let end = self.offset + self.buf.len() as u64;
+        if persisted < self.offset || persisted > end {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "persisted size of upload session is out of range",
+            )
+            .with_context("persisted", persisted.to_string())
+            .with_context("offset", self.offset.to_string())
+            .with_context("end", end.to_string()));
+        }
+
+        self.buf.advance((persisted - self.offset) as usize);
+        self.offset = persisted;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl oio::Write for DropboxWriter {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.buf.extend_from_slice(&bs);
+        self.finish().await
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.buf.extend_from_slice(&bs);
+
+        // Always keep the last chunk in buffer so that it could be uploaded
+        // via `files/upload` if there is only one chunk.
+        while self.buf.len() > self.backend.write_chunk_size {
+            self.upload_chunk().await?;
+        }
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        self.finish().await?;
+        Ok(RpWrite::new())
+    }
+
+    /// Dropbox doesn't provide APIs to cancel upload sessions, they will
+    /// be expired after 7 days.
+    async fn abort(&mut self) -> Result<()> {
+        self.buf.clear();
+        self.session_id = None;
+        self.closed = true;
+        Ok(())
+    }
+}
+
+/// Parse the correct offset from errors like:
+///
+/// ```json
+/// {
+///   "error_summary": "lookup_failed/incorrect_offset/..",
+///   "error": {
+///     ".tag": "lookup_failed",
+///     "lookup_failed": { ".tag": "incorrect_offset", "correct_offset": 8388608 }
+///   }
+/// }
+/// ```
+fn parse_correct_offset(bs: &[u8]) -> Option<u64> {
+    let err: DropboxUploadSessionError = serde_json::from_slice(bs).ok()?;
+
+    let mut err = &err.error;
+    loop {
+        if err.tag == "incorrect_offset" {
+            return err.correct_offset;
+        }
+        err = err.lookup_failed.as_ref()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oio::Write;
+    use wiremock::http::HeaderName;
+    use wiremock::matchers::body_bytes;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::Request;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::services::Dropbox;
+
+    const CHUNK_SIZE: usize = 1024;
+
+    fn new_backend(server: &MockServer) -> DropboxBackend {
+        let mut builder = Dropbox::default();
+        builder
+            .api_endpoint(&server.uri())
+            .content_endpoint(&server.uri())
+            .access_token("token")
+            .write_chunk_size(CHUNK_SIZE);
+        builder.build().expect("build must succeed")
+    }
+
+    /// wiremock splits header values by `,`, so we need to join them back
+    /// before matching the json encoded `Dropbox-API-Arg`.
+    fn api_arg_contains(pattern: String) -> impl Fn(&Request) -> bool + Send + Sync {
+        move |req: &Request| {
+            req.headers
+                .get(&HeaderName::from("dropbox-api-arg"))
+                .map(|vs| {
+                    let v: Vec<_> = vs.iter().map(|v| v.as_str()).collect();
+                    v.join(",").contains(&pattern)
+                })
+                .unwrap_or_default()
+        }
+    }
+
+    #[test]
+    fn test_parse_correct_offset() {
+        let bs = br#"{"error_summary": "incorrect_offset/..", "error": {".tag": "incorrect_offset", "correct_offset": 1024}}"#;
+        assert_eq!(parse_correct_offset(bs), Some(1024));
+
+        let bs = br#"{"error_summary": "lookup_failed/incorrect_offset/..", "error": {".tag": "lookup_failed", "lookup_failed": {".tag": "incorrect_offset", "correct_offset": 2048}}}"#;
+        assert_eq!(parse_correct_offset(bs), Some(2048));
+
+        let bs = br#"{"error_summary": "not_found/..", "error": {".tag": "not_found"}}"#;
+        assert_eq!(parse_correct_offset(bs), None);
+    }
+
+    #[tokio::test]
+    async fn test_upload_simple_if_not_exists() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/2/files/upload"))
+            .and(api_arg_contains(r#""mode":"add""#.to_string()))
+            .and(body_bytes(b"hello".to_vec()))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error_summary": "path/conflict/file/..",
+                "error": { ".tag": "path", "reason": { ".tag": "conflict", "conflict": { ".tag": "file" } } }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server);
+        let op = OpWrite::new().with_if_not_exists(true);
+        let mut w = DropboxWriter::new(backend, "/file".to_string(), op);
+
+        let err = w
+            .write(Bytes::from("hello"))
+            .await
+            .expect_err("write must fail");
+        assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
+    }
+
+    #[tokio::test]
+    async fn test_upload_session_with_incorrect_offset() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/2/files/upload_session/start"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "session_id": "session" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        // The second chunk has been persisted by a request which response
+        // is lost.
+        Mock::given(method("POST"))
+            .and(path("/2/files/upload_session/append_v2"))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error_summary": "incorrect_offset/..",
+                "error": { ".tag": "incorrect_offset", "correct_offset": 2 * CHUNK_SIZE }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/2/files/upload_session/finish"))
+            .and(api_arg_contains(format!(r#""offset":{}"#, 2 * CHUNK_SIZE)))
+            .and(body_bytes(vec![3; 100]))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                ".tag": "file",
+                "name": "file",
+                "size": 2 * CHUNK_SIZE + 100
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server);
+        let mut w = DropboxWriter::new(backend, "/file".to_string(), OpWrite::new());
+
+        w.append(Bytes::from(vec![1; CHUNK_SIZE]))
+            .await
+            .expect("append must succeed");
+        w.append(Bytes::from(vec![2; CHUNK_SIZE]))
+            .await
+            .expect("append must succeed");
+        w.append(Bytes::from(vec![3; 100]))
+            .await
+            .expect("append must succeed");
+        assert_eq!(w.offset, 2 * CHUNK_SIZE as u64);
+
+        w.close().await.expect("close must succeed");
+        assert!(w.closed);
+    }
+}
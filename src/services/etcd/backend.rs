@@ -0,0 +1,610 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+
+use async_trait::async_trait;
+use etcd_client::Certificate;
+use etcd_client::Client;
+use etcd_client::ConnectOptions;
+use etcd_client::Error as EtcdError;
+use etcd_client::GetOptions;
+use etcd_client::Identity;
+use etcd_client::KvClient;
+use etcd_client::TlsOptions;
+use tokio::sync::OnceCell;
+
+use crate::raw::adapters::kv;
+use crate::raw::*;
+use crate::*;
+
+const DEFAULT_ETCD_ENDPOINTS: &str = "http://127.0.0.1:2379";
+/// The default value of etcd's `--max-request-bytes`.
+const DEFAULT_MAX_REQUEST_BYTES: usize = 1536 * 1024;
+/// The number of keys fetched in one range request while scanning.
+const SCAN_PAGE_SIZE: i64 = 1000;
+
+/// [Etcd](https://etcd.io/) services support.
+///
+/// # Capabilities
+///
+/// This service can be used to:
+///
+/// - [x] read
+/// - [x] write
+/// - [ ] ~~list~~
+/// - [x] scan
+/// - [ ] ~~presign~~
+/// - [ ] blocking
+///
+/// # Notes
+///
+/// Etcd is designed for small values like config documents. Every value
+/// is stored in a single key, the path will be used as the key directly
+/// with `root` as the key prefix. For example, `abc` under root `/config/`
+/// will be stored at key `config/abc`.
+///
+/// - `scan` (and `list` which is emulated by `scan`) is served by range
+///   requests on the key prefix.
+/// - Values larger than etcd's request size limit (1.5MiB by default)
+///   will be rejected with [`ErrorKind::Unsupported`]. Please set
+///   `max_request_bytes` if the server is started with a different
+///   `--max-request-bytes`.
+///
+/// # Configuration
+///
+/// - `root`: Set the working directory of `OpenDAL`
+/// - `endpoints`: Set the network addresses of etcd servers
+/// - `username`: Set the username of etcd
+/// - `password`: Set the password for authentication
+/// - `ca_path`: Set the ca certificate to verify servers
+/// - `cert_path`: Set the client certificate for TLS authentication
+/// - `key_path`: Set the client private key for TLS authentication
+/// - `max_request_bytes`: Set the request size limit of etcd servers
+///
+/// You can refer to [`EtcdBuilder`]'s docs for more information
+///
+/// # Example
+///
+/// ## Via Builder
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::services::Etcd;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let mut builder = Etcd::default();
+///     builder.endpoints("http://127.0.0.1:2379");
+///     builder.root("/config");
+///
+///     let op: Operator = Operator::new(builder)?.finish();
+///     op.write("app.toml", "debug = true").await?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct EtcdBuilder {
+    /// network addresses of the etcd servers, separated by `,`.
+    ///
+    /// default is "http://127.0.0.1:2379"
+    endpoints: Option<String>,
+    /// the username to connect etcd service.
+    ///
+    /// default is None
+    username: Option<String>,
+    /// the password for authentication
+    ///
+    /// default is None
+    password: Option<String>,
+    /// the working directory of the etcd service. Can be "/path/to/dir"
+    ///
+    /// default is "/"
+    root: Option<String>,
+    /// path of the ca certificate in PEM format.
+    ///
+    /// default is None
+    ca_path: Option<String>,
+    /// path of the client certificate in PEM format.
+    ///
+    /// default is None
+    cert_path: Option<String>,
+    /// path of the client private key in PEM format.
+    ///
+    /// default is None
+    key_path: Option<String>,
+    /// the request size limit of etcd servers.
+    ///
+    /// default is 1.5MiB
+    max_request_bytes: Option<usize>,
+}
+
+impl Debug for EtcdBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("Builder");
+        ds.field("root", &self.root);
+        ds.field("endpoints", &self.endpoints);
+        if let Some(username) = self.username.clone() {
+            ds.field("username", &username);
+        }
+        if self.password.is_some() {
+            ds.field("password", &"<redacted>");
+        }
+        ds.field("ca_path", &self.ca_path);
+        ds.field("cert_path", &self.cert_path);
+        ds.field("key_path", &self.key_path);
+        ds.field("max_request_bytes", &self.max_request_bytes);
+        ds.finish()
+    }
+}
+
+impl EtcdBuilder {
+    /// set the network addresses of etcd servers, separated by `,`.
+    ///
+    /// For example: `http://127.0.0.1:2379,http://127.0.0.1:2380`
+    ///
+    /// default: "http://127.0.0.1:2379"
+    pub fn endpoints(&mut self, endpoints: &str) -> &mut Self {
+        if !endpoints.is_empty() {
+            self.endpoints = Some(endpoints.to_owned());
+        }
+        self
+    }
+
+    /// set the username for etcd
+    ///
+    /// default: no username
+    pub fn username(&mut self, username: &str) -> &mut Self {
+        if !username.is_empty() {
+            self.username = Some(username.to_owned());
+        }
+        self
+    }
+
+    /// set the password for etcd
+    ///
+    /// default: no password
+    pub fn password(&mut self, password: &str) -> &mut Self {
+        if !password.is_empty() {
+            self.password = Some(password.to_owned());
+        }
+        self
+    }
+
+    /// set the ca certificate used to verify etcd servers.
+    ///
+    /// TLS will be enabled if any of `ca_path`, `cert_path` and
+    /// `key_path` is set.
+    pub fn ca_path(&mut self, ca_path: &str) -> &mut Self {
+        if !ca_path.is_empty() {
+            self.ca_path = Some(ca_path.to_owned());
+        }
+        self
+    }
+
+    /// set the client certificate used for TLS authentication.
+    ///
+    /// `key_path` must be set too.
+    pub fn cert_path(&mut self, cert_path: &str) -> &mut Self {
+        if !cert_path.is_empty() {
+            self.cert_path = Some(cert_path.to_owned());
+        }
+        self
+    }
+
+    /// set the client private key used for TLS authentication.
+    ///
+    /// `cert_path` must be set too.
+    pub fn key_path(&mut self, key_path: &str) -> &mut Self {
+        if !key_path.is_empty() {
+            self.key_path = Some(key_path.to_owned());
+        }
+        self
+    }
+
+    /// set the request size limit of etcd servers, which should be the
+    /// same as etcd's `--max-request-bytes`.
+    ///
+    /// Writes larger than this limit will be rejected before sending.
+    ///
+    /// default: 1.5MiB
+    pub fn max_request_bytes(&mut self, bytes: usize) -> &mut Self {
+        self.max_request_bytes = Some(bytes);
+        self
+    }
+
+    /// set the working directory, all operations will be performed under it.
+    ///
+    /// The root will be used as the prefix of keys.
+    ///
+    /// default: "/"
+    pub fn root(&mut self, root: &str) -> &mut Self {
+        if !root.is_empty() {
+            self.root = Some(root.to_owned());
+        }
+        self
+    }
+
+    fn tls_options(&self) -> Result<Option<TlsOptions>> {
+        if self.ca_path.is_none() && self.cert_path.is_none() && self.key_path.is_none() {
+            return Ok(None);
+        }
+
+        let mut tls = TlsOptions::new();
+        if let Some(path) = &self.ca_path {
+            tls = tls.ca_certificate(Certificate::from_pem(read_pem("ca_path", path)?));
+        }
+        match (&self.cert_path, &self.key_path) {
+            (Some(cert), Some(key)) => {
+                tls = tls.identity(Identity::from_pem(
+                    read_pem("cert_path", cert)?,
+                    read_pem("key_path", key)?,
+                ));
+            }
+            (None, None) => {}
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "cert_path and key_path must be set together",
+                )
+                .with_context("service", Scheme::Etcd))
+            }
+        }
+
+        Ok(Some(tls))
+    }
+}
+
+impl Builder for EtcdBuilder {
+    const SCHEME: Scheme = Scheme::Etcd;
+    type Accessor = EtcdBackend;
+
+    fn from_map(map: HashMap<String, String>) -> Self {
+        let mut builder = EtcdBuilder::default();
+
+        map.get("root").map(|v| builder.root(v));
+        map.get("endpoints").map(|v| builder.endpoints(v));
+        map.get("username").map(|v| builder.username(v));
+        map.get("password").map(|v| builder.password(v));
+        map.get("ca_path").map(|v| builder.ca_path(v));
+        map.get("cert_path").map(|v| builder.cert_path(v));
+        map.get("key_path").map(|v| builder.key_path(v));
+        map.get("max_request_bytes")
+            .map(|v| v.parse::<usize>().map(|v| builder.max_request_bytes(v)));
+
+        builder
+    }
+
+    fn build(&mut self) -> Result<Self::Accessor> {
+        let endpoints = self
+            .endpoints
+            .clone()
+            .unwrap_or_else(|| DEFAULT_ETCD_ENDPOINTS.to_string());
+        let endpoints: Vec<String> = endpoints
+            .split(',')
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string())
+            .collect();
+        if endpoints.is_empty() {
+            return Err(Error::new(ErrorKind::ConfigInvalid, "endpoints are empty")
+                .with_context("service", Scheme::Etcd));
+        }
+
+        let mut options = ConnectOptions::new();
+        match (&self.username, &self.password) {
+            (Some(username), Some(password)) => {
+                options = options.with_user(username.clone(), password.clone());
+            }
+            (None, None) => {}
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "username and password must be set together",
+                )
+                .with_context("service", Scheme::Etcd))
+            }
+        }
+        if let Some(tls) = self.tls_options()? {
+            options = options.with_tls(tls);
+        }
+
+        let root = normalize_root(
+            self.root
+                .clone()
+                .unwrap_or_else(|| "/".to_string())
+                .as_str(),
+        );
+
+        Ok(EtcdBackend::new(Adapter {
+            endpoints,
+            options,
+            client: OnceCell::new(),
+            max_request_bytes: self.max_request_bytes.unwrap_or(DEFAULT_MAX_REQUEST_BYTES),
+        })
+        .with_root(&root))
+    }
+}
+
+fn read_pem(name: &str, path: &str) -> Result<Vec<u8>> {
+    std::fs::read(path).map_err(|e| {
+        Error::new(ErrorKind::ConfigInvalid, "read pem file failed")
+            .with_context("service", Scheme::Etcd)
+            .with_context(name, path)
+            .set_source(e)
+    })
+}
+
+/// Backend for etcd services.
+pub type EtcdBackend = kv::Backend<Adapter>;
+
+#[derive(Clone)]
+pub struct Adapter {
+    endpoints: Vec<String>,
+    options: ConnectOptions,
+    client: OnceCell<Client>,
+
+    max_request_bytes: usize,
+}
+
+// implement `Debug` manually, or password may be leaked.
+impl Debug for Adapter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("Adapter");
+        ds.field("endpoints", &self.endpoints);
+        ds.field("max_request_bytes", &self.max_request_bytes);
+        ds.finish()
+    }
+}
+
+impl Adapter {
+    async fn kv(&self) -> Result<KvClient> {
+        let client = self
+            .client
+            .get_or_try_init(|| async {
+                Client::connect(&self.endpoints, Some(self.options.clone())).await
+            })
+            .await?;
+
+        Ok(client.kv_client())
+    }
+}
+
+#[async_trait]
+impl kv::Adapter for Adapter {
+    fn metadata(&self) -> kv::Metadata {
+        kv::Metadata::new(
+            Scheme::Etcd,
+            &self.endpoints.join(","),
+            AccessorCapability::Read | AccessorCapability::Write | AccessorCapability::Scan,
+        )
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let mut kv = self.kv().await?;
+        let resp = kv.get(key, None).await?;
+
+        Ok(resp.kvs().first().map(|v| v.value().to_vec()))
+    }
+
+    async fn set(&self, key: &str, value: &[u8]) -> Result<()> {
+        // Key and value take almost the whole put request.
+        let size = key.len() + value.len();
+        if size > self.max_request_bytes {
+            return Err(
+                new_request_too_large_error(size, self.max_request_bytes).with_context("key", key)
+            );
+        }
+
+        let mut kv = self.kv().await?;
+        kv.put(key, value, None).await.map_err(|err| {
+            if err.to_string().contains("request is too large") {
+                new_request_too_large_error(size, self.max_request_bytes)
+                    .with_context("key", key)
+                    .set_source(err)
+            } else {
+                Error::from(err)
+            }
+        })?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let mut kv = self.kv().await?;
+        kv.delete(key, None).await?;
+        Ok(())
+    }
+
+    async fn scan(&self, path: &str) -> Result<Vec<String>> {
+        let mut kv = self.kv().await?;
+
+        let end = prefix_end(path.as_bytes());
+        // Etcd doesn't accept empty key, `\0` is the smallest key.
+        let mut start = if path.is_empty() {
+            vec![0]
+        } else {
+            path.as_bytes().to_vec()
+        };
+
+        let mut keys = Vec::new();
+        loop {
+            let resp = kv
+                .get(
+                    start.clone(),
+                    Some(
+                        GetOptions::new()
+                            .with_range(end.clone())
+                            .with_keys_only()
+                            .with_limit(SCAN_PAGE_SIZE),
+                    ),
+                )
+                .await?;
+
+            for v in resp.kvs() {
+                keys.push(v.key_str()?.to_string());
+            }
+
+            match resp.kvs().last() {
+                Some(last) if resp.more() => {
+                    // Continue from the smallest key after the last one.
+                    start = last.key().to_vec();
+                    start.push(0);
+                }
+                _ => break,
+            }
+        }
+
+        Ok(keys)
+    }
+}
+
+/// Compute the range end of given prefix, so that `[prefix, end)` covers
+/// all keys that start with prefix.
+///
+/// This follows etcd's `GetPrefixRangeEnd`: increase the last byte that
+/// is not `0xff` and drop the bytes after it. `\0` will be returned if
+/// no such byte, which means all keys larger than prefix.
+fn prefix_end(prefix: &[u8]) -> Vec<u8> {
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last < 0xff {
+            end.push(last + 1);
+            return end;
+        }
+    }
+    vec![0]
+}
+
+fn new_request_too_large_error(size: usize, limit: usize) -> Error {
+    Error::new(
+        ErrorKind::Unsupported,
+        "value is larger than etcd's request size limit",
+    )
+    .with_context("service", Scheme::Etcd)
+    .with_context("size", size.to_string())
+    .with_context("limit", limit.to_string())
+}
+
+impl From<EtcdError> for Error {
+    fn from(e: EtcdError) -> Self {
+        let (kind, temporary) = match &e {
+            EtcdError::InvalidArgs(_) | EtcdError::InvalidUri(_) => {
+                (ErrorKind::ConfigInvalid, false)
+            }
+            EtcdError::IoError(_) | EtcdError::TransportError(_) => (ErrorKind::Unexpected, true),
+            EtcdError::GRpcStatus(status)
+                if status.message().contains("permission denied")
+                    || status.message().contains("authentication failed") =>
+            {
+                (ErrorKind::PermissionDenied, false)
+            }
+            _ => (ErrorKind::Unexpected, false),
+        };
+
+        let err = Error::new(kind, "etcd error")
+            .with_context("service", Scheme::Etcd)
+            .set_source(e);
+        if temporary {
+            err.set_temporary()
+        } else {
+            err
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_end() {
+        let cases = vec![
+            ("empty", "".as_bytes(), vec![0]),
+            (
+                "normal",
+                "config/".as_bytes(),
+                "config0".as_bytes().to_vec(),
+            ),
+            ("single", "a".as_bytes(), "b".as_bytes().to_vec()),
+            ("trailing 0xff", &[b'a', 0xff, 0xff][..], vec![b'b']),
+            ("all 0xff", &[0xff, 0xff][..], vec![0]),
+        ];
+
+        for (name, input, expected) in cases {
+            assert_eq!(prefix_end(input), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_build() {
+        let mut builder = EtcdBuilder::default();
+        builder
+            .endpoints("http://127.0.0.1:2379, http://127.0.0.1:2380,")
+            .root("/config");
+        let backend = builder.build().expect("must build");
+        assert_eq!(backend.info().root(), "/config/");
+        assert_eq!(
+            backend.info().name(),
+            "http://127.0.0.1:2379,http://127.0.0.1:2380"
+        );
+        assert!(backend.info().capability().scan);
+
+        let mut builder = EtcdBuilder::default();
+        builder.username("root");
+        assert!(builder.build().is_err(), "password is required");
+
+        let mut builder = EtcdBuilder::default();
+        builder.cert_path("/path/to/cert.pem");
+        assert!(builder.build().is_err(), "key_path is required");
+
+        let mut builder = EtcdBuilder::default();
+        builder.ca_path("/path/to/not_exist.pem");
+        let err = builder.build().expect_err("ca file doesn't exist");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[test]
+    fn test_from_map() {
+        let mut map = HashMap::new();
+        map.insert("endpoints".to_string(), "http://a,http://b".to_string());
+        map.insert("username".to_string(), "root".to_string());
+        map.insert("password".to_string(), "secret".to_string());
+        map.insert("max_request_bytes".to_string(), "1024".to_string());
+
+        let builder = EtcdBuilder::from_map(map);
+        assert_eq!(builder.endpoints.as_deref(), Some("http://a,http://b"));
+        assert_eq!(builder.username.as_deref(), Some("root"));
+        assert_eq!(builder.max_request_bytes, Some(1024));
+        assert!(!format!("{builder:?}").contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_set_too_large() {
+        let adapter = Adapter {
+            endpoints: vec![DEFAULT_ETCD_ENDPOINTS.to_string()],
+            options: ConnectOptions::new(),
+            client: OnceCell::new(),
+            max_request_bytes: 8,
+        };
+
+        // The size guard must be checked before connecting to server.
+        let err = kv::Adapter::set(&adapter, "key", b"hello, world")
+            .await
+            .expect_err("value larger than limit must be rejected");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+}
@@ -26,6 +26,7 @@ use time::OffsetDateTime;
 use tokio::fs;
 use uuid::Uuid;
 
+use super::error::parse_if_not_exists_error;
 use super::error::parse_io_error;
 use super::pager::FsPager;
 use super::writer::FsWriter;
@@ -49,6 +50,45 @@ use crate::*;
 /// # Configuration
 ///
 /// - `root`: Set the work dir for backend.
+/// - `atomic_write_dir`: Set the temp dir for atomic write.
+/// - `disable_atomic_write`: Write into the target file directly.
+/// - `enable_sync_dir`: Sync the parent dir after file written.
+/// - `file_mode`: Set the unix mode in octal for new created files, like `600`.
+/// - `dir_mode`: Set the unix mode in octal for new created dirs, like `700`.
+/// - `symlink_policy`: Set how to handle symlinks, could be `follow`, `skip` or `report`.
+///
+/// # Atomic Write
+///
+/// By default, data will be written into a temp file named like
+/// `.opendal.tmp.<uuid>` in the same dir first, and renamed to the target
+/// path while closing. So readers will never see a partial written file.
+/// Temp files will be removed if the writer is aborted or dropped without
+/// close, and won't be returned while listing.
+///
+/// The temp file lives alongside the target because `rename` is only
+/// atomic within the same filesystem. Please make sure `atomic_write_dir`
+/// is on the same filesystem as `root` if it's set.
+///
+/// Atomic write could be turned on or off for a single write via
+/// [`OpWrite::with_atomic`], which overrides `disable_atomic_write`.
+///
+/// # Permissions and Timestamps
+///
+/// On unix, `stat` and `list` will return the mode, uid and gid of entries
+/// via [`Metadata::unix_mode`], [`Metadata::uid`] and [`Metadata::gid`].
+/// Writing with [`OpWrite::with_last_modified`] will set the mtime of the
+/// written file.
+///
+/// # Symlinks
+///
+/// Symlinks are handled by `symlink_policy` while `stat` and `list`:
+///
+/// - `follow` (default): Return the metadata of the link target. Dangling
+///   links will be skipped while listing.
+/// - `skip`: Symlinks will be skipped while listing, and `stat` on them
+///   will return `NotFound`.
+/// - `report`: Return symlinks themselves without following with
+///   [`EntryMode::Unknown`], and [`Metadata::is_symlink`] will be `true`.
 ///
 /// Refer to [`FsBuilder`]'s public API docs for more information.
 ///
@@ -82,7 +122,15 @@ use crate::*;
 pub struct FsBuilder {
     root: Option<PathBuf>,
     atomic_write_dir: Option<PathBuf>,
+    disable_atomic_write: bool,
+    enable_sync_dir: bool,
     enable_path_check: bool,
+    file_mode: Option<u32>,
+    dir_mode: Option<u32>,
+    symlink_policy: Option<String>,
+    /// Error of parsing config in `from_map`, which will be returned while
+    /// building.
+    config_error: Option<Error>,
 }
 
 impl FsBuilder {
@@ -108,6 +156,71 @@ impl FsBuilder {
         self
     }
 
+    /// Disable atomic write so that data will be written into the target
+    /// file directly.
+    ///
+    /// Useful for file systems that rename is expensive, but readers could
+    /// see partial written files.
+    pub fn disable_atomic_write(&mut self) -> &mut Self {
+        self.disable_atomic_write = true;
+
+        self
+    }
+
+    /// Sync the parent dir after the file has been written and renamed, so
+    /// that the new entry is durable after crash.
+    ///
+    /// Syncing dir is only supported on unix, this option will be ignored
+    /// on other platforms.
+    pub fn enable_sync_dir(&mut self) -> &mut Self {
+        self.enable_sync_dir = true;
+
+        self
+    }
+
+    /// Set the unix mode for new created files, like `0o600`.
+    ///
+    /// The mode will be set exactly without affected by umask. Existing
+    /// files' mode will be replaced after overwritten too.
+    ///
+    /// This option will be ignored on non-unix platforms.
+    pub fn file_mode(&mut self, mode: u32) -> &mut Self {
+        self.file_mode = Some(mode);
+
+        self
+    }
+
+    /// Set the unix mode for new created dirs, like `0o700`.
+    ///
+    /// The mode will still be masked by umask, and existing dirs will
+    /// not be changed.
+    ///
+    /// This option will be ignored on non-unix platforms.
+    pub fn dir_mode(&mut self, mode: u32) -> &mut Self {
+        self.dir_mode = Some(mode);
+
+        self
+    }
+
+    /// Set how to handle symlinks while `stat` and `list`.
+    ///
+    /// Available values are:
+    ///
+    /// - `follow`: Follow symlinks and return the metadata of targets,
+    ///   dangling symlinks will be skipped while listing. This is the default.
+    /// - `skip`: Skip symlinks while listing, and `stat` on them will
+    ///   return `NotFound`.
+    /// - `report`: Return symlinks themselves without following.
+    pub fn symlink_policy(&mut self, policy: &str) -> &mut Self {
+        self.symlink_policy = if policy.is_empty() {
+            None
+        } else {
+            Some(policy.to_string())
+        };
+
+        self
+    }
+
     /// OpenDAL requires all input path are normalized to make sure the
     /// behavior is consistent. By enable path check, we can make sure
     /// fs will behave the same as other services.
@@ -131,6 +244,35 @@ impl Builder for FsBuilder {
         map.get("root").map(|v| builder.root(v));
         map.get("atomic_write_dir")
             .map(|v| builder.atomic_write_dir(v));
+        map.get("disable_atomic_write")
+            .filter(|v| *v == "on" || *v == "true")
+            .map(|_| builder.disable_atomic_write());
+        map.get("enable_sync_dir")
+            .filter(|v| *v == "on" || *v == "true")
+            .map(|_| builder.enable_sync_dir());
+        for key in ["file_mode", "dir_mode"] {
+            let v = match map.get(key) {
+                Some(v) => v,
+                None => continue,
+            };
+            match u32::from_str_radix(v.trim_start_matches("0o"), 8) {
+                Ok(mode) if key == "file_mode" => {
+                    builder.file_mode(mode);
+                }
+                Ok(mode) => {
+                    builder.dir_mode(mode);
+                }
+                Err(e) => {
+                    builder.config_error.get_or_insert(
+                        Error::new(ErrorKind::ConfigInvalid, "mode is not a valid octal number")
+                            .with_operation("Builder::from_map")
+                            .with_context(key, v)
+                            .set_source(e),
+                    );
+                }
+            }
+        }
+        map.get("symlink_policy").map(|v| builder.symlink_policy(v));
 
         builder
     }
@@ -138,6 +280,10 @@ impl Builder for FsBuilder {
     fn build(&mut self) -> Result<Self::Accessor> {
         debug!("backend build started: {:?}", &self);
 
+        if let Some(err) = self.config_error.take() {
+            return Err(err);
+        }
+
         let root = match self.root.take() {
             Some(root) => Ok(root),
             None => Err(Error::new(
@@ -147,6 +293,19 @@ impl Builder for FsBuilder {
         }?;
         debug!("backend use root {}", root.to_string_lossy());
 
+        let symlink_policy = match self.symlink_policy.as_deref() {
+            None | Some("follow") => SymlinkPolicy::Follow,
+            Some("skip") => SymlinkPolicy::Skip,
+            Some("report") => SymlinkPolicy::Report,
+            Some(v) => {
+                return Err(
+                    Error::new(ErrorKind::ConfigInvalid, "symlink policy is invalid")
+                        .with_operation("Builder::build")
+                        .with_context("symlink_policy", v),
+                )
+            }
+        };
+
         // If root dir is not exist, we must create it.
         if let Err(e) = std::fs::metadata(&root) {
             if e.kind() == io::ErrorKind::NotFound {
@@ -207,7 +366,12 @@ impl Builder for FsBuilder {
         Ok(FsBackend {
             root,
             atomic_write_dir,
+            disable_atomic_write: self.disable_atomic_write,
+            enable_sync_dir: self.enable_sync_dir,
             enable_path_check: self.enable_path_check,
+            file_mode: self.file_mode,
+            dir_mode: self.dir_mode,
+            symlink_policy,
         })
     }
 }
@@ -217,9 +381,30 @@ impl Builder for FsBuilder {
 pub struct FsBackend {
     root: PathBuf,
     atomic_write_dir: Option<PathBuf>,
+    disable_atomic_write: bool,
+    enable_sync_dir: bool,
     enable_path_check: bool,
+    #[cfg_attr(not(unix), allow(dead_code))]
+    file_mode: Option<u32>,
+    #[cfg_attr(not(unix), allow(dead_code))]
+    dir_mode: Option<u32>,
+    symlink_policy: SymlinkPolicy,
 }
 
+/// SymlinkPolicy decides how to handle symlinks while `stat` and `list`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SymlinkPolicy {
+    /// Follow symlinks to their targets.
+    Follow,
+    /// Skip symlinks.
+    Skip,
+    /// Report symlinks themselves.
+    Report,
+}
+
+/// Prefix of temp files that created in the same dir of target path.
+pub(super) const TMP_FILE_PREFIX: &str = ".opendal.tmp.";
+
 #[inline]
 fn tmp_file_of(path: &str) -> String {
     let name = get_basename(path);
@@ -228,9 +413,25 @@ fn tmp_file_of(path: &str) -> String {
     format!("{name}.{uuid}")
 }
 
+/// Build the temp file path next to the target path.
+#[inline]
+fn sibling_tmp_file_of(target: &Path) -> PathBuf {
+    target.with_file_name(format!("{TMP_FILE_PREFIX}{}", Uuid::new_v4()))
+}
+
+/// Fill unix specific metadata like mode, uid and gid.
+#[cfg(unix)]
+pub(super) fn with_unix_metadata(m: Metadata, meta: &std::fs::Metadata) -> Metadata {
+    use std::os::unix::fs::MetadataExt;
+
+    m.with_unix_mode(meta.mode() & 0o7777)
+        .with_uid(meta.uid())
+        .with_gid(meta.gid())
+}
+
 impl FsBackend {
     // Synchronously build write path and ensure the parent dirs created
-    fn blocking_ensure_write_abs_path(parent: &Path, path: &str) -> Result<PathBuf> {
+    fn blocking_ensure_write_abs_path(&self, parent: &Path, path: &str) -> Result<PathBuf> {
         let p = parent.join(path);
 
         // Create dir before write path.
@@ -250,13 +451,13 @@ impl FsBackend {
             })?
             .to_path_buf();
 
-        std::fs::create_dir_all(parent).map_err(parse_io_error)?;
+        self.blocking_create_dir_all(&parent)?;
 
         Ok(p)
     }
 
     // Build write path and ensure the parent dirs created
-    async fn ensure_write_abs_path(parent: &Path, path: &str) -> Result<PathBuf> {
+    async fn ensure_write_abs_path(&self, parent: &Path, path: &str) -> Result<PathBuf> {
         let p = parent.join(path);
 
         // Create dir before write path.
@@ -276,10 +477,87 @@ impl FsBackend {
             })?
             .to_path_buf();
 
-        fs::create_dir_all(&parent).await.map_err(parse_io_error)?;
+        self.create_dir_all(&parent).await?;
 
         Ok(p)
     }
+
+    /// Create dir and all its parents with the configured dir mode.
+    fn blocking_create_dir_all(&self, p: &Path) -> Result<()> {
+        let mut builder = std::fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        if let Some(mode) = self.dir_mode {
+            use std::os::unix::fs::DirBuilderExt;
+
+            builder.mode(mode);
+        }
+
+        builder.create(p).map_err(parse_io_error)
+    }
+
+    /// Create dir and all its parents with the configured dir mode.
+    async fn create_dir_all(&self, p: &Path) -> Result<()> {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        if let Some(mode) = self.dir_mode {
+            builder.mode(mode);
+        }
+
+        builder.create(p).await.map_err(parse_io_error)
+    }
+
+    /// Open file for writing with the configured file mode.
+    ///
+    /// The mode is set while creating so that the file is never visible with
+    /// a wider mode, and then set again to bypass umask.
+    fn blocking_open_file(
+        &self,
+        opts: &mut std::fs::OpenOptions,
+        p: &Path,
+    ) -> io::Result<std::fs::File> {
+        #[cfg(unix)]
+        if let Some(mode) = self.file_mode {
+            use std::os::unix::fs::OpenOptionsExt;
+
+            opts.mode(mode);
+        }
+
+        let f = opts.open(p)?;
+
+        #[cfg(unix)]
+        if let Some(mode) = self.file_mode {
+            use std::os::unix::fs::PermissionsExt;
+
+            f.set_permissions(std::fs::Permissions::from_mode(mode))?;
+        }
+
+        Ok(f)
+    }
+
+    /// Open file for writing with the configured file mode.
+    ///
+    /// The mode is set while creating so that the file is never visible with
+    /// a wider mode, and then set again to bypass umask.
+    async fn open_file(&self, opts: &mut fs::OpenOptions, p: &Path) -> io::Result<fs::File> {
+        #[cfg(unix)]
+        if let Some(mode) = self.file_mode {
+            opts.mode(mode);
+        }
+
+        let f = opts.open(p).await?;
+
+        #[cfg(unix)]
+        if let Some(mode) = self.file_mode {
+            use std::os::unix::fs::PermissionsExt;
+
+            f.set_permissions(std::fs::Permissions::from_mode(mode))
+                .await?;
+        }
+
+        Ok(f)
+    }
 }
 
 #[async_trait]
@@ -301,6 +579,24 @@ impl Accessor for FsBackend {
                     | AccessorCapability::List
                     | AccessorCapability::Blocking,
             )
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_seek: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                write_with_if_not_exists: true,
+                write_with_last_modified: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                blocking: true,
+                ..Default::default()
+            })
             .set_hints(AccessorHint::ReadSeekable);
 
         am
@@ -321,21 +617,23 @@ impl Accessor for FsBackend {
                 })?
                 .to_path_buf();
 
-            fs::create_dir_all(&parent).await.map_err(parse_io_error)?;
+            self.create_dir_all(&parent).await?;
 
-            fs::OpenOptions::new()
-                .create(true)
-                .truncate(true)
-                .write(true)
-                .open(&p)
-                .await
-                .map_err(parse_io_error)?;
+            self.open_file(
+                fs::OpenOptions::new()
+                    .create(true)
+                    .truncate(true)
+                    .write(true),
+                &p,
+            )
+            .await
+            .map_err(parse_io_error)?;
 
             return Ok(RpCreate::default());
         }
 
         if args.mode() == EntryMode::DIR {
-            fs::create_dir_all(&p).await.map_err(parse_io_error)?;
+            self.create_dir_all(&p).await?;
 
             return Ok(RpCreate::default());
         }
@@ -415,33 +713,61 @@ impl Accessor for FsBackend {
         Ok((RpRead::new(end - start), r))
     }
 
-    async fn write(&self, path: &str, _: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        let (target_path, tmp_path) = if let Some(atomic_write_dir) = &self.atomic_write_dir {
-            let target_path = Self::ensure_write_abs_path(&self.root, path).await?;
-            let tmp_path =
-                Self::ensure_write_abs_path(atomic_write_dir, &tmp_file_of(path)).await?;
-            (target_path, Some(tmp_path))
-        } else {
-            let p = Self::ensure_write_abs_path(&self.root, path).await?;
-
-            (p, None)
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let target_path = self.ensure_write_abs_path(&self.root, path).await?;
+        // Fail fast if path exists, the final check happens while committing.
+        if args.if_not_exists() && fs::metadata(&target_path).await.is_ok() {
+            return Err(Error::new(
+                ErrorKind::ConditionNotMatch,
+                "path already exists",
+            ));
+        }
+        let atomic = args.atomic().unwrap_or(!self.disable_atomic_write);
+        let tmp_path = match &self.atomic_write_dir {
+            _ if !atomic => None,
+            Some(atomic_write_dir) => Some(
+                self.ensure_write_abs_path(atomic_write_dir, &tmp_file_of(path))
+                    .await?,
+            ),
+            None => Some(sibling_tmp_file_of(&target_path)),
         };
 
-        let f = tokio::fs::OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .open(tmp_path.as_ref().unwrap_or(&target_path))
+        let mut opts = fs::OpenOptions::new();
+        opts.write(true);
+        // Without temp file, `O_CREAT | O_EXCL` makes sure we never
+        // overwrite existing files.
+        if tmp_path.is_none() && args.if_not_exists() {
+            opts.create_new(true);
+        } else {
+            opts.create(true).truncate(true);
+        }
+        let f = self
+            .open_file(&mut opts, tmp_path.as_ref().unwrap_or(&target_path))
             .await
-            .map_err(parse_io_error)?;
+            .map_err(parse_if_not_exists_error)?;
 
-        Ok((RpWrite::new(), FsWriter::new(target_path, tmp_path, f)))
+        Ok((
+            RpWrite::new(),
+            FsWriter::new(target_path, tmp_path, args, self.enable_sync_dir, f),
+        ))
     }
 
     async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
         let p = self.root.join(path.trim_end_matches('/'));
 
-        let meta = tokio::fs::metadata(&p).await.map_err(parse_io_error)?;
+        let meta = match self.symlink_policy {
+            SymlinkPolicy::Follow => fs::metadata(&p).await,
+            SymlinkPolicy::Skip | SymlinkPolicy::Report => fs::symlink_metadata(&p).await,
+        }
+        .map_err(parse_io_error)?;
+        let is_symlink = meta.file_type().is_symlink();
+
+        if is_symlink && self.symlink_policy == SymlinkPolicy::Skip {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                "path is a symlink which is skipped by symlink policy",
+            ));
+        }
 
         if self.enable_path_check && meta.is_dir() != path.ends_with('/') {
             return Err(Error::new(
@@ -458,12 +784,15 @@ impl Accessor for FsBackend {
             EntryMode::Unknown
         };
         let m = Metadata::new(mode)
+            .with_symlink(is_symlink)
             .with_content_length(meta.len())
             .with_last_modified(
                 meta.modified()
                     .map(OffsetDateTime::from)
                     .map_err(parse_io_error)?,
             );
+        #[cfg(unix)]
+        let m = with_unix_metadata(m, &meta);
 
         Ok(RpStat::new(m))
     }
@@ -502,7 +831,7 @@ impl Accessor for FsBackend {
             }
         };
 
-        let rd = FsPager::new(&self.root, f, args.limit());
+        let rd = FsPager::new(&self.root, f, args.limit(), self.symlink_policy);
 
         Ok((RpList::default(), Some(rd)))
     }
@@ -522,19 +851,16 @@ impl Accessor for FsBackend {
                 })?
                 .to_path_buf();
 
-            std::fs::create_dir_all(parent).map_err(parse_io_error)?;
+            self.blocking_create_dir_all(&parent)?;
 
-            std::fs::OpenOptions::new()
-                .create(true)
-                .write(true)
-                .open(&p)
+            self.blocking_open_file(std::fs::OpenOptions::new().create(true).write(true), &p)
                 .map_err(parse_io_error)?;
 
             return Ok(RpCreate::default());
         }
 
         if args.mode() == EntryMode::DIR {
-            std::fs::create_dir_all(&p).map_err(parse_io_error)?;
+            self.blocking_create_dir_all(&p)?;
 
             return Ok(RpCreate::default());
         }
@@ -602,32 +928,59 @@ impl Accessor for FsBackend {
         Ok((RpRead::new(end - start), r))
     }
 
-    fn blocking_write(&self, path: &str, _: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
-        let (target_path, tmp_path) = if let Some(atomic_write_dir) = &self.atomic_write_dir {
-            let target_path = Self::blocking_ensure_write_abs_path(&self.root, path)?;
-            let tmp_path =
-                Self::blocking_ensure_write_abs_path(atomic_write_dir, &tmp_file_of(path))?;
-            (target_path, Some(tmp_path))
-        } else {
-            let p = Self::blocking_ensure_write_abs_path(&self.root, path)?;
-
-            (p, None)
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let target_path = self.blocking_ensure_write_abs_path(&self.root, path)?;
+        // Fail fast if path exists, the final check happens while committing.
+        if args.if_not_exists() && std::fs::metadata(&target_path).is_ok() {
+            return Err(Error::new(
+                ErrorKind::ConditionNotMatch,
+                "path already exists",
+            ));
+        }
+        let atomic = args.atomic().unwrap_or(!self.disable_atomic_write);
+        let tmp_path = match &self.atomic_write_dir {
+            _ if !atomic => None,
+            Some(atomic_write_dir) => {
+                Some(self.blocking_ensure_write_abs_path(atomic_write_dir, &tmp_file_of(path))?)
+            }
+            None => Some(sibling_tmp_file_of(&target_path)),
         };
 
-        let f = std::fs::OpenOptions::new()
-            .create(true)
-            .truncate(true)
-            .write(true)
-            .open(tmp_path.as_ref().unwrap_or(&target_path))
-            .map_err(parse_io_error)?;
-
-        Ok((RpWrite::new(), FsWriter::new(target_path, tmp_path, f)))
+        let mut opts = std::fs::OpenOptions::new();
+        opts.write(true);
+        // Without temp file, `O_CREAT | O_EXCL` makes sure we never
+        // overwrite existing files.
+        if tmp_path.is_none() && args.if_not_exists() {
+            opts.create_new(true);
+        } else {
+            opts.create(true).truncate(true);
+        }
+        let f = self
+            .blocking_open_file(&mut opts, tmp_path.as_ref().unwrap_or(&target_path))
+            .map_err(parse_if_not_exists_error)?;
+
+        Ok((
+            RpWrite::new(),
+            FsWriter::new(target_path, tmp_path, args, self.enable_sync_dir, f),
+        ))
     }
 
     fn blocking_stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
         let p = self.root.join(path.trim_end_matches('/'));
 
-        let meta = std::fs::metadata(p).map_err(parse_io_error)?;
+        let meta = match self.symlink_policy {
+            SymlinkPolicy::Follow => std::fs::metadata(&p),
+            SymlinkPolicy::Skip | SymlinkPolicy::Report => std::fs::symlink_metadata(&p),
+        }
+        .map_err(parse_io_error)?;
+        let is_symlink = meta.file_type().is_symlink();
+
+        if is_symlink && self.symlink_policy == SymlinkPolicy::Skip {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                "path is a symlink which is skipped by symlink policy",
+            ));
+        }
 
         if self.enable_path_check && meta.is_dir() != path.ends_with('/') {
             return Err(Error::new(
@@ -644,12 +997,15 @@ impl Accessor for FsBackend {
             EntryMode::Unknown
         };
         let m = Metadata::new(mode)
+            .with_symlink(is_symlink)
             .with_content_length(meta.len())
             .with_last_modified(
                 meta.modified()
                     .map(OffsetDateTime::from)
                     .map_err(parse_io_error)?,
             );
+        #[cfg(unix)]
+        let m = with_unix_metadata(m, &meta);
 
         Ok(RpStat::new(m))
     }
@@ -688,7 +1044,7 @@ impl Accessor for FsBackend {
             }
         };
 
-        let rd = FsPager::new(&self.root, f, args.limit());
+        let rd = FsPager::new(&self.root, f, args.limit(), self.symlink_policy);
 
         Ok((RpList::default(), Some(rd)))
     }
@@ -696,8 +1052,17 @@ impl Accessor for FsBackend {
 
 #[cfg(test)]
 mod tests {
+    use futures::TryStreamExt;
+
     use super::*;
 
+    fn new_operator(mut builder: FsBuilder) -> (Operator, PathBuf) {
+        let root = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        builder.root(&root.to_string_lossy());
+
+        (Operator::new(builder).unwrap().finish(), root)
+    }
+
     #[test]
     fn test_tmp_file_of() {
         let cases = vec![
@@ -712,4 +1077,388 @@ mod tests {
             assert!(tmp_file.starts_with(expected_prefix));
         }
     }
+
+    #[test]
+    fn test_capability() {
+        let mut builder = FsBuilder::default();
+        builder.root(&std::env::temp_dir().to_string_lossy());
+        let op = Operator::new(builder).unwrap().finish();
+
+        assert_eq!(
+            op.info().capability(),
+            Capability {
+                stat: true,
+                read: true,
+                read_can_seek: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                write_with_if_not_exists: true,
+                write_with_last_modified: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                scan: true,
+                blocking: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_atomic_write_with_concurrent_readers() -> anyhow::Result<()> {
+        let (op, root) = new_operator(FsBuilder::default());
+        let size = 4 * 1024 * 1024;
+        op.write("file", vec![0; size]).await?;
+
+        let write = async {
+            let mut w = op.writer("file").await?;
+            for _ in 0..16 {
+                w.append(vec![1; size / 16]).await?;
+                tokio::task::yield_now().await;
+            }
+            w.close().await?;
+
+            Ok::<(), Error>(())
+        };
+        let read = async {
+            for _ in 0..64 {
+                let bs = op.read("file").await?;
+                assert_eq!(bs.len(), size, "readers must not see short files");
+                tokio::task::yield_now().await;
+            }
+
+            Ok::<(), Error>(())
+        };
+        let (wr, rr) = tokio::join!(write, read);
+        wr?;
+        rr?;
+
+        assert_eq!(op.read("file").await?, vec![1; size]);
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_atomic_write_no_orphan_tmp_files() -> anyhow::Result<()> {
+        let (op, root) = new_operator(FsBuilder::default());
+
+        // Temp files are not visible while writing.
+        let mut w = op.writer("aborted").await?;
+        w.append(vec![1; 1024]).await?;
+        let entries: Vec<_> = op.list("/").await?.try_collect().await?;
+        assert!(entries.is_empty());
+
+        w.abort().await?;
+        assert_eq!(std::fs::read_dir(&root)?.count(), 0);
+
+        // Dropped writer should not leave temp files.
+        let mut w = op.writer("dropped").await?;
+        w.append(vec![1; 1024]).await?;
+        drop(w);
+        assert_eq!(std::fs::read_dir(&root)?.count(), 0);
+
+        // Blocking writer should not leave temp files too.
+        let mut w = op.blocking().writer("dropped")?;
+        w.append(vec![1; 1024])?;
+        drop(w);
+        assert_eq!(std::fs::read_dir(&root)?.count(), 0);
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_disable_atomic_write() -> anyhow::Result<()> {
+        let mut builder = FsBuilder::default();
+        builder.disable_atomic_write().enable_sync_dir();
+        let (op, root) = new_operator(builder);
+
+        let mut w = op.writer("file").await?;
+        w.append(vec![1; 1024]).await?;
+        // Data is written into target path directly.
+        assert!(root.join("file").exists());
+        w.close().await?;
+
+        assert_eq!(op.read("file").await?, vec![1; 1024]);
+        assert_eq!(std::fs::read_dir(&root)?.count(), 1);
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_atomic() -> anyhow::Result<()> {
+        let mut builder = FsBuilder::default();
+        builder.disable_atomic_write();
+        let (op, root) = new_operator(builder);
+
+        // Opt-in for a single write.
+        let mut w = op
+            .writer_with("atomic", OpWrite::new().with_atomic(true))
+            .await?;
+        w.append(vec![1; 1024]).await?;
+        assert!(!root.join("atomic").exists());
+        w.close().await?;
+        assert_eq!(op.read("atomic").await?, vec![1; 1024]);
+
+        // Temp file is cleaned up on failure.
+        let mut w = op
+            .blocking()
+            .writer_with("aborted", OpWrite::new().with_atomic(true))?;
+        w.append(vec![1; 1024])?;
+        drop(w);
+        assert!(!root.join("aborted").exists());
+        assert_eq!(std::fs::read_dir(&root)?.count(), 1);
+
+        std::fs::remove_dir_all(root)?;
+
+        // Opt-out for a single write.
+        let (op, root) = new_operator(FsBuilder::default());
+        let mut w = op
+            .writer_with("direct", OpWrite::new().with_atomic(false))
+            .await?;
+        w.append(vec![1; 1024]).await?;
+        assert!(root.join("direct").exists());
+        w.close().await?;
+        assert_eq!(op.read("direct").await?, vec![1; 1024]);
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_if_not_exists() -> anyhow::Result<()> {
+        let atomic = FsBuilder::default();
+        let mut direct = FsBuilder::default();
+        direct.disable_atomic_write();
+
+        for builder in [atomic, direct] {
+            let (op, root) = new_operator(builder);
+            let args = OpWrite::new().with_if_not_exists(true);
+
+            op.write_with("file", args.clone(), "first").await?;
+            let err = op
+                .write_with("file", args.clone(), "second")
+                .await
+                .expect_err("second write must fail");
+            assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
+            assert_eq!(op.read("file").await?, b"first");
+
+            let blocking = op.blocking();
+            let err = blocking
+                .write_with("file", args.clone(), "third")
+                .expect_err("blocking write must fail");
+            assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
+            assert_eq!(op.read("file").await?, b"first");
+            assert_eq!(std::fs::read_dir(&root)?.count(), 1);
+
+            std::fs::remove_dir_all(root)?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_if_not_exists_created_while_writing() -> anyhow::Result<()> {
+        let (op, root) = new_operator(FsBuilder::default());
+        let mut builder = FsBuilder::default();
+        builder.root(&root.to_string_lossy());
+        let backend = builder.build()?;
+
+        let (_, mut w) = backend
+            .write("file", OpWrite::new().with_if_not_exists(true))
+            .await?;
+        oio::Write::append(&mut w, bytes::Bytes::from("mine")).await?;
+
+        // Files created by others while writing must not be overwritten.
+        std::fs::write(root.join("file"), "theirs")?;
+        let err = oio::Write::close(&mut w)
+            .await
+            .expect_err("close must fail");
+        assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
+        drop(w);
+
+        assert_eq!(op.read("file").await?, b"theirs");
+        assert_eq!(std::fs::read_dir(&root)?.count(), 1);
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_map_modes() {
+        let map = HashMap::from([
+            ("file_mode".to_string(), "600".to_string()),
+            ("dir_mode".to_string(), "0o700".to_string()),
+        ]);
+        let builder = FsBuilder::from_map(map);
+
+        assert_eq!(builder.file_mode, Some(0o600));
+        assert_eq!(builder.dir_mode, Some(0o700));
+    }
+
+    #[test]
+    fn test_from_map_invalid_mode() {
+        let map = HashMap::from([("file_mode".to_string(), "rw-r--r--".to_string())]);
+        let mut builder = FsBuilder::from_map(map);
+
+        let err = builder.build().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_file_and_dir_mode() -> anyhow::Result<()> {
+        use std::os::unix::fs::MetadataExt;
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode_of = |p: PathBuf| std::fs::metadata(p).unwrap().permissions().mode() & 0o7777;
+
+        for disable_atomic_write in [false, true] {
+            let mut builder = FsBuilder::default();
+            builder.file_mode(0o600).dir_mode(0o700);
+            if disable_atomic_write {
+                builder.disable_atomic_write();
+            }
+            let (op, root) = new_operator(builder);
+
+            op.write("dir/file", "secret").await?;
+            op.blocking().write("dir/blocking", "secret")?;
+            op.create_dir("created/").await?;
+            assert_eq!(mode_of(root.join("dir/file")), 0o600);
+            assert_eq!(mode_of(root.join("dir/blocking")), 0o600);
+            assert_eq!(mode_of(root.join("dir")), 0o700);
+            assert_eq!(mode_of(root.join("created")), 0o700);
+
+            let fs_meta = std::fs::metadata(root.join("dir/file"))?;
+            let meta = op.stat("dir/file").await?;
+            assert_eq!(meta.unix_mode(), Some(0o600));
+            assert_eq!(meta.uid(), Some(fs_meta.uid()));
+            assert_eq!(meta.gid(), Some(fs_meta.gid()));
+            assert_eq!(op.blocking().stat("dir/file")?.unix_mode(), Some(0o600));
+
+            let entries: Vec<_> = op.list("dir/").await?.try_collect().await?;
+            assert_eq!(entries.len(), 2);
+            for de in entries {
+                let meta = op.metadata(&de, Metakey::UnixMode).await?;
+                assert_eq!(meta.unix_mode(), Some(0o600));
+            }
+
+            std::fs::remove_dir_all(root)?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_last_modified() -> anyhow::Result<()> {
+        let (op, root) = new_operator(FsBuilder::default());
+        let last_modified = OffsetDateTime::from_unix_timestamp(1_600_000_000)?;
+        let args = OpWrite::new().with_last_modified(last_modified);
+
+        op.write_with("file", args.clone(), "Hello, World!").await?;
+        assert_eq!(op.stat("file").await?.last_modified(), Some(last_modified));
+
+        op.blocking()
+            .write_with("blocking", args.clone(), "Hello, World!")?;
+        assert_eq!(
+            op.stat("blocking").await?.last_modified(),
+            Some(last_modified)
+        );
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_symlink_policy() {
+        let mut builder = FsBuilder::default();
+        builder
+            .root(&std::env::temp_dir().to_string_lossy())
+            .symlink_policy("unknown");
+
+        let err = builder.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink_policy() -> anyhow::Result<()> {
+        use std::os::unix::fs::symlink;
+
+        async fn list(op: &Operator) -> anyhow::Result<Vec<(String, Metadata)>> {
+            let mut entries = Vec::new();
+            let mut lister = op.list("/").await?;
+            while let Some(de) = lister.try_next().await? {
+                let meta = op.metadata(&de, Metakey::Mode).await?;
+                entries.push((de.path().to_string(), meta));
+            }
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+            Ok(entries)
+        }
+
+        let root = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(root.join("dir"))?;
+        std::fs::write(root.join("file"), "Hello, World!")?;
+        symlink(root.join("file"), root.join("good"))?;
+        symlink(root.join("dir"), root.join("good_dir"))?;
+        symlink(root.join("not_exist"), root.join("dangling"))?;
+
+        // Follow
+        let mut builder = FsBuilder::default();
+        builder.root(&root.to_string_lossy());
+        let op = Operator::new(builder)?.finish();
+        let entries = list(&op).await?;
+        let paths: Vec<_> = entries.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, vec!["dir/", "file", "good", "good_dir/"]);
+        assert!(entries.iter().all(|(_, m)| !m.is_symlink()));
+        let meta = op.stat("good").await?;
+        assert!(meta.is_file());
+        assert!(!meta.is_symlink());
+        assert_eq!(meta.content_length(), 13);
+        let err = op.stat("dangling").await.expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        // Skip
+        let mut builder = FsBuilder::default();
+        builder.root(&root.to_string_lossy()).symlink_policy("skip");
+        let op = Operator::new(builder)?.finish();
+        let entries = list(&op).await?;
+        let paths: Vec<_> = entries.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, vec!["dir/", "file"]);
+        let err = op.stat("good").await.expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert!(op.stat("file").await?.is_file());
+        let blocking: Vec<_> = op.blocking().list("/")?.collect::<Result<_>>()?;
+        assert_eq!(blocking.len(), 2);
+
+        // Report
+        let mut builder = FsBuilder::default();
+        builder
+            .root(&root.to_string_lossy())
+            .symlink_policy("report");
+        let op = Operator::new(builder)?.finish();
+        let entries = list(&op).await?;
+        let paths: Vec<_> = entries.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, vec!["dangling", "dir/", "file", "good", "good_dir"]);
+        for (path, meta) in entries {
+            let is_link = ["dangling", "good", "good_dir"].contains(&path.as_str());
+            assert_eq!(meta.is_symlink(), is_link, "{path}");
+            if is_link {
+                assert_eq!(meta.mode(), EntryMode::Unknown);
+            }
+        }
+        let meta = op.stat("dangling").await?;
+        assert!(meta.is_symlink());
+        assert_eq!(meta.mode(), EntryMode::Unknown);
+        assert!(op.blocking().stat("good")?.is_symlink());
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
 }
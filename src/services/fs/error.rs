@@ -36,3 +36,14 @@ pub fn parse_io_error(err: io::Error) -> Error {
 
     err
 }
+
+/// Parse io errors returned while writing with `if_not_exists`.
+///
+/// `AlreadyExists` means the path has been created by others.
+pub fn parse_if_not_exists_error(err: io::Error) -> Error {
+    if err.kind() == io::ErrorKind::AlreadyExists {
+        Error::new(ErrorKind::ConditionNotMatch, "path already exists").set_source(err)
+    } else {
+        parse_io_error(err)
+    }
+}
@@ -15,6 +15,6 @@
 mod backend;
 pub use backend::FsBuilder as Fs;
 
-mod error;
+pub(super) mod error;
 mod pager;
 mod writer;
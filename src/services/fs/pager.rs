@@ -12,11 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::ffi::OsStr;
 use std::path::Path;
 use std::path::PathBuf;
 
 use async_trait::async_trait;
+use log::debug;
 
+#[cfg(unix)]
+use super::backend::with_unix_metadata;
+use super::backend::SymlinkPolicy;
+use super::backend::TMP_FILE_PREFIX;
 use super::error::parse_io_error;
 use crate::raw::*;
 use crate::EntryMode;
@@ -28,28 +34,62 @@ pub struct FsPager<P> {
 
     size: usize,
     rd: P,
+    symlink_policy: SymlinkPolicy,
+}
+
+/// Temp files created by atomic write should not be visible to users.
+fn is_tmp_file(name: &OsStr) -> bool {
+    name.to_string_lossy().starts_with(TMP_FILE_PREFIX)
 }
 
 impl<P> FsPager<P> {
-    pub fn new(root: &Path, rd: P, limit: Option<usize>) -> Self {
+    pub(super) fn new(
+        root: &Path,
+        rd: P,
+        limit: Option<usize>,
+        symlink_policy: SymlinkPolicy,
+    ) -> Self {
         Self {
             root: root.to_owned(),
             size: limit.unwrap_or(1000),
             rd,
+            symlink_policy,
         }
     }
 }
 
+/// Build entry path and metadata by the file type.
+///
+/// Symlinks passed in here are not followed, so they will be marked.
+fn new_entry(rel_path: String, file_type: std::fs::FileType) -> (String, Metadata) {
+    if file_type.is_file() {
+        (rel_path, Metadata::new(EntryMode::FILE))
+    } else if file_type.is_dir() {
+        // Make sure we are returning the correct path.
+        (format!("{rel_path}/"), Metadata::new(EntryMode::DIR))
+    } else if file_type.is_symlink() {
+        (
+            rel_path,
+            Metadata::new(EntryMode::Unknown).with_symlink(true),
+        )
+    } else {
+        (rel_path, Metadata::new(EntryMode::Unknown))
+    }
+}
+
 #[async_trait]
 impl oio::Page for FsPager<tokio::fs::ReadDir> {
     async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
         let mut oes: Vec<oio::Entry> = Vec::with_capacity(self.size);
 
-        for _ in 0..self.size {
+        while oes.len() < self.size {
             let de = match self.rd.next_entry().await.map_err(parse_io_error)? {
                 Some(de) => de,
                 None => break,
             };
+            if is_tmp_file(&de.file_name()) {
+                continue;
+            }
 
             let entry_path = de.path();
             let rel_path = normalize_path(
@@ -66,16 +106,38 @@ impl oio::Page for FsPager<tokio::fs::ReadDir> {
             // the target file type.
             let file_type = de.file_type().await.map_err(parse_io_error)?;
 
-            let d = if file_type.is_file() {
-                oio::Entry::new(&rel_path, Metadata::new(EntryMode::FILE))
-            } else if file_type.is_dir() {
-                // Make sure we are returning the correct path.
-                oio::Entry::new(&format!("{rel_path}/"), Metadata::new(EntryMode::DIR))
+            // The metadata of link target if symlink is followed.
+            let target = if file_type.is_symlink() {
+                match self.symlink_policy {
+                    SymlinkPolicy::Skip => continue,
+                    SymlinkPolicy::Report => None,
+                    SymlinkPolicy::Follow => match tokio::fs::metadata(&entry_path).await {
+                        Ok(meta) => Some(meta),
+                        Err(err) => {
+                            debug!(
+                                "skip symlink {} which target can't be resolved: {err}",
+                                entry_path.display()
+                            );
+                            continue;
+                        }
+                    },
+                }
             } else {
-                oio::Entry::new(&rel_path, Metadata::new(EntryMode::Unknown))
+                None
             };
 
-            oes.push(d)
+            let (path, meta) = match &target {
+                Some(target) => new_entry(rel_path, target.file_type()),
+                None => new_entry(rel_path, file_type),
+            };
+            // Mode, uid and gid are only available via an extra `lstat`.
+            #[cfg(unix)]
+            let meta = match &target {
+                Some(target) => with_unix_metadata(meta, target),
+                None => with_unix_metadata(meta, &de.metadata().await.map_err(parse_io_error)?),
+            };
+
+            oes.push(oio::Entry::with(path, meta))
         }
 
         Ok(if oes.is_empty() { None } else { Some(oes) })
@@ -86,11 +148,14 @@ impl oio::BlockingPage for FsPager<std::fs::ReadDir> {
     fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
         let mut oes: Vec<oio::Entry> = Vec::with_capacity(self.size);
 
-        for _ in 0..self.size {
+        while oes.len() < self.size {
             let de = match self.rd.next() {
                 Some(de) => de.map_err(parse_io_error)?,
                 None => break,
             };
+            if is_tmp_file(&de.file_name()) {
+                continue;
+            }
 
             let entry_path = de.path();
             let rel_path = normalize_path(
@@ -107,16 +172,38 @@ impl oio::BlockingPage for FsPager<std::fs::ReadDir> {
             // the target file type.
             let file_type = de.file_type().map_err(parse_io_error)?;
 
-            let d = if file_type.is_file() {
-                oio::Entry::new(&rel_path, Metadata::new(EntryMode::FILE))
-            } else if file_type.is_dir() {
-                // Make sure we are returning the correct path.
-                oio::Entry::new(&format!("{rel_path}/"), Metadata::new(EntryMode::DIR))
+            // The metadata of link target if symlink is followed.
+            let target = if file_type.is_symlink() {
+                match self.symlink_policy {
+                    SymlinkPolicy::Skip => continue,
+                    SymlinkPolicy::Report => None,
+                    SymlinkPolicy::Follow => match std::fs::metadata(&entry_path) {
+                        Ok(meta) => Some(meta),
+                        Err(err) => {
+                            debug!(
+                                "skip symlink {} which target can't be resolved: {err}",
+                                entry_path.display()
+                            );
+                            continue;
+                        }
+                    },
+                }
             } else {
-                oio::Entry::new(&rel_path, Metadata::new(EntryMode::Unknown))
+                None
+            };
+
+            let (path, meta) = match &target {
+                Some(target) => new_entry(rel_path, target.file_type()),
+                None => new_entry(rel_path, file_type),
+            };
+            // Mode, uid and gid are only available via an extra `lstat`.
+            #[cfg(unix)]
+            let meta = match &target {
+                Some(target) => with_unix_metadata(meta, target),
+                None => with_unix_metadata(meta, &de.metadata().map_err(parse_io_error)?),
             };
 
-            oes.push(d)
+            oes.push(oio::Entry::with(path, meta))
         }
 
         Ok(if oes.is_empty() { None } else { Some(oes) })
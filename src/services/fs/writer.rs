@@ -15,33 +15,73 @@
 use std::io::Seek;
 use std::io::SeekFrom;
 use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use filetime::FileTime;
 use tokio::io::AsyncSeekExt;
 use tokio::io::AsyncWriteExt;
 
+use super::error::parse_if_not_exists_error;
 use super::error::parse_io_error;
+use crate::ops::*;
 use crate::raw::*;
 use crate::*;
 
 pub struct FsWriter<F> {
     target_path: PathBuf,
+    /// The temp file that data written into, will be set to `None` after
+    /// renamed or removed.
     tmp_path: Option<PathBuf>,
+    op: OpWrite,
+    sync_dir: bool,
     f: F,
     pos: u64,
 }
 
 impl<F> FsWriter<F> {
-    pub fn new(target_path: PathBuf, tmp_path: Option<PathBuf>, f: F) -> Self {
+    pub fn new(
+        target_path: PathBuf,
+        tmp_path: Option<PathBuf>,
+        op: OpWrite,
+        sync_dir: bool,
+        f: F,
+    ) -> Self {
         Self {
             target_path,
             tmp_path,
+            op,
+            sync_dir,
             f,
             pos: 0,
         }
     }
+
+    /// Set the mtime of written file if `last_modified` is specified.
+    ///
+    /// This must happen after all data written, or the mtime will be
+    /// updated again.
+    fn set_last_modified(&self) -> Result<()> {
+        if let Some(t) = self.op.last_modified() {
+            let path = self.tmp_path.as_ref().unwrap_or(&self.target_path);
+            let mtime = FileTime::from_unix_time(t.unix_timestamp(), t.nanosecond());
+
+            filetime::set_file_mtime(path, mtime).map_err(parse_io_error)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Remove the temp file if writer is dropped without close.
+impl<F> Drop for FsWriter<F> {
+    fn drop(&mut self) {
+        if let Some(tmp_path) = self.tmp_path.take() {
+            let _ = std::fs::remove_file(tmp_path);
+        }
+    }
 }
 
 #[async_trait]
@@ -72,16 +112,47 @@ impl oio::Write for FsWriter<tokio::fs::File> {
         Ok(())
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn close(&mut self) -> Result<RpWrite> {
+        self.f.flush().await.map_err(parse_io_error)?;
+        self.set_last_modified()?;
         self.f.sync_all().await.map_err(parse_io_error)?;
 
         if let Some(tmp_path) = &self.tmp_path {
-            tokio::fs::rename(tmp_path, &self.target_path)
-                .await
-                .map_err(parse_io_error)?;
+            if self.op.if_not_exists() {
+                // Hard link fails if target exists, which makes sure we
+                // never overwrite files created by others.
+                tokio::fs::hard_link(tmp_path, &self.target_path)
+                    .await
+                    .map_err(parse_if_not_exists_error)?;
+                tokio::fs::remove_file(tmp_path)
+                    .await
+                    .map_err(parse_io_error)?;
+            } else {
+                tokio::fs::rename(tmp_path, &self.target_path)
+                    .await
+                    .map_err(parse_io_error)?;
+            }
+            self.tmp_path = None;
         }
 
-        Ok(())
+        if self.sync_dir {
+            sync_parent_dir(&self.target_path).await?;
+        }
+
+        Ok(RpWrite::new())
+    }
+
+    /// # Notes
+    ///
+    /// With atomic write disabled, data is written into the target file
+    /// directly, so we have to remove the target file instead.
+    async fn abort(&mut self) -> Result<()> {
+        let path = self
+            .tmp_path
+            .take()
+            .unwrap_or_else(|| self.target_path.clone());
+
+        tokio::fs::remove_file(path).await.map_err(parse_io_error)
     }
 }
 
@@ -111,13 +182,72 @@ impl oio::BlockingWrite for FsWriter<std::fs::File> {
         Ok(())
     }
 
-    fn close(&mut self) -> Result<()> {
+    fn close(&mut self) -> Result<RpWrite> {
+        self.set_last_modified()?;
         self.f.sync_all().map_err(parse_io_error)?;
 
         if let Some(tmp_path) = &self.tmp_path {
-            std::fs::rename(tmp_path, &self.target_path).map_err(parse_io_error)?;
+            if self.op.if_not_exists() {
+                // Hard link fails if target exists, which makes sure we
+                // never overwrite files created by others.
+                std::fs::hard_link(tmp_path, &self.target_path)
+                    .map_err(parse_if_not_exists_error)?;
+                std::fs::remove_file(tmp_path).map_err(parse_io_error)?;
+            } else {
+                std::fs::rename(tmp_path, &self.target_path).map_err(parse_io_error)?;
+            }
+            self.tmp_path = None;
         }
 
-        Ok(())
+        if self.sync_dir {
+            blocking_sync_parent_dir(&self.target_path)?;
+        }
+
+        Ok(RpWrite::new())
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        let path = self
+            .tmp_path
+            .take()
+            .unwrap_or_else(|| self.target_path.clone());
+
+        std::fs::remove_file(path).map_err(parse_io_error)
+    }
+}
+
+/// Sync the parent dir of path to make sure the new entry is durable.
+#[cfg(unix)]
+async fn sync_parent_dir(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        let f = tokio::fs::File::open(parent)
+            .await
+            .map_err(parse_io_error)?;
+        f.sync_all().await.map_err(parse_io_error)?;
     }
+
+    Ok(())
+}
+
+/// Syncing dir is not supported on non-unix platforms.
+#[cfg(not(unix))]
+async fn sync_parent_dir(_: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Sync the parent dir of path to make sure the new entry is durable.
+#[cfg(unix)]
+fn blocking_sync_parent_dir(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        let f = std::fs::File::open(parent).map_err(parse_io_error)?;
+        f.sync_all().map_err(parse_io_error)?;
+    }
+
+    Ok(())
+}
+
+/// Syncing dir is not supported on non-unix platforms.
+#[cfg(not(unix))]
+fn blocking_sync_parent_dir(_: &Path) -> Result<()> {
+    Ok(())
 }
@@ -17,7 +17,7 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::str;
-use std::str::FromStr;
+use std::sync::Arc;
 
 use async_tls::TlsConnector;
 use async_trait::async_trait;
@@ -27,8 +27,8 @@ use futures::AsyncRead;
 use futures::AsyncReadExt;
 use http::Uri;
 use log::debug;
-use suppaftp::list::File;
 use suppaftp::types::FileType;
+use suppaftp::types::Mode;
 use suppaftp::types::Response;
 use suppaftp::FtpError;
 use suppaftp::FtpStream;
@@ -36,6 +36,8 @@ use suppaftp::Status;
 use time::OffsetDateTime;
 use tokio::sync::OnceCell;
 
+use super::pager::parse_list_entry;
+use super::pager::parse_metadata;
 use super::pager::FtpPager;
 use super::util::FtpReader;
 use super::writer::FtpWriter;
@@ -62,6 +64,8 @@ use crate::*;
 /// - `root`: Set the work directory for backend
 /// - `credential`:  login credentials
 /// - `tls`: tls mode
+/// - `enable_active_mode`: use active mode instead of passive mode
+/// - `disable_tls_verification`: skip verification of server's certificate
 ///
 /// You can refer to [`FtpBuilder`]'s docs for more information
 ///
@@ -93,6 +97,8 @@ pub struct FtpBuilder {
     root: Option<String>,
     user: Option<String>,
     password: Option<String>,
+    enable_active_mode: bool,
+    disable_tls_verification: bool,
 }
 
 impl Debug for FtpBuilder {
@@ -100,6 +106,8 @@ impl Debug for FtpBuilder {
         f.debug_struct("Builder")
             .field("endpoint", &self.endpoint)
             .field("root", &self.root)
+            .field("enable_active_mode", &self.enable_active_mode)
+            .field("disable_tls_verification", &self.disable_tls_verification)
             .finish()
     }
 }
@@ -148,6 +156,27 @@ impl FtpBuilder {
 
         self
     }
+
+    /// Enable active mode for data connections.
+    ///
+    /// By default, passive mode is used which works better behind NAT and
+    /// firewalls. In active mode, server will connect back to client for
+    /// every transfer.
+    pub fn enable_active_mode(&mut self) -> &mut Self {
+        self.enable_active_mode = true;
+        self
+    }
+
+    /// Disable verification of server's certificate for ftps.
+    ///
+    /// # Notes
+    ///
+    /// This is insecure and should only be used for servers with self-signed
+    /// certificates in trusted networks.
+    pub fn disable_tls_verification(&mut self) -> &mut Self {
+        self.disable_tls_verification = true;
+        self
+    }
 }
 
 impl Builder for FtpBuilder {
@@ -173,6 +202,7 @@ impl Builder for FtpBuilder {
         let host = endpoint_uri.host().unwrap_or("127.0.0.1");
         let port = endpoint_uri.port_u16().unwrap_or(21);
 
+        let host = host.to_string();
         let endpoint = format!("{host}:{port}");
 
         let enable_secure = match endpoint_uri.scheme_str() {
@@ -206,10 +236,13 @@ impl Builder for FtpBuilder {
 
         Ok(FtpBackend {
             endpoint,
+            host,
             root,
             user,
             password,
             enable_secure,
+            enable_active_mode: self.enable_active_mode,
+            disable_tls_verification: self.disable_tls_verification,
             pool: OnceCell::new(),
         })
     }
@@ -221,6 +254,12 @@ impl Builder for FtpBuilder {
         map.get("endpoint").map(|v| builder.endpoint(v));
         map.get("user").map(|v| builder.user(v));
         map.get("password").map(|v| builder.password(v));
+        map.get("enable_active_mode")
+            .filter(|v| *v == "on" || *v == "true")
+            .map(|_| builder.enable_active_mode());
+        map.get("disable_tls_verification")
+            .filter(|v| *v == "on" || *v == "true")
+            .map(|_| builder.disable_tls_verification());
 
         builder
     }
@@ -228,10 +267,13 @@ impl Builder for FtpBuilder {
 
 pub struct Manager {
     endpoint: String,
+    host: String,
     root: String,
     user: String,
     password: String,
     enable_secure: bool,
+    enable_active_mode: bool,
+    disable_tls_verification: bool,
 }
 
 #[async_trait]
@@ -244,13 +286,27 @@ impl bb8::ManageConnection for Manager {
 
         // switch to secure mode if ssl/tls is on.
         let mut ftp_stream = if self.enable_secure {
-            stream
-                .into_secure(TlsConnector::default().into(), &self.endpoint)
-                .await?
+            let connector = if self.disable_tls_verification {
+                let mut config = rustls::ClientConfig::new();
+                config
+                    .dangerous()
+                    .set_certificate_verifier(Arc::new(NoCertificateVerification));
+                TlsConnector::from(Arc::new(config))
+            } else {
+                TlsConnector::default()
+            };
+
+            stream.into_secure(connector.into(), &self.host).await?
         } else {
             stream
         };
 
+        ftp_stream.set_mode(if self.enable_active_mode {
+            Mode::Active
+        } else {
+            Mode::Passive
+        });
+
         // login if needed
         if !self.user.is_empty() {
             ftp_stream.login(&self.user, &self.password).await?;
@@ -284,14 +340,32 @@ impl bb8::ManageConnection for Manager {
     }
 }
 
+/// Accept any certificate presented by server.
+struct NoCertificateVerification;
+
+impl rustls::ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        _: &rustls::RootCertStore,
+        _: &[rustls::Certificate],
+        _: webpki::DNSNameRef<'_>,
+        _: &[u8],
+    ) -> std::result::Result<rustls::ServerCertVerified, rustls::TLSError> {
+        Ok(rustls::ServerCertVerified::assertion())
+    }
+}
+
 /// Backend is used to serve `Accessor` support for ftp.
 #[derive(Clone)]
 pub struct FtpBackend {
     endpoint: String,
+    host: String,
     root: String,
     user: String,
     password: String,
     enable_secure: bool,
+    enable_active_mode: bool,
+    disable_tls_verification: bool,
     pool: OnceCell<bb8::Pool<Manager>>,
 }
 
@@ -316,7 +390,21 @@ impl Accessor for FtpBackend {
             .set_root(&self.root)
             .set_capabilities(
                 AccessorCapability::Read | AccessorCapability::Write | AccessorCapability::List,
-            );
+            )
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                ..Default::default()
+            });
 
         am
     }
@@ -356,43 +444,39 @@ impl Accessor for FtpBackend {
         let mut ftp_stream = self.ftp_connect(Operation::Read).await?;
 
         let meta = self.ftp_stat(path).await?;
+        let total_size = meta.content_length();
 
+        // Ranged reads are served by `REST` before `RETR`.
         let br = args.range();
         let (r, size): (Box<dyn AsyncRead + Send + Unpin>, _) = match (br.offset(), br.size()) {
             (Some(offset), Some(size)) => {
                 ftp_stream.resume_transfer(offset as usize).await?;
                 let ds = ftp_stream.retr_as_stream(path).await?.take(size);
-                (Box::new(ds), min(size, meta.size() as u64 - offset))
+                (Box::new(ds), min(size, total_size.saturating_sub(offset)))
             }
             (Some(offset), None) => {
                 ftp_stream.resume_transfer(offset as usize).await?;
                 let ds = ftp_stream.retr_as_stream(path).await?;
-                (Box::new(ds), meta.size() as u64 - offset)
+                (Box::new(ds), total_size.saturating_sub(offset))
             }
             (None, Some(size)) => {
+                let size = min(size, total_size);
                 ftp_stream
-                    .resume_transfer((meta.size() as u64 - size) as usize)
+                    .resume_transfer((total_size - size) as usize)
                     .await?;
                 let ds = ftp_stream.retr_as_stream(path).await?;
                 (Box::new(ds), size)
             }
             (None, None) => {
                 let ds = ftp_stream.retr_as_stream(path).await?;
-                (Box::new(ds), meta.size() as u64)
+                (Box::new(ds), total_size)
             }
         };
 
         Ok((RpRead::new(size), FtpReader::new(r, ftp_stream)))
     }
 
-    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        if args.append() {
-            return Err(Error::new(
-                ErrorKind::Unsupported,
-                "append write is not supported",
-            ));
-        }
-
+    async fn write(&self, path: &str, _: OpWrite) -> Result<(RpWrite, Self::Writer)> {
         Ok((
             RpWrite::new(),
             FtpWriter::new(self.clone(), path.to_string()),
@@ -405,18 +489,7 @@ impl Accessor for FtpBackend {
             return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
         }
 
-        let file = self.ftp_stat(path).await?;
-
-        let mode = if file.is_file() {
-            EntryMode::FILE
-        } else if file.is_directory() {
-            EntryMode::DIR
-        } else {
-            EntryMode::Unknown
-        };
-        let mut meta = Metadata::new(mode);
-        meta.set_content_length(file.size() as u64);
-        meta.set_last_modified(OffsetDateTime::from(file.modified()));
+        let meta = self.ftp_stat(path).await?;
 
         Ok(RpStat::new(meta))
     }
@@ -466,10 +539,13 @@ impl FtpBackend {
                     .max_size(64)
                     .build(Manager {
                         endpoint: self.endpoint.to_string(),
+                        host: self.host.to_string(),
                         root: self.root.to_string(),
                         user: self.user.to_string(),
                         password: self.password.to_string(),
                         enable_secure: self.enable_secure,
+                        enable_active_mode: self.enable_active_mode,
+                        disable_tls_verification: self.disable_tls_verification,
                     })
                     .await
             })
@@ -483,30 +559,37 @@ impl FtpBackend {
         })
     }
 
-    async fn ftp_stat(&self, path: &str) -> Result<File> {
+    async fn ftp_stat(&self, path: &str) -> Result<Metadata> {
         let mut ftp_stream = self.ftp_connect(Operation::Stat).await?;
 
+        // `SIZE` and `MDTM` only work on files.
+        if !path.ends_with('/') {
+            let size = ftp_stream.size(path).await?;
+            let modified = ftp_stream.mdtm(path).await?;
+
+            let mut meta = Metadata::new(EntryMode::FILE);
+            meta.set_content_length(size as u64);
+            meta.set_last_modified(
+                OffsetDateTime::from_unix_timestamp(modified.timestamp()).map_err(|e| {
+                    Error::new(ErrorKind::Unexpected, "parse last modified of file").set_source(e)
+                })?,
+            );
+
+            return Ok(meta);
+        }
+
         let (parent, basename) = (get_parent(path), get_basename(path));
 
         let pathname = if parent == "/" { None } else { Some(parent) };
 
         let resp = ftp_stream.list(pathname).await?;
 
-        // Get stat of file.
-        let mut files = resp
-            .into_iter()
-            .filter_map(|file| File::from_str(file.as_str()).ok())
-            .filter(|f| f.name() == basename.trim_end_matches('/'))
-            .collect::<Vec<File>>();
-
-        if files.is_empty() {
-            Err(Error::new(
-                ErrorKind::NotFound,
-                "file is not found during list",
-            ))
-        } else {
-            Ok(files.remove(0))
-        }
+        // Get stat of dir.
+        resp.iter()
+            .filter_map(|line| parse_list_entry(line).ok())
+            .find(|f| f.name() == basename.trim_end_matches('/'))
+            .map(|f| parse_metadata(&f))
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "file is not found during list"))
     }
 }
 
@@ -543,4 +626,23 @@ mod build_test {
         let e = b.unwrap_err();
         assert_eq!(e.kind(), ErrorKind::ConfigInvalid);
     }
+
+    #[test]
+    fn test_from_map() {
+        let map = [
+            ("endpoint", "ftps://ftp_server.local"),
+            ("enable_active_mode", "on"),
+            ("disable_tls_verification", "true"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let mut builder = FtpBuilder::from_map(map);
+        let b = builder.build().expect("build must succeed");
+        assert_eq!(b.host, "ftp_server.local");
+        assert_eq!(b.endpoint, "ftp_server.local:21");
+        assert!(b.enable_active_mode);
+        assert!(b.disable_tls_verification);
+    }
 }
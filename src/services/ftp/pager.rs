@@ -44,27 +44,23 @@ impl oio::Page for FtpPager {
     async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
         let mut oes: Vec<oio::Entry> = Vec::with_capacity(self.size);
 
-        for _ in 0..self.size {
+        while oes.len() < self.size {
             let de = match self.file_iter.next() {
-                Some(file_str) => File::from_str(file_str.as_str()).map_err(|e| {
-                    Error::new(ErrorKind::Unexpected, "parse file from response").set_source(e)
-                })?,
+                Some(file_str) => parse_list_entry(&file_str)?,
                 None => break,
             };
 
+            // Some servers return current and parent dir, skip them.
+            if de.name() == "." || de.name() == ".." {
+                continue;
+            }
+
             let path = self.path.to_string() + de.name();
 
-            let d = if de.is_file() {
-                oio::Entry::new(
-                    &path,
-                    Metadata::new(EntryMode::FILE)
-                        .with_content_length(de.size() as u64)
-                        .with_last_modified(OffsetDateTime::from(de.modified())),
-                )
-            } else if de.is_directory() {
+            let d = if de.is_directory() {
                 oio::Entry::new(&format!("{}/", &path), Metadata::new(EntryMode::DIR))
             } else {
-                oio::Entry::new(&path, Metadata::new(EntryMode::Unknown))
+                oio::Entry::new(&path, parse_metadata(&de))
             };
 
             oes.push(d)
@@ -73,3 +69,93 @@ impl oio::Page for FtpPager {
         Ok(if oes.is_empty() { None } else { Some(oes) })
     }
 }
+
+/// Parse a line returned by `LIST`.
+///
+/// `LIST` could return unix or DOS style lines depending on the server.
+pub fn parse_list_entry(line: &str) -> Result<File> {
+    let line = line.trim();
+
+    File::from_str(line).map_err(|e| {
+        Error::new(ErrorKind::Unexpected, "parse file from response")
+            .with_context("line", line)
+            .set_source(e)
+    })
+}
+
+/// Build metadata from parsed file.
+pub fn parse_metadata(file: &File) -> Metadata {
+    let mode = if file.is_file() {
+        EntryMode::FILE
+    } else if file.is_directory() {
+        EntryMode::DIR
+    } else {
+        EntryMode::Unknown
+    };
+
+    let mut meta = Metadata::new(mode);
+    meta.set_content_length(file.size() as u64);
+    meta.set_last_modified(OffsetDateTime::from(file.modified()));
+    meta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list_entry() {
+        let cases = vec![
+            (
+                "unix file",
+                "-rw-r--r--    1 1000     1000         1024 Jan 16 11:14 hello.txt",
+                "hello.txt",
+                EntryMode::FILE,
+                1024,
+            ),
+            (
+                "unix dir",
+                "drwxr-xr-x    2 1000     1000         4096 Nov  5  2022 dir",
+                "dir",
+                EntryMode::DIR,
+                4096,
+            ),
+            (
+                "unix file with space",
+                "-rw-r--r--    1 ftp      ftp            42 Jan 16 11:14 hello world.txt",
+                "hello world.txt",
+                EntryMode::FILE,
+                42,
+            ),
+            (
+                "dos file",
+                "16-01-23  11:14AM                 2048 hello.txt",
+                "hello.txt",
+                EntryMode::FILE,
+                2048,
+            ),
+            (
+                "dos dir",
+                "19-10-20  03:19PM       <DIR>          dir",
+                "dir",
+                EntryMode::DIR,
+                0,
+            ),
+        ];
+
+        for (name, line, file_name, mode, size) in cases {
+            let file = parse_list_entry(line).unwrap_or_else(|err| panic!("{name}: {err}"));
+            assert_eq!(file.name(), file_name, "{name}");
+
+            let meta = parse_metadata(&file);
+            assert_eq!(meta.mode(), mode, "{name}");
+            assert_eq!(meta.content_length(), size, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_parse_list_entry_invalid() {
+        let err = parse_list_entry("total 42").expect_err("must be invalid");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+    }
+}
@@ -23,24 +23,29 @@ use crate::*;
 pub struct FtpWriter {
     backend: FtpBackend,
     path: String,
+    /// Whether the file has been created by this writer.
+    ///
+    /// The first append will use `STOR` to truncate existing content, and
+    /// the following appends will use `APPE` instead.
+    created: bool,
 }
 
-/// # TODO
-///
-/// Writer is not implemented correctly.
-///
-/// After we can use datastream, we should return it directly.
 impl FtpWriter {
     pub fn new(backend: FtpBackend, path: String) -> Self {
-        FtpWriter { backend, path }
+        FtpWriter {
+            backend,
+            path,
+            created: false,
+        }
     }
-}
 
-#[async_trait]
-impl oio::Write for FtpWriter {
-    async fn write(&mut self, bs: Bytes) -> Result<()> {
+    async fn upload(&mut self, bs: Bytes, append: bool) -> Result<()> {
         let mut ftp_stream = self.backend.ftp_connect(Operation::Write).await?;
-        let mut data_stream = ftp_stream.append_with_stream(&self.path).await?;
+        let mut data_stream = if append {
+            ftp_stream.append_with_stream(&self.path).await?
+        } else {
+            ftp_stream.put_with_stream(&self.path).await?
+        };
         data_stream.write_all(&bs).await.map_err(|err| {
             Error::new(ErrorKind::Unexpected, "copy from ftp stream").set_source(err)
         })?;
@@ -49,17 +54,42 @@ impl oio::Write for FtpWriter {
 
         Ok(())
     }
+}
+
+#[async_trait]
+impl oio::Write for FtpWriter {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.upload(bs, false).await?;
+        self.created = true;
+
+        Ok(())
+    }
 
     async fn append(&mut self, bs: Bytes) -> Result<()> {
-        let _ = bs;
+        self.upload(bs, self.created).await?;
+        self.created = true;
 
-        Err(Error::new(
-            ErrorKind::Unsupported,
-            "output writer doesn't support append",
-        ))
+        Ok(())
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn close(&mut self) -> Result<RpWrite> {
+        // Make sure the file exists even if nothing has been appended.
+        if !self.created {
+            self.upload(Bytes::new(), false).await?;
+            self.created = true;
+        }
+
+        Ok(RpWrite::new())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        if !self.created {
+            return Ok(());
+        }
+
+        let mut ftp_stream = self.backend.ftp_connect(Operation::Write).await?;
+        ftp_stream.rm(&self.path).await?;
+
         Ok(())
     }
 }
@@ -34,7 +34,9 @@ use time::OffsetDateTime;
 use super::error::parse_error;
 use super::pager::GcsPager;
 use super::uri::percent_encode_path;
+use super::writer::gcs_cancel_upload_request;
 use super::writer::GcsWriter;
+use super::writer::ResumableUpload;
 use crate::ops::*;
 use crate::raw::*;
 use crate::*;
@@ -42,6 +44,26 @@ use crate::*;
 const DEFAULT_GCS_ENDPOINT: &str = "https://storage.googleapis.com";
 const DEFAULT_GCS_SCOPE: &str = "https://www.googleapis.com/auth/devstorage.read_write";
 
+const X_UPLOAD_CONTENT_TYPE: &str = "x-upload-content-type";
+
+/// The chunk size of resumable upload must be a multiple of 256KiB.
+const WRITE_CHUNK_SIZE_ALIGNMENT: usize = 256 * 1024;
+/// The default chunk size of resumable upload, it's recommended to be at
+/// least 8MiB by GCS.
+const DEFAULT_WRITE_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Predefined ACLs that can be applied to uploaded objects.
+///
+/// Reference: [Predefined ACLs](https://cloud.google.com/storage/docs/access-control/lists#predefined-acl)
+const GCS_PREDEFINED_ACLS: &[&str] = &[
+    "authenticatedRead",
+    "bucketOwnerFullControl",
+    "bucketOwnerRead",
+    "private",
+    "projectPrivate",
+    "publicRead",
+];
+
 /// Google Cloud Storage service.
 ///
 /// # Capabilities
@@ -61,9 +83,39 @@ const DEFAULT_GCS_SCOPE: &str = "https://www.googleapis.com/auth/devstorage.read
 /// - `bucket`: Set the container name for backend
 /// - `endpoint`: Customizable endpoint setting
 /// - `credentials`: Credential string for GCS OAuth2
+/// - `default_kms_key`: Cloud KMS key used to encrypt uploaded objects
+/// - `predefined_acl`: Predefined ACL applied to uploaded objects
+/// - `write_chunk_size`: Chunk size of resumable uploads, default to 8MiB
 ///
 /// You can refer to [`GcsBuilder`]'s docs for more information
 ///
+/// # Resumable writes
+///
+/// Writers are backed by resumable upload sessions. The session returned
+/// by [`Writer::session`](crate::Writer::session) could be saved and used
+/// to resume the write after restarting via [`OpWrite::with_session`](crate::ops::OpWrite::with_session),
+/// bytes should be appended from [`Writer::offset`](crate::Writer::offset)
+/// of the resumed writer.
+///
+/// New sessions will be cancelled if writers are dropped before closing.
+/// Only sessions given by `with_session` are kept while dropping, so that
+/// they could be resumed again.
+///
+/// ```no_run
+/// # use anyhow::Result;
+/// # use opendal::Operator;
+/// use opendal::ops::OpWrite;
+///
+/// # async fn test(op: Operator, session: &str) -> Result<()> {
+/// let w = op
+///     .writer_with("path/to/file", OpWrite::new().with_session(session))
+///     .await?;
+/// let offset = w.offset();
+/// // Continue appending bytes from `offset`.
+/// # Ok(())
+/// # }
+/// ```
+///
 /// # Example
 ///
 /// ## Via Builder
@@ -109,6 +161,13 @@ pub struct GcsBuilder {
     /// credential path for GCS service.
     credential_path: Option<String>,
 
+    /// Cloud KMS key used to encrypt uploaded objects.
+    default_kms_key: Option<String>,
+    /// Predefined ACL applied to uploaded objects.
+    predefined_acl: Option<String>,
+    /// Chunk size of resumable uploads.
+    write_chunk_size: Option<usize>,
+
     http_client: Option<HttpClient>,
     signer: Option<Arc<GoogleSigner>>,
 }
@@ -182,6 +241,47 @@ impl GcsBuilder {
         self
     }
 
+    /// Set the Cloud KMS key that used to encrypt uploaded objects.
+    ///
+    /// The key should be the resource name of the key like
+    /// `projects/my-project/locations/us/keyRings/my-ring/cryptoKeys/my-key`,
+    /// refer to [Customer-managed encryption keys](https://cloud.google.com/storage/docs/encryption/customer-managed-keys)
+    /// for more information.
+    pub fn default_kms_key(&mut self, key: &str) -> &mut Self {
+        if !key.is_empty() {
+            self.default_kms_key = Some(key.to_string())
+        };
+        self
+    }
+
+    /// Set the predefined ACL that applied to uploaded objects.
+    ///
+    /// Available values are `authenticatedRead`, `bucketOwnerFullControl`,
+    /// `bucketOwnerRead`, `private`, `projectPrivate` and `publicRead`.
+    /// Building will fail with [`ErrorKind::ConfigInvalid`] for others.
+    ///
+    /// Reference: [Predefined ACLs](https://cloud.google.com/storage/docs/access-control/lists#predefined-acl)
+    pub fn predefined_acl(&mut self, acl: &str) -> &mut Self {
+        if !acl.is_empty() {
+            self.predefined_acl = Some(acl.to_string())
+        };
+        self
+    }
+
+    /// Set the chunk size of resumable uploads that used by writers.
+    ///
+    /// Appended bytes will be buffered and uploaded in chunks of this size.
+    /// The size must be a multiple of 256KiB, building will fail with
+    /// [`ErrorKind::ConfigInvalid`] for others.
+    ///
+    /// Default to 8MiB.
+    ///
+    /// Reference: [Resumable uploads](https://cloud.google.com/storage/docs/performing-resumable-uploads#chunked-upload)
+    pub fn write_chunk_size(&mut self, size: usize) -> &mut Self {
+        self.write_chunk_size = Some(size);
+        self
+    }
+
     /// Specify the http client that used by this service.
     ///
     /// # Notes
@@ -216,7 +316,10 @@ impl Debug for GcsBuilder {
 
         ds.field("root", &self.root)
             .field("bucket", &self.bucket)
-            .field("endpoint", &self.endpoint);
+            .field("endpoint", &self.endpoint)
+            .field("default_kms_key", &self.default_kms_key)
+            .field("predefined_acl", &self.predefined_acl)
+            .field("write_chunk_size", &self.write_chunk_size);
         if self.credential.is_some() {
             ds.field("credentials", &"<redacted>");
         }
@@ -236,6 +339,12 @@ impl Builder for GcsBuilder {
         map.get("endpoint").map(|v| builder.endpoint(v));
         map.get("credential").map(|v| builder.credential(v));
         map.get("scope").map(|v| builder.scope(v));
+        map.get("default_kms_key")
+            .map(|v| builder.default_kms_key(v));
+        map.get("predefined_acl").map(|v| builder.predefined_acl(v));
+        map.get("write_chunk_size")
+            .and_then(|v| v.parse::<usize>().ok())
+            .map(|v| builder.write_chunk_size(v));
 
         builder
     }
@@ -254,7 +363,27 @@ impl Builder for GcsBuilder {
                 .with_context("service", Scheme::Gcs)),
         }?;
 
-        // TODO: server side encryption
+        if let Some(acl) = &self.predefined_acl {
+            if !GCS_PREDEFINED_ACLS.contains(&acl.as_str()) {
+                return Err(
+                    Error::new(ErrorKind::ConfigInvalid, "predefined_acl is invalid")
+                        .with_operation("Builder::build")
+                        .with_context("service", Scheme::Gcs)
+                        .with_context("predefined_acl", acl),
+                );
+            }
+        }
+
+        let write_chunk_size = self.write_chunk_size.unwrap_or(DEFAULT_WRITE_CHUNK_SIZE);
+        if write_chunk_size == 0 || write_chunk_size % WRITE_CHUNK_SIZE_ALIGNMENT != 0 {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "write_chunk_size must be a multiple of 256KiB",
+            )
+            .with_operation("Builder::build")
+            .with_context("service", Scheme::Gcs)
+            .with_context("write_chunk_size", write_chunk_size.to_string()));
+        }
 
         let client = if let Some(client) = self.http_client.take() {
             client
@@ -269,6 +398,10 @@ impl Builder for GcsBuilder {
             .endpoint
             .clone()
             .unwrap_or_else(|| DEFAULT_GCS_ENDPOINT.to_string());
+        parse_endpoint(&endpoint).map_err(|e| {
+            e.with_operation("Builder::build")
+                .with_context("service", Scheme::Gcs)
+        })?;
 
         debug!("backend use endpoint: {endpoint}");
 
@@ -308,6 +441,10 @@ impl Builder for GcsBuilder {
             bucket: bucket.clone(),
             signer,
             client,
+
+            default_kms_key: self.default_kms_key.clone(),
+            predefined_acl: self.predefined_acl.clone(),
+            write_chunk_size,
         };
 
         Ok(backend)
@@ -324,6 +461,10 @@ pub struct GcsBackend {
 
     pub client: HttpClient,
     pub signer: Arc<GoogleSigner>,
+
+    default_kms_key: Option<String>,
+    predefined_acl: Option<String>,
+    write_chunk_size: usize,
 }
 
 impl Debug for GcsBackend {
@@ -332,6 +473,9 @@ impl Debug for GcsBackend {
         de.field("endpoint", &self.endpoint)
             .field("bucket", &self.bucket)
             .field("root", &self.root)
+            .field("default_kms_key", &self.default_kms_key)
+            .field("predefined_acl", &self.predefined_acl)
+            .field("write_chunk_size", &self.write_chunk_size)
             .field("client", &self.client)
             .field("signer", &"<redacted>")
             .finish()
@@ -356,12 +500,34 @@ impl Accessor for GcsBackend {
             .set_root(&self.root)
             .set_name(&self.bucket)
             .set_capabilities(Read | Write | List | Scan)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                write_multi: true,
+                write_with_session: true,
+                write_with_content_type: true,
+                write_with_if_not_exists: true,
+                abort_multipart: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                scan: true,
+                ..Default::default()
+            })
             .set_hints(ReadStreamable);
         am
     }
 
     async fn create(&self, path: &str, _: OpCreate) -> Result<RpCreate> {
-        let mut req = self.gcs_insert_object_request(path, Some(0), None, AsyncBody::Empty)?;
+        let mut req =
+            self.gcs_insert_object_request(path, Some(0), None, false, AsyncBody::Empty)?;
 
         self.signer.sign(&mut req).map_err(new_request_sign_error)?;
 
@@ -376,30 +542,81 @@ impl Accessor for GcsBackend {
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        let resp = self.gcs_get_object(path, args.range()).await?;
+        let range = args.range();
+        let resp = self.gcs_get_object(path, range).await?;
 
         if resp.status().is_success() {
-            let meta = parse_into_metadata(path, resp.headers())?;
-            Ok((RpRead::with_metadata(meta), resp.into_body()))
+            let (meta, body) = parse_into_read_response(path, range, resp)?;
+            Ok((RpRead::with_metadata(meta), body))
         } else {
             Err(parse_error(resp).await?)
         }
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        if args.append() {
+        if !args.append() && args.session().is_some() {
             return Err(Error::new(
                 ErrorKind::Unsupported,
-                "append write is not supported",
+                "write with session is only supported by writer",
             ));
         }
 
+        let upload = if args.append() {
+            let upload = match args.session() {
+                Some(location) => {
+                    ResumableUpload::resume(
+                        self.client.clone(),
+                        location.to_string(),
+                        self.write_chunk_size,
+                    )
+                    .await?
+                }
+                None => {
+                    let location = self
+                        .gcs_initiate_resumable_upload(
+                            path,
+                            args.content_type(),
+                            args.if_not_exists(),
+                        )
+                        .await?;
+
+                    ResumableUpload::new(self.client.clone(), location, self.write_chunk_size)
+                }
+            };
+            Some(upload)
+        } else {
+            None
+        };
+
+        let rp = match &upload {
+            Some(upload) => RpWrite::new()
+                .with_session(upload.location())
+                .with_offset(upload.offset()),
+            None => RpWrite::new(),
+        };
+
         Ok((
-            RpWrite::default(),
-            GcsWriter::new(self.clone(), args, path.to_string()),
+            rp,
+            GcsWriter::new(self.clone(), args, path.to_string(), upload),
         ))
     }
 
+    /// The upload id is the session uri of the resumable upload, path is
+    /// not needed since the session is bound to the object.
+    async fn abort_multipart(&self, _: &str, args: OpAbortMultipart) -> Result<RpAbortMultipart> {
+        let req = gcs_cancel_upload_request(args.upload_id())?;
+        let resp = self.client.send_async(req).await?;
+
+        match resp.status().as_u16() {
+            // GCS returns code 499 if the session has been cancelled.
+            499 => {
+                resp.into_body().consume().await?;
+                Ok(RpAbortMultipart::default())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
     async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
         // Stat root always returns a DIR.
         if path == "/" {
@@ -513,17 +730,28 @@ impl GcsBackend {
         path: &str,
         size: Option<usize>,
         content_type: Option<&str>,
+        if_not_exists: bool,
         body: AsyncBody,
     ) -> Result<Request<AsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
-        let url = format!(
+        let mut url = format!(
             "{}/upload/storage/v1/b/{}/o?uploadType=media&name={}",
             self.endpoint,
             self.bucket,
             percent_encode_path(&p)
         );
 
+        // Generation `0` means the object must not exist.
+        if if_not_exists {
+            url.push_str("&ifGenerationMatch=0");
+        }
+        write_upload_query(
+            &mut url,
+            self.default_kms_key.as_deref(),
+            self.predefined_acl.as_deref(),
+        );
+
         let mut req = Request::post(&url);
 
         if let Some(size) = size {
@@ -540,6 +768,66 @@ impl GcsBackend {
         Ok(req)
     }
 
+    /// Initiate a resumable upload session and return the session uri.
+    ///
+    /// ref: https://cloud.google.com/storage/docs/performing-resumable-uploads#initiate-session
+    async fn gcs_initiate_resumable_upload(
+        &self,
+        path: &str,
+        content_type: Option<&str>,
+        if_not_exists: bool,
+    ) -> Result<String> {
+        let p = build_abs_path(&self.root, path);
+
+        let mut url = format!(
+            "{}/upload/storage/v1/b/{}/o?uploadType=resumable&name={}",
+            self.endpoint,
+            self.bucket,
+            percent_encode_path(&p)
+        );
+
+        // Generation `0` means the object must not exist.
+        if if_not_exists {
+            url.push_str("&ifGenerationMatch=0");
+        }
+        write_upload_query(
+            &mut url,
+            self.default_kms_key.as_deref(),
+            self.predefined_acl.as_deref(),
+        );
+
+        let mut req = Request::post(&url).header(CONTENT_LENGTH, 0);
+
+        if let Some(mime) = content_type {
+            req = req.header(X_UPLOAD_CONTENT_TYPE, mime)
+        }
+
+        let mut req = req
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+
+        let resp = self.client.send_async(req).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let location = parse_location(resp.headers())?
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::Unexpected,
+                            "Location not present in returning response",
+                        )
+                    })?
+                    .to_string();
+
+                resp.into_body().consume().await?;
+                Ok(location)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
     async fn gcs_get_object_metadata(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
@@ -622,6 +910,17 @@ impl GcsBackend {
     }
 }
 
+/// Append the query params that should be carried by all uploads.
+fn write_upload_query(url: &mut String, kms_key: Option<&str>, predefined_acl: Option<&str>) {
+    if let Some(key) = kms_key {
+        write!(url, "&kmsKeyName={}", percent_encode_path(key))
+            .expect("write into string must succeed");
+    }
+    if let Some(acl) = predefined_acl {
+        write!(url, "&predefinedAcl={acl}").expect("write into string must succeed");
+    }
+}
+
 /// The raw json response returned by [`get`](https://cloud.google.com/storage/docs/json_api/v1/objects/get)
 #[derive(Debug, Default, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
@@ -634,6 +933,10 @@ struct GetObjectJsonResponse {
     ///
     /// For example: `"etag": "CKWasoTgyPkCEAE="`
     etag: String,
+    /// The version of this object.
+    ///
+    /// For example: `"generation": "1660563214863653"`
+    generation: String,
     /// RFC3339 styled datetime string.
     ///
     /// For example: `"updated": "2022-08-15T11:33:34.866Z"`
@@ -648,6 +951,28 @@ struct GetObjectJsonResponse {
     content_type: String,
 }
 
+/// Parse the etag and generation of the object resource returned by
+/// uploads.
+///
+/// Returns an empty reply if the body is empty.
+pub(super) fn parse_write_reply(bs: &[u8]) -> Result<RpWrite> {
+    if bs.is_empty() {
+        return Ok(RpWrite::new());
+    }
+
+    let meta: GetObjectJsonResponse =
+        serde_json::from_slice(bs).map_err(new_json_deserialize_error)?;
+
+    let mut rp = RpWrite::new();
+    if !meta.etag.is_empty() {
+        rp = rp.with_etag(&meta.etag);
+    }
+    if !meta.generation.is_empty() {
+        rp = rp.with_version(&meta.generation);
+    }
+    Ok(rp)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -681,6 +1006,53 @@ mod tests {
         assert_eq!(meta.updated, "2022-08-15T11:33:34.866Z");
         assert_eq!(meta.md5_hash, "fHcEH1vPwA6eTPqxuasXcg==");
         assert_eq!(meta.etag, "CKWasoTgyPkCEAE=");
+        assert_eq!(meta.generation, "1660563214863653");
         assert_eq!(meta.content_type, "image/png");
     }
+
+    #[test]
+    fn test_write_upload_query() {
+        let cases = vec![
+            ("empty", None, None, ""),
+            (
+                "kms key",
+                Some("projects/p/locations/us/keyRings/r/cryptoKeys/k"),
+                None,
+                "&kmsKeyName=projects%2Fp%2Flocations%2Fus%2FkeyRings%2Fr%2FcryptoKeys%2Fk",
+            ),
+            ("acl", None, Some("publicRead"), "&predefinedAcl=publicRead"),
+            (
+                "both",
+                Some("k"),
+                Some("private"),
+                "&kmsKeyName=k&predefinedAcl=private",
+            ),
+        ];
+
+        for (name, kms_key, acl, expected) in cases {
+            let mut url = String::new();
+            write_upload_query(&mut url, kms_key, acl);
+            assert_eq!(url, expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_build_with_invalid_predefined_acl() {
+        let mut builder = GcsBuilder::default();
+        builder.bucket("test").predefined_acl("public-read");
+
+        let err = builder.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[test]
+    fn test_build_with_invalid_write_chunk_size() {
+        for size in [0, 1024 * 1024 + 1] {
+            let mut builder = GcsBuilder::default();
+            builder.bucket("test").write_chunk_size(size);
+
+            let err = builder.build().expect_err("build must fail");
+            assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "{size}");
+        }
+    }
 }
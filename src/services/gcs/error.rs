@@ -13,7 +13,6 @@
 // limitations under the License.
 
 use http::Response;
-use http::StatusCode;
 use serde::Deserialize;
 use serde_json::de;
 
@@ -51,22 +50,31 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
     let (parts, body) = resp.into_parts();
     let bs = body.bytes().await?;
 
-    let (kind, retryable) = match parts.status {
-        StatusCode::NOT_FOUND => (ErrorKind::NotFound, false),
-        StatusCode::FORBIDDEN => (ErrorKind::PermissionDenied, false),
-        StatusCode::INTERNAL_SERVER_ERROR
-        | StatusCode::BAD_GATEWAY
-        | StatusCode::SERVICE_UNAVAILABLE
-        | StatusCode::GATEWAY_TIMEOUT => (ErrorKind::Unexpected, true),
-        _ => (ErrorKind::Unexpected, false),
-    };
+    let (mut kind, mut retryable) = parse_error_status(parts.status);
 
-    let message = match de::from_slice::<GcsErrorResponse>(&bs) {
-        Ok(gcs_err) => format!("{gcs_err:?}"),
-        Err(_) => String::from_utf8_lossy(&bs).into_owned(),
-    };
+    let (message, gcs_err) = de::from_slice::<GcsErrorResponse>(&bs)
+        .map(|gcs_err| (format!("{gcs_err:?}"), Some(gcs_err)))
+        .unwrap_or_else(|_| (String::from_utf8_lossy(&bs).into_owned(), None));
 
-    let mut err = Error::new(kind, &message).with_context("response", format!("{parts:?}"));
+    // All possible error reason: <https://cloud.google.com/storage/docs/json_api/v1/status-codes>
+    if let Some(reason) = gcs_err
+        .as_ref()
+        .and_then(|v| v.error.errors.first())
+        .map(|v| v.reason.as_str())
+    {
+        (kind, retryable) = match reason {
+            // Credentials are not valid, users need to fix their config.
+            "authError" => (ErrorKind::ConfigInvalid, false),
+            "forbidden" | "insufficientPermissions" => (ErrorKind::PermissionDenied, false),
+            _ => (kind, retryable),
+        }
+    }
+
+    let mut err = with_error_response_context(Error::new(kind, &message), &parts);
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
 
     if retryable {
         err = err.set_temporary();
@@ -77,6 +85,12 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use futures::stream;
+    use http::header::RETRY_AFTER;
+    use http::StatusCode;
+
     use super::*;
 
     #[test]
@@ -112,4 +126,60 @@ mod tests {
         assert_eq!(out.error.errors[0].location_type, "header");
         assert_eq!(out.error.errors[0].location, "Authorization");
     }
+
+    #[tokio::test]
+    async fn test_parse_error_rate_limited() -> Result<()> {
+        let bs = bytes::Bytes::from(
+            r#"{"error": {"code": 429, "message": "The rate of change requests to the object is too high."}}"#,
+        );
+        let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+        let resp = Response::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .header(RETRY_AFTER, "10")
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::RateLimited);
+        assert!(err.is_temporary());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(10)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_auth() -> Result<()> {
+        let cases = vec![
+            (
+                StatusCode::UNAUTHORIZED,
+                "authError",
+                ErrorKind::ConfigInvalid,
+            ),
+            (
+                StatusCode::UNAUTHORIZED,
+                "required",
+                ErrorKind::PermissionDenied,
+            ),
+            (
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                ErrorKind::PermissionDenied,
+            ),
+        ];
+
+        for (status, reason, kind) in cases {
+            let bs = bytes::Bytes::from(format!(
+                r#"{{"error": {{"errors": [{{"domain": "global", "reason": "{reason}", "message": "some message"}}], "code": {}, "message": "some message"}}}}"#,
+                status.as_u16()
+            ));
+            let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+            let resp = Response::builder().status(status).body(body).unwrap();
+
+            let err = parse_error(resp).await?;
+            assert_eq!(err.kind(), kind, "reason: {reason}");
+            assert!(!err.is_temporary(), "reason: {reason}");
+        }
+
+        Ok(())
+    }
 }
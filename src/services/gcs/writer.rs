@@ -12,10 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::mem;
+
 use async_trait::async_trait;
+use backon::BackoffBuilder;
+use backon::ExponentialBuilder;
+use bytes::Buf;
 use bytes::Bytes;
+use bytes::BytesMut;
+use http::header::CONTENT_LENGTH;
+use http::header::CONTENT_RANGE;
+use http::header::RANGE;
+use http::HeaderMap;
+use http::Request;
 use http::StatusCode;
+use log::debug;
+use log::warn;
 
+use super::backend::parse_write_reply;
 use super::backend::GcsBackend;
 use super::error::parse_error;
 use crate::ops::OpWrite;
@@ -27,21 +41,44 @@ pub struct GcsWriter {
 
     op: OpWrite,
     path: String,
+
+    upload: Option<ResumableUpload>,
+
+    /// The reply of `write`, will be returned while closing.
+    rp: RpWrite,
 }
 
 impl GcsWriter {
-    pub fn new(backend: GcsBackend, op: OpWrite, path: String) -> Self {
-        GcsWriter { backend, op, path }
+    pub fn new(
+        backend: GcsBackend,
+        op: OpWrite,
+        path: String,
+        upload: Option<ResumableUpload>,
+    ) -> Self {
+        GcsWriter {
+            backend,
+            op,
+            path,
+            upload,
+
+            rp: RpWrite::new(),
+        }
     }
 }
 
 #[async_trait]
 impl oio::Write for GcsWriter {
     async fn write(&mut self, bs: Bytes) -> Result<()> {
+        debug_assert!(
+            self.upload.is_none(),
+            "Writer initiated with resumable upload, but users trying to call write, must be buggy"
+        );
+
         let mut req = self.backend.gcs_insert_object_request(
             &self.path,
             Some(bs.len()),
             self.op.content_type(),
+            self.op.if_not_exists(),
             AsyncBody::Bytes(bs),
         )?;
 
@@ -56,7 +93,8 @@ impl oio::Write for GcsWriter {
 
         match status {
             StatusCode::CREATED | StatusCode::OK => {
-                resp.into_body().consume().await?;
+                let bs = resp.into_body().bytes().await?;
+                self.rp = parse_write_reply(&bs)?;
                 Ok(())
             }
             _ => Err(parse_error(resp).await?),
@@ -64,15 +102,606 @@ impl oio::Write for GcsWriter {
     }
 
     async fn append(&mut self, bs: Bytes) -> Result<()> {
-        let _ = bs;
+        let upload = self.upload.as_mut().expect(
+            "Writer doesn't have resumable upload, but users trying to call append, must be buggy",
+        );
+
+        upload.append(bs).await
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        match self.upload.as_mut() {
+            Some(upload) => upload.finish().await,
+            None => Ok(mem::take(&mut self.rp)),
+        }
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        match self.upload.as_mut() {
+            Some(upload) => upload.abort().await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// Status of a resumable upload session.
+#[derive(Debug, Clone)]
+enum UploadStatus {
+    /// The session is still active, carries the size of persisted bytes.
+    Incomplete(u64),
+    /// The object has been created, carries the etag and generation of it.
+    Finished(RpWrite),
+}
+
+/// ResumableUpload uploads appended bytes into a [resumable upload](https://cloud.google.com/storage/docs/performing-resumable-uploads)
+/// session.
+///
+/// - Appended bytes will be buffered and uploaded in chunks of `chunk_size`.
+/// - Chunks failed with temporary errors will be retried from the offset
+///   that the session has persisted.
+/// - A new session will be cancelled if it's dropped before finished, to
+///   avoid leaving incomplete uploads behind. Sessions given by `resume`
+///   are explicitly kept by users, so they will be kept while dropping and
+///   could be resumed again later (even by another process).
+///
+/// The session uri is the authentication of following requests, so they
+/// don't need to be signed.
+pub struct ResumableUpload {
+    client: HttpClient,
+    location: String,
+    chunk_size: usize,
+
+    /// Bytes that not persisted by the session yet.
+    buf: BytesMut,
+    /// Size of persisted bytes, also the offset of `buf` in the object.
+    offset: u64,
+    /// The session has been finished or cancelled.
+    closed: bool,
+    /// Keep the session instead of cancelling it while dropping.
+    keep_on_drop: bool,
+    /// The reply returned by the finished session.
+    rp: RpWrite,
+}
+
+impl ResumableUpload {
+    /// Create a new upload on the session uri returned by initiating.
+    ///
+    /// `chunk_size` must be a multiple of 256KiB.
+    pub fn new(client: HttpClient, location: String, chunk_size: usize) -> Self {
+        ResumableUpload {
+            client,
+            location,
+            chunk_size,
 
-        Err(Error::new(
-            ErrorKind::Unsupported,
-            "output writer doesn't support append",
-        ))
+            buf: BytesMut::new(),
+            offset: 0,
+            closed: false,
+            keep_on_drop: false,
+            rp: RpWrite::new(),
+        }
     }
 
-    async fn close(&mut self) -> Result<()> {
+    /// Resume an existing session from the size of bytes it has persisted.
+    ///
+    /// Returns [`ErrorKind::AlreadyExists`] if the session has been finished
+    /// which means the object has been created.
+    pub async fn resume(client: HttpClient, location: String, chunk_size: usize) -> Result<Self> {
+        let mut upload = ResumableUpload::new(client, location, chunk_size);
+        upload.keep_on_drop = true;
+
+        match upload.query_status().await? {
+            UploadStatus::Incomplete(persisted) => {
+                upload.offset = persisted;
+                Ok(upload)
+            }
+            UploadStatus::Finished(_) => {
+                upload.closed = true;
+                Err(Error::new(
+                    ErrorKind::AlreadyExists,
+                    "resumable upload session has been finished",
+                ))
+            }
+        }
+    }
+
+    /// The session uri of this upload.
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    /// Size of bytes that have been persisted by the session.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.buf.extend_from_slice(&bs);
+
+        while self.buf.len() >= self.chunk_size {
+            self.upload(false).await?;
+        }
         Ok(())
     }
+
+    async fn finish(&mut self) -> Result<RpWrite> {
+        while !self.closed {
+            let offset = self.offset;
+            self.upload(true).await?;
+
+            if !self.closed && self.offset == offset {
+                return Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "resumable upload session doesn't make progress while finishing",
+                )
+                .with_context("offset", offset.to_string()));
+            }
+        }
+        Ok(mem::take(&mut self.rp))
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+
+        self.buf.clear();
+        let resp = self
+            .client
+            .send_async(gcs_cancel_upload_request(&self.location)?)
+            .await?;
+        self.closed = true;
+
+        match resp.status().as_u16() {
+            // GCS returns code 499 if the session has been cancelled, while
+            // emulators like fake-gcs-server return 204.
+            499 | 204 => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Upload the leading chunk of buffered bytes, or all of them as the
+    /// last chunk if `is_last` is true.
+    ///
+    /// Errors returned here are persistent, retrying the whole `append`
+    /// will upload buffered bytes twice.
+    async fn upload(&mut self, is_last: bool) -> Result<()> {
+        let mut backoff = ExponentialBuilder::default().build();
+        let mut query = false;
+
+        loop {
+            let res = if query {
+                self.query_status().await
+            } else {
+                self.put_chunk(is_last).await
+            };
+
+            match res {
+                Ok(UploadStatus::Finished(rp)) => {
+                    self.buf.clear();
+                    self.closed = true;
+                    self.rp = rp;
+                    return Ok(());
+                }
+                Ok(UploadStatus::Incomplete(persisted)) => {
+                    self.advance(persisted)?;
+
+                    if !query || (!is_last && self.buf.len() < self.chunk_size) {
+                        return Ok(());
+                    }
+                    // Resume from the persisted offset.
+                    query = false;
+                }
+                Err(err) if err.is_temporary() => match backoff.next() {
+                    None => return Err(err.set_persistent()),
+                    Some(dur) => {
+                        warn!(
+                            target: "opendal::service",
+                            "resumable upload at offset {} -> retry after {}s: error={:?}",
+                            self.offset, dur.as_secs_f64(), err
+                        );
+                        tokio::time::sleep(dur).await;
+                        // Chunk could be partially persisted, query the
+                        // status of session to know where to resume.
+                        query = true;
+                    }
+                },
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Drop the persisted bytes from buffer.
+    fn advance(&mut self, persisted: u64) -> Result<()> {
+        let end = self.offset + self.buf.len() as u64;
+        if persisted < self.offset || persisted > end {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "persisted size of resumable upload is out of range",
+            )
+            .with_context("persisted", persisted.to_string())
+            .with_context("offset", self.offset.to_string())
+            .with_context("end", end.to_string()));
+        }
+
+        self.buf.advance((persisted - self.offset) as usize);
+        self.offset = persisted;
+        Ok(())
+    }
+
+    async fn put_chunk(&self, is_last: bool) -> Result<UploadStatus> {
+        let size = if is_last {
+            self.buf.len()
+        } else {
+            self.chunk_size
+        };
+        let bs = Bytes::copy_from_slice(&self.buf[..size]);
+
+        let total = if is_last {
+            (self.offset + size as u64).to_string()
+        } else {
+            "*".to_string()
+        };
+        let range = if size == 0 {
+            format!("bytes */{total}")
+        } else {
+            format!(
+                "bytes {}-{}/{total}",
+                self.offset,
+                self.offset + size as u64 - 1
+            )
+        };
+
+        let req = Request::put(&self.location)
+            .header(CONTENT_LENGTH, size)
+            .header(CONTENT_RANGE, range)
+            .body(AsyncBody::Bytes(bs))
+            .map_err(new_request_build_error)?;
+
+        self.send(req).await
+    }
+
+    /// ref: https://cloud.google.com/storage/docs/performing-resumable-uploads#status-check
+    async fn query_status(&self) -> Result<UploadStatus> {
+        let req = Request::put(&self.location)
+            .header(CONTENT_LENGTH, 0)
+            .header(CONTENT_RANGE, "bytes */*")
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send(req).await
+    }
+
+    async fn send(&self, req: Request<AsyncBody>) -> Result<UploadStatus> {
+        let resp = self.client.send_async(req).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK | StatusCode::CREATED => {
+                let bs = resp.into_body().bytes().await?;
+                Ok(UploadStatus::Finished(parse_write_reply(&bs)?))
+            }
+            // GCS uses `308 Resume Incomplete` for active sessions.
+            StatusCode::PERMANENT_REDIRECT => {
+                let persisted = parse_persisted_size(resp.headers())?;
+                resp.into_body().consume().await?;
+                Ok(UploadStatus::Incomplete(persisted))
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+}
+
+impl Drop for ResumableUpload {
+    fn drop(&mut self) {
+        if self.closed || self.keep_on_drop {
+            return;
+        }
+
+        // Cancel the unfinished session to avoid leaving incomplete uploads.
+        let client = self.client.clone();
+        let location = self.location.clone();
+        if let Ok(rt) = tokio::runtime::Handle::try_current() {
+            rt.spawn(async move {
+                let res = match gcs_cancel_upload_request(&location) {
+                    Ok(req) => client.send_async(req).await.map(|_| ()),
+                    Err(err) => Err(err),
+                };
+                if let Err(err) = res {
+                    debug!("cancel resumable upload {location} failed: {err:?}");
+                }
+            });
+        }
+    }
+}
+
+/// ref: https://cloud.google.com/storage/docs/performing-resumable-uploads#cancel-upload
+pub(super) fn gcs_cancel_upload_request(location: &str) -> Result<Request<AsyncBody>> {
+    Request::delete(location)
+        .header(CONTENT_LENGTH, 0)
+        .body(AsyncBody::Empty)
+        .map_err(new_request_build_error)
+}
+
+/// Parse the size of persisted bytes from `Range` header like `bytes=0-42`.
+///
+/// No `Range` header means no bytes have been persisted.
+fn parse_persisted_size(headers: &HeaderMap) -> Result<u64> {
+    let v = match headers.get(RANGE) {
+        None => return Ok(0),
+        Some(v) => v.to_str().map_err(|e| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value is not valid utf-8 string",
+            )
+            .with_operation("gcs::parse_persisted_size")
+            .set_source(e)
+        })?,
+    };
+
+    v.strip_prefix("bytes=0-")
+        .and_then(|end| end.parse::<u64>().ok())
+        .map(|end| end + 1)
+        .ok_or_else(|| {
+            Error::new(ErrorKind::Unexpected, "range header is invalid")
+                .with_operation("gcs::parse_persisted_size")
+                .with_context("range", v)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    const CHUNK_SIZE: usize = 256 * 1024;
+
+    #[test]
+    fn test_parse_persisted_size() {
+        let cases = vec![
+            ("no range", None, Some(0)),
+            ("range", Some("bytes=0-262143"), Some(262144)),
+            ("invalid", Some("bytes=1-2"), None),
+        ];
+
+        for (name, range, expected) in cases {
+            let mut headers = HeaderMap::new();
+            if let Some(range) = range {
+                headers.insert(RANGE, range.parse().unwrap());
+            }
+
+            assert_eq!(parse_persisted_size(&headers).ok(), expected, "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_resume_after_503() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes 0-262143/*"))
+            .respond_with(ResponseTemplate::new(308).insert_header("range", "bytes=0-262143"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // The second chunk fails once with 503 ...
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes 262144-524287/*"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // ... and the session status shows nothing of it has been persisted ...
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes */*"))
+            .respond_with(ResponseTemplate::new(308).insert_header("range", "bytes=0-262143"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // ... so it's uploaded again.
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes 262144-524287/*"))
+            .respond_with(ResponseTemplate::new(308).insert_header("range", "bytes=0-524287"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes 524288-524387/524388"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"name":"file","etag":"CKWasoTgyPkCEAE=","generation":"1660563214863653"}"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut upload = ResumableUpload::new(
+            HttpClient::new().unwrap(),
+            format!("{}/session", mock_server.uri()),
+            CHUNK_SIZE,
+        );
+
+        upload
+            .append(Bytes::from(vec![1; CHUNK_SIZE + 100]))
+            .await
+            .expect("append must succeed");
+        upload
+            .append(Bytes::from(vec![2; CHUNK_SIZE]))
+            .await
+            .expect("append must succeed");
+        let rp = upload.finish().await.expect("finish must succeed");
+        assert!(upload.closed);
+        assert_eq!(rp.etag(), Some("CKWasoTgyPkCEAE="));
+        assert_eq!(rp.version(), Some("1660563214863653"));
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_abort() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        // wiremock can't respond with non-standard code 499.
+        Mock::given(method("DELETE"))
+            .and(path("/session"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut upload = ResumableUpload::new(
+            HttpClient::new().unwrap(),
+            format!("{}/session", mock_server.uri()),
+            CHUNK_SIZE,
+        );
+
+        upload
+            .append(Bytes::from(vec![1; 100]))
+            .await
+            .expect("append must succeed");
+        upload.abort().await.expect("abort must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_cancel_on_drop() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/session"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut upload = ResumableUpload::new(
+            HttpClient::new().unwrap(),
+            format!("{}/session", mock_server.uri()),
+            CHUNK_SIZE,
+        );
+        upload
+            .append(Bytes::from(vec![1; 100]))
+            .await
+            .expect("append must succeed");
+        drop(upload);
+
+        // The session is cancelled in background.
+        for _ in 0..50 {
+            let reqs = mock_server.received_requests().await.unwrap_or_default();
+            if reqs
+                .iter()
+                .any(|req| req.method == wiremock::http::Method::Delete)
+            {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_keep_resumed_on_drop() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes */*"))
+            .respond_with(ResponseTemplate::new(308))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let upload = ResumableUpload::resume(
+            HttpClient::new().unwrap(),
+            format!("{}/session", mock_server.uri()),
+            CHUNK_SIZE,
+        )
+        .await
+        .expect("resume must succeed");
+        drop(upload);
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_resume() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        // The session has persisted the first chunk before restarting.
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes */*"))
+            .respond_with(ResponseTemplate::new(308).insert_header("range", "bytes=0-262143"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes 262144-262243/262244"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut upload = ResumableUpload::resume(
+            HttpClient::new().unwrap(),
+            format!("{}/session", mock_server.uri()),
+            CHUNK_SIZE,
+        )
+        .await
+        .expect("resume must succeed");
+        assert_eq!(upload.offset(), CHUNK_SIZE as u64);
+
+        upload
+            .append(Bytes::from(vec![1; 100]))
+            .await
+            .expect("append must succeed");
+        upload.finish().await.expect("finish must succeed");
+        assert!(upload.closed);
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_resume_finished() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes */*"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let err = ResumableUpload::resume(
+            HttpClient::new().unwrap(),
+            format!("{}/session", mock_server.uri()),
+            CHUNK_SIZE,
+        )
+        .await
+        .err()
+        .expect("resume finished session must fail");
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+    }
 }
@@ -0,0 +1,1044 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::header;
+use http::Request;
+use http::Response;
+use http::StatusCode;
+use log::debug;
+use log::warn;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde_json::json;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use super::error::parse_error;
+use super::pager::GdrivePager;
+use super::writer::GdriveWriter;
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+const DEFAULT_GDRIVE_ENDPOINT: &str = "https://www.googleapis.com";
+const DEFAULT_GDRIVE_TOKEN_ENDPOINT: &str = "https://oauth2.googleapis.com/token";
+
+/// The mime type of folders in Google Drive.
+pub(super) const GDRIVE_FOLDER_MIME_TYPE: &str = "application/vnd.google-apps.folder";
+/// The fields of files that returned by Drive API.
+pub(super) const GDRIVE_FILE_FIELDS: &str = "id,name,mimeType,size,modifiedTime,md5Checksum";
+
+/// The chunk size of resumable upload must be a multiple of 256KiB.
+const WRITE_CHUNK_SIZE_ALIGNMENT: usize = 256 * 1024;
+/// The default chunk size of resumable upload.
+const DEFAULT_WRITE_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// [Google Drive](https://www.google.com/drive/) services support.
+///
+/// # Capabilities
+///
+/// This service can be used to:
+///
+/// - [x] read
+/// - [x] write
+/// - [x] list
+/// - [ ] ~~scan~~
+/// - [ ] ~~presign~~
+/// - [ ] blocking
+///
+/// # Configuration
+///
+/// - `root`: Set the work directory for backend
+/// - `endpoint`: Customizable endpoint setting, default to `https://www.googleapis.com`
+/// - `access_token`: Set the OAuth2 access token
+/// - `refresh_token`: Set the OAuth2 refresh token
+/// - `client_id`: Set the OAuth2 client id that used to refresh token
+/// - `client_secret`: Set the OAuth2 client secret that used to refresh token
+/// - `write_chunk_size`: Chunk size of resumable uploads, default to 8MiB
+///
+/// You can refer to [`GdriveBuilder`]'s docs for more information
+///
+/// # Notes
+///
+/// Google Drive identifies files by ids instead of paths. Paths will be
+/// resolved by walking folder names from `root` and the resolved ids will
+/// be cached by the backend. Changes made by others (like renaming a folder)
+/// could make cached ids stale, entries that not found any more will be
+/// evicted from the cache.
+///
+/// Drive allows files with the same name in one folder. The most recently
+/// modified one will be used in this case, and a warning will be logged.
+///
+/// # Example
+///
+/// ## Via Builder
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::services::Gdrive;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     // create backend builder
+///     let mut builder = Gdrive::default();
+///
+///     builder
+///         .root("/path/to/dir")
+///         .refresh_token("refresh_token")
+///         .client_id("client_id")
+///         .client_secret("client_secret");
+///
+///     let op: Operator = Operator::new(builder)?.finish();
+///     Ok(())
+/// }
+/// ```
+#[derive(Default)]
+pub struct GdriveBuilder {
+    root: Option<String>,
+    endpoint: Option<String>,
+
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+
+    write_chunk_size: Option<usize>,
+    http_client: Option<HttpClient>,
+}
+
+impl Debug for GdriveBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("Builder");
+
+        ds.field("root", &self.root)
+            .field("endpoint", &self.endpoint)
+            .field("client_id", &self.client_id)
+            .field("write_chunk_size", &self.write_chunk_size);
+        if self.access_token.is_some() {
+            ds.field("access_token", &"<redacted>");
+        }
+        if self.refresh_token.is_some() {
+            ds.field("refresh_token", &"<redacted>");
+        }
+        if self.client_secret.is_some() {
+            ds.field("client_secret", &"<redacted>");
+        }
+        ds.finish()
+    }
+}
+
+impl GdriveBuilder {
+    /// Set root path of Google Drive.
+    ///
+    /// The root is relative to `My Drive`.
+    pub fn root(&mut self, root: &str) -> &mut Self {
+        if !root.is_empty() {
+            self.root = Some(root.to_string())
+        }
+
+        self
+    }
+
+    /// Set the endpoint of Drive API.
+    ///
+    /// Default to `https://www.googleapis.com`.
+    pub fn endpoint(&mut self, endpoint: &str) -> &mut Self {
+        if !endpoint.is_empty() {
+            self.endpoint = Some(endpoint.to_string())
+        }
+
+        self
+    }
+
+    /// Set the OAuth2 access token.
+    ///
+    /// The token will be used as is. Please set `refresh_token` instead for
+    /// long running services since access tokens are expired in an hour.
+    pub fn access_token(&mut self, token: &str) -> &mut Self {
+        if !token.is_empty() {
+            self.access_token = Some(token.to_string())
+        }
+
+        self
+    }
+
+    /// Set the OAuth2 refresh token.
+    ///
+    /// `client_id` and `client_secret` are required to refresh the access
+    /// token.
+    pub fn refresh_token(&mut self, token: &str) -> &mut Self {
+        if !token.is_empty() {
+            self.refresh_token = Some(token.to_string())
+        }
+
+        self
+    }
+
+    /// Set the OAuth2 client id.
+    pub fn client_id(&mut self, client_id: &str) -> &mut Self {
+        if !client_id.is_empty() {
+            self.client_id = Some(client_id.to_string())
+        }
+
+        self
+    }
+
+    /// Set the OAuth2 client secret.
+    pub fn client_secret(&mut self, client_secret: &str) -> &mut Self {
+        if !client_secret.is_empty() {
+            self.client_secret = Some(client_secret.to_string())
+        }
+
+        self
+    }
+
+    /// Set the chunk size of resumable uploads that used by writers.
+    ///
+    /// Appended bytes will be buffered and uploaded in chunks of this size.
+    /// The size must be a multiple of 256KiB, building will fail with
+    /// [`ErrorKind::ConfigInvalid`] for others.
+    ///
+    /// Default to 8MiB.
+    pub fn write_chunk_size(&mut self, size: usize) -> &mut Self {
+        self.write_chunk_size = Some(size);
+        self
+    }
+
+    /// Specify the http client that used by this service.
+    ///
+    /// # Notes
+    ///
+    /// This API is part of OpenDAL's Raw API. `HttpClient` could be changed
+    /// during minor updates.
+    pub fn http_client(&mut self, client: HttpClient) -> &mut Self {
+        self.http_client = Some(client);
+        self
+    }
+}
+
+impl Builder for GdriveBuilder {
+    const SCHEME: Scheme = Scheme::Gdrive;
+    type Accessor = GdriveBackend;
+
+    fn from_map(map: HashMap<String, String>) -> Self {
+        let mut builder = GdriveBuilder::default();
+
+        map.get("root").map(|v| builder.root(v));
+        map.get("endpoint").map(|v| builder.endpoint(v));
+        map.get("access_token").map(|v| builder.access_token(v));
+        map.get("refresh_token").map(|v| builder.refresh_token(v));
+        map.get("client_id").map(|v| builder.client_id(v));
+        map.get("client_secret").map(|v| builder.client_secret(v));
+        map.get("write_chunk_size")
+            .and_then(|v| v.parse::<usize>().ok())
+            .map(|v| builder.write_chunk_size(v));
+
+        builder
+    }
+
+    fn build(&mut self) -> Result<Self::Accessor> {
+        debug!("backend build started: {:?}", self);
+
+        let root = normalize_root(&self.root.take().unwrap_or_default());
+        debug!("backend use root {}", root);
+
+        let endpoint = self
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| DEFAULT_GDRIVE_ENDPOINT.to_string());
+        parse_endpoint(&endpoint).map_err(|e| {
+            e.with_operation("Builder::build")
+                .with_context("service", Scheme::Gdrive)
+        })?;
+        let endpoint = endpoint.trim_end_matches('/').to_string();
+        debug!("backend use endpoint: {endpoint}");
+
+        let write_chunk_size = self.write_chunk_size.unwrap_or(DEFAULT_WRITE_CHUNK_SIZE);
+        if write_chunk_size == 0 || write_chunk_size % WRITE_CHUNK_SIZE_ALIGNMENT != 0 {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "write_chunk_size must be a multiple of 256KiB",
+            )
+            .with_operation("Builder::build")
+            .with_context("service", Scheme::Gdrive)
+            .with_context("write_chunk_size", write_chunk_size.to_string()));
+        }
+
+        let client = if let Some(client) = self.http_client.take() {
+            client
+        } else {
+            HttpClient::new().map_err(|err| {
+                err.with_operation("Builder::build")
+                    .with_context("service", Scheme::Gdrive)
+            })?
+        };
+
+        let token_loader = match (
+            &self.access_token,
+            &self.refresh_token,
+            &self.client_id,
+            &self.client_secret,
+        ) {
+            // Access token given by user is used as is and never expired.
+            (Some(access_token), _, _, _) => OAuth2TokenLoader::new(client.clone(), access_token),
+            (None, Some(refresh_token), Some(client_id), Some(client_secret)) => {
+                OAuth2TokenLoader::with_refresh_token(
+                    client.clone(),
+                    DEFAULT_GDRIVE_TOKEN_ENDPOINT,
+                    refresh_token,
+                    client_id,
+                    Some(client_secret),
+                )
+            }
+            (None, Some(_), _, _) => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "client_id and client_secret are required to refresh token",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Gdrive))
+            }
+            (None, None, _, _) => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "access_token or refresh_token is required",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Gdrive))
+            }
+        };
+
+        debug!("backend build finished: {:?}", self);
+        Ok(GdriveBackend {
+            root,
+            endpoint,
+            client,
+            write_chunk_size,
+
+            token_loader,
+            path_cache: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+}
+
+/// File resource returned by Drive API.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(super) struct GdriveFile {
+    pub id: String,
+    pub name: String,
+    pub mime_type: String,
+    pub size: Option<String>,
+    pub modified_time: Option<String>,
+    pub md5_checksum: Option<String>,
+}
+
+impl GdriveFile {
+    pub fn is_folder(&self) -> bool {
+        self.mime_type == GDRIVE_FOLDER_MIME_TYPE
+    }
+
+    pub fn parse_into_metadata(&self) -> Result<Metadata> {
+        if self.is_folder() {
+            return Ok(Metadata::new(EntryMode::DIR));
+        }
+
+        let mut meta = Metadata::new(EntryMode::FILE);
+        if !self.mime_type.is_empty() {
+            meta.set_content_type(&self.mime_type);
+        }
+        if let Some(v) = &self.size {
+            let size = v.parse::<u64>().map_err(|e| {
+                Error::new(ErrorKind::Unexpected, "parse file size of gdrive").set_source(e)
+            })?;
+            meta.set_content_length(size);
+        }
+        if let Some(v) = &self.modified_time {
+            let dt = OffsetDateTime::parse(v, &Rfc3339).map_err(|e| {
+                Error::new(ErrorKind::Unexpected, "parse modified time as rfc3339").set_source(e)
+            })?;
+            meta.set_last_modified(dt);
+        }
+        if let Some(v) = &self.md5_checksum {
+            meta.set_content_md5(v);
+        }
+        Ok(meta)
+    }
+}
+
+/// List of files returned by Drive API.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(super) struct GdriveFileList {
+    pub next_page_token: Option<String>,
+    pub files: Vec<GdriveFile>,
+}
+
+/// Backend for Google Drive services.
+#[derive(Clone)]
+pub struct GdriveBackend {
+    root: String,
+    endpoint: String,
+    pub(super) client: HttpClient,
+    pub(super) write_chunk_size: usize,
+
+    token_loader: OAuth2TokenLoader,
+    /// Cache of resolved ids, keyed by absolute paths without tailing `/`.
+    path_cache: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl Debug for GdriveBackend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Backend")
+            .field("root", &self.root)
+            .field("endpoint", &self.endpoint)
+            .field("write_chunk_size", &self.write_chunk_size)
+            .field("client", &self.client)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Accessor for GdriveBackend {
+    type Reader = IncomingAsyncBody;
+    type BlockingReader = ();
+    type Writer = GdriveWriter;
+    type BlockingWriter = ();
+    type Pager = GdrivePager;
+    type BlockingPager = ();
+
+    fn info(&self) -> AccessorInfo {
+        use AccessorCapability::*;
+        use AccessorHint::*;
+
+        let mut am = AccessorInfo::default();
+        am.set_scheme(Scheme::Gdrive)
+            .set_root(&self.root)
+            .set_capabilities(Read | Write | List)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                write_with_content_type: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_delimiter: true,
+                ..Default::default()
+            })
+            .set_hints(ReadStreamable);
+        am
+    }
+
+    async fn create(&self, path: &str, _: OpCreate) -> Result<RpCreate> {
+        let abs_path = build_abs_path(&self.root, path);
+
+        self.gdrive_ensure_folder(&abs_path).await?;
+
+        Ok(RpCreate::default())
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let abs_path = build_abs_path(&self.root, path);
+        let id = self.gdrive_resolve_or_not_found(&abs_path).await?;
+
+        let range = args.range();
+        let resp = self.gdrive_get_file_content(&id, range).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
+            }
+            _ => {
+                let err = parse_error(resp).await?;
+                if err.kind() == ErrorKind::NotFound {
+                    self.evict_path(&abs_path);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let abs_path = build_abs_path(&self.root, path);
+
+        let location = self
+            .gdrive_initiate_resumable_upload(&abs_path, args.content_type())
+            .await?;
+
+        Ok((
+            RpWrite::default(),
+            GdriveWriter::new(self.clone(), abs_path, location),
+        ))
+    }
+
+    async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+        // Stat root always returns a DIR.
+        if path == "/" {
+            return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
+        }
+
+        let abs_path = build_abs_path(&self.root, path);
+        let id = self.gdrive_resolve_or_not_found(&abs_path).await?;
+
+        let resp = self.gdrive_get_file(&id).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                let file: GdriveFile =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+                // Folders and files are told apart by the tailing `/`.
+                if file.is_folder() != path.ends_with('/') {
+                    return Err(Error::new(ErrorKind::NotFound, "file is not found")
+                        .with_context("path", path));
+                }
+
+                file.parse_into_metadata().map(RpStat::new)
+            }
+            _ => {
+                let err = parse_error(resp).await?;
+                if err.kind() == ErrorKind::NotFound {
+                    self.evict_path(&abs_path);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        let abs_path = build_abs_path(&self.root, path);
+        let id = match self.gdrive_resolve(&abs_path).await? {
+            Some(id) => id,
+            None => return Ok(RpDelete::default()),
+        };
+
+        let resp = self.gdrive_delete_file(&id).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => {
+                resp.into_body().consume().await?;
+                self.evict_path(&abs_path);
+                Ok(RpDelete::default())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn list(&self, path: &str, _: OpList) -> Result<(RpList, Self::Pager)> {
+        let abs_path = build_abs_path(&self.root, path);
+        let id = self.gdrive_resolve(&abs_path).await?;
+
+        Ok((
+            RpList::default(),
+            GdrivePager::new(self.clone(), &abs_path, path, id),
+        ))
+    }
+}
+
+impl GdriveBackend {
+    /// Remove given path and all paths under it from cache.
+    pub(super) fn evict_path(&self, abs_path: &str) {
+        let key = abs_path.trim_end_matches('/');
+        let prefix = format!("{key}/");
+
+        self.path_cache
+            .lock()
+            .retain(|k, _| k != key && !k.starts_with(&prefix));
+    }
+
+    /// Record the id of given path into cache.
+    pub(super) fn cache_path(&self, abs_path: &str, id: &str) {
+        self.path_cache
+            .lock()
+            .insert(abs_path.trim_end_matches('/').to_string(), id.to_string());
+    }
+
+    /// Resolve the id of given path by walking folder names from root.
+    ///
+    /// Returns `None` if any part of the path is not found.
+    pub(super) async fn gdrive_resolve(&self, abs_path: &str) -> Result<Option<String>> {
+        let abs_path = abs_path.trim_end_matches('/');
+        let mut parent_id = "root".to_string();
+        if abs_path.is_empty() {
+            return Ok(Some(parent_id));
+        }
+
+        let names: Vec<&str> = abs_path.split('/').collect();
+        for idx in 0..names.len() {
+            let key = names[..=idx].join("/");
+
+            let cached = self.path_cache.lock().get(&key).cloned();
+            parent_id = match cached {
+                Some(id) => id,
+                None => {
+                    let file = match self.gdrive_find_child(&parent_id, names[idx]).await? {
+                        Some(file) => file,
+                        None => return Ok(None),
+                    };
+                    // Only folders could have children.
+                    if idx + 1 < names.len() && !file.is_folder() {
+                        return Ok(None);
+                    }
+
+                    self.cache_path(&key, &file.id);
+                    file.id
+                }
+            };
+        }
+
+        Ok(Some(parent_id))
+    }
+
+    async fn gdrive_resolve_or_not_found(&self, abs_path: &str) -> Result<String> {
+        self.gdrive_resolve(abs_path).await?.ok_or_else(|| {
+            Error::new(ErrorKind::NotFound, "file is not found").with_context("path", abs_path)
+        })
+    }
+
+    /// Create all folders of given path if they are not exist, returns the
+    /// id of the last one.
+    pub(super) async fn gdrive_ensure_folder(&self, abs_path: &str) -> Result<String> {
+        let abs_path = abs_path.trim_end_matches('/');
+        let mut parent_id = "root".to_string();
+        if abs_path.is_empty() {
+            return Ok(parent_id);
+        }
+
+        let names: Vec<&str> = abs_path.split('/').collect();
+        for idx in 0..names.len() {
+            let key = names[..=idx].join("/");
+
+            let cached = self.path_cache.lock().get(&key).cloned();
+            parent_id = match cached {
+                Some(id) => id,
+                None => {
+                    let file = match self.gdrive_find_child(&parent_id, names[idx]).await? {
+                        Some(file) if file.is_folder() => file,
+                        Some(_) => {
+                            return Err(Error::new(
+                                ErrorKind::NotADirectory,
+                                "parent of the path is not a folder",
+                            )
+                            .with_context("path", &key))
+                        }
+                        None => self.gdrive_create_folder(&parent_id, names[idx]).await?,
+                    };
+
+                    self.cache_path(&key, &file.id);
+                    file.id
+                }
+            };
+        }
+
+        Ok(parent_id)
+    }
+
+    /// Find the child of given folder by name.
+    ///
+    /// Drive allows files with the same name in one folder, the most
+    /// recently modified one will be returned.
+    async fn gdrive_find_child(&self, parent_id: &str, name: &str) -> Result<Option<GdriveFile>> {
+        let query = format!(
+            "name = '{}' and '{}' in parents and trashed = false",
+            escape_query_value(name),
+            escape_query_value(parent_id)
+        );
+        let url = format!(
+            "{}/drive/v3/files?q={}&orderBy={}&fields={}",
+            self.endpoint,
+            percent_encode_path(&query),
+            percent_encode_path("modifiedTime desc"),
+            percent_encode_path(&format!("files({GDRIVE_FILE_FIELDS})")),
+        );
+
+        let resp = self
+            .gdrive_send(Request::get(&url), AsyncBody::Empty)
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                let list: GdriveFileList =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+                if list.files.len() > 1 {
+                    warn!(
+                        "gdrive folder {parent_id} has {} files named {name}, the most recently modified one will be used",
+                        list.files.len()
+                    );
+                }
+                Ok(list.files.into_iter().next())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn gdrive_create_folder(&self, parent_id: &str, name: &str) -> Result<GdriveFile> {
+        let url = format!(
+            "{}/drive/v3/files?fields={}",
+            self.endpoint,
+            percent_encode_path(GDRIVE_FILE_FIELDS)
+        );
+        let body = json!({
+            "name": name,
+            "mimeType": GDRIVE_FOLDER_MIME_TYPE,
+            "parents": [parent_id],
+        })
+        .to_string();
+
+        let req = Request::post(&url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_LENGTH, body.len());
+        let resp = self
+            .gdrive_send(req, AsyncBody::Bytes(Bytes::from(body)))
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                serde_json::from_slice(&bs).map_err(new_json_deserialize_error)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn gdrive_get_file(&self, id: &str) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!(
+            "{}/drive/v3/files/{}?fields={}",
+            self.endpoint,
+            percent_encode_path(id),
+            percent_encode_path(GDRIVE_FILE_FIELDS)
+        );
+
+        self.gdrive_send(Request::get(&url), AsyncBody::Empty).await
+    }
+
+    async fn gdrive_get_file_content(
+        &self,
+        id: &str,
+        range: BytesRange,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!(
+            "{}/drive/v3/files/{}?alt=media",
+            self.endpoint,
+            percent_encode_path(id)
+        );
+
+        let mut req = Request::get(&url);
+        if !range.is_full() {
+            req = req.header(header::RANGE, range.to_header());
+        }
+
+        self.gdrive_send(req, AsyncBody::Empty).await
+    }
+
+    async fn gdrive_delete_file(&self, id: &str) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!(
+            "{}/drive/v3/files/{}",
+            self.endpoint,
+            percent_encode_path(id)
+        );
+
+        self.gdrive_send(Request::delete(&url), AsyncBody::Empty)
+            .await
+    }
+
+    /// List the children of given folder.
+    pub(super) async fn gdrive_list_children(
+        &self,
+        parent_id: &str,
+        page_token: &str,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let query = format!(
+            "'{}' in parents and trashed = false",
+            escape_query_value(parent_id)
+        );
+        let mut url = format!(
+            "{}/drive/v3/files?q={}&orderBy={}&fields={}",
+            self.endpoint,
+            percent_encode_path(&query),
+            percent_encode_path("name,modifiedTime desc"),
+            percent_encode_path(&format!("nextPageToken,files({GDRIVE_FILE_FIELDS})")),
+        );
+        if !page_token.is_empty() {
+            url.push_str("&pageToken=");
+            url.push_str(&percent_encode_path(page_token));
+        }
+
+        self.gdrive_send(Request::get(&url), AsyncBody::Empty).await
+    }
+
+    /// Initiate a resumable upload session for given path, returns the
+    /// session uri.
+    ///
+    /// Missing parent folders will be created, and existing file will be
+    /// updated in place so that the file id and sharing settings are kept.
+    ///
+    /// ref: <https://developers.google.com/drive/api/guides/manage-uploads#resumable>
+    async fn gdrive_initiate_resumable_upload(
+        &self,
+        abs_path: &str,
+        content_type: Option<&str>,
+    ) -> Result<String> {
+        let (parent, name) = match abs_path.rfind('/') {
+            Some(idx) => (&abs_path[..idx], &abs_path[idx + 1..]),
+            None => ("", abs_path),
+        };
+
+        let parent_id = self.gdrive_ensure_folder(parent).await?;
+        let existing = self
+            .gdrive_find_child(&parent_id, name)
+            .await?
+            .filter(|file| !file.is_folder());
+
+        let (req, body) = match existing {
+            Some(file) => {
+                let url = format!(
+                    "{}/upload/drive/v3/files/{}?uploadType=resumable",
+                    self.endpoint,
+                    percent_encode_path(&file.id)
+                );
+                (Request::patch(&url), json!({}))
+            }
+            None => {
+                let url = format!(
+                    "{}/upload/drive/v3/files?uploadType=resumable",
+                    self.endpoint
+                );
+                (
+                    Request::post(&url),
+                    json!({
+                        "name": name,
+                        "parents": [parent_id],
+                    }),
+                )
+            }
+        };
+
+        let body = body.to_string();
+        let mut req = req
+            .header(header::CONTENT_TYPE, "application/json; charset=UTF-8")
+            .header(header::CONTENT_LENGTH, body.len());
+        if let Some(mime) = content_type {
+            req = req.header("X-Upload-Content-Type", mime);
+        }
+
+        let resp = self
+            .gdrive_send(req, AsyncBody::Bytes(Bytes::from(body)))
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let location = parse_location(resp.headers())?
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::Unexpected,
+                            "initiate resumable upload response has no location",
+                        )
+                        .with_context("path", abs_path)
+                    })?
+                    .to_string();
+                resp.into_body().consume().await?;
+                Ok(location)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn gdrive_send(
+        &self,
+        req: http::request::Builder,
+        body: AsyncBody,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let req = req
+            .header(
+                header::AUTHORIZATION,
+                self.token_loader.authorization().await?,
+            )
+            .body(body)
+            .map_err(new_request_build_error)?;
+
+        self.client.send_async(req).await
+    }
+}
+
+/// Escape the value that used in the `q` of Drive API.
+///
+/// ref: <https://developers.google.com/drive/api/guides/ref-search-terms>
+fn escape_query_value(v: &str) -> String {
+    v.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    fn new_backend(endpoint: &str) -> GdriveBackend {
+        let mut builder = GdriveBuilder::default();
+        builder.endpoint(endpoint).access_token("token");
+        builder.build().expect("build must succeed")
+    }
+
+    async fn mount_child(server: &MockServer, parent: &str, name: &str, files: serde_json::Value) {
+        Mock::given(method("GET"))
+            .and(path("/drive/v3/files"))
+            .and(query_param(
+                "q",
+                format!("name = '{name}' and '{parent}' in parents and trashed = false").as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "files": files })))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[test]
+    fn test_build_without_token() {
+        let mut builder = GdriveBuilder::default();
+        let err = builder.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let mut builder = GdriveBuilder::default();
+        builder.refresh_token("refresh_token");
+        let err = builder.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[test]
+    fn test_escape_query_value() {
+        assert_eq!(escape_query_value("abc"), "abc");
+        assert_eq!(escape_query_value("it's"), "it\\'s");
+        assert_eq!(escape_query_value("a\\b"), "a\\\\b");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_path() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        mount_child(
+            &server,
+            "root",
+            "a",
+            json!([{ "id": "id_a", "name": "a", "mimeType": GDRIVE_FOLDER_MIME_TYPE }]),
+        )
+        .await;
+        // Duplicated names, the first one (most recently modified) wins.
+        mount_child(
+            &server,
+            "id_a",
+            "b",
+            json!([
+                { "id": "id_b_new", "name": "b", "mimeType": "text/plain" },
+                { "id": "id_b_old", "name": "b", "mimeType": "text/plain" },
+            ]),
+        )
+        .await;
+        mount_child(&server, "id_a", "c", json!([])).await;
+
+        let backend = new_backend(&server.uri());
+
+        assert_eq!(
+            backend.gdrive_resolve("a/b").await.unwrap().as_deref(),
+            Some("id_b_new")
+        );
+        // Served from cache without sending requests.
+        assert_eq!(
+            backend.gdrive_resolve("a/b").await.unwrap().as_deref(),
+            Some("id_b_new")
+        );
+        assert_eq!(
+            backend.gdrive_resolve("a/").await.unwrap().as_deref(),
+            Some("id_a")
+        );
+        // Walking from the cached parent.
+        assert_eq!(backend.gdrive_resolve("a/c").await.unwrap(), None);
+        assert_eq!(
+            backend.gdrive_resolve("").await.unwrap().as_deref(),
+            Some("root")
+        );
+
+        backend.evict_path("a/");
+        assert!(backend.path_cache.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_initiate_resumable_upload_creates_parents() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        mount_child(&server, "root", "a", json!([])).await;
+        mount_child(&server, "id_a", "file", json!([])).await;
+        Mock::given(method("POST"))
+            .and(path("/drive/v3/files"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "id_a",
+                "name": "a",
+                "mimeType": GDRIVE_FOLDER_MIME_TYPE,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/upload/drive/v3/files"))
+            .and(query_param("uploadType", "resumable"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("location", format!("{}/session", server.uri()).as_str()),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server.uri());
+
+        let location = backend
+            .gdrive_initiate_resumable_upload("a/file", None)
+            .await
+            .expect("initiate must succeed");
+        assert_eq!(location, format!("{}/session", server.uri()));
+        assert_eq!(
+            backend.path_cache.lock().get("a").map(|v| v.as_str()),
+            Some("id_a")
+        );
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use http::Response;
+use http::StatusCode;
+use serde::Deserialize;
+use serde_json::de;
+
+use crate::raw::*;
+use crate::Error;
+use crate::ErrorKind;
+use crate::Result;
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct GdriveErrorResponse {
+    error: GdriveError,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct GdriveError {
+    code: usize,
+    message: String,
+    errors: Vec<GdriveErrorDetail>,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct GdriveErrorDetail {
+    domain: String,
+    reason: String,
+    message: String,
+}
+
+/// Parse error response into Error.
+pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
+    let (parts, body) = resp.into_parts();
+    let bs = body.bytes().await?;
+
+    let gdrive_err = de::from_slice::<GdriveErrorResponse>(&bs).ok();
+
+    // Drive returns `403 Forbidden` for both permission errors and rate
+    // limits, they can only be told apart by the reason.
+    let rate_limited = gdrive_err
+        .as_ref()
+        .map(|v| {
+            v.error
+                .errors
+                .iter()
+                .any(|e| e.reason == "rateLimitExceeded" || e.reason == "userRateLimitExceeded")
+        })
+        .unwrap_or_default();
+
+    let (kind, retryable) = match parts.status {
+        StatusCode::FORBIDDEN if rate_limited => (ErrorKind::RateLimited, true),
+        v => parse_error_status(v),
+    };
+
+    let message = match gdrive_err {
+        Some(gdrive_err) => format!("{gdrive_err:?}"),
+        None => String::from_utf8_lossy(&bs).into_owned(),
+    };
+
+    let mut err = with_error_response_context(Error::new(kind, &message), &parts);
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
+
+    if retryable {
+        err = err.set_temporary();
+    }
+
+    Ok(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_parse_error_rate_limited() -> Result<()> {
+        let bs = bytes::Bytes::from(
+            r#"
+{
+ "error": {
+  "errors": [
+   {
+    "domain": "usageLimits",
+    "reason": "userRateLimitExceeded",
+    "message": "User Rate Limit Exceeded"
+   }
+  ],
+  "code": 403,
+  "message": "User Rate Limit Exceeded"
+ }
+}
+"#,
+        );
+        let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+        let resp = Response::builder()
+            .status(StatusCode::FORBIDDEN)
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::RateLimited);
+        assert!(err.is_temporary());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_forbidden() -> Result<()> {
+        let bs = bytes::Bytes::from(
+            r#"
+{
+ "error": {
+  "errors": [
+   {
+    "domain": "global",
+    "reason": "insufficientFilePermissions",
+    "message": "The user does not have sufficient permissions for this file."
+   }
+  ],
+  "code": 403,
+  "message": "The user does not have sufficient permissions for this file."
+ }
+}
+"#,
+        );
+        let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+        let resp = Response::builder()
+            .status(StatusCode::FORBIDDEN)
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        assert!(!err.is_temporary());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use log::warn;
+
+use super::backend::GdriveBackend;
+use super::backend::GdriveFileList;
+use super::error::parse_error;
+use crate::raw::*;
+use crate::*;
+
+/// GdrivePager lists the children of a folder page by page.
+pub struct GdrivePager {
+    backend: GdriveBackend,
+    /// The absolute path of listing folder, used to cache ids of children.
+    abs_path: String,
+    path: String,
+    /// `None` means the folder is not exist.
+    folder_id: Option<String>,
+
+    /// Names that have been returned, used to skip duplicated names.
+    names: HashSet<String>,
+    page_token: String,
+    done: bool,
+}
+
+impl GdrivePager {
+    pub fn new(
+        backend: GdriveBackend,
+        abs_path: &str,
+        path: &str,
+        folder_id: Option<String>,
+    ) -> Self {
+        Self {
+            backend,
+            abs_path: abs_path.to_string(),
+            path: path.to_string(),
+            folder_id,
+
+            names: HashSet::new(),
+            page_token: "".to_string(),
+            done: false,
+        }
+    }
+}
+
+#[async_trait]
+impl oio::Page for GdrivePager {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let folder_id = match (&self.folder_id, self.done) {
+            (Some(id), false) => id,
+            _ => return Ok(None),
+        };
+
+        let resp = self
+            .backend
+            .gdrive_list_children(folder_id, &self.page_token)
+            .await?;
+
+        if !resp.status().is_success() {
+            return Err(parse_error(resp).await?);
+        }
+        let bs = resp.into_body().bytes().await?;
+
+        let output: GdriveFileList =
+            serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+        match output.next_page_token {
+            Some(token) => self.page_token = token,
+            None => self.done = true,
+        }
+
+        let mut entries = Vec::with_capacity(output.files.len());
+        for file in output.files {
+            let name = if file.is_folder() {
+                format!("{}/", file.name)
+            } else {
+                file.name.clone()
+            };
+
+            // Files are listed in order of modified time desc for the same
+            // name, keep the first one just like resolving path.
+            if !self.names.insert(name.clone()) {
+                warn!(
+                    "gdrive folder {} has duplicated files named {}, the most recently modified one will be used",
+                    self.abs_path, file.name
+                );
+                continue;
+            }
+
+            let path = if self.path == "/" {
+                name
+            } else {
+                format!("{}{}", self.path, name)
+            };
+            self.backend
+                .cache_path(&format!("{}{}", self.abs_path, file.name), &file.id);
+
+            entries.push(oio::Entry::new(&path, file.parse_into_metadata()?));
+        }
+
+        Ok(Some(entries))
+    }
+}
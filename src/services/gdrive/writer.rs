@@ -0,0 +1,376 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use bytes::Buf;
+use bytes::Bytes;
+use bytes::BytesMut;
+use http::header::CONTENT_LENGTH;
+use http::header::CONTENT_RANGE;
+use http::header::RANGE;
+use http::HeaderMap;
+use http::Request;
+use http::StatusCode;
+
+use super::backend::GdriveBackend;
+use super::backend::GdriveFile;
+use super::error::parse_error;
+use crate::raw::*;
+use crate::*;
+
+/// GdriveWriter uploads bytes into a [resumable upload](https://developers.google.com/drive/api/guides/manage-uploads#resumable)
+/// session.
+///
+/// Appended bytes will be buffered and uploaded in chunks of
+/// `write_chunk_size`. The session uri is the authentication of following
+/// requests, so they don't need to carry the access token.
+pub struct GdriveWriter {
+    backend: GdriveBackend,
+    /// The absolute path of the file, used to cache the id after uploaded.
+    path: String,
+    location: String,
+
+    /// Bytes that not persisted by the session yet.
+    buf: BytesMut,
+    /// Size of persisted bytes, also the offset of `buf` in the file.
+    offset: u64,
+    /// The session has been finished or cancelled.
+    closed: bool,
+}
+
+impl GdriveWriter {
+    pub fn new(backend: GdriveBackend, path: String, location: String) -> Self {
+        GdriveWriter {
+            backend,
+            path,
+            location,
+
+            buf: BytesMut::new(),
+            offset: 0,
+            closed: false,
+        }
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        while !self.closed {
+            let offset = self.offset;
+            self.upload(true).await?;
+
+            if !self.closed && self.offset == offset {
+                return Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "resumable upload session doesn't make progress while finishing",
+                )
+                .with_context("offset", offset.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Upload the leading chunk of buffered bytes, or all of them as the
+    /// last chunk if `is_last` is true.
+    async fn upload(&mut self, is_last: bool) -> Result<()> {
+        let chunk_size = self.backend.write_chunk_size;
+        let size = if is_last { self.buf.len() } else { chunk_size };
+        let bs = Bytes::copy_from_slice(&self.buf[..size]);
+
+        let total = if is_last {
+            (self.offset + size as u64).to_string()
+        } else {
+            "*".to_string()
+        };
+        let range = if size == 0 {
+            format!("bytes */{total}")
+        } else {
+            format!(
+                "bytes {}-{}/{total}",
+                self.offset,
+                self.offset + size as u64 - 1
+            )
+        };
+
+        let req = Request::put(&self.location)
+            .header(CONTENT_LENGTH, size)
+            .header(CONTENT_RANGE, range)
+            .body(AsyncBody::Bytes(bs))
+            .map_err(new_request_build_error)?;
+
+        let resp = self.backend.client.send_async(req).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK | StatusCode::CREATED => {
+                let bs = resp.into_body().bytes().await?;
+                let file: GdriveFile =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+                self.backend.cache_path(&self.path, &file.id);
+
+                self.buf.clear();
+                self.closed = true;
+                Ok(())
+            }
+            // Drive uses `308 Resume Incomplete` for active sessions.
+            StatusCode::PERMANENT_REDIRECT => {
+                let persisted = parse_persisted_size(resp.headers())?;
+                resp.into_body().consume().await?;
+                self.advance(persisted)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Drop the persisted bytes from buffer.
+    fn advance(&mut self, persisted: u64) -> Result<()> {
+        let end = self.offset + self.buf.len() as u64;
+        if persisted < self.offset || persisted > end {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "persisted size of resumable upload is out of range",
+            )
+            .with_context("persisted", persisted.to_string())
+            .with_context("offset", self.offset.to_string())
+            .with_context("end", end.to_string()));
+        }
+
+        self.buf.advance((persisted - self.offset) as usize);
+        self.offset = persisted;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl oio::Write for GdriveWriter {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.buf.extend_from_slice(&bs);
+        self.finish().await
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.buf.extend_from_slice(&bs);
+
+        while self.buf.len() >= self.backend.write_chunk_size {
+            self.upload(false).await?;
+        }
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        self.finish().await?;
+        Ok(RpWrite::new())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+
+        self.buf.clear();
+        let req = Request::delete(&self.location)
+            .header(CONTENT_LENGTH, 0)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+        let resp = self.backend.client.send_async(req).await?;
+        self.closed = true;
+
+        match resp.status().as_u16() {
+            // Drive returns code 499 if the session has been cancelled.
+            499 | 204 => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+}
+
+/// Parse the size of persisted bytes from `Range` header like `bytes=0-42`.
+///
+/// No `Range` header means no bytes have been persisted.
+fn parse_persisted_size(headers: &HeaderMap) -> Result<u64> {
+    let v = match headers.get(RANGE) {
+        None => return Ok(0),
+        Some(v) => v.to_str().map_err(|e| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value is not valid utf-8 string",
+            )
+            .with_operation("gdrive::parse_persisted_size")
+            .set_source(e)
+        })?,
+    };
+
+    v.strip_prefix("bytes=0-")
+        .and_then(|end| end.parse::<u64>().ok())
+        .map(|end| end + 1)
+        .ok_or_else(|| {
+            Error::new(ErrorKind::Unexpected, "range header is invalid")
+                .with_operation("gdrive::parse_persisted_size")
+                .with_context("range", v)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use oio::Write;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::services::gdrive::backend::GDRIVE_FOLDER_MIME_TYPE;
+    use crate::services::Gdrive;
+
+    const CHUNK_SIZE: usize = 256 * 1024;
+
+    fn new_writer(server: &MockServer) -> GdriveWriter {
+        let mut builder = Gdrive::default();
+        builder
+            .endpoint(&server.uri())
+            .access_token("token")
+            .write_chunk_size(CHUNK_SIZE);
+        let backend = builder.build().expect("build must succeed");
+
+        GdriveWriter::new(
+            backend,
+            "dir/file".to_string(),
+            format!("{}/session", server.uri()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_in_chunks() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        // The session only persisted part of the first chunk ...
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes 0-262143/*"))
+            .respond_with(ResponseTemplate::new(308).insert_header("range", "bytes=0-99"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // ... so the next chunk starts from the persisted offset.
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes 100-262243/*"))
+            .respond_with(ResponseTemplate::new(308).insert_header("range", "bytes=0-262243"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes 262244-524387/*"))
+            .respond_with(ResponseTemplate::new(308).insert_header("range", "bytes=0-524387"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // All bytes have been persisted, close with the total size.
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes */524388"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "id_file",
+                "name": "file",
+                "mimeType": "application/octet-stream",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // Only the parent folder needs to be looked up, the id of file has
+        // been cached after uploaded.
+        Mock::given(method("GET"))
+            .and(path("/drive/v3/files"))
+            .and(query_param(
+                "q",
+                "name = 'dir' and 'root' in parents and trashed = false",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "files": [{
+                    "id": "id_dir",
+                    "name": "dir",
+                    "mimeType": GDRIVE_FOLDER_MIME_TYPE,
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut w = new_writer(&server);
+        w.append(Bytes::from(vec![1; CHUNK_SIZE + 100]))
+            .await
+            .expect("append must succeed");
+        w.append(Bytes::from(vec![2; CHUNK_SIZE]))
+            .await
+            .expect("append must succeed");
+        w.close().await.expect("close must succeed");
+        assert!(w.closed);
+        assert_eq!(
+            w.backend
+                .gdrive_resolve("dir/file")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("id_file")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_write_once() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes 0-99/100"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "id_file",
+                "name": "file",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut w = new_writer(&server);
+        w.write(Bytes::from(vec![1; 100]))
+            .await
+            .expect("write must succeed");
+        assert!(w.closed);
+    }
+
+    #[tokio::test]
+    async fn test_resumable_upload_abort() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/session"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut w = new_writer(&server);
+        w.append(Bytes::from(vec![1; 100]))
+            .await
+            .expect("append must succeed");
+        w.abort().await.expect("abort must succeed");
+    }
+}
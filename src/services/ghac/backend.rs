@@ -57,6 +57,11 @@ const GITHUB_API_URL: &str = "GITHUB_API_URL";
 const GITHUB_REPOSITORY: &str = "GITHUB_REPOSITORY";
 /// The github API version that used by OpenDAL.
 const GITHUB_API_VERSION: &str = "2022-11-28";
+/// The default github api url.
+const DEFAULT_GITHUB_API_URL: &str = "https://api.github.com";
+/// The default size of chunks uploaded in one `PATCH` request, which is
+/// the same as `actions/cache`.
+const DEFAULT_UPLOAD_CHUNK_SIZE: usize = 32 * 1024 * 1024;
 
 /// Github Action Cache Services support.
 ///
@@ -112,6 +117,12 @@ const GITHUB_API_VERSION: &str = "2022-11-28";
 /// # Configuration
 ///
 /// - `root`: Set the work dir for backend.
+/// - `version`: Set the version of cache, entries with different versions
+///   never hit each other.
+/// - `api_url`: Set the github api url, useful for GitHub Enterprise Server.
+/// - `restore_keys`: Set the key prefixes to fall back while reading,
+///   separated by `,`.
+/// - `upload_chunk_size`: Set the size of chunks in one upload request.
 ///
 /// Refer to [`GhacBuilder`]'s public API docs for more information.
 ///
@@ -144,6 +155,9 @@ const GITHUB_API_VERSION: &str = "2022-11-28";
 pub struct GhacBuilder {
     root: Option<String>,
     version: Option<String>,
+    api_url: Option<String>,
+    restore_keys: Vec<String>,
+    upload_chunk_size: Option<usize>,
     enable_create_simulation: bool,
 
     http_client: Option<HttpClient>,
@@ -164,6 +178,10 @@ impl GhacBuilder {
     /// The version is the unique value that provides namespacing.
     /// It's better to make sure this value is only used by this backend.
     ///
+    /// The version works like a salt of keys: caches written with one
+    /// version will never be read with another. Changing it is an easy
+    /// way to invalidate all existing caches.
+    ///
     /// If not set, we will use `opendal` as default.
     pub fn version(&mut self, version: &str) -> &mut Self {
         if !version.is_empty() {
@@ -173,6 +191,49 @@ impl GhacBuilder {
         self
     }
 
+    /// set the github api url, which is used by `delete`.
+    ///
+    /// Please set this to the API url of GitHub Enterprise Server like
+    /// `https://github.example.com/api/v3` if needed.
+    ///
+    /// If not set, we will use `GITHUB_API_URL` env and fallback to
+    /// `https://api.github.com`.
+    pub fn api_url(&mut self, api_url: &str) -> &mut Self {
+        if !api_url.is_empty() {
+            self.api_url = Some(api_url.trim_end_matches('/').to_string())
+        }
+
+        self
+    }
+
+    /// set the restore keys that used while reading.
+    ///
+    /// Just like `restore-keys` of `actions/cache`, if the exact key of
+    /// path is missed, the most recent cache whose key starts with the
+    /// first restore key will be read, then the second one and so on.
+    /// Restore keys are relative to root.
+    ///
+    /// Only `read` falls back, `stat` always checks the exact key.
+    pub fn restore_keys(&mut self, keys: &[&str]) -> &mut Self {
+        self.restore_keys = keys
+            .iter()
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string())
+            .collect();
+
+        self
+    }
+
+    /// set the size of chunks uploaded in one `PATCH` request.
+    ///
+    /// Written data will be buffered until a chunk is full.
+    ///
+    /// If not set, we will use `32 MiB` as default.
+    pub fn upload_chunk_size(&mut self, size: usize) -> &mut Self {
+        self.upload_chunk_size = Some(size);
+        self
+    }
+
     /// Enable create simulation for ghac service.
     ///
     /// ghac service doesn't support create empty files. By enabling
@@ -206,6 +267,11 @@ impl Builder for GhacBuilder {
 
         map.get("root").map(|v| builder.root(v));
         map.get("version").map(|v| builder.version(v));
+        map.get("api_url").map(|v| builder.api_url(v));
+        map.get("restore_keys")
+            .map(|v| builder.restore_keys(&v.split(',').map(|v| v.trim()).collect::<Vec<_>>()));
+        map.get("upload_chunk_size")
+            .map(|v| v.parse::<usize>().map(|v| builder.upload_chunk_size(v)));
         map.get("enable_create_simulation")
             .filter(|v| *v == "on" || *v == "true")
             .map(|_| builder.enable_create_simulation());
@@ -228,6 +294,15 @@ impl Builder for GhacBuilder {
             })?
         };
 
+        let upload_chunk_size = self.upload_chunk_size.unwrap_or(DEFAULT_UPLOAD_CHUNK_SIZE);
+        if upload_chunk_size == 0 {
+            return Err(
+                Error::new(ErrorKind::ConfigInvalid, "upload_chunk_size must not be 0")
+                    .with_operation("Builder::build")
+                    .with_context("service", Scheme::Ghac),
+            );
+        }
+
         let backend = GhacBackend {
             root,
             enable_create_simulation: self.enable_create_simulation,
@@ -252,9 +327,14 @@ impl Builder for GhacBuilder {
                 .version
                 .clone()
                 .unwrap_or_else(|| "opendal".to_string()),
+            restore_keys: self.restore_keys.clone(),
+            upload_chunk_size,
 
-            api_url: env::var(GITHUB_API_URL)
-                .unwrap_or_else(|_| "https://api.github.com".to_string()),
+            api_url: self
+                .api_url
+                .clone()
+                .or_else(|| env::var(GITHUB_API_URL).ok())
+                .unwrap_or_else(|| DEFAULT_GITHUB_API_URL.to_string()),
             api_token: env::var(GITHUB_TOKEN).unwrap_or_default(),
             repo: env::var(GITHUB_REPOSITORY).unwrap_or_default(),
 
@@ -275,6 +355,8 @@ pub struct GhacBackend {
     cache_url: String,
     catch_token: String,
     version: String,
+    restore_keys: Vec<String>,
+    pub upload_chunk_size: usize,
 
     api_url: String,
     api_token: String,
@@ -298,6 +380,16 @@ impl Accessor for GhacBackend {
             .set_root(&self.root)
             .set_name(&self.version)
             .set_capabilities(AccessorCapability::Read | AccessorCapability::Write)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                write: true,
+                create_dir: true,
+                delete: true,
+                ..Default::default()
+            })
             .set_hints(AccessorHint::ReadStreamable);
         am
     }
@@ -323,8 +415,9 @@ impl Accessor for GhacBackend {
             let reserve_resp: GhacReserveResponse =
                 serde_json::from_slice(&slc).map_err(new_json_deserialize_error)?;
             reserve_resp.cache_id
-        } else if resp.status().as_u16() == StatusCode::CONFLICT {
+        } else if is_reserve_conflict(resp.status()) {
             // If the file is already exist, just return Ok.
+            resp.into_body().consume().await?;
             return Ok(RpCreate::default());
         } else {
             return Err(parse_error(resp)
@@ -334,7 +427,7 @@ impl Accessor for GhacBackend {
 
         // Write only 1 byte to allow create.
         let req = self
-            .ghac_upload(cache_id, 1, AsyncBody::Bytes(Bytes::from_static(&[0])))
+            .ghac_upload(cache_id, 0, 1, AsyncBody::Bytes(Bytes::from_static(&[0])))
             .await?;
 
         let resp = self.client.send_async(req).await?;
@@ -361,7 +454,8 @@ impl Accessor for GhacBackend {
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        let req = self.ghac_query(path).await?;
+        let range = args.range();
+        let req = self.ghac_query(path, true).await?;
 
         let resp = self.client.send_async(req).await?;
 
@@ -374,14 +468,14 @@ impl Accessor for GhacBackend {
             return Err(parse_error(resp).await?);
         };
 
-        let req = self.ghac_get_location(&location, args.range()).await?;
+        let req = self.ghac_get_location(&location, range).await?;
         let resp = self.client.send_async(req).await?;
 
         let status = resp.status();
         match status {
             StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
-                let meta = parse_into_metadata(path, resp.headers())?;
-                Ok((RpRead::with_metadata(meta), resp.into_body()))
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
             }
             _ => Err(parse_error(resp).await?),
         }
@@ -404,6 +498,14 @@ impl Accessor for GhacBackend {
             let reserve_resp: GhacReserveResponse =
                 serde_json::from_slice(&slc).map_err(new_json_deserialize_error)?;
             reserve_resp.cache_id
+        } else if is_reserve_conflict(resp.status()) {
+            resp.into_body().consume().await?;
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                "cache already exists or is being written",
+            )
+            .with_operation("Backend::ghac_reserve")
+            .with_context("path", path));
         } else {
             return Err(parse_error(resp)
                 .await
@@ -419,7 +521,7 @@ impl Accessor for GhacBackend {
             return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
         }
 
-        let req = self.ghac_query(path).await?;
+        let req = self.ghac_query(path, false).await?;
 
         let resp = self.client.send_async(req).await?;
 
@@ -473,13 +575,25 @@ impl Accessor for GhacBackend {
 }
 
 impl GhacBackend {
-    async fn ghac_query(&self, path: &str) -> Result<Request<AsyncBody>> {
-        let p = build_abs_path(&self.root, path);
+    /// Query the cache entry of path.
+    ///
+    /// The restore keys will be appended to the keys list if `restore` is
+    /// true, and cache service will try them in order if the exact key
+    /// is missed.
+    async fn ghac_query(&self, path: &str, restore: bool) -> Result<Request<AsyncBody>> {
+        let mut keys = vec![percent_encode_path(&build_abs_path(&self.root, path))];
+        if restore {
+            keys.extend(
+                self.restore_keys
+                    .iter()
+                    .map(|k| percent_encode_path(&build_abs_path(&self.root, k))),
+            );
+        }
 
         let url = format!(
             "{}{CACHE_URL_BASE}/cache?keys={}&version={}",
             self.cache_url,
-            percent_encode_path(&p),
+            keys.join(","),
             self.version
         );
 
@@ -549,9 +663,12 @@ impl GhacBackend {
         Ok(req)
     }
 
+    /// Upload a chunk of `size` bytes starting at `offset` for reserved
+    /// cache.
     pub async fn ghac_upload(
         &self,
         cache_id: i64,
+        offset: u64,
         size: u64,
         body: AsyncBody,
     ) -> Result<Request<AsyncBody>> {
@@ -565,7 +682,7 @@ impl GhacBackend {
         req = req.header(
             CONTENT_RANGE,
             BytesContentRange::default()
-                .with_range(0, size - 1)
+                .with_range(offset, offset + size - 1)
                 .to_header(),
         );
 
@@ -616,6 +733,13 @@ impl GhacBackend {
     }
 }
 
+/// Cache service returns `409 Conflict` (and `404 Not Found` for some
+/// versions) while reserving a key that already exists or is being
+/// written by others.
+fn is_reserve_conflict(status: StatusCode) -> bool {
+    status == StatusCode::CONFLICT || status == StatusCode::NOT_FOUND
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GhacQueryResponse {
@@ -641,3 +765,174 @@ struct GhacReserveResponse {
 struct GhacCommitRequest {
     size: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::body_json;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    fn new_backend(uri: &str) -> GhacBackend {
+        GhacBackend {
+            root: "/cache/".to_string(),
+            enable_create_simulation: false,
+            cache_url: format!("{uri}/"),
+            catch_token: "token".to_string(),
+            version: "opendal".to_string(),
+            restore_keys: vec![],
+            upload_chunk_size: 4,
+            api_url: uri.to_string(),
+            api_token: String::new(),
+            repo: String::new(),
+            client: HttpClient::new().unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_reserve_conflict() {
+        let _ = env_logger::try_init();
+
+        for status in [409, 404] {
+            let mock_server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/_apis/artifactcache/caches"))
+                .and(body_json(serde_json::json!({
+                    "key": "cache/file",
+                    "version": "opendal",
+                })))
+                .respond_with(ResponseTemplate::new(status))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+
+            let backend = new_backend(&mock_server.uri());
+            let err = backend
+                .write("file", OpWrite::new())
+                .await
+                .err()
+                .expect("reserve conflict must fail");
+            assert_eq!(err.kind(), ErrorKind::AlreadyExists, "status {status}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_chunked_upload() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/_apis/artifactcache/caches"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "cacheId": 42,
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        for (range, len) in [
+            ("bytes 0-3/*", "4"),
+            ("bytes 4-7/*", "4"),
+            ("bytes 8-11/*", "4"),
+            ("bytes 12-12/*", "1"),
+        ] {
+            Mock::given(method("PATCH"))
+                .and(path("/_apis/artifactcache/caches/42"))
+                .and(header("content-range", range))
+                .and(header("content-length", len))
+                .respond_with(ResponseTemplate::new(204))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/_apis/artifactcache/caches/42"))
+            .and(body_json(serde_json::json!({ "size": 13 })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_backend(&mock_server.uri());
+        let (_, mut w) = backend
+            .write("file", OpWrite::new())
+            .await
+            .expect("reserve must succeed");
+
+        // Small writes are buffered, large writes are split into chunks.
+        oio::Write::write(&mut w, Bytes::from("ab")).await.unwrap();
+        oio::Write::write(&mut w, Bytes::from("cdefghij"))
+            .await
+            .unwrap();
+        oio::Write::write(&mut w, Bytes::from("klm")).await.unwrap();
+        oio::Write::close(&mut w).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_with_restore_keys() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/_apis/artifactcache/cache"))
+            .and(query_param(
+                "keys",
+                "cache/build-abc,cache/build-,cache/rel-",
+            ))
+            .and(query_param("version", "opendal"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "cacheKey": "cache/build-old",
+                "archiveLocation": format!("{}/archive", mock_server.uri()),
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/archive"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("old build"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        // Stat only checks the exact key.
+        Mock::given(method("GET"))
+            .and(path("/_apis/artifactcache/cache"))
+            .and(query_param("keys", "cache/build-abc"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut backend = new_backend(&mock_server.uri());
+        backend.restore_keys = vec!["build-".to_string(), "rel-".to_string()];
+
+        let (_, body) = backend
+            .read("build-abc", OpRead::new())
+            .await
+            .expect("read must fall back to restore keys");
+        assert_eq!(body.bytes().await.unwrap(), Bytes::from("old build"));
+
+        let err = backend
+            .stat("build-abc", OpStat::new())
+            .await
+            .expect_err("stat must not fall back");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_from_map() {
+        let mut map = HashMap::new();
+        map.insert("api_url".to_string(), "https://ghes/api/v3/".to_string());
+        map.insert("restore_keys".to_string(), "a-, b-,".to_string());
+        map.insert("upload_chunk_size".to_string(), "1024".to_string());
+
+        let builder = GhacBuilder::from_map(map);
+        assert_eq!(builder.api_url.as_deref(), Some("https://ghes/api/v3"));
+        assert_eq!(builder.restore_keys, vec!["a-", "b-"]);
+        assert_eq!(builder.upload_chunk_size, Some(1024));
+    }
+}
@@ -12,18 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::min;
+
 use async_trait::async_trait;
+use bytes::Buf;
 use bytes::Bytes;
+use bytes::BytesMut;
 
 use super::backend::GhacBackend;
 use super::error::parse_error;
 use crate::raw::*;
 use crate::*;
 
+/// GhacWriter uploads data in chunks of `upload_chunk_size` via `PATCH`
+/// and commits the cache while closing.
 pub struct GhacWriter {
     backend: GhacBackend,
 
     cache_id: i64,
+    /// Data that is not enough for a chunk.
+    buf: BytesMut,
+    /// The size that has been uploaded.
     size: u64,
 }
 
@@ -32,18 +41,16 @@ impl GhacWriter {
         GhacWriter {
             backend,
             cache_id,
+            buf: BytesMut::new(),
             size: 0,
         }
     }
-}
 
-#[async_trait]
-impl oio::Write for GhacWriter {
-    async fn write(&mut self, bs: Bytes) -> Result<()> {
+    async fn upload(&mut self, bs: Bytes) -> Result<()> {
         let size = bs.len() as u64;
         let req = self
             .backend
-            .ghac_upload(self.cache_id, size, AsyncBody::Bytes(bs))
+            .ghac_upload(self.cache_id, self.size, size, AsyncBody::Bytes(bs))
             .await?;
 
         let resp = self.backend.client.send_async(req).await?;
@@ -58,6 +65,35 @@ impl oio::Write for GhacWriter {
                 .map(|err| err.with_operation("Backend::ghac_upload"))?)
         }
     }
+}
+
+#[async_trait]
+impl oio::Write for GhacWriter {
+    async fn write(&mut self, mut bs: Bytes) -> Result<()> {
+        let chunk_size = self.backend.upload_chunk_size;
+
+        // Fill the buffered chunk first.
+        if !self.buf.is_empty() {
+            let n = min(chunk_size - self.buf.len(), bs.len());
+            self.buf.extend_from_slice(&bs[..n]);
+            bs.advance(n);
+
+            if self.buf.len() < chunk_size {
+                return Ok(());
+            }
+            let chunk = self.buf.split().freeze();
+            self.upload(chunk).await?;
+        }
+
+        // Upload full chunks without copy.
+        while bs.len() >= chunk_size {
+            let chunk = bs.split_to(chunk_size);
+            self.upload(chunk).await?;
+        }
+
+        self.buf.extend_from_slice(&bs);
+        Ok(())
+    }
 
     async fn append(&mut self, bs: Bytes) -> Result<()> {
         let _ = bs;
@@ -68,17 +104,30 @@ impl oio::Write for GhacWriter {
         ))
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn close(&mut self) -> Result<RpWrite> {
+        if !self.buf.is_empty() {
+            let chunk = self.buf.split().freeze();
+            self.upload(chunk).await?;
+        }
+
         let req = self.backend.ghac_commit(self.cache_id, self.size).await?;
         let resp = self.backend.client.send_async(req).await?;
 
         if resp.status().is_success() {
             resp.into_body().consume().await?;
-            Ok(())
+            Ok(RpWrite::new())
         } else {
             Err(parse_error(resp)
                 .await
                 .map(|err| err.with_operation("Backend::ghac_commit"))?)
         }
     }
+
+    /// # Notes
+    ///
+    /// Reserved cache will never be visible until committed, so we
+    /// don't need to do anything here.
+    async fn abort(&mut self) -> Result<()> {
+        Ok(())
+    }
 }
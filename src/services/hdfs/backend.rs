@@ -18,12 +18,15 @@ use std::fmt::Debug;
 use std::io;
 use std::io::SeekFrom;
 use std::path::PathBuf;
+use std::process::Command;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use log::debug;
 use time::OffsetDateTime;
+use uuid::Uuid;
 
+use super::error::parse_connect_error;
 use super::error::parse_io_error;
 use super::pager::HdfsPager;
 use super::writer::HdfsWriter;
@@ -58,9 +61,57 @@ use crate::*;
 ///
 /// - `root`: Set the work dir for backend.
 /// - `name_node`: Set the name node for backend.
+/// - `atomic_write_dir`: Set the temp dir for atomic write.
+/// - `user`: Set the user to connect as.
+/// - `kerberos_ticket_cache_path`: Set the kerberos ticket cache to use.
+/// - `kerberos_principal`: Set the kerberos principal to login with keytab.
+/// - `kerberos_keytab`: Set the kerberos keytab to login with.
 ///
 /// Refer to [`HdfsBuilder`]'s public API docs for more information.
 ///
+/// # Kerberos
+///
+/// For clusters secured by kerberos, users can either:
+///
+/// - Set `kerberos_ticket_cache_path` to an existing ticket cache, which is
+///   usually obtained and renewed by `kinit` outside.
+/// - Set both `kerberos_principal` and `kerberos_keytab`, opendal will run
+///   `kinit` to obtain a ticket into `kerberos_ticket_cache_path` (or a new
+///   temp file if not set) while building.
+///
+/// Tickets are not renewed by opendal, please rebuild the operator before
+/// tickets expired.
+///
+/// Authentication failures will be returned as
+/// [`ErrorKind::PermissionDenied`] with the detail in `kerberos` context.
+///
+/// # Short-circuit Read
+///
+/// Short-circuit local reads are configured on the hadoop client side.
+/// Please set `dfs.client.read.shortcircuit` to `true` and
+/// `dfs.domain.socket.path` to the datanode's socket in `hdfs-site.xml`
+/// under `HADOOP_CONF_DIR`, which will be picked up while connecting.
+///
+/// # Atomic Write
+///
+/// If `atomic_write_dir` is set, data will be written into a temp file in
+/// this dir first, and renamed to the target path while closing. So readers
+/// will never see a partial written file, which is required by committers
+/// like Spark's. The dir must be in the same HDFS cluster.
+///
+/// HDFS can't rename over an existing file, so existing target will be
+/// removed right before renaming.
+///
+/// # Append
+///
+/// Writing with [`OpWrite::with_append_existing`] will append data to the
+/// existing file directly, atomic write is not applied in this case.
+///
+/// # Write Options
+///
+/// [`OpWrite::with_replication`] and [`OpWrite::with_block_size`] will be
+/// passed to HDFS while creating files.
+///
 /// # Environment
 ///
 /// HDFS needs some environment set correctly.
@@ -128,6 +179,11 @@ use crate::*;
 pub struct HdfsBuilder {
     root: Option<String>,
     name_node: Option<String>,
+    atomic_write_dir: Option<String>,
+    user: Option<String>,
+    kerberos_ticket_cache_path: Option<String>,
+    kerberos_principal: Option<String>,
+    kerberos_keytab: Option<String>,
 }
 
 impl HdfsBuilder {
@@ -158,6 +214,110 @@ impl HdfsBuilder {
 
         self
     }
+
+    /// Set temp dir for atomic write.
+    ///
+    /// Data will be written into this dir first and renamed to the target
+    /// path while closing.
+    pub fn atomic_write_dir(&mut self, dir: &str) -> &mut Self {
+        self.atomic_write_dir = if dir.is_empty() {
+            None
+        } else {
+            Some(dir.to_string())
+        };
+
+        self
+    }
+
+    /// Set the user to connect to hdfs as.
+    pub fn user(&mut self, user: &str) -> &mut Self {
+        self.user = if user.is_empty() {
+            None
+        } else {
+            Some(user.to_string())
+        };
+
+        self
+    }
+
+    /// Set the kerberos ticket cache path to use while connecting.
+    ///
+    /// If `kerberos_principal` and `kerberos_keytab` are set too, the ticket
+    /// obtained from keytab will be stored into this path.
+    pub fn kerberos_ticket_cache_path(&mut self, path: &str) -> &mut Self {
+        self.kerberos_ticket_cache_path = if path.is_empty() {
+            None
+        } else {
+            Some(path.to_string())
+        };
+
+        self
+    }
+
+    /// Set the kerberos principal to login with `kerberos_keytab`.
+    pub fn kerberos_principal(&mut self, principal: &str) -> &mut Self {
+        self.kerberos_principal = if principal.is_empty() {
+            None
+        } else {
+            Some(principal.to_string())
+        };
+
+        self
+    }
+
+    /// Set the kerberos keytab to login `kerberos_principal` with.
+    pub fn kerberos_keytab(&mut self, keytab: &str) -> &mut Self {
+        self.kerberos_keytab = if keytab.is_empty() {
+            None
+        } else {
+            Some(keytab.to_string())
+        };
+
+        self
+    }
+
+    /// Obtain a kerberos ticket from keytab via `kinit` if principal and
+    /// keytab are set, and return the ticket cache path to use.
+    fn kerberos_login(&self) -> Result<Option<String>> {
+        let (principal, keytab) = match (&self.kerberos_principal, &self.kerberos_keytab) {
+            (Some(principal), Some(keytab)) => (principal, keytab),
+            (None, None) => return Ok(self.kerberos_ticket_cache_path.clone()),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "kerberos principal and keytab must be set together",
+                )
+                .with_context("service", Scheme::Hdfs))
+            }
+        };
+
+        let cache = self.kerberos_ticket_cache_path.clone().unwrap_or_else(|| {
+            std::env::temp_dir()
+                .join(format!("opendal_krb5cc_{}", Uuid::new_v4()))
+                .to_string_lossy()
+                .to_string()
+        });
+        debug!("kerberos login {principal} with keytab {keytab} into {cache}");
+
+        let output = Command::new("kinit")
+            .args(["-kt", keytab, "-c", &cache, principal])
+            .output()
+            .map_err(|e| {
+                Error::new(ErrorKind::Unexpected, "run kinit failed")
+                    .with_context("service", Scheme::Hdfs)
+                    .set_source(e)
+            })?;
+        if !output.status.success() {
+            return Err(
+                Error::new(ErrorKind::PermissionDenied, "kerberos login failed")
+                    .with_context("service", Scheme::Hdfs)
+                    .with_context("principal", principal)
+                    .with_context("kerberos", String::from_utf8_lossy(&output.stderr).trim()),
+            );
+        }
+
+        Ok(Some(cache))
+    }
 }
 
 impl Builder for HdfsBuilder {
@@ -169,6 +329,15 @@ impl Builder for HdfsBuilder {
 
         map.get("root").map(|v| builder.root(v));
         map.get("name_node").map(|v| builder.name_node(v));
+        map.get("atomic_write_dir")
+            .map(|v| builder.atomic_write_dir(v));
+        map.get("user").map(|v| builder.user(v));
+        map.get("kerberos_ticket_cache_path")
+            .map(|v| builder.kerberos_ticket_cache_path(v));
+        map.get("kerberos_principal")
+            .map(|v| builder.kerberos_principal(v));
+        map.get("kerberos_keytab")
+            .map(|v| builder.kerberos_keytab(v));
 
         builder
     }
@@ -187,20 +356,46 @@ impl Builder for HdfsBuilder {
         let root = normalize_root(&self.root.take().unwrap_or_default());
         debug!("backend use root {}", root);
 
-        let client = hdrs::Client::connect(name_node).map_err(parse_io_error)?;
+        let mut builder = hdrs::ClientBuilder::new(name_node);
+        if let Some(user) = &self.user {
+            builder = builder.with_user(user);
+        }
+        if let Some(cache) = self.kerberos_login()? {
+            builder = builder.with_kerberos_ticket_cache_path(&cache);
+        }
+        let client = builder.connect().map_err(parse_connect_error)?;
 
         // Create root dir if not exist.
-        if let Err(e) = client.metadata(&root) {
-            if e.kind() == io::ErrorKind::NotFound {
+        //
+        // This is the first request sent to name node, authentication
+        // failures will be returned here.
+        match client.metadata(&root) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
                 debug!("root {} is not exist, creating now", root);
 
-                client.create_dir(&root).map_err(parse_io_error)?
+                client.create_dir(&root).map_err(parse_connect_error)?
+            }
+            Err(e) => return Err(parse_connect_error(e)),
+            Ok(_) => {}
+        }
+
+        let atomic_write_dir = self.atomic_write_dir.take().map(|v| normalize_root(&v));
+
+        // Create atomic write dir if not exist.
+        if let Some(d) = &atomic_write_dir {
+            if let Err(e) = client.metadata(d) {
+                if e.kind() == io::ErrorKind::NotFound {
+                    debug!("atomic write dir {} is not exist, creating now", d);
+
+                    client.create_dir(d).map_err(parse_io_error)?
+                }
             }
         }
 
         debug!("backend build finished: {:?}", &self);
         Ok(HdfsBackend {
             root,
+            atomic_write_dir,
             client: Arc::new(client),
         })
     }
@@ -210,6 +405,7 @@ impl Builder for HdfsBuilder {
 #[derive(Debug, Clone)]
 pub struct HdfsBackend {
     root: String,
+    atomic_write_dir: Option<String>,
     client: Arc<hdrs::Client>,
 }
 
@@ -217,6 +413,65 @@ pub struct HdfsBackend {
 unsafe impl Send for HdfsBackend {}
 unsafe impl Sync for HdfsBackend {}
 
+impl HdfsBackend {
+    /// Build the target path, the temp path and open options for write.
+    ///
+    /// Returns the temp path if atomic write is enabled, and whether we are
+    /// appending to an existing file.
+    fn prepare_write(
+        &self,
+        path: &str,
+        args: &OpWrite,
+    ) -> Result<(String, Option<String>, bool, hdrs::OpenOptions)> {
+        let p = build_rooted_abs_path(&self.root, path);
+
+        let parent = PathBuf::from(&p)
+            .parent()
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::Unexpected,
+                    "path should have parent but not, it must be malformed",
+                )
+                .with_context("input", &p)
+            })?
+            .to_path_buf();
+
+        self.client
+            .create_dir(&parent.to_string_lossy())
+            .map_err(parse_io_error)?;
+
+        let mut opts = self.client.open_file();
+        opts.write(true);
+
+        // Appending happens on the target file directly.
+        if args.append_existing() {
+            match self.client.metadata(&p) {
+                Ok(_) => {
+                    opts.append(true);
+                    return Ok((p, None, true, opts));
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(parse_io_error(err)),
+            }
+        }
+
+        opts.create(true);
+        if let Some(replication) = args.replication() {
+            opts.with_replication(replication);
+        }
+        if let Some(block_size) = args.block_size() {
+            opts.with_blocksize(block_size);
+        }
+
+        let tmp_path = self
+            .atomic_write_dir
+            .as_ref()
+            .map(|dir| format!("{dir}{}.{}", get_basename(path), Uuid::new_v4()));
+
+        Ok((p, tmp_path, false, opts))
+    }
+}
+
 #[async_trait]
 impl Accessor for HdfsBackend {
     type Reader = oio::into_reader::FdReader<hdrs::AsyncFile>;
@@ -236,6 +491,23 @@ impl Accessor for HdfsBackend {
                     | AccessorCapability::List
                     | AccessorCapability::Blocking,
             )
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_seek: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                write_with_append_existing: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                blocking: true,
+                ..Default::default()
+            })
             .set_hints(AccessorHint::ReadSeekable);
 
         am
@@ -315,34 +587,18 @@ impl Accessor for HdfsBackend {
         Ok((RpRead::new(end - start), r))
     }
 
-    async fn write(&self, path: &str, _: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        let p = build_rooted_abs_path(&self.root, path);
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let (target_path, tmp_path, append, opts) = self.prepare_write(path, &args)?;
 
-        let parent = PathBuf::from(&p)
-            .parent()
-            .ok_or_else(|| {
-                Error::new(
-                    ErrorKind::Unexpected,
-                    "path should have parent but not, it must be malformed",
-                )
-                .with_context("input", &p)
-            })?
-            .to_path_buf();
-
-        self.client
-            .create_dir(&parent.to_string_lossy())
-            .map_err(parse_io_error)?;
-
-        let f = self
-            .client
-            .open_file()
-            .create(true)
-            .write(true)
-            .async_open(&p)
+        let f = opts
+            .async_open(tmp_path.as_ref().unwrap_or(&target_path))
             .await
             .map_err(parse_io_error)?;
 
-        Ok((RpWrite::new(), HdfsWriter::new(f)))
+        Ok((
+            RpWrite::new(),
+            HdfsWriter::new(self.client.clone(), target_path, tmp_path, append, f),
+        ))
     }
 
     async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
@@ -483,33 +739,17 @@ impl Accessor for HdfsBackend {
         Ok((RpRead::new(end - start), r))
     }
 
-    fn blocking_write(&self, path: &str, _: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
-        let p = build_rooted_abs_path(&self.root, path);
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let (target_path, tmp_path, append, opts) = self.prepare_write(path, &args)?;
 
-        let parent = PathBuf::from(&p)
-            .parent()
-            .ok_or_else(|| {
-                Error::new(
-                    ErrorKind::Unexpected,
-                    "path should have parent but not, it must be malformed",
-                )
-                .with_context("input", &p)
-            })?
-            .to_path_buf();
-
-        self.client
-            .create_dir(&parent.to_string_lossy())
-            .map_err(parse_io_error)?;
-
-        let f = self
-            .client
-            .open_file()
-            .create(true)
-            .write(true)
-            .open(&p)
+        let f = opts
+            .open(tmp_path.as_ref().unwrap_or(&target_path))
             .map_err(parse_io_error)?;
 
-        Ok((RpWrite::new(), HdfsWriter::new(f)))
+        Ok((
+            RpWrite::new(),
+            HdfsWriter::new(self.client.clone(), target_path, tmp_path, append, f),
+        ))
     }
 
     fn blocking_stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
@@ -577,3 +817,134 @@ impl Accessor for HdfsBackend {
         Ok((RpList::default(), Some(rd)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    #[test]
+    fn test_from_map() {
+        let map = HashMap::from([
+            ("root".to_string(), "/tmp".to_string()),
+            (
+                "name_node".to_string(),
+                "hdfs://127.0.0.1:9000/".to_string(),
+            ),
+            ("atomic_write_dir".to_string(), "/tmp/atomic".to_string()),
+        ]);
+        let builder = HdfsBuilder::from_map(map);
+
+        assert_eq!(builder.root.as_deref(), Some("/tmp"));
+        assert_eq!(builder.name_node.as_deref(), Some("hdfs://127.0.0.1:9000"));
+        assert_eq!(builder.atomic_write_dir.as_deref(), Some("/tmp/atomic"));
+    }
+
+    #[test]
+    fn test_from_map_kerberos() {
+        let map = HashMap::from([
+            ("user".to_string(), "hadoop".to_string()),
+            (
+                "kerberos_ticket_cache_path".to_string(),
+                "/tmp/krb5cc".to_string(),
+            ),
+            (
+                "kerberos_principal".to_string(),
+                "hdfs/host@EXAMPLE.COM".to_string(),
+            ),
+            (
+                "kerberos_keytab".to_string(),
+                "/etc/security/hdfs.keytab".to_string(),
+            ),
+        ]);
+        let builder = HdfsBuilder::from_map(map);
+
+        assert_eq!(builder.user.as_deref(), Some("hadoop"));
+        assert_eq!(
+            builder.kerberos_ticket_cache_path.as_deref(),
+            Some("/tmp/krb5cc")
+        );
+        assert_eq!(
+            builder.kerberos_principal.as_deref(),
+            Some("hdfs/host@EXAMPLE.COM")
+        );
+        assert_eq!(
+            builder.kerberos_keytab.as_deref(),
+            Some("/etc/security/hdfs.keytab")
+        );
+    }
+
+    #[test]
+    fn test_kerberos_principal_without_keytab() {
+        let mut builder = HdfsBuilder::default();
+        builder
+            .name_node("hdfs://127.0.0.1:9000")
+            .kerberos_principal("hdfs/host@EXAMPLE.COM");
+
+        let err = builder.build().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    /// Data written with atomic write dir must be invisible until closing,
+    /// and existing target must be replaced by renaming.
+    ///
+    /// Requires a running HDFS configured by `OPENDAL_HDFS_*` envs.
+    #[test]
+    fn test_atomic_write_rename() {
+        if std::env::var("OPENDAL_HDFS_TEST").as_deref() != Ok("on") {
+            return;
+        }
+
+        let mut map: HashMap<String, String> = std::env::vars()
+            .filter_map(|(k, v)| {
+                k.strip_prefix("OPENDAL_HDFS_")
+                    .map(|k| (k.to_lowercase(), v))
+            })
+            .collect();
+        map.insert("root".to_string(), format!("/{}/", Uuid::new_v4()));
+        let atomic_write_dir = format!("/tmp/{}/", Uuid::new_v4());
+        map.insert("atomic_write_dir".to_string(), atomic_write_dir.clone());
+
+        let backend = HdfsBuilder::from_map(map)
+            .build()
+            .expect("build must succeed");
+        let target = build_rooted_abs_path(&backend.root, "file");
+
+        let write = |content: &str| {
+            let (_, mut w) = backend
+                .blocking_write("file", OpWrite::new())
+                .expect("writer must be created");
+            oio::BlockingWrite::append(&mut w, Bytes::from(content.to_string()))
+                .expect("append must succeed");
+            w
+        };
+
+        // The target is invisible before closing.
+        let mut w = write("old");
+        let err = backend.client.metadata(&target).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        oio::BlockingWrite::close(&mut w).expect("close must succeed");
+        assert_eq!(backend.client.metadata(&target).unwrap().len(), 3);
+
+        // The existing target is kept until renaming.
+        let mut w = write("Hello, World!");
+        assert_eq!(backend.client.metadata(&target).unwrap().len(), 3);
+        oio::BlockingWrite::close(&mut w).expect("close must succeed");
+        assert_eq!(backend.client.metadata(&target).unwrap().len(), 13);
+
+        // Temp files are renamed or removed.
+        drop(write("dropped"));
+        assert_eq!(backend.client.metadata(&target).unwrap().len(), 13);
+        let tmp_files = backend
+            .client
+            .read_dir(&atomic_write_dir)
+            .expect("read dir must succeed")
+            .into_inner()
+            .count();
+        assert_eq!(tmp_files, 0);
+
+        backend.client.remove_dir_all(&backend.root).unwrap();
+        backend.client.remove_dir_all(&atomic_write_dir).unwrap();
+    }
+}
@@ -40,3 +40,51 @@ pub fn parse_io_error(err: io::Error) -> Error {
 
     err
 }
+
+/// Parse errors returned while connecting to name node.
+///
+/// Authentication failures are reported by the hadoop client as generic
+/// io errors, so we have to detect them via the message.
+pub fn parse_connect_error(err: io::Error) -> Error {
+    let msg = err.to_string();
+    let is_auth_failure = [
+        "GSS initiate failed",
+        "Client cannot authenticate",
+        "LoginException",
+        "KerberosAuthException",
+        "No valid credentials provided",
+    ]
+    .iter()
+    .any(|v| msg.contains(v));
+
+    if is_auth_failure {
+        Error::new(
+            ErrorKind::PermissionDenied,
+            "kerberos authentication failed",
+        )
+        .with_context("kerberos", msg)
+        .set_source(err)
+    } else {
+        parse_io_error(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_connect_error() {
+        let err = parse_connect_error(io::Error::new(
+            io::ErrorKind::Other,
+            "javax.security.sasl.SaslException: GSS initiate failed \
+             [Caused by GSSException: No valid credentials provided]",
+        ));
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        assert!(!err.is_temporary());
+        assert!(err.to_string().contains("No valid credentials provided"));
+
+        let err = parse_connect_error(io::Error::new(io::ErrorKind::NotFound, "not found"));
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+}
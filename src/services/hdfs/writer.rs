@@ -12,9 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io;
 use std::io::Seek;
 use std::io::SeekFrom;
 use std::io::Write;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -26,13 +28,86 @@ use crate::raw::*;
 use crate::*;
 
 pub struct HdfsWriter<F> {
-    f: F,
+    client: Arc<hdrs::Client>,
+    target_path: String,
+    /// The temp file that data written into, will be set to `None` after
+    /// renamed or removed.
+    tmp_path: Option<String>,
+    /// Appending to an existing file, which must not be removed while abort.
+    append: bool,
+    /// The file will be taken while closing to make sure it's closed before
+    /// renaming.
+    f: Option<F>,
     pos: u64,
 }
 
 impl<F> HdfsWriter<F> {
-    pub fn new(f: F) -> Self {
-        Self { f, pos: 0 }
+    pub fn new(
+        client: Arc<hdrs::Client>,
+        target_path: String,
+        tmp_path: Option<String>,
+        append: bool,
+        f: F,
+    ) -> Self {
+        Self {
+            client,
+            target_path,
+            tmp_path,
+            append,
+            f: Some(f),
+            pos: 0,
+        }
+    }
+
+    fn file(&mut self) -> Result<&mut F> {
+        self.f.as_mut().ok_or_else(|| {
+            Error::new(ErrorKind::Unexpected, "writer has been closed or aborted")
+                .with_context("path", &self.target_path)
+        })
+    }
+
+    /// Rename the temp file to the target path.
+    ///
+    /// HDFS rename fails if target exists, so we have to remove it first.
+    fn commit(&mut self) -> Result<()> {
+        if let Some(tmp_path) = &self.tmp_path {
+            match self.client.metadata(&self.target_path) {
+                Ok(_) => self
+                    .client
+                    .remove_file(&self.target_path)
+                    .map_err(parse_io_error)?,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(parse_io_error(err)),
+            }
+
+            self.client
+                .rename_file(tmp_path, &self.target_path)
+                .map_err(parse_io_error)?;
+            self.tmp_path = None;
+        }
+
+        Ok(())
+    }
+
+    /// Remove the written file, appended file will be kept since appended
+    /// data can't be reverted.
+    fn remove(&mut self) -> Result<()> {
+        let path = match self.tmp_path.take() {
+            Some(tmp_path) => tmp_path,
+            None if self.append => return Ok(()),
+            None => self.target_path.clone(),
+        };
+
+        self.client.remove_file(&path).map_err(parse_io_error)
+    }
+}
+
+/// Remove the temp file if writer is dropped without close.
+impl<F> Drop for HdfsWriter<F> {
+    fn drop(&mut self) {
+        if let Some(tmp_path) = self.tmp_path.take() {
+            let _ = self.client.remove_file(&tmp_path);
+        }
     }
 }
 
@@ -42,12 +117,15 @@ impl oio::Write for HdfsWriter<hdrs::AsyncFile> {
     ///
     /// File could be partial written, so we will seek to start to make sure
     /// we write the same content.
+    ///
+    /// Appending to existing file doesn't support seek.
     async fn write(&mut self, bs: Bytes) -> Result<()> {
-        self.f
-            .seek(SeekFrom::Start(0))
-            .await
-            .map_err(parse_io_error)?;
-        self.f.write_all(&bs).await.map_err(parse_io_error)?;
+        let append = self.append;
+        let f = self.file()?;
+        if !append {
+            f.seek(SeekFrom::Start(0)).await.map_err(parse_io_error)?;
+        }
+        f.write_all(&bs).await.map_err(parse_io_error)?;
 
         Ok(())
     }
@@ -57,20 +135,33 @@ impl oio::Write for HdfsWriter<hdrs::AsyncFile> {
     /// File could be partial written, so we will seek to start to make sure
     /// we write the same content.
     async fn append(&mut self, bs: Bytes) -> Result<()> {
-        self.f
-            .seek(SeekFrom::Start(self.pos))
-            .await
-            .map_err(parse_io_error)?;
-        self.f.write_all(&bs).await.map_err(parse_io_error)?;
+        let (pos, append) = (self.pos, self.append);
+        let f = self.file()?;
+        if !append {
+            f.seek(SeekFrom::Start(pos)).await.map_err(parse_io_error)?;
+        }
+        f.write_all(&bs).await.map_err(parse_io_error)?;
         self.pos += bs.len() as u64;
 
         Ok(())
     }
 
-    async fn close(&mut self) -> Result<()> {
-        self.f.close().await.map_err(parse_io_error)?;
+    async fn close(&mut self) -> Result<RpWrite> {
+        if let Some(mut f) = self.f.take() {
+            f.close().await.map_err(parse_io_error)?;
+        }
 
-        Ok(())
+        self.commit()?;
+
+        Ok(RpWrite::new())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        if let Some(mut f) = self.f.take() {
+            f.close().await.map_err(parse_io_error)?;
+        }
+
+        self.remove()
     }
 }
 
@@ -79,9 +170,15 @@ impl oio::BlockingWrite for HdfsWriter<hdrs::File> {
     ///
     /// File could be partial written, so we will seek to start to make sure
     /// we write the same content.
+    ///
+    /// Appending to existing file doesn't support seek.
     fn write(&mut self, bs: Bytes) -> Result<()> {
-        self.f.rewind().map_err(parse_io_error)?;
-        self.f.write_all(&bs).map_err(parse_io_error)?;
+        let append = self.append;
+        let f = self.file()?;
+        if !append {
+            f.rewind().map_err(parse_io_error)?;
+        }
+        f.write_all(&bs).map_err(parse_io_error)?;
 
         Ok(())
     }
@@ -91,18 +188,32 @@ impl oio::BlockingWrite for HdfsWriter<hdrs::File> {
     /// File could be partial written, so we will seek to start to make sure
     /// we write the same content.
     fn append(&mut self, bs: Bytes) -> Result<()> {
-        self.f
-            .seek(SeekFrom::Start(self.pos))
-            .map_err(parse_io_error)?;
-        self.f.write_all(&bs).map_err(parse_io_error)?;
+        let (pos, append) = (self.pos, self.append);
+        let f = self.file()?;
+        if !append {
+            f.seek(SeekFrom::Start(pos)).map_err(parse_io_error)?;
+        }
+        f.write_all(&bs).map_err(parse_io_error)?;
         self.pos += bs.len() as u64;
 
         Ok(())
     }
 
-    fn close(&mut self) -> Result<()> {
-        self.f.flush().map_err(parse_io_error)?;
+    fn close(&mut self) -> Result<RpWrite> {
+        // File will be closed while dropped.
+        if let Some(mut f) = self.f.take() {
+            f.flush().map_err(parse_io_error)?;
+        }
 
-        Ok(())
+        self.commit()?;
+
+        Ok(RpWrite::new())
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        // File will be closed while dropped.
+        drop(self.f.take());
+
+        self.remove()
     }
 }
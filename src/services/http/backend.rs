@@ -15,9 +15,13 @@
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use http::header;
+use http::HeaderMap;
 use http::Request;
 use http::Response;
 use http::StatusCode;
@@ -46,10 +50,18 @@ use crate::*;
 /// Only `read` ans `stat` are supported. We can use this service to visit any
 /// HTTP Server like nginx, caddy.
 ///
+/// Some servers (like signed CDN URLs) only allow `GET`. Enable
+/// `enable_head_fallback` to `stat` via a ranged `GET` of `bytes=0-0` if
+/// `HEAD` is rejected. The size of file will be unknown if server doesn't
+/// tell us, and read will stream until EOF.
+///
+/// Range reads will be disabled once server returns `Accept-Ranges: none`.
+///
 /// # Configuration
 ///
 /// - `endpoint`: set the endpoint for http
 /// - `root`: Set the work directory for backend
+/// - `enable_head_fallback`: stat via ranged `GET` if `HEAD` is rejected
 ///
 /// You can refer to [`HttpBuilder`]'s docs for more information
 ///
@@ -80,6 +92,7 @@ pub struct HttpBuilder {
     password: Option<String>,
     token: Option<String>,
     root: Option<String>,
+    enable_head_fallback: bool,
     http_client: Option<HttpClient>,
 }
 
@@ -88,6 +101,7 @@ impl Debug for HttpBuilder {
         let mut de = f.debug_struct("Builder");
         de.field("endpoint", &self.endpoint);
         de.field("root", &self.root);
+        de.field("enable_head_fallback", &self.enable_head_fallback);
 
         de.finish()
     }
@@ -148,6 +162,15 @@ impl HttpBuilder {
         self
     }
 
+    /// Fallback to a ranged `GET` of `bytes=0-0` while `stat` if server
+    /// rejects `HEAD` requests.
+    ///
+    /// The size of file will be read from `Content-Range` instead.
+    pub fn enable_head_fallback(&mut self) -> &mut Self {
+        self.enable_head_fallback = true;
+        self
+    }
+
     /// Specify the http client that used by this service.
     ///
     /// # Notes
@@ -172,6 +195,9 @@ impl Builder for HttpBuilder {
         map.get("username").map(|v| builder.username(v));
         map.get("password").map(|v| builder.password(v));
         map.get("token").map(|v| builder.token(v));
+        map.get("enable_head_fallback")
+            .filter(|v| *v == "on" || *v == "true")
+            .map(|_| builder.enable_head_fallback());
 
         builder
     }
@@ -186,6 +212,7 @@ impl Builder for HttpBuilder {
                     .with_context("service", Scheme::Http))
             }
         };
+        parse_endpoint(endpoint).map_err(|e| e.with_context("service", Scheme::Http))?;
 
         let root = normalize_root(&self.root.take().unwrap_or_default());
         debug!("backend use root {}", root);
@@ -215,6 +242,8 @@ impl Builder for HttpBuilder {
             endpoint: endpoint.to_string(),
             authorization: auth,
             root,
+            enable_head_fallback: self.enable_head_fallback,
+            range_disabled: Arc::new(AtomicBool::new(false)),
             client,
         })
     }
@@ -228,6 +257,9 @@ pub struct HttpBackend {
     client: HttpClient,
 
     authorization: Option<String>,
+    enable_head_fallback: bool,
+    /// Will be set once server returns `Accept-Ranges: none`.
+    range_disabled: Arc<AtomicBool>,
 }
 
 impl Debug for HttpBackend {
@@ -235,6 +267,8 @@ impl Debug for HttpBackend {
         f.debug_struct("Backend")
             .field("endpoint", &self.endpoint)
             .field("root", &self.root)
+            .field("enable_head_fallback", &self.enable_head_fallback)
+            .field("range_disabled", &self.range_disabled)
             .field("client", &self.client)
             .finish()
     }
@@ -250,24 +284,42 @@ impl Accessor for HttpBackend {
     type BlockingPager = ();
 
     fn info(&self) -> AccessorInfo {
+        let range_enabled = !self.range_disabled.load(Ordering::Relaxed);
+
         let mut ma = AccessorInfo::default();
         ma.set_scheme(Scheme::Http)
             .set_root(&self.root)
             .set_capabilities(AccessorCapability::Read)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: range_enabled,
+                read_with_suffix_range: range_enabled,
+                ..Default::default()
+            })
             .set_hints(AccessorHint::ReadStreamable);
 
         ma
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        let resp = self.http_get(path, args.range()).await?;
+        let range = args.range();
+        // Server doesn't support range, read the whole content and
+        // return the requested range only.
+        let resp = if self.range_disabled.load(Ordering::Relaxed) {
+            self.http_get(path, BytesRange::default()).await?
+        } else {
+            self.http_get(path, range).await?
+        };
+        self.check_accept_ranges(resp.headers());
 
         let status = resp.status();
 
         match status {
             StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
-                let meta = parse_into_metadata(path, resp.headers())?;
-                Ok((RpRead::with_metadata(meta), resp.into_body()))
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
             }
             _ => Err(parse_error(resp).await?),
         }
@@ -280,6 +332,7 @@ impl Accessor for HttpBackend {
         }
 
         let resp = self.http_head(path).await?;
+        self.check_accept_ranges(resp.headers());
 
         let status = resp.status();
 
@@ -290,12 +343,61 @@ impl Accessor for HttpBackend {
             StatusCode::NOT_FOUND | StatusCode::FORBIDDEN if path.ends_with('/') => {
                 Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
             }
+            // Signed URLs could return FORBIDDEN for HEAD since the
+            // signature is calculated with GET.
+            StatusCode::METHOD_NOT_ALLOWED
+            | StatusCode::FORBIDDEN
+            | StatusCode::NOT_IMPLEMENTED
+                if self.enable_head_fallback =>
+            {
+                resp.into_body().consume().await?;
+                self.stat_via_get(path).await
+            }
             _ => Err(parse_error(resp).await?),
         }
     }
 }
 
 impl HttpBackend {
+    /// Disable range reads if server tells us it doesn't support.
+    fn check_accept_ranges(&self, headers: &HeaderMap) {
+        let disabled = headers
+            .get(header::ACCEPT_RANGES)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().eq_ignore_ascii_case("none"))
+            .unwrap_or_default();
+
+        if disabled && !self.range_disabled.swap(true, Ordering::Relaxed) {
+            debug!("server doesn't accept ranges, range read has been disabled");
+        }
+    }
+
+    /// Stat via a ranged `GET` of `bytes=0-0` instead of `HEAD`.
+    async fn stat_via_get(&self, path: &str) -> Result<RpStat> {
+        let resp = self
+            .http_get(path, BytesRange::new(Some(0), Some(1)))
+            .await?;
+        self.check_accept_ranges(resp.headers());
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK
+            | StatusCode::PARTIAL_CONTENT
+            // Empty files will return `bytes */0`.
+            | StatusCode::RANGE_NOT_SATISFIABLE => {
+                let meta = parse_probe_metadata(path, status, resp.headers())?;
+                // The whole content will be returned if server ignores the
+                // range, drop it without reading.
+                if status != StatusCode::OK {
+                    resp.into_body().consume().await?;
+                }
+                Ok(RpStat::new(meta))
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
     async fn http_get(&self, path: &str, range: BytesRange) -> Result<Response<IncomingAsyncBody>> {
         let p = build_rooted_abs_path(&self.root, path);
 
@@ -337,11 +439,52 @@ impl HttpBackend {
     }
 }
 
+/// Parse metadata from the response of a ranged `GET` of `bytes=0-0`.
+///
+/// Content length of the response is the size of returned range, the total
+/// size should be read from `Content-Range` instead. The size will be left
+/// unset if server doesn't tell us.
+fn parse_probe_metadata(path: &str, status: StatusCode, headers: &HeaderMap) -> Result<Metadata> {
+    let mode = if path.ends_with('/') {
+        EntryMode::DIR
+    } else {
+        EntryMode::FILE
+    };
+    let mut m = Metadata::new(mode);
+
+    let size = match status {
+        StatusCode::OK => parse_content_length(headers)?,
+        _ => parse_content_range(headers)?.and_then(|v| v.size()),
+    };
+    if let Some(v) = size {
+        m.set_content_length(v);
+    }
+
+    if let Some(v) = parse_content_type(headers)? {
+        m.set_content_type(v);
+    }
+
+    if let Some(v) = parse_etag(headers)? {
+        m.set_etag(v);
+    }
+
+    if let Some(v) = parse_last_modified(headers)? {
+        m.set_last_modified(v);
+    }
+
+    Ok(m)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+    use std::time::Instant;
+
     use anyhow::Result;
     use wiremock::matchers::basic_auth;
     use wiremock::matchers::bearer_token;
+    use wiremock::matchers::header;
+    use wiremock::matchers::header_exists;
     use wiremock::matchers::method;
     use wiremock::matchers::path;
     use wiremock::Mock;
@@ -349,6 +492,7 @@ mod tests {
     use wiremock::ResponseTemplate;
 
     use super::*;
+    use crate::raw::oio::ReadExt;
     use crate::Operator;
 
     #[tokio::test]
@@ -377,6 +521,126 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_read_with_deadline() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/hello"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-length", "13")
+                    .set_body_string("Hello, World!")
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = HttpBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        builder.root("/");
+        let op = Operator::new(builder)?.finish();
+
+        let start = Instant::now();
+        let args = OpRead::new().with_deadline(start + Duration::from_millis(200));
+        let err = op.read_with("hello", args).await.unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(!err.is_temporary());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_with_ignored_range() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/hello"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-length", "13")
+                    .set_body_string("Hello, World!"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = HttpBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        builder.root("/");
+        let op = Operator::new(builder)?.finish();
+
+        let bs = op.range_read("hello", 7..12).await?;
+
+        assert_eq!(bs, b"World");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_with_mismatched_range() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/hello"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("content-length", "5")
+                    .insert_header("content-range", "bytes 0-4/13")
+                    .set_body_string("Hello"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = HttpBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        builder.root("/");
+        let op = Operator::new(builder)?.finish();
+
+        let err = op
+            .range_read("hello", 7..12)
+            .await
+            .expect_err("mismatched range must fail");
+
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_with_custom_http_client() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/hello"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-length", "13")
+                    .set_body_string("Hello, World!"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = HttpClient::builder()
+            .pool_max_idle_per_host(1)
+            .pool_idle_timeout(Duration::from_secs(1))
+            .connect_timeout(Duration::from_secs(1))
+            .build()?;
+
+        let mut builder = HttpBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        builder.root("/");
+        builder.http_client(client);
+        let op = Operator::new(builder)?.finish();
+
+        let bs = op.read("hello").await?;
+
+        assert_eq!(bs, b"Hello, World!");
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_read_via_basic_auth() -> Result<()> {
         let _ = env_logger::builder().is_test(true).try_init();
@@ -458,4 +722,177 @@ mod tests {
         assert_eq!(bs.content_length(), 128);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_stat_with_head_rejected() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .respond_with(ResponseTemplate::new(405))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/hello"))
+            .and(header("range", "bytes=0-0"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("content-range", "bytes 0-0/13")
+                    .insert_header("etag", "\"abc\"")
+                    .set_body_string("H"),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/hello"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/empty"))
+            .respond_with(ResponseTemplate::new(416).insert_header("content-range", "bytes */0"))
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = HttpBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        builder.root("/");
+        builder.enable_head_fallback();
+        let op = Operator::new(builder)?.finish();
+
+        let meta = op.stat("hello").await?;
+        assert_eq!(meta.mode(), EntryMode::FILE);
+        assert_eq!(meta.content_length(), 13);
+        assert_eq!(meta.etag(), Some("\"abc\""));
+
+        let meta = op.stat("empty").await?;
+        assert_eq!(meta.content_length(), 0);
+
+        let bs = op.read("hello").await?;
+        assert_eq!(bs, b"Hello, World!");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_head_rejected_and_unknown_size() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/hello"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("content-range", "bytes 0-0/*")
+                    .set_body_string("H"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = HttpBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        builder.root("/");
+        builder.enable_head_fallback();
+        let op = Operator::new(builder)?.finish();
+
+        let meta = op.stat("hello").await?;
+        assert_eq!(meta.mode(), EntryMode::FILE);
+        assert_eq!(meta.content_length_raw(), None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_without_head_fallback() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .respond_with(ResponseTemplate::new(405))
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = HttpBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        builder.root("/");
+        let op = Operator::new(builder)?.finish();
+
+        assert!(op.stat("hello").await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_with_accept_ranges_none() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/hello"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-length", "13")
+                    .insert_header("accept-ranges", "none"),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/hello"))
+            .and(header_exists("range"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/hello"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = HttpBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        builder.root("/");
+        let backend = builder.build()?;
+        assert!(backend.info().capability().read_with_range);
+
+        let meta = backend.stat("hello", OpStat::new()).await?.into_metadata();
+        assert_eq!(meta.content_length(), 13);
+        assert!(!backend.info().capability().read_with_range);
+        assert!(!backend.info().capability().read_with_suffix_range);
+
+        let (rp, mut r) = backend
+            .read(
+                "hello",
+                OpRead::new().with_range(BytesRange::new(Some(7), Some(5))),
+            )
+            .await?;
+        assert_eq!(rp.metadata().content_length(), 5);
+        let mut bs = Vec::new();
+        while let Some(v) = r.next().await {
+            bs.extend_from_slice(&v?);
+        }
+        assert_eq!(bs, b"World");
+        Ok(())
+    }
+
+    #[test]
+    fn test_capability() -> Result<()> {
+        let mut builder = HttpBuilder::default();
+        builder.endpoint("http://127.0.0.1");
+        let op = Operator::new(builder)?.finish();
+
+        assert_eq!(
+            op.info().capability(),
+            Capability {
+                stat: true,
+                read: true,
+                read_can_seek: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                ..Default::default()
+            }
+        );
+        Ok(())
+    }
 }
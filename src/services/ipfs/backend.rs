@@ -16,16 +16,23 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
+use futures::future;
+use futures::future::Either;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
 use http::Request;
 use http::Response;
 use http::StatusCode;
 use log::debug;
-use prost::Message;
+use log::warn;
+use serde::Deserialize;
 
+use super::error::parse_api_error;
 use super::error::parse_error;
-use super::ipld::PBNode;
+use super::pager::IpfsPager;
 use crate::ops::*;
 use crate::raw::*;
 use crate::*;
@@ -47,6 +54,27 @@ use crate::*;
 ///
 /// - `root`: Set the work directory for backend
 /// - `endpoint`: Customizable endpoint setting
+/// - `gateways`: Fallback gateways to try in order if `endpoint` failed
+/// - `gateway_timeout`: Timeout in milliseconds of every gateway before fallback
+/// - `hedge_delay`: Delay in milliseconds before sending the hedged request
+/// - `api_endpoint`: Endpoint of ipfs node's RPC API, used by `stat` and `list`
+///
+/// # Gateway Fallback
+///
+/// CIDs are immutable, so requests could be sent to any gateway safely.
+/// Requests are sent to `endpoint` first, and then to `gateways` in
+/// order if the previous one failed with temporary errors (like
+/// connection failures and timeouts) or responded with `408`, `429` and
+/// `5xx`. Every fallback will be logged at `warn` level.
+///
+/// - If `gateway_timeout` is set, a gateway that doesn't respond in time
+///   will be treated as failed.
+/// - If `hedge_delay` is set, the request will be sent to the next gateway
+///   if the current one doesn't respond within the delay, while the
+///   previous requests are still kept. The first succeeded response wins.
+///
+/// The gateway that served the request will be logged at `debug` level,
+/// and errors will carry the gateway in their context.
 ///
 /// You can refer to [`IpfsBuilder`]'s docs for more information
 ///
@@ -81,6 +109,10 @@ use crate::*;
 #[derive(Default, Clone, Debug)]
 pub struct IpfsBuilder {
     endpoint: Option<String>,
+    gateways: Vec<String>,
+    gateway_timeout: Option<Duration>,
+    hedge_delay: Option<Duration>,
+    api_endpoint: Option<String>,
     root: Option<String>,
     http_client: Option<HttpClient>,
 }
@@ -121,6 +153,56 @@ impl IpfsBuilder {
         self
     }
 
+    /// Set fallback gateways of ipfs backend.
+    ///
+    /// Gateways will be tried in order after `endpoint` failed.
+    pub fn gateways(&mut self, gateways: &[&str]) -> &mut Self {
+        self.gateways = gateways
+            .iter()
+            .filter(|v| !v.is_empty())
+            .map(|v| v.trim_end_matches('/').to_string())
+            .collect();
+
+        self
+    }
+
+    /// Set the timeout of every gateway.
+    ///
+    /// The next gateway will be tried if the current one doesn't respond
+    /// in time. The timeout only covers receiving the response head, the
+    /// body will be streamed without limit.
+    ///
+    /// Default to no timeout.
+    pub fn gateway_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.gateway_timeout = Some(timeout);
+        self
+    }
+
+    /// Enable hedged requests across gateways.
+    ///
+    /// If the current gateway doesn't respond within `delay`, the same
+    /// request will be sent to the next gateway without canceling the
+    /// previous one. The first succeeded response will be used.
+    ///
+    /// This trades extra requests for lower tail latency.
+    pub fn hedge_delay(&mut self, delay: Duration) -> &mut Self {
+        self.hedge_delay = Some(delay);
+        self
+    }
+
+    /// Set the endpoint of ipfs node's RPC API like `http://127.0.0.1:5001`.
+    ///
+    /// If set, `stat` and `list` will be served by `/api/v0/files/stat` and
+    /// `/api/v0/ls` instead of gateways, which returns the type and size of
+    /// entries directly.
+    pub fn api_endpoint(&mut self, endpoint: &str) -> &mut Self {
+        if !endpoint.is_empty() {
+            self.api_endpoint = Some(endpoint.trim_end_matches('/').to_string());
+        }
+
+        self
+    }
+
     /// Specify the http client that used by this service.
     ///
     /// # Notes
@@ -141,6 +223,17 @@ impl Builder for IpfsBuilder {
 
         map.get("root").map(|v| builder.root(v));
         map.get("endpoint").map(|v| builder.endpoint(v));
+        map.get("gateways")
+            .map(|v| builder.gateways(&v.split(',').map(|v| v.trim()).collect::<Vec<_>>()));
+        map.get("gateway_timeout").map(|v| {
+            v.parse::<u64>()
+                .map(|v| builder.gateway_timeout(Duration::from_millis(v)))
+        });
+        map.get("hedge_delay").map(|v| {
+            v.parse::<u64>()
+                .map(|v| builder.hedge_delay(Duration::from_millis(v)))
+        });
+        map.get("api_endpoint").map(|v| builder.api_endpoint(v));
 
         builder
     }
@@ -149,23 +242,24 @@ impl Builder for IpfsBuilder {
         debug!("backend build started: {:?}", &self);
 
         let root = normalize_root(&self.root.take().unwrap_or_default());
-        if !root.starts_with("/ipfs/") && !root.starts_with("/ipns/") {
-            return Err(Error::new(
-                ErrorKind::ConfigInvalid,
-                "root must start with /ipfs/ or /ipns/",
-            )
-            .with_context("service", Scheme::Ipfs)
-            .with_context("root", &root));
-        }
+        validate_root(&root)?;
         debug!("backend use root {}", root);
 
-        let endpoint = match &self.endpoint {
-            Some(endpoint) => Ok(endpoint.clone()),
-            None => Err(Error::new(ErrorKind::ConfigInvalid, "endpoint is empty")
+        let gateways: Vec<_> = self
+            .endpoint
+            .iter()
+            .chain(self.gateways.iter())
+            .cloned()
+            .collect();
+        if gateways.is_empty() {
+            return Err(Error::new(ErrorKind::ConfigInvalid, "endpoint is empty")
                 .with_context("service", Scheme::Ipfs)
-                .with_context("root", &root)),
-        }?;
-        debug!("backend use endpoint {}", &endpoint);
+                .with_context("root", &root));
+        }
+        debug!("backend use gateways {:?}", &gateways);
+
+        let api_endpoint = self.api_endpoint.clone();
+        debug!("backend use api endpoint {:?}", &api_endpoint);
 
         let client = if let Some(client) = self.http_client.take() {
             client
@@ -179,16 +273,88 @@ impl Builder for IpfsBuilder {
         debug!("backend build finished: {:?}", &self);
         Ok(IpfsBackend {
             root,
-            endpoint,
+            gateways,
+            gateway_timeout: self.gateway_timeout,
+            hedge_delay: self.hedge_delay,
+            api_endpoint,
             client,
         })
     }
 }
 
+/// Validate root of ipfs backend.
+///
+/// Root must be `/ipfs/<cid>/<subpath>` or `/ipns/<name>/<subpath>`.
+fn validate_root(root: &str) -> Result<()> {
+    let (cid, name) = match (root.strip_prefix("/ipfs/"), root.strip_prefix("/ipns/")) {
+        (Some(v), _) => (true, v.split('/').next().unwrap_or_default()),
+        (_, Some(v)) => (false, v.split('/').next().unwrap_or_default()),
+        _ => {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "root must start with /ipfs/ or /ipns/",
+            )
+            .with_context("service", Scheme::Ipfs)
+            .with_context("root", root))
+        }
+    };
+
+    if name.is_empty() || (cid && !is_valid_cid(name)) {
+        return Err(Error::new(
+            ErrorKind::ConfigInvalid,
+            "root must contain a valid cid or ipns name",
+        )
+        .with_context("service", Scheme::Ipfs)
+        .with_context("root", root));
+    }
+
+    Ok(())
+}
+
+/// Check if given string is a valid CID.
+///
+/// - CIDv0 is always 46 characters of base58btc starting with `Qm`.
+/// - CIDv1 is multibase encoded, the first character is the prefix of
+///   the base. `b` (base32), `B` (base32upper), `k` (base36), `z`
+///   (base58btc) and `f` (base16) are accepted here.
+///
+/// ref: <https://docs.ipfs.tech/concepts/content-addressing/>
+fn is_valid_cid(s: &str) -> bool {
+    const BASE58: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    if s.len() == 46 && s.starts_with("Qm") {
+        return s.chars().all(|c| BASE58.contains(c));
+    }
+
+    if s.len() < 2 || !s.is_char_boundary(1) {
+        return false;
+    }
+    let (prefix, body) = s.split_at(1);
+    match prefix {
+        "b" => body.chars().all(|c| matches!(c, 'a'..='z' | '2'..='7')),
+        "B" => body.chars().all(|c| matches!(c, 'A'..='Z' | '2'..='7')),
+        "k" => body.chars().all(|c| matches!(c, 'a'..='z' | '0'..='9')),
+        "z" => body.chars().all(|c| BASE58.contains(c)),
+        "f" => body.chars().all(|c| matches!(c, 'a'..='f' | '0'..='9')),
+        _ => false,
+    }
+}
+
+/// Check if the response of a gateway is failed and worth to try the
+/// next gateway.
+fn is_gateway_failure(status: StatusCode) -> bool {
+    status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status.is_server_error()
+}
+
 /// Backend for IPFS.
 #[derive(Clone)]
 pub struct IpfsBackend {
-    endpoint: String,
+    gateways: Vec<String>,
+    gateway_timeout: Option<Duration>,
+    hedge_delay: Option<Duration>,
+    api_endpoint: Option<String>,
     root: String,
     client: HttpClient,
 }
@@ -196,7 +362,10 @@ pub struct IpfsBackend {
 impl Debug for IpfsBackend {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Backend")
-            .field("endpoint", &self.endpoint)
+            .field("gateways", &self.gateways)
+            .field("gateway_timeout", &self.gateway_timeout)
+            .field("hedge_delay", &self.hedge_delay)
+            .field("api_endpoint", &self.api_endpoint)
             .field("root", &self.root)
             .field("client", &self.client)
             .finish()
@@ -209,7 +378,7 @@ impl Accessor for IpfsBackend {
     type BlockingReader = ();
     type Writer = ();
     type BlockingWriter = ();
-    type Pager = DirStream;
+    type Pager = IpfsPager;
     type BlockingPager = ();
 
     fn info(&self) -> AccessorInfo {
@@ -217,20 +386,31 @@ impl Accessor for IpfsBackend {
         ma.set_scheme(Scheme::Ipfs)
             .set_root(&self.root)
             .set_capabilities(AccessorCapability::Read | AccessorCapability::List)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                list: true,
+                list_with_delimiter: true,
+                ..Default::default()
+            })
             .set_hints(AccessorHint::ReadStreamable);
 
         ma
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        let resp = self.ipfs_get(path, args.range()).await?;
+        let range = args.range();
+        let resp = self.ipfs_get(path, range).await?;
 
         let status = resp.status();
 
         match status {
             StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
-                let meta = parse_into_metadata(path, resp.headers())?;
-                Ok((RpRead::with_metadata(meta), resp.into_body()))
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
             }
             _ => Err(parse_error(resp).await?),
         }
@@ -344,6 +524,10 @@ impl Accessor for IpfsBackend {
             return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
         }
 
+        if self.api_endpoint.is_some() {
+            return self.ipfs_api_stat(path).await;
+        }
+
         let resp = self.ipfs_head(path).await?;
 
         let status = resp.status();
@@ -390,122 +574,554 @@ impl Accessor for IpfsBackend {
     async fn list(&self, path: &str, _: OpList) -> Result<(RpList, Self::Pager)> {
         Ok((
             RpList::default(),
-            DirStream::new(Arc::new(self.clone()), path),
+            IpfsPager::new(Arc::new(self.clone()), path),
         ))
     }
 }
 
 impl IpfsBackend {
+    /// Send request built by `build` to gateways in order.
+    ///
+    /// The next gateway will be tried if the current one failed with
+    /// temporary errors or responded with failure status, or if it doesn't
+    /// respond within `hedge_delay`. Response of the last failed gateway
+    /// will be returned as is.
+    async fn ipfs_send<F>(&self, build: F) -> Result<Response<IncomingAsyncBody>>
+    where
+        F: Fn(&str) -> Result<Request<AsyncBody>>,
+    {
+        let mut gateways = self.gateways.iter();
+        let mut pending = FuturesUnordered::new();
+        // Response of the last failed gateway.
+        let mut last;
+
+        let gateway = gateways.next().expect("gateways must not be empty");
+        pending.push(self.ipfs_send_to(gateway, build(gateway)?));
+
+        loop {
+            let hedge = match self.hedge_delay {
+                Some(delay) if gateways.len() > 0 => Either::Left(tokio::time::sleep(delay)),
+                _ => Either::Right(future::pending::<()>()),
+            };
+            futures::pin_mut!(hedge);
+
+            // Drop the `next` future before pushing new requests.
+            let finished = match future::select(pending.next(), hedge).await {
+                Either::Left((res, _)) => res,
+                Either::Right(_) => None,
+            };
+
+            let (gateway, res) = match finished {
+                Some(v) => v,
+                None => {
+                    let gateway = gateways.next().expect("hedge must have next gateway");
+                    debug!("ipfs gateways are slow, hedge request to {gateway}");
+                    pending.push(self.ipfs_send_to(gateway, build(gateway)?));
+                    continue;
+                }
+            };
+
+            match res {
+                Ok(resp) if !is_gateway_failure(resp.status()) => {
+                    debug!("ipfs request served by gateway {gateway}");
+                    return Ok(resp);
+                }
+                Ok(resp) => {
+                    warn!("ipfs gateway {gateway} responded {}", resp.status());
+                    last = Ok(resp);
+                }
+                Err(err) if err.is_temporary() => {
+                    warn!("ipfs gateway {gateway} failed: {err}");
+                    last = Err(err.with_context("gateway", gateway));
+                }
+                Err(err) => return Err(err.with_context("gateway", gateway)),
+            }
+
+            // Other hedged requests are still running, wait for them.
+            if !pending.is_empty() {
+                continue;
+            }
+            match gateways.next() {
+                Some(gateway) => {
+                    debug!("ipfs fallback to next gateway {gateway}");
+                    pending.push(self.ipfs_send_to(gateway, build(gateway)?));
+                }
+                None => return last,
+            }
+        }
+    }
+
+    /// Send request to given gateway with `gateway_timeout`.
+    async fn ipfs_send_to<'a>(
+        &self,
+        gateway: &'a str,
+        req: Request<AsyncBody>,
+    ) -> (&'a str, Result<Response<IncomingAsyncBody>>) {
+        let timeout = match self.gateway_timeout {
+            Some(timeout) => timeout,
+            None => return (gateway, self.client.send_async(req).await),
+        };
+
+        let res = match tokio::time::timeout(timeout, self.client.send_async(req)).await {
+            Ok(res) => res,
+            Err(_) => Err(Error::new(ErrorKind::Unexpected, "ipfs gateway timed out")
+                .with_context("timeout", format!("{timeout:?}"))
+                .set_temporary()),
+        };
+        (gateway, res)
+    }
+
     async fn ipfs_get(&self, path: &str, range: BytesRange) -> Result<Response<IncomingAsyncBody>> {
         let p = build_rooted_abs_path(&self.root, path);
 
-        let url = format!("{}{}", self.endpoint, percent_encode_path(&p));
+        self.ipfs_send(|gateway| {
+            let url = format!("{}{}", gateway, percent_encode_path(&p));
 
-        let mut req = Request::get(&url);
-
-        if !range.is_full() {
-            req = req.header(http::header::RANGE, range.to_header());
-        }
+            let mut req = Request::get(&url);
 
-        let req = req
-            .body(AsyncBody::Empty)
-            .map_err(new_request_build_error)?;
+            if !range.is_full() {
+                req = req.header(http::header::RANGE, range.to_header());
+            }
 
-        self.client.send_async(req).await
+            req.body(AsyncBody::Empty).map_err(new_request_build_error)
+        })
+        .await
     }
 
     async fn ipfs_head(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
         let p = build_rooted_abs_path(&self.root, path);
 
-        let url = format!("{}{}", self.endpoint, percent_encode_path(&p));
+        self.ipfs_send(|gateway| {
+            let url = format!("{}{}", gateway, percent_encode_path(&p));
 
-        let req = Request::head(&url);
+            Request::head(&url)
+                .body(AsyncBody::Empty)
+                .map_err(new_request_build_error)
+        })
+        .await
+    }
 
-        let req = req
-            .body(AsyncBody::Empty)
-            .map_err(new_request_build_error)?;
+    pub(super) async fn ipfs_list(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_rooted_abs_path(&self.root, path);
 
-        self.client.send_async(req).await
+        self.ipfs_send(|gateway| {
+            let url = format!("{}{}", gateway, percent_encode_path(&p));
+
+            // Use "application/vnd.ipld.raw" to disable IPLD codec deserialization
+            // OpenDAL will parse ipld data directly.
+            //
+            // ref: https://github.com/ipfs/specs/blob/main/http-gateways/PATH_GATEWAY.md
+            Request::get(&url)
+                .header(http::header::ACCEPT, "application/vnd.ipld.raw")
+                .body(AsyncBody::Empty)
+                .map_err(new_request_build_error)
+        })
+        .await
     }
 
-    async fn ipfs_list(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
-        let p = build_rooted_abs_path(&self.root, path);
+    /// Stat given path via ipfs node's `/api/v0/files/stat`.
+    async fn ipfs_api_stat(&self, path: &str) -> Result<RpStat> {
+        let resp = self.ipfs_api_request("files/stat", path).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
 
-        let url = format!("{}{}", self.endpoint, percent_encode_path(&p));
+                let res: IpfsFilesStatResponse =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
 
-        let mut req = Request::get(&url);
+                let mode = match res.file_type.as_str() {
+                    "file" => EntryMode::FILE,
+                    "directory" => EntryMode::DIR,
+                    _ => EntryMode::Unknown,
+                };
 
-        // Use "application/vnd.ipld.raw" to disable IPLD codec deserialization
-        // OpenDAL will parse ipld data directly.
-        //
-        // ref: https://github.com/ipfs/specs/blob/main/http-gateways/PATH_GATEWAY.md
-        req = req.header(http::header::ACCEPT, "application/vnd.ipld.raw");
+                let mut meta = Metadata::new(mode);
+                meta.set_content_length(res.size);
+                meta.set_etag(&res.hash);
+
+                Ok(RpStat::new(meta))
+            }
+            _ => Err(parse_api_error(resp).await?),
+        }
+    }
+
+    /// Send request to ipfs node's RPC API, all of them are `POST`.
+    ///
+    /// ref: <https://docs.ipfs.tech/reference/kubo/rpc/>
+    pub(super) async fn ipfs_api_request(
+        &self,
+        api: &str,
+        path: &str,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let endpoint = self
+            .api_endpoint
+            .as_deref()
+            .expect("api endpoint must be set");
+        let p = build_rooted_abs_path(&self.root, path);
+
+        let url = format!(
+            "{}/api/v0/{}?arg={}",
+            endpoint,
+            api,
+            percent_encode_path(&p)
+        );
 
-        let req = req
+        let req = Request::post(&url)
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
 
         self.client.send_async(req).await
     }
+
+    pub(super) fn has_api_endpoint(&self) -> bool {
+        self.api_endpoint.is_some()
+    }
 }
 
-pub struct DirStream {
-    backend: Arc<IpfsBackend>,
-    path: String,
-    consumed: bool,
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+struct IpfsFilesStatResponse {
+    #[serde(rename = "Hash")]
+    hash: String,
+    #[serde(rename = "Size")]
+    size: u64,
+    #[serde(rename = "Type")]
+    file_type: String,
 }
 
-impl DirStream {
-    fn new(backend: Arc<IpfsBackend>, path: &str) -> Self {
-        Self {
-            backend,
-            path: path.to_string(),
-            consumed: false,
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::services::Ipfs;
+
+    const CID_V0: &str = "QmPpCt1aYGb9JWJRmXRUnmJtVgeFFTJGzWFYEEX7bo9zGJ";
+    const CID_V1: &str = "bafybeibozpulxtpv5nhfa2ue3dcjx23ndh3gwr5vwllk7ptoyfwnfjjr4q";
+
+    /// Get an endpoint that nobody listens on.
+    fn dead_endpoint() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind must succeed");
+        let addr = listener.local_addr().expect("addr must be valid");
+        format!("http://{addr}")
+    }
+
+    #[test]
+    fn test_is_valid_cid() {
+        let cases = vec![
+            ("cid v0", CID_V0, true),
+            ("cid v1 base32", CID_V1, true),
+            (
+                "cid v1 base36",
+                "k51qzi5uqu5dlvj2baxnqndepeb86cbk3ng7n3i46uzyxzyqj2xjonzllnv0v8",
+                true,
+            ),
+            (
+                "cid v1 base16",
+                "f01701220c3c4733ec8affd06cf9e9ff50ffc6bcd2ec85a6170004bb709669c31de94391a",
+                true,
+            ),
+            (
+                "too short cid v0",
+                "QmPpCt1aYGb9JWJRmXRUnmJtVgeFFTJGzWFYEEX7bo9",
+                false,
+            ),
+            (
+                "cid v0 with invalid char",
+                "QmPpCt1aYGb9JWJRmXRUnmJtVgeFFTJGzWFYEEX7bo9zG0",
+                false,
+            ),
+            (
+                "cid v1 with upper char",
+                "bafybeibozpulxtpv5nhfa2ue3dcjx23ndh3gwr5vwllk7ptoyfwnfjjr4Q",
+                false,
+            ),
+            (
+                "unknown multibase",
+                "xafybeibozpulxtpv5nhfa2ue3dcjx23ndh3gwr5vwllk7ptoyfwnfjjr4q",
+                false,
+            ),
+            ("empty", "", false),
+            ("non ascii", "b文件", false),
+        ];
+
+        for (name, input, expected) in cases {
+            assert_eq!(is_valid_cid(input), expected, "{name}");
         }
     }
-}
 
-#[async_trait]
-impl oio::Page for DirStream {
-    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
-        if self.consumed {
-            return Ok(None);
+    #[test]
+    fn test_build_with_root() {
+        let cases = vec![
+            (
+                "cid v0",
+                format!("/ipfs/{CID_V0}"),
+                Some(format!("/ipfs/{CID_V0}/")),
+            ),
+            (
+                "cid v1 with subpath",
+                format!("/ipfs/{CID_V1}/dir/subdir"),
+                Some(format!("/ipfs/{CID_V1}/dir/subdir/")),
+            ),
+            (
+                "ipns",
+                "/ipns/opendal.databend.rs".to_string(),
+                Some("/ipns/opendal.databend.rs/".to_string()),
+            ),
+            ("invalid cid", "/ipfs/not-a-cid/dir".to_string(), None),
+            ("without cid", "/ipfs/".to_string(), None),
+            ("without prefix", format!("/{CID_V0}"), None),
+        ];
+
+        for (name, root, expected) in cases {
+            let mut builder = Ipfs::default();
+            builder.endpoint("https://ipfs.io").root(&root);
+
+            match expected {
+                Some(expected) => {
+                    let backend = builder.build().expect(name);
+                    assert_eq!(backend.root, expected, "{name}");
+                }
+                None => {
+                    let err = builder.build().expect_err(name);
+                    assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "{name}");
+                }
+            }
         }
+    }
 
-        let resp = self.backend.ipfs_list(&self.path).await?;
+    #[test]
+    fn test_build_with_gateways() {
+        let mut builder = Ipfs::from_map(HashMap::from([
+            ("root".to_string(), format!("/ipfs/{CID_V0}")),
+            ("endpoint".to_string(), "https://ipfs.io/".to_string()),
+            (
+                "gateways".to_string(),
+                "https://dweb.link/, https://w3s.link".to_string(),
+            ),
+            ("gateway_timeout".to_string(), "3000".to_string()),
+            ("hedge_delay".to_string(), "500".to_string()),
+        ]));
+        let backend = builder.build().expect("build must succeed");
+        assert_eq!(backend.gateway_timeout, Some(Duration::from_secs(3)));
+        assert_eq!(backend.hedge_delay, Some(Duration::from_millis(500)));
+
+        assert_eq!(
+            backend.gateways,
+            vec!["https://ipfs.io", "https://dweb.link", "https://w3s.link"]
+        );
+
+        let mut builder = Ipfs::default();
+        builder.root(&format!("/ipfs/{CID_V0}"));
+        let err = builder
+            .build()
+            .expect_err("build without endpoint must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
 
-        if resp.status() != StatusCode::OK {
-            return Err(parse_error(resp).await?);
-        }
+    #[tokio::test]
+    async fn test_read_with_failed_gateways() {
+        let _ = env_logger::try_init();
+
+        let failed = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/ipfs/{CID_V1}/dir/file")))
+            .respond_with(ResponseTemplate::new(504))
+            .expect(1)
+            .mount(&failed)
+            .await;
+
+        let alive = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/ipfs/{CID_V1}/dir/file")))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .expect(1)
+            .mount(&alive)
+            .await;
+
+        let mut builder = Ipfs::default();
+        builder
+            .root(&format!("/ipfs/{CID_V1}/dir"))
+            .endpoint(&dead_endpoint())
+            .gateways(&[&failed.uri(), &alive.uri()]);
+        let op = Operator::new(builder).unwrap().finish();
 
-        let bs = resp.into_body().bytes().await?;
-        let pb_node = PBNode::decode(bs).map_err(|e| {
-            Error::new(ErrorKind::Unexpected, "deserialize protobuf from response").set_source(e)
-        })?;
+        let bs = op.read("file").await.expect("read must succeed");
+        assert_eq!(bs, b"Hello, World!");
+    }
 
-        let names = pb_node
-            .links
-            .into_iter()
-            .map(|v| v.name.unwrap())
-            .collect::<Vec<String>>();
+    #[tokio::test]
+    async fn test_read_with_gateway_timeout() {
+        let _ = env_logger::try_init();
 
-        let mut oes = Vec::with_capacity(names.len());
+        let slow = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("slow")
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&slow)
+            .await;
+
+        let alive = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/ipfs/{CID_V0}/file")))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .expect(1)
+            .mount(&alive)
+            .await;
+
+        let mut builder = Ipfs::default();
+        builder
+            .root(&format!("/ipfs/{CID_V0}"))
+            .endpoint(&slow.uri())
+            .gateways(&[&alive.uri()])
+            .gateway_timeout(Duration::from_millis(100));
+        let op = Operator::new(builder).unwrap().finish();
+
+        let bs = op.read("file").await.expect("read must succeed");
+        assert_eq!(bs, b"Hello, World!");
+    }
 
-        for mut name in names {
-            let meta = self
-                .backend
-                .stat(&name, OpStat::new())
-                .await?
-                .into_metadata();
+    #[tokio::test]
+    async fn test_read_with_hedged_gateways() {
+        let _ = env_logger::try_init();
 
-            if meta.mode().is_dir() {
-                name += "/";
-            }
+        let slow = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_string("slow")
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&slow)
+            .await;
+
+        let fast = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/ipfs/{CID_V0}/file")))
+            .respond_with(ResponseTemplate::new(200).set_body_string("fast"))
+            .expect(1)
+            .mount(&fast)
+            .await;
+
+        let mut builder = Ipfs::default();
+        builder
+            .root(&format!("/ipfs/{CID_V0}"))
+            .endpoint(&slow.uri())
+            .gateways(&[&fast.uri()])
+            .hedge_delay(Duration::from_millis(100));
+        let op = Operator::new(builder).unwrap().finish();
+
+        let start = std::time::Instant::now();
+        let bs = op.read("file").await.expect("read must succeed");
+        assert_eq!(bs, b"fast");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
 
-            oes.push(oio::Entry::new(&name, meta))
-        }
+    #[tokio::test]
+    async fn test_read_not_found_without_fallback() {
+        let _ = env_logger::try_init();
+
+        let first = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/ipfs/{CID_V0}/not_exist")))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&first)
+            .await;
+
+        let second = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&second)
+            .await;
+
+        let mut builder = Ipfs::default();
+        builder
+            .root(&format!("/ipfs/{CID_V0}"))
+            .endpoint(&first.uri())
+            .gateways(&[&second.uri()]);
+        let op = Operator::new(builder).unwrap().finish();
 
-        self.consumed = true;
-        Ok(Some(oes))
+        let err = op.read("not_exist").await.expect_err("read must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_read_with_all_gateways_failed() {
+        let _ = env_logger::try_init();
+
+        let mut builder = Ipfs::default();
+        builder
+            .root(&format!("/ipfs/{CID_V0}"))
+            .endpoint(&dead_endpoint())
+            .gateways(&[&dead_endpoint()]);
+        let op = Operator::new(builder).unwrap().finish();
+
+        let err = op.read("file").await.expect_err("read must fail");
+        assert!(err.is_temporary(), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_stat_via_api() {
+        let _ = env_logger::try_init();
+
+        let gateway = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&gateway)
+            .await;
+
+        let api = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v0/files/stat"))
+            .and(query_param("arg", format!("/ipfs/{CID_V1}/dir/file")))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"{"Hash":"QmdP6teFTLSNVhT4W5jkhEuUBsjQ3xkp1GmRvDU6937Me1","Size":262144,"CumulativeSize":262158,"Blocks":1,"Type":"file"}"#,
+            ))
+            .expect(1)
+            .mount(&api)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v0/files/stat"))
+            .and(query_param("arg", format!("/ipfs/{CID_V1}/dir/not_exist")))
+            .respond_with(ResponseTemplate::new(500).set_body_string(
+                r#"{"Message":"no link named \"not_exist\" under QmY44DyCDymRN1Qy7sGbupz1ysMkXTWomAQku5vBg7fRQW","Code":0,"Type":"error"}"#,
+            ))
+            .expect(1)
+            .mount(&api)
+            .await;
+
+        let mut builder = Ipfs::default();
+        builder
+            .root(&format!("/ipfs/{CID_V1}/dir"))
+            .endpoint(&gateway.uri())
+            .api_endpoint(&api.uri());
+        let op = Operator::new(builder).unwrap().finish();
+
+        let meta = op.stat("file").await.expect("stat must succeed");
+        assert!(meta.mode().is_file());
+        assert_eq!(meta.content_length(), 262144);
+        assert_eq!(
+            meta.etag(),
+            Some("QmdP6teFTLSNVhT4W5jkhEuUBsjQ3xkp1GmRvDU6937Me1")
+        );
+
+        let err = op.stat("not_exist").await.expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
     }
 }
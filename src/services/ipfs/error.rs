@@ -14,6 +14,8 @@
 
 use http::Response;
 use http::StatusCode;
+use serde::Deserialize;
+use serde_json::de;
 
 use crate::raw::*;
 use crate::Error;
@@ -25,20 +27,75 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
     let (parts, body) = resp.into_parts();
     let bs = body.bytes().await?;
 
+    // IPFS Gateway will return `408 REQUEST_TIMEOUT` while `ipfs resolve -r` failed,
+    // which will be retried.
+    let (kind, retryable) = parse_error_status(parts.status);
+
+    let mut err =
+        with_error_response_context(Error::new(kind, &String::from_utf8_lossy(&bs)), &parts);
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
+
+    if retryable {
+        err = err.set_temporary();
+    }
+
+    Ok(err)
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+struct IpfsApiError {
+    #[serde(rename = "Message")]
+    message: String,
+    #[serde(rename = "Code")]
+    code: usize,
+    #[serde(rename = "Type")]
+    ty: String,
+}
+
+/// Parse error response of ipfs node's RPC API into Error.
+///
+/// RPC API returns `500` for all errors while handling the request, we
+/// have to check the message to know whether the path is not found.
+///
+/// ref: https://docs.ipfs.tech/reference/kubo/rpc/#http-status-codes
+pub async fn parse_api_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
+    let (parts, body) = resp.into_parts();
+    let bs = body.bytes().await?;
+
+    let api_error = de::from_slice::<IpfsApiError>(&bs).ok();
+
     let (kind, retryable) = match parts.status {
-        StatusCode::NOT_FOUND => (ErrorKind::NotFound, false),
-        StatusCode::FORBIDDEN => (ErrorKind::PermissionDenied, false),
-        StatusCode::INTERNAL_SERVER_ERROR
-        | StatusCode::BAD_GATEWAY
-        | StatusCode::SERVICE_UNAVAILABLE
-        | StatusCode::GATEWAY_TIMEOUT
-        // IPFS Gateway will return `408 REQUEST_TIMEOUT` while `ipfs resolve -r` failed.
-        | StatusCode::REQUEST_TIMEOUT => (ErrorKind::Unexpected, true),
+        StatusCode::TOO_MANY_REQUESTS => (ErrorKind::RateLimited, true),
+        StatusCode::INTERNAL_SERVER_ERROR => match &api_error {
+            Some(e)
+                if e.message.starts_with("no link named")
+                    || e.message.contains("file does not exist")
+                    || e.message.contains("not found") =>
+            {
+                (ErrorKind::NotFound, false)
+            }
+            _ => (ErrorKind::Unexpected, false),
+        },
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => {
+            (ErrorKind::Unexpected, true)
+        }
         _ => (ErrorKind::Unexpected, false),
     };
 
-    let mut err = Error::new(kind, &String::from_utf8_lossy(&bs))
-        .with_context("response", format!("{parts:?}"));
+    let message = match api_error {
+        Some(api_error) => format!("{api_error:?}"),
+        None => String::from_utf8_lossy(&bs).into_owned(),
+    };
+
+    let mut err = with_error_response_context(Error::new(kind, &message), &parts);
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
 
     if retryable {
         err = err.set_temporary();
@@ -17,3 +17,4 @@ pub use backend::IpfsBuilder as Ipfs;
 
 mod error;
 mod ipld;
+mod pager;
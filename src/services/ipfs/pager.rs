@@ -0,0 +1,299 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use http::StatusCode;
+use prost::Message;
+use serde::Deserialize;
+
+use super::backend::IpfsBackend;
+use super::error::parse_api_error;
+use super::error::parse_error;
+use super::ipld::PBNode;
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// IpfsPager lists a dir in one page.
+///
+/// - With api endpoint, entries are returned by `/api/v0/ls` with their
+///   type and size.
+/// - Otherwise, the raw dag node of dir is fetched from gateways, every
+///   entry will be stat to know its type.
+pub struct IpfsPager {
+    backend: Arc<IpfsBackend>,
+    path: String,
+    consumed: bool,
+}
+
+impl IpfsPager {
+    pub fn new(backend: Arc<IpfsBackend>, path: &str) -> Self {
+        Self {
+            backend,
+            path: path.to_string(),
+            consumed: false,
+        }
+    }
+
+    /// Build the path of a child relative to root.
+    fn child_path(&self, name: &str) -> String {
+        if self.path == "/" {
+            name.to_string()
+        } else {
+            format!("{}{}", self.path, name)
+        }
+    }
+
+    async fn next_via_api(&self) -> Result<Vec<oio::Entry>> {
+        let resp = self.backend.ipfs_api_request("ls", &self.path).await?;
+
+        if resp.status() != StatusCode::OK {
+            return Err(parse_api_error(resp).await?);
+        }
+
+        let bs = resp.into_body().bytes().await?;
+        let res: IpfsLsResponse =
+            serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+        Ok(res
+            .objects
+            .into_iter()
+            .flat_map(|object| object.links)
+            .map(|link| match link.mode() {
+                EntryMode::DIR => oio::Entry::new(
+                    &format!("{}/", self.child_path(&link.name)),
+                    Metadata::new(EntryMode::DIR),
+                ),
+                mode => oio::Entry::new(
+                    &self.child_path(&link.name),
+                    Metadata::new(mode)
+                        .with_content_length(link.size)
+                        .with_etag(link.hash),
+                ),
+            })
+            .collect())
+    }
+
+    async fn next_via_gateway(&self) -> Result<Vec<oio::Entry>> {
+        let resp = self.backend.ipfs_list(&self.path).await?;
+
+        if resp.status() != StatusCode::OK {
+            return Err(parse_error(resp).await?);
+        }
+
+        let bs = resp.into_body().bytes().await?;
+        let pb_node = PBNode::decode(bs).map_err(|e| {
+            Error::new(ErrorKind::Unexpected, "deserialize protobuf from response").set_source(e)
+        })?;
+
+        let names = pb_node
+            .links
+            .into_iter()
+            .filter_map(|v| v.name)
+            .collect::<Vec<String>>();
+
+        let mut oes = Vec::with_capacity(names.len());
+
+        for name in names {
+            let mut path = self.child_path(&name);
+
+            let meta = self
+                .backend
+                .stat(&path, OpStat::new())
+                .await?
+                .into_metadata();
+
+            if meta.mode().is_dir() {
+                path += "/";
+            }
+
+            oes.push(oio::Entry::new(&path, meta))
+        }
+
+        Ok(oes)
+    }
+}
+
+#[async_trait]
+impl oio::Page for IpfsPager {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        if self.consumed {
+            return Ok(None);
+        }
+
+        let oes = if self.backend.has_api_endpoint() {
+            self.next_via_api().await?
+        } else {
+            self.next_via_gateway().await?
+        };
+
+        self.consumed = true;
+        Ok(Some(oes))
+    }
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+struct IpfsLsResponse {
+    #[serde(rename = "Objects")]
+    objects: Vec<IpfsLsObject>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+struct IpfsLsObject {
+    #[serde(rename = "Links")]
+    links: Vec<IpfsLsLink>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+struct IpfsLsLink {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Hash")]
+    hash: String,
+    #[serde(rename = "Size")]
+    size: u64,
+    #[serde(rename = "Type")]
+    file_type: i64,
+}
+
+impl IpfsLsLink {
+    /// ref: <https://github.com/ipfs/specs/blob/main/UNIXFS.md#data-format>
+    fn mode(&self) -> EntryMode {
+        match &self.file_type {
+            1 => EntryMode::DIR,
+            0 | 2 => EntryMode::FILE,
+            _ => EntryMode::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oio::Page;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::services::ipfs::ipld::PBLink;
+    use crate::services::Ipfs;
+
+    const CID: &str = "QmPpCt1aYGb9JWJRmXRUnmJtVgeFFTJGzWFYEEX7bo9zGJ";
+
+    #[tokio::test]
+    async fn test_list_via_gateway() {
+        let _ = env_logger::try_init();
+
+        let node = PBNode {
+            data: None,
+            links: vec![
+                PBLink {
+                    hash: None,
+                    name: Some("file".to_string()),
+                    tsize: Some(13),
+                },
+                PBLink {
+                    hash: None,
+                    name: Some("subdir".to_string()),
+                    tsize: Some(1024),
+                },
+            ],
+        };
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/ipfs/{CID}/dir/")))
+            .and(header("accept", "application/vnd.ipld.raw"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(node.encode_to_vec()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path(format!("/ipfs/{CID}/dir/file")))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-length", "13")
+                    .insert_header("etag", "\"QmdP6teFTLSNVhT4W5jkhEuUBsjQ3xkp1GmRvDU6937Me1\""),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path(format!("/ipfs/{CID}/dir/subdir")))
+            .respond_with(
+                ResponseTemplate::new(302)
+                    .insert_header("location", format!("/ipfs/{CID}/dir/subdir/").as_str()),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut builder = Ipfs::default();
+        builder
+            .root(&format!("/ipfs/{CID}"))
+            .endpoint(&server.uri());
+        let backend = builder.build().expect("build must succeed");
+
+        let mut pager = IpfsPager::new(Arc::new(backend), "dir/");
+        let entries = pager.next().await.unwrap().expect("page must exist");
+
+        let paths: Vec<_> = entries.iter().map(|e| e.path().to_string()).collect();
+        assert_eq!(paths, vec!["dir/file", "dir/subdir/"]);
+        assert_eq!(entries[0].metadata().content_length(), 13);
+        assert!(pager.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_via_api() {
+        let _ = env_logger::try_init();
+
+        let api = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v0/ls"))
+            .and(query_param("arg", format!("/ipfs/{CID}/dir/")))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+                r#"{{"Objects":[{{"Hash":"/ipfs/{CID}/dir/","Links":[
+                    {{"Name":"file","Hash":"QmdP6teFTLSNVhT4W5jkhEuUBsjQ3xkp1GmRvDU6937Me1","Size":13,"Type":2,"Target":""}},
+                    {{"Name":"subdir","Hash":"QmY44DyCDymRN1Qy7sGbupz1ysMkXTWomAQku5vBg7fRQW","Size":0,"Type":1,"Target":""}}
+                ]}}]}}"#
+            )))
+            .expect(1)
+            .mount(&api)
+            .await;
+
+        let mut builder = Ipfs::default();
+        builder
+            .root(&format!("/ipfs/{CID}"))
+            .endpoint("http://127.0.0.1:1")
+            .api_endpoint(&api.uri());
+        let backend = builder.build().expect("build must succeed");
+
+        let mut pager = IpfsPager::new(Arc::new(backend), "dir/");
+        let entries = pager.next().await.unwrap().expect("page must exist");
+
+        let paths: Vec<_> = entries.iter().map(|e| e.path().to_string()).collect();
+        assert_eq!(paths, vec!["dir/file", "dir/subdir/"]);
+        assert_eq!(entries[0].metadata().content_length(), 13);
+        assert!(entries[1].metadata().mode().is_dir());
+    }
+}
@@ -73,6 +73,18 @@ impl Accessor for IpmfsBackend {
             .set_capabilities(
                 AccessorCapability::Read | AccessorCapability::Write | AccessorCapability::List,
             )
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                write: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_delimiter: true,
+                ..Default::default()
+            })
             .set_hints(AccessorHint::ReadStreamable);
 
         am
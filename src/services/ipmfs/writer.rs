@@ -61,7 +61,11 @@ impl oio::Write for IpmfsWriter {
         ))
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn close(&mut self) -> Result<RpWrite> {
+        Ok(RpWrite::new())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
         Ok(())
     }
 }
@@ -70,26 +70,41 @@ where
         Ok(buffer)
     }
 
-    /// Set key to given value and don't wait for response.
+    /// Set key to given value.
+    ///
+    /// Errors like `SERVER_ERROR object too large for cache` will be
+    /// returned as [`ErrorKind::Other`] with the response line.
     pub async fn set<K: Display>(
         &mut self,
         key: K,
         val: &[u8],
         expiration: u32,
     ) -> Result<(), Error> {
-        let header = format!("set {} 0 {} {} noreply\r\n", key, expiration, val.len());
+        let header = format!("set {} 0 {} {}\r\n", key, expiration, val.len());
         self.io.write_all(header.as_bytes()).await?;
         self.io.write_all(val).await?;
         self.io.write_all(b"\r\n").await?;
         self.io.flush().await?;
+
+        let header = self.read_line().await?;
+        let header = std::str::from_utf8(header).map_err(|_| ErrorKind::InvalidData)?;
+        if !header.starts_with("STORED") {
+            return Err(Error::new(ErrorKind::Other, header.trim_end()));
+        }
         Ok(())
     }
 
-    /// Delete a key and don't wait for response.
+    /// Delete a key, it's ok if the key doesn't exist.
     pub async fn delete<K: Display>(&mut self, key: K) -> Result<(), Error> {
-        let header = format!("delete {} noreply\r\n", key);
+        let header = format!("delete {}\r\n", key);
         self.io.write_all(header.as_bytes()).await?;
         self.io.flush().await?;
+
+        let header = self.read_line().await?;
+        let header = std::str::from_utf8(header).map_err(|_| ErrorKind::InvalidData)?;
+        if !header.starts_with("DELETED") && !header.starts_with("NOT_FOUND") {
+            return Err(Error::new(ErrorKind::Other, header.trim_end()));
+        }
         Ok(())
     }
 
@@ -243,16 +258,43 @@ mod tests {
     fn test_ascii_set() {
         let (key, val, ttl) = ("foo", "bar", 5);
         let mut cache = Cache::new();
+        cache.r.get_mut().extend_from_slice(b"STORED\r\n");
         let mut ascii = super::Protocol::new(&mut cache);
         block_on(ascii.set(&key, val.as_bytes(), ttl)).unwrap();
         assert_eq!(
             cache.w.get_ref(),
-            &format!("set {} 0 {} {} noreply\r\n{}\r\n", key, ttl, val.len(), val)
+            &format!("set {} 0 {} {}\r\n{}\r\n", key, ttl, val.len(), val)
                 .as_bytes()
                 .to_vec()
         );
     }
 
+    #[test]
+    fn test_ascii_set_too_large() {
+        let mut cache = Cache::new();
+        cache
+            .r
+            .get_mut()
+            .extend_from_slice(b"SERVER_ERROR object too large for cache\r\n");
+        let mut ascii = super::Protocol::new(&mut cache);
+        let err = block_on(ascii.set(&"foo", b"bar", 0)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Other);
+        assert_eq!(err.to_string(), "SERVER_ERROR object too large for cache");
+    }
+
+    #[test]
+    fn test_ascii_delete() {
+        let mut cache = Cache::new();
+        cache
+            .r
+            .get_mut()
+            .extend_from_slice(b"DELETED\r\nNOT_FOUND\r\n");
+        let mut ascii = super::Protocol::new(&mut cache);
+        block_on(ascii.delete(&"foo")).unwrap();
+        block_on(ascii.delete(&"bar")).unwrap();
+        assert_eq!(cache.w.get_ref(), b"delete foo\r\ndelete bar\r\n");
+    }
+
     #[test]
     fn test_ascii_version() {
         let mut cache = Cache::new();
@@ -14,6 +14,8 @@
 
 use std::collections::HashMap;
 use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use async_compat::Compat;
 use async_trait::async_trait;
@@ -22,6 +24,7 @@ use tokio::net::TcpStream;
 use tokio::sync::OnceCell;
 
 use super::ascii;
+use super::ring::HashRing;
 use crate::raw::adapters::kv;
 use crate::raw::*;
 use crate::*;
@@ -35,15 +38,25 @@ use crate::*;
 /// - [x] read
 /// - [x] write
 /// - [ ] ~~list~~
-/// - [ ] scan
+/// - [ ] ~~scan~~
 /// - [ ] ~~presign~~
 /// - [ ] blocking
 ///
+/// # Notes
+///
+/// - `root` will be used as the namespace prefix of keys.
+/// - Keys will be distributed across multiple endpoints by consistent
+///   hashing, every endpoint has a small fixed connection pool.
+/// - Values larger than `max_value_size` (1MiB by default, the same as
+///   memcached's default item size limit) will be rejected before sending.
+///
 /// # Configuration
 ///
 /// - `root`: Set the working directory of `OpenDAL`
-/// - `endpoint`: Set the network address of memcached server
+/// - `endpoint`: Set the network address of memcached server, multiple
+///   endpoints can be separated by `,`
 /// - `default_ttl`: Set the ttl for memcached service.
+/// - `max_value_size`: Set the max size of values.
 ///
 /// You can refer to [`MemcachedBuilder`]'s docs for more information
 ///
@@ -80,12 +93,18 @@ pub struct MemcachedBuilder {
     root: Option<String>,
     /// The default ttl for put operations.
     default_ttl: Option<Duration>,
+    /// The max size of values.
+    max_value_size: Option<usize>,
 }
 
 impl MemcachedBuilder {
     /// set the network address of memcached service.
     ///
     /// For example: "tcp://localhost:11211"
+    ///
+    /// Multiple endpoints can be separated by `,`, and keys will be
+    /// distributed across them by consistent hashing. For example:
+    /// "tcp://10.0.0.1:11211,tcp://10.0.0.2:11211"
     pub fn endpoint(&mut self, endpoint: &str) -> &mut Self {
         if !endpoint.is_empty() {
             self.endpoint = Some(endpoint.to_owned());
@@ -108,6 +127,20 @@ impl MemcachedBuilder {
         self.default_ttl = Some(ttl);
         self
     }
+
+    /// Set the max size of values, writing values larger than it will
+    /// return an error directly.
+    ///
+    /// Please keep it the same as the item size limit of memcached server
+    /// (`-I`, `--max-item-size`).
+    ///
+    /// default: 1MiB
+    pub fn max_value_size(&mut self, size: usize) -> &mut Self {
+        if size > 0 {
+            self.max_value_size = Some(size);
+        }
+        self
+    }
 }
 
 impl Builder for MemcachedBuilder {
@@ -119,6 +152,12 @@ impl Builder for MemcachedBuilder {
 
         map.get("root").map(|v| builder.root(v));
         map.get("endpoint").map(|v| builder.endpoint(v));
+        map.get("default_ttl").map(|v| {
+            v.parse::<u64>()
+                .map(|v| builder.default_ttl(Duration::from_secs(v)))
+        });
+        map.get("max_value_size")
+            .map(|v| v.parse::<usize>().map(|v| builder.max_value_size(v)));
 
         builder
     }
@@ -128,49 +167,16 @@ impl Builder for MemcachedBuilder {
             Error::new(ErrorKind::ConfigInvalid, "endpoint is empty")
                 .with_context("service", Scheme::Memcached)
         })?;
-        let uri = http::Uri::try_from(&endpoint).map_err(|err| {
-            Error::new(ErrorKind::ConfigInvalid, "endpoint is invalid")
-                .with_context("service", Scheme::Memcached)
-                .with_context("endpoint", &endpoint)
-                .set_source(err)
-        })?;
-
-        match uri.scheme_str() {
-            // If scheme is none, we will use tcp by default.
-            None => (),
-            Some(scheme) => {
-                // We only support tcp by now.
-                if scheme != "tcp" {
-                    return Err(Error::new(
-                        ErrorKind::ConfigInvalid,
-                        "endpoint is using invalid scheme",
-                    )
-                    .with_context("service", Scheme::Memcached)
-                    .with_context("endpoint", &endpoint)
-                    .with_context("scheme", scheme.to_string()));
-                }
-            }
-        };
-
-        let host = if let Some(host) = uri.host() {
-            host.to_string()
-        } else {
-            return Err(
-                Error::new(ErrorKind::ConfigInvalid, "endpoint doesn't have host")
-                    .with_context("service", Scheme::Memcached)
-                    .with_context("endpoint", &endpoint),
-            );
-        };
-        let port = if let Some(port) = uri.port_u16() {
-            port
-        } else {
-            return Err(
-                Error::new(ErrorKind::ConfigInvalid, "endpoint doesn't have port")
-                    .with_context("service", Scheme::Memcached)
-                    .with_context("endpoint", &endpoint),
-            );
-        };
-        let endpoint = format!("{host}:{port}",);
+        let endpoints = endpoint
+            .split(',')
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(parse_memcached_endpoint)
+            .collect::<Result<Vec<_>>>()?;
+        if endpoints.is_empty() {
+            return Err(Error::new(ErrorKind::ConfigInvalid, "endpoint is empty")
+                .with_context("service", Scheme::Memcached));
+        }
 
         let root = normalize_root(
             self.root
@@ -179,37 +185,102 @@ impl Builder for MemcachedBuilder {
                 .as_str(),
         );
 
-        let conn = OnceCell::new();
         Ok(MemcachedBackend::new(Adapter {
-            endpoint,
-            conn,
+            ring: HashRing::new(&endpoints),
+            conns: endpoints.iter().map(|_| OnceCell::new()).collect(),
+            endpoints,
             default_ttl: self.default_ttl,
+            max_value_size: self.max_value_size.unwrap_or(DEFAULT_MAX_VALUE_SIZE),
         })
         .with_root(&root))
     }
 }
 
+/// Parse endpoint like `tcp://127.0.0.1:11211` into `127.0.0.1:11211`.
+fn parse_memcached_endpoint(endpoint: &str) -> Result<String> {
+    let uri = http::Uri::try_from(endpoint).map_err(|err| {
+        Error::new(ErrorKind::ConfigInvalid, "endpoint is invalid")
+            .with_context("service", Scheme::Memcached)
+            .with_context("endpoint", endpoint)
+            .set_source(err)
+    })?;
+
+    match uri.scheme_str() {
+        // If scheme is none, we will use tcp by default.
+        None | Some("tcp") => (),
+        // We only support tcp by now.
+        Some(scheme) => {
+            return Err(
+                Error::new(ErrorKind::ConfigInvalid, "endpoint is using invalid scheme")
+                    .with_context("service", Scheme::Memcached)
+                    .with_context("endpoint", endpoint)
+                    .with_context("scheme", scheme.to_string()),
+            );
+        }
+    };
+
+    let host = if let Some(host) = uri.host() {
+        host.to_string()
+    } else {
+        return Err(
+            Error::new(ErrorKind::ConfigInvalid, "endpoint doesn't have host")
+                .with_context("service", Scheme::Memcached)
+                .with_context("endpoint", endpoint),
+        );
+    };
+    let port = if let Some(port) = uri.port_u16() {
+        port
+    } else {
+        return Err(
+            Error::new(ErrorKind::ConfigInvalid, "endpoint doesn't have port")
+                .with_context("service", Scheme::Memcached)
+                .with_context("endpoint", endpoint),
+        );
+    };
+
+    Ok(format!("{host}:{port}"))
+}
+
 /// Backend for memcached services.
 pub type MemcachedBackend = kv::Backend<Adapter>;
 
+/// Memcached's default item size limit is 1MiB.
+const DEFAULT_MAX_VALUE_SIZE: usize = 1024 * 1024;
+/// Memcached's max key length.
+const MAX_KEY_LENGTH: usize = 250;
+/// Every endpoint has a small fixed connection pool.
+const POOL_MAX_SIZE: u32 = 8;
+/// Memcached treats expiration larger than 30 days as unix timestamp.
+const MAX_RELATIVE_EXPIRATION: u64 = 60 * 60 * 24 * 30;
+
 #[derive(Clone, Debug)]
 pub struct Adapter {
-    endpoint: String,
+    endpoints: Vec<String>,
+    ring: HashRing,
     default_ttl: Option<Duration>,
-    conn: OnceCell<bb8::Pool<MemcacheConnectionManager>>,
+    max_value_size: usize,
+    conns: Vec<OnceCell<bb8::Pool<MemcacheConnectionManager>>>,
 }
 
 impl Adapter {
-    async fn conn(&self) -> Result<bb8::PooledConnection<'_, MemcacheConnectionManager>> {
-        let pool = self
-            .conn
+    /// Get a connection to the endpoint that owns this key.
+    async fn conn(
+        &self,
+        key: &str,
+    ) -> Result<bb8::PooledConnection<'_, MemcacheConnectionManager>> {
+        let idx = self.ring.get(key);
+        let pool = self.conns[idx]
             .get_or_try_init(|| async {
-                let mgr = MemcacheConnectionManager::new(&self.endpoint);
-
-                bb8::Pool::builder().build(mgr).await.map_err(|err| {
-                    Error::new(ErrorKind::ConfigInvalid, "connect to memecached failed")
-                        .set_source(err)
-                })
+                let mgr = MemcacheConnectionManager::new(&self.endpoints[idx]);
+
+                bb8::Pool::builder()
+                    .max_size(POOL_MAX_SIZE)
+                    .build(mgr)
+                    .await
+                    .map_err(|err| {
+                        Error::new(ErrorKind::ConfigInvalid, "connect to memecached failed")
+                            .set_source(err)
+                    })
             })
             .await?;
 
@@ -233,9 +304,10 @@ impl kv::Adapter for Adapter {
     }
 
     async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
-        let mut conn = self.conn().await?;
+        let key = encode_key(key)?;
+        let mut conn = self.conn(&key).await?;
         // TODO: memcache-async have `Sized` limit on key, can we remove it?
-        match conn.get(&percent_encode_path(key)).await {
+        match conn.get(&key).await {
             Ok(bs) => Ok(Some(bs)),
             Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
             Err(err) => Err(parse_io_error(err)),
@@ -243,33 +315,85 @@ impl kv::Adapter for Adapter {
     }
 
     async fn set(&self, key: &str, value: &[u8]) -> Result<()> {
-        let mut conn = self.conn().await?;
-
-        conn.set(
-            &percent_encode_path(key),
-            value,
-            // Set expiration to 0 if ttl not set.
-            self.default_ttl
-                .map(|v| v.as_secs() as u32)
-                .unwrap_or_default(),
-        )
-        .await
-        .map_err(parse_io_error)?;
+        if value.len() > self.max_value_size {
+            return Err(new_value_too_large_error(value.len(), self.max_value_size)
+                .with_context("key", key));
+        }
+
+        let key = encode_key(key)?;
+        let mut conn = self.conn(&key).await?;
+
+        conn.set(&key, value, self.expiration())
+            .await
+            .map_err(|err| {
+                if err.to_string().contains("too large") {
+                    new_value_too_large_error(value.len(), self.max_value_size)
+                        .with_context("key", &key)
+                        .set_source(err)
+                } else {
+                    parse_io_error(err)
+                }
+            })?;
 
         Ok(())
     }
 
     async fn delete(&self, key: &str) -> Result<()> {
-        let mut conn = self.conn().await?;
+        let key = encode_key(key)?;
+        let mut conn = self.conn(&key).await?;
 
-        let _: () = conn
-            .delete(&percent_encode_path(key))
-            .await
-            .map_err(parse_io_error)?;
+        let _: () = conn.delete(&key).await.map_err(parse_io_error)?;
         Ok(())
     }
 }
 
+impl Adapter {
+    /// Build the expiration for `set`, 0 means never expire.
+    fn expiration(&self) -> u32 {
+        let ttl = match self.default_ttl {
+            Some(ttl) => ttl.as_secs(),
+            None => return 0,
+        };
+
+        // Expiration larger than 30 days will be treated as unix timestamp.
+        if ttl > MAX_RELATIVE_EXPIRATION {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("system time must be later than unix epoch")
+                .as_secs();
+            (now + ttl) as u32
+        } else {
+            ttl as u32
+        }
+    }
+}
+
+/// Encode key so that it doesn't contain whitespace and control chars, and
+/// check the length of key.
+fn encode_key(key: &str) -> Result<String> {
+    let encoded = percent_encode_path(key);
+    if encoded.len() > MAX_KEY_LENGTH {
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            "key is longer than memcached's limit",
+        )
+        .with_context("service", Scheme::Memcached)
+        .with_context("key", key)
+        .with_context("limit", MAX_KEY_LENGTH.to_string()));
+    }
+    Ok(encoded)
+}
+
+fn new_value_too_large_error(size: usize, limit: usize) -> Error {
+    Error::new(
+        ErrorKind::Unsupported,
+        "value is larger than memcached's item size limit",
+    )
+    .with_context("service", Scheme::Memcached)
+    .with_context("size", size.to_string())
+    .with_context("limit", limit.to_string())
+}
+
 fn parse_io_error(err: std::io::Error) -> Error {
     use std::io::ErrorKind::*;
 
@@ -325,3 +449,98 @@ impl bb8::ManageConnection for MemcacheConnectionManager {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::adapters::kv::Adapter as _;
+
+    fn new_adapter(endpoint: &str) -> Adapter {
+        let endpoints: Vec<String> = endpoint
+            .split(',')
+            .map(|v| parse_memcached_endpoint(v).unwrap())
+            .collect();
+
+        Adapter {
+            ring: HashRing::new(&endpoints),
+            conns: endpoints.iter().map(|_| OnceCell::new()).collect(),
+            endpoints,
+            default_ttl: None,
+            max_value_size: 4,
+        }
+    }
+
+    #[test]
+    fn test_parse_endpoint() {
+        assert_eq!(
+            parse_memcached_endpoint("tcp://127.0.0.1:11211").unwrap(),
+            "127.0.0.1:11211"
+        );
+        assert_eq!(
+            parse_memcached_endpoint("localhost:11211").unwrap(),
+            "localhost:11211"
+        );
+        assert!(parse_memcached_endpoint("udp://127.0.0.1:11211").is_err());
+        assert!(parse_memcached_endpoint("tcp://127.0.0.1").is_err());
+    }
+
+    #[test]
+    fn test_build_with_multiple_endpoints() {
+        let mut builder = MemcachedBuilder::default();
+        builder.endpoint("tcp://10.0.0.1:11211, tcp://10.0.0.2:11211");
+        assert!(builder.build().is_ok());
+
+        let mut builder = MemcachedBuilder::default();
+        builder.endpoint("tcp://10.0.0.1:11211,udp://10.0.0.2:11211");
+        assert!(builder.build().is_err());
+
+        let mut builder = MemcachedBuilder::default();
+        builder.endpoint(",");
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn test_from_map() {
+        let mut map = HashMap::new();
+        map.insert("endpoint".to_string(), "tcp://127.0.0.1:11211".to_string());
+        map.insert("default_ttl".to_string(), "60".to_string());
+        map.insert("max_value_size".to_string(), "2048".to_string());
+
+        let builder = MemcachedBuilder::from_map(map);
+        assert_eq!(builder.default_ttl, Some(Duration::from_secs(60)));
+        assert_eq!(builder.max_value_size, Some(2048));
+    }
+
+    #[test]
+    fn test_encode_key() {
+        assert_eq!(encode_key("path/to/a b").unwrap(), "path/to/a%20b");
+
+        let err = encode_key(&"a".repeat(MAX_KEY_LENGTH + 1)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn test_expiration() {
+        let mut adapter = new_adapter("tcp://127.0.0.1:11211");
+        assert_eq!(adapter.expiration(), 0);
+
+        adapter.default_ttl = Some(Duration::from_secs(60));
+        assert_eq!(adapter.expiration(), 60);
+
+        // Long ttl will be converted into unix timestamp.
+        adapter.default_ttl = Some(Duration::from_secs(MAX_RELATIVE_EXPIRATION + 1));
+        assert!(adapter.expiration() as u64 > MAX_RELATIVE_EXPIRATION + 1);
+    }
+
+    #[tokio::test]
+    async fn test_set_too_large() {
+        let adapter = new_adapter("tcp://127.0.0.1:11211");
+
+        // The size guard must be checked before connecting to server.
+        let err = adapter
+            .set("key", b"hello")
+            .await
+            .expect_err("value larger than limit must be rejected");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+}
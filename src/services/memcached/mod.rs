@@ -16,3 +16,4 @@ mod backend;
 pub use backend::MemcachedBuilder as Memcached;
 
 mod ascii;
+mod ring;
@@ -0,0 +1,106 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use md5::Digest;
+use md5::Md5;
+
+/// Virtual nodes for every server, every md5 digest generates 4 points.
+const POINTS_PER_SERVER: usize = 160;
+
+/// HashRing distributes keys across servers with ketama style consistent
+/// hashing, so that only keys of the removed server will be remapped while
+/// servers changed.
+#[derive(Clone, Debug)]
+pub struct HashRing {
+    points: Vec<(u32, usize)>,
+}
+
+impl HashRing {
+    /// Build a ring for given servers, the returned value of [`HashRing::get`]
+    /// is the index of the server.
+    pub fn new<S: AsRef<str>>(servers: &[S]) -> Self {
+        let mut points = Vec::with_capacity(servers.len() * POINTS_PER_SERVER);
+
+        for (idx, server) in servers.iter().enumerate() {
+            for i in 0..POINTS_PER_SERVER / 4 {
+                let digest = Md5::digest(format!("{}-{}", server.as_ref(), i).as_bytes());
+                for chunk in digest.chunks(4) {
+                    let point = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+                    points.push((point, idx));
+                }
+            }
+        }
+        points.sort_unstable();
+
+        Self { points }
+    }
+
+    /// Get the index of server for given key.
+    pub fn get(&self, key: &str) -> usize {
+        if self.points.is_empty() {
+            return 0;
+        }
+
+        let digest = Md5::digest(key.as_bytes());
+        let hash = u32::from_le_bytes([digest[0], digest[1], digest[2], digest[3]]);
+
+        let idx = match self.points.binary_search_by(|(p, _)| p.cmp(&hash)) {
+            Ok(idx) => idx,
+            Err(idx) if idx == self.points.len() => 0,
+            Err(idx) => idx,
+        };
+        self.points[idx].1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_single_server() {
+        let ring = HashRing::new(&["127.0.0.1:11211"]);
+        for i in 0..100 {
+            assert_eq!(ring.get(&format!("key-{i}")), 0);
+        }
+    }
+
+    #[test]
+    fn test_distribution() {
+        let ring = HashRing::new(&["10.0.0.1:11211", "10.0.0.2:11211", "10.0.0.3:11211"]);
+
+        let used: HashSet<_> = (0..1000).map(|i| ring.get(&format!("key-{i}"))).collect();
+        assert_eq!(used.len(), 3, "all servers must be used");
+
+        // Same key always goes to the same server.
+        assert_eq!(ring.get("path/to/file"), ring.get("path/to/file"));
+    }
+
+    #[test]
+    fn test_remove_server() {
+        let full = HashRing::new(&["10.0.0.1:11211", "10.0.0.2:11211", "10.0.0.3:11211"]);
+        let removed = HashRing::new(&["10.0.0.1:11211", "10.0.0.2:11211"]);
+
+        for i in 0..1000 {
+            let key = format!("key-{i}");
+            let idx = full.get(&key);
+            // Only keys of the removed server will be remapped.
+            if idx != 2 {
+                assert_eq!(removed.get(&key), idx, "key {key} must not be remapped");
+            }
+        }
+    }
+}
@@ -14,11 +14,17 @@
 
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::io::SeekFrom;
 use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
 
 use async_trait::async_trait;
+use bytes::Bytes;
+use log::debug;
 use parking_lot::Mutex;
 
+use crate::ops::*;
 use crate::raw::adapters::kv;
 use crate::raw::*;
 use crate::*;
@@ -35,32 +41,389 @@ use crate::*;
 /// - [x] scan
 /// - [ ] ~~presign~~
 /// - [x] blocking
+///
+/// # Configuration
+///
+/// - `max_bytes`: Set the max total bytes of all entries
+/// - `max_entries`: Set the max count of entries
+///
+/// # Eviction
+///
+/// Memory is unbounded by default. If `max_bytes` or `max_entries` is set,
+/// the least recently used entries will be evicted after writing to keep
+/// the usage under limits. Both reads and writes count as use.
+///
+/// Entries that have open readers will never be evicted, so the usage
+/// could exceed the limits until these readers are dropped.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::services::Memory;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let mut builder = Memory::default();
+///     builder
+///         .max_entries(1024)
+///         .populate([("dir/file", "Hello, World!")]);
+///     let handle = builder.handle();
+///
+///     let op: Operator = Operator::new(builder)?.finish();
+///     op.write("dir/another", "Hello, OpenDAL!").await?;
+///
+///     let snapshot = handle.snapshot();
+///     assert_eq!(snapshot["dir/file"], "Hello, World!");
+///     assert_eq!(snapshot["dir/another"], "Hello, OpenDAL!");
+///     Ok(())
+/// }
+/// ```
 #[derive(Default)]
-pub struct MemoryBuilder {}
+pub struct MemoryBuilder {
+    max_bytes: Option<usize>,
+    max_entries: Option<usize>,
+
+    store: Arc<Mutex<Store>>,
+}
+
+impl MemoryBuilder {
+    /// Set the max total bytes of all entries.
+    ///
+    /// Writing content that larger than `max_bytes` will return an error.
+    pub fn max_bytes(&mut self, max_bytes: usize) -> &mut Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Set the max count of entries, dirs are counted as entries too.
+    pub fn max_entries(&mut self, max_entries: usize) -> &mut Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Pre-populate entries from given iterator.
+    ///
+    /// Paths are relative to root like other operations, existing entries
+    /// will be overwritten.
+    pub fn populate<K, V>(&mut self, entries: impl IntoIterator<Item = (K, V)>) -> &mut Self
+    where
+        K: AsRef<str>,
+        V: Into<Bytes>,
+    {
+        let mut store = self.store.lock();
+        for (path, value) in entries {
+            let path = build_abs_path("/", &normalize_path(path.as_ref()));
+            store.insert(path, value.into());
+        }
+        drop(store);
+
+        self
+    }
+
+    /// Get a handle to the content of backends built by this builder.
+    ///
+    /// Backends built by the same builder share the same content.
+    pub fn handle(&self) -> MemoryHandle {
+        MemoryHandle {
+            store: self.store.clone(),
+        }
+    }
+}
 
 impl Builder for MemoryBuilder {
     const SCHEME: Scheme = Scheme::Memory;
     type Accessor = MemoryBackend;
 
-    fn from_map(_: HashMap<String, String>) -> Self {
-        Self::default()
+    fn from_map(map: HashMap<String, String>) -> Self {
+        let mut builder = MemoryBuilder::default();
+
+        map.get("max_bytes")
+            .map(|v| v.parse::<usize>().map(|v| builder.max_bytes(v)));
+        map.get("max_entries")
+            .map(|v| v.parse::<usize>().map(|v| builder.max_entries(v)));
+
+        builder
     }
 
     fn build(&mut self) -> Result<Self::Accessor> {
+        {
+            let mut store = self.store.lock();
+            store.max_bytes = self.max_bytes;
+            store.max_entries = self.max_entries;
+            // Pre-populated entries could exceed the limits.
+            store.evict(None);
+        }
+
         let adapter = Adapter {
-            inner: Arc::new(Mutex::new(BTreeMap::default())),
+            inner: self.store.clone(),
         };
 
-        Ok(MemoryBackend::new(adapter))
+        Ok(MemoryBackend {
+            inner: kv::Backend::new(adapter),
+            store: self.store.clone(),
+        })
+    }
+}
+
+/// MemoryHandle is used to access the content of memory backend directly.
+#[derive(Debug, Clone)]
+pub struct MemoryHandle {
+    store: Arc<Mutex<Store>>,
+}
+
+impl MemoryHandle {
+    /// Snapshot the current content, keys are paths without leading `/`.
+    ///
+    /// Snapshot doesn't count as use of entries.
+    pub fn snapshot(&self) -> HashMap<String, Bytes> {
+        self.store
+            .lock()
+            .entries
+            .iter()
+            .map(|(k, v)| (k.clone(), v.value.clone()))
+            .collect()
+    }
+
+    /// Get the total bytes of all entries.
+    pub fn total_bytes(&self) -> usize {
+        self.store.lock().total_bytes
+    }
+}
+
+#[derive(Debug, Default)]
+struct Store {
+    entries: BTreeMap<String, Entry>,
+    /// Access order of entries, smaller tick means less recently used.
+    lru: BTreeMap<u64, String>,
+    tick: u64,
+    total_bytes: usize,
+    /// Count of open readers of every path.
+    readers: HashMap<String, usize>,
+
+    max_bytes: Option<usize>,
+    max_entries: Option<usize>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    value: Bytes,
+    tick: u64,
+}
+
+impl Store {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn get(&mut self, path: &str) -> Option<Bytes> {
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(path)?;
+
+        self.lru.remove(&entry.tick);
+        self.lru.insert(tick, path.to_string());
+        entry.tick = tick;
+
+        Some(entry.value.clone())
+    }
+
+    fn insert(&mut self, path: String, value: Bytes) {
+        self.remove(&path);
+
+        let tick = self.next_tick();
+        self.total_bytes += value.len();
+        self.lru.insert(tick, path.clone());
+        self.entries.insert(path, Entry { value, tick });
+    }
+
+    fn remove(&mut self, path: &str) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.total_bytes -= entry.value.len();
+            self.lru.remove(&entry.tick);
+        }
+    }
+
+    fn is_over_limits(&self) -> bool {
+        matches!(self.max_bytes, Some(v) if self.total_bytes > v)
+            || matches!(self.max_entries, Some(v) if self.entries.len() > v)
+    }
+
+    /// Evict least recently used entries until usage is under limits.
+    ///
+    /// `keep` and entries with open readers will not be evicted.
+    fn evict(&mut self, keep: Option<&str>) {
+        while self.is_over_limits() {
+            let victim = self
+                .lru
+                .values()
+                .find(|path| Some(path.as_str()) != keep && !self.readers.contains_key(*path))
+                .cloned();
+
+            match victim {
+                Some(path) => {
+                    debug!("memory evict entry {path}");
+                    self.remove(&path)
+                }
+                None => {
+                    debug!("memory is over limits but all entries are in use");
+                    break;
+                }
+            }
+        }
+    }
+
+    fn pin(&mut self, path: &str) {
+        *self.readers.entry(path.to_string()).or_default() += 1;
+    }
+
+    fn unpin(&mut self, path: &str) {
+        if let Some(count) = self.readers.get_mut(path) {
+            *count -= 1;
+            if *count == 0 {
+                self.readers.remove(path);
+            }
+        }
     }
 }
 
 /// Backend is used to serve `Accessor` support in memory.
-pub type MemoryBackend = kv::Backend<Adapter>;
+#[derive(Debug, Clone)]
+pub struct MemoryBackend {
+    inner: kv::Backend<Adapter>,
+    store: Arc<Mutex<Store>>,
+}
+
+#[async_trait]
+impl LayeredAccessor for MemoryBackend {
+    type Inner = kv::Backend<Adapter>;
+    type Reader = MemoryReader<oio::Cursor>;
+    type BlockingReader = MemoryReader<oio::Cursor>;
+    type Writer = kv::KvWriter<Adapter>;
+    type BlockingWriter = kv::KvWriter<Adapter>;
+    type Pager = kv::KvPager;
+    type BlockingPager = kv::KvPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn layer_name(&self) -> Option<&'static str> {
+        None
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        // Pin the entry before reading so that it can't be evicted in between.
+        let guard = ReaderGuard::new(self.store.clone(), path);
+
+        self.inner.read(path, args).await.map(|(rp, r)| {
+            (
+                rp,
+                MemoryReader {
+                    inner: r,
+                    _guard: guard,
+                },
+            )
+        })
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.inner.write(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        self.inner.scan(path, args).await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let guard = ReaderGuard::new(self.store.clone(), path);
+
+        self.inner.blocking_read(path, args).map(|(rp, r)| {
+            (
+                rp,
+                MemoryReader {
+                    inner: r,
+                    _guard: guard,
+                },
+            )
+        })
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        self.inner.blocking_scan(path, args)
+    }
+}
+
+/// ReaderGuard pins the entry until the reader is dropped.
+struct ReaderGuard {
+    store: Arc<Mutex<Store>>,
+    path: String,
+}
+
+impl ReaderGuard {
+    fn new(store: Arc<Mutex<Store>>, path: &str) -> Self {
+        let path = build_abs_path("/", path);
+        store.lock().pin(&path);
+
+        Self { store, path }
+    }
+}
+
+impl Drop for ReaderGuard {
+    fn drop(&mut self) {
+        self.store.lock().unpin(&self.path);
+    }
+}
+
+pub struct MemoryReader<R> {
+    inner: R,
+    _guard: ReaderGuard,
+}
+
+impl<R: oio::Read> oio::Read for MemoryReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        self.inner.poll_read(cx, buf)
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+        self.inner.poll_seek(cx, pos)
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        self.inner.poll_next(cx)
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for MemoryReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.inner.read(buf)
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        self.inner.seek(pos)
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        self.inner.next()
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Adapter {
-    inner: Arc<Mutex<BTreeMap<String, Vec<u8>>>>,
+    inner: Arc<Mutex<Store>>,
 }
 
 #[async_trait]
@@ -68,7 +431,7 @@ impl kv::Adapter for Adapter {
     fn metadata(&self) -> kv::Metadata {
         kv::Metadata::new(
             Scheme::Memory,
-            &format!("{:?}", &self.inner as *const _),
+            &format!("{:?}", Arc::as_ptr(&self.inner)),
             AccessorCapability::Read | AccessorCapability::Write | AccessorCapability::Scan,
         )
     }
@@ -89,7 +452,17 @@ impl kv::Adapter for Adapter {
     }
 
     fn blocking_set(&self, path: &str, value: &[u8]) -> Result<()> {
-        self.inner.lock().insert(path.to_string(), value.to_vec());
+        let mut inner = self.inner.lock();
+
+        if matches!(inner.max_bytes, Some(v) if value.len() > v) {
+            return Err(
+                Error::new(ErrorKind::Unexpected, "content is larger than max bytes")
+                    .with_context("size", value.len().to_string()),
+            );
+        }
+
+        inner.insert(path.to_string(), Bytes::copy_from_slice(value));
+        inner.evict(Some(path));
 
         Ok(())
     }
@@ -111,10 +484,11 @@ impl kv::Adapter for Adapter {
     fn blocking_scan(&self, path: &str) -> Result<Vec<String>> {
         let inner = self.inner.lock();
         let keys: Vec<_> = if path.is_empty() {
-            inner.keys().cloned().collect()
+            inner.entries.keys().cloned().collect()
         } else {
             let right_range = format!("{}0", &path[..path.len() - 1]);
             inner
+                .entries
                 .range(path.to_string()..right_range)
                 .map(|(k, _)| k.to_string())
                 .collect()
@@ -136,4 +510,185 @@ mod tests {
         let b2 = MemoryBuilder::default().build().unwrap();
         assert_ne!(b1.info().name(), b2.info().name())
     }
+
+    #[test]
+    fn test_capability() {
+        let op = Operator::new(MemoryBuilder::default()).unwrap().finish();
+
+        assert_eq!(
+            op.info().capability(),
+            Capability {
+                stat: true,
+                read: true,
+                read_can_seek: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                scan: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        let op = Operator::new(MemoryBuilder::default()).unwrap().finish();
+        op.check().await.expect("check must succeed");
+
+        op.write("file", "Hello, World!")
+            .await
+            .expect("write must succeed");
+        op.check().await.expect("check must succeed");
+        op.blocking().check().expect("blocking check must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_write_with_if_not_exists_unsupported() {
+        let op = Operator::new(MemoryBuilder::default()).unwrap().finish();
+
+        let err = op
+            .write_with(
+                "file",
+                OpWrite::new().with_if_not_exists(true),
+                "Hello, World!",
+            )
+            .await
+            .expect_err("write with if_not_exists must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert!(!op.is_exist("file").await.expect("stat must succeed"));
+    }
+
+    #[tokio::test]
+    async fn test_write_with_if_match_unsupported() {
+        let op = Operator::new(MemoryBuilder::default()).unwrap().finish();
+        op.write("file", "Hello, World!")
+            .await
+            .expect("write must succeed");
+
+        let err = op
+            .write_with("file", OpWrite::new().with_if_match("\"etag\""), "Bye")
+            .await
+            .expect_err("write with if_match must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert_eq!(
+            op.read("file").await.expect("read must succeed"),
+            b"Hello, World!"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tags_unsupported() {
+        let op = Operator::new(MemoryBuilder::default()).unwrap().finish();
+
+        let tags = HashMap::from([("k".to_string(), "v".to_string())]);
+        let err = op
+            .write_with("file", OpWrite::new().with_tags(tags), "Hello, World!")
+            .await
+            .expect_err("write with tags must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        op.write("file", "Hello, World!")
+            .await
+            .expect("write must succeed");
+        let err = op
+            .stat_with("file", OpStat::new().with_tags(true))
+            .await
+            .expect_err("stat with tags must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    #[tokio::test]
+    async fn test_evict_least_recently_used() {
+        let mut builder = MemoryBuilder::default();
+        builder.max_entries(2);
+        let handle = builder.handle();
+        let op = Operator::new(builder).unwrap().finish();
+
+        op.write("a", "a").await.expect("write must succeed");
+        op.write("b", "b").await.expect("write must succeed");
+        // Read `a` so that `b` becomes the least recently used.
+        op.read("a").await.expect("read must succeed");
+        op.write("c", "c").await.expect("write must succeed");
+
+        let mut keys: Vec<_> = handle.snapshot().into_keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["a", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_evict_by_max_bytes() {
+        let mut builder = MemoryBuilder::default();
+        builder.max_bytes(10);
+        let handle = builder.handle();
+        let op = Operator::new(builder).unwrap().finish();
+
+        op.write("a", vec![0; 4]).await.expect("write must succeed");
+        op.write("b", vec![0; 4]).await.expect("write must succeed");
+        op.write("c", vec![0; 4]).await.expect("write must succeed");
+
+        let mut keys: Vec<_> = handle.snapshot().into_keys().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["b", "c"]);
+        assert_eq!(handle.total_bytes(), 8);
+
+        let err = op
+            .write("d", vec![0; 11])
+            .await
+            .expect_err("write must fail");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+    }
+
+    #[tokio::test]
+    async fn test_never_evict_entries_with_open_reader() {
+        let mut builder = MemoryBuilder::default();
+        builder.max_entries(1);
+        let handle = builder.handle();
+        let op = Operator::new(builder).unwrap().finish();
+
+        op.write("a", "Hello, World!")
+            .await
+            .expect("write must succeed");
+        let r = op.reader("a").await.expect("reader must succeed");
+
+        // `a` is pinned by reader, so memory is over limits now.
+        op.write("b", "b").await.expect("write must succeed");
+        assert_eq!(handle.snapshot().len(), 2);
+        assert_eq!(
+            op.read("a").await.expect("read must succeed"),
+            b"Hello, World!"
+        );
+
+        drop(r);
+        op.write("c", "c").await.expect("write must succeed");
+        let keys: Vec<_> = handle.snapshot().into_keys().collect();
+        assert_eq!(keys, vec!["c"]);
+    }
+
+    #[tokio::test]
+    async fn test_populate_and_snapshot() {
+        let entries = HashMap::from([
+            ("file".to_string(), Bytes::from("Hello, World!")),
+            ("dir/file".to_string(), Bytes::from("Hello, OpenDAL!")),
+            ("dir/empty".to_string(), Bytes::new()),
+        ]);
+
+        let mut builder = MemoryBuilder::default();
+        builder.populate(entries.clone());
+        let handle = builder.handle();
+        let op = Operator::new(builder).unwrap().finish();
+
+        for (path, content) in &entries {
+            let bs = op.read(path).await.expect("read must succeed");
+            assert_eq!(bs, content.as_ref());
+        }
+        assert_eq!(handle.snapshot(), entries);
+
+        op.delete("file").await.expect("delete must succeed");
+        assert!(!handle.snapshot().contains_key("file"));
+    }
 }
@@ -14,3 +14,4 @@
 
 mod backend;
 pub use backend::MemoryBuilder as Memory;
+pub use backend::MemoryHandle;
@@ -22,11 +22,32 @@ pub use azblob::Azblob;
 mod azdfs;
 pub use azdfs::Azdfs;
 
+#[cfg(feature = "services-b2")]
+mod b2;
+#[cfg(feature = "services-b2")]
+pub use b2::B2;
+
+mod cachefs;
+pub use cachefs::CacheFs;
+
+mod cos;
+pub use cos::Cos;
+
 #[cfg(feature = "services-dashmap")]
 mod dashmap;
 #[cfg(feature = "services-dashmap")]
 pub use self::dashmap::Dashmap;
 
+#[cfg(feature = "services-dropbox")]
+mod dropbox;
+#[cfg(feature = "services-dropbox")]
+pub use dropbox::Dropbox;
+
+#[cfg(feature = "services-etcd")]
+mod etcd;
+#[cfg(feature = "services-etcd")]
+pub use self::etcd::Etcd;
+
 mod fs;
 pub use fs::Fs;
 
@@ -38,6 +59,11 @@ pub use ftp::Ftp;
 mod gcs;
 pub use gcs::Gcs;
 
+#[cfg(feature = "services-gdrive")]
+mod gdrive;
+#[cfg(feature = "services-gdrive")]
+pub use gdrive::Gdrive;
+
 mod ghac;
 pub use ghac::Ghac;
 
@@ -64,6 +90,7 @@ pub use memcached::Memcached;
 
 mod memory;
 pub use memory::Memory;
+pub use memory::MemoryHandle;
 
 #[cfg(feature = "services-moka")]
 mod moka;
@@ -73,8 +100,15 @@ pub use self::moka::Moka;
 mod obs;
 pub use obs::Obs;
 
+#[cfg(feature = "services-onedrive")]
+mod onedrive;
+#[cfg(feature = "services-onedrive")]
+pub use onedrive::Onedrive;
+
 mod oss;
 pub use oss::Oss;
+pub use oss::OssCredential;
+pub use oss::OssCredentialLoad;
 
 #[cfg(feature = "services-redis")]
 mod redis;
@@ -89,11 +123,21 @@ pub use self::rocksdb::Rocksdb;
 mod s3;
 pub use s3::S3;
 
+#[cfg(feature = "services-sftp")]
+mod sftp;
+#[cfg(feature = "services-sftp")]
+pub use sftp::Sftp;
+
 #[cfg(feature = "services-sled")]
 mod sled;
 #[cfg(feature = "services-sled")]
 pub use self::sled::Sled;
 
+#[cfg(feature = "services-supabase")]
+mod supabase;
+#[cfg(feature = "services-supabase")]
+pub use supabase::Supabase;
+
 mod webdav;
 pub use webdav::Webdav;
 
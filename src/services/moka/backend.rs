@@ -18,8 +18,7 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use log::debug;
-use moka::sync::CacheBuilder;
-use moka::sync::SegmentedCache;
+use moka::future::Cache;
 
 use crate::raw::adapters::kv;
 use crate::raw::*;
@@ -34,33 +33,33 @@ use crate::*;
 /// - [x] read
 /// - [x] write
 /// - [ ] ~~list~~
-/// - [ ] ~~scan~~
+/// - [x] scan
 /// - [ ] ~~presign~~
 /// - [x] blocking
+///
+/// # Notes
+///
+/// - `max_capacity` is measured in bytes of keys and values.
+/// - Entries that exceed `time_to_live` or `time_to_idle` will be treated as
+///   not found immediately, although they are removed in background.
+/// - `scan` is best-effort: it iterates the cache and doesn't provide a
+///   consistent snapshot while the cache is being modified.
 #[derive(Default, Debug)]
 pub struct MokaBuilder {
     /// Name for this cache instance.
     name: Option<String>,
     /// Sets the max capacity of the cache.
     ///
-    /// Refer to [`moka::sync::CacheBuilder::max_capacity`](https://docs.rs/moka/latest/moka/sync/struct.CacheBuilder.html#method.max_capacity)
+    /// Refer to [`moka::future::CacheBuilder::max_capacity`](https://docs.rs/moka/latest/moka/future/struct.CacheBuilder.html#method.max_capacity)
     max_capacity: Option<u64>,
     /// Sets the time to live of the cache.
     ///
-    /// Refer to [`moka::sync::CacheBuilder::time_to_live`](https://docs.rs/moka/latest/moka/sync/struct.CacheBuilder.html#method.time_to_live)
+    /// Refer to [`moka::future::CacheBuilder::time_to_live`](https://docs.rs/moka/latest/moka/future/struct.CacheBuilder.html#method.time_to_live)
     time_to_live: Option<Duration>,
     /// Sets the time to idle of the cache.
     ///
-    /// Refer to [`moka::sync::CacheBuilder::time_to_idle`](https://docs.rs/moka/latest/moka/sync/struct.CacheBuilder.html#method.time_to_idle)
+    /// Refer to [`moka::future::CacheBuilder::time_to_idle`](https://docs.rs/moka/latest/moka/future/struct.CacheBuilder.html#method.time_to_idle)
     time_to_idle: Option<Duration>,
-    /// Sets the segments number of the cache.
-    ///
-    /// Refer to [`moka::sync::CacheBuilder::segments`](https://docs.rs/moka/latest/moka/sync/struct.CacheBuilder.html#method.segments)
-    num_segments: Option<usize>,
-    /// Decides whether to enable thread pool of the cache.
-    ///
-    /// Refer to [`moka::sync::CacheBuilder::thread_pool_enabled`](https://docs.rs/moka/latest/moka/sync/struct.CacheBuilder.html#method.thread_pool_enabled)
-    thread_pool_enabled: Option<bool>,
 }
 
 impl MokaBuilder {
@@ -74,7 +73,7 @@ impl MokaBuilder {
 
     /// Sets the max capacity of the cache.
     ///
-    /// Refer to [`moka::sync::CacheBuilder::max_capacity`](https://docs.rs/moka/latest/moka/sync/struct.CacheBuilder.html#method.max_capacity)
+    /// Refer to [`moka::future::CacheBuilder::max_capacity`](https://docs.rs/moka/latest/moka/future/struct.CacheBuilder.html#method.max_capacity)
     pub fn max_capacity(&mut self, v: u64) -> &mut Self {
         if v != 0 {
             self.max_capacity = Some(v);
@@ -84,7 +83,7 @@ impl MokaBuilder {
 
     /// Sets the time to live of the cache.
     ///
-    /// Refer to [`moka::sync::CacheBuilder::time_to_live`](https://docs.rs/moka/latest/moka/sync/struct.CacheBuilder.html#method.time_to_live)
+    /// Refer to [`moka::future::CacheBuilder::time_to_live`](https://docs.rs/moka/latest/moka/future/struct.CacheBuilder.html#method.time_to_live)
     pub fn time_to_live(&mut self, v: Duration) -> &mut Self {
         if !v.is_zero() {
             self.time_to_live = Some(v);
@@ -94,7 +93,7 @@ impl MokaBuilder {
 
     /// Sets the time to idle of the cache.
     ///
-    /// Refer to [`moka::sync::CacheBuilder::time_to_idle`](https://docs.rs/moka/latest/moka/sync/struct.CacheBuilder.html#method.time_to_idle)
+    /// Refer to [`moka::future::CacheBuilder::time_to_idle`](https://docs.rs/moka/latest/moka/future/struct.CacheBuilder.html#method.time_to_idle)
     pub fn time_to_idle(&mut self, v: Duration) -> &mut Self {
         if !v.is_zero() {
             self.time_to_idle = Some(v);
@@ -104,18 +103,19 @@ impl MokaBuilder {
 
     /// Sets the segments number of the cache.
     ///
-    /// Refer to [`moka::sync::CacheBuilder::segments`](https://docs.rs/moka/latest/moka/sync/struct.CacheBuilder.html#method.segments)
+    /// This option is ignored since moka's future cache is not segmented.
+    #[deprecated(note = "moka's future cache is not segmented, this option is ignored")]
     pub fn segments(&mut self, v: usize) -> &mut Self {
         assert!(v != 0);
-        self.num_segments = Some(v);
         self
     }
 
     /// Decides whether to enable thread pool of the cache.
     ///
-    /// Refer to [`moka::sync::CacheBuilder::thread_pool_enabled`](https://docs.rs/moka/latest/moka/sync/struct.CacheBuilder.html#method.thread_pool_enabled)
-    pub fn thread_pool_enabled(&mut self, v: bool) -> &mut Self {
-        self.thread_pool_enabled = Some(v);
+    /// This is a no-op: moka's future cache always runs its housekeeping
+    /// in the thread pool.
+    #[deprecated(note = "moka's future cache always enables thread pool")]
+    pub fn thread_pool_enabled(&mut self, _: bool) -> &mut Self {
         self
     }
 }
@@ -138,10 +138,6 @@ impl Builder for MokaBuilder {
             v.parse::<u64>()
                 .map(|v| builder.time_to_idle(Duration::from_secs(v)))
         });
-        map.get("num_segments")
-            .map(|v| v.parse::<usize>().map(|v| builder.segments(v)));
-        map.get("thread_pool_enabled")
-            .map(|v| v.parse::<bool>().map(|v| builder.thread_pool_enabled(v)));
 
         builder
     }
@@ -149,11 +145,9 @@ impl Builder for MokaBuilder {
     fn build(&mut self) -> Result<Self::Accessor> {
         debug!("backend build started: {:?}", &self);
 
-        let mut builder: CacheBuilder<String, Vec<u8>, _> =
-            SegmentedCache::builder(self.num_segments.unwrap_or(1))
-                .thread_pool_enabled(self.thread_pool_enabled.unwrap_or(false));
         // Use entries' bytes as capacity weigher.
-        builder = builder.weigher(|k, v| (k.len() + v.len()) as u32);
+        let mut builder =
+            Cache::<String, Vec<u8>>::builder().weigher(|k, v| (k.len() + v.len()) as u32);
         if let Some(v) = &self.name {
             builder = builder.name(v);
         }
@@ -179,7 +173,7 @@ pub type MokaBackend = kv::Backend<Adapter>;
 
 #[derive(Clone)]
 pub struct Adapter {
-    inner: SegmentedCache<String, Vec<u8>>,
+    inner: Cache<String, Vec<u8>>,
 }
 
 impl Debug for Adapter {
@@ -197,7 +191,7 @@ impl kv::Adapter for Adapter {
         kv::Metadata::new(
             Scheme::Moka,
             self.inner.name().unwrap_or("moka"),
-            AccessorCapability::Read | AccessorCapability::Write,
+            AccessorCapability::Read | AccessorCapability::Write | AccessorCapability::Scan,
         )
     }
 
@@ -213,22 +207,110 @@ impl kv::Adapter for Adapter {
     }
 
     async fn set(&self, path: &str, value: &[u8]) -> Result<()> {
-        self.blocking_set(path, value)
+        self.inner.insert(path.to_string(), value.to_vec()).await;
+
+        Ok(())
     }
 
     fn blocking_set(&self, path: &str, value: &[u8]) -> Result<()> {
-        self.inner.insert(path.to_string(), value.to_vec());
+        self.inner
+            .blocking()
+            .insert(path.to_string(), value.to_vec());
 
         Ok(())
     }
 
     async fn delete(&self, path: &str) -> Result<()> {
-        self.blocking_delete(path)
+        self.inner.invalidate(path).await;
+
+        Ok(())
     }
 
     fn blocking_delete(&self, path: &str) -> Result<()> {
-        self.inner.invalidate(path);
+        self.inner.blocking().invalidate(path);
 
         Ok(())
     }
+
+    async fn scan(&self, path: &str) -> Result<Vec<String>> {
+        self.blocking_scan(path)
+    }
+
+    fn blocking_scan(&self, path: &str) -> Result<Vec<String>> {
+        let mut keys: Vec<_> = self
+            .inner
+            .iter()
+            .map(|(k, _)| k.to_string())
+            .filter(|k| k.starts_with(path))
+            .collect();
+        // Keep the same order with other kv services.
+        keys.sort();
+
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_time_to_live() {
+        let mut builder = MokaBuilder::default();
+        builder.time_to_live(Duration::from_millis(200));
+        let op = Operator::new(builder).unwrap().finish();
+
+        op.write("file", "Hello, World!")
+            .await
+            .expect("write must succeed");
+        let meta = op.stat("file").await.expect("stat must succeed");
+        assert_eq!(meta.content_length(), 13);
+
+        std::thread::sleep(Duration::from_millis(400));
+        let err = op.stat("file").await.expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_time_to_idle() {
+        let mut builder = MokaBuilder::default();
+        builder.time_to_idle(Duration::from_secs(1));
+        let op = Operator::new(builder).unwrap().finish();
+
+        op.write("file", "Hello, World!")
+            .await
+            .expect("write must succeed");
+        // moka applies the recorded reads in housekeeping which runs about
+        // every 300ms, so the accessed time could lag behind.
+        for _ in 0..4 {
+            std::thread::sleep(Duration::from_millis(400));
+            op.stat("file").await.expect("stat must succeed");
+        }
+
+        std::thread::sleep(Duration::from_secs(2));
+        let err = op.stat("file").await.expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_scan() {
+        let op = Operator::new(MokaBuilder::default()).unwrap().finish();
+
+        for path in ["dir/a", "dir/b", "other"] {
+            op.write(path, "Hello, World!")
+                .await
+                .expect("write must succeed");
+        }
+        op.delete("dir/b").await.expect("delete must succeed");
+
+        let mut lister = op.scan("dir/").await.expect("scan must succeed");
+        let mut paths = vec![];
+        while let Some(entry) = futures::TryStreamExt::try_next(&mut lister)
+            .await
+            .expect("next must succeed")
+        {
+            paths.push(entry.path().to_string());
+        }
+        assert_eq!(paths, vec!["dir/a"]);
+    }
 }
@@ -17,6 +17,7 @@ use std::fmt::Debug;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use bytes::Bytes;
 use http::header::CONTENT_LENGTH;
 use http::header::CONTENT_TYPE;
 use http::Request;
@@ -25,6 +26,8 @@ use http::StatusCode;
 use http::Uri;
 use log::debug;
 use reqsign::HuaweicloudObsSigner;
+use serde::Deserialize;
+use serde::Serialize;
 
 use super::error::parse_error;
 use super::pager::ObsPager;
@@ -53,9 +56,16 @@ use crate::*;
 /// - `endpoint`: Customizable endpoint setting
 /// - `access_key_id`: Set the access_key_id for backend.
 /// - `secret_access_key`: Set the secret_access_key for backend.
+/// - `write_part_size`: Set the part size of multipart upload.
 ///
 /// You can refer to [`ObsBuilder`]'s docs for more information
 ///
+/// # Multipart Upload
+///
+/// `Writer::append` is implemented via multipart upload. Appended bytes
+/// will be uploaded as parts of `write_part_size`, the upload will be
+/// aborted if the writer is aborted or dropped before `close`.
+///
 /// # Example
 ///
 /// ## Via Builder
@@ -92,6 +102,7 @@ pub struct ObsBuilder {
     access_key_id: Option<String>,
     secret_access_key: Option<String>,
     bucket: Option<String>,
+    write_part_size: Option<usize>,
     http_client: Option<HttpClient>,
 }
 
@@ -103,6 +114,7 @@ impl Debug for ObsBuilder {
             .field("access_key_id", &"<redacted>")
             .field("secret_access_key", &"<redacted>")
             .field("bucket", &self.bucket)
+            .field("write_part_size", &self.write_part_size)
             .finish()
     }
 }
@@ -167,6 +179,21 @@ impl ObsBuilder {
         self
     }
 
+    /// Set the part size of multipart upload.
+    ///
+    /// Bytes appended by writer will be buffered until `write_part_size`
+    /// is reached and then uploaded as one part. The last part could be
+    /// smaller than it.
+    ///
+    /// OBS requires part size to be at least 100 KiB, building will fail
+    /// with [`ErrorKind::ConfigInvalid`] if given size is smaller.
+    ///
+    /// If not set, every append will be uploaded as one part directly.
+    pub fn write_part_size(&mut self, size: usize) -> &mut Self {
+        self.write_part_size = Some(size);
+        self
+    }
+
     /// Specify the http client that used by this service.
     ///
     /// # Notes
@@ -192,6 +219,9 @@ impl Builder for ObsBuilder {
         map.get("access_key_id").map(|v| builder.access_key_id(v));
         map.get("secret_access_key")
             .map(|v| builder.secret_access_key(v));
+        map.get("write_part_size")
+            .and_then(|v| v.parse().ok())
+            .map(|v| builder.write_part_size(v));
 
         builder
     }
@@ -209,6 +239,18 @@ impl Builder for ObsBuilder {
         }?;
         debug!("backend use bucket {}", &bucket);
 
+        if let Some(size) = self.write_part_size {
+            if size < MIN_WRITE_PART_SIZE {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "write_part_size must be at least 100 KiB",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Obs)
+                .with_context("write_part_size", size.to_string()));
+            }
+        }
+
         let uri = match &self.endpoint {
             Some(endpoint) => endpoint.parse::<Uri>().map_err(|err| {
                 Error::new(ErrorKind::ConfigInvalid, "endpoint is invalid")
@@ -278,10 +320,16 @@ impl Builder for ObsBuilder {
             endpoint: format!("{}://{}", &scheme, &endpoint),
             signer: Arc::new(signer),
             bucket,
+            write_part_size: self.write_part_size,
         })
     }
 }
 
+/// The minimum part size of multipart upload, except the last part.
+const MIN_WRITE_PART_SIZE: usize = 100 * 1024;
+
+pub(super) const X_OBS_VERSION_ID: &str = "x-obs-version-id";
+
 /// Backend for Huaweicloud OBS services.
 #[derive(Debug, Clone)]
 pub struct ObsBackend {
@@ -290,13 +338,15 @@ pub struct ObsBackend {
     endpoint: String,
     pub signer: Arc<HuaweicloudObsSigner>,
     bucket: String,
+    /// Part size of multipart upload, `None` means every append is a part.
+    pub(super) write_part_size: Option<usize>,
 }
 
 #[async_trait]
 impl Accessor for ObsBackend {
     type Reader = IncomingAsyncBody;
     type BlockingReader = ();
-    type Writer = ObsWriter;
+    type Writer = oio::MultipartUploadWriter<ObsWriter>;
     type BlockingWriter = ();
     type Pager = ObsPager;
     type BlockingPager = ();
@@ -310,6 +360,23 @@ impl Accessor for ObsBackend {
             .set_root(&self.root)
             .set_name(&self.bucket)
             .set_capabilities(Read | Write | List | Scan)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                write_with_content_type: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                scan: true,
+                ..Default::default()
+            })
             .set_hints(ReadStreamable);
 
         am
@@ -334,30 +401,26 @@ impl Accessor for ObsBackend {
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        let resp = self.obs_get_object(path, args.range()).await?;
+        let range = args.range();
+        let resp = self.obs_get_object(path, range).await?;
 
         let status = resp.status();
 
         match status {
             StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
-                let meta = parse_into_metadata(path, resp.headers())?;
-                Ok((RpRead::with_metadata(meta), resp.into_body()))
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
             }
             _ => Err(parse_error(resp).await?),
         }
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        if args.append() {
-            return Err(Error::new(
-                ErrorKind::Unsupported,
-                "append write is not supported",
-            ));
-        }
+        let w = ObsWriter::new(self.clone(), args, path.to_string());
 
         Ok((
             RpWrite::default(),
-            ObsWriter::new(self.clone(), args, path.to_string()),
+            oio::MultipartUploadWriter::new(w, self.write_part_size),
         ))
     }
 
@@ -495,6 +558,113 @@ impl ObsBackend {
         self.client.send_async(req).await
     }
 
+    pub(super) async fn obs_initiate_multipart_upload(
+        &self,
+        path: &str,
+        content_type: Option<&str>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!("{}/{}?uploads", self.endpoint, percent_encode_path(&p));
+
+        let mut req = Request::post(&url);
+
+        if let Some(mime) = content_type {
+            req = req.header(CONTENT_TYPE, mime)
+        }
+
+        let mut req = req
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+
+        self.client.send_async(req).await
+    }
+
+    pub(super) async fn obs_upload_part(
+        &self,
+        path: &str,
+        upload_id: &str,
+        part_number: usize,
+        size: u64,
+        body: AsyncBody,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!(
+            "{}/{}?partNumber={}&uploadId={}",
+            self.endpoint,
+            percent_encode_path(&p),
+            part_number,
+            upload_id
+        );
+
+        let mut req = Request::put(&url)
+            .header(CONTENT_LENGTH, size)
+            .body(body)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+
+        self.client.send_async(req).await
+    }
+
+    pub(super) async fn obs_complete_multipart_upload(
+        &self,
+        path: &str,
+        upload_id: &str,
+        parts: &[oio::MultipartUploadPart],
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!(
+            "{}/{}?uploadId={}",
+            self.endpoint,
+            percent_encode_path(&p),
+            upload_id
+        );
+
+        let content = build_complete_multipart_upload_body(parts)?;
+
+        // Make sure content length has been set to avoid post with chunked encoding.
+        let mut req = Request::post(&url)
+            .header(CONTENT_LENGTH, content.len())
+            .header(CONTENT_TYPE, "application/xml")
+            .body(AsyncBody::Bytes(Bytes::from(content)))
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+
+        self.client.send_async(req).await
+    }
+
+    /// Abort an on-going multipart upload.
+    ///
+    /// All parts uploaded will be freed by OBS after this call.
+    pub(super) async fn obs_abort_multipart_upload(
+        &self,
+        path: &str,
+        upload_id: &str,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!(
+            "{}/{}?uploadId={}",
+            self.endpoint,
+            percent_encode_path(&p),
+            upload_id
+        );
+
+        let mut req = Request::delete(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+
+        self.client.send_async(req).await
+    }
+
     pub(crate) async fn obs_list_objects(
         &self,
         path: &str,
@@ -533,3 +703,325 @@ impl ObsBackend {
         self.client.send_async(req).await
     }
 }
+
+/// Result of InitiateMultipartUpload
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub(super) struct InitiateMultipartUploadResult {
+    pub upload_id: String,
+}
+
+/// Result of CompleteMultipartUpload
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub(super) struct CompleteMultipartUploadResult {
+    #[serde(rename = "ETag")]
+    pub etag: String,
+}
+
+/// Request of CompleteMultipartUpload
+#[derive(Default, Debug, Serialize)]
+#[serde(default, rename = "CompleteMultipartUpload", rename_all = "PascalCase")]
+struct CompleteMultipartUploadRequest {
+    part: Vec<CompleteMultipartUploadRequestPart>,
+}
+
+#[derive(Default, Debug, Serialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct CompleteMultipartUploadRequestPart {
+    #[serde(rename = "PartNumber")]
+    part_number: usize,
+    #[serde(rename = "ETag")]
+    etag: String,
+}
+
+/// Build the body of CompleteMultipartUpload.
+///
+/// OBS returns quoted etag in `UploadPart` but expects the bare value in
+/// `CompleteMultipartUpload`, so quotes will be trimmed here.
+fn build_complete_multipart_upload_body(parts: &[oio::MultipartUploadPart]) -> Result<String> {
+    quick_xml::se::to_string(&CompleteMultipartUploadRequest {
+        part: parts
+            .iter()
+            .map(|part| CompleteMultipartUploadRequestPart {
+                part_number: part.part_number,
+                etag: part.etag.trim_matches('"').to_string(),
+            })
+            .collect(),
+    })
+    .map_err(new_xml_deserialize_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use wiremock::matchers::body_string;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    fn new_backend(endpoint: &str, write_part_size: Option<usize>) -> ObsBackend {
+        let signer = HuaweicloudObsSigner::builder()
+            .access_key("access_key_id")
+            .secret_key("secret_access_key")
+            .bucket("test")
+            .build()
+            .expect("signer must be built");
+
+        ObsBackend {
+            client: HttpClient::new().unwrap(),
+            root: "/".to_string(),
+            endpoint: endpoint.to_string(),
+            signer: Arc::new(signer),
+            bucket: "test".to_string(),
+            write_part_size,
+        }
+    }
+
+    async fn mount_initiate(mock_server: &MockServer) {
+        Mock::given(method("POST"))
+            .and(path("/file"))
+            .and(query_param("uploads", ""))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<InitiateMultipartUploadResult><Bucket>test</Bucket><Key>file</Key><UploadId>upload-id</UploadId></InitiateMultipartUploadResult>"#,
+            ))
+            .expect(1)
+            .mount(mock_server)
+            .await;
+    }
+
+    #[test]
+    fn test_build_complete_multipart_upload_body() {
+        let body = build_complete_multipart_upload_body(&[
+            oio::MultipartUploadPart::new(1, r#""etag-1""#),
+            oio::MultipartUploadPart::new(2, "etag-2"),
+        ])
+        .expect("must succeed");
+
+        assert_eq!(
+            body,
+            "<CompleteMultipartUpload>\
+             <Part><PartNumber>1</PartNumber><ETag>etag-1</ETag></Part>\
+             <Part><PartNumber>2</PartNumber><ETag>etag-2</ETag></Part>\
+             </CompleteMultipartUpload>"
+        );
+    }
+
+    #[test]
+    fn test_write_part_size_too_small() {
+        let mut builder = ObsBuilder::default();
+        builder
+            .bucket("test")
+            .endpoint("https://obs.cn-north-4.myhuaweicloud.com")
+            .write_part_size(1024);
+
+        let err = builder.build().expect_err("must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        mount_initiate(&mock_server).await;
+        for (part_number, size) in [(1, 100 * 1024), (2, 100 * 1024), (3, 50 * 1024)] {
+            Mock::given(method("PUT"))
+                .and(path("/file"))
+                .and(query_param("uploadId", "upload-id"))
+                .and(query_param("partNumber", part_number.to_string()))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("ETag", format!("\"etag-{size}\"").as_str()),
+                )
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .and(body_string(
+                "<CompleteMultipartUpload>\
+                 <Part><PartNumber>1</PartNumber><ETag>etag-102400</ETag></Part>\
+                 <Part><PartNumber>2</PartNumber><ETag>etag-102400</ETag></Part>\
+                 <Part><PartNumber>3</PartNumber><ETag>etag-51200</ETag></Part>\
+                 </CompleteMultipartUpload>",
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_backend(&mock_server.uri(), Some(100 * 1024));
+        let (_, mut w) = backend
+            .write("file", OpWrite::new().with_append())
+            .await
+            .expect("writer must be created");
+        for _ in 0..5 {
+            oio::Write::append(&mut w, Bytes::from(vec![0; 50 * 1024]))
+                .await
+                .expect("append must succeed");
+        }
+        oio::Write::close(&mut w).await.expect("close must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_small_content() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/file"))
+            .and(body_string("Hello, World!"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_backend(&mock_server.uri(), Some(100 * 1024));
+        let (_, mut w) = backend
+            .write("file", OpWrite::new().with_append())
+            .await
+            .expect("writer must be created");
+        oio::Write::append(&mut w, Bytes::from("Hello, World!"))
+            .await
+            .expect("append must succeed");
+        oio::Write::close(&mut w).await.expect("close must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_write_read() {
+        use std::sync::Mutex;
+
+        use wiremock::Request;
+        use wiremock::Respond;
+
+        /// Store the content of PutObject and return it for GetObject.
+        #[derive(Clone, Default)]
+        struct ObjectResponder(Arc<Mutex<Vec<u8>>>);
+
+        impl Respond for ObjectResponder {
+            fn respond(&self, req: &Request) -> ResponseTemplate {
+                let mut content = self.0.lock().unwrap();
+                if req.method == wiremock::http::Method::Put {
+                    *content = req.body.clone();
+                    ResponseTemplate::new(200)
+                } else {
+                    ResponseTemplate::new(200).set_body_bytes(content.clone())
+                }
+            }
+        }
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        let responder = ObjectResponder::default();
+        Mock::given(method("PUT"))
+            .and(path("/file"))
+            .respond_with(responder.clone())
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/file"))
+            .respond_with(responder)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let op = OperatorBuilder::new(new_backend(&mock_server.uri(), None)).finish();
+        op.write("file", "Hello, World!")
+            .await
+            .expect("write must succeed");
+
+        let bs = op.read("file").await.expect("read must succeed");
+        assert_eq!(bs, b"Hello, World!");
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_abort() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        mount_initiate(&mock_server).await;
+        Mock::given(method("PUT"))
+            .and(path("/file"))
+            .respond_with(ResponseTemplate::new(200).insert_header("ETag", "\"etag\""))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_backend(&mock_server.uri(), None);
+        let (_, mut w) = backend
+            .write("file", OpWrite::new().with_append())
+            .await
+            .expect("writer must be created");
+        oio::Write::append(&mut w, Bytes::from("Hello, World!"))
+            .await
+            .expect("append must succeed");
+        oio::Write::abort(&mut w).await.expect("abort must succeed");
+
+        // Aborted writer will not abort again while dropping.
+        drop(w);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_abort_on_drop() {
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        mount_initiate(&mock_server).await;
+        Mock::given(method("PUT"))
+            .and(path("/file"))
+            .respond_with(ResponseTemplate::new(200).insert_header("ETag", "\"etag\""))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let backend = new_backend(&mock_server.uri(), None);
+        let (_, mut w) = backend
+            .write("file", OpWrite::new().with_append())
+            .await
+            .expect("writer must be created");
+        oio::Write::append(&mut w, Bytes::from("Hello, World!"))
+            .await
+            .expect("append must succeed");
+
+        drop(w);
+        // Wait for the background abort.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
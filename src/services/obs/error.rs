@@ -14,7 +14,6 @@
 
 use bytes::Buf;
 use http::Response;
-use http::StatusCode;
 use quick_xml::de;
 use serde::Deserialize;
 
@@ -39,25 +38,33 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
     let (parts, body) = resp.into_parts();
     let bs = body.bytes().await?;
 
-    let (kind, retryable) = match parts.status {
-        StatusCode::NOT_FOUND => (ErrorKind::NotFound, false),
-        StatusCode::FORBIDDEN => (ErrorKind::PermissionDenied, false),
-        StatusCode::INTERNAL_SERVER_ERROR
-        | StatusCode::BAD_GATEWAY
-        | StatusCode::SERVICE_UNAVAILABLE
-        | StatusCode::GATEWAY_TIMEOUT => (ErrorKind::Unexpected, true),
+    let (mut kind, mut retryable) = match parts.status {
         // OBS could return `520 Origin Error` errors which should be retried.
         v if v.as_u16() == 520 => (ErrorKind::Unexpected, true),
-
-        _ => (ErrorKind::Unexpected, false),
+        v => parse_error_status(v),
     };
 
-    let message = match de::from_reader::<_, ObsError>(bs.clone().reader()) {
-        Ok(obs_error) => format!("{obs_error:?}"),
-        Err(_) => String::from_utf8_lossy(&bs).into_owned(),
-    };
+    let (message, obs_err) = de::from_reader::<_, ObsError>(bs.clone().reader())
+        .map(|obs_err| (format!("{obs_err:?}"), Some(obs_err)))
+        .unwrap_or_else(|_| (String::from_utf8_lossy(&bs).into_owned(), None));
+
+    if let Some(obs_err) = obs_err {
+        (kind, retryable) = match obs_err.code.as_str() {
+            // Credentials are not valid, users need to fix their config.
+            "InvalidAccessKeyId" | "SignatureDoesNotMatch" | "InvalidToken" => {
+                (ErrorKind::ConfigInvalid, false)
+            }
+            "AccessDenied" => (ErrorKind::PermissionDenied, false),
+            "NoSuchBucket" => (ErrorKind::NotFound, false),
+            _ => (kind, retryable),
+        }
+    }
 
-    let mut err = Error::new(kind, &message).with_context("response", format!("{parts:?}"));
+    let mut err = with_error_response_context(Error::new(kind, &message), &parts);
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
 
     if retryable {
         err = err.set_temporary();
@@ -68,6 +75,9 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
 
 #[cfg(test)]
 mod tests {
+    use futures::stream;
+    use http::StatusCode;
+
     use super::*;
 
     #[test]
@@ -97,4 +107,35 @@ mod tests {
             "RkRCRDJENDc5MzdGQkQ4OUY3MTI4NTQ3NDk2Mjg0M0FBQUFBQUFBYmJiYmJiYmJD"
         );
     }
+
+    #[tokio::test]
+    async fn test_parse_error_auth() -> Result<()> {
+        let cases = vec![
+            ("InvalidAccessKeyId", ErrorKind::ConfigInvalid),
+            ("SignatureDoesNotMatch", ErrorKind::ConfigInvalid),
+            ("AccessDenied", ErrorKind::PermissionDenied),
+        ];
+
+        for (code, kind) in cases {
+            let bs = bytes::Bytes::from(format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>{code}</Code>
+  <Message>some message</Message>
+  <RequestId>1D842BC54255</RequestId>
+</Error>"#
+            ));
+            let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+            let resp = Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .body(body)
+                .unwrap();
+
+            let err = parse_error(resp).await?;
+            assert_eq!(err.kind(), kind, "code: {code}");
+            assert!(!err.is_temporary(), "code: {code}");
+        }
+
+        Ok(())
+    }
 }
@@ -13,10 +13,13 @@
 // limitations under the License.
 
 use async_trait::async_trait;
-use bytes::Bytes;
+use bytes::Buf;
 use http::StatusCode;
 
+use super::backend::CompleteMultipartUploadResult;
+use super::backend::InitiateMultipartUploadResult;
 use super::backend::ObsBackend;
+use super::backend::X_OBS_VERSION_ID;
 use super::error::parse_error;
 use crate::ops::OpWrite;
 use crate::raw::*;
@@ -36,13 +39,13 @@ impl ObsWriter {
 }
 
 #[async_trait]
-impl oio::Write for ObsWriter {
-    async fn write(&mut self, bs: Bytes) -> Result<()> {
+impl oio::MultipartUploadWrite for ObsWriter {
+    async fn write_once(&self, size: u64, body: AsyncBody) -> Result<RpWrite> {
         let mut req = self.backend.obs_put_object_request(
             &self.path,
-            Some(bs.len()),
+            Some(size as usize),
             self.op.content_type(),
-            AsyncBody::Bytes(bs),
+            body,
         )?;
 
         self.backend
@@ -56,23 +59,114 @@ impl oio::Write for ObsWriter {
 
         match status {
             StatusCode::CREATED | StatusCode::OK => {
+                let rp = parse_into_write_reply(resp.headers(), X_OBS_VERSION_ID)?;
                 resp.into_body().consume().await?;
-                Ok(())
+                Ok(rp)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn initiate_part(&self) -> Result<String> {
+        let resp = self
+            .backend
+            .obs_initiate_multipart_upload(&self.path, self.op.content_type())
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let result: InitiateMultipartUploadResult =
+                    quick_xml::de::from_reader(bs.reader()).map_err(new_xml_deserialize_error)?;
+
+                Ok(result.upload_id)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn write_part(
+        &self,
+        upload_id: &str,
+        part_number: usize,
+        size: u64,
+        body: AsyncBody,
+    ) -> Result<oio::MultipartUploadPart> {
+        let resp = self
+            .backend
+            .obs_upload_part(&self.path, upload_id, part_number, size, body)
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let etag = parse_etag(resp.headers())?
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::Unexpected,
+                            "ETag not present in returning response",
+                        )
+                    })?
+                    .to_string();
+
+                resp.into_body().consume().await?;
+
+                Ok(oio::MultipartUploadPart::new(part_number, etag))
             }
             _ => Err(parse_error(resp).await?),
         }
     }
 
-    async fn append(&mut self, bs: Bytes) -> Result<()> {
-        let _ = bs;
+    async fn complete_part(
+        &self,
+        upload_id: &str,
+        parts: &[oio::MultipartUploadPart],
+    ) -> Result<RpWrite> {
+        let resp = self
+            .backend
+            .obs_complete_multipart_upload(&self.path, upload_id, parts)
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let mut rp = parse_into_write_reply(resp.headers(), X_OBS_VERSION_ID)?;
+                let bs = resp.into_body().bytes().await?;
+
+                // The etag of multipart upload is only returned in body.
+                if !bs.is_empty() {
+                    let result: CompleteMultipartUploadResult =
+                        quick_xml::de::from_reader(bs.reader())
+                            .map_err(new_xml_deserialize_error)?;
+                    if !result.etag.is_empty() {
+                        rp = rp.with_etag(&result.etag);
+                    }
+                }
 
-        Err(Error::new(
-            ErrorKind::Unsupported,
-            "output writer doesn't support append",
-        ))
+                Ok(rp)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
     }
 
-    async fn close(&mut self) -> Result<()> {
-        Ok(())
+    async fn abort_part(&self, upload_id: &str) -> Result<()> {
+        let resp = self
+            .backend
+            .obs_abort_multipart_upload(&self.path, upload_id)
+            .await?;
+
+        match resp.status() {
+            // OBS returns code 204 if abort succeeds.
+            StatusCode::NO_CONTENT => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
     }
 }
@@ -0,0 +1,929 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::header;
+use http::Request;
+use http::Response;
+use http::StatusCode;
+use log::debug;
+use serde::Deserialize;
+use serde_json::json;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use super::error::parse_error;
+use super::pager::OnedrivePager;
+use super::writer::OnedriveWriter;
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+const DEFAULT_ONEDRIVE_ENDPOINT: &str = "https://graph.microsoft.com";
+const DEFAULT_ONEDRIVE_TOKEN_ENDPOINT: &str =
+    "https://login.microsoftonline.com/common/oauth2/v2.0/token";
+
+/// The chunk size of upload session must be a multiple of 320KiB.
+const WRITE_CHUNK_SIZE_ALIGNMENT: usize = 320 * 1024;
+/// The chunk size of upload session must be less than 60MiB.
+const MAX_WRITE_CHUNK_SIZE: usize = 60 * 1024 * 1024;
+/// The default chunk size of upload session.
+const DEFAULT_WRITE_CHUNK_SIZE: usize = 32 * WRITE_CHUNK_SIZE_ALIGNMENT;
+
+/// [OneDrive](https://onedrive.live.com/) services support via Microsoft Graph API.
+///
+/// # Capabilities
+///
+/// This service can be used to:
+///
+/// - [x] read
+/// - [x] write
+/// - [x] list
+/// - [ ] ~~scan~~
+/// - [ ] ~~presign~~
+/// - [ ] blocking
+///
+/// # Configuration
+///
+/// - `root`: Set the work directory for backend
+/// - `endpoint`: Customizable endpoint setting, default to `https://graph.microsoft.com`
+/// - `access_token`: Set the OAuth2 access token
+/// - `refresh_token`: Set the OAuth2 refresh token
+/// - `client_id`: Set the OAuth2 client id that used to refresh token
+/// - `client_secret`: Set the OAuth2 client secret that used to refresh token
+/// - `write_chunk_size`: Chunk size of upload sessions, default to 10MiB
+///
+/// You can refer to [`OnedriveBuilder`]'s docs for more information
+///
+/// # Notes
+///
+/// Files not larger than 4MiB will be uploaded in one request, larger files
+/// will be uploaded via [upload sessions](https://learn.microsoft.com/en-us/graph/api/driveitem-createuploadsession).
+/// Every chunk of an upload session must carry the total size of the file,
+/// so appended bytes will be buffered until close unless the content length
+/// is given in [`OpWrite`].
+///
+/// Existing files will be replaced while writing, or the write will fail
+/// with [`ErrorKind::ConditionNotMatch`] if `if_not_exists` is set.
+///
+/// # Example
+///
+/// ## Via Builder
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::services::Onedrive;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     // create backend builder
+///     let mut builder = Onedrive::default();
+///
+///     builder
+///         .root("/path/to/dir")
+///         .refresh_token("refresh_token")
+///         .client_id("client_id")
+///         .client_secret("client_secret");
+///
+///     let op: Operator = Operator::new(builder)?.finish();
+///     Ok(())
+/// }
+/// ```
+#[derive(Default)]
+pub struct OnedriveBuilder {
+    root: Option<String>,
+    endpoint: Option<String>,
+
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+
+    write_chunk_size: Option<usize>,
+    http_client: Option<HttpClient>,
+}
+
+impl Debug for OnedriveBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("Builder");
+
+        ds.field("root", &self.root)
+            .field("endpoint", &self.endpoint)
+            .field("client_id", &self.client_id)
+            .field("write_chunk_size", &self.write_chunk_size);
+        if self.access_token.is_some() {
+            ds.field("access_token", &"<redacted>");
+        }
+        if self.refresh_token.is_some() {
+            ds.field("refresh_token", &"<redacted>");
+        }
+        if self.client_secret.is_some() {
+            ds.field("client_secret", &"<redacted>");
+        }
+        ds.finish()
+    }
+}
+
+impl OnedriveBuilder {
+    /// Set root path of OneDrive.
+    ///
+    /// The root is relative to the root folder of user's drive.
+    pub fn root(&mut self, root: &str) -> &mut Self {
+        if !root.is_empty() {
+            self.root = Some(root.to_string())
+        }
+
+        self
+    }
+
+    /// Set the endpoint of Microsoft Graph API.
+    ///
+    /// Default to `https://graph.microsoft.com`.
+    pub fn endpoint(&mut self, endpoint: &str) -> &mut Self {
+        if !endpoint.is_empty() {
+            self.endpoint = Some(endpoint.to_string())
+        }
+
+        self
+    }
+
+    /// Set the OAuth2 access token.
+    ///
+    /// The token will be used as is. Please set `refresh_token` instead for
+    /// long running services since access tokens are expired in an hour.
+    pub fn access_token(&mut self, token: &str) -> &mut Self {
+        if !token.is_empty() {
+            self.access_token = Some(token.to_string())
+        }
+
+        self
+    }
+
+    /// Set the OAuth2 refresh token.
+    ///
+    /// `client_id` is required to refresh the access token, `client_secret`
+    /// is required for confidential clients only.
+    pub fn refresh_token(&mut self, token: &str) -> &mut Self {
+        if !token.is_empty() {
+            self.refresh_token = Some(token.to_string())
+        }
+
+        self
+    }
+
+    /// Set the OAuth2 client id.
+    pub fn client_id(&mut self, client_id: &str) -> &mut Self {
+        if !client_id.is_empty() {
+            self.client_id = Some(client_id.to_string())
+        }
+
+        self
+    }
+
+    /// Set the OAuth2 client secret.
+    pub fn client_secret(&mut self, client_secret: &str) -> &mut Self {
+        if !client_secret.is_empty() {
+            self.client_secret = Some(client_secret.to_string())
+        }
+
+        self
+    }
+
+    /// Set the chunk size of upload sessions that used by writers.
+    ///
+    /// The size must be a multiple of 320KiB and not larger than 60MiB,
+    /// building will fail with [`ErrorKind::ConfigInvalid`] for others.
+    ///
+    /// Default to 10MiB.
+    pub fn write_chunk_size(&mut self, size: usize) -> &mut Self {
+        self.write_chunk_size = Some(size);
+        self
+    }
+
+    /// Specify the http client that used by this service.
+    ///
+    /// # Notes
+    ///
+    /// This API is part of OpenDAL's Raw API. `HttpClient` could be changed
+    /// during minor updates.
+    pub fn http_client(&mut self, client: HttpClient) -> &mut Self {
+        self.http_client = Some(client);
+        self
+    }
+}
+
+impl Builder for OnedriveBuilder {
+    const SCHEME: Scheme = Scheme::Onedrive;
+    type Accessor = OnedriveBackend;
+
+    fn from_map(map: HashMap<String, String>) -> Self {
+        let mut builder = OnedriveBuilder::default();
+
+        map.get("root").map(|v| builder.root(v));
+        map.get("endpoint").map(|v| builder.endpoint(v));
+        map.get("access_token").map(|v| builder.access_token(v));
+        map.get("refresh_token").map(|v| builder.refresh_token(v));
+        map.get("client_id").map(|v| builder.client_id(v));
+        map.get("client_secret").map(|v| builder.client_secret(v));
+        map.get("write_chunk_size")
+            .and_then(|v| v.parse::<usize>().ok())
+            .map(|v| builder.write_chunk_size(v));
+
+        builder
+    }
+
+    fn build(&mut self) -> Result<Self::Accessor> {
+        debug!("backend build started: {:?}", self);
+
+        let root = normalize_root(&self.root.take().unwrap_or_default());
+        debug!("backend use root {}", root);
+
+        let endpoint = self
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| DEFAULT_ONEDRIVE_ENDPOINT.to_string());
+        parse_endpoint(&endpoint).map_err(|e| {
+            e.with_operation("Builder::build")
+                .with_context("service", Scheme::Onedrive)
+        })?;
+        let endpoint = endpoint.trim_end_matches('/').to_string();
+        debug!("backend use endpoint: {endpoint}");
+
+        let write_chunk_size = self.write_chunk_size.unwrap_or(DEFAULT_WRITE_CHUNK_SIZE);
+        if write_chunk_size == 0
+            || write_chunk_size % WRITE_CHUNK_SIZE_ALIGNMENT != 0
+            || write_chunk_size > MAX_WRITE_CHUNK_SIZE
+        {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "write_chunk_size must be a multiple of 320KiB and not larger than 60MiB",
+            )
+            .with_operation("Builder::build")
+            .with_context("service", Scheme::Onedrive)
+            .with_context("write_chunk_size", write_chunk_size.to_string()));
+        }
+
+        let client = if let Some(client) = self.http_client.take() {
+            client
+        } else {
+            HttpClient::new().map_err(|err| {
+                err.with_operation("Builder::build")
+                    .with_context("service", Scheme::Onedrive)
+            })?
+        };
+
+        let token_loader = match (
+            &self.access_token,
+            &self.refresh_token,
+            &self.client_id,
+            &self.client_secret,
+        ) {
+            // Access token given by user is used as is and never expired.
+            (Some(access_token), _, _, _) => OAuth2TokenLoader::new(client.clone(), access_token),
+            (None, Some(refresh_token), Some(client_id), client_secret) => {
+                OAuth2TokenLoader::with_refresh_token(
+                    client.clone(),
+                    DEFAULT_ONEDRIVE_TOKEN_ENDPOINT,
+                    refresh_token,
+                    client_id,
+                    client_secret.as_deref(),
+                )
+            }
+            (None, Some(_), None, _) => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "client_id is required to refresh token",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Onedrive))
+            }
+            (None, None, _, _) => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "access_token or refresh_token is required",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Onedrive))
+            }
+        };
+
+        debug!("backend build finished: {:?}", self);
+        Ok(OnedriveBackend {
+            root,
+            endpoint,
+            client,
+            write_chunk_size,
+
+            token_loader,
+        })
+    }
+}
+
+/// DriveItem resource returned by Graph API.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(super) struct OnedriveItem {
+    pub id: String,
+    pub name: String,
+    pub size: u64,
+    pub e_tag: Option<String>,
+    pub last_modified_date_time: Option<String>,
+    pub file: Option<OnedriveFileFacet>,
+    pub folder: Option<OnedriveFolderFacet>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(super) struct OnedriveFileFacet {
+    pub mime_type: Option<String>,
+}
+
+/// Folder facet is only used to tell folders apart from files.
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct OnedriveFolderFacet {}
+
+impl OnedriveItem {
+    pub fn is_folder(&self) -> bool {
+        self.folder.is_some()
+    }
+
+    pub fn parse_into_metadata(&self) -> Result<Metadata> {
+        if self.is_folder() {
+            return Ok(Metadata::new(EntryMode::DIR));
+        }
+
+        let mut meta = Metadata::new(EntryMode::FILE);
+        meta.set_content_length(self.size);
+        if let Some(v) = self.file.as_ref().and_then(|v| v.mime_type.as_deref()) {
+            meta.set_content_type(v);
+        }
+        if let Some(v) = &self.e_tag {
+            meta.set_etag(v);
+        }
+        if let Some(v) = &self.last_modified_date_time {
+            let dt = OffsetDateTime::parse(v, &Rfc3339).map_err(|e| {
+                Error::new(ErrorKind::Unexpected, "parse last modified time as rfc3339")
+                    .set_source(e)
+            })?;
+            meta.set_last_modified(dt);
+        }
+        Ok(meta)
+    }
+}
+
+/// Collection of DriveItems returned by Graph API.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(super) struct OnedriveItemList {
+    /// The full url of next page.
+    #[serde(rename = "@odata.nextLink")]
+    pub next_link: Option<String>,
+    pub value: Vec<OnedriveItem>,
+}
+
+/// UploadSession resource returned by Graph API.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(super) struct OnedriveUploadSession {
+    /// Only returned while creating the session.
+    pub upload_url: String,
+    /// Ranges like `26-` or `26-100` that the server is still missing.
+    pub next_expected_ranges: Vec<String>,
+}
+
+/// Backend for OneDrive services.
+#[derive(Clone)]
+pub struct OnedriveBackend {
+    root: String,
+    endpoint: String,
+    pub(super) client: HttpClient,
+    pub(super) write_chunk_size: usize,
+
+    token_loader: OAuth2TokenLoader,
+}
+
+impl Debug for OnedriveBackend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Backend")
+            .field("root", &self.root)
+            .field("endpoint", &self.endpoint)
+            .field("write_chunk_size", &self.write_chunk_size)
+            .field("client", &self.client)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Accessor for OnedriveBackend {
+    type Reader = IncomingAsyncBody;
+    type BlockingReader = ();
+    type Writer = OnedriveWriter;
+    type BlockingWriter = ();
+    type Pager = OnedrivePager;
+    type BlockingPager = ();
+
+    fn info(&self) -> AccessorInfo {
+        use AccessorCapability::*;
+        use AccessorHint::*;
+
+        let mut am = AccessorInfo::default();
+        am.set_scheme(Scheme::Onedrive)
+            .set_root(&self.root)
+            .set_capabilities(Read | Write | List)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                write: true,
+                write_can_append: true,
+                write_with_if_not_exists: true,
+                write_with_if_match: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_delimiter: true,
+                ..Default::default()
+            })
+            .set_hints(ReadStreamable);
+        am
+    }
+
+    async fn create(&self, path: &str, _: OpCreate) -> Result<RpCreate> {
+        let abs_path = build_abs_path(&self.root, path);
+        let abs_path = abs_path.trim_end_matches('/');
+        if abs_path.is_empty() {
+            return Ok(RpCreate::default());
+        }
+
+        let names: Vec<&str> = abs_path.split('/').collect();
+
+        // Try to create the folder directly, and walk up only if the parent
+        // is not found. Most folders are created under existing ones.
+        let mut idx = names.len();
+        loop {
+            match self
+                .onedrive_create_folder(&names[..idx - 1].join("/"), names[idx - 1])
+                .await
+            {
+                Ok(()) => break,
+                Err(err) if err.kind() == ErrorKind::NotFound && idx > 1 => idx -= 1,
+                Err(err) => return Err(err),
+            }
+        }
+        for idx in idx + 1..=names.len() {
+            self.onedrive_create_folder(&names[..idx - 1].join("/"), names[idx - 1])
+                .await?;
+        }
+
+        Ok(RpCreate::default())
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let abs_path = build_abs_path(&self.root, path);
+
+        let range = args.range();
+        let resp = self.onedrive_get_content(&abs_path, range).await?;
+
+        let status = resp.status();
+
+        let resp = match status {
+            // Graph redirects to a pre-authenticated download url.
+            StatusCode::FOUND => {
+                let location = parse_location(resp.headers())?
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::Unexpected,
+                            "redirect response of download has no location",
+                        )
+                        .with_context("path", path)
+                    })?
+                    .to_string();
+                resp.into_body().consume().await?;
+
+                self.onedrive_download(&location, range).await?
+            }
+            _ => resp,
+        };
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let abs_path = build_abs_path(&self.root, path);
+
+        Ok((
+            RpWrite::default(),
+            OnedriveWriter::new(self.clone(), abs_path, args),
+        ))
+    }
+
+    async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+        // Stat root always returns a DIR.
+        if path == "/" {
+            return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
+        }
+
+        let abs_path = build_abs_path(&self.root, path);
+        let resp = self.onedrive_get_item(&abs_path).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                let item: OnedriveItem =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+                // Folders and files are told apart by the tailing `/`.
+                if item.is_folder() != path.ends_with('/') {
+                    return Err(Error::new(ErrorKind::NotFound, "file is not found")
+                        .with_context("path", path));
+                }
+
+                item.parse_into_metadata().map(RpStat::new)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        let abs_path = build_abs_path(&self.root, path);
+        let resp = self.onedrive_delete_item(&abs_path).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => {
+                resp.into_body().consume().await?;
+                Ok(RpDelete::default())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn list(&self, path: &str, _: OpList) -> Result<(RpList, Self::Pager)> {
+        let abs_path = build_abs_path(&self.root, path);
+        let url = format!("{}/children", self.onedrive_item_url(&abs_path));
+
+        Ok((
+            RpList::default(),
+            OnedrivePager::new(self.clone(), path, url),
+        ))
+    }
+}
+
+impl OnedriveBackend {
+    /// Build the url of DriveItem addressed by path.
+    ///
+    /// ref: <https://learn.microsoft.com/en-us/onedrive/developer/rest-api/concepts/addressing-driveitems>
+    pub(super) fn onedrive_item_url(&self, abs_path: &str) -> String {
+        let p = abs_path.trim_end_matches('/');
+        if p.is_empty() {
+            format!("{}/v1.0/me/drive/root", self.endpoint)
+        } else {
+            format!(
+                "{}/v1.0/me/drive/root:/{}:",
+                self.endpoint,
+                percent_encode_path(p)
+            )
+        }
+    }
+
+    async fn onedrive_get_item(&self, abs_path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let url = self.onedrive_item_url(abs_path);
+
+        self.onedrive_send(Request::get(&url), AsyncBody::Empty)
+            .await
+    }
+
+    async fn onedrive_get_content(
+        &self,
+        abs_path: &str,
+        range: BytesRange,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!("{}/content", self.onedrive_item_url(abs_path));
+
+        let mut req = Request::get(&url);
+        if !range.is_full() {
+            req = req.header(header::RANGE, range.to_header());
+        }
+
+        self.onedrive_send(req, AsyncBody::Empty).await
+    }
+
+    /// Download from the pre-authenticated url, the access token must not
+    /// be sent.
+    async fn onedrive_download(
+        &self,
+        url: &str,
+        range: BytesRange,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let mut req = Request::get(url);
+        if !range.is_full() {
+            req = req.header(header::RANGE, range.to_header());
+        }
+
+        let req = req
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.client.send_async(req).await
+    }
+
+    async fn onedrive_delete_item(&self, abs_path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let url = self.onedrive_item_url(abs_path);
+
+        self.onedrive_send(Request::delete(&url), AsyncBody::Empty)
+            .await
+    }
+
+    /// Create folder `name` under `parent`, an existing folder is treated
+    /// as created.
+    ///
+    /// Returns [`ErrorKind::NotFound`] if the parent is not exist.
+    async fn onedrive_create_folder(&self, parent: &str, name: &str) -> Result<()> {
+        let url = format!("{}/children", self.onedrive_item_url(parent));
+        let body = json!({
+            "name": name,
+            "folder": {},
+            "@microsoft.graph.conflictBehavior": "fail",
+        })
+        .to_string();
+
+        let req = Request::post(&url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_LENGTH, body.len());
+        let resp = self
+            .onedrive_send(req, AsyncBody::Bytes(Bytes::from(body)))
+            .await?;
+
+        match resp.status() {
+            StatusCode::CREATED | StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            StatusCode::CONFLICT => {
+                resp.into_body().consume().await?;
+
+                let path = if parent.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{parent}/{name}")
+                };
+                let resp = self.onedrive_get_item(&path).await?;
+                if resp.status() != StatusCode::OK {
+                    return Err(parse_error(resp).await?);
+                }
+                let bs = resp.into_body().bytes().await?;
+                let item: OnedriveItem =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+                if item.is_folder() {
+                    Ok(())
+                } else {
+                    Err(
+                        Error::new(ErrorKind::NotADirectory, "path exists but is not a folder")
+                            .with_context("path", path),
+                    )
+                }
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Upload the file in one request, only files not larger than 4MiB are
+    /// allowed.
+    ///
+    /// ref: <https://learn.microsoft.com/en-us/graph/api/driveitem-put-content>
+    pub(super) async fn onedrive_upload_simple(
+        &self,
+        abs_path: &str,
+        args: &OpWrite,
+        bs: Bytes,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!(
+            "{}/content?@microsoft.graph.conflictBehavior={}",
+            self.onedrive_item_url(abs_path),
+            conflict_behavior(args)
+        );
+
+        let mut req = Request::put(&url).header(header::CONTENT_LENGTH, bs.len());
+        if let Some(mime) = args.content_type() {
+            req = req.header(header::CONTENT_TYPE, mime);
+        }
+        if let Some(etag) = args.if_match() {
+            req = req.header(header::IF_MATCH, etag);
+        }
+
+        self.onedrive_send(req, AsyncBody::Bytes(bs)).await
+    }
+
+    /// Create an upload session for given path, returns the upload url.
+    ///
+    /// ref: <https://learn.microsoft.com/en-us/graph/api/driveitem-createuploadsession>
+    pub(super) async fn onedrive_create_upload_session(
+        &self,
+        abs_path: &str,
+        args: &OpWrite,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!("{}/createUploadSession", self.onedrive_item_url(abs_path));
+        let body = json!({
+            "item": {
+                "@microsoft.graph.conflictBehavior": conflict_behavior(args),
+            },
+        })
+        .to_string();
+
+        let mut req = Request::post(&url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_LENGTH, body.len());
+        if let Some(etag) = args.if_match() {
+            req = req.header(header::IF_MATCH, etag);
+        }
+
+        self.onedrive_send(req, AsyncBody::Bytes(Bytes::from(body)))
+            .await
+    }
+
+    pub(super) async fn onedrive_send(
+        &self,
+        req: http::request::Builder,
+        body: AsyncBody,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let req = req
+            .header(
+                header::AUTHORIZATION,
+                self.token_loader.authorization().await?,
+            )
+            .body(body)
+            .map_err(new_request_build_error)?;
+
+        self.client.send_async(req).await
+    }
+}
+
+/// Map our write semantics into Graph's conflict behavior: existing files
+/// will be replaced unless `if_not_exists` is set.
+fn conflict_behavior(args: &OpWrite) -> &'static str {
+    if args.if_not_exists() {
+        "fail"
+    } else {
+        "replace"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::body_partial_json;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    fn new_backend(endpoint: &str) -> OnedriveBackend {
+        let mut builder = OnedriveBuilder::default();
+        builder.endpoint(endpoint).access_token("token");
+        builder.build().expect("build must succeed")
+    }
+
+    #[test]
+    fn test_build_without_token() {
+        let mut builder = OnedriveBuilder::default();
+        let err = builder.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let mut builder = OnedriveBuilder::default();
+        builder.refresh_token("refresh_token");
+        let err = builder.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let mut builder = OnedriveBuilder::default();
+        builder.access_token("token").write_chunk_size(1024 * 1024);
+        let err = builder.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[test]
+    fn test_item_url() {
+        let backend = new_backend("https://graph.microsoft.com");
+
+        assert_eq!(
+            backend.onedrive_item_url(""),
+            "https://graph.microsoft.com/v1.0/me/drive/root"
+        );
+        assert_eq!(
+            backend.onedrive_item_url("a/b c/"),
+            "https://graph.microsoft.com/v1.0/me/drive/root:/a/b%20c:"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_dir_with_missing_parents() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        // `a/b` is not exist, so creating `c` under it fails.
+        Mock::given(method("POST"))
+            .and(path("/v1.0/me/drive/root:/a/b:/children"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "error": { "code": "itemNotFound", "message": "Item does not exist" }
+            })))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1.0/me/drive/root:/a:/children"))
+            .and(body_partial_json(json!({ "name": "b" })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "id": "id_b", "name": "b", "folder": { "childCount": 0 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1.0/me/drive/root:/a/b:/children"))
+            .and(body_partial_json(json!({ "name": "c" })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "id": "id_c", "name": "c", "folder": { "childCount": 0 }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server.uri());
+        backend
+            .create("a/b/c/", OpCreate::new(EntryMode::DIR))
+            .await
+            .expect("create must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_create_dir_already_exists() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1.0/me/drive/root/children"))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error": { "code": "nameAlreadyExists", "message": "Name already exists" }
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1.0/me/drive/root:/dir:"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "id_dir", "name": "dir", "folder": { "childCount": 1 }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1.0/me/drive/root:/file:"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "id_file", "name": "file", "size": 1, "file": {}
+            })))
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server.uri());
+        backend
+            .create("dir/", OpCreate::new(EntryMode::DIR))
+            .await
+            .expect("create existing folder must succeed");
+        let err = backend
+            .create("file/", OpCreate::new(EntryMode::DIR))
+            .await
+            .expect_err("create on existing file must fail");
+        assert_eq!(err.kind(), ErrorKind::NotADirectory);
+    }
+}
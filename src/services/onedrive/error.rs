@@ -0,0 +1,123 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use http::Response;
+use http::StatusCode;
+use serde::Deserialize;
+use serde_json::de;
+
+use crate::raw::*;
+use crate::Error;
+use crate::ErrorKind;
+use crate::Result;
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct OnedriveErrorResponse {
+    error: OnedriveError,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct OnedriveError {
+    code: String,
+    message: String,
+}
+
+/// Parse error response into Error.
+pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
+    let (parts, body) = resp.into_parts();
+    let bs = body.bytes().await?;
+
+    let (kind, retryable) = match parts.status {
+        StatusCode::CONFLICT => (ErrorKind::AlreadyExists, false),
+        v => parse_error_status(v),
+    };
+
+    let message = match de::from_slice::<OnedriveErrorResponse>(&bs) {
+        Ok(onedrive_err) => format!("{onedrive_err:?}"),
+        Err(_) => String::from_utf8_lossy(&bs).into_owned(),
+    };
+
+    let mut err = with_error_response_context(Error::new(kind, &message), &parts);
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
+
+    if retryable {
+        err = err.set_temporary();
+    }
+
+    Ok(err)
+}
+
+/// Parse error response of writes into Error.
+///
+/// Writes with `if_not_exists` are sent with conflict behavior `fail`, the
+/// returning `409 Conflict` means the condition is not match.
+pub async fn parse_write_error(
+    resp: Response<IncomingAsyncBody>,
+    if_not_exists: bool,
+) -> Result<Error> {
+    let conflict = resp.status() == StatusCode::CONFLICT;
+    let err = parse_error(resp).await?;
+
+    if conflict && if_not_exists {
+        Ok(Error::new(ErrorKind::ConditionNotMatch, "path already exists").set_source(err))
+    } else {
+        Ok(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_parse_error() -> Result<()> {
+        let bs = bytes::Bytes::from(
+            r#"
+{
+  "error": {
+    "code": "nameAlreadyExists",
+    "message": "The specified item name already exists."
+  }
+}
+"#,
+        );
+        let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs.clone())])), None);
+        let resp = Response::builder()
+            .status(StatusCode::CONFLICT)
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        assert!(err.to_string().contains("nameAlreadyExists"));
+
+        let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+        let resp = Response::builder()
+            .status(StatusCode::CONFLICT)
+            .body(body)
+            .unwrap();
+
+        let err = parse_write_error(resp, true).await?;
+        assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use http::Request;
+use http::StatusCode;
+
+use super::backend::OnedriveBackend;
+use super::backend::OnedriveItemList;
+use super::error::parse_error;
+use crate::raw::*;
+use crate::*;
+
+/// OnedrivePager lists the children of a folder page by page.
+///
+/// Pages are chained by `@odata.nextLink`, which is the full url of the
+/// next page.
+pub struct OnedrivePager {
+    backend: OnedriveBackend,
+    path: String,
+
+    /// The url of next page, `None` means all pages have been listed.
+    next_link: Option<String>,
+}
+
+impl OnedrivePager {
+    pub fn new(backend: OnedriveBackend, path: &str, url: String) -> Self {
+        Self {
+            backend,
+            path: path.to_string(),
+
+            next_link: Some(url),
+        }
+    }
+}
+
+#[async_trait]
+impl oio::Page for OnedrivePager {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let url = match self.next_link.take() {
+            Some(url) => url,
+            None => return Ok(None),
+        };
+
+        let resp = self
+            .backend
+            .onedrive_send(Request::get(&url), AsyncBody::Empty)
+            .await?;
+
+        // Listing a not exist folder returns nothing.
+        if resp.status() == StatusCode::NOT_FOUND {
+            resp.into_body().consume().await?;
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(parse_error(resp).await?);
+        }
+        let bs = resp.into_body().bytes().await?;
+
+        let output: OnedriveItemList =
+            serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+        self.next_link = output.next_link;
+
+        let mut entries = Vec::with_capacity(output.value.len());
+        for item in output.value {
+            let name = if item.is_folder() {
+                format!("{}/", item.name)
+            } else {
+                item.name.clone()
+            };
+
+            let path = if self.path == "/" {
+                name
+            } else {
+                format!("{}{}", self.path, name)
+            };
+
+            entries.push(oio::Entry::new(&path, item.parse_into_metadata()?));
+        }
+
+        Ok(Some(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oio::Page;
+    use serde_json::json;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::services::Onedrive;
+
+    #[tokio::test]
+    async fn test_list_with_next_link() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        let children = format!("{}/v1.0/me/drive/root:/dir:/children", server.uri());
+        Mock::given(method("GET"))
+            .and(path("/v1.0/me/drive/root:/dir:/children"))
+            .and(query_param("$skiptoken", "page2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "value": [
+                    {
+                        "id": "id_c",
+                        "name": "c",
+                        "size": 4,
+                        "eTag": "\"{C},1\"",
+                        "lastModifiedDateTime": "2023-03-01T10:00:00Z",
+                        "file": { "mimeType": "text/plain" }
+                    }
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1.0/me/drive/root:/dir:/children"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "@odata.context": "https://graph.microsoft.com/v1.0/$metadata#users('me')/drive/root/children",
+                "@odata.nextLink": format!("{children}?$skiptoken=page2"),
+                "value": [
+                    { "id": "id_a", "name": "a", "folder": { "childCount": 3 } },
+                    { "id": "id_b", "name": "b", "size": 1, "file": {} }
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut builder = Onedrive::default();
+        builder.endpoint(&server.uri()).access_token("token");
+        let backend = builder.build().expect("build must succeed");
+
+        let mut pager = OnedrivePager::new(backend, "dir/", children);
+
+        let entries = pager.next().await.unwrap().expect("first page must exist");
+        let paths: Vec<_> = entries.iter().map(|e| e.path().to_string()).collect();
+        assert_eq!(paths, vec!["dir/a/", "dir/b"]);
+
+        let entries = pager.next().await.unwrap().expect("second page must exist");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path(), "dir/c");
+        let meta = entries[0].metadata();
+        assert_eq!(meta.content_length(), 4);
+        assert_eq!(meta.content_type(), Some("text/plain"));
+        assert_eq!(meta.etag(), Some("\"{C},1\""));
+
+        assert!(pager.next().await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,500 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp;
+
+use async_trait::async_trait;
+use bytes::Buf;
+use bytes::Bytes;
+use bytes::BytesMut;
+use http::header::CONTENT_LENGTH;
+use http::header::CONTENT_RANGE;
+use http::Request;
+use http::StatusCode;
+
+use super::backend::OnedriveBackend;
+use super::backend::OnedriveUploadSession;
+use super::error::parse_error;
+use super::error::parse_write_error;
+use crate::ops::OpWrite;
+use crate::raw::*;
+use crate::*;
+
+/// Files not larger than this size will be uploaded in one request.
+const SIMPLE_UPLOAD_LIMIT: u64 = 4 * 1024 * 1024;
+
+/// OnedriveWriter uploads small files in one request, and larger files via
+/// an [upload session](https://learn.microsoft.com/en-us/graph/api/driveitem-createuploadsession).
+///
+/// Every chunk of an upload session must carry the total size, so appended
+/// bytes are only uploaded before close if the content length is known.
+pub struct OnedriveWriter {
+    backend: OnedriveBackend,
+    path: String,
+    op: OpWrite,
+
+    /// The pre-authenticated url of upload session, created lazily.
+    upload_url: Option<String>,
+    /// Bytes that not persisted by the session yet.
+    buf: BytesMut,
+    /// Size of persisted bytes, also the offset of `buf` in the file.
+    offset: u64,
+    /// The upload has been finished or cancelled.
+    closed: bool,
+}
+
+impl OnedriveWriter {
+    pub fn new(backend: OnedriveBackend, path: String, op: OpWrite) -> Self {
+        OnedriveWriter {
+            backend,
+            path,
+            op,
+
+            upload_url: None,
+            buf: BytesMut::new(),
+            offset: 0,
+            closed: false,
+        }
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        if self.closed {
+            return Ok(());
+        }
+
+        let total = self.offset + self.buf.len() as u64;
+        if let Some(expected) = self.op.content_length() {
+            if expected != total {
+                return Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "written size is not equal to the content length",
+                )
+                .with_context("path", &self.path)
+                .with_context("expected", expected.to_string())
+                .with_context("actual", total.to_string()));
+            }
+        }
+
+        if self.upload_url.is_none() && total <= SIMPLE_UPLOAD_LIMIT {
+            return self.upload_simple().await;
+        }
+
+        while !self.closed {
+            let offset = self.offset;
+            self.upload_chunk(total).await?;
+
+            if !self.closed && self.offset == offset {
+                return Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "upload session doesn't make progress while finishing",
+                )
+                .with_context("offset", offset.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    async fn upload_simple(&mut self) -> Result<()> {
+        let bs = self.buf.split().freeze();
+        let resp = self
+            .backend
+            .onedrive_upload_simple(&self.path, &self.op, bs)
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK | StatusCode::CREATED => {
+                resp.into_body().consume().await?;
+                self.closed = true;
+                Ok(())
+            }
+            _ => Err(parse_write_error(resp, self.op.if_not_exists()).await?),
+        }
+    }
+
+    async fn upload_url(&mut self) -> Result<String> {
+        if let Some(url) = &self.upload_url {
+            return Ok(url.clone());
+        }
+
+        let resp = self
+            .backend
+            .onedrive_create_upload_session(&self.path, &self.op)
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                let session: OnedriveUploadSession =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+                self.upload_url = Some(session.upload_url.clone());
+                Ok(session.upload_url)
+            }
+            _ => Err(parse_write_error(resp, self.op.if_not_exists()).await?),
+        }
+    }
+
+    /// Upload the leading chunk of buffered bytes into the upload session.
+    ///
+    /// The upload url is pre-authenticated, the access token must not be
+    /// sent along with chunks.
+    async fn upload_chunk(&mut self, total: u64) -> Result<()> {
+        if self.buf.is_empty() {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "upload session has persisted all bytes but not finished",
+            )
+            .with_context("path", &self.path)
+            .with_context("offset", self.offset.to_string()));
+        }
+
+        let upload_url = self.upload_url().await?;
+
+        let size = cmp::min(self.buf.len(), self.backend.write_chunk_size);
+        let bs = Bytes::copy_from_slice(&self.buf[..size]);
+        let range = format!(
+            "bytes {}-{}/{total}",
+            self.offset,
+            self.offset + size as u64 - 1
+        );
+
+        let req = Request::put(&upload_url)
+            .header(CONTENT_LENGTH, size)
+            .header(CONTENT_RANGE, range)
+            .body(AsyncBody::Bytes(bs))
+            .map_err(new_request_build_error)?;
+
+        let resp = self.backend.client.send_async(req).await?;
+
+        let status = resp.status();
+
+        match status {
+            // The last chunk has been received and the file is created.
+            StatusCode::OK | StatusCode::CREATED => {
+                resp.into_body().consume().await?;
+
+                self.buf.clear();
+                self.offset = total;
+                self.closed = true;
+                Ok(())
+            }
+            StatusCode::ACCEPTED => {
+                let bs = resp.into_body().bytes().await?;
+                let session: OnedriveUploadSession =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+
+                self.advance(&session.next_expected_ranges)
+            }
+            // The chunk overlaps with or skips the persisted bytes, resume
+            // from the ranges that the session is expecting.
+            StatusCode::RANGE_NOT_SATISFIABLE => {
+                resp.into_body().consume().await?;
+
+                let session = self.upload_session_status(&upload_url).await?;
+                self.advance(&session.next_expected_ranges)
+            }
+            _ => Err(parse_write_error(resp, self.op.if_not_exists()).await?),
+        }
+    }
+
+    /// Query the status of upload session.
+    async fn upload_session_status(&self, upload_url: &str) -> Result<OnedriveUploadSession> {
+        let req = Request::get(upload_url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        let resp = self.backend.client.send_async(req).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                serde_json::from_slice(&bs).map_err(new_json_deserialize_error)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    /// Drop the persisted bytes from buffer according to the ranges that
+    /// the session is expecting.
+    fn advance(&mut self, next_expected_ranges: &[String]) -> Result<()> {
+        let persisted = parse_persisted_size(next_expected_ranges)?;
+
+        let end = self.offset + self.buf.len() as u64;
+        if persisted < self.offset || persisted > end {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "persisted size of upload session is out of range",
+            )
+            .with_context("persisted", persisted.to_string())
+            .with_context("offset", self.offset.to_string())
+            .with_context("end", end.to_string()));
+        }
+
+        self.buf.advance((persisted - self.offset) as usize);
+        self.offset = persisted;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl oio::Write for OnedriveWriter {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.buf.extend_from_slice(&bs);
+        self.finish().await
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.buf.extend_from_slice(&bs);
+
+        // Bytes will be uploaded while closing if the total size is unknown
+        // or small enough to be uploaded in one request.
+        let total = match self.op.content_length() {
+            Some(total) if total > SIMPLE_UPLOAD_LIMIT => total,
+            _ => return Ok(()),
+        };
+        if self.offset + self.buf.len() as u64 > total {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "written size is larger than the content length",
+            )
+            .with_context("path", &self.path)
+            .with_context("content_length", total.to_string()));
+        }
+
+        while !self.closed && self.buf.len() >= self.backend.write_chunk_size {
+            self.upload_chunk(total).await?;
+        }
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        self.finish().await?;
+        Ok(RpWrite::new())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        self.buf.clear();
+        if self.closed {
+            return Ok(());
+        }
+        self.closed = true;
+
+        let upload_url = match self.upload_url.take() {
+            Some(url) => url,
+            None => return Ok(()),
+        };
+        let req = Request::delete(&upload_url)
+            .header(CONTENT_LENGTH, 0)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+        let resp = self.backend.client.send_async(req).await?;
+
+        match resp.status() {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+}
+
+/// Parse the size of persisted bytes from next expected ranges like
+/// `["26-"]` or `["26-100", "200-"]`.
+///
+/// Graph allows uploading chunks out of order, but we always upload them
+/// in order, so only the start of the first range matters.
+fn parse_persisted_size(next_expected_ranges: &[String]) -> Result<u64> {
+    let range = match next_expected_ranges.first() {
+        Some(v) => v,
+        None => {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "upload session has no next expected ranges",
+            )
+            .with_operation("onedrive::parse_persisted_size"))
+        }
+    };
+
+    range
+        .split('-')
+        .next()
+        .and_then(|start| start.parse::<u64>().ok())
+        .ok_or_else(|| {
+            Error::new(ErrorKind::Unexpected, "next expected range is invalid")
+                .with_operation("onedrive::parse_persisted_size")
+                .with_context("range", range)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use oio::Write;
+    use serde_json::json;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::services::Onedrive;
+
+    const CHUNK_SIZE: usize = 320 * 1024;
+
+    fn new_writer(server: &MockServer, op: OpWrite) -> OnedriveWriter {
+        let mut builder = Onedrive::default();
+        builder
+            .endpoint(&server.uri())
+            .access_token("token")
+            .write_chunk_size(CHUNK_SIZE);
+        let backend = builder.build().expect("build must succeed");
+
+        OnedriveWriter::new(backend, "dir/file".to_string(), op)
+    }
+
+    async fn mount_upload_session(server: &MockServer) {
+        Mock::given(method("POST"))
+            .and(path("/v1.0/me/drive/root:/dir/file:/createUploadSession"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "uploadUrl": format!("{}/session", server.uri()),
+                "expirationDateTime": "2023-03-01T10:00:00Z",
+                "nextExpectedRanges": ["0-"]
+            })))
+            .expect(1)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_upload_simple_if_not_exists() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/v1.0/me/drive/root:/dir/file:/content"))
+            .and(query_param("@microsoft.graph.conflictBehavior", "fail"))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error": { "code": "nameAlreadyExists", "message": "Name already exists" }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut w = new_writer(&server, OpWrite::new().with_if_not_exists(true));
+        let err = w
+            .write(Bytes::from(vec![1; 100]))
+            .await
+            .expect_err("write must fail");
+        assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
+    }
+
+    #[tokio::test]
+    async fn test_upload_session_resume() {
+        let _ = env_logger::try_init();
+
+        let total = CHUNK_SIZE + 100;
+
+        let server = MockServer::start().await;
+        mount_upload_session(&server).await;
+        // The session only persisted part of the first chunk ...
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes 0-327679/327780"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                "expirationDateTime": "2023-03-01T10:00:00Z",
+                "nextExpectedRanges": ["100-"]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // ... and rejects the next chunk, the status of session is queried
+        // to find out where to resume from.
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes 100-327779/327780"))
+            .respond_with(ResponseTemplate::new(416))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/session"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "expirationDateTime": "2023-03-01T10:00:00Z",
+                "nextExpectedRanges": ["327680-"]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // The last chunk completes the upload.
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .and(header("content-range", "bytes 327680-327779/327780"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "id": "id_file",
+                "name": "file",
+                "size": total,
+                "file": {}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut w = new_writer(&server, OpWrite::new().with_content_length(total as u64));
+        w.buf.extend_from_slice(&vec![1; total]);
+        w.upload_chunk(total as u64)
+            .await
+            .expect("upload chunk must succeed");
+        assert_eq!(w.offset, 100);
+        w.close().await.expect("close must succeed");
+        assert!(w.closed);
+        assert_eq!(w.offset, total as u64);
+    }
+
+    #[tokio::test]
+    async fn test_upload_session_abort() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        mount_upload_session(&server).await;
+        Mock::given(method("PUT"))
+            .and(path("/session"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({
+                "nextExpectedRanges": ["327680-"]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/session"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // Larger than 4MiB, so the session is created while appending.
+        let total = 4 * 1024 * 1024 + 100;
+        let mut w = new_writer(&server, OpWrite::new().with_content_length(total));
+        w.append(Bytes::from(vec![1; CHUNK_SIZE]))
+            .await
+            .expect("append must succeed");
+        w.abort().await.expect("abort must succeed");
+    }
+}
@@ -14,6 +14,7 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::env;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::sync::Arc;
@@ -25,23 +26,36 @@ use http::header::CONTENT_DISPOSITION;
 use http::header::CONTENT_LENGTH;
 use http::header::CONTENT_TYPE;
 use http::header::RANGE;
+use http::HeaderMap;
 use http::Request;
 use http::Response;
 use http::StatusCode;
 use http::Uri;
 use log::debug;
 use reqsign::AliyunOssBuilder;
-use reqsign::AliyunOssSigner;
 use serde::Deserialize;
 use serde::Serialize;
 
 use super::error::parse_error;
 use super::pager::OssPager;
+use super::signer::OssCredential;
+use super::signer::OssCredentialLoad;
+use super::signer::OssSigner;
 use super::writer::OssWriter;
 use crate::ops::*;
 use crate::raw::*;
 use crate::*;
 
+pub(super) mod constants {
+    pub const X_OSS_STORAGE_CLASS: &str = "x-oss-storage-class";
+    pub const X_OSS_NEXT_APPEND_POSITION: &str = "x-oss-next-append-position";
+    pub const X_OSS_VERSION_ID: &str = "x-oss-version-id";
+
+    pub const ALIBABA_CLOUD_ACCESS_KEY_ID: &str = "ALIBABA_CLOUD_ACCESS_KEY_ID";
+    pub const ALIBABA_CLOUD_ACCESS_KEY_SECRET: &str = "ALIBABA_CLOUD_ACCESS_KEY_SECRET";
+    pub const ALIBABA_CLOUD_SECURITY_TOKEN: &str = "ALIBABA_CLOUD_SECURITY_TOKEN";
+}
+
 /// Aliyun Object Storage Service (OSS) support
 ///
 /// # Capabilities
@@ -63,12 +77,39 @@ use crate::*;
 /// - `presign_endpoint`: Set the endpoint for presign.
 /// - `access_key_id`: Set the access_key_id for backend.
 /// - `access_key_secret`: Set the access_key_secret for backend.
+/// - `security_token`: Set the security_token for backend.
+/// - `storage_class`: Set the storage class of written objects.
 /// - `role_arn`: Set the role of backend.
 /// - `oidc_token`: Set the oidc_token for backend.
 /// - `allow_anonymous`: Set the backend access OSS in anonymous way.
 ///
 /// Refer to [`OssBuilder`]'s public API docs for more information.
 ///
+/// # Temporary Credentials
+///
+/// Temporary credentials issued by STS could be used by setting
+/// `security_token`, which will be sent as `x-oss-security-token` and
+/// signed with every request.
+///
+/// Static credentials can't be refreshed, use
+/// [`OssBuilder::customed_credential_load`] with an [`OssCredentialLoad`]
+/// instead. Loaded credentials will be refreshed before expiring, if the
+/// loader returns `None`, credentials will be loaded as below.
+///
+/// If nothing is set, credentials will be loaded from env
+/// `ALIBABA_CLOUD_ACCESS_KEY_ID`, `ALIBABA_CLOUD_ACCESS_KEY_SECRET` and
+/// `ALIBABA_CLOUD_SECURITY_TOKEN`, or by assuming role with OIDC via env
+/// `ALIBABA_CLOUD_ROLE_ARN`, `ALIBABA_CLOUD_OIDC_PROVIDER_ARN` and
+/// `ALIBABA_CLOUD_OIDC_TOKEN_FILE`. Credentials of OIDC will be refreshed
+/// before expiring.
+///
+/// # Append
+///
+/// Writers are backed by [AppendObject](https://www.alibabacloud.com/help/en/object-storage-service/latest/appendobject),
+/// the position of next append is tracked inside the writer. Writing with
+/// [`OpWrite::with_append_existing`] will append to the end of the existing
+/// appendable object.
+///
 /// # Example
 ///
 /// ## Via Builder
@@ -120,6 +161,10 @@ pub struct OssBuilder {
     // authenticate options
     access_key_id: Option<String>,
     access_key_secret: Option<String>,
+    security_token: Option<String>,
+    customed_credential_load: Option<Arc<dyn OssCredentialLoad>>,
+
+    storage_class: Option<String>,
 
     allow_anonymous: bool,
 
@@ -133,6 +178,7 @@ impl Debug for OssBuilder {
             .field("bucket", &self.bucket)
             .field("endpoint", &self.endpoint)
             .field("presign_endpoint", &self.presign_endpoint)
+            .field("storage_class", &self.storage_class)
             .field("allow_anonymous", &self.allow_anonymous);
 
         if self.access_key_id.is_some() {
@@ -143,6 +189,10 @@ impl Debug for OssBuilder {
             d.field("access_key_secret", &"<redacted>");
         }
 
+        if self.security_token.is_some() {
+            d.field("security_token", &"<redacted>");
+        }
+
         d.finish()
     }
 }
@@ -219,6 +269,38 @@ impl OssBuilder {
         self
     }
 
+    /// Set security_token of this backend.
+    ///
+    /// It's required while using temporary credentials issued by STS.
+    pub fn security_token(&mut self, v: &str) -> &mut Self {
+        if !v.is_empty() {
+            self.security_token = Some(v.to_string())
+        }
+
+        self
+    }
+
+    /// Adding a customed credential load for service.
+    ///
+    /// Loaded credential will be used instead of the static ones and be
+    /// refreshed before expiring.
+    pub fn customed_credential_load(&mut self, cred: impl OssCredentialLoad) -> &mut Self {
+        self.customed_credential_load = Some(Arc::new(cred));
+        self
+    }
+
+    /// Set storage class of written objects, like `Standard`, `IA` and
+    /// `Archive`.
+    ///
+    /// Bucket's default storage class will be used if not set.
+    pub fn storage_class(&mut self, v: &str) -> &mut Self {
+        if !v.is_empty() {
+            self.storage_class = Some(v.to_string())
+        }
+
+        self
+    }
+
     /// Anonymously access the bucket.
     pub fn allow_anonymous(&mut self) -> &mut Self {
         self.allow_anonymous = true;
@@ -291,6 +373,8 @@ impl Builder for OssBuilder {
         map.get("access_key_id").map(|v| builder.access_key_id(v));
         map.get("access_key_secret")
             .map(|v| builder.access_key_secret(v));
+        map.get("security_token").map(|v| builder.security_token(v));
+        map.get("storage_class").map(|v| builder.storage_class(v));
         map.get("allow_anonymous")
             .filter(|v| *v == "on" || *v == "true")
             .map(|_| builder.allow_anonymous());
@@ -345,7 +429,7 @@ impl Builder for OssBuilder {
             signer_builder.access_key_secret(sk);
         }
 
-        let signer = signer_builder.build().map_err(|e| {
+        let reqsign_signer = signer_builder.build().map_err(|e| {
             Error::new(ErrorKind::ConfigInvalid, "build AliyunOssSigner")
                 .with_context("service", Scheme::Oss)
                 .with_context("endpoint", &endpoint)
@@ -353,6 +437,42 @@ impl Builder for OssBuilder {
                 .set_source(e)
         })?;
 
+        // AliyunOssSigner can't sign with security token, take user's input
+        // first, and fallback to env if not set.
+        let credential = match (
+            &self.access_key_id,
+            &self.access_key_secret,
+            &self.security_token,
+        ) {
+            (Some(ak), Some(sk), Some(token)) => Some(OssCredential {
+                access_key_id: ak.clone(),
+                access_key_secret: sk.clone(),
+                security_token: Some(token.clone()),
+                expires_at: None,
+            }),
+            (Some(_), Some(_), None) => None,
+            _ => match (
+                env::var(constants::ALIBABA_CLOUD_ACCESS_KEY_ID),
+                env::var(constants::ALIBABA_CLOUD_ACCESS_KEY_SECRET),
+                env::var(constants::ALIBABA_CLOUD_SECURITY_TOKEN),
+            ) {
+                (Ok(ak), Ok(sk), Ok(token)) => Some(OssCredential {
+                    access_key_id: ak,
+                    access_key_secret: sk,
+                    security_token: Some(token),
+                    expires_at: None,
+                }),
+                _ => None,
+            },
+        };
+
+        let signer = OssSigner::new(
+            bucket,
+            reqsign_signer,
+            credential,
+            self.customed_credential_load.clone(),
+        );
+
         debug!("Backend build finished: {:?}", &self);
 
         Ok(OssBackend {
@@ -363,6 +483,7 @@ impl Builder for OssBuilder {
             client,
             bucket: self.bucket.clone(),
             signer: Arc::new(signer),
+            storage_class: self.storage_class.clone(),
         })
     }
 }
@@ -380,7 +501,8 @@ pub struct OssBackend {
     host: String,
     endpoint: String,
     presign_endpoint: String,
-    pub signer: Arc<AliyunOssSigner>,
+    pub signer: Arc<OssSigner>,
+    storage_class: Option<String>,
 }
 
 impl Debug for OssBackend {
@@ -390,6 +512,7 @@ impl Debug for OssBackend {
             .field("bucket", &self.bucket)
             .field("endpoint", &self.endpoint)
             .field("host", &self.host)
+            .field("storage_class", &self.storage_class)
             .finish()
     }
 }
@@ -412,6 +535,30 @@ impl Accessor for OssBackend {
             .set_root(&self.root)
             .set_name(&self.bucket)
             .set_capabilities(Read | Write | List | Scan | Presign | Batch)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                write_with_content_type: true,
+                write_with_append_existing: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                scan: true,
+                presign: true,
+                presign_read: true,
+                presign_stat: true,
+                presign_write: true,
+                batch: true,
+                batch_delete: true,
+                ..Default::default()
+            })
             .set_hints(ReadStreamable);
 
         am
@@ -433,27 +580,21 @@ impl Accessor for OssBackend {
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        let resp = self.oss_get_object(path, args.range()).await?;
+        let range = args.range();
+        let resp = self.oss_get_object(path, range).await?;
 
         let status = resp.status();
 
         match status {
             StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
-                let meta = parse_into_metadata(path, resp.headers())?;
-                Ok((RpRead::with_metadata(meta), resp.into_body()))
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
             }
             _ => Err(parse_error(resp).await?),
         }
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        if args.append() {
-            return Err(Error::new(
-                ErrorKind::Unsupported,
-                "append write is not supported",
-            ));
-        }
-
         Ok((
             RpWrite::default(),
             OssWriter::new(self.clone(), args, path.to_string()),
@@ -471,7 +612,13 @@ impl Accessor for OssBackend {
         let status = resp.status();
 
         match status {
-            StatusCode::OK => parse_into_metadata(path, resp.headers()).map(RpStat::new),
+            StatusCode::OK => {
+                let mut meta = parse_into_metadata(path, resp.headers())?;
+                if let Some(v) = parse_storage_class(resp.headers())? {
+                    meta.set_storage_class(&v);
+                }
+                Ok(RpStat::new(meta))
+            }
             StatusCode::NOT_FOUND if path.ends_with('/') => {
                 let m = Metadata::new(EntryMode::DIR);
                 Ok(RpStat::new(m))
@@ -522,9 +669,7 @@ impl Accessor for OssBackend {
             )?,
         };
 
-        self.signer
-            .sign_query(&mut req, args.expire())
-            .map_err(new_request_sign_error)?;
+        self.signer.sign_query(&mut req, args.expire())?;
 
         // We don't need this request anymore, consume it directly.
         let (parts, _) = req.into_parts();
@@ -623,6 +768,45 @@ impl OssBackend {
             req = req.header(CONTENT_DISPOSITION, pos);
         }
 
+        if let Some(v) = &self.storage_class {
+            req = req.header(constants::X_OSS_STORAGE_CLASS, v);
+        }
+
+        let req = req.body(body).map_err(new_request_build_error)?;
+        Ok(req)
+    }
+
+    pub fn oss_append_object_request(
+        &self,
+        path: &str,
+        position: u64,
+        size: usize,
+        content_type: Option<&str>,
+        body: AsyncBody,
+    ) -> Result<Request<AsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+        let endpoint = self.get_endpoint(false);
+        let url = format!(
+            "{}/{}?append&position={position}",
+            endpoint,
+            percent_encode_path(&p)
+        );
+
+        let mut req = Request::post(&url);
+
+        req = req.header(CONTENT_LENGTH, size);
+
+        if let Some(mime) = content_type {
+            req = req.header(CONTENT_TYPE, mime);
+        }
+
+        // Storage class can only be set while creating the object.
+        if position == 0 {
+            if let Some(v) = &self.storage_class {
+                req = req.header(constants::X_OSS_STORAGE_CLASS, v);
+            }
+        }
+
         let req = req.body(body).map_err(new_request_build_error)?;
         Ok(req)
     }
@@ -713,14 +897,14 @@ impl OssBackend {
     ) -> Result<Response<IncomingAsyncBody>> {
         let mut req = self.oss_get_object_request(path, range, false)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        self.signer.sign(&mut req)?;
         self.client.send_async(req).await
     }
 
-    async fn oss_head_object(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+    pub(super) async fn oss_head_object(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
         let mut req = self.oss_head_object_request(path, false)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        self.signer.sign(&mut req)?;
         self.client.send_async(req).await
     }
 
@@ -741,7 +925,7 @@ impl OssBackend {
             false,
         )?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        self.signer.sign(&mut req)?;
         self.client.send_async(req).await
     }
 
@@ -754,13 +938,30 @@ impl OssBackend {
     ) -> Result<Response<IncomingAsyncBody>> {
         let mut req = self.oss_list_object_request(path, token, delimiter, limit)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        self.signer.sign(&mut req)?;
         self.client.send_async(req).await
     }
 
-    async fn oss_delete_object(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+    pub(super) async fn oss_append_object(
+        &self,
+        path: &str,
+        position: u64,
+        size: usize,
+        content_type: Option<&str>,
+        body: AsyncBody,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let mut req = self.oss_append_object_request(path, position, size, content_type, body)?;
+
+        self.signer.sign(&mut req)?;
+        self.client.send_async(req).await
+    }
+
+    pub(super) async fn oss_delete_object(
+        &self,
+        path: &str,
+    ) -> Result<Response<IncomingAsyncBody>> {
         let mut req = self.oss_delete_object_request(path)?;
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        self.signer.sign(&mut req)?;
         self.client.send_async(req).await
     }
 
@@ -790,7 +991,7 @@ impl OssBackend {
             .body(AsyncBody::Bytes(Bytes::from(content)))
             .map_err(new_request_build_error)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        self.signer.sign(&mut req)?;
 
         self.client.send_async(req).await
     }
@@ -804,6 +1005,37 @@ impl OssBackend {
     }
 }
 
+/// Parse storage class from `x-oss-storage-class` header.
+fn parse_storage_class(headers: &HeaderMap) -> Result<Option<String>> {
+    match headers.get(constants::X_OSS_STORAGE_CLASS) {
+        None => Ok(None),
+        Some(v) => v.to_str().map(|v| Some(v.to_string())).map_err(|e| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value is not valid utf-8 string",
+            )
+            .with_operation("oss::parse_storage_class")
+            .set_source(e)
+        }),
+    }
+}
+
+/// Parse next append position from `x-oss-next-append-position` header.
+pub(super) fn parse_next_append_position(headers: &HeaderMap) -> Result<Option<u64>> {
+    match headers.get(constants::X_OSS_NEXT_APPEND_POSITION) {
+        None => Ok(None),
+        Some(v) => v
+            .to_str()
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Some)
+            .ok_or_else(|| {
+                Error::new(ErrorKind::Unexpected, "header value is not valid integer")
+                    .with_operation("oss::parse_next_append_position")
+            }),
+    }
+}
+
 /// Request of DeleteObjects.
 #[derive(Default, Debug, Serialize)]
 #[serde(default, rename = "Delete", rename_all = "PascalCase")]
@@ -842,9 +1074,197 @@ struct DeleteObjectsResultError {
 mod tests {
     use bytes::Buf;
     use bytes::Bytes;
+    use wiremock::matchers::body_string;
+    use wiremock::matchers::header;
+    use wiremock::matchers::header_exists;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
 
     use super::*;
 
+    /// Build a backend against the mock server directly, the endpoint
+    /// built by builder will have bucket name prepended to the host.
+    fn new_backend(server: &MockServer, storage_class: Option<&str>) -> OssBackend {
+        OssBackend {
+            client: HttpClient::new().unwrap(),
+            root: "/".to_string(),
+            bucket: "bucket".to_string(),
+            host: server.address().to_string(),
+            endpoint: server.uri(),
+            presign_endpoint: server.uri(),
+            signer: Arc::new(OssSigner::new(
+                "bucket",
+                AliyunOssBuilder::default()
+                    .bucket("bucket")
+                    .allow_anonymous()
+                    .build()
+                    .unwrap(),
+                Some(OssCredential {
+                    access_key_id: "access_key_id".to_string(),
+                    access_key_secret: "access_key_secret".to_string(),
+                    security_token: Some("token".to_string()),
+                    expires_at: None,
+                }),
+                None,
+            )),
+            storage_class: storage_class.map(|v| v.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_append_position_sequencing() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/file"))
+            .and(query_param("position", "0"))
+            .and(header("x-oss-security-token", "token"))
+            .and(header("x-oss-storage-class", "IA"))
+            .and(body_string("Hello, "))
+            .respond_with(
+                ResponseTemplate::new(200).insert_header("x-oss-next-append-position", "7"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/file"))
+            .and(query_param("position", "7"))
+            .and(body_string("World!"))
+            .respond_with(
+                ResponseTemplate::new(200).insert_header("x-oss-next-append-position", "13"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server, Some("IA"));
+        let (_, mut w) = backend
+            .write("file", OpWrite::new().with_append())
+            .await
+            .unwrap();
+        oio::Write::append(&mut w, Bytes::from("Hello, "))
+            .await
+            .unwrap();
+        oio::Write::append(&mut w, Bytes::from("World!"))
+            .await
+            .unwrap();
+        oio::Write::close(&mut w).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_append_existing() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/file"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-oss-next-append-position", "13")
+                    .insert_header("x-oss-storage-class", "Standard"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/file"))
+            .and(query_param("position", "13"))
+            .respond_with(
+                ResponseTemplate::new(200).insert_header("x-oss-next-append-position", "14"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/file"))
+            .and(query_param("position", "14"))
+            .respond_with(
+                ResponseTemplate::new(200).insert_header("x-oss-next-append-position", "15"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server, None);
+        let (_, mut w) = backend
+            .write(
+                "file",
+                OpWrite::new().with_append().with_append_existing(true),
+            )
+            .await
+            .unwrap();
+        oio::Write::append(&mut w, Bytes::from("a")).await.unwrap();
+        oio::Write::append(&mut w, Bytes::from("b")).await.unwrap();
+        oio::Write::close(&mut w).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_append_overwrite_existing() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        // Mounted first so that it will be matched first.
+        Mock::given(method("POST"))
+            .and(path("/file"))
+            .and(query_param("position", "0"))
+            .respond_with(ResponseTemplate::new(409))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/file"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/file"))
+            .and(query_param("position", "0"))
+            .respond_with(
+                ResponseTemplate::new(200).insert_header("x-oss-next-append-position", "1"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server, None);
+        let (_, mut w) = backend
+            .write("file", OpWrite::new().with_append())
+            .await
+            .unwrap();
+        oio::Write::append(&mut w, Bytes::from("a")).await.unwrap();
+        oio::Write::close(&mut w).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_storage_class() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/file"))
+            .and(header_exists("authorization"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-length", "13")
+                    .insert_header("x-oss-storage-class", "Archive"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server, None);
+        let rp = backend.stat("file", OpStat::new()).await.unwrap();
+        assert_eq!(rp.into_metadata().storage_class(), Some("Archive"));
+    }
+
     /// This example is from https://www.alibabacloud.com/help/zh/object-storage-service/latest/deletemultipleobjects
     #[test]
     fn test_serialize_delete_objects_request() {
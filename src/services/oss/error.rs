@@ -14,7 +14,6 @@
 
 use bytes::Buf;
 use http::Response;
-use http::StatusCode;
 use quick_xml::de;
 use serde::Deserialize;
 
@@ -38,22 +37,29 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
     let (parts, body) = resp.into_parts();
     let bs = body.bytes().await?;
 
-    let (kind, retryable) = match parts.status {
-        StatusCode::NOT_FOUND => (ErrorKind::NotFound, false),
-        StatusCode::FORBIDDEN => (ErrorKind::PermissionDenied, false),
-        StatusCode::INTERNAL_SERVER_ERROR
-        | StatusCode::BAD_GATEWAY
-        | StatusCode::SERVICE_UNAVAILABLE
-        | StatusCode::GATEWAY_TIMEOUT => (ErrorKind::Unexpected, true),
-        _ => (ErrorKind::Unexpected, false),
-    };
+    let (mut kind, mut retryable) = parse_error_status(parts.status);
 
-    let message = match de::from_reader::<_, OssError>(bs.clone().reader()) {
-        Ok(oss_err) => format!("{oss_err:?}"),
-        Err(_) => String::from_utf8_lossy(&bs).into_owned(),
-    };
+    let (message, oss_err) = de::from_reader::<_, OssError>(bs.clone().reader())
+        .map(|oss_err| (format!("{oss_err:?}"), Some(oss_err)))
+        .unwrap_or_else(|_| (String::from_utf8_lossy(&bs).into_owned(), None));
 
-    let mut err = Error::new(kind, &message).with_context("response", format!("{parts:?}"));
+    if let Some(oss_err) = oss_err {
+        (kind, retryable) = match oss_err.code.trim() {
+            // Credentials are not valid, users need to fix their config.
+            "InvalidAccessKeyId" | "SignatureDoesNotMatch" | "InvalidSecurityToken" => {
+                (ErrorKind::ConfigInvalid, false)
+            }
+            "AccessDenied" => (ErrorKind::PermissionDenied, false),
+            "NoSuchBucket" => (ErrorKind::NotFound, false),
+            _ => (kind, retryable),
+        }
+    }
+
+    let mut err = with_error_response_context(Error::new(kind, &message), &parts);
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
 
     if retryable {
         err = err.set_temporary();
@@ -64,6 +70,9 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
 
 #[cfg(test)]
 mod tests {
+    use futures::stream;
+    use http::StatusCode;
+
     use super::*;
 
     /// Error response example is from https://www.alibabacloud.com/help/en/object-storage-service/latest/error-responses
@@ -97,4 +106,35 @@ mod tests {
         assert_eq!(out.request_id, "1D842BC54255****");
         assert_eq!(out.host_id, "oss-cn-hangzhou.aliyuncs.com");
     }
+
+    #[tokio::test]
+    async fn test_parse_error_auth() -> Result<()> {
+        let cases = vec![
+            ("InvalidAccessKeyId", ErrorKind::ConfigInvalid),
+            ("SignatureDoesNotMatch", ErrorKind::ConfigInvalid),
+            ("AccessDenied", ErrorKind::PermissionDenied),
+        ];
+
+        for (code, kind) in cases {
+            let bs = bytes::Bytes::from(format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error xmlns="http://doc.oss-cn-hangzhou.aliyuncs.com">
+  <Code>{code}</Code>
+  <Message>some message</Message>
+  <RequestId>1D842BC54255</RequestId>
+</Error>"#
+            ));
+            let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+            let resp = Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .body(body)
+                .unwrap();
+
+            let err = parse_error(resp).await?;
+            assert_eq!(err.kind(), kind, "code: {code}");
+            assert!(!err.is_temporary(), "code: {code}");
+        }
+
+        Ok(())
+    }
 }
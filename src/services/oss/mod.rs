@@ -15,6 +15,10 @@
 mod backend;
 pub use backend::OssBuilder as Oss;
 
+mod signer;
+pub use signer::OssCredential;
+pub use signer::OssCredentialLoad;
+
 mod error;
 mod pager;
 mod writer;
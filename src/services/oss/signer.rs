@@ -0,0 +1,607 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use base64::engine::general_purpose;
+use base64::Engine;
+use hmac::Hmac;
+use hmac::Mac;
+use http::header::AUTHORIZATION;
+use http::header::CONTENT_TYPE;
+use http::header::DATE;
+use http::HeaderMap;
+use http::HeaderValue;
+use http::Method;
+use http::Request;
+use http::Uri;
+use percent_encoding::percent_decode_str;
+use reqsign::AliyunOssSigner;
+use sha1::Sha1;
+use time::Duration;
+use time::OffsetDateTime;
+
+use crate::raw::*;
+use crate::*;
+
+const X_OSS_SECURITY_TOKEN: &str = "x-oss-security-token";
+
+/// Credential will be refreshed if it's going to expire in this duration.
+const EXPIRE_BUFFER: Duration = Duration::minutes(2);
+
+/// Sub-resources that need to be included in the canonicalized resource.
+///
+/// ref: <https://www.alibabacloud.com/help/en/object-storage-service/latest/include-signatures-in-the-authorization-header>
+const SUB_RESOURCES: &[&str] = &[
+    "acl",
+    "append",
+    "bucketInfo",
+    "cname",
+    "comp",
+    "cors",
+    "delete",
+    "lifecycle",
+    "location",
+    "logging",
+    "objectMeta",
+    "partNumber",
+    "position",
+    "referer",
+    "response-cache-control",
+    "response-content-disposition",
+    "response-content-encoding",
+    "response-content-language",
+    "response-content-type",
+    "response-expires",
+    "restore",
+    "security-token",
+    "symlink",
+    "tagging",
+    "uploadId",
+    "uploads",
+    "versionId",
+    "versioning",
+    "versions",
+    "website",
+    "x-oss-process",
+];
+
+/// Credential to access OSS.
+///
+/// `security_token` is required while using temporary credentials
+/// issued by STS.
+#[derive(Default, Clone)]
+pub struct OssCredential {
+    /// Access key id of this credential.
+    pub access_key_id: String,
+    /// Access key secret of this credential.
+    pub access_key_secret: String,
+    /// Security token of this credential, only used by STS.
+    pub security_token: Option<String>,
+    /// The time this credential will expire, `None` means never.
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+impl Debug for OssCredential {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OssCredential")
+            .field("access_key_id", &self.access_key_id)
+            .field("access_key_secret", &"<redacted>")
+            .field(
+                "security_token",
+                &self.security_token.as_ref().map(|_| "<redacted>"),
+            )
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+impl OssCredential {
+    fn is_valid(&self) -> bool {
+        match self.expires_at {
+            None => true,
+            Some(t) => t > OffsetDateTime::now_utc() + EXPIRE_BUFFER,
+        }
+    }
+}
+
+/// Load [`OssCredential`] from customized source, for example, by
+/// assuming role via STS.
+///
+/// Loaded credential will be cached until it's about to expire, and then
+/// `load_credential` will be called again to refresh it.
+pub trait OssCredentialLoad: Send + Sync + 'static {
+    /// Load credential, returns `None` if there is no valid credential.
+    fn load_credential(&self) -> Result<Option<OssCredential>>;
+}
+
+/// OssSigner signs requests with OSS signature version 1.
+///
+/// Requests are signed by [`AliyunOssSigner`] which loads credentials from
+/// static keys, env and assume role with OIDC, unless a credential with
+/// security token is given or loaded by [`OssCredentialLoad`]. Those are
+/// signed by OssSigner itself since `AliyunOssSigner` of reqsign 0.8 can't
+/// take them.
+pub struct OssSigner {
+    bucket: String,
+    signer: AliyunOssSigner,
+
+    credential: Option<OssCredential>,
+    loader: Option<Arc<dyn OssCredentialLoad>>,
+    loaded: Mutex<Option<OssCredential>>,
+}
+
+impl Debug for OssSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OssSigner")
+            .field("bucket", &self.bucket)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OssSigner {
+    pub fn new(
+        bucket: &str,
+        signer: AliyunOssSigner,
+        credential: Option<OssCredential>,
+        loader: Option<Arc<dyn OssCredentialLoad>>,
+    ) -> Self {
+        Self {
+            bucket: bucket.to_string(),
+            signer,
+            credential,
+            loader,
+            loaded: Mutex::new(None),
+        }
+    }
+
+    /// Get the credential to sign, customized loader will be preferred.
+    ///
+    /// Returns `None` if requests should be signed by `AliyunOssSigner`.
+    fn credential(&self) -> Result<Option<OssCredential>> {
+        let loader = match &self.loader {
+            Some(loader) => loader,
+            None => return Ok(self.credential.clone()),
+        };
+
+        let mut loaded = self.loaded.lock().expect("lock must be valid");
+        if let Some(cred) = loaded.as_ref().filter(|cred| cred.is_valid()) {
+            return Ok(Some(cred.clone()));
+        }
+
+        *loaded = loader.load_credential()?;
+        Ok(loaded.clone().or_else(|| self.credential.clone()))
+    }
+
+    /// Sign the request via the `Authorization` header.
+    pub fn sign(&self, req: &mut Request<AsyncBody>) -> Result<()> {
+        match self.credential()? {
+            Some(cred) => self.sign_at(req, &cred, OffsetDateTime::now_utc()),
+            None => self.signer.sign(req).map_err(new_request_sign_error),
+        }
+    }
+
+    fn sign_at(
+        &self,
+        req: &mut Request<AsyncBody>,
+        cred: &OssCredential,
+        now: OffsetDateTime,
+    ) -> Result<()> {
+        let date = format_http_date(now);
+        req.headers_mut().insert(DATE, build_header_value(&date)?);
+        if let Some(token) = &cred.security_token {
+            req.headers_mut()
+                .insert(X_OSS_SECURITY_TOKEN, build_header_value(token)?);
+        }
+
+        let string_to_sign =
+            build_string_to_sign(req.method(), req.headers(), &date, &self.bucket, req.uri());
+        let signature = sign_string(&cred.access_key_secret, &string_to_sign);
+
+        req.headers_mut().insert(
+            AUTHORIZATION,
+            build_header_value(&format!("OSS {}:{}", cred.access_key_id, signature))?,
+        );
+
+        Ok(())
+    }
+
+    /// Sign the request via query string which will expire after given
+    /// duration.
+    pub fn sign_query(&self, req: &mut Request<AsyncBody>, expire: Duration) -> Result<()> {
+        match self.credential()? {
+            Some(cred) => self.sign_query_at(req, &cred, expire, OffsetDateTime::now_utc()),
+            None => self
+                .signer
+                .sign_query(req, expire)
+                .map_err(new_request_sign_error),
+        }
+    }
+
+    fn sign_query_at(
+        &self,
+        req: &mut Request<AsyncBody>,
+        cred: &OssCredential,
+        expire: Duration,
+        now: OffsetDateTime,
+    ) -> Result<()> {
+        // security-token is a sub-resource which must be signed.
+        let mut query = req.uri().query().unwrap_or_default().to_string();
+        if let Some(token) = &cred.security_token {
+            query.push_str(&format!("&security-token={}", percent_encode_path(token)));
+        }
+        let uri = replace_query(req.uri(), query.trim_start_matches('&'))?;
+
+        let expires = (now.unix_timestamp() + expire.whole_seconds()).to_string();
+        let string_to_sign =
+            build_string_to_sign(req.method(), req.headers(), &expires, &self.bucket, &uri);
+        let signature = sign_string(&cred.access_key_secret, &string_to_sign);
+
+        let mut query = uri.query().unwrap_or_default().to_string();
+        query.push_str(&format!(
+            "&OSSAccessKeyId={}&Expires={expires}&Signature={}",
+            percent_encode_path(&cred.access_key_id),
+            percent_encode_path(&signature)
+        ));
+        *req.uri_mut() = replace_query(&uri, query.trim_start_matches('&'))?;
+
+        Ok(())
+    }
+}
+
+fn build_header_value(v: &str) -> Result<HeaderValue> {
+    HeaderValue::from_str(v).map_err(|e| {
+        Error::new(ErrorKind::Unexpected, "header value is invalid")
+            .with_operation("oss::OssSigner")
+            .set_source(e)
+    })
+}
+
+fn replace_query(uri: &Uri, query: &str) -> Result<Uri> {
+    let path = uri.path();
+    let path_and_query = if query.is_empty() {
+        path.to_string()
+    } else {
+        format!("{path}?{query}")
+    };
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(path_and_query.parse().map_err(|e| {
+        Error::new(ErrorKind::Unexpected, "uri is invalid")
+            .with_operation("oss::OssSigner")
+            .set_source(e)
+    })?);
+    Uri::from_parts(parts).map_err(|e| {
+        Error::new(ErrorKind::Unexpected, "uri is invalid")
+            .with_operation("oss::OssSigner")
+            .set_source(e)
+    })
+}
+
+/// Format time in the http date format, like `Sat, 26 Nov 2022 10:43:05 GMT`.
+fn format_http_date(t: OffsetDateTime) -> String {
+    let t = t.to_offset(time::UtcOffset::UTC);
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        &t.weekday().to_string()[..3],
+        t.day(),
+        &t.month().to_string()[..3],
+        t.year(),
+        t.hour(),
+        t.minute(),
+        t.second()
+    )
+}
+
+/// Build the string to sign:
+///
+/// ```text
+/// VERB + "\n"
+/// + Content-MD5 + "\n"
+/// + Content-Type + "\n"
+/// + Date + "\n"
+/// + CanonicalizedOSSHeaders
+/// + CanonicalizedResource
+/// ```
+fn build_string_to_sign(
+    method: &Method,
+    headers: &HeaderMap,
+    date: &str,
+    bucket: &str,
+    uri: &Uri,
+) -> String {
+    let header = |k: &str| {
+        headers
+            .get(k)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+    };
+
+    let mut s = format!(
+        "{}\n{}\n{}\n{}\n",
+        method.as_str(),
+        header("content-md5"),
+        header(CONTENT_TYPE.as_str()),
+        date
+    );
+
+    // Canonicalized OSS headers.
+    let mut oss_headers: Vec<(String, &str)> = headers
+        .iter()
+        .filter(|(k, _)| k.as_str().starts_with("x-oss-"))
+        .map(|(k, v)| {
+            (
+                k.as_str().to_lowercase(),
+                v.to_str().unwrap_or_default().trim(),
+            )
+        })
+        .collect();
+    oss_headers.sort();
+    for (k, v) in oss_headers {
+        s.push_str(&format!("{k}:{v}\n"));
+    }
+
+    // Canonicalized resource.
+    let path = percent_decode_str(uri.path()).decode_utf8_lossy();
+    s.push_str(&format!("/{bucket}{path}"));
+
+    let mut sub_resources: Vec<(String, String)> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|kv| !kv.is_empty())
+        .map(|kv| {
+            let (k, v) = kv.split_once('=').unwrap_or((kv, ""));
+            (
+                percent_decode_str(k).decode_utf8_lossy().to_string(),
+                percent_decode_str(v).decode_utf8_lossy().to_string(),
+            )
+        })
+        .filter(|(k, _)| SUB_RESOURCES.contains(&k.as_str()))
+        .collect();
+    sub_resources.sort();
+    for (idx, (k, v)) in sub_resources.iter().enumerate() {
+        s.push(if idx == 0 { '?' } else { '&' });
+        s.push_str(k);
+        if !v.is_empty() {
+            s.push('=');
+            s.push_str(v);
+        }
+    }
+
+    s
+}
+
+fn sign_string(secret: &str, s: &str) -> String {
+    let mut mac =
+        Hmac::<Sha1>::new_from_slice(secret.as_bytes()).expect("hmac accepts any key size");
+    mac.update(s.as_bytes());
+
+    general_purpose::STANDARD.encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+
+    /// `Sat, 26 Nov 2022 10:43:05 GMT`
+    fn test_time() -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(1669459385).unwrap()
+    }
+
+    fn new_reqsign_signer() -> AliyunOssSigner {
+        reqsign::AliyunOssBuilder::default()
+            .bucket("bucket")
+            .access_key_id("reqsign_access_key_id")
+            .access_key_secret("access_key_secret")
+            .build()
+            .unwrap()
+    }
+
+    fn new_credential(token: Option<&str>) -> OssCredential {
+        OssCredential {
+            access_key_id: "access_key_id".to_string(),
+            access_key_secret: "access_key_secret".to_string(),
+            security_token: token.map(|v| v.to_string()),
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn test_format_http_date() {
+        assert_eq!(
+            format_http_date(test_time()),
+            "Sat, 26 Nov 2022 10:43:05 GMT"
+        );
+    }
+
+    /// Example is from https://www.alibabacloud.com/help/en/object-storage-service/latest/include-signatures-in-the-authorization-header
+    #[test]
+    fn test_build_string_to_sign() {
+        let req = Request::put("https://oss-example.oss-cn-hangzhou.aliyuncs.com/nelson")
+            .header("content-md5", "eB5eJF1ptWaXm4bijSPyxw==")
+            .header(CONTENT_TYPE, "text/html")
+            .header("x-oss-meta-author", "foo@example.com")
+            .header("X-OSS-Meta-Magic", "abracadabra")
+            .body(AsyncBody::Empty)
+            .unwrap();
+
+        let s = build_string_to_sign(
+            req.method(),
+            req.headers(),
+            "Wed, 28 Dec 2022 10:27:41 GMT",
+            "examplebucket",
+            req.uri(),
+        );
+        assert_eq!(
+            s,
+            "PUT\neB5eJF1ptWaXm4bijSPyxw==\ntext/html\nWed, 28 Dec 2022 10:27:41 GMT\nx-oss-meta-author:foo@example.com\nx-oss-meta-magic:abracadabra\n/examplebucket/nelson"
+        );
+    }
+
+    #[test]
+    fn test_sign_with_security_token() {
+        let signer = OssSigner::new(
+            "bucket",
+            new_reqsign_signer(),
+            Some(new_credential(Some("token"))),
+            None,
+        );
+
+        let mut req =
+            Request::post("https://bucket.oss-cn-hangzhou.aliyuncs.com/dir/file?append&position=0")
+                .header(CONTENT_TYPE, "text/plain")
+                .body(AsyncBody::Empty)
+                .unwrap();
+        signer
+            .sign_at(&mut req, &new_credential(Some("token")), test_time())
+            .unwrap();
+
+        assert_eq!(req.headers()[X_OSS_SECURITY_TOKEN], "token");
+        assert_eq!(req.headers()[DATE], "Sat, 26 Nov 2022 10:43:05 GMT");
+
+        let s = build_string_to_sign(
+            req.method(),
+            req.headers(),
+            "Sat, 26 Nov 2022 10:43:05 GMT",
+            "bucket",
+            req.uri(),
+        );
+        assert_eq!(
+            s,
+            "POST\n\ntext/plain\nSat, 26 Nov 2022 10:43:05 GMT\nx-oss-security-token:token\n/bucket/dir/file?append&position=0"
+        );
+        assert_eq!(
+            req.headers()[AUTHORIZATION].to_str().unwrap(),
+            format!("OSS access_key_id:{}", sign_string("access_key_secret", &s))
+        );
+    }
+
+    #[test]
+    fn test_sign_query_with_security_token() {
+        let signer = OssSigner::new(
+            "bucket",
+            new_reqsign_signer(),
+            Some(new_credential(Some("token"))),
+            None,
+        );
+
+        let mut req = Request::get("https://bucket.oss-cn-hangzhou.aliyuncs.com/file")
+            .body(AsyncBody::Empty)
+            .unwrap();
+        signer
+            .sign_query_at(
+                &mut req,
+                &new_credential(Some("token")),
+                Duration::hours(1),
+                test_time(),
+            )
+            .unwrap();
+
+        let expected = "GET\n\n\n1669462985\n/bucket/file?security-token=token";
+        assert_eq!(
+            req.uri().query(),
+            Some(
+                format!(
+                    "security-token=token&OSSAccessKeyId=access_key_id&Expires=1669462985&Signature={}",
+                    percent_encode_path(&sign_string("access_key_secret", expected))
+                )
+                .as_str()
+            )
+        );
+    }
+
+    #[test]
+    fn test_sign_via_reqsign() {
+        let signer = OssSigner::new("bucket", new_reqsign_signer(), None, None);
+
+        let mut req = Request::get("https://bucket.oss-cn-hangzhou.aliyuncs.com/file")
+            .body(AsyncBody::Empty)
+            .unwrap();
+        signer.sign(&mut req).unwrap();
+
+        assert!(req.headers()[AUTHORIZATION]
+            .to_str()
+            .unwrap()
+            .starts_with("OSS reqsign_access_key_id:"));
+        assert!(req.headers().get(X_OSS_SECURITY_TOKEN).is_none());
+
+        // Loaders without credential fallback to reqsign too.
+        let signer = OssSigner::new(
+            "bucket",
+            new_reqsign_signer(),
+            None,
+            Some(Arc::new(NoneLoader)),
+        );
+        let mut req = Request::get("https://bucket.oss-cn-hangzhou.aliyuncs.com/file")
+            .body(AsyncBody::Empty)
+            .unwrap();
+        signer.sign(&mut req).unwrap();
+        assert!(req.headers()[AUTHORIZATION]
+            .to_str()
+            .unwrap()
+            .starts_with("OSS reqsign_access_key_id:"));
+    }
+
+    struct NoneLoader;
+
+    impl OssCredentialLoad for NoneLoader {
+        fn load_credential(&self) -> Result<Option<OssCredential>> {
+            Ok(None)
+        }
+    }
+
+    #[derive(Default)]
+    struct MockLoader {
+        count: AtomicUsize,
+    }
+
+    impl OssCredentialLoad for MockLoader {
+        fn load_credential(&self) -> Result<Option<OssCredential>> {
+            let n = self.count.fetch_add(1, Ordering::SeqCst);
+            Ok(Some(OssCredential {
+                access_key_id: format!("access_key_id_{n}"),
+                access_key_secret: "access_key_secret".to_string(),
+                security_token: Some(format!("token_{n}")),
+                // The first credential is about to expire.
+                expires_at: Some(if n == 0 {
+                    OffsetDateTime::now_utc() + Duration::seconds(10)
+                } else {
+                    OffsetDateTime::now_utc() + Duration::hours(1)
+                }),
+            }))
+        }
+    }
+
+    #[test]
+    fn test_refresh_credential() {
+        let loader = Arc::new(MockLoader::default());
+        let signer = OssSigner::new("bucket", new_reqsign_signer(), None, Some(loader.clone()));
+
+        for expected in ["token_0", "token_1", "token_1"] {
+            let mut req = Request::get("https://bucket.oss-cn-hangzhou.aliyuncs.com/file")
+                .body(AsyncBody::Empty)
+                .unwrap();
+            signer.sign(&mut req).unwrap();
+            assert_eq!(req.headers()[X_OSS_SECURITY_TOKEN], expected);
+        }
+        assert_eq!(loader.count.load(Ordering::SeqCst), 2);
+    }
+}
@@ -12,10 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::mem;
+
 use async_trait::async_trait;
 use bytes::Bytes;
 use http::StatusCode;
 
+use super::backend::constants;
+use super::backend::parse_next_append_position;
 use super::backend::OssBackend;
 use super::error::parse_error;
 use crate::ops::OpWrite;
@@ -27,17 +31,114 @@ pub struct OssWriter {
 
     op: OpWrite,
     path: String,
+
+    /// The position of next append, `None` means it's not fetched yet.
+    position: Option<u64>,
+
+    /// The reply of the latest put or append, will be returned while
+    /// closing.
+    rp: RpWrite,
 }
 
 impl OssWriter {
     pub fn new(backend: OssBackend, op: OpWrite, path: String) -> Self {
-        OssWriter { backend, op, path }
+        // Appending to existing object should start from its end which
+        // will be fetched before the first append.
+        let position = if op.append_existing() { None } else { Some(0) };
+
+        OssWriter {
+            backend,
+            op,
+            path,
+            position,
+
+            rp: RpWrite::new(),
+        }
+    }
+
+    async fn position(&mut self) -> Result<u64> {
+        if let Some(position) = self.position {
+            return Ok(position);
+        }
+
+        let resp = self.backend.oss_head_object(&self.path).await?;
+        let position = match resp.status() {
+            StatusCode::OK => match parse_next_append_position(resp.headers())? {
+                Some(v) => v,
+                None => parse_content_length(resp.headers())?.unwrap_or_default(),
+            },
+            StatusCode::NOT_FOUND => 0,
+            _ => return Err(parse_error(resp).await?),
+        };
+
+        self.position = Some(position);
+        Ok(position)
+    }
+
+    async fn append_at(&mut self, position: u64, bs: Bytes) -> Result<()> {
+        let size = bs.len();
+        let mut resp = self
+            .backend
+            .oss_append_object(
+                &self.path,
+                position,
+                size,
+                self.op.content_type(),
+                AsyncBody::Bytes(bs.clone()),
+            )
+            .await?;
+
+        // The object existed before this writer, remove it and start
+        // over so that the file is overwritten like other writes.
+        if resp.status() == StatusCode::CONFLICT && position == 0 && !self.op.append_existing() {
+            resp.into_body().consume().await?;
+            self.delete().await?;
+
+            resp = self
+                .backend
+                .oss_append_object(
+                    &self.path,
+                    0,
+                    size,
+                    self.op.content_type(),
+                    AsyncBody::Bytes(bs),
+                )
+                .await?;
+        }
+
+        match resp.status() {
+            StatusCode::OK => {
+                let next = parse_next_append_position(resp.headers())?;
+                self.rp = parse_into_write_reply(resp.headers(), constants::X_OSS_VERSION_ID)?;
+                resp.into_body().consume().await?;
+
+                self.position = Some(next.unwrap_or(position + size as u64));
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn delete(&self) -> Result<()> {
+        let resp = self.backend.oss_delete_object(&self.path).await?;
+        match resp.status() {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
     }
 }
 
 #[async_trait]
 impl oio::Write for OssWriter {
     async fn write(&mut self, bs: Bytes) -> Result<()> {
+        if self.op.append_existing() {
+            let position = self.position().await?;
+            return self.append_at(position, bs).await;
+        }
+
         let mut req = self.backend.oss_put_object_request(
             &self.path,
             Some(bs.len()),
@@ -47,10 +148,7 @@ impl oio::Write for OssWriter {
             false,
         )?;
 
-        self.backend
-            .signer
-            .sign(&mut req)
-            .map_err(new_request_sign_error)?;
+        self.backend.signer.sign(&mut req)?;
 
         let resp = self.backend.client.send_async(req).await?;
 
@@ -58,6 +156,7 @@ impl oio::Write for OssWriter {
 
         match status {
             StatusCode::CREATED | StatusCode::OK => {
+                self.rp = parse_into_write_reply(resp.headers(), constants::X_OSS_VERSION_ID)?;
                 resp.into_body().consume().await?;
                 Ok(())
             }
@@ -66,15 +165,25 @@ impl oio::Write for OssWriter {
     }
 
     async fn append(&mut self, bs: Bytes) -> Result<()> {
-        let _ = bs;
+        let position = self.position().await?;
+        self.append_at(position, bs).await
+    }
 
-        Err(Error::new(
-            ErrorKind::Unsupported,
-            "output writer doesn't support append",
-        ))
+    async fn close(&mut self) -> Result<RpWrite> {
+        // Make sure the file exists even if nothing has been appended.
+        if self.op.append() && !self.op.append_existing() && self.position == Some(0) {
+            self.append_at(0, Bytes::new()).await?;
+        }
+
+        Ok(mem::take(&mut self.rp))
     }
 
-    async fn close(&mut self) -> Result<()> {
-        Ok(())
+    async fn abort(&mut self) -> Result<()> {
+        // Appended data to existing object can't be reverted.
+        if self.op.append_existing() || self.position == Some(0) {
+            return Ok(());
+        }
+
+        self.delete().await
     }
 }
@@ -21,10 +21,14 @@ use std::time::Duration;
 use async_trait::async_trait;
 use http::Uri;
 use redis::aio::ConnectionManager;
-use redis::AsyncCommands;
+use redis::cluster::ClusterClient;
+use redis::cluster_async::ClusterConnection;
 use redis::Client;
+use redis::Cmd;
 use redis::ConnectionAddr;
 use redis::ConnectionInfo;
+use redis::FromRedisValue;
+use redis::Pipeline;
 use redis::RedisConnectionInfo;
 use redis::RedisError;
 use tokio::sync::OnceCell;
@@ -35,6 +39,7 @@ use crate::*;
 
 const DEFAULT_REDIS_ENDPOINT: &str = "tcp://127.0.0.1:6379";
 const DEFAULT_REDIS_PORT: u16 = 6379;
+const DEFAULT_SENTINEL_PORT: u16 = 26379;
 
 /// [Redis](https://redis.io/) services support.
 ///
@@ -45,17 +50,34 @@ const DEFAULT_REDIS_PORT: u16 = 6379;
 /// - [x] read
 /// - [x] write
 /// - [ ] ~~list~~
-/// - [ ] scan
+/// - [x] scan
 /// - [ ] ~~presign~~
 /// - [ ] blocking
 ///
+/// # Notes
+///
+/// Values are stored as redis strings, the path will be used as the key
+/// directly with `root` as the key prefix. For example, `abc` under root
+/// `/sessions/` will be stored at key `sessions/abc`.
+///
+/// - `stat` is served by `STRLEN` without fetching the value.
+/// - `scan` (and `list` which is emulated by `scan`) is served by `SCAN`
+///   with `MATCH` on the key prefix, which is O(keys) of the whole DB.
+///   `scan` is not supported while connecting to a redis cluster.
+/// - Redis sentinel will be asked for the master address while building
+///   the first connection.
+///
 /// # Configuration
 ///
 /// - `root`: Set the working directory of `OpenDAL`
 /// - `endpoint`: Set the network address of redis server
+/// - `cluster_endpoints`: Set the network addresses of redis cluster
+/// - `sentinel_endpoints`: Set the network addresses of redis sentinels
+/// - `sentinel_master`: Set the master name monitored by sentinels
 /// - `username`: Set the username of Redis
 /// - `password`: Set the password for authentication
 /// - `db`: Set the DB of redis
+/// - `default_ttl`: Set the default ttl in seconds for written keys
 ///
 /// You can refer to [`RedisBuilder`]'s docs for more information
 ///
@@ -85,6 +107,18 @@ pub struct RedisBuilder {
     ///
     /// default is "tcp://127.0.0.1:6379"
     endpoint: Option<String>,
+    /// network addresses of the Redis cluster nodes, separated by `,`.
+    ///
+    /// default is None
+    cluster_endpoints: Option<String>,
+    /// network addresses of the Redis sentinels, separated by `,`.
+    ///
+    /// default is None
+    sentinel_endpoints: Option<String>,
+    /// the master name monitored by sentinels.
+    ///
+    /// default is None
+    sentinel_master: Option<String>,
     /// the username to connect redis service.
     ///
     /// default is None
@@ -113,12 +147,22 @@ impl Debug for RedisBuilder {
         if let Some(endpoint) = self.endpoint.clone() {
             ds.field("endpoint", &endpoint);
         }
+        if let Some(endpoints) = self.cluster_endpoints.clone() {
+            ds.field("cluster_endpoints", &endpoints);
+        }
+        if let Some(endpoints) = self.sentinel_endpoints.clone() {
+            ds.field("sentinel_endpoints", &endpoints);
+        }
+        if let Some(master) = self.sentinel_master.clone() {
+            ds.field("sentinel_master", &master);
+        }
         if let Some(username) = self.username.clone() {
             ds.field("username", &username);
         }
         if self.password.is_some() {
             ds.field("password", &"<redacted>");
         }
+        ds.field("default_ttl", &self.default_ttl);
         ds.finish()
     }
 }
@@ -137,6 +181,38 @@ impl RedisBuilder {
         self
     }
 
+    /// set the network addresses of redis cluster nodes, separated by `,`.
+    ///
+    /// For example: `tcp://127.0.0.1:7000,tcp://127.0.0.1:7001`
+    ///
+    /// `endpoint` will be ignored if cluster endpoints are set.
+    pub fn cluster_endpoints(&mut self, endpoints: &str) -> &mut Self {
+        if !endpoints.is_empty() {
+            self.cluster_endpoints = Some(endpoints.to_owned());
+        }
+        self
+    }
+
+    /// set the network addresses of redis sentinels, separated by `,`.
+    ///
+    /// For example: `tcp://127.0.0.1:26379,tcp://127.0.0.1:26380`
+    ///
+    /// `sentinel_master` must be set too, and `endpoint` will be ignored.
+    pub fn sentinel_endpoints(&mut self, endpoints: &str) -> &mut Self {
+        if !endpoints.is_empty() {
+            self.sentinel_endpoints = Some(endpoints.to_owned());
+        }
+        self
+    }
+
+    /// set the master name monitored by redis sentinels.
+    pub fn sentinel_master(&mut self, master: &str) -> &mut Self {
+        if !master.is_empty() {
+            self.sentinel_master = Some(master.to_owned());
+        }
+        self
+    }
+
     /// set the username for redis
     ///
     /// default: no username
@@ -167,7 +243,8 @@ impl RedisBuilder {
 
     /// Set the default ttl for redis services.
     ///
-    /// If set, we will specify `EX` for write operations.
+    /// If set, we will specify `PX` for write operations so that keys will
+    /// be expired after ttl.
     pub fn default_ttl(&mut self, ttl: Duration) -> &mut Self {
         self.default_ttl = Some(ttl);
         self
@@ -175,6 +252,8 @@ impl RedisBuilder {
 
     /// set the working directory, all operations will be performed under it.
     ///
+    /// The root will be used as the prefix of keys.
+    ///
     /// default: "/"
     pub fn root(&mut self, root: &str) -> &mut Self {
         if !root.is_empty() {
@@ -193,68 +272,105 @@ impl Builder for RedisBuilder {
 
         map.get("root").map(|v| builder.root(v));
         map.get("endpoint").map(|v| builder.endpoint(v));
+        map.get("cluster_endpoints")
+            .map(|v| builder.cluster_endpoints(v));
+        map.get("sentinel_endpoints")
+            .map(|v| builder.sentinel_endpoints(v));
+        map.get("sentinel_master")
+            .map(|v| builder.sentinel_master(v));
         map.get("username").map(|v| builder.username(v));
         map.get("password").map(|v| builder.password(v));
         map.get("db")
             .map(|v| v.parse::<i64>().map(|v| builder.db(v)));
+        map.get("default_ttl").map(|v| {
+            v.parse::<u64>()
+                .map(|v| builder.default_ttl(Duration::from_secs(v)))
+        });
 
         builder
     }
 
     fn build(&mut self) -> Result<Self::Accessor> {
-        let endpoint = self
-            .endpoint
-            .clone()
-            .unwrap_or_else(|| DEFAULT_REDIS_ENDPOINT.to_string());
-
-        let ep_url = endpoint.parse::<Uri>().map_err(|e| {
-            Error::new(ErrorKind::ConfigInvalid, "endpoint is invalid")
-                .with_context("service", Scheme::Redis)
-                .with_context("endpoint", endpoint)
-                .set_source(e)
-        })?;
-
-        let con_addr = match ep_url.scheme_str() {
-            Some("tcp") | Some("redis") | None => {
-                let host = ep_url
-                    .host()
-                    .map(|h| h.to_string())
-                    .unwrap_or_else(|| "127.0.0.1".to_string());
-                let port = ep_url.port_u16().unwrap_or(DEFAULT_REDIS_PORT);
-                ConnectionAddr::Tcp(host, port)
-            }
-            // TODO: wait for upstream to support `rustls` based TLS connection.
-            Some("unix") | Some("redis+unix") => {
-                let path = PathBuf::from(ep_url.path());
-                ConnectionAddr::Unix(path)
-            }
-            Some(s) => {
-                return Err(
-                    Error::new(ErrorKind::ConfigInvalid, "invalid or unsupported scheme")
-                        .with_context("service", Scheme::Redis)
-                        .with_context("scheme", s),
-                )
-            }
-        };
-
         let redis_info = RedisConnectionInfo {
             db: self.db,
             username: self.username.clone(),
             password: self.password.clone(),
         };
 
-        let con_info = ConnectionInfo {
-            addr: con_addr,
-            redis: redis_info,
-        };
+        let mode = match (&self.cluster_endpoints, &self.sentinel_endpoints) {
+            (Some(_), Some(_)) => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "cluster and sentinel can't be used together",
+                )
+                .with_context("service", Scheme::Redis))
+            }
+            (Some(endpoints), None) => {
+                if self.db != 0 {
+                    return Err(Error::new(
+                        ErrorKind::ConfigInvalid,
+                        "redis cluster only supports db 0",
+                    )
+                    .with_context("service", Scheme::Redis)
+                    .with_context("db", self.db.to_string()));
+                }
+
+                let nodes = split_endpoints(endpoints)
+                    .map(|ep| {
+                        Ok(ConnectionInfo {
+                            addr: parse_connection_addr(ep, DEFAULT_REDIS_PORT)?,
+                            redis: redis_info.clone(),
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                let client = ClusterClient::new(nodes).map_err(|e| {
+                    Error::new(ErrorKind::ConfigInvalid, "invalid cluster endpoints")
+                        .with_context("service", Scheme::Redis)
+                        .with_context("cluster_endpoints", endpoints)
+                        .set_source(e)
+                })?;
 
-        let client = Client::open(con_info).map_err(|e| {
-            Error::new(ErrorKind::ConfigInvalid, "invalid or unsupported scheme")
-                .with_context("service", Scheme::Redis)
-                .with_context("endpoint", self.endpoint.as_ref().unwrap())
-                .with_context("db", self.db.to_string())
-                .set_source(e)
-        })?;
+                Mode::Cluster(client)
+            }
+            (None, Some(endpoints)) => {
+                let master = self.sentinel_master.clone().ok_or_else(|| {
+                    Error::new(ErrorKind::ConfigInvalid, "sentinel master is not set")
+                        .with_context("service", Scheme::Redis)
+                })?;
+
+                let sentinels = split_endpoints(endpoints)
+                    .map(|ep| parse_connection_addr(ep, DEFAULT_SENTINEL_PORT))
+                    .collect::<Result<Vec<_>>>()?;
+
+                Mode::Sentinel {
+                    sentinels,
+                    master,
+                    redis: redis_info,
+                }
+            }
+            (None, None) => {
+                let endpoint = self
+                    .endpoint
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_REDIS_ENDPOINT.to_string());
+
+                let con_info = ConnectionInfo {
+                    addr: parse_connection_addr(&endpoint, DEFAULT_REDIS_PORT)?,
+                    redis: redis_info,
+                };
+
+                let client = Client::open(con_info).map_err(|e| {
+                    Error::new(ErrorKind::ConfigInvalid, "invalid or unsupported scheme")
+                        .with_context("service", Scheme::Redis)
+                        .with_context("endpoint", &endpoint)
+                        .with_context("db", self.db.to_string())
+                        .set_source(e)
+                })?;
+
+                Mode::Single(client)
+            }
+        };
 
         let root = normalize_root(
             self.root
@@ -263,23 +379,118 @@ impl Builder for RedisBuilder {
                 .as_str(),
         );
 
-        let conn = OnceCell::new();
         Ok(RedisBackend::new(Adapter {
-            client,
-            conn,
+            mode,
+            conn: OnceCell::new(),
             default_ttl: self.default_ttl,
         })
         .with_root(&root))
     }
 }
 
+fn split_endpoints(endpoints: &str) -> impl Iterator<Item = &str> {
+    endpoints
+        .split(',')
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+}
+
+fn parse_connection_addr(endpoint: &str, default_port: u16) -> Result<ConnectionAddr> {
+    // `Uri` doesn't accept empty authority, so we need to handle unix socket
+    // endpoints like `unix:///var/run/redis.sock` by hand.
+    if let Some(path) = endpoint
+        .strip_prefix("unix://")
+        .or_else(|| endpoint.strip_prefix("redis+unix://"))
+    {
+        if path.starts_with('/') {
+            return Ok(ConnectionAddr::Unix(PathBuf::from(path)));
+        }
+    }
+
+    let ep_url = endpoint.parse::<Uri>().map_err(|e| {
+        Error::new(ErrorKind::ConfigInvalid, "endpoint is invalid")
+            .with_context("service", Scheme::Redis)
+            .with_context("endpoint", endpoint)
+            .set_source(e)
+    })?;
+
+    match ep_url.scheme_str() {
+        Some("tcp") | Some("redis") | None => {
+            let host = ep_url
+                .host()
+                .map(|h| h.to_string())
+                .unwrap_or_else(|| "127.0.0.1".to_string());
+            let port = ep_url.port_u16().unwrap_or(default_port);
+            Ok(ConnectionAddr::Tcp(host, port))
+        }
+        // TODO: wait for upstream to support `rustls` based TLS connection.
+        Some("unix") | Some("redis+unix") => {
+            let path = PathBuf::from(ep_url.path());
+            Ok(ConnectionAddr::Unix(path))
+        }
+        Some(s) => Err(
+            Error::new(ErrorKind::ConfigInvalid, "invalid or unsupported scheme")
+                .with_context("service", Scheme::Redis)
+                .with_context("scheme", s),
+        ),
+    }
+}
+
+/// Escape glob-style special chars in key so that it can be used in
+/// the pattern of `SCAN MATCH`.
+fn escape_pattern(key: &str) -> String {
+    let mut s = String::with_capacity(key.len());
+    for c in key.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '^' | '\\') {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+    s
+}
+
 /// Backend for redis services.
 pub type RedisBackend = kv::Backend<Adapter>;
 
+#[derive(Clone)]
+enum Mode {
+    Single(Client),
+    Cluster(ClusterClient),
+    Sentinel {
+        sentinels: Vec<ConnectionAddr>,
+        master: String,
+        redis: RedisConnectionInfo,
+    },
+}
+
+#[derive(Clone)]
+enum Connection {
+    Single(ConnectionManager),
+    Cluster(ClusterConnection),
+}
+
+impl Connection {
+    async fn query<T: FromRedisValue>(&mut self, cmd: &Cmd) -> Result<T> {
+        let v = match self {
+            Connection::Single(conn) => cmd.query_async(conn).await?,
+            Connection::Cluster(conn) => cmd.query_async(conn).await?,
+        };
+        Ok(v)
+    }
+
+    async fn query_pipeline<T: FromRedisValue>(&mut self, pipe: &Pipeline) -> Result<T> {
+        let v = match self {
+            Connection::Single(conn) => pipe.query_async(conn).await?,
+            Connection::Cluster(conn) => pipe.query_async(conn).await?,
+        };
+        Ok(v)
+    }
+}
+
 #[derive(Clone)]
 pub struct Adapter {
-    client: Client,
-    conn: OnceCell<ConnectionManager>,
+    mode: Mode,
+    conn: OnceCell<Connection>,
 
     default_ttl: Option<Duration>,
 }
@@ -289,54 +500,197 @@ impl Debug for Adapter {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut ds = f.debug_struct("Adapter");
 
-        let info = self.client.get_connection_info();
-        ds.field("addr", &info.addr);
-        ds.field("db", &info.redis.db);
-        ds.field("user", &info.redis.username);
+        match &self.mode {
+            Mode::Single(client) => {
+                let info = client.get_connection_info();
+                ds.field("addr", &info.addr);
+                ds.field("db", &info.redis.db);
+                ds.field("user", &info.redis.username);
+            }
+            Mode::Cluster(_) => {
+                ds.field("addr", &self.name());
+            }
+            Mode::Sentinel { master, redis, .. } => {
+                ds.field("addr", &self.name());
+                ds.field("master", master);
+                ds.field("db", &redis.db);
+                ds.field("user", &redis.username);
+            }
+        }
+        ds.field("default_ttl", &self.default_ttl);
         ds.finish()
     }
 }
 
 impl Adapter {
-    async fn conn(&self) -> Result<ConnectionManager> {
+    fn name(&self) -> String {
+        match &self.mode {
+            Mode::Single(client) => client.get_connection_info().addr.to_string(),
+            Mode::Cluster(_) => "cluster".to_string(),
+            Mode::Sentinel { sentinels, .. } => sentinels
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        }
+    }
+
+    async fn conn(&self) -> Result<Connection> {
         Ok(self
             .conn
-            .get_or_try_init(|| async { ConnectionManager::new(self.client.clone()).await })
+            .get_or_try_init(|| async {
+                match &self.mode {
+                    Mode::Single(client) => Ok::<_, Error>(Connection::Single(
+                        ConnectionManager::new(client.clone()).await?,
+                    )),
+                    Mode::Cluster(client) => {
+                        Ok(Connection::Cluster(client.get_async_connection().await?))
+                    }
+                    Mode::Sentinel {
+                        sentinels,
+                        master,
+                        redis,
+                    } => {
+                        let client = Self::resolve_master(sentinels, master, redis).await?;
+                        Ok(Connection::Single(ConnectionManager::new(client).await?))
+                    }
+                }
+            })
             .await?
             .clone())
     }
+
+    /// Ask sentinels one by one for the address of master.
+    async fn resolve_master(
+        sentinels: &[ConnectionAddr],
+        master: &str,
+        redis: &RedisConnectionInfo,
+    ) -> Result<Client> {
+        let mut last_err = None;
+
+        for addr in sentinels {
+            let client = Client::open(ConnectionInfo {
+                addr: addr.clone(),
+                redis: RedisConnectionInfo::default(),
+            })?;
+
+            let resolved: std::result::Result<Option<(String, u16)>, RedisError> = async {
+                let mut conn = client.get_async_connection().await?;
+                redis::cmd("SENTINEL")
+                    .arg("get-master-addr-by-name")
+                    .arg(master)
+                    .query_async(&mut conn)
+                    .await
+            }
+            .await;
+
+            match resolved {
+                Ok(Some((host, port))) => {
+                    return Ok(Client::open(ConnectionInfo {
+                        addr: ConnectionAddr::Tcp(host, port),
+                        redis: redis.clone(),
+                    })?)
+                }
+                Ok(None) => continue,
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        let err = Error::new(
+            ErrorKind::Unexpected,
+            "no sentinel returns the address of master",
+        )
+        .with_context("service", Scheme::Redis)
+        .with_context("master", master);
+        Err(match last_err {
+            Some(e) => err.set_source(e),
+            None => err,
+        })
+    }
 }
 
 #[async_trait]
 impl kv::Adapter for Adapter {
     fn metadata(&self) -> kv::Metadata {
-        kv::Metadata::new(
-            Scheme::Redis,
-            &self.client.get_connection_info().addr.to_string(),
-            AccessorCapability::Read | AccessorCapability::Write,
-        )
+        let capabilities = match self.mode {
+            // `SCAN` only returns keys in one node for cluster.
+            Mode::Cluster(_) => AccessorCapability::Read | AccessorCapability::Write,
+            _ => AccessorCapability::Read | AccessorCapability::Write | AccessorCapability::Scan,
+        };
+
+        kv::Metadata::new(Scheme::Redis, &self.name(), capabilities)
     }
 
     async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
         let mut conn = self.conn().await?;
-        let bs: Option<Vec<u8>> = conn.get(key).await?;
-        Ok(bs)
+        conn.query(redis::cmd("GET").arg(key)).await
+    }
+
+    async fn get_len(&self, key: &str) -> Result<Option<u64>> {
+        let mut conn = self.conn().await?;
+
+        // `STRLEN` returns 0 for not exist keys, so we need `EXISTS` to
+        // tell empty values from not exist keys.
+        let (exists, len): (bool, u64) = conn
+            .query_pipeline(redis::pipe().cmd("EXISTS").arg(key).cmd("STRLEN").arg(key))
+            .await?;
+
+        Ok(if exists { Some(len) } else { None })
     }
 
     async fn set(&self, key: &str, value: &[u8]) -> Result<()> {
         let mut conn = self.conn().await?;
-        match self.default_ttl {
-            Some(ttl) => conn.set_ex(key, value, ttl.as_secs() as usize).await?,
-            None => conn.set(key, value).await?,
+
+        let mut cmd = redis::cmd("SET");
+        cmd.arg(key).arg(value);
+        if let Some(ttl) = self.default_ttl {
+            cmd.arg("PX").arg(ttl.as_millis() as u64);
         }
+        let _: () = conn.query(&cmd).await?;
         Ok(())
     }
 
     async fn delete(&self, key: &str) -> Result<()> {
         let mut conn = self.conn().await?;
-        let _: () = conn.del(key).await?;
+        let _: () = conn.query(redis::cmd("DEL").arg(key)).await?;
         Ok(())
     }
+
+    async fn scan(&self, path: &str) -> Result<Vec<String>> {
+        if let Mode::Cluster(_) = self.mode {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "scan is not supported for redis cluster",
+            )
+            .with_operation("kv::Adapter::scan"));
+        }
+
+        let mut conn = self.conn().await?;
+        let pattern = format!("{}*", escape_pattern(path));
+
+        let mut keys = Vec::new();
+        let mut cursor: u64 = 0;
+        loop {
+            let (next, batch): (u64, Vec<String>) = conn
+                .query(
+                    redis::cmd("SCAN")
+                        .arg(cursor)
+                        .arg("MATCH")
+                        .arg(&pattern)
+                        .arg("COUNT")
+                        .arg(1000),
+                )
+                .await?;
+            keys.extend(batch);
+
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        Ok(keys)
+    }
 }
 
 impl From<RedisError> for Error {
@@ -344,3 +698,130 @@ impl From<RedisError> for Error {
         Error::new(ErrorKind::Unexpected, e.category()).set_source(e)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_connection_addr() {
+        let cases = vec![
+            (
+                "tcp with port",
+                "tcp://10.0.0.1:7000",
+                ConnectionAddr::Tcp("10.0.0.1".to_string(), 7000),
+            ),
+            (
+                "redis without port",
+                "redis://example.com",
+                ConnectionAddr::Tcp("example.com".to_string(), DEFAULT_REDIS_PORT),
+            ),
+            (
+                "no scheme",
+                "example.com:6380",
+                ConnectionAddr::Tcp("example.com".to_string(), 6380),
+            ),
+            (
+                "unix socket",
+                "unix:///var/run/redis.sock",
+                ConnectionAddr::Unix(PathBuf::from("/var/run/redis.sock")),
+            ),
+        ];
+
+        for (name, input, expected) in cases {
+            let actual = parse_connection_addr(input, DEFAULT_REDIS_PORT)
+                .unwrap_or_else(|e| panic!("{name}: {e}"));
+            assert_eq!(actual, expected, "{name}");
+        }
+
+        assert!(parse_connection_addr("rediss://example.com", DEFAULT_REDIS_PORT).is_err());
+    }
+
+    #[test]
+    fn test_split_endpoints() {
+        let endpoints: Vec<_> =
+            split_endpoints("tcp://127.0.0.1:7000, tcp://127.0.0.1:7001,,").collect();
+        assert_eq!(
+            endpoints,
+            vec!["tcp://127.0.0.1:7000", "tcp://127.0.0.1:7001"]
+        );
+    }
+
+    #[test]
+    fn test_escape_pattern() {
+        let cases = vec![
+            ("empty", "", ""),
+            ("normal", "sessions/abc/", "sessions/abc/"),
+            ("percent", "dir/100%/", "dir/100%/"),
+            ("glob", "a*b?c[d]^e", "a\\*b\\?c\\[d\\]\\^e"),
+            ("backslash", "a\\b", "a\\\\b"),
+        ];
+
+        for (name, input, expected) in cases {
+            assert_eq!(escape_pattern(input), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_key_mapping() -> Result<()> {
+        let mut builder = RedisBuilder::default();
+        builder.root("/sessions/");
+        let backend = builder.build()?;
+
+        assert_eq!(backend.info().root(), "/sessions/");
+        assert_eq!(
+            build_abs_path(backend.info().root(), "user/abc"),
+            "sessions/user/abc"
+        );
+        assert_eq!(build_abs_path("/", "user/a b%20c"), "user/a b%20c");
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_modes() {
+        let mut builder = RedisBuilder::default();
+        builder.cluster_endpoints("tcp://127.0.0.1:7000,tcp://127.0.0.1:7001");
+        let backend = builder.build().expect("cluster must build");
+        assert!(!backend.info().capability().scan);
+
+        let mut builder = RedisBuilder::default();
+        builder.cluster_endpoints("tcp://127.0.0.1:7000").db(1);
+        assert!(builder.build().is_err(), "cluster doesn't support db");
+
+        let mut builder = RedisBuilder::default();
+        builder.sentinel_endpoints("tcp://127.0.0.1");
+        assert!(builder.build().is_err(), "sentinel master is required");
+
+        let mut builder = RedisBuilder::default();
+        builder
+            .sentinel_endpoints("tcp://127.0.0.1")
+            .sentinel_master("mymaster");
+        let backend = builder.build().expect("sentinel must build");
+        assert!(backend.info().capability().scan);
+
+        let mut builder = RedisBuilder::default();
+        builder
+            .cluster_endpoints("tcp://127.0.0.1:7000")
+            .sentinel_endpoints("tcp://127.0.0.1");
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn test_from_map() {
+        let mut map = HashMap::new();
+        map.insert("default_ttl".to_string(), "60".to_string());
+        map.insert(
+            "sentinel_endpoints".to_string(),
+            "tcp://a,tcp://b".to_string(),
+        );
+        map.insert("sentinel_master".to_string(), "mymaster".to_string());
+
+        let builder = RedisBuilder::from_map(map);
+        assert_eq!(builder.default_ttl, Some(Duration::from_secs(60)));
+        assert_eq!(
+            builder.sentinel_endpoints.as_deref(),
+            Some("tcp://a,tcp://b")
+        );
+        assert_eq!(builder.sentinel_master.as_deref(), Some("mymaster"));
+    }
+}
@@ -12,19 +12,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::min;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use rocksdb::ColumnFamily;
+use rocksdb::Options;
+use rocksdb::WriteBatch;
 use rocksdb::DB;
 
-use crate::raw::adapters::kv;
+use super::chunk;
+use super::pager::RocksdbPager;
+use super::writer::RocksdbWriter;
+use crate::ops::*;
 use crate::raw::*;
 use crate::Result;
 use crate::*;
 
+/// Name of rocksdb's default column family.
+const DEFAULT_COLUMN_FAMILY: &str = "default";
+/// Values larger than this will be split into chunks by default.
+const DEFAULT_CHUNK_SIZE: u32 = 1024 * 1024;
+
 /// Rocksdb service support.
 ///
 /// # Capabilities
@@ -33,8 +45,8 @@ use crate::*;
 ///
 /// - [x] read
 /// - [x] write
-/// - [ ] ~~list~~
-/// - [ ] scan
+/// - [x] list
+/// - [x] scan
 /// - [ ] ~~presign~~
 /// - [x] blocking
 ///
@@ -52,10 +64,20 @@ use crate::*;
 /// file to build rocksdb, rather than relying on system libraries that
 /// may be outdated and incompatible.
 ///
+/// Values larger than `chunk_size` will be split into multiple keys so that
+/// memtables will not be blown up by a single large value, they will be
+/// reassembled while reading. Directories are emulated by key prefix:
+/// `a/b` implies a virtual dir `a/`.
+///
+/// Rocksdb is a sync library, all async operations are running in tokio's
+/// blocking thread pool.
+///
 /// # Configuration
 ///
 /// - `root`: Set the working directory of `OpenDAL`
 /// - `datadir`: Set the path to the rocksdb data directory
+/// - `column_family`: Set the column family to store objects, default to `default`
+/// - `chunk_size`: Set the max size of a single key's value, default to 1MiB
 ///
 /// You can refer to [`RocksdbBuilder`]'s docs for more information
 ///
@@ -66,16 +88,16 @@ use crate::*;
 /// ```no_run
 /// use anyhow::Result;
 /// use opendal::services::Rocksdb;
-/// use opendal::Object;
 /// use opendal::Operator;
 ///
 /// #[tokio::main]
 /// async fn main() -> Result<()> {
 ///     let mut builder = Rocksdb::default();
 ///     builder.datadir("/tmp/opendal/rocksdb");
+///     builder.column_family("opendal");
 ///
 ///     let op: Operator = Operator::new(builder)?.finish();
-///     let _: Object = op.object("test_file");
+///     op.write("test_file", "Hello, World!").await?;
 ///     Ok(())
 /// }
 /// ```
@@ -87,6 +109,8 @@ pub struct RocksdbBuilder {
     ///
     /// default is "/"
     root: Option<String>,
+    column_family: Option<String>,
+    chunk_size: Option<u32>,
 }
 
 impl RocksdbBuilder {
@@ -105,6 +129,26 @@ impl RocksdbBuilder {
         }
         self
     }
+
+    /// Set the column family to store objects. Will create if not exists.
+    ///
+    /// default: "default"
+    pub fn column_family(&mut self, column_family: &str) -> &mut Self {
+        if !column_family.is_empty() {
+            self.column_family = Some(column_family.to_owned());
+        }
+        self
+    }
+
+    /// Set the max size of a single key's value.
+    ///
+    /// Values larger than it will be split into chunks of this size.
+    ///
+    /// default: 1MiB
+    pub fn chunk_size(&mut self, chunk_size: u32) -> &mut Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
 }
 
 impl Builder for RocksdbBuilder {
@@ -115,6 +159,11 @@ impl Builder for RocksdbBuilder {
         let mut builder = RocksdbBuilder::default();
 
         map.get("datadir").map(|v| builder.datadir(v));
+        map.get("root").map(|v| builder.root(v));
+        map.get("column_family").map(|v| builder.column_family(v));
+        map.get("chunk_size")
+            .and_then(|v| v.parse::<u32>().ok())
+            .map(|v| builder.chunk_size(v));
 
         builder
     }
@@ -124,66 +173,371 @@ impl Builder for RocksdbBuilder {
             Error::new(ErrorKind::ConfigInvalid, "datadir is required but not set")
                 .with_context("service", Scheme::Rocksdb)
         })?;
-        let db = DB::open_default(&path).map_err(|e| {
-            Error::new(ErrorKind::ConfigInvalid, "open default transaction db")
+
+        let chunk_size = self.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+        if chunk_size == 0 {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "chunk_size must be greater than 0",
+            )
+            .with_context("service", Scheme::Rocksdb));
+        }
+
+        let column_family = self
+            .column_family
+            .take()
+            .unwrap_or_else(|| DEFAULT_COLUMN_FAMILY.to_string());
+
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        // All existing column families must be opened together.
+        let mut cfs =
+            DB::list_cf(&opts, &path).unwrap_or_else(|_| vec![DEFAULT_COLUMN_FAMILY.to_string()]);
+        if !cfs.contains(&column_family) {
+            cfs.push(column_family.clone());
+        }
+
+        let db = DB::open_cf(&opts, &path, &cfs).map_err(|e| {
+            Error::new(ErrorKind::ConfigInvalid, "open db")
                 .with_context("service", Scheme::Rocksdb)
-                .with_context("datadir", path)
+                .with_context("datadir", path.clone())
+                .with_context("column_family", column_family.clone())
                 .set_source(e)
         })?;
 
-        Ok(RocksdbBackend::new(Adapter { db: Arc::new(db) }))
+        Ok(RocksdbBackend {
+            db: Arc::new(db),
+            root: normalize_root(&self.root.take().unwrap_or_default()),
+            column_family,
+            chunk_size,
+        })
     }
 }
 
 /// Backend for rocksdb services.
-pub type RocksdbBackend = kv::Backend<Adapter>;
-
 #[derive(Clone)]
-pub struct Adapter {
+pub struct RocksdbBackend {
     db: Arc<DB>,
+    root: String,
+    column_family: String,
+    chunk_size: u32,
 }
 
-impl Debug for Adapter {
+impl Debug for RocksdbBackend {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut ds = f.debug_struct("Adapter");
-        ds.field("path", &self.db.path());
-        ds.finish()
+        f.debug_struct("RocksdbBackend")
+            .field("path", &self.db.path())
+            .field("root", &self.root)
+            .field("column_family", &self.column_family)
+            .field("chunk_size", &self.chunk_size)
+            .finish()
     }
 }
 
 #[async_trait]
-impl kv::Adapter for Adapter {
-    fn metadata(&self) -> kv::Metadata {
-        kv::Metadata::new(
-            Scheme::Rocksdb,
-            &self.db.path().to_string_lossy(),
-            AccessorCapability::Read | AccessorCapability::Write,
-        )
+impl Accessor for RocksdbBackend {
+    type Reader = oio::Cursor;
+    type BlockingReader = oio::Cursor;
+    type Writer = RocksdbWriter;
+    type BlockingWriter = RocksdbWriter;
+    type Pager = RocksdbPager;
+    type BlockingPager = RocksdbPager;
+
+    fn info(&self) -> AccessorInfo {
+        use AccessorCapability::*;
+
+        let mut am = AccessorInfo::default();
+        am.set_scheme(Scheme::Rocksdb)
+            .set_root(&self.root)
+            .set_name(&self.db.path().to_string_lossy())
+            .set_capabilities(Read | Write | List | Scan | Blocking)
+            .set_hints(AccessorHint::ReadStreamable | AccessorHint::ReadSeekable)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_seek: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                scan: true,
+                blocking: true,
+                ..Default::default()
+            });
+
+        am
+    }
+
+    async fn create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        let path = path.to_string();
+        self.spawn(move |b| b.blocking_create(&path, args)).await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let path = path.to_string();
+        self.spawn(move |b| b.blocking_read(&path, args)).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.blocking_write(path, args)
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let path = path.to_string();
+        self.spawn(move |b| b.blocking_stat(&path, args)).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let path = path.to_string();
+        self.spawn(move |b| b.blocking_delete(&path, args)).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        let p = build_abs_path(&self.root, path);
+        let pager = RocksdbPager::new(self, &p, true, args.limit());
+
+        Ok((RpList::default(), pager))
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        let p = build_abs_path(&self.root, path);
+        let pager = RocksdbPager::new(self, &p, false, args.limit());
+
+        Ok((RpScan::default(), pager))
+    }
+
+    fn blocking_create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        let p = build_abs_path(&self.root, path);
+
+        if args.mode() == EntryMode::DIR {
+            self.db.put_cf(self.cf()?, &p, b"")?;
+        } else {
+            self.rocksdb_set(&p, &[])?;
+        }
+
+        Ok(RpCreate::default())
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let p = build_abs_path(&self.root, path);
+
+        let bs = self.rocksdb_read(&p, args.range())?;
+        Ok((RpRead::new(bs.len() as u64), oio::Cursor::from(bs)))
+    }
+
+    fn blocking_write(&self, path: &str, _: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let p = build_abs_path(&self.root, path);
+
+        Ok((RpWrite::new(), RocksdbWriter::new(self.clone(), p)))
+    }
+
+    fn blocking_stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+        let p = build_abs_path(&self.root, path);
+
+        if p.is_empty() {
+            return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
+        }
+
+        // Dir exists if any key starts with it.
+        if p.ends_with('/') {
+            let mut it = self.db.prefix_iterator_cf(self.cf()?, &p);
+            return match it.next() {
+                Some((k, _)) if k.starts_with(p.as_bytes()) => {
+                    Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+                }
+                _ => Err(new_not_found_error(&p)),
+            };
+        }
+
+        let bs = match self.db.get_cf(self.cf()?, &p)? {
+            Some(bs) => bs,
+            None => return Err(new_not_found_error(&p)),
+        };
+        let size = chunk::decode(&bs)?.size();
+
+        Ok(RpStat::new(
+            Metadata::new(EntryMode::FILE).with_content_length(size),
+        ))
+    }
+
+    fn blocking_delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        let p = build_abs_path(&self.root, path);
+        let cf = self.cf()?;
+
+        let mut batch = WriteBatch::default();
+        self.delete_chunks(&mut batch, &p, 0)?;
+        batch.delete_cf(cf, &p);
+        self.db.write(batch)?;
+
+        Ok(RpDelete::default())
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        let p = build_abs_path(&self.root, path);
+        let pager = RocksdbPager::new(self, &p, true, args.limit());
+
+        Ok((RpList::default(), pager))
     }
 
-    async fn get(&self, path: &str) -> Result<Option<Vec<u8>>> {
-        self.blocking_get(path)
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        let p = build_abs_path(&self.root, path);
+        let pager = RocksdbPager::new(self, &p, false, args.limit());
+
+        Ok((RpScan::default(), pager))
     }
+}
 
-    fn blocking_get(&self, path: &str) -> Result<Option<Vec<u8>>> {
-        Ok(self.db.get(path)?)
+impl RocksdbBackend {
+    /// Run blocking rocksdb operations in tokio's blocking thread pool.
+    async fn spawn<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(RocksdbBackend) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let backend = self.clone();
+        tokio::task::spawn_blocking(move || f(backend))
+            .await
+            .map_err(new_join_error)?
     }
 
-    async fn set(&self, path: &str, value: &[u8]) -> Result<()> {
-        self.blocking_set(path, value)
+    pub(super) fn db(&self) -> &DB {
+        &self.db
     }
 
-    fn blocking_set(&self, path: &str, value: &[u8]) -> Result<()> {
-        Ok(self.db.put(path, value)?)
+    pub(super) fn root(&self) -> &str {
+        &self.root
     }
 
-    async fn delete(&self, path: &str) -> Result<()> {
-        self.blocking_delete(path)
+    pub(super) fn cf(&self) -> Result<&ColumnFamily> {
+        self.db.cf_handle(&self.column_family).ok_or_else(|| {
+            Error::new(ErrorKind::Unexpected, "column family is not opened")
+                .with_context("service", Scheme::Rocksdb)
+                .with_context("column_family", &self.column_family)
+        })
     }
 
-    fn blocking_delete(&self, path: &str) -> Result<()> {
-        Ok(self.db.delete(path)?)
+    /// Read the value of key in given range, only required chunks will be
+    /// fetched.
+    fn rocksdb_read(&self, key: &str, br: BytesRange) -> Result<Vec<u8>> {
+        let cf = self.cf()?;
+
+        let bs = match self.db.get_cf(cf, key)? {
+            Some(bs) => bs,
+            None => return Err(new_not_found_error(key)),
+        };
+
+        let (size, chunk_size) = match chunk::decode(&bs)? {
+            chunk::Value::Inline(v) => {
+                let (start, end) = apply_range(v.len() as u64, br);
+                return Ok(v[start as usize..end as usize].to_vec());
+            }
+            chunk::Value::Chunked { size, chunk_size } => (size, chunk_size as u64),
+        };
+
+        let (start, end) = apply_range(size, br);
+        let mut buf = Vec::with_capacity((end - start) as usize);
+        let mut idx = start / chunk_size;
+        while idx * chunk_size < end {
+            let chunk_start = idx * chunk_size;
+            let chunk_end = min(chunk_start + chunk_size, size);
+
+            let data = self
+                .db
+                .get_cf(cf, chunk::chunk_key(key, idx))?
+                .filter(|data| data.len() as u64 == chunk_end - chunk_start)
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::Unexpected,
+                        "chunk of value is missing or corrupted",
+                    )
+                    .with_context("service", Scheme::Rocksdb)
+                    .with_context("path", key)
+                    .with_context("chunk", idx.to_string())
+                })?;
+
+            let from = (start.max(chunk_start) - chunk_start) as usize;
+            let to = (end.min(chunk_end) - chunk_start) as usize;
+            buf.extend_from_slice(&data[from..to]);
+
+            idx += 1;
+        }
+
+        Ok(buf)
     }
+
+    /// Store value of key, values larger than `chunk_size` will be split
+    /// into chunks. All changes are applied in one batch.
+    pub(super) fn rocksdb_set(&self, key: &str, value: &[u8]) -> Result<()> {
+        let cf = self.cf()?;
+        let chunk_size = self.chunk_size as usize;
+
+        let mut batch = WriteBatch::default();
+        if value.len() <= chunk_size {
+            self.delete_chunks(&mut batch, key, 0)?;
+            batch.put_cf(cf, key, chunk::encode_inline(value));
+        } else {
+            let mut count = 0;
+            for (idx, data) in value.chunks(chunk_size).enumerate() {
+                batch.put_cf(cf, chunk::chunk_key(key, idx as u64), data);
+                count += 1;
+            }
+            // Remove chunks of the old value that are not overwritten.
+            self.delete_chunks(&mut batch, key, count)?;
+            batch.put_cf(
+                cf,
+                key,
+                chunk::encode_chunked(value.len() as u64, self.chunk_size),
+            );
+        }
+
+        Ok(self.db.write(batch)?)
+    }
+
+    /// Delete chunks of the current value of key from `from`.
+    fn delete_chunks(&self, batch: &mut WriteBatch, key: &str, from: u64) -> Result<()> {
+        let cf = self.cf()?;
+
+        let bs = match self.db.get_cf(cf, key)? {
+            Some(bs) => bs,
+            None => return Ok(()),
+        };
+        if let chunk::Value::Chunked { size, chunk_size } = chunk::decode(&bs)? {
+            for idx in from..chunk::chunk_count(size, chunk_size) {
+                batch.delete_cf(cf, chunk::chunk_key(key, idx));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Calculate the range to read, out of bound range will be truncated.
+fn apply_range(size: u64, br: BytesRange) -> (u64, u64) {
+    let (start, end) = match (br.offset(), br.size()) {
+        (Some(offset), Some(n)) => (offset, offset.saturating_add(n)),
+        (Some(offset), None) => (offset, size),
+        (None, Some(n)) => (size.saturating_sub(n), size),
+        (None, None) => (0, size),
+    };
+    let end = min(end, size);
+
+    (min(start, end), end)
+}
+
+fn new_not_found_error(key: &str) -> Error {
+    Error::new(ErrorKind::NotFound, "rocksdb doesn't have this path").with_context("path", key)
+}
+
+pub(super) fn new_join_error(err: tokio::task::JoinError) -> Error {
+    Error::new(ErrorKind::Unexpected, "rocksdb blocking task failed").set_source(err)
 }
 
 impl From<rocksdb::Error> for Error {
@@ -191,3 +545,118 @@ impl From<rocksdb::Error> for Error {
         Error::new(ErrorKind::Unexpected, "got rocksdb error").set_source(e)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_operator(chunk_size: u32) -> (BlockingOperator, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("opendal-rocksdb-{}", uuid::Uuid::new_v4()));
+
+        let mut builder = RocksdbBuilder::default();
+        builder
+            .datadir(&dir.to_string_lossy())
+            .column_family("test")
+            .chunk_size(chunk_size);
+        let op = Operator::new(builder)
+            .expect("must init")
+            .finish()
+            .blocking();
+
+        (op, dir)
+    }
+
+    #[test]
+    fn test_chunk_boundary() -> Result<()> {
+        let (op, dir) = new_operator(4);
+
+        let cases: Vec<(&str, &[u8])> = vec![
+            ("zero length", b""),
+            ("smaller than chunk size", b"abc"),
+            ("exactly chunk size", b"abcd"),
+            ("one byte larger", b"abcde"),
+            ("multiple of chunk size", b"abcdefgh"),
+            ("multiple chunks", b"Hello, World!"),
+        ];
+
+        for (name, value) in cases {
+            op.write(name, value.to_vec())?;
+            assert_eq!(op.read(name)?, value, "{name}");
+            assert_eq!(
+                op.stat(name)?.content_length(),
+                value.len() as u64,
+                "{name}"
+            );
+        }
+
+        // Range across chunks.
+        assert_eq!(op.range_read("multiple chunks", 3..9)?, b"lo, Wo");
+        assert_eq!(op.range_read("multiple chunks", 4..8)?, b"o, W");
+        assert_eq!(op.range_read("multiple chunks", 12..)?, b"!");
+        assert_eq!(op.range_read("multiple chunks", 100..)?, b"");
+
+        let _ = std::fs::remove_dir_all(dir);
+        Ok(())
+    }
+
+    #[test]
+    fn test_overwrite_and_delete_chunks() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("opendal-rocksdb-{}", uuid::Uuid::new_v4()));
+        let mut builder = RocksdbBuilder::default();
+        builder.datadir(&dir.to_string_lossy()).chunk_size(4);
+        let backend = builder.build()?;
+
+        let count_keys = |backend: &RocksdbBackend| {
+            let cf = backend.cf().expect("column family must exist");
+            backend
+                .db
+                .iterator_cf(cf, rocksdb::IteratorMode::Start)
+                .count()
+        };
+
+        backend.rocksdb_set("file", b"Hello, World!")?;
+        // 1 header and 4 chunks.
+        assert_eq!(count_keys(&backend), 5);
+
+        backend.rocksdb_set("file", b"Hello")?;
+        assert_eq!(count_keys(&backend), 3);
+        assert_eq!(
+            backend.rocksdb_read("file", BytesRange::new(None, None))?,
+            b"Hello"
+        );
+
+        backend.rocksdb_set("file", b"Hi")?;
+        assert_eq!(count_keys(&backend), 1);
+
+        backend.rocksdb_set("file", b"Hello, World!")?;
+        backend.blocking_delete("file", OpDelete::new())?;
+        assert_eq!(count_keys(&backend), 0, "chunk keys must be cleaned up");
+
+        drop(backend);
+        let _ = std::fs::remove_dir_all(dir);
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_with_virtual_dirs() -> Result<()> {
+        let (op, dir) = new_operator(4);
+
+        op.write("a/b/c", b"Hello".to_vec())?;
+        op.write("a/b/d", b"World".to_vec())?;
+        op.write("a/e", b"!".to_vec())?;
+        op.create_dir("a/f/")?;
+
+        let mut entries: Vec<_> = op
+            .list("a/")?
+            .map(|e| e.map(|e| e.path().to_string()))
+            .collect::<Result<_>>()?;
+        entries.sort();
+        assert_eq!(entries, vec!["a/b/", "a/e", "a/f/"]);
+
+        assert!(op.stat("a/b/")?.is_dir());
+        assert_eq!(op.stat("a/x/").unwrap_err().kind(), ErrorKind::NotFound);
+
+        let _ = std::fs::remove_dir_all(dir);
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Chunking format for large values.
+//!
+//! Values are stored in the key of their path with a small header:
+//!
+//! ```text
+//! | magic (4B) | kind (1B) | payload |
+//! ```
+//!
+//! - kind `0` (inline): payload is the value itself.
+//! - kind `1` (chunked): payload is `size (8B BE) | chunk_size (4B BE)`,
+//!   the value is split into `chunk_size` pieces stored at [`chunk_key`].
+//!
+//! Values without the magic are treated as raw values written before
+//! chunking is introduced.
+
+use crate::*;
+
+/// Magic of the header.
+const MAGIC: &[u8; 4] = b"ODRC";
+const KIND_INLINE: u8 = 0;
+const KIND_CHUNKED: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1;
+const CHUNKED_PAYLOAD_LEN: usize = 8 + 4;
+
+/// Prefix of all chunk keys, which will never be a valid path.
+pub const CHUNK_KEY_PREFIX: &[u8] = b"\0chunks/";
+
+/// Value decoded from the key of path.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Value<'a> {
+    /// The value is stored inline.
+    Inline(&'a [u8]),
+    /// The value is split into chunks.
+    Chunked { size: u64, chunk_size: u32 },
+}
+
+impl Value<'_> {
+    /// Get the size of the value.
+    pub fn size(&self) -> u64 {
+        match self {
+            Value::Inline(bs) => bs.len() as u64,
+            Value::Chunked { size, .. } => *size,
+        }
+    }
+}
+
+/// Encode the value that is not larger than `chunk_size`.
+pub fn encode_inline(value: &[u8]) -> Vec<u8> {
+    let mut bs = Vec::with_capacity(HEADER_LEN + value.len());
+    bs.extend_from_slice(MAGIC);
+    bs.push(KIND_INLINE);
+    bs.extend_from_slice(value);
+    bs
+}
+
+/// Encode the header of a chunked value.
+pub fn encode_chunked(size: u64, chunk_size: u32) -> Vec<u8> {
+    let mut bs = Vec::with_capacity(HEADER_LEN + CHUNKED_PAYLOAD_LEN);
+    bs.extend_from_slice(MAGIC);
+    bs.push(KIND_CHUNKED);
+    bs.extend_from_slice(&size.to_be_bytes());
+    bs.extend_from_slice(&chunk_size.to_be_bytes());
+    bs
+}
+
+/// Decode the value stored in the key of path.
+pub fn decode(bs: &[u8]) -> Result<Value<'_>> {
+    if bs.len() < HEADER_LEN || &bs[..MAGIC.len()] != MAGIC {
+        return Ok(Value::Inline(bs));
+    }
+
+    let payload = &bs[HEADER_LEN..];
+    match bs[MAGIC.len()] {
+        KIND_INLINE => Ok(Value::Inline(payload)),
+        KIND_CHUNKED if payload.len() == CHUNKED_PAYLOAD_LEN => {
+            let mut size = [0; 8];
+            size.copy_from_slice(&payload[..8]);
+            let mut chunk_size = [0; 4];
+            chunk_size.copy_from_slice(&payload[8..]);
+
+            let chunk_size = u32::from_be_bytes(chunk_size);
+            if chunk_size == 0 {
+                return Err(new_invalid_header_error("chunk size is zero"));
+            }
+
+            Ok(Value::Chunked {
+                size: u64::from_be_bytes(size),
+                chunk_size,
+            })
+        }
+        KIND_CHUNKED => Err(new_invalid_header_error("chunked header is truncated")),
+        _ => Err(new_invalid_header_error("unknown value kind")),
+    }
+}
+
+/// Build the key of the `idx` chunk of path.
+pub fn chunk_key(path: &str, idx: u64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(CHUNK_KEY_PREFIX.len() + path.len() + 9);
+    key.extend_from_slice(CHUNK_KEY_PREFIX);
+    key.extend_from_slice(path.as_bytes());
+    key.push(b'/');
+    key.extend_from_slice(&idx.to_be_bytes());
+    key
+}
+
+/// Get the count of chunks.
+pub fn chunk_count(size: u64, chunk_size: u32) -> u64 {
+    let chunk_size = chunk_size as u64;
+    (size + chunk_size - 1) / chunk_size
+}
+
+fn new_invalid_header_error(reason: &str) -> Error {
+    Error::new(ErrorKind::Unexpected, "rocksdb value header is invalid")
+        .with_context("service", Scheme::Rocksdb)
+        .with_context("reason", reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline() {
+        let bs = encode_inline(b"Hello, World!");
+        assert_eq!(decode(&bs).unwrap(), Value::Inline(b"Hello, World!"));
+    }
+
+    #[test]
+    fn test_zero_length() {
+        let bs = encode_inline(&[]);
+        assert_eq!(bs.len(), HEADER_LEN);
+        assert_eq!(decode(&bs).unwrap(), Value::Inline(&[]));
+        assert_eq!(decode(&bs).unwrap().size(), 0);
+
+        // Empty raw value written without header.
+        assert_eq!(decode(&[]).unwrap(), Value::Inline(&[]));
+    }
+
+    #[test]
+    fn test_chunked() {
+        let bs = encode_chunked(1025, 1024);
+        let v = decode(&bs).unwrap();
+        assert_eq!(
+            v,
+            Value::Chunked {
+                size: 1025,
+                chunk_size: 1024
+            }
+        );
+        assert_eq!(v.size(), 1025);
+    }
+
+    #[test]
+    fn test_raw_value() {
+        assert_eq!(decode(b"ODR").unwrap(), Value::Inline(b"ODR"));
+        assert_eq!(decode(b"raw value").unwrap(), Value::Inline(b"raw value"));
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let mut bs = encode_chunked(1025, 1024);
+        bs.pop();
+        assert!(decode(&bs).is_err(), "truncated header");
+
+        let bs = encode_chunked(1025, 0);
+        assert!(decode(&bs).is_err(), "zero chunk size");
+
+        let mut bs = encode_inline(&[]);
+        bs[MAGIC.len()] = 2;
+        assert!(decode(&bs).is_err(), "unknown kind");
+    }
+
+    #[test]
+    fn test_chunk_count() {
+        assert_eq!(chunk_count(0, 4), 0);
+        assert_eq!(chunk_count(1, 4), 1);
+        assert_eq!(chunk_count(4, 4), 1);
+        assert_eq!(chunk_count(5, 4), 2);
+        assert_eq!(chunk_count(8, 4), 2);
+    }
+
+    #[test]
+    fn test_chunk_key() {
+        let key = chunk_key("a/b", 1);
+        assert!(key.starts_with(CHUNK_KEY_PREFIX));
+        assert!(key > chunk_key("a/b", 0));
+        assert!(key < chunk_key("a/b", 256));
+    }
+}
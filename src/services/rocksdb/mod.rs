@@ -14,3 +14,7 @@
 
 mod backend;
 pub use backend::RocksdbBuilder as Rocksdb;
+
+mod chunk;
+mod pager;
+mod writer;
@@ -0,0 +1,198 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use rocksdb::Direction;
+use rocksdb::IteratorMode;
+
+use super::backend::new_join_error;
+use super::backend::RocksdbBackend;
+use super::chunk;
+use crate::raw::*;
+use crate::*;
+
+/// RocksdbPager iterates keys with given prefix in order.
+///
+/// Iterators borrow the db, so every page seeks from the next key again.
+/// Keys under a virtual dir are skipped by seeking past the dir.
+pub struct RocksdbPager {
+    state: Option<PagerState>,
+}
+
+struct PagerState {
+    backend: RocksdbBackend,
+    prefix: String,
+    /// Length of the abs root which will be stripped from keys.
+    root_len: usize,
+    /// Fold keys into virtual dirs at `/` if set.
+    delimiter: bool,
+    size: usize,
+    /// The key to seek in the next iteration, `None` means finished.
+    next_key: Option<Vec<u8>>,
+}
+
+impl RocksdbPager {
+    pub fn new(
+        backend: &RocksdbBackend,
+        path: &str,
+        delimiter: bool,
+        limit: Option<usize>,
+    ) -> Self {
+        Self {
+            state: Some(PagerState {
+                backend: backend.clone(),
+                prefix: path.to_string(),
+                root_len: backend.root().len() - 1,
+                delimiter,
+                size: limit.unwrap_or(1000),
+                next_key: Some(path.as_bytes().to_vec()),
+            }),
+        }
+    }
+}
+
+/// Get the smallest key that is larger than all keys with given prefix.
+fn seek_past(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut key = prefix.to_vec();
+    while let Some(last) = key.pop() {
+        if last < u8::MAX {
+            key.push(last + 1);
+            return Some(key);
+        }
+    }
+    None
+}
+
+impl PagerState {
+    fn next_page(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let mut entries = Vec::with_capacity(self.size);
+        let cf = self.backend.cf()?;
+
+        'seek: while entries.len() < self.size {
+            let from = match &self.next_key {
+                Some(key) => key.clone(),
+                None => break,
+            };
+            let it = self
+                .backend
+                .db()
+                .iterator_cf(cf, IteratorMode::From(&from, Direction::Forward));
+
+            for (k, v) in it {
+                if !k.starts_with(self.prefix.as_bytes()) {
+                    break;
+                }
+                // Chunk keys are only visible while listing the whole db.
+                if k.starts_with(chunk::CHUNK_KEY_PREFIX) {
+                    self.next_key = seek_past(chunk::CHUNK_KEY_PREFIX);
+                    continue 'seek;
+                }
+
+                let key = std::str::from_utf8(&k).map_err(|err| {
+                    Error::new(ErrorKind::Unexpected, "store key is not valid utf-8 string")
+                        .set_source(err)
+                })?;
+                let rest = &key[self.prefix.len()..];
+                // Skip the listed dir itself.
+                if rest.is_empty() {
+                    let mut next = k.to_vec();
+                    next.push(0);
+                    self.next_key = Some(next);
+                    continue 'seek;
+                }
+
+                if self.delimiter {
+                    if let Some(idx) = rest.find('/') {
+                        let dir = &key[..self.prefix.len() + idx + 1];
+                        entries.push(oio::Entry::new(
+                            &dir[self.root_len..],
+                            Metadata::new(EntryMode::DIR),
+                        ));
+                        self.next_key = seek_past(dir.as_bytes());
+                        continue 'seek;
+                    }
+                }
+
+                let path = &key[self.root_len..];
+                if key.ends_with('/') {
+                    entries.push(oio::Entry::new(path, Metadata::new(EntryMode::DIR)));
+                } else {
+                    let size = chunk::decode(&v)?.size();
+                    entries.push(oio::Entry::new(
+                        path,
+                        Metadata::new(EntryMode::FILE).with_content_length(size),
+                    ));
+                }
+
+                let mut next = k.to_vec();
+                next.push(0);
+                self.next_key = Some(next);
+                if entries.len() >= self.size {
+                    break 'seek;
+                }
+            }
+
+            // Iterator exhausted or out of prefix.
+            self.next_key = None;
+        }
+
+        Ok(if entries.is_empty() {
+            None
+        } else {
+            Some(entries)
+        })
+    }
+}
+
+#[async_trait]
+impl oio::Page for RocksdbPager {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let mut state = match self.state.take() {
+            Some(state) => state,
+            None => return Ok(None),
+        };
+
+        let (state, res) = tokio::task::spawn_blocking(move || {
+            let res = state.next_page();
+            (state, res)
+        })
+        .await
+        .map_err(new_join_error)?;
+
+        self.state = Some(state);
+        res
+    }
+}
+
+impl oio::BlockingPage for RocksdbPager {
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        match &mut self.state {
+            Some(state) => state.next_page(),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seek_past() {
+        assert_eq!(seek_past(b"a/b/"), Some(b"a/b0".to_vec()));
+        assert_eq!(seek_past(&[b'a', u8::MAX]), Some(b"b".to_vec()));
+        assert_eq!(seek_past(&[u8::MAX, u8::MAX]), None);
+        assert_eq!(seek_past(b""), None);
+    }
+}
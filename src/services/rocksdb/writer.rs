@@ -0,0 +1,97 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use super::backend::new_join_error;
+use super::backend::RocksdbBackend;
+use crate::raw::*;
+use crate::*;
+
+/// RocksdbWriter buffers all data in memory and stores it while closing.
+pub struct RocksdbWriter {
+    backend: RocksdbBackend,
+    path: String,
+    buf: Vec<u8>,
+}
+
+impl RocksdbWriter {
+    pub fn new(backend: RocksdbBackend, path: String) -> Self {
+        RocksdbWriter {
+            backend,
+            path,
+            buf: Vec::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl oio::Write for RocksdbWriter {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.buf = bs.into();
+
+        Ok(())
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.buf.extend(bs);
+
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        let backend = self.backend.clone();
+        let path = self.path.clone();
+        let buf = std::mem::take(&mut self.buf);
+
+        tokio::task::spawn_blocking(move || backend.rocksdb_set(&path, &buf))
+            .await
+            .map_err(new_join_error)??;
+
+        Ok(RpWrite::new())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        self.buf.clear();
+
+        Ok(())
+    }
+}
+
+impl oio::BlockingWrite for RocksdbWriter {
+    fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.buf = bs.into();
+
+        Ok(())
+    }
+
+    fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.buf.extend(bs);
+
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<RpWrite> {
+        self.backend.rocksdb_set(&self.path, &self.buf)?;
+
+        Ok(RpWrite::new())
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        self.buf.clear();
+
+        Ok(())
+    }
+}
@@ -16,6 +16,7 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Write;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -27,6 +28,9 @@ use http::header::HeaderName;
 use http::header::CONTENT_DISPOSITION;
 use http::header::CONTENT_LENGTH;
 use http::header::CONTENT_TYPE;
+use http::header::IF_MATCH;
+use http::header::IF_NONE_MATCH;
+use http::HeaderMap;
 use http::HeaderValue;
 use http::Request;
 use http::Response;
@@ -37,15 +41,25 @@ use log::warn;
 use md5::Digest;
 use md5::Md5;
 use once_cell::sync::Lazy;
+use percent_encoding::utf8_percent_encode;
+use percent_encoding::AsciiSet;
+use percent_encoding::NON_ALPHANUMERIC;
 use reqsign::AwsConfigLoader;
 use reqsign::AwsCredentialLoad;
 use reqsign::AwsCredentialLoader;
 use reqsign::AwsV4Signer;
 use serde::Deserialize;
 use serde::Serialize;
+use sha2::Sha256;
+use time::format_description::well_known::Rfc2822;
+use time::OffsetDateTime;
 
+use super::clock_skew::ClockSkew;
 use super::error::parse_error;
+use super::error::parse_ok_error;
+use super::error::parse_request_time_too_skewed;
 use super::pager::S3Pager;
+use super::sts::StsLoader;
 use super::writer::S3Writer;
 use crate::ops::*;
 use crate::raw::*;
@@ -62,7 +76,7 @@ static ENDPOINT_TEMPLATES: Lazy<HashMap<&'static str, &'static str>> = Lazy::new
     m
 });
 
-mod constants {
+pub(super) mod constants {
     pub const X_AMZ_SERVER_SIDE_ENCRYPTION: &str = "x-amz-server-side-encryption";
     pub const X_AMZ_SERVER_SIDE_ENCRYPTION_CUSTOMER_ALGORITHM: &str =
         "x-amz-server-side-encryption-customer-algorithm";
@@ -73,6 +87,60 @@ mod constants {
     pub const X_AMZ_SERVER_SIDE_ENCRYPTION_AWS_KMS_KEY_ID: &str =
         "x-amz-server-side-encryption-aws-kms-key-id";
     pub const X_AMZ_BUCKET_REGION: &str = "x-amz-bucket-region";
+    pub const X_AMZ_REQUEST_PAYER: &str = "x-amz-request-payer";
+    pub const X_AMZ_TAGGING: &str = "x-amz-tagging";
+    pub const X_AMZ_STORAGE_CLASS: &str = "x-amz-storage-class";
+    pub const X_AMZ_RESTORE: &str = "x-amz-restore";
+    pub const X_AMZ_CHECKSUM_ALGORITHM: &str = "x-amz-checksum-algorithm";
+    pub const X_AMZ_COPY_SOURCE: &str = "x-amz-copy-source";
+    pub const X_AMZ_COPY_SOURCE_SERVER_SIDE_ENCRYPTION_CUSTOMER_ALGORITHM: &str =
+        "x-amz-copy-source-server-side-encryption-customer-algorithm";
+    pub const X_AMZ_COPY_SOURCE_SERVER_SIDE_ENCRYPTION_CUSTOMER_KEY: &str =
+        "x-amz-copy-source-server-side-encryption-customer-key";
+    pub const X_AMZ_COPY_SOURCE_SERVER_SIDE_ENCRYPTION_CUSTOMER_KEY_MD5: &str =
+        "x-amz-copy-source-server-side-encryption-customer-key-md5";
+    pub const X_AMZ_VERSION_ID: &str = "x-amz-version-id";
+    pub const X_AMZ_CONTENT_SHA_256: &str = "x-amz-content-sha256";
+    pub const X_AMZ_DECODED_CONTENT_LENGTH: &str = "x-amz-decoded-content-length";
+    pub const X_AMZ_TRAILER: &str = "x-amz-trailer";
+
+    pub const STREAMING_UNSIGNED_PAYLOAD_TRAILER: &str = "STREAMING-UNSIGNED-PAYLOAD-TRAILER";
+}
+
+/// The minimum part size of multipart upload, except the last part.
+const MIN_WRITE_PART_SIZE: usize = 5 * 1024 * 1024;
+
+/// Checksum algorithm that used to verify the integrity of uploaded parts.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(super) enum ChecksumAlgorithm {
+    Crc32c,
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    /// The value of `x-amz-checksum-algorithm` header.
+    pub(super) fn name(&self) -> &'static str {
+        match self {
+            Self::Crc32c => "CRC32C",
+            Self::Sha256 => "SHA256",
+        }
+    }
+
+    /// The header that carries checksum of the request body.
+    pub(super) fn header_name(&self) -> &'static str {
+        match self {
+            Self::Crc32c => "x-amz-checksum-crc32c",
+            Self::Sha256 => "x-amz-checksum-sha256",
+        }
+    }
+
+    /// Calculate the base64 encoded checksum of given content.
+    pub(super) fn checksum(&self, content: &[u8]) -> String {
+        match self {
+            Self::Crc32c => BASE64_STANDARD.encode(crc32c::crc32c(content).to_be_bytes()),
+            Self::Sha256 => BASE64_STANDARD.encode(Sha256::digest(content)),
+        }
+    }
 }
 
 /// Aws S3 and compatible services (including minio, digitalocean space and so on) support
@@ -97,6 +165,9 @@ mod constants {
 /// - `access_key_id`: Set the access_key_id for backend.
 /// - `secret_access_key`: Set the secret_access_key for backend.
 /// - `security_token`: Set the security_token for backend.
+/// - `role_arn`: Set the role to assume for backend.
+/// - `external_id`: Set the external_id used while assuming role.
+/// - `sts_endpoint`: Set the STS endpoint used while assuming role.
 /// - `server_side_encryption`: Set the server_side_encryption for backend.
 /// - `server_side_encryption_aws_kms_key_id`: Set the server_side_encryption_aws_kms_key_id for backend.
 /// - `server_side_encryption_customer_algorithm`: Set the server_side_encryption_customer_algorithm for backend.
@@ -104,6 +175,12 @@ mod constants {
 /// - `server_side_encryption_customer_key_md5`: Set the server_side_encryption_customer_key_md5 for backend.
 /// - `disable_config_load`: Disable aws config load from env
 /// - `enable_virtual_host_style`: Enable virtual host style.
+/// - `enable_request_payer`: Enable request payer for requester pays buckets.
+/// - `enable_list_objects_v1`: Use ListObjects (v1) instead of ListObjectsV2.
+/// - `write_part_size`: Set the part size of multipart upload.
+/// - `write_concurrency`: Set the max concurrent part uploads of multipart upload.
+/// - `checksum_algorithm`: Set the checksum algorithm of uploads, `crc32c` or `sha256`.
+/// - `http_headers`: Extra http headers in format like `key1:value1,key2:value2`.
 ///
 /// Refer to [`S3Builder`]'s public API docs for more information.
 ///
@@ -113,7 +190,33 @@ mod constants {
 ///
 /// The way to take advantage of this feature is to build your S3 backend with `Builder::security_token`.
 ///
-/// But OpenDAL will not refresh the temporary security credentials, please keep in mind to refresh those credentials in time.
+/// Credentials given by `Builder::security_token` are used as is, OpenDAL can't
+/// refresh them, please keep in mind to refresh those credentials in time. Use
+/// assume role below to let OpenDAL load and refresh temporary credentials.
+///
+/// # Assume role
+///
+/// Instead of static credentials, OpenDAL can load temporary credentials
+/// from STS and refresh them before they expire:
+///
+/// - Web identity (IRSA on EKS): `AWS_WEB_IDENTITY_TOKEN_FILE` and
+///   `AWS_ROLE_ARN` will be loaded from env and used to call
+///   `AssumeRoleWithWebIdentity`.
+/// - Assume role: set `role_arn` (and `external_id` if required by the role's
+///   trust policy), OpenDAL will call `AssumeRole` with the credentials
+///   loaded from other sources, for example, the web identity above.
+///
+/// Web identity is loaded from env, so it will not take effect if
+/// `disable_config_load` is set.
+///
+/// Credentials will be refreshed 5 minutes before they expire. Concurrent
+/// requests share a single refresh in flight. If STS rejects the token or
+/// role, requests will fail with `PermissionDenied`, other invalid requests
+/// like a malformed role arn will fail with `ConfigInvalid`.
+///
+/// STS is called via the global endpoint `https://sts.amazonaws.com` unless
+/// `AWS_STS_REGIONAL_ENDPOINTS` is `regional`, set `sts_endpoint` to use
+/// another one like a VPC endpoint.
 ///
 /// # Server Side Encryption
 ///
@@ -140,7 +243,15 @@ mod constants {
 ///   - `server_side_encryption_customer_key="base64-of-your-aes256-key"`
 ///   - `server_side_encryption_customer_key_md5="base64-of-your-aes256-key-md5"`
 ///
-/// After SSE have been configured, all requests send by this backed will attach those headers.
+/// After SSE have been configured, all requests send by this backed will attach those headers:
+///
+/// - `PutObject` and `CreateMultipartUpload` will carry all SSE headers.
+/// - `UploadPart`, `CompleteMultipartUpload`, `GetObject` and `HeadObject` will carry SSE-C headers only.
+/// - Presigned requests will sign those headers, and users must send the headers returned in
+///   `PresignedRequest` along with the url.
+///
+/// Misconfigured options (like customer key that is not 256 bits, or customer key used along with
+/// `server_side_encryption`) will be rejected with [`ErrorKind::ConfigInvalid`] while building.
 ///
 /// Reference: [Protecting data using server-side encryption](https://docs.aws.amazon.com/AmazonS3/latest/userguide/serv-side-encryption.html)
 ///
@@ -203,8 +314,9 @@ mod constants {
 ///
 ///     // Setup builders
 ///
-///     // Enable SSE-C
-///     builder.server_side_encryption_with_customer_key("AES256", "customer_key".as_bytes());
+///     // Enable SSE-C, customer key must be 256 bits.
+///     let customer_key = [0; 32];
+///     builder.server_side_encryption_with_customer_key("AES256", &customer_key);
 ///
 ///     let op = Operator::new(builder)?.finish();
 ///     info!("operator: {:?}", op);
@@ -304,6 +416,7 @@ pub struct S3Builder {
     region: Option<String>,
     role_arn: Option<String>,
     external_id: Option<String>,
+    sts_endpoint: Option<String>,
     access_key_id: Option<String>,
     secret_access_key: Option<String>,
     server_side_encryption: Option<String>,
@@ -317,6 +430,13 @@ pub struct S3Builder {
 
     disable_config_load: bool,
     enable_virtual_host_style: bool,
+    enable_request_payer: bool,
+    enable_list_objects_v1: bool,
+    http_headers: HashMap<String, String>,
+
+    write_part_size: Option<usize>,
+    write_concurrency: Option<usize>,
+    checksum_algorithm: Option<String>,
 
     http_client: Option<HttpClient>,
     customed_credential_load: Option<Arc<dyn AwsCredentialLoad>>,
@@ -332,8 +452,15 @@ impl Debug for S3Builder {
             .field("region", &self.region)
             .field("role_arn", &self.role_arn)
             .field("external_id", &self.external_id)
+            .field("sts_endpoint", &self.sts_endpoint)
             .field("disable_config_load", &self.disable_config_load)
-            .field("enable_virtual_host_style", &self.enable_virtual_host_style);
+            .field("enable_virtual_host_style", &self.enable_virtual_host_style)
+            .field("enable_request_payer", &self.enable_request_payer)
+            .field("enable_list_objects_v1", &self.enable_list_objects_v1)
+            .field("http_headers", &self.http_headers.keys())
+            .field("write_part_size", &self.write_part_size)
+            .field("write_concurrency", &self.write_concurrency)
+            .field("checksum_algorithm", &self.checksum_algorithm);
 
         if self.access_key_id.is_some() {
             d.field("access_key_id", &"<redacted>");
@@ -397,6 +524,10 @@ impl S3Builder {
     ///
     /// If user inputs endpoint without scheme like "s3.amazonaws.com", we
     /// will prepend "https://" before it.
+    ///
+    /// Services like Minio and Ceph RGW that serve on ip address should use
+    /// path style (the default). Building with [`S3Builder::enable_virtual_host_style`]
+    /// on such endpoints will return [`ErrorKind::ConfigInvalid`].
     pub fn endpoint(&mut self, endpoint: &str) -> &mut Self {
         if !endpoint.is_empty() {
             // Trim trailing `/` so that we can accept `http://127.0.0.1:9000/`
@@ -445,6 +576,10 @@ impl S3Builder {
     }
 
     /// Set role_arn for this backend.
+    ///
+    /// If set, OpenDAL will assume this role via STS `AssumeRole` with
+    /// the credentials loaded from other sources, and refresh the
+    /// temporary credentials before they expire.
     pub fn role_arn(&mut self, v: &str) -> &mut Self {
         if !v.is_empty() {
             self.role_arn = Some(v.to_string())
@@ -454,6 +589,9 @@ impl S3Builder {
     }
 
     /// Set external_id for this backend.
+    ///
+    /// external_id will be sent while assuming `role_arn`, it's required
+    /// if the role's trust policy checks `sts:ExternalId`.
     pub fn external_id(&mut self, v: &str) -> &mut Self {
         if !v.is_empty() {
             self.external_id = Some(v.to_string())
@@ -462,6 +600,19 @@ impl S3Builder {
         self
     }
 
+    /// Set the STS endpoint used while assuming role, for example,
+    /// `https://sts.us-east-1.amazonaws.com`.
+    ///
+    /// If not set, the global endpoint will be used unless
+    /// `AWS_STS_REGIONAL_ENDPOINTS` is `regional`.
+    pub fn sts_endpoint(&mut self, v: &str) -> &mut Self {
+        if !v.is_empty() {
+            self.sts_endpoint = Some(v.to_string())
+        }
+
+        self
+    }
+
     /// Set server_side_encryption for this backend.
     ///
     /// Available values: `AES256`, `aws:kms`.
@@ -644,11 +795,93 @@ impl S3Builder {
     ///
     /// - By default, opendal will send API to `https://s3.us-east-1.amazonaws.com/bucket_name`
     /// - Enabled, opendal will send API to `https://bucket_name.s3.us-east-1.amazonaws.com`
+    ///
+    /// Requests are signed against the url built in the chosen style, so
+    /// users don't need to configure signer separately.
+    ///
+    /// Virtual host style requires a domain endpoint and a dns compatible
+    /// bucket name, building will fail with [`ErrorKind::ConfigInvalid`]
+    /// if endpoint is an ip address or `localhost`.
     pub fn enable_virtual_host_style(&mut self) -> &mut Self {
         self.enable_virtual_host_style = true;
         self
     }
 
+    /// Enable request payer so that opendal will send
+    /// `x-amz-request-payer: requester` in every request.
+    ///
+    /// This is required to access buckets with [Requester Pays](https://docs.aws.amazon.com/AmazonS3/latest/userguide/RequesterPaysBuckets.html)
+    /// enabled, the cost of requests and data transfer will be charged to
+    /// the requester.
+    pub fn enable_request_payer(&mut self) -> &mut Self {
+        self.enable_request_payer = true;
+        self
+    }
+
+    /// Enable list objects v1 so that opendal will use [ListObjects](https://docs.aws.amazon.com/AmazonS3/latest/API/API_ListObjects.html)
+    /// instead of `ListObjectsV2` while listing.
+    ///
+    /// Some s3 compatible services (like old versions of Ceph) don't
+    /// implement `ListObjectsV2`, enable this to make list work with them.
+    pub fn enable_list_objects_v1(&mut self) -> &mut Self {
+        self.enable_list_objects_v1 = true;
+        self
+    }
+
+    /// Set the part size of multipart upload.
+    ///
+    /// Bytes appended by writer will be buffered until `write_part_size`
+    /// is reached and then uploaded as one part. The last part could be
+    /// smaller than it.
+    ///
+    /// S3 requires part size to be at least 5 MiB, building will fail with
+    /// [`ErrorKind::ConfigInvalid`] if given size is smaller.
+    ///
+    /// If not set, every append will be uploaded as one part directly.
+    pub fn write_part_size(&mut self, size: usize) -> &mut Self {
+        self.write_part_size = Some(size);
+        self
+    }
+
+    /// Set the max concurrent part uploads of multipart upload.
+    ///
+    /// Parts will be uploaded in parallel while users keep appending,
+    /// at most `write_part_size * write_concurrency` bytes will be kept
+    /// in memory.
+    ///
+    /// Default to `1` which means parts will be uploaded one by one.
+    pub fn write_concurrency(&mut self, concurrency: usize) -> &mut Self {
+        self.write_concurrency = Some(concurrency);
+        self
+    }
+
+    /// Set the checksum algorithm of uploads.
+    ///
+    /// Available values are `crc32c` and `sha256`. Checksum will be
+    /// calculated for every object and part, and be verified by S3.
+    pub fn checksum_algorithm(&mut self, algorithm: &str) -> &mut Self {
+        if !algorithm.is_empty() {
+            self.checksum_algorithm = Some(algorithm.to_string());
+        }
+        self
+    }
+
+    /// Set extra http header that will be sent in every request.
+    ///
+    /// This is useful for proxies that require custom headers. Headers
+    /// will be signed along with the request.
+    ///
+    /// Headers that are managed by opendal or the signer like `host`,
+    /// `authorization` and `x-amz-date` are not allowed, building will
+    /// fail with [`ErrorKind::ConfigInvalid`].
+    pub fn http_header(&mut self, key: &str, value: &str) -> &mut Self {
+        if !key.is_empty() {
+            self.http_headers
+                .insert(key.to_lowercase(), value.to_string());
+        }
+        self
+    }
+
     /// Adding a customed credential load for service.
     pub fn customed_credential_load(&mut self, cred: impl AwsCredentialLoad) -> &mut Self {
         self.customed_credential_load = Some(Arc::new(cred));
@@ -666,6 +899,29 @@ impl S3Builder {
         self
     }
 
+    /// Returns the STS endpoint and the region to sign STS requests.
+    ///
+    /// ref: <https://docs.aws.amazon.com/sdkref/latest/guide/feature-sts-regionalized-endpoints.html>
+    fn build_sts_endpoint(&self, cfg: &AwsConfigLoader, region: &str) -> (String, String) {
+        if let Some(v) = &self.sts_endpoint {
+            return (v.clone(), region.to_string());
+        }
+
+        let suffix = if region.starts_with("cn-") {
+            "amazonaws.com.cn"
+        } else {
+            "amazonaws.com"
+        };
+        if cfg.sts_regional_endpoints() == "regional" || region.starts_with("cn-") {
+            (format!("https://sts.{region}.{suffix}"), region.to_string())
+        } else {
+            (
+                "https://sts.amazonaws.com".to_string(),
+                "us-east-1".to_string(),
+            )
+        }
+    }
+
     /// Read RFC-0057: Auto Region for detailed behavior.
     ///
     /// - If region is already known, the region will be returned directly.
@@ -684,20 +940,7 @@ impl S3Builder {
         // Builder's bucket must be valid.
         let bucket = self.bucket.as_str();
 
-        let mut endpoint = match &self.endpoint {
-            Some(endpoint) => {
-                if endpoint.starts_with("http") {
-                    endpoint.to_string()
-                } else {
-                    // Prefix https if endpoint doesn't start with scheme.
-                    format!("https://{endpoint}")
-                }
-            }
-            None => "https://s3.amazonaws.com".to_string(),
-        };
-
-        // If endpoint contains bucket name, we should trim them.
-        endpoint = endpoint.replace(&format!("//{bucket}."), "//");
+        let endpoint = self.normalized_endpoint();
 
         let url = format!("{endpoint}/{bucket}");
         debug!("backend detect region with url: {url}");
@@ -761,15 +1004,15 @@ impl S3Builder {
         }
     }
 
-    /// Build endpoint with given region.
-    fn build_endpoint(&self, region: &str) -> String {
-        let bucket = {
-            debug_assert!(!self.bucket.is_empty(), "bucket must be valid");
-
-            self.bucket.as_str()
-        };
+    /// Normalize user input endpoint.
+    ///
+    /// - Prefix `https://` if endpoint doesn't start with scheme.
+    /// - Trim bucket name if endpoint contains it:
+    ///   `https://bucket_name.s3.amazonaws.com` => `https://s3.amazonaws.com`
+    fn normalized_endpoint(&self) -> String {
+        let bucket = self.bucket.as_str();
 
-        let mut endpoint = match &self.endpoint {
+        let endpoint = match &self.endpoint {
             Some(endpoint) => {
                 if endpoint.starts_with("http") {
                     endpoint.to_string()
@@ -782,7 +1025,182 @@ impl S3Builder {
         };
 
         // If endpoint contains bucket name, we should trim them.
-        endpoint = endpoint.replace(&format!("//{bucket}."), "//");
+        endpoint.replace(&format!("//{bucket}."), "//")
+    }
+
+    /// Check whether the endpoint is valid for current addressing style.
+    ///
+    /// Virtual host style puts bucket name into the host, which doesn't
+    /// work for endpoints like `http://127.0.0.1:9000` or
+    /// `http://localhost:9000`. We return error early instead of failing
+    /// with dns or signature errors later.
+    fn validate_endpoint(&self) -> Result<()> {
+        let endpoint = self.normalized_endpoint();
+
+        let uri = parse_endpoint(&endpoint).map_err(|e| e.with_context("service", Scheme::S3))?;
+        let host = uri.host().expect("parsed endpoint must have host");
+
+        if !self.enable_virtual_host_style {
+            return Ok(());
+        }
+
+        let is_ip = host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok();
+        if is_ip || host.eq_ignore_ascii_case("localhost") {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "virtual host style requires a domain endpoint, please use path style instead",
+            )
+            .with_context("service", Scheme::S3)
+            .with_context("endpoint", &endpoint));
+        }
+
+        // Bucket name will be used as dns label in virtual host style.
+        let is_dns_compatible = self
+            .bucket
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.');
+        if !is_dns_compatible {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "bucket name is not dns compatible for virtual host style",
+            )
+            .with_context("service", Scheme::S3)
+            .with_context("bucket", &self.bucket));
+        }
+
+        Ok(())
+    }
+
+    /// Check SSE related options so that misconfigurations will be
+    /// reported while building instead of failing every request.
+    fn validate_server_side_encryption(&self) -> Result<()> {
+        if let Some(v) = &self.server_side_encryption {
+            if v != "AES256" && v != "aws:kms" {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "server_side_encryption must be AES256 or aws:kms",
+                )
+                .with_context("service", Scheme::S3)
+                .with_context("server_side_encryption", v));
+            }
+        }
+
+        let key = match &self.server_side_encryption_customer_key {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+
+        if self.server_side_encryption.is_some() {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "server_side_encryption can't be used along with customer key",
+            )
+            .with_context("service", Scheme::S3));
+        }
+
+        match self.server_side_encryption_customer_algorithm.as_deref() {
+            Some("AES256") => {}
+            v => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "server_side_encryption_customer_algorithm must be AES256",
+                )
+                .with_context("service", Scheme::S3)
+                .with_context("algorithm", v.unwrap_or_default()))
+            }
+        }
+
+        let key = BASE64_STANDARD.decode(key).map_err(|e| {
+            Error::new(
+                ErrorKind::ConfigInvalid,
+                "server_side_encryption_customer_key is not valid base64",
+            )
+            .with_context("service", Scheme::S3)
+            .set_source(e)
+        })?;
+        if key.len() != 32 {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "server_side_encryption_customer_key must be 256 bits",
+            )
+            .with_context("service", Scheme::S3)
+            .with_context("length", key.len().to_string()));
+        }
+
+        let md5 = BASE64_STANDARD.encode(Md5::digest(&key).as_slice());
+        match &self.server_side_encryption_customer_key_md5 {
+            Some(v) if v != &md5 => Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "server_side_encryption_customer_key_md5 doesn't match customer key",
+            )
+            .with_context("service", Scheme::S3)),
+            Some(_) => Ok(()),
+            None => Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "server_side_encryption_customer_key_md5 is required for customer key",
+            )
+            .with_context("service", Scheme::S3)),
+        }
+    }
+
+    /// Build extra headers that will be sent in every request.
+    fn build_http_headers(&self) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+
+        for (k, v) in &self.http_headers {
+            if matches!(
+                k.as_str(),
+                "host" | "authorization" | "x-amz-date" | "x-amz-content-sha256"
+            ) {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "http header is managed by opendal and can't be set",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::S3)
+                .with_context("header", k));
+            }
+
+            let name = HeaderName::from_bytes(k.as_bytes()).map_err(|e| {
+                Error::new(ErrorKind::ConfigInvalid, "http header name is invalid")
+                    .with_operation("Builder::build")
+                    .with_context("service", Scheme::S3)
+                    .with_context("header", k)
+                    .set_source(e)
+            })?;
+            let value = HeaderValue::from_str(v).map_err(|e| {
+                Error::new(ErrorKind::ConfigInvalid, "http header value is invalid")
+                    .with_operation("Builder::build")
+                    .with_context("service", Scheme::S3)
+                    .with_context("header", k)
+                    .set_source(e)
+            })?;
+            headers.insert(name, value);
+        }
+
+        if self.enable_request_payer {
+            headers.insert(
+                HeaderName::from_static(constants::X_AMZ_REQUEST_PAYER),
+                HeaderValue::from_static("requester"),
+            );
+        }
+
+        Ok(headers)
+    }
+
+    /// Build endpoint with given region.
+    fn build_endpoint(&self, region: &str) -> String {
+        let bucket = {
+            debug_assert!(!self.bucket.is_empty(), "bucket must be valid");
+
+            self.bucket.as_str()
+        };
+
+        let mut endpoint = self.normalized_endpoint();
 
         // Update with endpoint templates.
         endpoint = if let Some(template) = ENDPOINT_TEMPLATES.get(endpoint.as_str()) {
@@ -821,6 +1239,7 @@ impl Builder for S3Builder {
         map.get("security_token").map(|v| builder.security_token(v));
         map.get("role_arn").map(|v| builder.role_arn(v));
         map.get("external_id").map(|v| builder.external_id(v));
+        map.get("sts_endpoint").map(|v| builder.sts_endpoint(v));
         map.get("server_side_encryption")
             .map(|v| builder.server_side_encryption(v));
         map.get("server_side_encryption_aws_kms_key_id")
@@ -837,6 +1256,26 @@ impl Builder for S3Builder {
         map.get("enable_virtual_host_style")
             .filter(|v| *v == "on" || *v == "true")
             .map(|_| builder.enable_virtual_host_style());
+        map.get("enable_request_payer")
+            .filter(|v| *v == "on" || *v == "true")
+            .map(|_| builder.enable_request_payer());
+        map.get("enable_list_objects_v1")
+            .filter(|v| *v == "on" || *v == "true")
+            .map(|_| builder.enable_list_objects_v1());
+        map.get("write_part_size")
+            .and_then(|v| v.parse().ok())
+            .map(|v| builder.write_part_size(v));
+        map.get("write_concurrency")
+            .and_then(|v| v.parse().ok())
+            .map(|v| builder.write_concurrency(v));
+        map.get("checksum_algorithm")
+            .map(|v| builder.checksum_algorithm(v));
+        // http_headers is in format like `key1:value1,key2:value2`.
+        if let Some(v) = map.get("http_headers") {
+            for (k, v) in v.split(',').filter_map(|kv| kv.split_once(':')) {
+                builder.http_header(k.trim(), v.trim());
+            }
+        }
 
         builder
     }
@@ -850,10 +1289,27 @@ impl Builder for S3Builder {
         // Handle bucket name.
         let bucket = match self.bucket.is_empty() {
             false => Ok(&self.bucket),
-            true => Err(Error::new(ErrorKind::ConfigInvalid, "bucket is empty")),
+            true => Err(Error::new(ErrorKind::ConfigInvalid, "bucket is empty")
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::S3)),
         }?;
         debug!("backend use bucket {}", &bucket);
 
+        self.validate_endpoint()
+            .map_err(|err| err.with_operation("Builder::build"))?;
+
+        self.validate_server_side_encryption()
+            .map_err(|err| err.with_operation("Builder::build"))?;
+
+        if self.external_id.is_some() && self.role_arn.is_none() {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "external_id is set but role_arn is empty",
+            )
+            .with_operation("Builder::build")
+            .with_context("service", Scheme::S3));
+        }
+
         let server_side_encryption = match &self.server_side_encryption {
             None => None,
             Some(v) => Some(v.parse().map_err(|e| {
@@ -916,6 +1372,43 @@ impl Builder for S3Builder {
                 })?),
             };
 
+        let http_headers = self.build_http_headers()?;
+
+        if let Some(size) = self.write_part_size {
+            if size < MIN_WRITE_PART_SIZE {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "write_part_size must be at least 5 MiB",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::S3)
+                .with_context("write_part_size", size.to_string()));
+            }
+        }
+        let write_concurrency = self.write_concurrency.unwrap_or(1);
+        if write_concurrency == 0 {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "write_concurrency must be greater than 0",
+            )
+            .with_operation("Builder::build")
+            .with_context("service", Scheme::S3));
+        }
+        let checksum_algorithm = match self.checksum_algorithm.as_deref() {
+            None => None,
+            Some(v) if v.eq_ignore_ascii_case("crc32c") => Some(ChecksumAlgorithm::Crc32c),
+            Some(v) if v.eq_ignore_ascii_case("sha256") => Some(ChecksumAlgorithm::Sha256),
+            Some(v) => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "checksum_algorithm value is invalid",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::S3)
+                .with_context("value", v))
+            }
+        };
+
         let client = if let Some(client) = self.http_client.take() {
             client
         } else {
@@ -943,12 +1436,6 @@ impl Builder for S3Builder {
         if let Some(v) = &self.security_token {
             cfg.set_session_token(v);
         }
-        if let Some(v) = &self.role_arn {
-            cfg.set_role_arn(v);
-        }
-        if let Some(v) = &self.external_id {
-            cfg.set_external_id(v);
-        }
 
         // Calculate region based on current cfg.
         let region = match cfg.region() {
@@ -979,26 +1466,75 @@ impl Builder for S3Builder {
         let endpoint = self.build_endpoint(&region);
         debug!("backend use endpoint: {endpoint}");
 
+        let mut cred_loader = AwsCredentialLoader::new(cfg.clone());
+        cred_loader = cred_loader.with_allow_anonymous();
+        cred_loader = cred_loader.with_client(client.sync_client());
+        if self.disable_config_load {
+            // If load config has been disable, we should also disable
+            // ec2 metadata to avoid leaking permits.
+            cred_loader = cred_loader.with_disable_ec2_metadata();
+        }
+
+        // Loaders of temporary credentials from STS, their credentials
+        // will be loaded in order before signing to surface STS errors.
+        let mut sts = Vec::new();
+        let (sts_endpoint, sts_region) = self.build_sts_endpoint(&cfg, &region);
+
+        if let Some(ccl) = &self.customed_credential_load {
+            cred_loader = cred_loader.with_customed_credential_loader(ccl.clone());
+        } else if let (Some(token_file), Some(role_arn)) =
+            (cfg.web_identity_token_file(), cfg.role_arn())
+        {
+            let loader = Arc::new(StsLoader::web_identity(
+                client.clone(),
+                &sts_endpoint,
+                &role_arn,
+                &cfg.role_session_name(),
+                &token_file,
+            ));
+            cred_loader = cred_loader.with_customed_credential_loader(loader.clone());
+            sts.push(loader);
+        }
+        let mut cred_loader = Arc::new(cred_loader);
+
+        if let Some(role_arn) = &self.role_arn {
+            let sts_cfg = AwsConfigLoader::default();
+            sts_cfg.set_region(&sts_region);
+
+            // AssumeRole is signed by the credentials loaded above.
+            let sts_signer = AwsV4Signer::builder()
+                .service("sts")
+                .config_loader(sts_cfg)
+                .credential_loader(ClockSkew::shared_credential_loader(cred_loader))
+                .build()
+                .map_err(|e| {
+                    Error::new(ErrorKind::Unexpected, "build AwsV4Signer").set_source(e)
+                })?;
+            let loader = Arc::new(StsLoader::assume_role(
+                client.clone(),
+                &sts_endpoint,
+                role_arn,
+                &cfg.role_session_name(),
+                self.external_id.as_deref(),
+                sts_signer,
+            ));
+            sts.push(loader.clone());
+
+            cred_loader = Arc::new(
+                AwsCredentialLoader::new(AwsConfigLoader::default())
+                    .with_allow_anonymous()
+                    .with_disable_ec2_metadata()
+                    .with_customed_credential_loader(loader),
+            );
+        }
+
+        let clock_skew = ClockSkew::new(&region, cred_loader.clone());
+
         let mut signer_builder = AwsV4Signer::builder();
         signer_builder.service("s3");
         signer_builder.allow_anonymous();
         signer_builder.config_loader(cfg.clone());
-        signer_builder.credential_loader({
-            let mut cred_loader = AwsCredentialLoader::new(cfg);
-            cred_loader = cred_loader.with_allow_anonymous();
-            cred_loader = cred_loader.with_client(client.sync_client());
-            if self.disable_config_load {
-                // If load config has been disable, we should also disable
-                // ec2 metadata to avoid leaking permits.
-                cred_loader = cred_loader.with_disable_ec2_metadata();
-            }
-
-            if let Some(ccl) = &self.customed_credential_load {
-                cred_loader = cred_loader.with_customed_credential_loader(ccl.clone());
-            }
-
-            cred_loader
-        });
+        signer_builder.credential_loader(ClockSkew::shared_credential_loader(cred_loader));
 
         let signer = signer_builder
             .build()
@@ -1009,6 +1545,8 @@ impl Builder for S3Builder {
             root,
             endpoint,
             signer: Arc::new(signer),
+            clock_skew: Arc::new(clock_skew),
+            sts,
             bucket: self.bucket.clone(),
             client,
 
@@ -1017,16 +1555,45 @@ impl Builder for S3Builder {
             server_side_encryption_customer_algorithm,
             server_side_encryption_customer_key,
             server_side_encryption_customer_key_md5,
+
+            http_headers,
+            enable_list_objects_v1: self.enable_list_objects_v1,
+
+            write_part_size: self.write_part_size,
+            write_concurrency,
+            checksum_algorithm,
         })
     }
 }
 
+/// Clone the request so that it can be signed and sent again.
+///
+/// All bodies of s3 requests are in memory, so cloning is cheap.
+fn clone_request(req: &Request<AsyncBody>) -> Request<AsyncBody> {
+    let body = match req.body() {
+        AsyncBody::Empty => AsyncBody::Empty,
+        AsyncBody::Bytes(bs) => AsyncBody::Bytes(bs.clone()),
+        AsyncBody::Multipart(name, bs) => AsyncBody::Multipart(name.clone(), bs.clone()),
+    };
+
+    let mut new = Request::new(body);
+    *new.method_mut() = req.method().clone();
+    *new.uri_mut() = req.uri().clone();
+    *new.version_mut() = req.version();
+    *new.headers_mut() = req.headers().clone();
+    new
+}
+
 /// Backend for s3 services.
 #[derive(Debug, Clone)]
 pub struct S3Backend {
     bucket: String,
     endpoint: String,
     pub signer: Arc<AwsV4Signer>,
+    /// Corrects the signing time if the local clock is skewed.
+    clock_skew: Arc<ClockSkew>,
+    /// Loaders of temporary credentials from STS, in the order of loading.
+    sts: Vec<Arc<StsLoader>>,
     pub client: HttpClient,
     // root will be "/" or "/abc/"
     root: String,
@@ -1036,13 +1603,118 @@ pub struct S3Backend {
     server_side_encryption_customer_algorithm: Option<HeaderValue>,
     server_side_encryption_customer_key: Option<HeaderValue>,
     server_side_encryption_customer_key_md5: Option<HeaderValue>,
+
+    /// Extra headers that will be inserted into every request before
+    /// signing, including `x-amz-request-payer`.
+    http_headers: HeaderMap,
+    /// Use ListObjects (v1) instead of ListObjectsV2 while listing.
+    pub(super) enable_list_objects_v1: bool,
+
+    /// Part size of multipart upload, `None` means every append is a part.
+    pub(super) write_part_size: Option<usize>,
+    /// Max concurrent part uploads of multipart upload.
+    pub(super) write_concurrency: usize,
+    /// Checksum algorithm of uploads.
+    pub(super) checksum_algorithm: Option<ChecksumAlgorithm>,
 }
 
 impl S3Backend {
+    /// Insert extra headers and sign the request.
+    ///
+    /// All requests must be signed via this function so that extra headers
+    /// like `x-amz-request-payer` are included in the signature.
+    pub fn sign(&self, req: &mut Request<AsyncBody>) -> Result<()> {
+        self.load_sts_credential()?;
+        self.insert_http_headers(req);
+
+        // `AwsV4Signer` always signs with `UNSIGNED-PAYLOAD`, requests with
+        // their own payload hash like trailer checksums are signed by
+        // ClockSkew instead.
+        if self.clock_skew.is_skewed()
+            || req.headers().contains_key(constants::X_AMZ_CONTENT_SHA_256)
+        {
+            return self.clock_skew.sign(req);
+        }
+
+        self.signer.sign(req).map_err(new_request_sign_error)
+    }
+
+    /// Sign and send the request.
+    ///
+    /// If S3 rejects the request with `RequestTimeTooSkewed`, the offset of
+    /// our clock will be learned from the response, and the request will be
+    /// signed with the corrected time and sent again. Following requests
+    /// will be signed with the corrected time directly.
+    pub(super) async fn send(
+        &self,
+        req: Request<AsyncBody>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let retry = clone_request(&req);
+
+        let mut req = req;
+        self.sign(&mut req)?;
+        let resp = self.client.send_async(req).await?;
+
+        // Skew errors are always returned as `403 Forbidden`, other
+        // responses are returned as is without buffering the body.
+        if resp.status() != StatusCode::FORBIDDEN {
+            return Ok(resp);
+        }
+
+        let (parts, body) = resp.into_parts();
+        let bs = body.bytes().await?;
+
+        let server_time = match parse_request_time_too_skewed(&parts.headers, &bs) {
+            Some(v) => v,
+            None => {
+                let size = bs.len() as u64;
+                let body = IncomingAsyncBody::new(
+                    Box::new(futures::stream::iter(vec![Ok(bs)])),
+                    Some(size),
+                );
+                return Ok(Response::from_parts(parts, body));
+            }
+        };
+
+        let offset = self.clock_skew.update(server_time);
+        warn!("local clock is skewed from s3 by {offset}, retrying with corrected time");
+
+        // Concurrent requests are likely to hit the skew at the same time,
+        // spread their retries a bit.
+        tokio::time::sleep(ClockSkew::jitter()).await;
+
+        let mut req = retry;
+        self.sign(&mut req)?;
+        self.client.send_async(req).await
+    }
+
+    /// Load credentials from STS so that failed refreshes are returned
+    /// instead of sending requests anonymously.
+    ///
+    /// Credentials are cached by loaders, this is cheap unless they are
+    /// about to expire.
+    fn load_sts_credential(&self) -> Result<()> {
+        for loader in &self.sts {
+            loader.load()?;
+        }
+        Ok(())
+    }
+
+    fn insert_http_headers<T>(&self, req: &mut Request<T>) {
+        for (k, v) in &self.http_headers {
+            req.headers_mut().insert(k.clone(), v.clone());
+        }
+    }
+
     /// # Note
     ///
     /// header like X_AMZ_SERVER_SIDE_ENCRYPTION doesn't need to set while
-    //  get or stat.
+    /// get or stat.
+    ///
+    /// `is_write` should only be true for requests that create object like
+    /// `PutObject` and `CreateMultipartUpload`. `UploadPart` and
+    /// `CompleteMultipartUpload` only accept SSE-C headers and will reject
+    /// the others.
     pub(crate) fn insert_sse_headers(
         &self,
         mut req: http::request::Builder,
@@ -1105,7 +1777,7 @@ impl S3Backend {
 impl Accessor for S3Backend {
     type Reader = IncomingAsyncBody;
     type BlockingReader = ();
-    type Writer = S3Writer;
+    type Writer = oio::MultipartUploadWriter<S3Writer>;
     type BlockingWriter = ();
     type Pager = S3Pager;
     type BlockingPager = ();
@@ -1119,17 +1791,47 @@ impl Accessor for S3Backend {
             .set_root(&self.root)
             .set_name(&self.bucket)
             .set_capabilities(Read | Write | List | Scan | Presign | Batch)
+            .set_capability(Capability {
+                stat: true,
+                stat_with_tags: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                write_multi: true,
+                write_with_content_type: true,
+                write_with_if_not_exists: true,
+                write_with_if_match: true,
+                write_with_tags: true,
+                create_dir: true,
+                delete: true,
+                copy: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                scan: true,
+                presign: true,
+                presign_read: true,
+                presign_stat: true,
+                presign_write: true,
+                batch: true,
+                batch_delete: true,
+                restore: true,
+                abort_multipart: true,
+                tags: true,
+                ..Default::default()
+            })
             .set_hints(ReadStreamable);
 
         am
     }
 
     async fn create(&self, path: &str, _: OpCreate) -> Result<RpCreate> {
-        let mut req = self.s3_put_object_request(path, Some(0), None, None, AsyncBody::Empty)?;
-
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        let req = self.s3_put_object_request(path, Some(0), None, None, AsyncBody::Empty)?;
 
-        let resp = self.client.send_async(req).await?;
+        let resp = self.send(req).await?;
 
         let status = resp.status();
 
@@ -1143,48 +1845,51 @@ impl Accessor for S3Backend {
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        let resp = self.s3_get_object(path, args.range()).await?;
+        let range = args.range();
+        let resp = self.s3_get_object(path, range).await?;
 
         let status = resp.status();
 
         match status {
             StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
-                let meta = parse_into_metadata(path, resp.headers())?;
-                Ok((RpRead::with_metadata(meta), resp.into_body()))
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
             }
             _ => Err(parse_error(resp).await?),
         }
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        let upload_id = if args.append() {
-            let resp = self.s3_initiate_multipart_upload(path).await?;
-
-            let status = resp.status();
+        // Validate tags before sending any request.
+        format_tagging(args.tags())?;
 
-            match status {
-                StatusCode::OK => {
-                    let bs = resp.into_body().bytes().await?;
-
-                    let result: InitiateMultipartUploadResult =
-                        quick_xml::de::from_reader(bs.reader())
-                            .map_err(new_xml_deserialize_error)?;
+        let append = args.append();
+        let w = S3Writer::new(self.clone(), args, path.to_string());
 
-                    Some(result.upload_id)
-                }
-                _ => return Err(parse_error(resp).await?),
-            }
+        // Initiate the upload while creating the writer and return the
+        // upload id as session, so that users could abort it even if the
+        // writer is lost.
+        let upload_id = if append {
+            Some(oio::MultipartUploadWrite::initiate_part(&w).await?)
         } else {
             None
         };
 
-        Ok((
-            RpWrite::default(),
-            S3Writer::new(self.clone(), args, path.to_string(), upload_id),
-        ))
+        let mut w = oio::MultipartUploadWriter::new(w, self.write_part_size)
+            .with_concurrency(self.write_concurrency);
+        let rp = match upload_id {
+            Some(upload_id) => {
+                let rp = RpWrite::new().with_session(&upload_id);
+                w = w.with_upload_id(upload_id);
+                rp
+            }
+            None => RpWrite::new(),
+        };
+
+        Ok((rp, w))
     }
 
-    async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
         // Stat root always returns a DIR.
         if path == "/" {
             return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
@@ -1195,7 +1900,17 @@ impl Accessor for S3Backend {
         let status = resp.status();
 
         match status {
-            StatusCode::OK => parse_into_metadata(path, resp.headers()).map(RpStat::new),
+            StatusCode::OK => {
+                let mut meta = parse_into_metadata(path, resp.headers())?;
+                if meta.mode().is_file() {
+                    meta.set_storage_class(&parse_storage_class(resp.headers())?);
+                    meta.set_restore_status(parse_restore_status(resp.headers())?);
+                }
+                if args.tags() {
+                    meta.set_tags(self.get_object_tags(path).await?);
+                }
+                Ok(RpStat::new(meta))
+            }
             StatusCode::NOT_FOUND if path.ends_with('/') => {
                 Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
             }
@@ -1203,20 +1918,100 @@ impl Accessor for S3Backend {
         }
     }
 
-    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
-        let resp = self.s3_delete_object(path).await?;
+    async fn restore(&self, path: &str, args: OpRestore) -> Result<RpRestore> {
+        let resp = self.s3_restore_object(path, &args).await?;
 
         let status = resp.status();
 
         match status {
-            StatusCode::NO_CONTENT => Ok(RpDelete::default()),
+            // - `202 Accepted`: restore has been initiated.
+            // - `200 OK`: object has been restored, expiry is updated.
+            // - `409 Conflict`: restore is already in progress.
+            StatusCode::ACCEPTED | StatusCode::OK | StatusCode::CONFLICT => {
+                resp.into_body().consume().await?;
+                Ok(RpRestore::default())
+            }
             _ => Err(parse_error(resp).await?),
         }
     }
 
-    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
-        Ok((
-            RpList::default(),
+    async fn abort_multipart(
+        &self,
+        path: &str,
+        args: OpAbortMultipart,
+    ) -> Result<RpAbortMultipart> {
+        let resp = self
+            .s3_abort_multipart_upload(path, args.upload_id())
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            // s3 returns code 204 if abort succeeds.
+            StatusCode::NO_CONTENT => {
+                resp.into_body().consume().await?;
+                Ok(RpAbortMultipart::default())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn get_tags(&self, path: &str, _: OpGetTags) -> Result<RpGetTags> {
+        let tags = self.get_object_tags(path).await?;
+
+        Ok(RpGetTags::new(tags))
+    }
+
+    async fn put_tags(&self, path: &str, args: OpPutTags) -> Result<RpPutTags> {
+        validate_tags(args.tags())?;
+
+        let resp = self.s3_put_object_tagging(path, args.tags()).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(RpPutTags::default())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        let resp = self.s3_delete_object(path).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::NO_CONTENT => Ok(RpDelete::default()),
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn copy(&self, from: &str, to: &str, _: OpCopy) -> Result<RpCopy> {
+        let resp = self.s3_copy_object(from, to).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                // Copy could fail after `200 OK` has been sent, check the
+                // body for errors.
+                let (parts, body) = resp.into_parts();
+                let bs = body.bytes().await?;
+                match parse_ok_error(&parts, &bs) {
+                    Some(err) => Err(err),
+                    None => Ok(RpCopy::default()),
+                }
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        Ok((
+            RpList::default(),
             S3Pager::new(Arc::new(self.clone()), &self.root, path, "/", args.limit()),
         ))
     }
@@ -1238,9 +2033,15 @@ impl Accessor for S3Backend {
             }
         };
 
-        self.signer
-            .sign_query(&mut req, args.expire())
-            .map_err(new_request_sign_error)?;
+        self.load_sts_credential()?;
+        self.insert_http_headers(&mut req);
+        if self.clock_skew.is_skewed() {
+            self.clock_skew.sign_query(&mut req, args.expire())?;
+        } else {
+            self.signer
+                .sign_query(&mut req, args.expire())
+                .map_err(new_request_sign_error)?;
+        }
 
         // We don't need this request anymore, consume it directly.
         let (parts, _) = req.into_parts();
@@ -1330,7 +2131,9 @@ impl S3Backend {
         }
 
         // Set SSE headers.
-        // TODO: how will this work with presign?
+        //
+        // While presigning, these headers will be signed and returned in
+        // `PresignedRequest`, users must send them along with the url.
         req = self.insert_sse_headers(req, false);
 
         let req = req
@@ -1345,11 +2148,9 @@ impl S3Backend {
         path: &str,
         range: BytesRange,
     ) -> Result<Response<IncomingAsyncBody>> {
-        let mut req = self.s3_get_object_request(path, range)?;
+        let req = self.s3_get_object_request(path, range)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
-
-        self.client.send_async(req).await
+        self.send(req).await
     }
 
     pub fn s3_put_object_request(
@@ -1397,13 +2198,152 @@ impl S3Backend {
         // Set SSE headers.
         req = self.insert_sse_headers(req, false);
 
-        let mut req = req
+        let req = req
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        self.send(req).await
+    }
+
+    async fn s3_restore_object(
+        &self,
+        path: &str,
+        args: &OpRestore,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!("{}/{}?restore", self.endpoint, percent_encode_path(&p));
 
-        self.client.send_async(req).await
+        let content = quick_xml::se::to_string(&RestoreRequest {
+            days: args.days(),
+            glacier_job_parameters: args.tier().map(|tier| GlacierJobParameters {
+                tier: tier.to_string(),
+            }),
+        })
+        .map_err(new_xml_deserialize_error)?;
+
+        let req = Request::post(&url)
+            .header(CONTENT_LENGTH, content.len())
+            .header(CONTENT_TYPE, "application/xml")
+            .body(AsyncBody::Bytes(Bytes::from(content)))
+            .map_err(new_request_build_error)?;
+
+        self.send(req).await
+    }
+
+    async fn s3_copy_object(&self, from: &str, to: &str) -> Result<Response<IncomingAsyncBody>> {
+        let source = build_abs_path(&self.root, from);
+        let target = build_abs_path(&self.root, to);
+
+        let source = format!("{}/{}", self.bucket, percent_encode_path(&source));
+        let url = format!("{}/{}", self.endpoint, percent_encode_path(&target));
+
+        let mut req = Request::put(&url).header(constants::X_AMZ_COPY_SOURCE, source);
+
+        // The destination object will be encrypted with the configured
+        // SSE settings just like a normal write.
+        req = self.insert_sse_headers(req, true);
+        // The source object is encrypted with the same customer key, which
+        // is required to read it.
+        for (name, v) in [
+            (
+                constants::X_AMZ_COPY_SOURCE_SERVER_SIDE_ENCRYPTION_CUSTOMER_ALGORITHM,
+                &self.server_side_encryption_customer_algorithm,
+            ),
+            (
+                constants::X_AMZ_COPY_SOURCE_SERVER_SIDE_ENCRYPTION_CUSTOMER_KEY,
+                &self.server_side_encryption_customer_key,
+            ),
+            (
+                constants::X_AMZ_COPY_SOURCE_SERVER_SIDE_ENCRYPTION_CUSTOMER_KEY_MD5,
+                &self.server_side_encryption_customer_key_md5,
+            ),
+        ] {
+            if let Some(v) = v {
+                let mut v = v.clone();
+                v.set_sensitive(true);
+
+                req = req.header(HeaderName::from_static(name), v)
+            }
+        }
+
+        let req = req
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send(req).await
+    }
+
+    async fn s3_get_object_tagging(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!("{}/{}?tagging", self.endpoint, percent_encode_path(&p));
+
+        let req = Request::get(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send(req).await
+    }
+
+    /// Fetch tags of given path via GetObjectTagging.
+    async fn get_object_tags(&self, path: &str) -> Result<HashMap<String, String>> {
+        let resp = self.s3_get_object_tagging(path).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let result: Tagging =
+                    quick_xml::de::from_reader(bs.reader()).map_err(new_xml_deserialize_error)?;
+
+                Ok(result
+                    .tag_set
+                    .tag
+                    .into_iter()
+                    .map(|t| (t.key, t.value))
+                    .collect())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn s3_put_object_tagging(
+        &self,
+        path: &str,
+        tags: &HashMap<String, String>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!("{}/{}?tagging", self.endpoint, percent_encode_path(&p));
+
+        let mut tags: Vec<_> = tags.iter().collect();
+        tags.sort();
+
+        let content = quick_xml::se::to_string(&Tagging {
+            tag_set: TagSet {
+                tag: tags
+                    .into_iter()
+                    .map(|(k, v)| Tag {
+                        key: k.to_string(),
+                        value: v.to_string(),
+                    })
+                    .collect(),
+            },
+        })
+        .map_err(new_xml_deserialize_error)?;
+
+        let req = Request::put(&url)
+            .header(CONTENT_LENGTH, content.len())
+            .header(CONTENT_TYPE, "application/xml")
+            // Set content-md5 as required by API.
+            .header("CONTENT-MD5", format_content_md5(content.as_bytes()))
+            .body(AsyncBody::Bytes(Bytes::from(content)))
+            .map_err(new_request_build_error)?;
+
+        self.send(req).await
     }
 
     async fn s3_delete_object(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
@@ -1411,13 +2351,11 @@ impl S3Backend {
 
         let url = format!("{}/{}", self.endpoint, percent_encode_path(&p));
 
-        let mut req = Request::delete(&url)
+        let req = Request::delete(&url)
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
-
-        self.client.send_async(req).await
+        self.send(req).await
     }
 
     /// Make this functions as `pub(suber)` because `DirStream` depends
@@ -1452,35 +2390,73 @@ impl S3Backend {
             .expect("write into string must succeed");
         }
 
-        let mut req = Request::get(&url)
+        let req = Request::get(&url)
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        self.send(req).await
+    }
 
-        self.client.send_async(req).await
+    /// ListObjects (v1) which uses `marker` instead of `continuation-token`
+    /// for pagination.
+    ///
+    /// Make this functions as `pub(suber)` because `DirStream` depends
+    /// on this.
+    pub(super) async fn s3_list_objects_v1(
+        &self,
+        path: &str,
+        marker: &str,
+        delimiter: &str,
+        limit: Option<usize>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let mut url = format!(
+            "{}?delimiter={delimiter}&prefix={}",
+            self.endpoint,
+            percent_encode_path(&p)
+        );
+        if let Some(limit) = limit {
+            write!(url, "&max-keys={limit}").expect("write into string must succeed");
+        }
+        if !marker.is_empty() {
+            write!(url, "&marker={}", percent_encode_path(marker))
+                .expect("write into string must succeed");
+        }
+
+        let req = Request::get(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send(req).await
     }
 
-    async fn s3_initiate_multipart_upload(
+    pub(super) async fn s3_initiate_multipart_upload(
         &self,
         path: &str,
+        tagging: Option<&str>,
     ) -> Result<Response<IncomingAsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
         let url = format!("{}/{}?uploads", self.endpoint, percent_encode_path(&p));
 
-        let req = Request::post(&url);
+        let mut req = Request::post(&url);
+
+        if let Some(tagging) = tagging {
+            req = req.header(constants::X_AMZ_TAGGING, tagging);
+        }
+        if let Some(algo) = self.checksum_algorithm {
+            req = req.header(constants::X_AMZ_CHECKSUM_ALGORITHM, algo.name());
+        }
 
         // Set SSE headers.
         let req = self.insert_sse_headers(req, true);
 
-        let mut req = req
+        let req = req
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
-
-        self.client.send_async(req).await
+        self.send(req).await
     }
 
     pub fn s3_upload_part_request(
@@ -1507,8 +2483,8 @@ impl S3Backend {
             req = req.header(CONTENT_LENGTH, size);
         }
 
-        // Set SSE headers.
-        req = self.insert_sse_headers(req, true);
+        // Set SSE-C headers only, upload part doesn't accept others.
+        req = self.insert_sse_headers(req, false);
 
         // Set body
         let req = req.body(body).map_err(new_request_build_error)?;
@@ -1521,6 +2497,7 @@ impl S3Backend {
         path: &str,
         upload_id: &str,
         parts: &[CompleteMultipartUploadRequestPart],
+        args: &OpWrite,
     ) -> Result<Response<IncomingAsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
@@ -1533,8 +2510,17 @@ impl S3Backend {
 
         let req = Request::post(&url);
 
-        // Set SSE headers.
-        let req = self.insert_sse_headers(req, true);
+        // Set SSE-C headers only, complete upload doesn't accept others.
+        let mut req = self.insert_sse_headers(req, false);
+
+        // Only complete the upload if the object doesn't exist.
+        if args.if_not_exists() {
+            req = req.header(IF_NONE_MATCH, "*");
+        }
+        // Only complete the upload if the object's etag matches.
+        if let Some(etag) = args.if_match() {
+            req = req.header(IF_MATCH, etag);
+        }
 
         let content = quick_xml::se::to_string(&CompleteMultipartUploadRequest {
             part: parts.to_vec(),
@@ -1545,13 +2531,35 @@ impl S3Backend {
         // Set content-type to `application/xml` to avoid mixed with form post.
         let req = req.header(CONTENT_TYPE, "application/xml");
 
-        let mut req = req
+        let req = req
             .body(AsyncBody::Bytes(Bytes::from(content)))
             .map_err(new_request_build_error)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        self.send(req).await
+    }
 
-        self.client.send_async(req).await
+    /// Abort an on-going multipart upload.
+    ///
+    /// All parts uploaded will be freed by S3 after this call.
+    pub async fn s3_abort_multipart_upload(
+        &self,
+        path: &str,
+        upload_id: &str,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+
+        let url = format!(
+            "{}/{}?uploadId={}",
+            self.endpoint,
+            percent_encode_path(&p),
+            upload_id
+        );
+
+        let req = Request::delete(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.send(req).await
     }
 
     async fn s3_delete_objects(&self, paths: Vec<String>) -> Result<Response<IncomingAsyncBody>> {
@@ -1576,21 +2584,215 @@ impl S3Backend {
         // Set content-md5 as required by API.
         let req = req.header("CONTENT-MD5", format_content_md5(content.as_bytes()));
 
-        let mut req = req
+        let req = req
             .body(AsyncBody::Bytes(Bytes::from(content)))
             .map_err(new_request_build_error)?;
 
-        self.signer.sign(&mut req).map_err(new_request_sign_error)?;
+        self.send(req).await
+    }
+}
 
-        self.client.send_async(req).await
+/// TAGGING_ENCODE_SET is the encode set for the `x-amz-tagging` header.
+///
+/// Tags are encoded as url query parameters, so all characters except
+/// `A-Z a-z 0-9 - _ . ~` will be encoded.
+static TAGGING_ENCODE_SET: AsciiSet = NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+/// Format tags into the value of `x-amz-tagging` header.
+///
+/// Returns `None` if there are no tags to set.
+pub(super) fn format_tagging(tags: &HashMap<String, String>) -> Result<Option<String>> {
+    if tags.is_empty() {
+        return Ok(None);
+    }
+
+    validate_tags(tags)?;
+
+    let mut tags: Vec<_> = tags.iter().collect();
+    tags.sort();
+
+    let mut s = String::new();
+    for (k, v) in tags {
+        if !s.is_empty() {
+            s.push('&');
+        }
+        write!(
+            s,
+            "{}={}",
+            utf8_percent_encode(k, &TAGGING_ENCODE_SET),
+            utf8_percent_encode(v, &TAGGING_ENCODE_SET)
+        )
+        .expect("write into string must succeed");
+    }
+
+    Ok(Some(s))
+}
+
+/// Validate tags against s3's restrictions:
+///
+/// - At most 10 tags per object.
+/// - Keys must be 1 to 128 characters, values must be at most 256 characters.
+/// - Only letters, numbers, spaces and `+ - = . _ : / @` are allowed.
+///
+/// ref: <https://docs.aws.amazon.com/AmazonS3/latest/userguide/object-tagging.html>
+fn validate_tags(tags: &HashMap<String, String>) -> Result<()> {
+    if tags.len() > 10 {
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            "s3 services only allow up to 10 tags per object",
+        )
+        .with_context("length", tags.len().to_string()));
+    }
+
+    fn is_valid(s: &str) -> bool {
+        s.chars().all(|c| {
+            c.is_alphanumeric()
+                || c.is_whitespace()
+                || matches!(c, '+' | '-' | '=' | '.' | '_' | ':' | '/' | '@')
+        })
+    }
+
+    let mut tags: Vec<_> = tags.iter().collect();
+    tags.sort();
+
+    for (k, v) in tags {
+        let key_len = k.chars().count();
+        if key_len == 0 || key_len > 128 || !is_valid(k) {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "s3 services only allow tag key with 1 to 128 letters, numbers, spaces and `+ - = . _ : / @`",
+            )
+            .with_context("key", k));
+        }
+        if v.chars().count() > 256 || !is_valid(v) {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "s3 services only allow tag value with up to 256 letters, numbers, spaces and `+ - = . _ : / @`",
+            )
+            .with_context("key", k)
+            .with_context("value", v));
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse storage class from `x-amz-storage-class` header.
+///
+/// S3 doesn't return this header for objects in `STANDARD` storage class.
+fn parse_storage_class(headers: &HeaderMap) -> Result<String> {
+    match headers.get(constants::X_AMZ_STORAGE_CLASS) {
+        None => Ok("STANDARD".to_string()),
+        Some(v) => v.to_str().map(|v| v.to_string()).map_err(|e| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value is not valid utf-8 string",
+            )
+            .with_operation("s3::parse_storage_class")
+            .set_source(e)
+        }),
+    }
+}
+
+/// Parse restore status from `x-amz-restore` header.
+///
+/// The header is in format like:
+///
+/// - `ongoing-request="true"`
+/// - `ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT"`
+fn parse_restore_status(headers: &HeaderMap) -> Result<Option<RestoreStatus>> {
+    let v = match headers.get(constants::X_AMZ_RESTORE) {
+        None => return Ok(None),
+        Some(v) => v.to_str().map_err(|e| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value is not valid utf-8 string",
+            )
+            .with_operation("s3::parse_restore_status")
+            .set_source(e)
+        })?,
+    };
+
+    let invalid = || {
+        Error::new(ErrorKind::Unexpected, "x-amz-restore header is invalid")
+            .with_operation("s3::parse_restore_status")
+            .with_context("value", v)
+    };
+
+    let mut ongoing = None;
+    let mut expiry = None;
+    // Expiry date contains `,`, so we split by `"` instead.
+    let mut rest = v.trim();
+    while !rest.is_empty() {
+        let (key, value, next) = rest
+            .split_once("=\"")
+            .and_then(|(key, rest)| {
+                rest.split_once('"')
+                    .map(|(value, next)| (key.trim(), value, next))
+            })
+            .ok_or_else(invalid)?;
+        match key {
+            "ongoing-request" => ongoing = Some(value == "true"),
+            "expiry-date" => {
+                expiry = Some(
+                    OffsetDateTime::parse(value, &Rfc2822).map_err(|e| invalid().set_source(e))?,
+                )
+            }
+            _ => {}
+        }
+        rest = next.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+    }
+
+    match ongoing {
+        Some(ongoing) => Ok(Some(RestoreStatus::new(ongoing, expiry))),
+        None => Err(invalid()),
     }
 }
 
+/// Request of RestoreObject.
+#[derive(Default, Debug, Serialize)]
+#[serde(default, rename = "RestoreRequest", rename_all = "PascalCase")]
+struct RestoreRequest {
+    days: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    glacier_job_parameters: Option<GlacierJobParameters>,
+}
+
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct GlacierJobParameters {
+    tier: String,
+}
+
+/// Result of GetObjectTagging and request of PutObjectTagging.
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct Tagging {
+    tag_set: TagSet,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct TagSet {
+    tag: Vec<Tag>,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct Tag {
+    key: String,
+    value: String,
+}
+
 /// Result of CreateMultipartUpload
 #[derive(Default, Debug, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
-struct InitiateMultipartUploadResult {
-    upload_id: String,
+pub(super) struct InitiateMultipartUploadResult {
+    pub upload_id: String,
 }
 
 /// Request of CompleteMultipartUploadRequest
@@ -1635,6 +2837,39 @@ pub struct CompleteMultipartUploadRequestPart {
     /// ref: <https://github.com/tafia/quick-xml/issues/362>
     #[serde(rename = "ETag")]
     pub etag: String,
+    #[serde(rename = "ChecksumCRC32C", skip_serializing_if = "Option::is_none")]
+    pub checksum_crc32c: Option<String>,
+    #[serde(rename = "ChecksumSHA256", skip_serializing_if = "Option::is_none")]
+    pub checksum_sha256: Option<String>,
+}
+
+impl CompleteMultipartUploadRequestPart {
+    /// Create a new part with its checksum calculated by given algorithm.
+    pub(super) fn new(
+        part_number: usize,
+        etag: String,
+        checksum: Option<(ChecksumAlgorithm, String)>,
+    ) -> Self {
+        let mut part = Self {
+            part_number,
+            etag,
+            ..Default::default()
+        };
+        match checksum {
+            Some((ChecksumAlgorithm::Crc32c, v)) => part.checksum_crc32c = Some(v),
+            Some((ChecksumAlgorithm::Sha256, v)) => part.checksum_sha256 = Some(v),
+            None => {}
+        }
+        part
+    }
+}
+
+/// Result of CompleteMultipartUpload
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+pub(super) struct CompleteMultipartUploadResult {
+    #[serde(rename = "ETag")]
+    pub etag: String,
 }
 
 /// Request of DeleteObjects.
@@ -1679,6 +2914,7 @@ mod tests {
     use bytes::Buf;
     use bytes::Bytes;
 
+    use super::super::writer::encode_trailer_checksum_body;
     use super::*;
 
     #[test]
@@ -1744,9 +2980,74 @@ mod tests {
         }
     }
 
-    /// This example is from https://docs.aws.amazon.com/AmazonS3/latest/API/API_CreateMultipartUpload.html#API_CreateMultipartUpload_Examples
     #[test]
-    fn test_deserialize_initiate_multipart_upload_result() {
+    fn test_build_endpoint_for_compatible_services() {
+        let mut b = S3Builder::default();
+        b.bucket("test").endpoint("http://127.0.0.1:9000/");
+        assert_eq!(b.build_endpoint("us-east-1"), "http://127.0.0.1:9000/test");
+
+        let mut b = S3Builder::default();
+        b.bucket("test")
+            .endpoint("https://rgw.example.com")
+            .enable_virtual_host_style();
+        assert_eq!(b.build_endpoint("default"), "https://test.rgw.example.com");
+    }
+
+    #[test]
+    fn test_build_with_empty_bucket() {
+        let mut b = S3Builder::default();
+        b.endpoint("http://127.0.0.1:9000");
+
+        let err = b.build().expect_err("build with empty bucket must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[test]
+    fn test_validate_endpoint() {
+        let cases = vec![
+            // (endpoint, bucket, virtual host style, valid)
+            ("http://127.0.0.1:9000", "test", false, true),
+            ("http://127.0.0.1:9000", "test", true, false),
+            ("http://[::1]:9000", "test", true, false),
+            ("http://localhost:9000", "test", true, false),
+            ("https://s3.amazonaws.com", "test", true, true),
+            ("https://s3.amazonaws.com", "Test_Bucket", true, false),
+            ("https://s3.amazonaws.com", "Test_Bucket", false, true),
+            ("http://exa mple.com", "test", false, false),
+            ("httpx://example.com", "test", false, false),
+        ];
+
+        for (endpoint, bucket, virtual_host_style, valid) in cases {
+            let mut b = S3Builder::default();
+            b.bucket(bucket).endpoint(endpoint);
+            if virtual_host_style {
+                b.enable_virtual_host_style();
+            }
+
+            let res = b.validate_endpoint();
+            assert_eq!(res.is_ok(), valid, "endpoint {endpoint} bucket {bucket}");
+            if let Err(err) = res {
+                assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_with_ip_endpoint_and_virtual_host_style() {
+        let mut b = S3Builder::default();
+        b.bucket("test")
+            .endpoint("http://127.0.0.1:9000")
+            .region("us-east-1")
+            .disable_config_load()
+            .enable_virtual_host_style();
+
+        let err = b.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    /// This example is from https://docs.aws.amazon.com/AmazonS3/latest/API/API_CreateMultipartUpload.html#API_CreateMultipartUpload_Examples
+    #[test]
+    fn test_deserialize_initiate_multipart_upload_result() {
         let bs = Bytes::from(
             r#"<?xml version="1.0" encoding="UTF-8"?>
             <InitiateMultipartUploadResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
@@ -1765,6 +3066,25 @@ mod tests {
         )
     }
 
+    /// This example is from https://docs.aws.amazon.com/AmazonS3/latest/API/API_CompleteMultipartUpload.html#API_CompleteMultipartUpload_Examples
+    #[test]
+    fn test_deserialize_complete_multipart_upload_result() {
+        let bs = Bytes::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <CompleteMultipartUploadResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+              <Location>http://Example-Bucket.s3.us-east-1.amazonaws.com/Example-Object</Location>
+              <Bucket>Example-Bucket</Bucket>
+              <Key>Example-Object</Key>
+              <ETag>"3858f62230ac3c915f300c664312c11f-9"</ETag>
+            </CompleteMultipartUploadResult>"#,
+        );
+
+        let out: CompleteMultipartUploadResult =
+            quick_xml::de::from_reader(bs.reader()).expect("must success");
+
+        assert_eq!(out.etag, "\"3858f62230ac3c915f300c664312c11f-9\"")
+    }
+
     /// This example is from https://docs.aws.amazon.com/AmazonS3/latest/API/API_CompleteMultipartUpload.html#API_CompleteMultipartUpload_Examples
     #[test]
     fn test_serialize_complete_multipart_upload_request() {
@@ -1773,14 +3093,17 @@ mod tests {
                 CompleteMultipartUploadRequestPart {
                     part_number: 1,
                     etag: "\"a54357aff0632cce46d942af68356b38\"".to_string(),
+                    ..Default::default()
                 },
                 CompleteMultipartUploadRequestPart {
                     part_number: 2,
                     etag: "\"0c78aef83f66abc1fa1e8477f296d394\"".to_string(),
+                    ..Default::default()
                 },
                 CompleteMultipartUploadRequestPart {
                     part_number: 3,
                     etag: "\"acbd18db4cc2f85cedef654fccc4a4d8\"".to_string(),
+                    ..Default::default()
                 },
             ],
         };
@@ -1868,4 +3191,1904 @@ mod tests {
         assert_eq!(out.error[0].code, "AccessDenied");
         assert_eq!(out.error[0].message, "Access Denied");
     }
+
+    #[tokio::test]
+    async fn test_check_permission_denied() {
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/test"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+                <Error>
+                  <Code>AccessDenied</Code>
+                  <Message>Access Denied</Message>
+                  <RequestId>4442587FB7D0A2F9</RequestId>
+                </Error>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+
+        let err = op.check().await.expect_err("check must fail");
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        assert!(err.to_string().contains("Access Denied"));
+    }
+
+    #[tokio::test]
+    async fn test_stat_many() {
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::matchers::query_param;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/test"))
+            .and(query_param("prefix", "dir/"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                  <IsTruncated>false</IsTruncated>
+                  <Contents>
+                    <Key>dir/a</Key>
+                    <LastModified>2016-04-30T23:51:29.000Z</LastModified>
+                    <ETag>"d41d8cd98f00b204e9800998ecf8427e"</ETag>
+                    <Size>1</Size>
+                  </Contents>
+                  <Contents>
+                    <Key>dir/b</Key>
+                    <LastModified>2016-04-30T23:51:29.000Z</LastModified>
+                    <ETag>"d41d8cd98f00b204e9800998ecf8427e"</ETag>
+                    <Size>2</Size>
+                  </Contents>
+                </ListBucketResult>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path("/test/other"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path("/test/dir/not_exist"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+
+        let metas = op
+            .stat_many(vec![
+                "dir/a".to_string(),
+                "dir/b".to_string(),
+                "dir/not_exist".to_string(),
+                "other".to_string(),
+            ])
+            .await
+            .expect("stat many must succeed");
+
+        assert_eq!(metas.len(), 3);
+        assert_eq!(metas["dir/a"].0.content_length(), 1);
+        assert_eq!(metas["dir/a"].1, StatSource::List);
+        assert_eq!(metas["dir/b"].0.content_length(), 2);
+        assert_eq!(metas["dir/b"].1, StatSource::List);
+        assert_eq!(metas["other"].1, StatSource::Stat);
+    }
+
+    #[tokio::test]
+    async fn test_path_style_request_is_signed() {
+        use wiremock::matchers::header_exists;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/test/path/to/file"))
+            .and(header_exists("authorization"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-length", "13"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            // Trailing slash should not lead to `//test` in path.
+            .endpoint(&format!("{}/", mock_server.uri()))
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+
+        let meta = op.stat("path/to/file").await.expect("stat must succeed");
+        assert_eq!(meta.content_length(), 13);
+    }
+
+    fn sse_test_builder() -> S3Builder {
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint("http://127.0.0.1:9000")
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        builder
+    }
+
+    #[test]
+    fn test_validate_server_side_encryption() {
+        let mut b = sse_test_builder();
+        b.server_side_encryption_with_customer_key("AES256", &[0; 16]);
+        let err = b.build().expect_err("short customer key must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let mut b = sse_test_builder();
+        b.server_side_encryption_with_customer_key("AES256", &[0; 32]);
+        b.server_side_encryption_customer_key_md5("invalid");
+        let err = b.build().expect_err("mismatched md5 must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let mut b = sse_test_builder();
+        b.server_side_encryption_with_customer_key("AES256", &[0; 32]);
+        b.server_side_encryption("aws:kms");
+        let err = b.build().expect_err("mixed sse must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let mut b = sse_test_builder();
+        b.server_side_encryption("aws:unknown");
+        let err = b.build().expect_err("unknown sse must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let mut b = sse_test_builder();
+        b.server_side_encryption_with_customer_managed_kms_key("kms_key");
+        assert!(b.build().is_ok());
+    }
+
+    #[test]
+    fn test_sse_kms_headers() {
+        let mut b = sse_test_builder();
+        b.server_side_encryption_with_customer_managed_kms_key("kms_key");
+        let backend = b.build().expect("build must succeed");
+
+        let req = backend
+            .s3_put_object_request("test", None, None, None, AsyncBody::Empty)
+            .expect("request must be built");
+        assert_eq!(
+            req.headers()[constants::X_AMZ_SERVER_SIDE_ENCRYPTION],
+            "aws:kms"
+        );
+        assert_eq!(
+            req.headers()[constants::X_AMZ_SERVER_SIDE_ENCRYPTION_AWS_KMS_KEY_ID],
+            "kms_key"
+        );
+
+        // Upload part doesn't accept SSE-KMS headers.
+        let req = backend
+            .s3_upload_part_request("test", "upload_id", 1, None, AsyncBody::Empty)
+            .expect("request must be built");
+        assert!(!req
+            .headers()
+            .contains_key(constants::X_AMZ_SERVER_SIDE_ENCRYPTION));
+        assert!(!req
+            .headers()
+            .contains_key(constants::X_AMZ_SERVER_SIDE_ENCRYPTION_AWS_KMS_KEY_ID));
+
+        // Read doesn't need SSE-KMS headers.
+        let req = backend
+            .s3_get_object_request("test", BytesRange::default())
+            .expect("request must be built");
+        assert!(!req
+            .headers()
+            .contains_key(constants::X_AMZ_SERVER_SIDE_ENCRYPTION));
+    }
+
+    #[test]
+    fn test_sse_c_headers() {
+        let key = [1; 32];
+        let key_b64 = BASE64_STANDARD.encode(key);
+        let md5_b64 = BASE64_STANDARD.encode(Md5::digest(key).as_slice());
+
+        let mut b = sse_test_builder();
+        b.server_side_encryption_with_customer_key("AES256", &key);
+        let backend = b.build().expect("build must succeed");
+
+        let reqs = vec![
+            backend
+                .s3_put_object_request("test", None, None, None, AsyncBody::Empty)
+                .unwrap(),
+            backend
+                .s3_upload_part_request("test", "upload_id", 1, None, AsyncBody::Empty)
+                .unwrap(),
+            backend
+                .s3_get_object_request("test", BytesRange::default())
+                .unwrap(),
+            backend.s3_head_object_request("test").unwrap(),
+        ];
+        for req in reqs {
+            let headers = req.headers();
+            assert_eq!(
+                headers[constants::X_AMZ_SERVER_SIDE_ENCRYPTION_CUSTOMER_ALGORITHM],
+                "AES256"
+            );
+            assert_eq!(
+                headers[constants::X_AMZ_SERVER_SIDE_ENCRYPTION_CUSTOMER_KEY],
+                key_b64.as_str()
+            );
+            assert_eq!(
+                headers[constants::X_AMZ_SERVER_SIDE_ENCRYPTION_CUSTOMER_KEY_MD5],
+                md5_b64.as_str()
+            );
+        }
+
+        // Presigned request must return the headers to send.
+        let rp = backend
+            .presign(
+                "test",
+                OpPresign::new(OpRead::new(), time::Duration::hours(1)),
+            )
+            .expect("presign must succeed");
+        let req = rp.into_presigned_request();
+        assert_eq!(
+            req.header()[constants::X_AMZ_SERVER_SIDE_ENCRYPTION_CUSTOMER_KEY],
+            key_b64.as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_with_if_match() {
+        use wiremock::matchers::header;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/test/file"))
+            .and(header("if-match", "\"current\""))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/test/file"))
+            .and(header("if-match", "\"stale\""))
+            .respond_with(ResponseTemplate::new(412).set_body_string(
+                r#"<Error><Code>PreconditionFailed</Code><Message>At least one of the pre-conditions you specified did not hold</Message></Error>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+
+        op.write_with(
+            "file",
+            OpWrite::new().with_if_match("\"current\""),
+            "Hello, World!",
+        )
+        .await
+        .expect("write with matched etag must succeed");
+
+        let err = op
+            .write_with(
+                "file",
+                OpWrite::new().with_if_match("\"stale\""),
+                "Hello, World!",
+            )
+            .await
+            .expect_err("write with stale etag must fail");
+        assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
+    }
+
+    #[tokio::test]
+    async fn test_write_returns_etag_and_version() {
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/test/file"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"fba9dede5f27731c9771645a39863328\"")
+                    .insert_header("x-amz-version-id", "version-id"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/test/unversioned"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+
+        let rp = op
+            .write_with("file", OpWrite::new(), "Hello, World!")
+            .await
+            .expect("write must succeed");
+        assert_eq!(rp.etag(), Some("\"fba9dede5f27731c9771645a39863328\""));
+        assert_eq!(rp.version(), Some("version-id"));
+
+        let rp = op
+            .write_with("unversioned", OpWrite::new(), "Hello, World!")
+            .await
+            .expect("write must succeed");
+        assert_eq!(rp.etag(), None);
+        assert_eq!(rp.version(), None);
+    }
+
+    #[test]
+    fn test_http_headers_from_map() {
+        let mut map = HashMap::new();
+        map.insert("bucket".to_string(), "test".to_string());
+        map.insert("enable_request_payer".to_string(), "true".to_string());
+        map.insert(
+            "http_headers".to_string(),
+            "X-Proxy-Token: abc, x-tenant:t1".to_string(),
+        );
+
+        let b = S3Builder::from_map(map);
+        assert!(b.enable_request_payer);
+        assert_eq!(b.http_headers["x-proxy-token"], "abc");
+        assert_eq!(b.http_headers["x-tenant"], "t1");
+    }
+
+    #[test]
+    fn test_assume_role_from_map() {
+        let mut map = HashMap::new();
+        map.insert("bucket".to_string(), "test".to_string());
+        map.insert(
+            "role_arn".to_string(),
+            "arn:aws:iam::123456789012:role/test".to_string(),
+        );
+        map.insert("external_id".to_string(), "external".to_string());
+
+        let b = S3Builder::from_map(map);
+        assert_eq!(
+            b.role_arn.as_deref(),
+            Some("arn:aws:iam::123456789012:role/test")
+        );
+        assert_eq!(b.external_id.as_deref(), Some("external"));
+    }
+
+    #[test]
+    fn test_external_id_without_role_arn() {
+        let mut b = sse_test_builder();
+        b.external_id("external");
+        let err = b
+            .build()
+            .expect_err("external_id without role_arn must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        b.role_arn("arn:aws:iam::123456789012:role/test");
+        b.build().expect("external_id with role_arn must succeed");
+    }
+
+    #[test]
+    fn test_build_sts_endpoint() {
+        let cfg = AwsConfigLoader::default();
+        let mut b = S3Builder::default();
+
+        assert_eq!(
+            b.build_sts_endpoint(&cfg, "us-west-2"),
+            (
+                "https://sts.amazonaws.com".to_string(),
+                "us-east-1".to_string()
+            )
+        );
+        assert_eq!(
+            b.build_sts_endpoint(&cfg, "cn-north-1"),
+            (
+                "https://sts.cn-north-1.amazonaws.com.cn".to_string(),
+                "cn-north-1".to_string()
+            )
+        );
+
+        b.sts_endpoint("https://sts.us-west-2.amazonaws.com");
+        assert_eq!(
+            b.build_sts_endpoint(&cfg, "us-west-2"),
+            (
+                "https://sts.us-west-2.amazonaws.com".to_string(),
+                "us-west-2".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_http_headers_invalid() {
+        let mut b = sse_test_builder();
+        b.http_header("Authorization", "token");
+        let err = b.build().expect_err("managed header must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let mut b = sse_test_builder();
+        b.http_header("x-proxy-token", "invalid\nvalue");
+        let err = b.build().expect_err("invalid header value must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[test]
+    fn test_request_payer_is_signed() {
+        let mut b = sse_test_builder();
+        b.enable_request_payer().http_header("x-proxy-token", "abc");
+        let backend = b.build().expect("build must succeed");
+
+        let mut req = backend
+            .s3_get_object_request("test", BytesRange::default())
+            .expect("request must be built");
+        backend.sign(&mut req).expect("sign must succeed");
+
+        let headers = req.headers();
+        assert_eq!(headers[constants::X_AMZ_REQUEST_PAYER], "requester");
+        assert_eq!(headers["x-proxy-token"], "abc");
+
+        let auth = headers[http::header::AUTHORIZATION]
+            .to_str()
+            .expect("authorization must be valid");
+        assert!(auth.starts_with("AWS4-HMAC-SHA256 Credential=access_key_id/"));
+        let signed_headers = auth
+            .split(", ")
+            .find_map(|v| v.strip_prefix("SignedHeaders="))
+            .expect("signed headers must exist");
+        assert!(signed_headers
+            .split(';')
+            .any(|v| v == "x-amz-request-payer"));
+        assert!(signed_headers.split(';').any(|v| v == "x-proxy-token"));
+
+        // Presigned request must carry the headers too.
+        let rp = backend
+            .presign(
+                "test",
+                OpPresign::new(OpRead::new(), time::Duration::hours(1)),
+            )
+            .expect("presign must succeed");
+        let req = rp.into_presigned_request();
+        assert_eq!(req.header()[constants::X_AMZ_REQUEST_PAYER], "requester");
+    }
+
+    #[tokio::test]
+    async fn test_request_payer() {
+        use futures::TryStreamExt;
+        use wiremock::matchers::header;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        for m in ["GET", "HEAD", "PUT"] {
+            Mock::given(method(m))
+                .and(path("/test/file"))
+                .and(header(constants::X_AMZ_REQUEST_PAYER, "requester"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("content-length", "13")
+                        .set_body_string("Hello, World!"),
+                )
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path("/test"))
+            .and(header(constants::X_AMZ_REQUEST_PAYER, "requester"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+                  <IsTruncated>false</IsTruncated>
+                  <Contents>
+                    <Key>file</Key>
+                    <LastModified>2016-04-30T23:51:29.000Z</LastModified>
+                    <ETag>"d41d8cd98f00b204e9800998ecf8427e"</ETag>
+                    <Size>13</Size>
+                  </Contents>
+                </ListBucketResult>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load()
+            .enable_request_payer();
+        let op = Operator::new(builder).unwrap().finish();
+
+        op.write("file", "Hello, World!")
+            .await
+            .expect("write must succeed");
+        op.stat("file").await.expect("stat must succeed");
+        op.read("file").await.expect("read must succeed");
+        let entries: Vec<_> = op
+            .list("/")
+            .await
+            .expect("list must succeed")
+            .try_collect()
+            .await
+            .expect("list must succeed");
+        assert_eq!(entries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sliced_reader_sends_range() {
+        use futures::AsyncReadExt;
+        use wiremock::matchers::header;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/test/file"))
+            .and(header("range", "bytes=0-3"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("content-length", "4")
+                    .insert_header("content-range", "bytes 0-3/13")
+                    .set_body_string("Hell"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+
+        let mut r = op
+            .sliced_reader("file", 4)
+            .await
+            .expect("reader must be created");
+        let mut bs = vec![];
+        r.read_to_end(&mut bs).await.expect("read must succeed");
+        assert_eq!(bs, b"Hell");
+    }
+
+    #[test]
+    fn test_format_tagging() {
+        assert_eq!(format_tagging(&HashMap::new()).unwrap(), None);
+
+        let tags = HashMap::from([
+            ("project".to_string(), "opendal".to_string()),
+            ("owner name".to_string(), "a+b=c/d@e:f".to_string()),
+            ("中文".to_string(), "".to_string()),
+        ]);
+        assert_eq!(
+            format_tagging(&tags).unwrap().as_deref(),
+            Some("owner%20name=a%2Bb%3Dc%2Fd%40e%3Af&project=opendal&%E4%B8%AD%E6%96%87=")
+        );
+    }
+
+    #[test]
+    fn test_format_tagging_invalid() {
+        let cases = vec![
+            (
+                "invalid char in key",
+                HashMap::from([("a&b".to_string(), "v".to_string())]),
+            ),
+            (
+                "invalid char in value",
+                HashMap::from([("k".to_string(), "v?".to_string())]),
+            ),
+            (
+                "empty key",
+                HashMap::from([("".to_string(), "v".to_string())]),
+            ),
+            (
+                "key too long",
+                HashMap::from([("k".repeat(129), "v".to_string())]),
+            ),
+            (
+                "value too long",
+                HashMap::from([("k".to_string(), "v".repeat(257))]),
+            ),
+            (
+                "too many tags",
+                (0..11)
+                    .map(|i| (format!("k{i}"), "v".to_string()))
+                    .collect(),
+            ),
+        ];
+
+        for (name, tags) in cases {
+            let err = format_tagging(&tags).expect_err(name);
+            assert_eq!(err.kind(), ErrorKind::Unsupported, "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_and_stat_with_tags() {
+        use wiremock::matchers::header;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::matchers::query_param;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/test/file"))
+            .and(header("x-amz-tagging", "env=prod&team=data%20infra"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path("/test/file"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-length", "13"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/test/file"))
+            .and(query_param("tagging", ""))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<Tagging xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <TagSet>
+    <Tag><Key>env</Key><Value>prod</Value></Tag>
+    <Tag><Key>team</Key><Value>data infra</Value></Tag>
+  </TagSet>
+</Tagging>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+
+        let tags = HashMap::from([
+            ("env".to_string(), "prod".to_string()),
+            ("team".to_string(), "data infra".to_string()),
+        ]);
+        op.write_with(
+            "file",
+            OpWrite::new().with_tags(tags.clone()),
+            "Hello, World!",
+        )
+        .await
+        .expect("write with tags must succeed");
+
+        // Plain stat must not fetch tags.
+        op.stat("file").await.expect("stat must succeed");
+
+        let meta = op
+            .stat_with("file", OpStat::new().with_tags(true))
+            .await
+            .expect("stat with tags must succeed");
+        assert_eq!(meta.tags(), Some(&tags));
+    }
+
+    #[tokio::test]
+    async fn test_get_and_put_tags() {
+        use wiremock::matchers::body_string;
+        use wiremock::matchers::header;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::matchers::query_param;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let content = "<Tagging><TagSet><Tag><Key>env</Key><Value>prod</Value></Tag><Tag><Key>team</Key><Value>data infra</Value></Tag></TagSet></Tagging>";
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/test/file"))
+            .and(query_param("tagging", ""))
+            .and(header(
+                "content-md5",
+                format_content_md5(content.as_bytes()).as_str(),
+            ))
+            .and(body_string(content))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/test/file"))
+            .and(query_param("tagging", ""))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<Tagging xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <TagSet>
+    <Tag><Key>env</Key><Value>prod</Value></Tag>
+    <Tag><Key>team</Key><Value>data infra</Value></Tag>
+  </TagSet>
+</Tagging>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+
+        let tags = HashMap::from([
+            ("env".to_string(), "prod".to_string()),
+            ("team".to_string(), "data infra".to_string()),
+        ]);
+        op.put_tags("file", tags.clone())
+            .await
+            .expect("put tags must succeed");
+        assert_eq!(
+            op.get_tags("file").await.expect("get tags must succeed"),
+            tags
+        );
+
+        // Invalid tags must be rejected before sending request.
+        let err = op
+            .put_tags(
+                "file",
+                HashMap::from([("env".to_string(), "prod!".to_string())]),
+            )
+            .await
+            .expect_err("put invalid tags must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+
+    #[tokio::test]
+    async fn test_copy_with_customer_key() {
+        use wiremock::matchers::header;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let key = [1u8; 32];
+        let encoded_key = BASE64_STANDARD.encode(key);
+        let encoded_key_md5 = BASE64_STANDARD.encode(Md5::digest(key).as_slice());
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/test/to"))
+            .and(header("x-amz-copy-source", "test/from"))
+            .and(header(
+                "x-amz-server-side-encryption-customer-algorithm",
+                "AES256",
+            ))
+            .and(header(
+                "x-amz-copy-source-server-side-encryption-customer-algorithm",
+                "AES256",
+            ))
+            .and(header(
+                "x-amz-copy-source-server-side-encryption-customer-key",
+                encoded_key.as_str(),
+            ))
+            .and(header(
+                "x-amz-copy-source-server-side-encryption-customer-key-md5",
+                encoded_key_md5.as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<CopyObjectResult>
+  <ETag>"9b2cf535f27731c974343645a3985328"</ETag>
+  <LastModified>2009-10-28T22:32:00.000Z</LastModified>
+</CopyObjectResult>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .server_side_encryption_with_customer_key("AES256", &key)
+            .disable_config_load();
+        let backend = builder.build().expect("build must succeed");
+
+        backend
+            .copy("from", "to", OpCopy::new())
+            .await
+            .expect("copy must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_copy_ok_with_error() {
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/test/to"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>AccessDenied</Code>
+  <Message>Access Denied</Message>
+  <RequestId>656c76696e6727732072657175657374</RequestId>
+</Error>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let backend = builder.build().expect("build must succeed");
+
+        let err = backend
+            .copy("from", "to", OpCopy::new())
+            .await
+            .expect_err("copy with error body must fail");
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn test_list_objects_v1() {
+        use futures::TryStreamExt;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::matchers::query_param;
+        use wiremock::Match;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::Request;
+        use wiremock::ResponseTemplate;
+
+        /// Match ListObjects (v1) requests with given marker.
+        struct MarkerMatcher(Option<&'static str>);
+
+        impl Match for MarkerMatcher {
+            fn matches(&self, req: &Request) -> bool {
+                let query: HashMap<_, _> = req.url.query_pairs().into_owned().collect();
+                !query.contains_key("list-type")
+                    && query.get("marker").map(|v| v.as_str()) == self.0
+            }
+        }
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        // NextMarker is omitted, the last key should be used instead.
+        Mock::given(method("GET"))
+            .and(path("/test"))
+            .and(query_param("prefix", "dir/"))
+            .and(query_param("delimiter", "/"))
+            .and(MarkerMatcher(None))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<ListBucketResult>
+  <IsTruncated>true</IsTruncated>
+  <Contents>
+    <Key>dir/a</Key>
+    <LastModified>2016-04-30T23:51:29.000Z</LastModified>
+    <ETag>"d41d8cd98f00b204e9800998ecf8427e"</ETag>
+    <Size>1</Size>
+  </Contents>
+  <Contents>
+    <Key>dir/b</Key>
+    <LastModified>2016-04-30T23:51:29.000Z</LastModified>
+    <ETag>"d41d8cd98f00b204e9800998ecf8427e"</ETag>
+    <Size>2</Size>
+  </Contents>
+</ListBucketResult>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/test"))
+            .and(MarkerMatcher(Some("dir/b")))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<ListBucketResult>
+  <IsTruncated>false</IsTruncated>
+  <Contents>
+    <Key>dir/c</Key>
+    <LastModified>2016-04-30T23:51:29.000Z</LastModified>
+    <ETag>"d41d8cd98f00b204e9800998ecf8427e"</ETag>
+    <Size>3</Size>
+  </Contents>
+  <CommonPrefixes>
+    <Prefix>dir/sub/</Prefix>
+  </CommonPrefixes>
+</ListBucketResult>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load()
+            .enable_list_objects_v1();
+        let op = Operator::new(builder).unwrap().finish();
+
+        let mut paths = op
+            .list("dir/")
+            .await
+            .expect("list must succeed")
+            .map_ok(|e| e.path().to_string())
+            .try_collect::<Vec<_>>()
+            .await
+            .expect("list must succeed");
+        paths.sort();
+        assert_eq!(paths, vec!["dir/a", "dir/b", "dir/c", "dir/sub/"]);
+    }
+
+    #[tokio::test]
+    async fn test_request_signer() {
+        use wiremock::matchers::header;
+        use wiremock::matchers::header_exists;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/test/file"))
+            .and(header_exists("authorization"))
+            .and(header("x-gateway-token", "HEAD /test/file"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-length", "13"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = HttpClient::builder()
+            .request_signer(|method, uri, headers| {
+                // Signer must be called after native signing.
+                assert!(headers.contains_key(http::header::AUTHORIZATION));
+
+                let mut hm = HeaderMap::new();
+                hm.insert(
+                    "x-gateway-token",
+                    HeaderValue::from_str(&format!("{method} {}", uri.path())).unwrap(),
+                );
+                Ok(hm)
+            })
+            .build()
+            .unwrap();
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load()
+            .http_client(client);
+        let op = Operator::new(builder).unwrap().finish();
+
+        let meta = op.stat("file").await.expect("stat must succeed");
+        assert_eq!(meta.content_length(), 13);
+    }
+
+    #[tokio::test]
+    async fn test_retry_request_time_too_skewed() {
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let server_time = OffsetDateTime::now_utc() + time::Duration::hours(1);
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/test/skewed"))
+            .respond_with(
+                ResponseTemplate::new(403)
+                    .insert_header("date", server_time.format(&Rfc2822).unwrap().as_str())
+                    .set_body_string(
+                        r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error><Code>RequestTimeTooSkewed</Code><Message>The difference between the request time and the current time is too large.</Message></Error>"#,
+                    ),
+            )
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/test/skewed"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/test/denied"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error><Code>AccessDenied</Code><Message>Access Denied</Message></Error>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let backend = builder.build().expect("build must succeed");
+
+        let (_, r) = backend
+            .read("skewed", OpRead::new())
+            .await
+            .expect("read must succeed after retry");
+        let bs = r.bytes().await.expect("read body must succeed");
+        assert_eq!(bs.as_ref(), b"Hello, World!");
+
+        // The offset is learned and kept for following requests.
+        let offset = backend.clock_skew.offset();
+        assert!(offset > time::Duration::minutes(59) && offset <= time::Duration::hours(1));
+
+        // Genuine auth failures must not be retried.
+        let err = backend
+            .read("denied", OpRead::new())
+            .await
+            .err()
+            .expect("read must fail");
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn test_credential_loaded_once() {
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::atomic::Ordering;
+
+        use reqsign::credential::Credential;
+
+        #[derive(Debug, Default)]
+        struct CountLoad(Arc<AtomicUsize>);
+
+        impl AwsCredentialLoad for CountLoad {
+            fn load_credential(&self) -> anyhow::Result<Option<Credential>> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(Some(
+                    Credential::new("access_key_id", "secret_access_key")
+                        .with_expires_in(OffsetDateTime::now_utc() + time::Duration::hours(1)),
+                ))
+            }
+        }
+
+        let loads = Arc::new(AtomicUsize::new(0));
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint("http://127.0.0.1:9000")
+            .region("us-east-1")
+            .disable_config_load()
+            .customed_credential_load(CountLoad(loads.clone()));
+        let backend = builder.build().expect("build must succeed");
+
+        // Signed by AwsV4Signer.
+        let mut req = Request::get("http://127.0.0.1:9000/test/file")
+            .body(AsyncBody::Empty)
+            .expect("request must be valid");
+        backend.sign(&mut req).expect("sign must succeed");
+        assert!(req.headers().contains_key(http::header::AUTHORIZATION));
+
+        // Signed by ClockSkew.
+        let mut req = Request::put("http://127.0.0.1:9000/test/file")
+            .header(
+                constants::X_AMZ_CONTENT_SHA_256,
+                constants::STREAMING_UNSIGNED_PAYLOAD_TRAILER,
+            )
+            .body(AsyncBody::Empty)
+            .expect("request must be valid");
+        backend.sign(&mut req).expect("sign must succeed");
+        assert!(req.headers().contains_key(http::header::AUTHORIZATION));
+
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_assume_role() {
+        use wiremock::matchers::header;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::matchers::query_param;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let expiration = (OffsetDateTime::now_utc() + time::Duration::hours(1))
+            .format(&time::format_description::well_known::Rfc3339)
+            .unwrap();
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/sts/"))
+            .and(query_param("Action", "AssumeRole"))
+            .and(query_param(
+                "RoleArn",
+                "arn:aws:iam::123456789012:role/test",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!(
+                r#"<AssumeRoleResponse>
+  <AssumeRoleResult>
+    <Credentials>
+      <AccessKeyId>assumed</AccessKeyId>
+      <SecretAccessKey>secret_access_key</SecretAccessKey>
+      <SessionToken>session_token</SessionToken>
+      <Expiration>{expiration}</Expiration>
+    </Credentials>
+  </AssumeRoleResult>
+</AssumeRoleResponse>"#
+            )))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/sts/"))
+            .and(query_param("RoleArn", "arn:aws:iam::123456789012:role/denied"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(
+                r#"<ErrorResponse><Error><Code>AccessDenied</Code><Message>denied</Message></Error></ErrorResponse>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path("/test/file"))
+            .and(header("x-amz-security-token", "session_token"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-length", "13"))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let new_builder = |role_arn: &str| {
+            let mut builder = S3Builder::default();
+            builder
+                .bucket("test")
+                .endpoint(&mock_server.uri())
+                .region("us-east-1")
+                .access_key_id("access_key_id")
+                .secret_access_key("secret_access_key")
+                .role_arn(role_arn)
+                .sts_endpoint(&format!("{}/sts", mock_server.uri()))
+                .disable_config_load();
+            builder
+        };
+
+        let op = Operator::new(new_builder("arn:aws:iam::123456789012:role/test"))
+            .unwrap()
+            .finish();
+        op.stat("file").await.expect("stat must succeed");
+        // Credentials of STS are cached.
+        op.stat("file").await.expect("stat must succeed");
+
+        // Failed STS requests must not be sent anonymously.
+        let op = Operator::new(new_builder("arn:aws:iam::123456789012:role/denied"))
+            .unwrap()
+            .finish();
+        let err = op.stat("file").await.expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+
+    #[tokio::test]
+    async fn test_user_agent() {
+        use wiremock::matchers::header;
+        use wiremock::matchers::header_exists;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/test/file"))
+            .and(header_exists("authorization"))
+            .and(header(
+                "user-agent",
+                format!("opendal/{VERSION} myapp/1.2.3").as_str(),
+            ))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-length", "13"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = HttpClient::builder()
+            .user_agent("myapp/1.2.3")
+            .build()
+            .unwrap();
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load()
+            .http_client(client);
+        let op = Operator::new(builder).unwrap().finish();
+
+        let meta = op.stat("file").await.expect("stat must succeed");
+        assert_eq!(meta.content_length(), 13);
+    }
+
+    #[test]
+    fn test_invalid_user_agent() {
+        let err = HttpClient::builder()
+            .user_agent("myapp\n")
+            .build()
+            .expect_err("user agent with new line must be invalid");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[test]
+    fn test_parse_restore_status() {
+        let cases = vec![
+            ("not restored", None, None),
+            (
+                "ongoing",
+                Some(r#"ongoing-request="true""#),
+                Some(RestoreStatus::new(true, None)),
+            ),
+            (
+                "finished",
+                Some(r#"ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT""#),
+                Some(RestoreStatus::new(
+                    false,
+                    Some(OffsetDateTime::parse("Fri, 21 Dec 2012 00:00:00 GMT", &Rfc2822).unwrap()),
+                )),
+            ),
+        ];
+
+        for (name, input, expected) in cases {
+            let mut headers = HeaderMap::new();
+            if let Some(v) = input {
+                headers.insert(constants::X_AMZ_RESTORE, HeaderValue::from_static(v));
+            }
+
+            let actual = parse_restore_status(&headers).expect(name);
+            assert_eq!(actual, expected, "{name}");
+        }
+
+        for v in [r#"ongoing-request=true"#, r#"expiry-date="invalid""#] {
+            let mut headers = HeaderMap::new();
+            headers.insert(constants::X_AMZ_RESTORE, HeaderValue::from_static(v));
+            assert!(parse_restore_status(&headers).is_err(), "{v}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restore_archived_object() {
+        use wiremock::matchers::body_string_contains;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::matchers::query_param;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/test/file"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(
+                r#"<Error><Code>InvalidObjectState</Code><Message>The operation is not valid for the object's storage class</Message></Error>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/test/file"))
+            .and(query_param("restore", ""))
+            .and(body_string_contains("<Days>7</Days>"))
+            .and(body_string_contains(
+                "<GlacierJobParameters><Tier>Bulk</Tier></GlacierJobParameters>",
+            ))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("HEAD"))
+            .and(path("/test/file"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-length", "13")
+                    .insert_header("x-amz-storage-class", "GLACIER")
+                    .insert_header("x-amz-restore", r#"ongoing-request="true""#),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+
+        let err = op.read("file").await.expect_err("read must fail");
+        assert_eq!(err.kind(), ErrorKind::Archived);
+
+        op.restore_with("file", OpRestore::new(7).with_tier("Bulk"))
+            .await
+            .expect("restore must succeed");
+
+        let meta = op.stat("file").await.expect("stat must succeed");
+        assert_eq!(meta.storage_class(), Some("GLACIER"));
+        assert_eq!(meta.restore_status(), Some(RestoreStatus::new(true, None)));
+    }
+
+    #[test]
+    fn test_build_with_invalid_write_options() {
+        type Case = (&'static str, fn(&mut S3Builder));
+        let cases: Vec<Case> = vec![
+            ("write_part_size too small", |b| {
+                b.write_part_size(1024);
+            }),
+            ("write_concurrency is zero", |b| {
+                b.write_concurrency(0);
+            }),
+            ("checksum_algorithm is invalid", |b| {
+                b.checksum_algorithm("md5");
+            }),
+        ];
+
+        for (name, f) in cases {
+            let mut b = S3Builder::default();
+            b.bucket("test")
+                .endpoint("http://127.0.0.1:9000")
+                .region("us-east-1")
+                .disable_config_load();
+            f(&mut b);
+
+            let err = b.build().expect_err(name);
+            assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_checksum_algorithm() {
+        assert_eq!(ChecksumAlgorithm::Crc32c.checksum(b"123456789"), "4waSgw==");
+        assert_eq!(
+            ChecksumAlgorithm::Sha256.checksum(b""),
+            "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="
+        );
+    }
+
+    #[test]
+    fn test_serialize_complete_multipart_upload_request_with_checksum() {
+        let req = CompleteMultipartUploadRequest {
+            part: vec![CompleteMultipartUploadRequestPart::new(
+                1,
+                "etag-1".to_string(),
+                Some((ChecksumAlgorithm::Crc32c, "4waSgw==".to_string())),
+            )],
+        };
+
+        let actual = quick_xml::se::to_string(&req).expect("must succeed");
+
+        assert_eq!(
+            actual,
+            "<CompleteMultipartUpload><Part><PartNumber>1</PartNumber><ETag>etag-1</ETag><ChecksumCRC32C>4waSgw==</ChecksumCRC32C></Part></CompleteMultipartUpload>"
+        )
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_concurrently() {
+        use std::sync::Mutex;
+        use std::time::Duration;
+        use std::time::Instant;
+
+        use wiremock::matchers::header;
+        use wiremock::matchers::header_exists;
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::matchers::query_param;
+        use wiremock::Match;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::Request;
+        use wiremock::Respond;
+        use wiremock::ResponseTemplate;
+
+        /// Respond UploadPart with etag of part number, the first part
+        /// will be the slowest one.
+        ///
+        /// The time range of every part is recorded to calculate how many
+        /// parts are in flight at the same time.
+        #[derive(Clone, Default)]
+        struct UploadPartResponder(Arc<Mutex<Vec<(Instant, Instant)>>>);
+
+        impl UploadPartResponder {
+            fn peak_in_flight(&self) -> usize {
+                let ranges = self.0.lock().unwrap();
+                ranges
+                    .iter()
+                    .map(|(start, _)| {
+                        ranges
+                            .iter()
+                            .filter(|(s, e)| s <= start && start < e)
+                            .count()
+                    })
+                    .max()
+                    .unwrap_or_default()
+            }
+        }
+
+        impl Respond for UploadPartResponder {
+            fn respond(&self, req: &Request) -> ResponseTemplate {
+                let part_number: usize = req
+                    .url
+                    .query_pairs()
+                    .find(|(k, _)| k == "partNumber")
+                    .map(|(_, v)| v.parse().unwrap())
+                    .unwrap();
+                let delay = Duration::from_millis(if part_number == 1 { 1000 } else { 500 });
+
+                let now = Instant::now();
+                self.0.lock().unwrap().push((now, now + delay));
+
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", format!("etag-{part_number}").as_str())
+                    .set_delay(delay)
+            }
+        }
+
+        /// Match CompleteMultipartUpload requests with parts in order.
+        struct CompleteMatcher(Vec<String>);
+
+        impl Match for CompleteMatcher {
+            fn matches(&self, req: &Request) -> bool {
+                let body = String::from_utf8_lossy(&req.body);
+                let mut pos = 0;
+                for part in &self.0 {
+                    match body[pos..].find(part.as_str()) {
+                        Some(idx) => pos += idx + part.len(),
+                        None => return false,
+                    }
+                }
+                true
+            }
+        }
+
+        let _ = env_logger::try_init();
+
+        let part_size = MIN_WRITE_PART_SIZE;
+        let content: Vec<u8> = (0..part_size * 3 + 1024).map(|i| (i % 251) as u8).collect();
+        let expected_parts = content
+            .chunks(part_size)
+            .enumerate()
+            .map(|(idx, part)| {
+                format!(
+                    "<Part><PartNumber>{n}</PartNumber><ETag>etag-{n}</ETag><ChecksumCRC32C>{}</ChecksumCRC32C></Part>",
+                    ChecksumAlgorithm::Crc32c.checksum(part),
+                    n = idx + 1
+                )
+            })
+            .collect();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/test/file"))
+            .and(query_param("uploads", ""))
+            .and(header("x-amz-checksum-algorithm", "CRC32C"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<InitiateMultipartUploadResult><Bucket>test</Bucket><Key>file</Key><UploadId>upload-id</UploadId></InitiateMultipartUploadResult>"#,
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let responder = UploadPartResponder::default();
+        Mock::given(method("PUT"))
+            .and(path("/test/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .and(header("content-encoding", "aws-chunked"))
+            .and(header("x-amz-trailer", "x-amz-checksum-crc32c"))
+            .and(header(
+                "x-amz-content-sha256",
+                "STREAMING-UNSIGNED-PAYLOAD-TRAILER",
+            ))
+            .and(header_exists("authorization"))
+            .respond_with(responder.clone())
+            .expect(4)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/test/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .and(CompleteMatcher(expected_parts))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-amz-version-id", "version-id")
+                    .set_body_string(
+                        r#"<CompleteMultipartUploadResult><Bucket>test</Bucket><Key>file</Key><ETag>"etag-4"</ETag></CompleteMultipartUploadResult>"#,
+                    ),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .write_part_size(part_size)
+            .write_concurrency(4)
+            .checksum_algorithm("crc32c")
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+
+        let mut w = op.writer("file").await.expect("writer must be created");
+        // Append in chunks that not aligned with part size.
+        for chunk in content.chunks(3 * 1024 * 1024) {
+            w.append(chunk.to_vec()).await.expect("append must succeed");
+        }
+        w.close().await.expect("close must succeed");
+        assert_eq!(w.etag(), Some("\"etag-4\""));
+        assert_eq!(w.version(), Some("version-id"));
+
+        // All parts are uploaded at the same time, but no more than
+        // `write_concurrency`.
+        assert_eq!(responder.peak_in_flight(), 4);
+    }
+
+    #[test]
+    fn test_encode_trailer_checksum_body() {
+        let checksum = ChecksumAlgorithm::Crc32c.checksum(b"Hello, World!");
+        assert_eq!(
+            encode_trailer_checksum_body(ChecksumAlgorithm::Crc32c, &checksum, b"Hello, World!"),
+            Bytes::from(format!(
+                "d\r\nHello, World!\r\n0\r\nx-amz-checksum-crc32c:{checksum}\r\n\r\n"
+            ))
+        );
+        assert_eq!(
+            encode_trailer_checksum_body(ChecksumAlgorithm::Sha256, "sha", b""),
+            Bytes::from("0\r\nx-amz-checksum-sha256:sha\r\n\r\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_abort() {
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::matchers::query_param;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/test/file"))
+            .and(query_param("uploads", ""))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<InitiateMultipartUploadResult><Bucket>test</Bucket><Key>file</Key><UploadId>upload-id</UploadId></InitiateMultipartUploadResult>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/test/file"))
+            .and(query_param("partNumber", "2"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(
+                r#"<Error><Code>AccessDenied</Code><Message>Access Denied</Message></Error>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/test/file"))
+            .respond_with(ResponseTemplate::new(200).insert_header("ETag", "etag"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/test/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/test/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .write_concurrency(4)
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+
+        let mut w = op.writer("file").await.expect("writer must be created");
+        let mut res = Ok(());
+        for _ in 0..3 {
+            res = res.and(w.append(vec![0; 1024]).await);
+        }
+        let err = res.and(w.close().await).expect_err("upload part must fail");
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+
+        w.abort().await.expect("abort must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_multipart_upload_abort_on_drop() {
+        use std::time::Duration;
+
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::matchers::query_param;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/test/file"))
+            .and(query_param("uploads", ""))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                r#"<InitiateMultipartUploadResult><Bucket>test</Bucket><Key>file</Key><UploadId>upload-id</UploadId></InitiateMultipartUploadResult>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/test/file"))
+            .respond_with(ResponseTemplate::new(200).insert_header("ETag", "etag"))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/test/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+
+        let mut w = op.writer("file").await.expect("writer must be created");
+        assert_eq!(w.session(), Some("upload-id"));
+        w.append(vec![0; 1024]).await.expect("append must succeed");
+
+        drop(w);
+        // Wait for the background abort.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    #[tokio::test]
+    async fn test_abort_multipart() {
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::matchers::query_param;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path("/test/file"))
+            .and(query_param("uploadId", "upload-id"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/test/file"))
+            .and(query_param("uploadId", "not-exist"))
+            .respond_with(ResponseTemplate::new(404).set_body_string(
+                r#"<Error><Code>NoSuchUpload</Code><Message>The specified upload does not exist.</Message></Error>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+        assert!(op.info().capability().abort_multipart);
+
+        op.abort_multipart("file", "upload-id")
+            .await
+            .expect("abort must succeed");
+
+        let err = op
+            .abort_multipart("file", "not-exist")
+            .await
+            .expect_err("abort must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_error_context_getters() {
+        use wiremock::matchers::method;
+        use wiremock::matchers::path;
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+
+        let _ = env_logger::try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/test/file"))
+            .respond_with(
+                ResponseTemplate::new(403)
+                    .insert_header("x-amz-request-id", "4442587FB7D0A2F9")
+                    .set_body_string(
+                        r#"<Error><Code>AccessDenied</Code><Message>Access Denied</Message></Error>"#,
+                    ),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = S3Builder::default();
+        builder
+            .bucket("test")
+            .endpoint(&mock_server.uri())
+            .region("us-east-1")
+            .access_key_id("access_key_id")
+            .secret_access_key("secret_access_key")
+            .disable_config_load();
+        let op = Operator::new(builder).unwrap().finish();
+
+        let err = op.read("file").await.expect_err("read must fail");
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        assert_eq!(err.operation(), Operation::Read.into_static());
+        assert_eq!(err.service(), Some(Scheme::S3.into_static()));
+        assert_eq!(err.path(), Some("file"));
+        assert_eq!(err.status_code(), Some(403));
+        assert_eq!(err.request_id(), Some("4442587FB7D0A2F9"));
+    }
 }
@@ -13,11 +13,19 @@
 // limitations under the License.
 
 use bytes::Buf;
+use bytes::Bytes;
+use http::header::DATE;
+use http::response::Parts;
+use http::HeaderMap;
 use http::Response;
 use http::StatusCode;
 use quick_xml::de;
 use serde::Deserialize;
+use time::format_description::well_known::Rfc2822;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 
+use super::backend::constants;
 use crate::raw::*;
 use crate::Error;
 use crate::ErrorKind;
@@ -31,50 +39,194 @@ struct S3Error {
     message: String,
     resource: String,
     request_id: String,
+    host_id: String,
+    /// Only returned with region mismatch errors like `AuthorizationHeaderMalformed`.
+    region: String,
+    /// Only returned with redirect errors like `PermanentRedirect`.
+    endpoint: String,
+    /// Only returned with `RequestTimeTooSkewed`.
+    server_time: String,
+}
+
+impl S3Error {
+    /// Returns the region that the bucket expected to be accessed with.
+    ///
+    /// `x-amz-bucket-region` header is preferred, and fallback to `Region`
+    /// or `Endpoint` in body.
+    fn expected_region(&self, headers: &HeaderMap) -> Option<String> {
+        if let Some(v) = headers
+            .get(constants::X_AMZ_BUCKET_REGION)
+            .and_then(|v| v.to_str().ok())
+        {
+            return Some(v.to_string());
+        }
+        if !self.region.is_empty() {
+            return Some(self.region.clone());
+        }
+
+        // Endpoint is like `bucket.s3.eu-west-1.amazonaws.com` or
+        // `bucket.s3-eu-west-1.amazonaws.com`.
+        let host = self.endpoint.strip_suffix(".amazonaws.com")?;
+        let segments: Vec<&str> = host.split('.').collect();
+        segments.iter().enumerate().find_map(|(idx, v)| match *v {
+            "s3" => segments.get(idx + 1).map(|v| v.to_string()),
+            v => v.strip_prefix("s3-").map(|v| v.to_string()),
+        })
+    }
 }
 
 /// Parse error response into Error.
+///
+/// Following context parsed from the error body will be added:
+///
+/// - `code`: the error code like `NoSuchKey`
+/// - `request_id`: only if it's not returned in headers
+/// - `host_id`: the special token to help AWS troubleshoot problems
+/// - `region`: the region that the bucket expected for region mismatch errors
+/// - `endpoint`: the endpoint that the bucket expected for redirect errors
 pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
     let (parts, body) = resp.into_parts();
     let bs = body.bytes().await?;
 
-    let (mut kind, mut retryable) = match parts.status {
-        StatusCode::NOT_FOUND => (ErrorKind::NotFound, false),
-        StatusCode::FORBIDDEN => (ErrorKind::PermissionDenied, false),
-        StatusCode::INTERNAL_SERVER_ERROR
-        | StatusCode::BAD_GATEWAY
-        | StatusCode::SERVICE_UNAVAILABLE
-        | StatusCode::GATEWAY_TIMEOUT => (ErrorKind::Unexpected, true),
-        _ => (ErrorKind::Unexpected, false),
-    };
+    Ok(parse_error_body(&parts, &bs))
+}
+
+/// Parse the error carried by body of a `200 OK` response.
+///
+/// Some requests like `CopyObject` could fail after `200 OK` has been
+/// sent, the error will be returned in body instead.
+///
+/// ref: <https://docs.aws.amazon.com/AmazonS3/latest/API/API_CopyObject.html>
+pub fn parse_ok_error(parts: &Parts, bs: &Bytes) -> Option<Error> {
+    let s3_err = de::from_reader::<_, S3Error>(bs.clone().reader()).ok()?;
+    if s3_err.code.is_empty() {
+        return None;
+    }
+
+    Some(parse_error_body(parts, bs))
+}
 
-    let (message, s3_err) = de::from_reader::<_, S3Error>(bs.clone().reader())
+fn parse_error_body(parts: &Parts, bs: &Bytes) -> Error {
+    let (mut kind, mut retryable) = parse_error_status(parts.status);
+
+    let (mut message, s3_err) = de::from_reader::<_, S3Error>(bs.clone().reader())
         .map(|s3_err| (format!("{s3_err:?}"), Some(s3_err)))
-        .unwrap_or_else(|_| (String::from_utf8_lossy(&bs).into_owned(), None));
+        .unwrap_or_else(|_| (String::from_utf8_lossy(bs).into_owned(), None));
+    // `HEAD` requests will get `301 Moved Permanently` without body.
+    let s3_err = match s3_err {
+        None if parts.status == StatusCode::MOVED_PERMANENTLY => Some(S3Error {
+            code: "PermanentRedirect".to_string(),
+            ..Default::default()
+        }),
+        v => v,
+    };
 
+    let mut expected_region = None;
     // All possible error code: <https://docs.aws.amazon.com/AmazonS3/latest/API/ErrorResponses.html#ErrorCodeList>
-    if let Some(s3_err) = s3_err {
+    if let Some(s3_err) = &s3_err {
         (kind, retryable) = match s3_err.code.as_str() {
             // > Your socket connection to the server was not read from
             // > or written to within the timeout period."
             //
             // It's Ok for us to retry it again.
             "RequestTimeout" => (ErrorKind::Unexpected, true),
+            // Returned with `200 OK` if copy fails after started.
+            "InternalError" => (ErrorKind::Unexpected, true),
+            // Our clock is skewed, requests will be signed with the
+            // corrected time while retrying.
+            "RequestTimeTooSkewed" => (ErrorKind::Unexpected, true),
+            // > Reduce your request rate.
+            //
+            // Returned with `503 Service Unavailable` while throttled.
+            "SlowDown" => (ErrorKind::RateLimited, true),
+            // Credentials are not valid, users need to fix their config.
+            "InvalidAccessKeyId" | "SignatureDoesNotMatch" | "InvalidToken" => {
+                (ErrorKind::ConfigInvalid, false)
+            }
+            // Credentials are valid but not allowed to access this resource.
+            "AccessDenied" | "AccountProblem" | "AllAccessDisabled" => {
+                (ErrorKind::PermissionDenied, false)
+            }
+            // The bucket is not in the region we configured, users need
+            // to fix the region or endpoint of builder.
+            "PermanentRedirect"
+            | "AuthorizationHeaderMalformed"
+            | "IllegalLocationConstraintException" => {
+                expected_region = s3_err.expected_region(&parts.headers);
+                message = match &expected_region {
+                    Some(region) => format!(
+                        "bucket is in region {region}, please set region of builder to {region}: {message}"
+                    ),
+                    None if !s3_err.endpoint.is_empty() => format!(
+                        "bucket must be accessed via endpoint {}, please fix endpoint of builder: {message}",
+                        s3_err.endpoint
+                    ),
+                    None => message,
+                };
+                (ErrorKind::ConfigInvalid, false)
+            }
+            "NoSuchBucket" => (ErrorKind::NotFound, false),
+            // Object is archived, users need to restore it before reading.
+            "InvalidObjectState" => (ErrorKind::Archived, false),
             _ => (kind, retryable),
         }
     }
 
-    let mut err = Error::new(kind, &message).with_context("response", format!("{parts:?}"));
+    let mut err = with_error_response_context(Error::new(kind, &message), parts);
+
+    if let Some(s3_err) = s3_err {
+        err = err.with_context("code", s3_err.code.as_str());
+        if err.request_id().is_none() && !s3_err.request_id.is_empty() {
+            err = err.with_context("request_id", s3_err.request_id.as_str());
+        }
+        if !s3_err.host_id.is_empty() {
+            err = err.with_context("host_id", s3_err.host_id.as_str());
+        }
+        if let Some(region) = expected_region {
+            err = err.with_context("region", region);
+        }
+        if !s3_err.endpoint.is_empty() {
+            err = err.with_context("endpoint", s3_err.endpoint);
+        }
+    }
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
 
     if retryable {
         err = err.set_temporary();
     }
 
-    Ok(err)
+    err
+}
+
+/// Parse the server time from `RequestTimeTooSkewed` error response.
+///
+/// Returns `None` for all other errors, so that genuine auth failures will
+/// not be retried.
+pub fn parse_request_time_too_skewed(headers: &HeaderMap, bs: &Bytes) -> Option<OffsetDateTime> {
+    let s3_err = de::from_reader::<_, S3Error>(bs.clone().reader()).ok()?;
+    if s3_err.code != "RequestTimeTooSkewed" {
+        return None;
+    }
+
+    // `Date` header is preferred, and fallback to `ServerTime` in body.
+    headers
+        .get(DATE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| OffsetDateTime::parse(v, &Rfc2822).ok())
+        .or_else(|| OffsetDateTime::parse(&s3_err.server_time, &Rfc3339).ok())
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use futures::stream;
+    use http::header::RETRY_AFTER;
+    use http::StatusCode;
+
     use super::*;
 
     /// Error response example is from https://docs.aws.amazon.com/AmazonS3/latest/API/ErrorResponses.html
@@ -100,4 +252,273 @@ mod tests {
         assert_eq!(out.resource, "/mybucket/myfoto.jpg");
         assert_eq!(out.request_id, "4442587FB7D0A2F9");
     }
+
+    #[tokio::test]
+    async fn test_parse_error_slow_down() -> Result<()> {
+        let bs = Bytes::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>SlowDown</Code>
+  <Message>Please reduce your request rate.</Message>
+  <RequestId>4442587FB7D0A2F9</RequestId>
+</Error>"#,
+        );
+        let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+        let resp = Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .header(RETRY_AFTER, "3")
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::RateLimited);
+        assert!(err.is_temporary());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(3)));
+
+        // Plain `503` without hints is still an unexpected error.
+        let body = IncomingAsyncBody::new(Box::new(stream::empty()), None);
+        let resp = Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(err.is_temporary());
+        assert_eq!(err.retry_after(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_request_time_too_skewed() {
+        let bs = Bytes::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>RequestTimeTooSkewed</Code>
+  <Message>The difference between the request time and the current time is too large.</Message>
+  <RequestTime>20230301T100000Z</RequestTime>
+  <ServerTime>2023-03-01T10:20:00Z</ServerTime>
+  <MaxAllowedSkewMilliseconds>900000</MaxAllowedSkewMilliseconds>
+  <RequestId>4442587FB7D0A2F9</RequestId>
+</Error>"#,
+        );
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            parse_request_time_too_skewed(&headers, &bs),
+            Some(OffsetDateTime::parse("2023-03-01T10:20:00Z", &Rfc3339).unwrap())
+        );
+
+        headers.insert(DATE, "Wed, 01 Mar 2023 10:20:01 GMT".parse().unwrap());
+        assert_eq!(
+            parse_request_time_too_skewed(&headers, &bs),
+            Some(OffsetDateTime::parse("2023-03-01T10:20:01Z", &Rfc3339).unwrap())
+        );
+
+        // Other errors must not be treated as skewed.
+        let bs = Bytes::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>AccessDenied</Code>
+  <Message>Access Denied</Message>
+</Error>"#,
+        );
+        assert_eq!(parse_request_time_too_skewed(&headers, &bs), None);
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_auth() -> Result<()> {
+        let cases = vec![
+            (
+                StatusCode::FORBIDDEN,
+                "InvalidAccessKeyId",
+                ErrorKind::ConfigInvalid,
+            ),
+            (
+                StatusCode::FORBIDDEN,
+                "SignatureDoesNotMatch",
+                ErrorKind::ConfigInvalid,
+            ),
+            (
+                StatusCode::BAD_REQUEST,
+                "InvalidToken",
+                ErrorKind::ConfigInvalid,
+            ),
+            (
+                StatusCode::FORBIDDEN,
+                "AccessDenied",
+                ErrorKind::PermissionDenied,
+            ),
+            (
+                StatusCode::FORBIDDEN,
+                "AllAccessDisabled",
+                ErrorKind::PermissionDenied,
+            ),
+        ];
+
+        for (status, code, kind) in cases {
+            let bs = Bytes::from(format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>{code}</Code>
+  <Message>some message</Message>
+  <RequestId>4442587FB7D0A2F9</RequestId>
+</Error>"#
+            ));
+            let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+            let resp = Response::builder().status(status).body(body).unwrap();
+
+            let err = parse_error(resp).await?;
+            assert_eq!(err.kind(), kind, "code: {code}");
+            assert!(!err.is_temporary(), "code: {code}");
+        }
+
+        // `401` without body is a permission error too.
+        let body = IncomingAsyncBody::new(Box::new(stream::empty()), None);
+        let resp = Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        assert!(!err.is_temporary());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_context() -> Result<()> {
+        let bs = Bytes::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>NoSuchKey</Code>
+  <Message>The resource you requested does not exist</Message>
+  <Resource>/mybucket/myfoto.jpg</Resource>
+  <RequestId>4442587FB7D0A2F9</RequestId>
+  <HostId>ZJqjQ4TqGmSEnoQpKUwBp8ExIx5FYJ0OchZKu1nYEQE=</HostId>
+</Error>"#,
+        );
+        let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+        let resp = Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(err.context_value("code"), Some("NoSuchKey"));
+        assert_eq!(err.request_id(), Some("4442587FB7D0A2F9"));
+        assert_eq!(
+            err.context_value("host_id"),
+            Some("ZJqjQ4TqGmSEnoQpKUwBp8ExIx5FYJ0OchZKu1nYEQE=")
+        );
+        assert_eq!(err.context_value("region"), None);
+
+        // Request id in headers is preferred.
+        let bs = Bytes::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>NoSuchKey</Code>
+  <RequestId>4442587FB7D0A2F9</RequestId>
+</Error>"#,
+        );
+        let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+        let resp = Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header("x-amz-request-id", "header-request-id")
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.request_id(), Some("header-request-id"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_region_mismatch() -> Result<()> {
+        let cases = vec![
+            (
+                StatusCode::MOVED_PERMANENTLY,
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>PermanentRedirect</Code>
+  <Message>The bucket you are attempting to access must be addressed using the specified endpoint.</Message>
+  <Endpoint>mybucket.s3.eu-west-1.amazonaws.com</Endpoint>
+  <Bucket>mybucket</Bucket>
+  <RequestId>4442587FB7D0A2F9</RequestId>
+</Error>"#,
+                "PermanentRedirect",
+                Some("eu-west-1"),
+            ),
+            (
+                StatusCode::BAD_REQUEST,
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>AuthorizationHeaderMalformed</Code>
+  <Message>The authorization header is malformed; the region 'us-east-1' is wrong; expecting 'ap-southeast-1'</Message>
+  <Region>ap-southeast-1</Region>
+  <RequestId>4442587FB7D0A2F9</RequestId>
+</Error>"#,
+                "AuthorizationHeaderMalformed",
+                Some("ap-southeast-1"),
+            ),
+            (
+                StatusCode::BAD_REQUEST,
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>IllegalLocationConstraintException</Code>
+  <Message>The unspecified location constraint is incompatible for the region specific endpoint this request was sent to.</Message>
+  <RequestId>4442587FB7D0A2F9</RequestId>
+</Error>"#,
+                "IllegalLocationConstraintException",
+                None,
+            ),
+            (
+                StatusCode::MOVED_PERMANENTLY,
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+  <Code>PermanentRedirect</Code>
+  <Endpoint>mybucket.s3-us-west-2.amazonaws.com</Endpoint>
+</Error>"#,
+                "PermanentRedirect",
+                Some("us-west-2"),
+            ),
+        ];
+
+        for (status, body, code, region) in cases {
+            let body =
+                IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(Bytes::from(body))])), None);
+            let resp = Response::builder().status(status).body(body).unwrap();
+
+            let err = parse_error(resp).await?;
+            assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "code: {code}");
+            assert!(!err.is_temporary(), "code: {code}");
+            assert_eq!(err.context_value("code"), Some(code));
+            assert_eq!(err.context_value("region"), region, "code: {code}");
+            if let Some(region) = region {
+                assert!(
+                    err.to_string()
+                        .contains(&format!("bucket is in region {region}")),
+                    "error message must contain the expected region: {err}"
+                );
+            }
+        }
+
+        // `HEAD` returns redirects without body, but with region in headers.
+        let body = IncomingAsyncBody::new(Box::new(stream::empty()), None);
+        let resp = Response::builder()
+            .status(StatusCode::MOVED_PERMANENTLY)
+            .header("x-amz-bucket-region", "eu-central-1")
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+        assert_eq!(err.context_value("region"), Some("eu-central-1"));
+
+        Ok(())
+    }
 }
@@ -15,6 +15,8 @@
 mod backend;
 pub use backend::S3Builder as S3;
 
+mod clock_skew;
 mod error;
 mod pager;
+mod sts;
 mod writer;
@@ -37,6 +37,7 @@ pub struct S3Pager {
     delimiter: String,
     limit: Option<usize>,
 
+    /// `continuation-token` for ListObjectsV2 or `marker` for ListObjects.
     token: String,
     done: bool,
 }
@@ -69,10 +70,15 @@ impl oio::Page for S3Pager {
             return Ok(None);
         }
 
-        let resp = self
-            .backend
-            .s3_list_objects(&self.path, &self.token, &self.delimiter, self.limit)
-            .await?;
+        let resp = if self.backend.enable_list_objects_v1 {
+            self.backend
+                .s3_list_objects_v1(&self.path, &self.token, &self.delimiter, self.limit)
+                .await?
+        } else {
+            self.backend
+                .s3_list_objects(&self.path, &self.token, &self.delimiter, self.limit)
+                .await?
+        };
 
         if resp.status() != http::StatusCode::OK {
             return Err(parse_error(resp).await?);
@@ -82,19 +88,30 @@ impl oio::Page for S3Pager {
 
         let output: Output = de::from_reader(bs.reader()).map_err(new_xml_deserialize_error)?;
 
-        // Try our best to check whether this list is done.
-        //
-        // - Check `is_truncated`
-        // - Check `next_continuation_token`
-        // - Check the length of `common_prefixes` and `contents` (very rarely case)
-        self.done = if let Some(is_truncated) = output.is_truncated {
-            !is_truncated
-        } else if let Some(next_continuation_token) = output.next_continuation_token.as_ref() {
-            next_continuation_token.is_empty()
+        if self.backend.enable_list_objects_v1 {
+            self.token = output.next_marker_v1();
+            // Stop if there is no marker to continue, otherwise we will
+            // list the same page again.
+            self.done = self.token.is_empty()
+                || match output.is_truncated {
+                    Some(is_truncated) => !is_truncated,
+                    None => output.common_prefixes.is_empty() && output.contents.is_empty(),
+                };
         } else {
-            output.common_prefixes.is_empty() && output.contents.is_empty()
-        };
-        self.token = output.next_continuation_token.clone().unwrap_or_default();
+            // Try our best to check whether this list is done.
+            //
+            // - Check `is_truncated`
+            // - Check `next_continuation_token`
+            // - Check the length of `common_prefixes` and `contents` (very rarely case)
+            self.done = if let Some(is_truncated) = output.is_truncated {
+                !is_truncated
+            } else if let Some(next_continuation_token) = output.next_continuation_token.as_ref() {
+                next_continuation_token.is_empty()
+            } else {
+                output.common_prefixes.is_empty() && output.contents.is_empty()
+            };
+            self.token = output.next_continuation_token.clone().unwrap_or_default();
+        }
 
         let mut entries = Vec::with_capacity(output.common_prefixes.len() + output.contents.len());
 
@@ -120,6 +137,7 @@ impl oio::Page for S3Pager {
             meta.set_etag(&object.etag);
             meta.set_content_md5(object.etag.trim_matches('"'));
             meta.set_content_length(object.size);
+            meta.set_storage_class(object.storage_class.as_deref().unwrap_or("STANDARD"));
 
             // object.last_modified provides more precious time that contains
             // nanosecond, let's trim them.
@@ -160,10 +178,28 @@ impl oio::Page for S3Pager {
 struct Output {
     is_truncated: Option<bool>,
     next_continuation_token: Option<String>,
+    /// Only returned by ListObjects (v1) while delimiter is specified.
+    next_marker: Option<String>,
     common_prefixes: Vec<OutputCommonPrefix>,
     contents: Vec<OutputContent>,
 }
 
+impl Output {
+    /// Get the marker to list next page of ListObjects (v1).
+    ///
+    /// `NextMarker` could be omitted by services, use the last key or
+    /// common prefix returned in this page instead.
+    fn next_marker_v1(&self) -> String {
+        if let Some(marker) = self.next_marker.as_ref().filter(|v| !v.is_empty()) {
+            return marker.clone();
+        }
+
+        let last_key = self.contents.last().map(|v| v.key.as_str());
+        let last_prefix = self.common_prefixes.last().map(|v| v.prefix.as_str());
+        last_key.max(last_prefix).unwrap_or_default().to_string()
+    }
+}
+
 #[derive(Default, Debug, Eq, PartialEq, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 struct OutputContent {
@@ -172,6 +208,7 @@ struct OutputContent {
     last_modified: String,
     #[serde(rename = "ETag")]
     etag: String,
+    storage_class: Option<String>,
 }
 
 #[derive(Default, Debug, Eq, PartialEq, Deserialize)]
@@ -237,14 +274,101 @@ mod tests {
                     size: 56,
                     etag: "\"d41d8cd98f00b204e9800998ecf8427e\"".to_string(),
                     last_modified: "2016-04-30T23:51:29.000Z".to_string(),
+                    storage_class: Some("STANDARD".to_string()),
                 },
                 OutputContent {
                     key: "photos/2007".to_string(),
                     size: 100,
                     last_modified: "2016-04-30T23:51:29.000Z".to_string(),
                     etag: "\"d41d8cd98f00b204e9800998ecf8427e\"".to_string(),
+                    storage_class: Some("STANDARD".to_string()),
                 }
             ]
         )
     }
+
+    #[test]
+    fn test_parse_list_output_v1() {
+        let bs = bytes::Bytes::from(
+            r#"<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Name>example-bucket</Name>
+  <Prefix>photos/</Prefix>
+  <Marker></Marker>
+  <NextMarker>photos/2006/</NextMarker>
+  <MaxKeys>2</MaxKeys>
+  <Delimiter>/</Delimiter>
+  <IsTruncated>true</IsTruncated>
+  <Contents>
+    <Key>photos/2005.jpg</Key>
+    <LastModified>2016-04-30T23:51:29.000Z</LastModified>
+    <ETag>"d41d8cd98f00b204e9800998ecf8427e"</ETag>
+    <Size>56</Size>
+    <StorageClass>STANDARD</StorageClass>
+  </Contents>
+  <CommonPrefixes>
+    <Prefix>photos/2006/</Prefix>
+  </CommonPrefixes>
+</ListBucketResult>"#,
+        );
+
+        let out: Output = de::from_reader(bs.reader()).expect("must success");
+
+        assert!(out.is_truncated.unwrap());
+        assert_eq!(out.next_marker.as_deref(), Some("photos/2006/"));
+        assert_eq!(out.next_marker_v1(), "photos/2006/");
+    }
+
+    #[test]
+    fn test_next_marker_v1_fallback() {
+        let content = |key: &str| OutputContent {
+            key: key.to_string(),
+            ..Default::default()
+        };
+        let prefix = |prefix: &str| OutputCommonPrefix {
+            prefix: prefix.to_string(),
+        };
+
+        let cases = vec![
+            ("empty page", Output::default(), ""),
+            (
+                "only contents",
+                Output {
+                    contents: vec![content("a"), content("b")],
+                    ..Default::default()
+                },
+                "b",
+            ),
+            (
+                "prefix after key",
+                Output {
+                    contents: vec![content("a")],
+                    common_prefixes: vec![prefix("b/")],
+                    ..Default::default()
+                },
+                "b/",
+            ),
+            (
+                "key after prefix",
+                Output {
+                    contents: vec![content("c")],
+                    common_prefixes: vec![prefix("b/")],
+                    ..Default::default()
+                },
+                "c",
+            ),
+            (
+                "empty next marker",
+                Output {
+                    next_marker: Some("".to_string()),
+                    contents: vec![content("a")],
+                    ..Default::default()
+                },
+                "a",
+            ),
+        ];
+
+        for (name, output, expected) in cases {
+            assert_eq!(output.next_marker_v1(), expected, "{name}");
+        }
+    }
 }
@@ -0,0 +1,596 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::fmt::Write;
+use std::io::Read;
+
+use bytes::Buf;
+use http::header::CONTENT_TYPE;
+use http::Request;
+use http::StatusCode;
+use log::warn;
+use parking_lot::Mutex;
+use percent_encoding::utf8_percent_encode;
+use percent_encoding::NON_ALPHANUMERIC;
+use reqsign::credential::Credential;
+use reqsign::AwsCredentialLoad;
+use reqsign::AwsV4Signer;
+use serde::Deserialize;
+use time::format_description::well_known::Rfc3339;
+use time::Duration;
+use time::OffsetDateTime;
+
+use crate::raw::*;
+use crate::*;
+
+/// Temporary credentials will be refreshed if they expire in 5 minutes.
+///
+/// It must be larger than the 2 minutes that `AwsCredentialLoader` keeps
+/// before expiry, so that the loader always gets fresh credentials.
+const REFRESH_AHEAD: Duration = Duration::minutes(5);
+
+/// StsLoader loads temporary credentials from STS and refreshes them
+/// before they expire.
+///
+/// The lock is held while refreshing, so concurrent callers will wait for
+/// the refresh in flight and reuse its credential instead of calling STS
+/// again.
+pub(super) struct StsLoader {
+    client: HttpClient,
+    endpoint: String,
+    role_arn: String,
+    role_session_name: String,
+    kind: StsKind,
+
+    credential: Mutex<Option<(Credential, OffsetDateTime)>>,
+}
+
+enum StsKind {
+    /// `AssumeRoleWithWebIdentity` with the token read from file.
+    WebIdentity { token_file: String },
+    /// `AssumeRole` signed by the credentials loaded from other sources.
+    AssumeRole {
+        external_id: Option<String>,
+        signer: AwsV4Signer,
+    },
+}
+
+impl Debug for StsLoader {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            StsKind::WebIdentity { .. } => "AssumeRoleWithWebIdentity",
+            StsKind::AssumeRole { .. } => "AssumeRole",
+        };
+        f.debug_struct("StsLoader")
+            .field("endpoint", &self.endpoint)
+            .field("role_arn", &self.role_arn)
+            .field("kind", &kind)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StsLoader {
+    /// Create a loader that calls `AssumeRoleWithWebIdentity`.
+    pub fn web_identity(
+        client: HttpClient,
+        endpoint: &str,
+        role_arn: &str,
+        role_session_name: &str,
+        token_file: &str,
+    ) -> Self {
+        Self::new(
+            client,
+            endpoint,
+            role_arn,
+            role_session_name,
+            StsKind::WebIdentity {
+                token_file: token_file.to_string(),
+            },
+        )
+    }
+
+    /// Create a loader that calls `AssumeRole`, the request is signed by
+    /// given signer of service `sts`.
+    pub fn assume_role(
+        client: HttpClient,
+        endpoint: &str,
+        role_arn: &str,
+        role_session_name: &str,
+        external_id: Option<&str>,
+        signer: AwsV4Signer,
+    ) -> Self {
+        Self::new(
+            client,
+            endpoint,
+            role_arn,
+            role_session_name,
+            StsKind::AssumeRole {
+                external_id: external_id.map(|v| v.to_string()),
+                signer,
+            },
+        )
+    }
+
+    fn new(
+        client: HttpClient,
+        endpoint: &str,
+        role_arn: &str,
+        role_session_name: &str,
+        kind: StsKind,
+    ) -> Self {
+        Self {
+            client,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            role_arn: role_arn.to_string(),
+            role_session_name: role_session_name.to_string(),
+            kind,
+            credential: Mutex::new(None),
+        }
+    }
+
+    /// Load the credential, refresh it via STS if it's about to expire.
+    pub fn load(&self) -> Result<Credential> {
+        let mut credential = self.credential.lock();
+        if let Some((cred, expires_in)) = credential.as_ref() {
+            if *expires_in > OffsetDateTime::now_utc() + REFRESH_AHEAD {
+                return Ok(cred.clone());
+            }
+        }
+
+        match self.refresh() {
+            Ok((cred, expires_in)) => {
+                *credential = Some((cred.clone(), expires_in));
+                Ok(cred)
+            }
+            Err(err) => {
+                let err = err
+                    .with_operation("s3::StsLoader::refresh")
+                    .with_context("endpoint", &self.endpoint)
+                    .with_context("role_arn", &self.role_arn);
+                // Keep using the credential until it's expired, the refresh
+                // will be retried by following calls.
+                match credential.as_ref() {
+                    Some((cred, expires_in)) if *expires_in > OffsetDateTime::now_utc() => {
+                        warn!("refresh sts credential failed, using the current one: {err:?}");
+                        Ok(cred.clone())
+                    }
+                    _ => Err(err),
+                }
+            }
+        }
+    }
+
+    fn refresh(&self) -> Result<(Credential, OffsetDateTime)> {
+        let mut url = format!(
+            "{}/?Version=2011-06-15&RoleArn={}&RoleSessionName={}",
+            self.endpoint,
+            utf8_percent_encode(&self.role_arn, NON_ALPHANUMERIC),
+            utf8_percent_encode(&self.role_session_name, NON_ALPHANUMERIC)
+        );
+
+        let req = match &self.kind {
+            StsKind::WebIdentity { token_file } => {
+                let token = std::fs::read_to_string(token_file).map_err(|err| {
+                    Error::new(ErrorKind::ConfigInvalid, "read web identity token file")
+                        .with_context("token_file", token_file)
+                        .set_source(err)
+                })?;
+                write!(
+                    url,
+                    "&Action=AssumeRoleWithWebIdentity&WebIdentityToken={}",
+                    utf8_percent_encode(token.trim(), NON_ALPHANUMERIC)
+                )
+                .expect("write into string must succeed");
+
+                Request::get(&url)
+                    .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::Empty)
+                    .map_err(new_request_build_error)?
+            }
+            StsKind::AssumeRole {
+                external_id,
+                signer,
+            } => {
+                url.push_str("&Action=AssumeRole");
+                if let Some(v) = external_id {
+                    write!(
+                        url,
+                        "&ExternalId={}",
+                        utf8_percent_encode(v, NON_ALPHANUMERIC)
+                    )
+                    .expect("write into string must succeed");
+                }
+
+                let mut req = Request::get(&url)
+                    .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+                    .body(Body::Empty)
+                    .map_err(new_request_build_error)?;
+                signer.sign(&mut req).map_err(|err| {
+                    Error::new(
+                        ErrorKind::ConfigInvalid,
+                        "no credential found to sign AssumeRole",
+                    )
+                    .set_source(err)
+                })?;
+                req
+            }
+        };
+
+        let resp = self.client.send(req)?;
+        let (parts, mut body) = resp.into_parts();
+        let mut bs = Vec::new();
+        body.read_to_end(&mut bs).map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "read sts response")
+                .set_temporary()
+                .set_source(err)
+        })?;
+
+        if parts.status != StatusCode::OK {
+            return Err(parse_sts_error(&parts, &bs));
+        }
+
+        let result: StsResult = quick_xml::de::from_reader(bs.reader())
+            .map(|resp: AssumeRoleResponse| resp.result)
+            .map_err(new_xml_deserialize_error)?;
+        let expires_in =
+            OffsetDateTime::parse(&result.credentials.expiration, &Rfc3339).map_err(|err| {
+                Error::new(ErrorKind::Unexpected, "parse expiration of sts credential")
+                    .with_context("expiration", &result.credentials.expiration)
+                    .set_source(err)
+            })?;
+
+        let cred = Credential::new(
+            &result.credentials.access_key_id,
+            &result.credentials.secret_access_key,
+        )
+        .with_security_token(&result.credentials.session_token)
+        .with_expires_in(expires_in);
+
+        Ok((cred, expires_in))
+    }
+}
+
+impl AwsCredentialLoad for StsLoader {
+    fn load_credential(&self) -> anyhow::Result<Option<Credential>> {
+        Ok(Some(self.load()?))
+    }
+}
+
+/// Map the error of STS into error kinds.
+///
+/// - Rejected tokens and roles are `PermissionDenied`.
+/// - Other invalid requests like malformed role arn are `ConfigInvalid`.
+fn parse_sts_error(parts: &http::response::Parts, bs: &[u8]) -> Error {
+    let (mut kind, retryable) = parse_error_status(parts.status);
+    let mut message = String::from_utf8_lossy(bs).to_string();
+
+    if let Ok(resp) = quick_xml::de::from_reader::<_, StsErrorResponse>(bs.reader()) {
+        kind = match (parts.status, resp.error.code.as_str()) {
+            (
+                _,
+                "AccessDenied"
+                | "InvalidIdentityToken"
+                | "ExpiredTokenException"
+                | "IDPRejectedClaim"
+                | "InvalidClientTokenId"
+                | "SignatureDoesNotMatch",
+            ) => ErrorKind::PermissionDenied,
+            (StatusCode::BAD_REQUEST, _) => ErrorKind::ConfigInvalid,
+            _ => kind,
+        };
+        message = format!("{}: {}", resp.error.code, resp.error.message);
+    } else if parts.status == StatusCode::BAD_REQUEST {
+        kind = ErrorKind::ConfigInvalid;
+    }
+
+    let mut err = with_error_response_context(Error::new(kind, &message), parts);
+    if retryable {
+        err = err.set_temporary();
+    }
+    err
+}
+
+/// Response of both `AssumeRole` and `AssumeRoleWithWebIdentity`.
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct AssumeRoleResponse {
+    #[serde(alias = "AssumeRoleResult", alias = "AssumeRoleWithWebIdentityResult")]
+    result: StsResult,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct StsResult {
+    credentials: StsCredentials,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct StsCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: String,
+    expiration: String,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct StsErrorResponse {
+    error: StsError,
+}
+
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "PascalCase")]
+struct StsError {
+    code: String,
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use reqsign::AwsConfigLoader;
+    use reqsign::AwsCredentialLoader;
+    use wiremock::matchers::header_exists;
+    use wiremock::matchers::method;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    fn sts_response(action: &str, key: &str, expires_in: Duration) -> ResponseTemplate {
+        let expiration = (OffsetDateTime::now_utc() + expires_in)
+            .format(&Rfc3339)
+            .expect("time must be valid");
+        ResponseTemplate::new(200).set_body_string(format!(
+            r#"<{action}Response xmlns="https://sts.amazonaws.com/doc/2011-06-15/">
+  <{action}Result>
+    <Credentials>
+      <AccessKeyId>{key}</AccessKeyId>
+      <SecretAccessKey>secret_access_key</SecretAccessKey>
+      <SessionToken>session_token</SessionToken>
+      <Expiration>{expiration}</Expiration>
+    </Credentials>
+  </{action}Result>
+</{action}Response>"#
+        ))
+    }
+
+    fn sts_error(status: u16, code: &str) -> ResponseTemplate {
+        ResponseTemplate::new(status).set_body_string(format!(
+            r#"<ErrorResponse xmlns="https://sts.amazonaws.com/doc/2011-06-15/">
+  <Error>
+    <Type>Sender</Type>
+    <Code>{code}</Code>
+    <Message>{code} from mock</Message>
+  </Error>
+  <RequestId>request_id</RequestId>
+</ErrorResponse>"#
+        ))
+    }
+
+    fn new_token_file(name: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("opendal-s3-sts-{name}-{}", std::process::id()));
+        std::fs::write(&path, "web_identity_token\n").expect("write token file must succeed");
+        path.to_string_lossy().to_string()
+    }
+
+    fn new_web_identity_loader(endpoint: &str, token_file: &str) -> Arc<StsLoader> {
+        Arc::new(StsLoader::web_identity(
+            HttpClient::new().unwrap(),
+            endpoint,
+            "arn:aws:iam::123456789012:role/web",
+            "opendal",
+            token_file,
+        ))
+    }
+
+    /// Load the credential in blocking threads so that the mock server
+    /// can make progress.
+    async fn load(loader: &Arc<StsLoader>) -> Result<Credential> {
+        let loader = loader.clone();
+        tokio::task::spawn_blocking(move || loader.load())
+            .await
+            .expect("join must succeed")
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_web_identity() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("Action", "AssumeRoleWithWebIdentity"))
+            .and(query_param("RoleArn", "arn:aws:iam::123456789012:role/web"))
+            .and(query_param("RoleSessionName", "opendal"))
+            .and(query_param("WebIdentityToken", "web_identity_token"))
+            .respond_with(sts_response(
+                "AssumeRoleWithWebIdentity",
+                "web",
+                Duration::hours(1),
+            ))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let loader =
+            new_web_identity_loader(&mock_server.uri(), &new_token_file("test_web_identity"));
+
+        let cred = load(&loader).await.expect("load must succeed");
+        assert_eq!(cred.access_key(), "web");
+        assert_eq!(cred.security_token(), Some("session_token"));
+
+        // The credential is cached until it's about to expire.
+        let cred = load(&loader).await.expect("load must succeed");
+        assert_eq!(cred.access_key(), "web");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_assume_role() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("Action", "AssumeRole"))
+            .and(query_param(
+                "RoleArn",
+                "arn:aws:iam::123456789012:role/test",
+            ))
+            .and(query_param("ExternalId", "external_id"))
+            .and(header_exists("authorization"))
+            .respond_with(sts_response("AssumeRole", "assumed", Duration::hours(1)))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let cfg = AwsConfigLoader::default();
+        cfg.set_region("us-east-1");
+        cfg.set_access_key_id("access_key_id");
+        cfg.set_secret_access_key("secret_access_key");
+        let signer = AwsV4Signer::builder()
+            .service("sts")
+            .config_loader(cfg.clone())
+            .credential_loader(AwsCredentialLoader::new(cfg).with_disable_ec2_metadata())
+            .build()
+            .expect("build signer must succeed");
+        let loader = Arc::new(StsLoader::assume_role(
+            HttpClient::new().unwrap(),
+            &mock_server.uri(),
+            "arn:aws:iam::123456789012:role/test",
+            "opendal",
+            Some("external_id"),
+            signer,
+        ));
+
+        let cred = load(&loader).await.expect("load must succeed");
+        assert_eq!(cred.access_key(), "assumed");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_refresh_near_expiry() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(sts_response(
+                "AssumeRoleWithWebIdentity",
+                "web",
+                Duration::minutes(3),
+            ))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let loader = new_web_identity_loader(
+            &mock_server.uri(),
+            &new_token_file("test_refresh_near_expiry"),
+        );
+
+        // Credentials expire in 3 minutes will be refreshed every time.
+        load(&loader).await.expect("load must succeed");
+        load(&loader).await.expect("load must succeed");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_single_refresh_in_flight() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                sts_response("AssumeRoleWithWebIdentity", "web", Duration::hours(1))
+                    .set_delay(std::time::Duration::from_millis(100)),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let loader = new_web_identity_loader(
+            &mock_server.uri(),
+            &new_token_file("test_single_refresh_in_flight"),
+        );
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let loader = loader.clone();
+                tokio::task::spawn_blocking(move || loader.load())
+            })
+            .collect();
+        for task in tasks {
+            let cred = task
+                .await
+                .expect("join must succeed")
+                .expect("load must succeed");
+            assert_eq!(cred.access_key(), "web");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_refresh_failed() {
+        let cases = [
+            (403, "AccessDenied", ErrorKind::PermissionDenied),
+            (400, "InvalidIdentityToken", ErrorKind::PermissionDenied),
+            (400, "ValidationError", ErrorKind::ConfigInvalid),
+        ];
+
+        for (status, code, kind) in cases {
+            let mock_server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .respond_with(sts_error(status, code))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+
+            let loader =
+                new_web_identity_loader(&mock_server.uri(), &new_token_file("test_refresh_failed"));
+
+            let err = load(&loader).await.expect_err("load must fail");
+            assert_eq!(err.kind(), kind, "{code}: {err}");
+            assert!(err.to_string().contains(code), "{err}");
+        }
+
+        // Token file that can't be read is invalid config.
+        let loader = new_web_identity_loader("http://127.0.0.1:1", "/not/exist/token");
+        let err = load(&loader).await.expect_err("load must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_refresh_failed_with_valid_credential() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(sts_response(
+                "AssumeRoleWithWebIdentity",
+                "web",
+                Duration::minutes(3),
+            ))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(sts_error(503, "ServiceUnavailable"))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let loader = new_web_identity_loader(
+            &mock_server.uri(),
+            &new_token_file("test_refresh_failed_with_valid_credential"),
+        );
+
+        load(&loader).await.expect("load must succeed");
+        // The refresh fails but current credential is still valid.
+        let cred = load(&loader).await.expect("load must succeed");
+        assert_eq!(cred.access_key(), "web");
+    }
+}
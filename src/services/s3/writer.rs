@@ -13,93 +13,198 @@
 // limitations under the License.
 
 use async_trait::async_trait;
+use bytes::Buf;
+use bytes::BufMut;
 use bytes::Bytes;
+use bytes::BytesMut;
+use http::header::CONTENT_ENCODING;
+use http::header::CONTENT_LENGTH;
+use http::header::IF_MATCH;
+use http::header::IF_NONE_MATCH;
+use http::HeaderValue;
+use http::Request;
 use http::StatusCode;
 
+use super::backend::constants;
+use super::backend::format_tagging;
+use super::backend::ChecksumAlgorithm;
 use super::backend::CompleteMultipartUploadRequestPart;
+use super::backend::CompleteMultipartUploadResult;
+use super::backend::InitiateMultipartUploadResult;
 use super::backend::S3Backend;
 use super::error::parse_error;
 use crate::ops::OpWrite;
 use crate::raw::*;
 use crate::*;
 
+/// S3Writer implements the requests of multipart upload, buffering and
+/// concurrent part uploads are handled by [`oio::MultipartUploadWriter`].
+///
+/// If `checksum_algorithm` is set, the checksum of every request will be
+/// sent as trailer of `aws-chunked` body.
 pub struct S3Writer {
     backend: S3Backend,
 
     op: OpWrite,
     path: String,
-
-    upload_id: Option<String>,
-    parts: Vec<CompleteMultipartUploadRequestPart>,
 }
 
 impl S3Writer {
-    pub fn new(backend: S3Backend, op: OpWrite, path: String, upload_id: Option<String>) -> Self {
-        S3Writer {
-            backend,
-            op,
-            path,
-            upload_id,
-            parts: vec![],
-        }
+    pub fn new(backend: S3Backend, op: OpWrite, path: String) -> Self {
+        S3Writer { backend, op, path }
     }
-}
 
-#[async_trait]
-impl oio::Write for S3Writer {
-    async fn write(&mut self, bs: Bytes) -> Result<()> {
-        debug_assert!(
-            self.upload_id.is_none(),
-            "Writer initiated with upload id, but users trying to call write, must be buggy"
+    /// Encode the body with its checksum as trailer if `checksum_algorithm`
+    /// is set, returns the checksum.
+    fn insert_checksum(&self, req: &mut Request<AsyncBody>) -> Result<Option<String>> {
+        let algo = match self.backend.checksum_algorithm {
+            Some(algo) => algo,
+            None => return Ok(None),
+        };
+
+        let bs = match req.body() {
+            AsyncBody::Empty => Bytes::new(),
+            AsyncBody::Bytes(bs) => bs.clone(),
+            AsyncBody::Multipart(..) => {
+                return Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "multipart body can't be uploaded with checksum",
+                )
+                .with_operation("s3::S3Writer"))
+            }
+        };
+
+        let checksum = algo.checksum(&bs);
+        let body = encode_trailer_checksum_body(algo, &checksum, &bs);
+
+        let headers = req.headers_mut();
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static("aws-chunked"));
+        headers.insert(CONTENT_LENGTH, HeaderValue::from(body.len()));
+        headers.insert(
+            constants::X_AMZ_DECODED_CONTENT_LENGTH,
+            HeaderValue::from(bs.len()),
         );
+        headers.insert(
+            constants::X_AMZ_TRAILER,
+            HeaderValue::from_static(algo.header_name()),
+        );
+        headers.insert(
+            constants::X_AMZ_CONTENT_SHA_256,
+            HeaderValue::from_static(constants::STREAMING_UNSIGNED_PAYLOAD_TRAILER),
+        );
+        *req.body_mut() = AsyncBody::Bytes(body);
+
+        Ok(Some(checksum))
+    }
+}
 
+/// Encode content into `aws-chunked` body with its checksum as trailer:
+///
+/// ```text
+/// <hex size>\r\n<content>\r\n0\r\n<checksum header>:<checksum>\r\n\r\n
+/// ```
+///
+/// ref: <https://docs.aws.amazon.com/AmazonS3/latest/API/sigv4-streaming.html>
+pub(super) fn encode_trailer_checksum_body(
+    algo: ChecksumAlgorithm,
+    checksum: &str,
+    content: &[u8],
+) -> Bytes {
+    let mut buf = BytesMut::with_capacity(content.len() + 128);
+    if !content.is_empty() {
+        buf.put_slice(format!("{:x}\r\n", content.len()).as_bytes());
+        buf.put_slice(content);
+        buf.put_slice(b"\r\n");
+    }
+    buf.put_slice(format!("0\r\n{}:{checksum}\r\n\r\n", algo.header_name()).as_bytes());
+    buf.freeze()
+}
+
+#[async_trait]
+impl oio::MultipartUploadWrite for S3Writer {
+    async fn write_once(&self, size: u64, body: AsyncBody) -> Result<RpWrite> {
         let mut req = self.backend.s3_put_object_request(
             &self.path,
-            Some(bs.len()),
+            Some(size as usize),
             self.op.content_type(),
             self.op.content_disposition(),
-            AsyncBody::Bytes(bs),
+            body,
         )?;
 
-        self.backend
-            .signer
-            .sign(&mut req)
-            .map_err(new_request_sign_error)?;
+        // Only put the object if it doesn't exist.
+        if self.op.if_not_exists() {
+            req.headers_mut()
+                .insert(IF_NONE_MATCH, HeaderValue::from_static("*"));
+        }
+        // Only put the object if its etag matches.
+        if let Some(etag) = self.op.if_match() {
+            let etag =
+                HeaderValue::from_str(etag).map_err(|e| new_request_build_error(e.into()))?;
+            req.headers_mut().insert(IF_MATCH, etag);
+        }
+        // Set tags of the object.
+        if let Some(tagging) = format_tagging(self.op.tags())? {
+            let tagging =
+                HeaderValue::from_str(&tagging).map_err(|e| new_request_build_error(e.into()))?;
+            req.headers_mut().insert(constants::X_AMZ_TAGGING, tagging);
+        }
+        // Set checksum of the object.
+        self.insert_checksum(&mut req)?;
 
-        let resp = self.backend.client.send_async(req).await?;
+        let resp = self.backend.send(req).await?;
 
         let status = resp.status();
 
         match status {
             StatusCode::CREATED | StatusCode::OK => {
+                let rp = parse_into_write_reply(resp.headers(), constants::X_AMZ_VERSION_ID)?;
                 resp.into_body().consume().await?;
-                Ok(())
+                Ok(rp)
             }
             _ => Err(parse_error(resp).await?),
         }
     }
 
-    async fn append(&mut self, bs: Bytes) -> Result<()> {
-        let upload_id = self.upload_id.as_ref().expect(
-            "Writer doesn't have upload id, but users trying to call append, must be buggy",
-        );
-        // AWS S3 requires part number must between [1..=10000]
-        let part_number = self.parts.len() + 1;
+    async fn initiate_part(&self) -> Result<String> {
+        let tagging = format_tagging(self.op.tags())?;
+
+        let resp = self
+            .backend
+            .s3_initiate_multipart_upload(&self.path, tagging.as_deref())
+            .await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+
+                let result: InitiateMultipartUploadResult =
+                    quick_xml::de::from_reader(bs.reader()).map_err(new_xml_deserialize_error)?;
 
+                Ok(result.upload_id)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn write_part(
+        &self,
+        upload_id: &str,
+        part_number: usize,
+        size: u64,
+        body: AsyncBody,
+    ) -> Result<oio::MultipartUploadPart> {
         let mut req = self.backend.s3_upload_part_request(
             &self.path,
             upload_id,
             part_number,
-            Some(bs.len() as u64),
-            AsyncBody::Bytes(bs),
+            Some(size),
+            body,
         )?;
+        let checksum = self.insert_checksum(&mut req)?;
 
-        self.backend
-            .signer
-            .sign(&mut req)
-            .map_err(new_request_sign_error)?;
-
-        let resp = self.backend.client.send_async(req).await?;
+        let resp = self.backend.send(req).await?;
 
         let status = resp.status();
 
@@ -116,33 +221,72 @@ impl oio::Write for S3Writer {
 
                 resp.into_body().consume().await?;
 
-                self.parts
-                    .push(CompleteMultipartUploadRequestPart { part_number, etag });
-
-                Ok(())
+                let part = oio::MultipartUploadPart::new(part_number, etag);
+                Ok(match checksum {
+                    Some(checksum) => part.with_checksum(checksum),
+                    None => part,
+                })
             }
             _ => Err(parse_error(resp).await?),
         }
     }
 
-    async fn close(&mut self) -> Result<()> {
-        let upload_id = if let Some(upload_id) = &self.upload_id {
-            upload_id
-        } else {
-            return Ok(());
-        };
+    async fn complete_part(
+        &self,
+        upload_id: &str,
+        parts: &[oio::MultipartUploadPart],
+    ) -> Result<RpWrite> {
+        let parts: Vec<_> = parts
+            .iter()
+            .map(|part| {
+                CompleteMultipartUploadRequestPart::new(
+                    part.part_number,
+                    part.etag.clone(),
+                    self.backend.checksum_algorithm.zip(part.checksum.clone()),
+                )
+            })
+            .collect();
 
         let resp = self
             .backend
-            .s3_complete_multipart_upload(&self.path, upload_id, &self.parts)
+            .s3_complete_multipart_upload(&self.path, upload_id, &parts, &self.op)
             .await?;
 
         let status = resp.status();
 
         match status {
             StatusCode::OK => {
-                resp.into_body().consume().await?;
+                // The etag of multipart upload is only returned in body,
+                // in the form of `"<hash>-<parts>"`.
+                let mut rp = parse_into_write_reply(resp.headers(), constants::X_AMZ_VERSION_ID)?;
+                let bs = resp.into_body().bytes().await?;
+
+                // Some s3 compatible services don't return the result.
+                if !bs.is_empty() {
+                    let result: CompleteMultipartUploadResult =
+                        quick_xml::de::from_reader(bs.reader())
+                            .map_err(new_xml_deserialize_error)?;
+                    if !result.etag.is_empty() {
+                        rp = rp.with_etag(&result.etag);
+                    }
+                }
+
+                Ok(rp)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
 
+    async fn abort_part(&self, upload_id: &str) -> Result<()> {
+        let resp = self
+            .backend
+            .s3_abort_multipart_upload(&self.path, upload_id)
+            .await?;
+
+        match resp.status() {
+            // s3 returns code 204 if abort succeeds.
+            StatusCode::NO_CONTENT => {
+                resp.into_body().consume().await?;
                 Ok(())
             }
             _ => Err(parse_error(resp).await?),
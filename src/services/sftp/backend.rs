@@ -0,0 +1,1006 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::min;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bb8::PooledConnection;
+use bb8::RunError;
+use http::Uri;
+use log::debug;
+use log::warn;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use russh::client;
+use russh::client::Handle;
+use russh_keys::key::PublicKey;
+use russh_sftp::client::error::Error as SftpError;
+use russh_sftp::client::RawSftpSession;
+use russh_sftp::client::SftpSession;
+use russh_sftp::protocol::FileAttributes;
+use russh_sftp::protocol::OpenFlags;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::OnceCell;
+
+use super::error::is_channel_error;
+use super::error::parse_sftp_error;
+use super::error::parse_ssh_error;
+use super::pager::parse_mtime;
+use super::pager::SftpPager;
+use super::reader::SftpReader;
+use super::writer::SftpWriter;
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// Default max connections of the pool.
+const DEFAULT_MAX_CONNECTIONS: u32 = 8;
+/// Default number of outstanding read requests of every reader.
+const DEFAULT_READ_CONCURRENCY: usize = 16;
+
+/// Pools shared by all backends that connect to the same server with the
+/// same credentials and pool options.
+static POOLS: Lazy<Mutex<HashMap<PoolKey, bb8::Pool<Manager>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct PoolKey {
+    manager: Manager,
+    max_connections: u32,
+    idle_timeout: Option<Duration>,
+}
+
+/// SFTP services support.
+///
+/// # Capabilities
+///
+/// This service can be used to:
+///
+/// - [x] read
+/// - [x] write
+/// - [x] list
+/// - [ ] ~~scan~~
+/// - [ ] ~~presign~~
+/// - [ ] blocking
+///
+/// # Configuration
+///
+/// - `endpoint`: Set the endpoint for connection, like `ssh://127.0.0.1:22`
+/// - `root`: Set the work directory for backend, must be absolute path on server
+/// - `user`: Set the login user
+/// - `password`: Set the password for password authentication
+/// - `key`: Set the path of private key for public key authentication
+/// - `known_hosts_strategy`: Set the verification policy of server key, available values are `strict`, `add` and `accept`
+/// - `max_connections`: Set the max connections of the pool, default to 8
+/// - `idle_timeout`: Set the idle timeout in seconds of pooled connections
+/// - `read_concurrency`: Set the max outstanding read requests of every reader, default to 16
+///
+/// # Notes
+///
+/// Connections are pooled and shared by all operators that connect to the
+/// same server with the same user, credentials and pool options. Sftp
+/// channels that hit connection level errors will be discarded and
+/// reopened while others in the pool are still in use.
+///
+/// Every reader sends up to `read_concurrency` read requests of 32KiB
+/// at the same time to make full use of high latency links.
+///
+/// You can refer to [`SftpBuilder`]'s docs for more information
+///
+/// # Example
+///
+/// ## Via Builder
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::services::Sftp;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     // create backend builder
+///     let mut builder = Sftp::default();
+///
+///     builder.endpoint("ssh://127.0.0.1:22");
+///     builder.root("/home/opendal/data");
+///     builder.user("opendal");
+///     builder.key("/home/opendal/.ssh/id_ed25519");
+///
+///     let op: Operator = Operator::new(builder)?.finish();
+///     Ok(())
+/// }
+/// ```
+#[derive(Default)]
+pub struct SftpBuilder {
+    endpoint: Option<String>,
+    root: Option<String>,
+    user: Option<String>,
+    password: Option<String>,
+    key: Option<String>,
+    known_hosts_strategy: Option<String>,
+    max_connections: Option<u32>,
+    idle_timeout: Option<Duration>,
+    read_concurrency: Option<usize>,
+}
+
+impl Debug for SftpBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("Builder");
+
+        ds.field("endpoint", &self.endpoint);
+        ds.field("root", &self.root);
+        ds.field("user", &self.user);
+        if self.password.is_some() {
+            ds.field("password", &"<redacted>");
+        }
+        ds.field("key", &self.key);
+        ds.field("known_hosts_strategy", &self.known_hosts_strategy);
+        ds.field("max_connections", &self.max_connections);
+        ds.field("idle_timeout", &self.idle_timeout);
+        ds.field("read_concurrency", &self.read_concurrency);
+
+        ds.finish()
+    }
+}
+
+impl SftpBuilder {
+    /// set endpoint for sftp backend.
+    ///
+    /// Endpoint could be `ssh://host:port`, `sftp://host:port` or `host:port`,
+    /// port will be `22` if not set.
+    pub fn endpoint(&mut self, endpoint: &str) -> &mut Self {
+        self.endpoint = if endpoint.is_empty() {
+            None
+        } else {
+            Some(endpoint.to_string())
+        };
+
+        self
+    }
+
+    /// set root path for sftp backend.
+    ///
+    /// Root must be an absolute path on the server.
+    pub fn root(&mut self, root: &str) -> &mut Self {
+        self.root = if root.is_empty() {
+            None
+        } else {
+            Some(root.to_string())
+        };
+
+        self
+    }
+
+    /// set user for sftp backend.
+    pub fn user(&mut self, user: &str) -> &mut Self {
+        self.user = if user.is_empty() {
+            None
+        } else {
+            Some(user.to_string())
+        };
+
+        self
+    }
+
+    /// set password for sftp backend.
+    ///
+    /// Password will be used for password authentication if `key` is not set.
+    pub fn password(&mut self, password: &str) -> &mut Self {
+        self.password = if password.is_empty() {
+            None
+        } else {
+            Some(password.to_string())
+        };
+
+        self
+    }
+
+    /// set the path of private key for sftp backend.
+    ///
+    /// Public key authentication will be used if key is set.
+    pub fn key(&mut self, key: &str) -> &mut Self {
+        self.key = if key.is_empty() {
+            None
+        } else {
+            Some(key.to_string())
+        };
+
+        self
+    }
+
+    /// set the verification policy of server key for sftp backend.
+    ///
+    /// - `strict`: Server key must be found in `~/.ssh/known_hosts`, this is the default.
+    /// - `add`: Unknown server key will be accepted and added into `~/.ssh/known_hosts`,
+    ///   changed server key will still be rejected.
+    /// - `accept`: All server keys will be accepted without verification.
+    pub fn known_hosts_strategy(&mut self, strategy: &str) -> &mut Self {
+        self.known_hosts_strategy = if strategy.is_empty() {
+            None
+        } else {
+            Some(strategy.to_string())
+        };
+
+        self
+    }
+
+    /// set the max connections of the pool for sftp backend.
+    ///
+    /// Connections will be reused across operations, readers and writers
+    /// hold a connection until they are dropped.
+    pub fn max_connections(&mut self, max_connections: u32) -> &mut Self {
+        self.max_connections = Some(max_connections);
+
+        self
+    }
+
+    /// set the idle timeout of pooled connections for sftp backend.
+    ///
+    /// Connections idle for longer than this will be closed.
+    pub fn idle_timeout(&mut self, idle_timeout: Duration) -> &mut Self {
+        self.idle_timeout = Some(idle_timeout);
+
+        self
+    }
+
+    /// set the max outstanding read requests of every reader for sftp backend.
+    ///
+    /// Larger value helps saturate links with high latency.
+    pub fn read_concurrency(&mut self, read_concurrency: usize) -> &mut Self {
+        self.read_concurrency = Some(read_concurrency);
+
+        self
+    }
+}
+
+impl Builder for SftpBuilder {
+    const SCHEME: Scheme = Scheme::Sftp;
+    type Accessor = SftpBackend;
+
+    fn build(&mut self) -> Result<Self::Accessor> {
+        debug!("sftp backend build started: {:?}", &self);
+        let endpoint = match &self.endpoint {
+            None => return Err(Error::new(ErrorKind::ConfigInvalid, "endpoint is empty")),
+            Some(v) => v,
+        };
+
+        let endpoint_uri = match endpoint.parse::<Uri>() {
+            Err(e) => {
+                return Err(Error::new(ErrorKind::ConfigInvalid, "endpoint is invalid")
+                    .with_context("endpoint", endpoint)
+                    .set_source(e));
+            }
+            Ok(uri) => uri,
+        };
+
+        match endpoint_uri.scheme_str() {
+            Some("ssh") | Some("sftp") | None => {}
+            Some(s) => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "endpoint is unsupported or invalid",
+                )
+                .with_context("endpoint", s));
+            }
+        }
+
+        let host = match endpoint_uri.host() {
+            Some(host) if !host.is_empty() => host.to_string(),
+            _ => {
+                return Err(
+                    Error::new(ErrorKind::ConfigInvalid, "endpoint doesn't have host")
+                        .with_context("endpoint", endpoint),
+                )
+            }
+        };
+        let port = endpoint_uri.port_u16().unwrap_or(22);
+
+        let root = normalize_root(&self.root.take().unwrap_or_default());
+
+        let user = match &self.user {
+            None => return Err(Error::new(ErrorKind::ConfigInvalid, "user is empty")),
+            Some(v) => v.clone(),
+        };
+
+        let known_hosts_strategy = match &self.known_hosts_strategy {
+            None => KnownHostsStrategy::Strict,
+            Some(v) => KnownHostsStrategy::from_str(v)?,
+        };
+
+        let max_connections = self.max_connections.unwrap_or(DEFAULT_MAX_CONNECTIONS);
+        if max_connections == 0 {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "max_connections must be greater than 0",
+            ));
+        }
+
+        let read_concurrency = self.read_concurrency.unwrap_or(DEFAULT_READ_CONCURRENCY);
+        if read_concurrency == 0 {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "read_concurrency must be greater than 0",
+            ));
+        }
+
+        debug!("sftp backend finished: {:?}", &self);
+
+        Ok(SftpBackend {
+            root,
+            read_concurrency,
+            pool_key: PoolKey {
+                manager: Manager {
+                    host,
+                    port,
+                    user,
+                    password: self.password.clone(),
+                    key: self.key.as_ref().map(PathBuf::from),
+                    known_hosts_strategy,
+                },
+                max_connections,
+                idle_timeout: self.idle_timeout,
+            },
+            pool: once_cell::sync::OnceCell::new(),
+        })
+    }
+
+    fn from_map(map: HashMap<String, String>) -> Self {
+        let mut builder = SftpBuilder::default();
+
+        map.get("root").map(|v| builder.root(v));
+        map.get("endpoint").map(|v| builder.endpoint(v));
+        map.get("user").map(|v| builder.user(v));
+        map.get("password").map(|v| builder.password(v));
+        map.get("key").map(|v| builder.key(v));
+        map.get("known_hosts_strategy")
+            .map(|v| builder.known_hosts_strategy(v));
+        map.get("max_connections")
+            .and_then(|v| v.parse::<u32>().ok())
+            .map(|v| builder.max_connections(v));
+        map.get("idle_timeout")
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(|v| builder.idle_timeout(Duration::from_secs(v)));
+        map.get("read_concurrency")
+            .and_then(|v| v.parse::<usize>().ok())
+            .map(|v| builder.read_concurrency(v));
+
+        builder
+    }
+}
+
+/// Verification policy of server key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum KnownHostsStrategy {
+    /// Server key must be found in known hosts.
+    Strict,
+    /// Unknown server key will be added into known hosts.
+    Add,
+    /// All server keys will be accepted.
+    Accept,
+}
+
+impl FromStr for KnownHostsStrategy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "strict" => Ok(KnownHostsStrategy::Strict),
+            "add" => Ok(KnownHostsStrategy::Add),
+            "accept" => Ok(KnownHostsStrategy::Accept),
+            _ => Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "known_hosts_strategy value is invalid",
+            )
+            .with_context("known_hosts_strategy", s)),
+        }
+    }
+}
+
+/// Handler verifies server key while connecting.
+struct Handler {
+    host: String,
+    port: u16,
+    known_hosts_strategy: KnownHostsStrategy,
+}
+
+#[async_trait]
+impl client::Handler for Handler {
+    type Error = russh::Error;
+
+    async fn check_server_key(
+        self,
+        server_public_key: &PublicKey,
+    ) -> std::result::Result<(Self, bool), Self::Error> {
+        if self.known_hosts_strategy == KnownHostsStrategy::Accept {
+            return Ok((self, true));
+        }
+
+        let accepted = match russh_keys::check_known_hosts(&self.host, self.port, server_public_key)
+        {
+            Ok(true) => true,
+            Ok(false) if self.known_hosts_strategy == KnownHostsStrategy::Add => {
+                if let Err(err) =
+                    russh_keys::learn_known_hosts(&self.host, self.port, server_public_key)
+                {
+                    warn!(
+                        "add server key of {} into known hosts failed: {err:?}",
+                        self.host
+                    );
+                }
+                true
+            }
+            Ok(false) => false,
+            // Changed server key will always be rejected.
+            Err(err) => {
+                warn!("check server key of {} failed: {err:?}", self.host);
+                false
+            }
+        };
+
+        Ok((self, accepted))
+    }
+}
+
+/// Connection is a sftp session on an authenticated ssh connection.
+pub struct Connection {
+    handle: Handle<Handler>,
+    pub sftp: SftpSession,
+    /// Raw sftp session on another channel which allows sending multiple
+    /// requests at the same time, will be opened at the first read.
+    raw: OnceCell<Arc<RawSftpSession>>,
+    /// Set while channel level errors happened, the connection will be
+    /// discarded instead of returning to the pool.
+    broken: AtomicBool,
+}
+
+impl Connection {
+    /// Parse the sftp error and mark the connection as broken if the
+    /// channel is not usable anymore.
+    pub fn parse_error(&self, err: SftpError) -> Error {
+        if is_channel_error(&err) {
+            self.broken.store(true, Ordering::Relaxed);
+        }
+        parse_sftp_error(err)
+    }
+
+    /// Get the raw sftp session, open it if not exist.
+    pub async fn raw_sftp(&self) -> Result<Arc<RawSftpSession>> {
+        let raw = self
+            .raw
+            .get_or_try_init(|| async {
+                let channel = self
+                    .handle
+                    .channel_open_session()
+                    .await
+                    .map_err(parse_ssh_error)?;
+                channel
+                    .request_subsystem(true, "sftp")
+                    .await
+                    .map_err(parse_ssh_error)?;
+
+                let raw = RawSftpSession::new(channel.into_stream());
+                raw.init().await.map_err(|err| self.parse_error(err))?;
+
+                Ok::<_, Error>(Arc::new(raw))
+            })
+            .await?;
+
+        Ok(raw.clone())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Manager {
+    host: String,
+    port: u16,
+    user: String,
+    password: Option<String>,
+    key: Option<PathBuf>,
+    known_hosts_strategy: KnownHostsStrategy,
+}
+
+#[async_trait]
+impl bb8::ManageConnection for Manager {
+    type Connection = Connection;
+    type Error = Error;
+
+    async fn connect(&self) -> Result<Self::Connection> {
+        let config = Arc::new(client::Config::default());
+        let handler = Handler {
+            host: self.host.clone(),
+            port: self.port,
+            known_hosts_strategy: self.known_hosts_strategy,
+        };
+
+        let mut handle = client::connect(config, (self.host.as_str(), self.port), handler)
+            .await
+            .map_err(parse_ssh_error)?;
+
+        let authenticated = match &self.key {
+            Some(key) => {
+                let key = russh_keys::load_secret_key(key, None).map_err(|e| {
+                    Error::new(ErrorKind::ConfigInvalid, "load private key failed")
+                        .with_context("key", key.to_string_lossy())
+                        .set_source(e)
+                })?;
+                handle
+                    .authenticate_publickey(&self.user, Arc::new(key))
+                    .await
+            }
+            None => {
+                handle
+                    .authenticate_password(&self.user, self.password.as_deref().unwrap_or(""))
+                    .await
+            }
+        }
+        .map_err(parse_ssh_error)?;
+        if !authenticated {
+            return Err(
+                Error::new(ErrorKind::PermissionDenied, "ssh authentication failed")
+                    .with_context("user", &self.user),
+            );
+        }
+
+        let channel = handle
+            .channel_open_session()
+            .await
+            .map_err(parse_ssh_error)?;
+        channel
+            .request_subsystem(true, "sftp")
+            .await
+            .map_err(parse_ssh_error)?;
+        let sftp = SftpSession::new(channel.into_stream())
+            .await
+            .map_err(parse_sftp_error)?;
+
+        Ok(Connection {
+            handle,
+            sftp,
+            raw: OnceCell::new(),
+            broken: AtomicBool::new(false),
+        })
+    }
+
+    async fn is_valid(&self, conn: &mut Self::Connection) -> Result<()> {
+        conn.sftp
+            .canonicalize(".")
+            .await
+            .map(|_| ())
+            .map_err(|err| conn.parse_error(err))
+    }
+
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        conn.broken.load(Ordering::Relaxed) || conn.handle.is_closed()
+    }
+}
+
+/// Backend is used to serve `Accessor` support for sftp.
+#[derive(Clone)]
+pub struct SftpBackend {
+    root: String,
+    read_concurrency: usize,
+    pool_key: PoolKey,
+    pool: once_cell::sync::OnceCell<bb8::Pool<Manager>>,
+}
+
+impl Debug for SftpBackend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Backend")
+            .field("root", &self.root)
+            .field("host", &self.pool_key.manager.host)
+            .field("port", &self.pool_key.manager.port)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Accessor for SftpBackend {
+    type Reader = SftpReader;
+    type BlockingReader = ();
+    type Writer = SftpWriter;
+    type BlockingWriter = ();
+    type Pager = SftpPager;
+    type BlockingPager = ();
+
+    fn info(&self) -> AccessorInfo {
+        let mut am = AccessorInfo::default();
+        am.set_scheme(Scheme::Sftp)
+            .set_root(&self.root)
+            .set_capabilities(
+                AccessorCapability::Read | AccessorCapability::Write | AccessorCapability::List,
+            )
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_seek: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                ..Default::default()
+            });
+
+        am
+    }
+
+    async fn create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        let conn = self.sftp_connect(Operation::Create).await?;
+        let p = build_rooted_abs_path(&self.root, path);
+
+        if args.mode() == EntryMode::DIR {
+            self.sftp_create_dir_all(&conn, &p).await?;
+
+            return Ok(RpCreate::default());
+        }
+
+        self.sftp_create_dir_all(&conn, get_parent(&p)).await?;
+        let mut file = conn
+            .sftp
+            .create(&p)
+            .await
+            .map_err(|err| conn.parse_error(err))?;
+        file.shutdown().await.map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "close sftp file")
+                .with_context("path", &p)
+                .set_source(err)
+        })?;
+
+        Ok(RpCreate::default())
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let conn = self.sftp_connect(Operation::Read).await?;
+        let p = build_rooted_abs_path(&self.root, path);
+
+        let raw = conn.raw_sftp().await?;
+        let handle = raw
+            .open(&p, OpenFlags::READ, FileAttributes::default())
+            .await
+            .map_err(|err| conn.parse_error(err))?
+            .handle;
+        let meta = match raw.fstat(&handle).await {
+            Ok(attrs) => attrs.attrs,
+            Err(err) => {
+                let _ = raw.close(&handle).await;
+                return Err(conn.parse_error(err));
+            }
+        };
+        if meta.is_dir() {
+            let _ = raw.close(&handle).await;
+            return Err(Error::new(
+                ErrorKind::IsADirectory,
+                "given path is a directory",
+            ));
+        }
+        let total_length = meta.size.unwrap_or_default();
+
+        let br = args.range();
+        let (start, end) = match (br.offset(), br.size()) {
+            // Read a specific range.
+            (Some(offset), Some(size)) => (offset, min(offset + size, total_length)),
+            // Read from offset.
+            (Some(offset), None) => (offset, total_length),
+            // Read the last size bytes.
+            (None, Some(size)) => (total_length.saturating_sub(size), total_length),
+            // Read the whole file.
+            (None, None) => (0, total_length),
+        };
+        let end = end.max(start);
+
+        let r = SftpReader::new(conn, raw, handle, p, start, end, self.read_concurrency);
+
+        Ok((RpRead::new(end - start), r))
+    }
+
+    async fn write(&self, path: &str, _: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let conn = self.sftp_connect(Operation::Write).await?;
+        let p = build_rooted_abs_path(&self.root, path);
+
+        self.sftp_create_dir_all(&conn, get_parent(&p)).await?;
+        let file = conn
+            .sftp
+            .create(&p)
+            .await
+            .map_err(|err| conn.parse_error(err))?;
+
+        Ok((RpWrite::new(), SftpWriter::new(conn, file, p)))
+    }
+
+    async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+        // root dir, return default Metadata with Dir EntryMode.
+        if path == "/" {
+            return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
+        }
+
+        let conn = self.sftp_connect(Operation::Stat).await?;
+        let p = build_rooted_abs_path(&self.root, path);
+
+        let meta = conn
+            .sftp
+            .metadata(p.trim_end_matches('/'))
+            .await
+            .map_err(|err| conn.parse_error(err))?;
+
+        let mode = if meta.is_dir() {
+            EntryMode::DIR
+        } else if meta.is_regular() {
+            EntryMode::FILE
+        } else {
+            EntryMode::Unknown
+        };
+        let mut m = Metadata::new(mode);
+        if let Some(size) = meta.size {
+            m.set_content_length(size);
+        }
+        if let Some(mtime) = meta.mtime {
+            m.set_last_modified(parse_mtime(mtime)?);
+        }
+
+        Ok(RpStat::new(m))
+    }
+
+    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        let conn = self.sftp_connect(Operation::Delete).await?;
+        let p = build_rooted_abs_path(&self.root, path);
+
+        let res = if path.ends_with('/') {
+            conn.sftp.remove_dir(p.trim_end_matches('/')).await
+        } else {
+            conn.sftp.remove_file(&p).await
+        };
+
+        match res.map_err(|err| conn.parse_error(err)) {
+            Ok(_) => Ok(RpDelete::default()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(RpDelete::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        let conn = self.sftp_connect(Operation::List).await?;
+        let p = build_rooted_abs_path(&self.root, path);
+
+        let entries = match conn
+            .sftp
+            .read_dir(&p)
+            .await
+            .map_err(|err| conn.parse_error(err))
+        {
+            Ok(entries) => entries.collect(),
+            // List a not exist dir should return empty.
+            Err(err) if err.kind() == ErrorKind::NotFound => vec![],
+            Err(err) => return Err(err),
+        };
+
+        Ok((
+            RpList::default(),
+            SftpPager::new(if path == "/" { "" } else { path }, entries, args.limit()),
+        ))
+    }
+}
+
+impl SftpBackend {
+    /// Get the pool shared by backends with the same pool key.
+    fn pool(&self) -> &bb8::Pool<Manager> {
+        self.pool.get_or_init(|| {
+            POOLS
+                .lock()
+                .entry(self.pool_key.clone())
+                .or_insert_with(|| {
+                    let mut builder = bb8::Pool::builder().max_size(self.pool_key.max_connections);
+                    if let Some(idle_timeout) = self.pool_key.idle_timeout {
+                        builder = builder.idle_timeout(Some(idle_timeout));
+                    }
+                    // Connections will be established while getting from pool.
+                    builder.build_unchecked(self.pool_key.manager.clone())
+                })
+                .clone()
+        })
+    }
+
+    pub async fn sftp_connect(&self, _: Operation) -> Result<PooledConnection<'static, Manager>> {
+        self.pool().get_owned().await.map_err(|err| match err {
+            RunError::User(err) => err,
+            RunError::TimedOut => {
+                Error::new(ErrorKind::Unexpected, "connection request: timeout").set_temporary()
+            }
+        })
+    }
+
+    /// Create given dir and all its parents if not exist.
+    async fn sftp_create_dir_all(&self, conn: &Connection, dir: &str) -> Result<()> {
+        let mut p = String::new();
+
+        for seg in dir.split_inclusive('/') {
+            p.push_str(seg);
+            if p == "/" {
+                continue;
+            }
+
+            let path = p.trim_end_matches('/');
+            match conn
+                .sftp
+                .metadata(path)
+                .await
+                .map_err(|err| conn.parse_error(err))
+            {
+                Ok(meta) if meta.is_dir() => continue,
+                Ok(_) => {
+                    return Err(Error::new(
+                        ErrorKind::NotADirectory,
+                        "parent path is not a directory",
+                    )
+                    .with_context("path", path))
+                }
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+
+            if let Err(err) = conn.sftp.create_dir(path).await {
+                // The dir could be created by others at the same time.
+                let exists = conn
+                    .sftp
+                    .metadata(path)
+                    .await
+                    .map(|meta| meta.is_dir())
+                    .unwrap_or_default();
+                if !exists {
+                    return Err(conn.parse_error(err));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build() {
+        let cases = vec![
+            ("ssh scheme", "ssh://sftp_server.local", true),
+            (
+                "sftp scheme with port",
+                "sftp://sftp_server.local:2222",
+                true,
+            ),
+            ("no scheme", "sftp_server.local:2222", true),
+            ("invalid scheme", "ftp://sftp_server.local", false),
+        ];
+
+        for (name, endpoint, ok) in cases {
+            let mut builder = SftpBuilder::default();
+            builder.endpoint(endpoint).user("opendal");
+            let res = builder.build();
+            assert_eq!(res.is_ok(), ok, "{name}");
+            if let Err(err) = res {
+                assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "{name}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_with_default_port() {
+        let mut builder = SftpBuilder::default();
+        builder.endpoint("ssh://sftp_server.local").user("opendal");
+        let backend = builder.build().expect("build must succeed");
+
+        assert_eq!(backend.pool_key.manager.host, "sftp_server.local");
+        assert_eq!(backend.pool_key.manager.port, 22);
+        assert_eq!(
+            backend.pool_key.manager.known_hosts_strategy,
+            KnownHostsStrategy::Strict
+        );
+    }
+
+    #[test]
+    fn test_build_with_invalid_options() {
+        type Case = (&'static str, fn(&mut SftpBuilder));
+        let cases: Vec<Case> = vec![
+            ("user is empty", |_| {}),
+            ("known_hosts_strategy is invalid", |b| {
+                b.user("opendal").known_hosts_strategy("trust");
+            }),
+            ("max_connections is zero", |b| {
+                b.user("opendal").max_connections(0);
+            }),
+            ("read_concurrency is zero", |b| {
+                b.user("opendal").read_concurrency(0);
+            }),
+        ];
+
+        for (name, f) in cases {
+            let mut builder = SftpBuilder::default();
+            builder.endpoint("ssh://sftp_server.local");
+            f(&mut builder);
+
+            let err = builder.build().expect_err(name);
+            assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_known_hosts_strategy_from_str() {
+        assert_eq!(
+            KnownHostsStrategy::from_str("Strict").unwrap(),
+            KnownHostsStrategy::Strict
+        );
+        assert_eq!(
+            KnownHostsStrategy::from_str("add").unwrap(),
+            KnownHostsStrategy::Add
+        );
+        assert_eq!(
+            KnownHostsStrategy::from_str("ACCEPT").unwrap(),
+            KnownHostsStrategy::Accept
+        );
+    }
+
+    #[test]
+    fn test_from_map() {
+        let mut map = HashMap::new();
+        map.insert(
+            "endpoint".to_string(),
+            "ssh://sftp_server.local".to_string(),
+        );
+        map.insert("user".to_string(), "opendal".to_string());
+        map.insert("idle_timeout".to_string(), "60".to_string());
+        map.insert("read_concurrency".to_string(), "4".to_string());
+
+        let backend = SftpBuilder::from_map(map)
+            .build()
+            .expect("build must succeed");
+        assert_eq!(backend.pool_key.idle_timeout, Some(Duration::from_secs(60)));
+        assert_eq!(backend.read_concurrency, 4);
+        assert_eq!(backend.pool_key.max_connections, DEFAULT_MAX_CONNECTIONS);
+    }
+
+    #[tokio::test]
+    async fn test_pool_shared_by_same_credentials() {
+        let build = |user: &str| {
+            let mut builder = SftpBuilder::default();
+            builder
+                .endpoint("ssh://pool_shared.local")
+                .user(user)
+                .password("secret")
+                .known_hosts_strategy("accept");
+            builder.build().expect("build must succeed")
+        };
+
+        let (a, b, c) = (build("opendal"), build("opendal"), build("others"));
+        assert!(a.pool_key == b.pool_key);
+        assert!(a.pool_key != c.pool_key);
+
+        // Pool will be created without connecting.
+        let _ = a.pool();
+        assert!(POOLS.lock().contains_key(&b.pool_key));
+        assert!(!POOLS.lock().contains_key(&c.pool_key));
+    }
+}
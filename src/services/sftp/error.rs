@@ -0,0 +1,77 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use russh_sftp::client::error::Error as SftpError;
+use russh_sftp::protocol::StatusCode;
+
+use crate::Error;
+use crate::ErrorKind;
+
+/// Parse errors returned by sftp session.
+pub fn parse_sftp_error(err: SftpError) -> Error {
+    let (kind, retryable) = match &err {
+        SftpError::Status(status) => match status.status_code {
+            StatusCode::NoSuchFile => (ErrorKind::NotFound, false),
+            StatusCode::PermissionDenied => (ErrorKind::PermissionDenied, false),
+            StatusCode::OpUnsupported => (ErrorKind::Unsupported, false),
+            StatusCode::NoConnection | StatusCode::ConnectionLost => (ErrorKind::Unexpected, true),
+            _ => (ErrorKind::Unexpected, false),
+        },
+        // Allow retry for io errors and timeout.
+        SftpError::IO(_) | SftpError::Timeout => (ErrorKind::Unexpected, true),
+        _ => (ErrorKind::Unexpected, false),
+    };
+
+    let mut err = Error::new(kind, "sftp error").set_source(err);
+
+    if retryable {
+        err = err.set_temporary();
+    }
+
+    err
+}
+
+/// Check if the error is caused by the sftp channel itself instead of the
+/// requested file, the session should be discarded after that.
+pub fn is_channel_error(err: &SftpError) -> bool {
+    match err {
+        SftpError::Status(status) => matches!(
+            status.status_code,
+            StatusCode::NoConnection | StatusCode::ConnectionLost
+        ),
+        _ => true,
+    }
+}
+
+/// Parse errors returned by ssh connection.
+pub fn parse_ssh_error(err: russh::Error) -> Error {
+    let (kind, retryable) = match &err {
+        russh::Error::UnknownKey => (ErrorKind::PermissionDenied, false),
+        russh::Error::NotAuthenticated => (ErrorKind::PermissionDenied, false),
+        // Allow retry for network errors.
+        russh::Error::IO(_)
+        | russh::Error::Disconnect
+        | russh::Error::ConnectionTimeout
+        | russh::Error::HUP => (ErrorKind::Unexpected, true),
+        _ => (ErrorKind::Unexpected, false),
+    };
+
+    let mut err = Error::new(kind, "ssh error").set_source(err);
+
+    if retryable {
+        err = err.set_temporary();
+    }
+
+    err
+}
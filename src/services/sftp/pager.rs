@@ -0,0 +1,88 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::vec::IntoIter;
+
+use async_trait::async_trait;
+use russh_sftp::client::fs::DirEntry;
+use time::OffsetDateTime;
+
+use crate::raw::*;
+use crate::*;
+
+pub struct SftpPager {
+    path: String,
+    size: usize,
+    entries: IntoIter<DirEntry>,
+}
+
+impl SftpPager {
+    pub fn new(path: &str, entries: Vec<DirEntry>, limit: Option<usize>) -> Self {
+        Self {
+            path: path.to_string(),
+            size: limit.unwrap_or(1000),
+            entries: entries.into_iter(),
+        }
+    }
+}
+
+#[async_trait]
+impl oio::Page for SftpPager {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let mut oes: Vec<oio::Entry> = Vec::with_capacity(self.size);
+
+        while oes.len() < self.size {
+            let de = match self.entries.next() {
+                Some(de) => de,
+                None => break,
+            };
+
+            let name = de.file_name();
+            if name == "." || name == ".." {
+                continue;
+            }
+
+            let path = self.path.to_string() + &name;
+            let meta = de.metadata();
+
+            let d = if meta.is_dir() {
+                oio::Entry::new(&format!("{}/", &path), Metadata::new(EntryMode::DIR))
+            } else if meta.is_regular() {
+                let mut m = Metadata::new(EntryMode::FILE);
+                if let Some(size) = meta.size {
+                    m.set_content_length(size);
+                }
+                if let Some(mtime) = meta.mtime {
+                    m.set_last_modified(parse_mtime(mtime)?);
+                }
+                oio::Entry::new(&path, m)
+            } else {
+                oio::Entry::new(&path, Metadata::new(EntryMode::Unknown))
+            };
+
+            oes.push(d)
+        }
+
+        Ok(if oes.is_empty() { None } else { Some(oes) })
+    }
+}
+
+/// Parse mtime in unix seconds returned by sftp server.
+pub fn parse_mtime(mtime: u32) -> Result<OffsetDateTime> {
+    OffsetDateTime::from_unix_timestamp(mtime as i64).map_err(|e| {
+        Error::new(ErrorKind::Unexpected, "mtime is out of range")
+            .with_context("mtime", mtime.to_string())
+            .set_source(e)
+    })
+}
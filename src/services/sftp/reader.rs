@@ -0,0 +1,270 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::min;
+use std::collections::VecDeque;
+use std::io::SeekFrom;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use bb8::PooledConnection;
+use bytes::Buf;
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::ready;
+use futures::FutureExt;
+use log::debug;
+use russh_sftp::client::error::Error as SftpError;
+use russh_sftp::client::RawSftpSession;
+use russh_sftp::protocol::StatusCode;
+
+use super::backend::Manager;
+use crate::raw::*;
+use crate::*;
+
+/// Size of every read request.
+///
+/// 32KiB is the max read length that all sftp servers must support.
+const READ_CHUNK_SIZE: u64 = 32 * 1024;
+
+/// A read request that has been sent to server.
+struct PendingRead {
+    offset: u64,
+    size: u64,
+    fut: BoxFuture<'static, std::result::Result<Bytes, SftpError>>,
+}
+
+/// SftpReader keeps multiple read requests outstanding on the same sftp
+/// channel so that the link will not be idle while waiting for responses.
+///
+/// The pooled connection is held while reading so that the underlying
+/// ssh session will not be closed or reused by others.
+pub struct SftpReader {
+    conn: PooledConnection<'static, Manager>,
+    raw: Arc<RawSftpSession>,
+    handle: String,
+    path: String,
+
+    /// Absolute range of the file to read.
+    start: u64,
+    end: u64,
+    /// Absolute offset of the first byte in `buf`.
+    pos: u64,
+    /// Absolute offset of the next read request.
+    next: u64,
+
+    concurrency: usize,
+    pending: VecDeque<PendingRead>,
+    buf: Bytes,
+}
+
+/// Safety: SftpReader will only be accessed under &mut.
+unsafe impl Sync for SftpReader {}
+
+impl SftpReader {
+    pub fn new(
+        conn: PooledConnection<'static, Manager>,
+        raw: Arc<RawSftpSession>,
+        handle: String,
+        path: String,
+        start: u64,
+        end: u64,
+        concurrency: usize,
+    ) -> Self {
+        SftpReader {
+            conn,
+            raw,
+            handle,
+            path,
+            start,
+            end,
+            pos: start,
+            next: start,
+            concurrency,
+            pending: VecDeque::with_capacity(concurrency),
+            buf: Bytes::new(),
+        }
+    }
+
+    fn read_request(&self, offset: u64, size: u64) -> PendingRead {
+        let raw = self.raw.clone();
+        let handle = self.handle.clone();
+
+        let fut = async move {
+            match raw.read(handle, offset, size as u32).await {
+                Ok(data) => Ok(Bytes::from(data.data)),
+                // Server returns EOF if we are reading beyond the end of file.
+                Err(SftpError::Status(status)) if status.status_code == StatusCode::Eof => {
+                    Ok(Bytes::new())
+                }
+                Err(err) => Err(err),
+            }
+        };
+
+        PendingRead {
+            offset,
+            size,
+            fut: fut.boxed(),
+        }
+    }
+
+    /// Drop all in flight requests and continue reading from `pos`.
+    fn reset(&mut self, pos: u64) {
+        self.pending.clear();
+        self.buf = Bytes::new();
+        self.pos = pos;
+        self.next = pos;
+    }
+
+    /// Fill `buf` with the response of the earliest read request.
+    ///
+    /// Returns `false` if all data has been read.
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<Result<bool>> {
+        while self.buf.is_empty() {
+            while self.pending.len() < self.concurrency && self.next < self.end {
+                let size = min(READ_CHUNK_SIZE, self.end - self.next);
+                let req = self.read_request(self.next, size);
+                self.pending.push_back(req);
+                self.next += size;
+            }
+
+            let req = match self.pending.front_mut() {
+                Some(req) => req,
+                None => return Poll::Ready(Ok(false)),
+            };
+            let res = ready!(req.fut.poll_unpin(cx));
+            let req = self.pending.pop_front().expect("front request must exist");
+
+            let bs = match res {
+                Ok(bs) => bs,
+                Err(err) => {
+                    let pos = self.pos;
+                    self.reset(pos);
+
+                    return Poll::Ready(Err(self
+                        .conn
+                        .parse_error(err)
+                        .with_operation(oio::ReadOperation::Read)
+                        .with_context("path", &self.path)));
+                }
+            };
+
+            if bs.is_empty() {
+                let pos = self.pos;
+                self.reset(pos);
+
+                return Poll::Ready(Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "sftp file ended before all data has been read",
+                )
+                .with_operation(oio::ReadOperation::Read)
+                .with_context("path", &self.path)
+                .with_context("offset", req.offset.to_string())
+                .set_temporary()));
+            }
+
+            // Server could return less data than requested, send another
+            // request for the rest before all others to keep the order.
+            let n = bs.len() as u64;
+            if n < req.size {
+                debug!(
+                    "sftp short read on {}: requested {}, got {n}",
+                    self.path, req.size
+                );
+                let rest = self.read_request(req.offset + n, req.size - n);
+                self.pending.push_front(rest);
+            }
+
+            self.buf = bs;
+        }
+
+        Poll::Ready(Ok(true))
+    }
+}
+
+impl oio::Read for SftpReader {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        if buf.is_empty() || !ready!(self.poll_fill(cx))? {
+            return Poll::Ready(Ok(0));
+        }
+
+        let n = min(buf.len(), self.buf.len());
+        buf[..n].copy_from_slice(&self.buf[..n]);
+        self.buf.advance(n);
+        self.pos += n as u64;
+
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+        let _ = cx;
+
+        let (base, offset) = match pos {
+            SeekFrom::Start(n) => (self.start as i64, n as i64),
+            SeekFrom::End(n) => (self.end as i64, n),
+            SeekFrom::Current(n) => (self.pos as i64, n),
+        };
+        let seek_pos = match base.checked_add(offset) {
+            Some(n) if n >= self.start as i64 => n as u64,
+            _ => {
+                return Poll::Ready(Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "invalid seek to a negative or overflowing position",
+                )
+                .with_operation(oio::ReadOperation::Seek)
+                .with_context("path", &self.path)))
+            }
+        };
+
+        // Data of the new position is already in buffer.
+        if seek_pos >= self.pos && seek_pos - self.pos < self.buf.len() as u64 {
+            let n = (seek_pos - self.pos) as usize;
+            self.buf.advance(n);
+            self.pos = seek_pos;
+        } else if seek_pos != self.pos {
+            self.reset(seek_pos);
+        }
+
+        Poll::Ready(Ok(self.pos - self.start))
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        match ready!(self.poll_fill(cx)) {
+            Ok(true) => {
+                let bs = self.buf.split_off(0);
+                self.pos += bs.len() as u64;
+                Poll::Ready(Some(Ok(bs)))
+            }
+            Ok(false) => Poll::Ready(None),
+            Err(err) => Poll::Ready(Some(Err(err))),
+        }
+    }
+}
+
+impl Drop for SftpReader {
+    fn drop(&mut self) {
+        // The channel will be reused by others, close the handle to avoid
+        // leaking it on the server.
+        let raw = self.raw.clone();
+        let handle = std::mem::take(&mut self.handle);
+        if let Ok(rt) = tokio::runtime::Handle::try_current() {
+            rt.spawn(async move {
+                if let Err(err) = raw.close(handle).await {
+                    debug!("close sftp file handle failed: {err:?}");
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use bb8::PooledConnection;
+use bytes::Bytes;
+use russh_sftp::client::fs::File;
+use tokio::io::AsyncWriteExt;
+
+use super::backend::Manager;
+use crate::raw::*;
+use crate::*;
+
+/// SftpWriter streams appended bytes into the remote file directly.
+pub struct SftpWriter {
+    conn: PooledConnection<'static, Manager>,
+    file: File,
+    /// Absolute path of the file on the server.
+    path: String,
+}
+
+/// Safety: SftpWriter will only be accessed under &mut.
+unsafe impl Sync for SftpWriter {}
+
+impl SftpWriter {
+    pub fn new(conn: PooledConnection<'static, Manager>, file: File, path: String) -> Self {
+        SftpWriter { conn, file, path }
+    }
+}
+
+#[async_trait]
+impl oio::Write for SftpWriter {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        self.append(bs).await
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        self.file.write_all(&bs).await.map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "write into sftp file")
+                .with_context("path", &self.path)
+                .set_source(err)
+        })
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        // Shutdown will flush all pending writes and close the file handle.
+        self.file.shutdown().await.map_err(|err| {
+            Error::new(ErrorKind::Unexpected, "close sftp file")
+                .with_context("path", &self.path)
+                .set_source(err)
+        })?;
+
+        Ok(RpWrite::new())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        // Ignore errors while closing since the file will be removed.
+        let _ = self.file.shutdown().await;
+
+        self.conn
+            .sftp
+            .remove_file(&self.path)
+            .await
+            .map_err(|err| self.conn.parse_error(err))
+    }
+}
@@ -12,20 +12,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::min;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 
 use async_trait::async_trait;
+use sled::transaction::ConflictableTransactionError;
+use sled::transaction::TransactionError;
+use sled::Transactional;
+use time::OffsetDateTime;
 
-use crate::raw::adapters::kv;
+use super::pager::SledPager;
+use super::writer::SledWriter;
+use crate::ops::*;
 use crate::raw::*;
-use crate::Builder;
-use crate::Error;
-use crate::ErrorKind;
-use crate::Scheme;
 use crate::*;
 
+/// Name of sled's default tree.
+const DEFAULT_TREE: &str = "__sled__default";
+/// Suffix of the tree that stores metadata like last modified time.
+const META_TREE_SUFFIX: &str = "__meta";
+
 /// Sled service support.
 ///
 /// # Capabilities
@@ -34,17 +42,28 @@ use crate::*;
 ///
 /// - [x] read
 /// - [x] write
-/// - [ ] ~~list~~
-/// - [ ] scan
+/// - [x] list
+/// - [x] scan
 /// - [ ] ~~presign~~
 /// - [x] blocking
 ///
 /// # Configuration
 ///
+/// - `root`: Set the working directory of `OpenDAL`
 /// - `datadir`: Set the path to the sled data directory
+/// - `tree`: Set the tree to store objects, default to sled's default tree
 ///
 /// You can refer to [`SledBuilder`]'s docs for more information
 ///
+/// # Notes
+///
+/// Objects are stored in the tree keyed by their absolute path, directories
+/// are emulated by key prefix: `a/b` implies a virtual dir `a/`. Last
+/// modified time is stored in a separate tree named `<tree>__meta`.
+///
+/// Sled is a sync library, all async operations are running in tokio's
+/// blocking thread pool.
+///
 /// # Example
 ///
 /// ## Via Builder
@@ -52,23 +71,25 @@ use crate::*;
 /// ```no_run
 /// use anyhow::Result;
 /// use opendal::services::Sled;
-/// use opendal::Object;
 /// use opendal::Operator;
 ///
 /// #[tokio::main]
 /// async fn main() -> Result<()> {
 ///     let mut builder = Sled::default();
 ///     builder.datadir("/tmp/opendal/sled");
+///     builder.tree("opendal");
 ///
 ///     let op: Operator = Operator::new(builder)?.finish();
-///     let _: Object = op.object("test_file");
+///     op.write("test_file", "Hello, World!").await?;
 ///     Ok(())
 /// }
 /// ```
-#[derive(Default)]
+#[derive(Default, Debug)]
 pub struct SledBuilder {
     /// That path to the sled data directory.
     datadir: Option<String>,
+    tree: Option<String>,
+    root: Option<String>,
 }
 
 impl SledBuilder {
@@ -77,6 +98,26 @@ impl SledBuilder {
         self.datadir = Some(path.into());
         self
     }
+
+    /// Set the tree to store objects. Will create if not exists.
+    ///
+    /// default: sled's default tree
+    pub fn tree(&mut self, tree: &str) -> &mut Self {
+        if !tree.is_empty() {
+            self.tree = Some(tree.to_string());
+        }
+        self
+    }
+
+    /// set the working directory, all operations will be performed under it.
+    ///
+    /// default: "/"
+    pub fn root(&mut self, root: &str) -> &mut Self {
+        if !root.is_empty() {
+            self.root = Some(root.to_owned());
+        }
+        self
+    }
 }
 
 impl Builder for SledBuilder {
@@ -87,6 +128,8 @@ impl Builder for SledBuilder {
         let mut builder = SledBuilder::default();
 
         map.get("datadir").map(|v| builder.datadir(v));
+        map.get("tree").map(|v| builder.tree(v));
+        map.get("root").map(|v| builder.root(v));
 
         builder
     }
@@ -104,85 +147,398 @@ impl Builder for SledBuilder {
                 .set_source(e)
         })?;
 
-        Ok(SledBackend::new(Adapter {
+        let tree_name = self.tree.take().unwrap_or_else(|| DEFAULT_TREE.to_string());
+        let open_tree = |name: String| {
+            db.open_tree(&name).map_err(|e| {
+                Error::new(ErrorKind::ConfigInvalid, "open tree")
+                    .with_context("service", Scheme::Sled)
+                    .with_context("datadir", datadir_path.clone())
+                    .with_context("tree", name)
+                    .set_source(e)
+            })
+        };
+        let tree = open_tree(tree_name.clone())?;
+        let meta = open_tree(format!("{tree_name}{META_TREE_SUFFIX}"))?;
+
+        Ok(SledBackend {
             datadir: datadir_path,
-            db,
-        }))
+            root: normalize_root(&self.root.take().unwrap_or_default()),
+            tree,
+            meta,
+        })
     }
 }
 
 /// Backend for sled services.
-pub type SledBackend = kv::Backend<Adapter>;
-
 #[derive(Clone)]
-pub struct Adapter {
+pub struct SledBackend {
     datadir: String,
-    db: sled::Db,
+    root: String,
+    tree: sled::Tree,
+    meta: sled::Tree,
 }
 
-impl Debug for Adapter {
+impl Debug for SledBackend {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut ds = f.debug_struct("Adapter");
-        ds.field("path", &self.datadir);
-        ds.finish()
+        f.debug_struct("SledBackend")
+            .field("datadir", &self.datadir)
+            .field("root", &self.root)
+            .finish_non_exhaustive()
     }
 }
 
 #[async_trait]
-impl kv::Adapter for Adapter {
-    fn metadata(&self) -> kv::Metadata {
+impl Accessor for SledBackend {
+    type Reader = oio::Cursor;
+    type BlockingReader = oio::Cursor;
+    type Writer = SledWriter;
+    type BlockingWriter = SledWriter;
+    type Pager = SledPager;
+    type BlockingPager = SledPager;
+
+    fn info(&self) -> AccessorInfo {
         use AccessorCapability::*;
-        kv::Metadata::new(Scheme::Sled, &self.datadir, Read | Write | Scan | Blocking)
+
+        let mut am = AccessorInfo::default();
+        am.set_scheme(Scheme::Sled)
+            .set_root(&self.root)
+            .set_name(&self.datadir)
+            .set_capabilities(Read | Write | List | Scan | Blocking)
+            .set_hints(AccessorHint::ReadStreamable | AccessorHint::ReadSeekable)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_seek: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_can_append: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                scan: true,
+                blocking: true,
+                ..Default::default()
+            });
+
+        am
     }
 
-    async fn get(&self, path: &str) -> Result<Option<Vec<u8>>> {
-        self.blocking_get(path)
+    async fn create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        let path = path.to_string();
+        self.spawn(move |b| b.blocking_create(&path, args)).await
     }
 
-    fn blocking_get(&self, path: &str) -> Result<Option<Vec<u8>>> {
-        Ok(self.db.get(path).map_err(parse_error)?.map(|v| v.to_vec()))
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let path = path.to_string();
+        self.spawn(move |b| b.blocking_read(&path, args)).await
     }
 
-    async fn set(&self, path: &str, value: &[u8]) -> Result<()> {
-        self.blocking_set(path, value)
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.blocking_write(path, args)
     }
 
-    fn blocking_set(&self, path: &str, value: &[u8]) -> Result<()> {
-        self.db.insert(path, value).map_err(parse_error)?;
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let path = path.to_string();
+        self.spawn(move |b| b.blocking_stat(&path, args)).await
+    }
 
-        Ok(())
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let path = path.to_string();
+        self.spawn(move |b| b.blocking_delete(&path, args)).await
     }
 
-    async fn delete(&self, path: &str) -> Result<()> {
-        self.blocking_delete(path)
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        let p = build_abs_path(&self.root, path);
+        let pager = SledPager::new(self, &p, true, args.limit());
+
+        Ok((RpList::default(), pager))
     }
 
-    fn blocking_delete(&self, path: &str) -> Result<()> {
-        self.db.remove(path).map_err(parse_error)?;
+    async fn scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::Pager)> {
+        let p = build_abs_path(&self.root, path);
+        let pager = SledPager::new(self, &p, false, args.limit());
 
-        Ok(())
+        Ok((RpScan::default(), pager))
+    }
+
+    fn blocking_create(&self, path: &str, args: OpCreate) -> Result<RpCreate> {
+        let p = build_abs_path(&self.root, path);
+
+        if args.mode() == EntryMode::DIR {
+            self.tree
+                .insert(p.as_str(), sled::IVec::default())
+                .map_err(parse_error)?;
+        } else {
+            self.sled_set(&p, &[])?;
+        }
+
+        Ok(RpCreate::default())
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let p = build_abs_path(&self.root, path);
+
+        let bs = match self.tree.get(p.as_str()).map_err(parse_error)? {
+            Some(bs) => bs,
+            None => {
+                return Err(
+                    Error::new(ErrorKind::NotFound, "sled doesn't have this path")
+                        .with_context("path", &p),
+                )
+            }
+        };
+
+        let bs = apply_range(&bs, args.range()).to_vec();
+        Ok((RpRead::new(bs.len() as u64), oio::Cursor::from(bs)))
     }
 
-    async fn scan(&self, path: &str) -> Result<Vec<String>> {
-        self.blocking_scan(path)
+    fn blocking_write(&self, path: &str, _: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let p = build_abs_path(&self.root, path);
+
+        Ok((RpWrite::new(), SledWriter::new(self.clone(), p)))
     }
 
-    fn blocking_scan(&self, path: &str) -> Result<Vec<String>> {
-        let it = self.db.scan_prefix(path).keys();
-        let mut res = Vec::default();
+    fn blocking_stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+        let p = build_abs_path(&self.root, path);
+
+        if p.is_empty() {
+            return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
+        }
 
-        for i in it {
-            let bs = i.map_err(parse_error)?.to_vec();
-            res.push(String::from_utf8(bs).map_err(|err| {
-                Error::new(ErrorKind::Unexpected, "store key is not valid utf-8 string")
-                    .set_source(err)
-            })?);
+        // Dir exists if any key starts with it.
+        if p.ends_with('/') {
+            return match self.tree.scan_prefix(p.as_str()).next() {
+                Some(res) => {
+                    res.map_err(parse_error)?;
+                    Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+                }
+                None => Err(
+                    Error::new(ErrorKind::NotFound, "sled doesn't have this path")
+                        .with_context("path", &p),
+                ),
+            };
         }
 
-        Ok(res)
+        let bs = match self.tree.get(p.as_str()).map_err(parse_error)? {
+            Some(bs) => bs,
+            None => {
+                return Err(
+                    Error::new(ErrorKind::NotFound, "sled doesn't have this path")
+                        .with_context("path", &p),
+                )
+            }
+        };
+
+        let mut meta = Metadata::new(EntryMode::FILE).with_content_length(bs.len() as u64);
+        if let Some(t) = self.sled_last_modified(p.as_bytes())? {
+            meta.set_last_modified(t);
+        }
+
+        Ok(RpStat::new(meta))
+    }
+
+    fn blocking_delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        let p = build_abs_path(&self.root, path);
+
+        (&self.tree, &self.meta)
+            .transaction(|(tree, meta)| {
+                tree.remove(p.as_str())?;
+                meta.remove(p.as_str())?;
+                Ok::<_, ConflictableTransactionError<()>>(())
+            })
+            .map_err(parse_transaction_error)?;
+
+        Ok(RpDelete::default())
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        let p = build_abs_path(&self.root, path);
+        let pager = SledPager::new(self, &p, true, args.limit());
+
+        Ok((RpList::default(), pager))
+    }
+
+    fn blocking_scan(&self, path: &str, args: OpScan) -> Result<(RpScan, Self::BlockingPager)> {
+        let p = build_abs_path(&self.root, path);
+        let pager = SledPager::new(self, &p, false, args.limit());
+
+        Ok((RpScan::default(), pager))
     }
 }
 
-fn parse_error(err: sled::Error) -> Error {
+impl SledBackend {
+    /// Run blocking sled operations in tokio's blocking thread pool.
+    async fn spawn<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(SledBackend) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let backend = self.clone();
+        tokio::task::spawn_blocking(move || f(backend))
+            .await
+            .map_err(new_join_error)?
+    }
+
+    pub(super) fn root(&self) -> &str {
+        &self.root
+    }
+
+    pub(super) fn tree(&self) -> &sled::Tree {
+        &self.tree
+    }
+
+    /// Store value and last modified time of the key in one transaction.
+    pub(super) fn sled_set(&self, key: &str, value: &[u8]) -> Result<()> {
+        let now = OffsetDateTime::now_utc().unix_timestamp_nanos();
+
+        (&self.tree, &self.meta)
+            .transaction(|(tree, meta)| {
+                tree.insert(key, value)?;
+                meta.insert(key, &now.to_be_bytes()[..])?;
+                Ok::<_, ConflictableTransactionError<()>>(())
+            })
+            .map_err(parse_transaction_error)
+    }
+
+    /// Get the stored last modified time of the key.
+    pub(super) fn sled_last_modified(&self, key: &[u8]) -> Result<Option<OffsetDateTime>> {
+        let bs = match self.meta.get(key).map_err(parse_error)? {
+            Some(bs) => bs,
+            None => return Ok(None),
+        };
+
+        let nanos: [u8; 16] = bs.as_ref().try_into().map_err(|_| {
+            Error::new(ErrorKind::Unexpected, "stored last modified is invalid")
+                .with_context("service", Scheme::Sled)
+                .with_context("key", String::from_utf8_lossy(key))
+        })?;
+        let t = OffsetDateTime::from_unix_timestamp_nanos(i128::from_be_bytes(nanos)).map_err(
+            |err| {
+                Error::new(
+                    ErrorKind::Unexpected,
+                    "stored last modified is out of range",
+                )
+                .with_context("service", Scheme::Sled)
+                .set_source(err)
+            },
+        )?;
+
+        Ok(Some(t))
+    }
+}
+
+/// Slice the value by given range, out of bound range will be truncated.
+fn apply_range(bs: &[u8], br: BytesRange) -> &[u8] {
+    let len = bs.len() as u64;
+    let (start, end) = match (br.offset(), br.size()) {
+        (Some(offset), Some(size)) => (offset, offset.saturating_add(size)),
+        (Some(offset), None) => (offset, len),
+        (None, Some(size)) => (len.saturating_sub(size), len),
+        (None, None) => (0, len),
+    };
+    let end = min(end, len);
+    let start = min(start, end);
+
+    &bs[start as usize..end as usize]
+}
+
+pub(super) fn parse_error(err: sled::Error) -> Error {
     Error::new(ErrorKind::Unexpected, "error from sled").set_source(err)
 }
+
+fn parse_transaction_error(err: TransactionError<()>) -> Error {
+    match err {
+        TransactionError::Storage(err) => parse_error(err),
+        TransactionError::Abort(()) => {
+            Error::new(ErrorKind::Unexpected, "sled transaction aborted")
+        }
+    }
+}
+
+pub(super) fn new_join_error(err: tokio::task::JoinError) -> Error {
+    Error::new(ErrorKind::Unexpected, "sled blocking task failed").set_source(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_range() {
+        let bs = b"Hello, World!";
+
+        let cases = vec![
+            ("full", BytesRange::new(None, None), &b"Hello, World!"[..]),
+            ("offset", BytesRange::new(Some(7), None), &b"World!"[..]),
+            (
+                "offset and size",
+                BytesRange::new(Some(7), Some(5)),
+                &b"World"[..],
+            ),
+            ("suffix", BytesRange::new(None, Some(6)), &b"World!"[..]),
+            (
+                "suffix too large",
+                BytesRange::new(None, Some(100)),
+                &bs[..],
+            ),
+            (
+                "size too large",
+                BytesRange::new(Some(7), Some(100)),
+                &b"World!"[..],
+            ),
+            (
+                "offset too large",
+                BytesRange::new(Some(100), None),
+                &b""[..],
+            ),
+        ];
+
+        for (name, br, expected) in cases {
+            assert_eq!(apply_range(bs, br), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_list_with_virtual_dirs() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("opendal-sled-{}", uuid::Uuid::new_v4()));
+
+        let mut builder = SledBuilder::default();
+        builder
+            .datadir(&dir.to_string_lossy())
+            .tree("test")
+            .root("/root/");
+        let op = Operator::new(builder)?.finish().blocking();
+
+        op.write("a/b/c", "Hello")?;
+        op.write("a/b/d", "World")?;
+        op.write("a/e", "!")?;
+        op.create_dir("a/f/")?;
+
+        let mut entries: Vec<_> = op
+            .list("a/")?
+            .map(|e| e.map(|e| e.path().to_string()))
+            .collect::<Result<_>>()?;
+        entries.sort();
+        assert_eq!(entries, vec!["a/b/", "a/e", "a/f/"]);
+
+        assert!(op.stat("a/b/")?.is_dir());
+        assert_eq!(
+            op.stat("a/x/").unwrap_err().kind(),
+            ErrorKind::NotFound,
+            "dir without any keys must not exist"
+        );
+
+        let meta = op.stat("a/b/c")?;
+        assert_eq!(meta.content_length(), 5);
+        assert!(meta.last_modified().is_some());
+
+        assert_eq!(op.range_read("a/b/c", 1..3)?, b"el");
+
+        let _ = std::fs::remove_dir_all(dir);
+        Ok(())
+    }
+}
@@ -13,5 +13,7 @@
 // limitations under the License.
 
 mod backend;
-
 pub use backend::SledBuilder as Sled;
+
+mod pager;
+mod writer;
@@ -0,0 +1,142 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+
+use super::backend::new_join_error;
+use super::backend::parse_error;
+use super::backend::SledBackend;
+use crate::raw::*;
+use crate::*;
+
+/// SledPager iterates keys with given prefix in order.
+///
+/// Keys are sorted, so keys under the same dir are adjacent and virtual
+/// dirs can be deduplicated by comparing with the last one.
+pub struct SledPager {
+    state: Option<PagerState>,
+}
+
+struct PagerState {
+    backend: SledBackend,
+    iter: sled::Iter,
+    /// Length of the abs root which will be stripped from keys.
+    root_len: usize,
+    /// Length of the listed path.
+    prefix_len: usize,
+    /// Fold keys into virtual dirs at `/` if set.
+    delimiter: bool,
+    size: usize,
+    last_dir: Option<String>,
+}
+
+impl SledPager {
+    pub fn new(backend: &SledBackend, path: &str, delimiter: bool, limit: Option<usize>) -> Self {
+        let iter = backend.tree().scan_prefix(path);
+
+        Self {
+            state: Some(PagerState {
+                backend: backend.clone(),
+                iter,
+                root_len: backend.root().len() - 1,
+                prefix_len: path.len(),
+                delimiter,
+                size: limit.unwrap_or(1000),
+                last_dir: None,
+            }),
+        }
+    }
+}
+
+impl PagerState {
+    fn next_page(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let mut entries = Vec::with_capacity(self.size);
+
+        while entries.len() < self.size {
+            let (k, v) = match self.iter.next() {
+                Some(kv) => kv.map_err(parse_error)?,
+                None => break,
+            };
+            let key = std::str::from_utf8(&k).map_err(|err| {
+                Error::new(ErrorKind::Unexpected, "store key is not valid utf-8 string")
+                    .set_source(err)
+            })?;
+
+            let rest = &key[self.prefix_len..];
+            // Skip the listed dir itself.
+            if rest.is_empty() {
+                continue;
+            }
+
+            if self.delimiter {
+                if let Some(idx) = rest.find('/') {
+                    let dir = &key[self.root_len..self.prefix_len + idx + 1];
+                    if self.last_dir.as_deref() == Some(dir) {
+                        continue;
+                    }
+                    self.last_dir = Some(dir.to_string());
+                    entries.push(oio::Entry::new(dir, Metadata::new(EntryMode::DIR)));
+                    continue;
+                }
+            }
+
+            let path = &key[self.root_len..];
+            if key.ends_with('/') {
+                entries.push(oio::Entry::new(path, Metadata::new(EntryMode::DIR)));
+                continue;
+            }
+
+            let mut meta = Metadata::new(EntryMode::FILE).with_content_length(v.len() as u64);
+            if let Some(t) = self.backend.sled_last_modified(&k)? {
+                meta.set_last_modified(t);
+            }
+            entries.push(oio::Entry::new(path, meta));
+        }
+
+        Ok(if entries.is_empty() {
+            None
+        } else {
+            Some(entries)
+        })
+    }
+}
+
+#[async_trait]
+impl oio::Page for SledPager {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let mut state = match self.state.take() {
+            Some(state) => state,
+            None => return Ok(None),
+        };
+
+        let (state, res) = tokio::task::spawn_blocking(move || {
+            let res = state.next_page();
+            (state, res)
+        })
+        .await
+        .map_err(new_join_error)?;
+
+        self.state = Some(state);
+        res
+    }
+}
+
+impl oio::BlockingPage for SledPager {
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        match &mut self.state {
+            Some(state) => state.next_page(),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,877 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::header;
+use http::HeaderValue;
+use http::Request;
+use http::Response;
+use http::StatusCode;
+use log::debug;
+use serde::Deserialize;
+use serde_json::json;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use super::error::parse_error;
+use super::pager::SupabasePager;
+use super::writer::SupabaseWriter;
+use crate::ops::*;
+use crate::raw::*;
+use crate::*;
+
+/// The placeholder that Supabase dashboard creates for empty folders.
+pub(super) const EMPTY_FOLDER_PLACEHOLDER: &str = ".emptyFolderPlaceholder";
+
+/// [Supabase Storage](https://supabase.com/docs/guides/storage) services
+/// support.
+///
+/// # Capabilities
+///
+/// This service can be used to:
+///
+/// - [x] read
+/// - [x] write
+/// - [x] list
+/// - [ ] ~~scan~~
+/// - [ ] ~~presign~~
+/// - [ ] blocking
+///
+/// # Configuration
+///
+/// - `root`: Set the work directory for backend
+/// - `endpoint`: Set the project url like `https://<project>.supabase.co`
+/// - `bucket`: Set the name of bucket
+/// - `key`: Set the service role key or anon key
+/// - `public_read`: Read objects via public urls, the bucket must be public
+///
+/// You can refer to [`SupabaseBuilder`]'s docs for more information
+///
+/// # Notes
+///
+/// - Writes will overwrite existing objects via `x-upsert`, unless
+///   `if_not_exists` is set.
+/// - Supabase doesn't have dirs, `create_dir` will create a
+///   `.emptyFolderPlaceholder` under it like the dashboard does.
+/// - Presign is not supported: signed urls are issued by the `sign` API,
+///   which can't be called by the sync `presign`.
+///
+/// # Example
+///
+/// ## Via Builder
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::services::Supabase;
+/// use opendal::Operator;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     // create backend builder
+///     let mut builder = Supabase::default();
+///
+///     builder
+///         .root("/path/to/dir")
+///         .endpoint("https://<project>.supabase.co")
+///         .bucket("opendal")
+///         .key("service_role_key");
+///
+///     let op: Operator = Operator::new(builder)?.finish();
+///     Ok(())
+/// }
+/// ```
+#[derive(Default)]
+pub struct SupabaseBuilder {
+    root: Option<String>,
+    endpoint: Option<String>,
+    bucket: String,
+    key: Option<String>,
+    public_read: bool,
+
+    http_client: Option<HttpClient>,
+}
+
+impl Debug for SupabaseBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut ds = f.debug_struct("Builder");
+
+        ds.field("root", &self.root)
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("public_read", &self.public_read);
+        if self.key.is_some() {
+            ds.field("key", &"<redacted>");
+        }
+        ds.finish()
+    }
+}
+
+impl SupabaseBuilder {
+    /// Set root path of Supabase Storage.
+    pub fn root(&mut self, root: &str) -> &mut Self {
+        if !root.is_empty() {
+            self.root = Some(root.to_string())
+        }
+
+        self
+    }
+
+    /// Set the project url like `https://<project>.supabase.co`.
+    ///
+    /// Storage API will be accessed via `<endpoint>/storage/v1`.
+    pub fn endpoint(&mut self, endpoint: &str) -> &mut Self {
+        if !endpoint.is_empty() {
+            self.endpoint = Some(endpoint.trim_end_matches('/').to_string())
+        }
+
+        self
+    }
+
+    /// Set the name of bucket.
+    pub fn bucket(&mut self, bucket: &str) -> &mut Self {
+        self.bucket = bucket.to_string();
+        self
+    }
+
+    /// Set the key to access Supabase Storage.
+    ///
+    /// Both service role key and anon key are accepted, anon key will be
+    /// restricted by the RLS policies of `storage.objects`.
+    pub fn key(&mut self, key: &str) -> &mut Self {
+        if !key.is_empty() {
+            self.key = Some(key.to_string())
+        }
+
+        self
+    }
+
+    /// Read objects via public urls instead of authenticated urls.
+    ///
+    /// Only works with public buckets, but public urls could be cached by
+    /// Supabase CDN.
+    pub fn enable_public_read(&mut self) -> &mut Self {
+        self.public_read = true;
+        self
+    }
+
+    /// Specify the http client that used by this service.
+    ///
+    /// # Notes
+    ///
+    /// This API is part of OpenDAL's Raw API. `HttpClient` could be changed
+    /// during minor updates.
+    pub fn http_client(&mut self, client: HttpClient) -> &mut Self {
+        self.http_client = Some(client);
+        self
+    }
+}
+
+impl Builder for SupabaseBuilder {
+    const SCHEME: Scheme = Scheme::Supabase;
+    type Accessor = SupabaseBackend;
+
+    fn from_map(map: HashMap<String, String>) -> Self {
+        let mut builder = SupabaseBuilder::default();
+
+        map.get("root").map(|v| builder.root(v));
+        map.get("endpoint").map(|v| builder.endpoint(v));
+        map.get("bucket").map(|v| builder.bucket(v));
+        map.get("key").map(|v| builder.key(v));
+        map.get("public_read")
+            .filter(|v| *v == "on" || *v == "true")
+            .map(|_| builder.enable_public_read());
+
+        builder
+    }
+
+    fn build(&mut self) -> Result<Self::Accessor> {
+        debug!("backend build started: {:?}", self);
+
+        let root = normalize_root(&self.root.take().unwrap_or_default());
+        debug!("backend use root {}", root);
+
+        let endpoint = match &self.endpoint {
+            Some(endpoint) => {
+                parse_endpoint(endpoint).map_err(|e| {
+                    e.with_operation("Builder::build")
+                        .with_context("service", Scheme::Supabase)
+                })?;
+                format!("{endpoint}/storage/v1")
+            }
+            None => {
+                return Err(Error::new(ErrorKind::ConfigInvalid, "endpoint is empty")
+                    .with_operation("Builder::build")
+                    .with_context("service", Scheme::Supabase))
+            }
+        };
+        debug!("backend use endpoint {}", endpoint);
+
+        if self.bucket.is_empty() {
+            return Err(Error::new(ErrorKind::ConfigInvalid, "bucket is empty")
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Supabase));
+        }
+
+        let client = if let Some(client) = self.http_client.take() {
+            client
+        } else {
+            HttpClient::new().map_err(|err| {
+                err.with_operation("Builder::build")
+                    .with_context("service", Scheme::Supabase)
+            })?
+        };
+
+        debug!("backend build finished: {:?}", self);
+        Ok(SupabaseBackend {
+            root,
+            endpoint,
+            bucket: self.bucket.clone(),
+            key: self.key.clone(),
+            public_read: self.public_read,
+            client,
+        })
+    }
+}
+
+/// Object returned by the `info` and `list` APIs.
+///
+/// `info` returns attributes at top level, while `list` returns them in
+/// `metadata`. Folders returned by `list` don't have `id`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(super) struct SupabaseObject {
+    pub name: String,
+    pub id: Option<String>,
+    size: Option<u64>,
+    content_type: Option<String>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    updated_at: Option<String>,
+    metadata: Option<SupabaseObjectMetadata>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct SupabaseObjectMetadata {
+    size: Option<u64>,
+    mimetype: Option<String>,
+    e_tag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl SupabaseObject {
+    pub fn is_folder(&self) -> bool {
+        self.id.is_none()
+    }
+
+    pub fn parse_into_metadata(&self) -> Result<Metadata> {
+        if self.is_folder() {
+            return Ok(Metadata::new(EntryMode::DIR));
+        }
+
+        let meta = self.metadata.as_ref();
+        let mut m = Metadata::new(EntryMode::FILE);
+
+        if let Some(v) = self.size.or_else(|| meta.and_then(|v| v.size)) {
+            m.set_content_length(v);
+        }
+        if let Some(v) = self
+            .content_type
+            .as_deref()
+            .or_else(|| meta.and_then(|v| v.mimetype.as_deref()))
+        {
+            m.set_content_type(v);
+        }
+        if let Some(v) = self
+            .etag
+            .as_deref()
+            .or_else(|| meta.and_then(|v| v.e_tag.as_deref()))
+        {
+            m.set_etag(v);
+        }
+        if let Some(v) = self
+            .last_modified
+            .as_deref()
+            .or_else(|| meta.and_then(|v| v.last_modified.as_deref()))
+            .or(self.updated_at.as_deref())
+        {
+            let dt = OffsetDateTime::parse(v, &Rfc3339).map_err(|e| {
+                Error::new(ErrorKind::Unexpected, "parse last modified of object")
+                    .with_context("value", v)
+                    .set_source(e)
+            })?;
+            m.set_last_modified(dt);
+        }
+
+        Ok(m)
+    }
+}
+
+/// Backend for Supabase Storage services.
+#[derive(Clone)]
+pub struct SupabaseBackend {
+    root: String,
+    endpoint: String,
+    bucket: String,
+    key: Option<String>,
+    public_read: bool,
+    pub(super) client: HttpClient,
+}
+
+impl Debug for SupabaseBackend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Backend")
+            .field("root", &self.root)
+            .field("endpoint", &self.endpoint)
+            .field("bucket", &self.bucket)
+            .field("public_read", &self.public_read)
+            .field("client", &self.client)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Accessor for SupabaseBackend {
+    type Reader = IncomingAsyncBody;
+    type BlockingReader = ();
+    type Writer = SupabaseWriter;
+    type BlockingWriter = ();
+    type Pager = SupabasePager;
+    type BlockingPager = ();
+
+    fn info(&self) -> AccessorInfo {
+        use AccessorCapability::*;
+        use AccessorHint::*;
+
+        let mut am = AccessorInfo::default();
+        am.set_scheme(Scheme::Supabase)
+            .set_root(&self.root)
+            .set_name(&self.bucket)
+            .set_capabilities(Read | Write | List)
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_with_content_type: true,
+                write_with_if_not_exists: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_limit: true,
+                list_with_delimiter: true,
+                ..Default::default()
+            })
+            .set_hints(ReadStreamable);
+        am
+    }
+
+    async fn create(&self, path: &str, _: OpCreate) -> Result<RpCreate> {
+        // Supabase doesn't have dirs, create a placeholder under it instead.
+        let p = format!("{path}{EMPTY_FOLDER_PLACEHOLDER}");
+        let resp = self
+            .supabase_upload_object(&p, Some(0), None, true, AsyncBody::Empty)
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(RpCreate::default())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let range = args.range();
+        let resp = self.supabase_get_object(path, range).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        if args.append() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "append write is not supported",
+            ));
+        }
+
+        Ok((
+            RpWrite::default(),
+            SupabaseWriter::new(self.clone(), args, path.to_string()),
+        ))
+    }
+
+    async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+        // Stat root always returns a DIR.
+        if path == "/" {
+            return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
+        }
+
+        // Dirs are found by any objects under it.
+        if path.ends_with('/') {
+            let objects = self.supabase_list_objects(path, 1, 0).await?;
+            return if objects.is_empty() {
+                Err(Error::new(ErrorKind::NotFound, "dir is not found").with_context("path", path))
+            } else {
+                Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+            };
+        }
+
+        let resp = self.supabase_get_object_info(path).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                let object: SupabaseObject =
+                    serde_json::from_slice(&bs).map_err(new_json_deserialize_error)?;
+                object.parse_into_metadata().map(RpStat::new)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        // Deleting dir removes the placeholder created by `create_dir`.
+        let p = if path.ends_with('/') {
+            format!("{path}{EMPTY_FOLDER_PLACEHOLDER}")
+        } else {
+            path.to_string()
+        };
+
+        let resp = self.supabase_delete_object(&p).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(RpDelete::default())
+            }
+            _ => {
+                let err = parse_error(resp).await?;
+                if err.kind() == ErrorKind::NotFound {
+                    Ok(RpDelete::default())
+                } else {
+                    Err(err)
+                }
+            }
+        }
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        Ok((
+            RpList::default(),
+            SupabasePager::new(self.clone(), path, args.limit()),
+        ))
+    }
+}
+
+impl SupabaseBackend {
+    /// Build the object name that used by Supabase, which doesn't start
+    /// with `/`.
+    fn supabase_path(&self, path: &str) -> String {
+        build_abs_path(&self.root, path)
+    }
+
+    fn sign<T>(&self, req: &mut Request<T>) -> Result<()> {
+        let key = match &self.key {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+
+        let auth = format_authorization_by_bearer(key)?;
+        req.headers_mut().insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&auth).map_err(|e| new_request_build_error(e.into()))?,
+        );
+        req.headers_mut().insert(
+            "apikey",
+            HeaderValue::from_str(key).map_err(|e| new_request_build_error(e.into()))?,
+        );
+
+        Ok(())
+    }
+
+    async fn supabase_get_object(
+        &self,
+        path: &str,
+        range: BytesRange,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = percent_encode_path(&self.supabase_path(path));
+
+        let url = if self.public_read {
+            format!("{}/object/public/{}/{p}", self.endpoint, self.bucket)
+        } else {
+            format!("{}/object/{}/{p}", self.endpoint, self.bucket)
+        };
+
+        let mut req = Request::get(&url);
+        if !range.is_full() {
+            req = req.header(header::RANGE, range.to_header());
+        }
+
+        let mut req = req
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        if !self.public_read {
+            self.sign(&mut req)?;
+        }
+
+        self.client.send_async(req).await
+    }
+
+    async fn supabase_get_object_info(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let p = percent_encode_path(&self.supabase_path(path));
+
+        let url = format!("{}/object/info/{}/{p}", self.endpoint, self.bucket);
+
+        let mut req = Request::get(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+
+    /// Upload an object, existing object will be replaced if `upsert`.
+    pub(super) async fn supabase_upload_object(
+        &self,
+        path: &str,
+        size: Option<usize>,
+        content_type: Option<&str>,
+        upsert: bool,
+        body: AsyncBody,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let p = percent_encode_path(&self.supabase_path(path));
+
+        let url = format!("{}/object/{}/{p}", self.endpoint, self.bucket);
+
+        let mut req = Request::post(&url);
+        if let Some(size) = size {
+            req = req.header(header::CONTENT_LENGTH, size);
+        }
+        if let Some(mime) = content_type {
+            req = req.header(header::CONTENT_TYPE, mime);
+        }
+        if upsert {
+            req = req.header("x-upsert", "true");
+        }
+
+        let mut req = req.body(body).map_err(new_request_build_error)?;
+
+        self.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+
+    async fn supabase_delete_object(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let p = percent_encode_path(&self.supabase_path(path));
+
+        let url = format!("{}/object/{}/{p}", self.endpoint, self.bucket);
+
+        let mut req = Request::delete(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.sign(&mut req)?;
+
+        self.client.send_async(req).await
+    }
+
+    /// List objects and folders under given dir path.
+    pub(super) async fn supabase_list_objects(
+        &self,
+        path: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<SupabaseObject>> {
+        let url = format!("{}/object/list/{}", self.endpoint, self.bucket);
+
+        let body = json!({
+            "prefix": self.supabase_path(path),
+            "limit": limit,
+            "offset": offset,
+            "sortBy": { "column": "name", "order": "asc" },
+        })
+        .to_string();
+
+        let mut req = Request::post(&url)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_LENGTH, body.len())
+            .body(AsyncBody::Bytes(Bytes::from(body)))
+            .map_err(new_request_build_error)?;
+
+        self.sign(&mut req)?;
+
+        let resp = self.client.send_async(req).await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                serde_json::from_slice(&bs).map_err(new_json_deserialize_error)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::body_string;
+    use wiremock::matchers::header;
+    use wiremock::matchers::header_exists;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+
+    fn new_backend(server: &MockServer, public_read: bool) -> SupabaseBackend {
+        let mut builder = SupabaseBuilder::default();
+        builder.endpoint(&server.uri()).bucket("bucket").key("key");
+        if public_read {
+            builder.enable_public_read();
+        }
+        builder.build().expect("build must succeed")
+    }
+
+    #[test]
+    fn test_build_invalid() {
+        let mut builder = SupabaseBuilder::default();
+        builder.bucket("bucket");
+        let err = builder.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+
+        let mut builder = SupabaseBuilder::default();
+        builder.endpoint("https://project.supabase.co");
+        let err = builder.build().expect_err("build must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[tokio::test]
+    async fn test_read() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/object/bucket/dir/file"))
+            .and(header("authorization", "Bearer key"))
+            .and(header("apikey", "key"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/object/public/bucket/dir/file"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        for public_read in [false, true] {
+            let backend = new_backend(&server, public_read);
+            let (_, r) = backend
+                .read("dir/file", OpRead::new())
+                .await
+                .expect("read must succeed");
+            let bs = r.bytes().await.unwrap();
+            assert_eq!(bs, Bytes::from("Hello, World!"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stat() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/object/info/bucket/dir/file"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "id",
+                "name": "dir/file",
+                "bucket_id": "bucket",
+                "size": 13,
+                "content_type": "text/plain",
+                "etag": "\"etag\"",
+                "last_modified": "2023-03-01T12:00:00.000Z",
+                "created_at": "2023-03-01T12:00:00.000Z"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // Supabase returns `400` for missing objects.
+        Mock::given(method("GET"))
+            .and(path("/storage/v1/object/info/bucket/dir/not_exist"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "statusCode": "404",
+                "error": "not_found",
+                "message": "Object not found"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server, false);
+
+        let meta = backend
+            .stat("dir/file", OpStat::new())
+            .await
+            .expect("stat must succeed")
+            .into_metadata();
+        assert_eq!(meta.mode(), EntryMode::FILE);
+        assert_eq!(meta.content_length(), 13);
+        assert_eq!(meta.content_type(), Some("text/plain"));
+        assert_eq!(meta.etag(), Some("\"etag\""));
+        assert_eq!(
+            meta.last_modified(),
+            Some(OffsetDateTime::from_unix_timestamp(1677672000).unwrap())
+        );
+
+        let err = backend
+            .stat("dir/not_exist", OpStat::new())
+            .await
+            .expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_write() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/object/bucket/dir/file"))
+            .and(header("x-upsert", "true"))
+            .and(header("content-type", "text/plain"))
+            .and(body_string("Hello, World!"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "Key": "bucket/dir/file" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server, false);
+
+        let (_, mut w) = backend
+            .write("dir/file", OpWrite::new().with_content_type("text/plain"))
+            .await
+            .expect("writer must be created");
+        oio::Write::write(&mut w, Bytes::from("Hello, World!"))
+            .await
+            .expect("write must succeed");
+    }
+
+    #[tokio::test]
+    async fn test_write_if_not_exists() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/object/bucket/dir/file"))
+            .and(header_exists("x-upsert"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/object/bucket/dir/file"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "statusCode": "409",
+                "error": "Duplicate",
+                "message": "The resource already exists"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server, false);
+
+        let (_, mut w) = backend
+            .write("dir/file", OpWrite::new().with_if_not_exists(true))
+            .await
+            .expect("writer must be created");
+        let err = oio::Write::write(&mut w, Bytes::from("Hello, World!"))
+            .await
+            .expect_err("write must fail");
+        assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
+    }
+
+    #[tokio::test]
+    async fn test_create_dir_and_delete() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(
+                "/storage/v1/object/bucket/dir/.emptyFolderPlaceholder",
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(
+                "/storage/v1/object/bucket/dir/.emptyFolderPlaceholder",
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/storage/v1/object/bucket/dir/not_exist"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "statusCode": "404",
+                "error": "not_found",
+                "message": "Object not found"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let backend = new_backend(&server, false);
+
+        backend
+            .create("dir/", OpCreate::new(EntryMode::DIR))
+            .await
+            .expect("create dir must succeed");
+        backend
+            .delete("dir/", OpDelete::new())
+            .await
+            .expect("delete dir must succeed");
+        backend
+            .delete("dir/not_exist", OpDelete::new())
+            .await
+            .expect("delete not exist file must succeed");
+    }
+}
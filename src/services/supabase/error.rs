@@ -0,0 +1,157 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use http::Response;
+use http::StatusCode;
+use serde::Deserialize;
+use serde_json::de;
+
+use crate::raw::*;
+use crate::Error;
+use crate::ErrorKind;
+use crate::Result;
+
+/// Supabase Storage returns errors like:
+///
+/// ```json
+/// {
+///   "statusCode": "404",
+///   "error": "not_found",
+///   "message": "Object not found"
+/// }
+/// ```
+///
+/// The `statusCode` in payload could be different from the status of
+/// response: missing objects and RLS violations are returned as
+/// `400 Bad Request` with `404` and `403` in payload.
+#[derive(Default, Debug, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct SupabaseError {
+    /// Could be either a string or a number.
+    status_code: serde_json::Value,
+    error: String,
+    message: String,
+}
+
+impl SupabaseError {
+    fn status_code(&self) -> Option<u16> {
+        match &self.status_code {
+            serde_json::Value::String(v) => v.parse().ok(),
+            serde_json::Value::Number(v) => v.as_u64().and_then(|v| u16::try_from(v).ok()),
+            _ => None,
+        }
+    }
+}
+
+/// Parse error response into Error.
+pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
+    let (parts, body) = resp.into_parts();
+    let bs = body.bytes().await?;
+
+    let supabase_err = de::from_slice::<SupabaseError>(&bs).ok();
+    let status = supabase_err
+        .as_ref()
+        .and_then(|v| v.status_code())
+        .and_then(|v| StatusCode::from_u16(v).ok())
+        .unwrap_or(parts.status);
+
+    let (kind, retryable) = match status {
+        StatusCode::CONFLICT => (ErrorKind::AlreadyExists, false),
+        v => parse_error_status(v),
+    };
+
+    let message = match supabase_err {
+        Some(supabase_err) => format!("{supabase_err:?}"),
+        None => String::from_utf8_lossy(&bs).into_owned(),
+    };
+
+    let mut err = with_error_response_context(Error::new(kind, &message), &parts);
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
+
+    if retryable {
+        err = err.set_temporary();
+    }
+
+    Ok(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_parse_error() -> Result<()> {
+        let cases = vec![
+            (
+                StatusCode::BAD_REQUEST,
+                r#"{"statusCode": "404", "error": "not_found", "message": "Object not found"}"#,
+                ErrorKind::NotFound,
+                false,
+            ),
+            (
+                StatusCode::BAD_REQUEST,
+                r#"{"statusCode": "403", "error": "Unauthorized", "message": "new row violates row-level security policy"}"#,
+                ErrorKind::PermissionDenied,
+                false,
+            ),
+            (
+                StatusCode::BAD_REQUEST,
+                r#"{"statusCode": "409", "error": "Duplicate", "message": "The resource already exists"}"#,
+                ErrorKind::AlreadyExists,
+                false,
+            ),
+            (
+                StatusCode::BAD_REQUEST,
+                r#"{"statusCode": "400", "error": "InvalidKey", "message": "Invalid key: dir/file\u0000"}"#,
+                ErrorKind::Unexpected,
+                false,
+            ),
+            (
+                StatusCode::BAD_REQUEST,
+                r#"{"statusCode": 400, "error": "invalid_request", "message": "bucket is required"}"#,
+                ErrorKind::Unexpected,
+                false,
+            ),
+            (
+                StatusCode::UNAUTHORIZED,
+                r#"{"message": "Invalid JWT"}"#,
+                ErrorKind::PermissionDenied,
+                false,
+            ),
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "upstream connect error",
+                ErrorKind::Unexpected,
+                true,
+            ),
+        ];
+
+        for (status, content, kind, temporary) in cases {
+            let bs = bytes::Bytes::from(content);
+            let body = IncomingAsyncBody::new(Box::new(stream::iter(vec![Ok(bs)])), None);
+            let resp = Response::builder().status(status).body(body).unwrap();
+
+            let err = parse_error(resp).await?;
+            assert_eq!(err.kind(), kind, "{content}");
+            assert_eq!(err.is_temporary(), temporary, "{content}");
+        }
+
+        Ok(())
+    }
+}
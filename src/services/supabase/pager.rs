@@ -0,0 +1,174 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+
+use super::backend::SupabaseBackend;
+use super::backend::EMPTY_FOLDER_PLACEHOLDER;
+use crate::raw::*;
+use crate::*;
+
+/// The default number of objects returned by one `list` call.
+const DEFAULT_LIMIT: usize = 1000;
+
+/// SupabasePager lists objects and folders under a dir page by page via
+/// the `list` API, which is paginated by offset.
+pub struct SupabasePager {
+    backend: SupabaseBackend,
+    path: String,
+    limit: usize,
+
+    offset: usize,
+    done: bool,
+}
+
+impl SupabasePager {
+    pub fn new(backend: SupabaseBackend, path: &str, limit: Option<usize>) -> Self {
+        Self {
+            backend,
+            path: path.to_string(),
+            limit: limit.unwrap_or(DEFAULT_LIMIT),
+
+            offset: 0,
+            done: false,
+        }
+    }
+}
+
+#[async_trait]
+impl oio::Page for SupabasePager {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let objects = self
+            .backend
+            .supabase_list_objects(&self.path, self.limit, self.offset)
+            .await?;
+
+        self.offset += objects.len();
+        self.done = objects.len() < self.limit;
+
+        let mut entries = Vec::with_capacity(objects.len());
+        for object in objects {
+            // Placeholders are created by `create_dir`, they should be ignored.
+            if object.name == EMPTY_FOLDER_PLACEHOLDER {
+                continue;
+            }
+
+            // Names are relative to the listed dir.
+            let mut path = if self.path == "/" {
+                object.name.clone()
+            } else {
+                format!("{}{}", self.path, object.name)
+            };
+            if object.is_folder() {
+                path.push('/');
+            }
+
+            entries.push(oio::Entry::new(&path, object.parse_into_metadata()?));
+        }
+
+        Ok(Some(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oio::Page;
+    use serde_json::json;
+    use wiremock::matchers::body_json;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::services::Supabase;
+
+    #[tokio::test]
+    async fn test_list_with_offset() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/object/list/bucket"))
+            .and(header("apikey", "key"))
+            .and(body_json(json!({
+                "prefix": "root/dir/",
+                "limit": 3,
+                "offset": 0,
+                "sortBy": { "column": "name", "order": "asc" }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": ".emptyFolderPlaceholder", "id": "id_0", "metadata": { "size": 0 } },
+                { "name": "a", "id": null, "metadata": null },
+                {
+                    "name": "b",
+                    "id": "id_b",
+                    "updated_at": "2023-03-01T12:00:00.000Z",
+                    "metadata": {
+                        "eTag": "\"etag_b\"",
+                        "size": 1,
+                        "mimetype": "text/plain",
+                        "lastModified": "2023-03-01T12:00:00.000Z"
+                    }
+                }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/storage/v1/object/list/bucket"))
+            .and(body_json(json!({
+                "prefix": "root/dir/",
+                "limit": 3,
+                "offset": 3,
+                "sortBy": { "column": "name", "order": "asc" }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "name": "c", "id": "id_c", "metadata": { "size": 4 } }
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut builder = Supabase::default();
+        builder
+            .root("/root/")
+            .endpoint(&server.uri())
+            .bucket("bucket")
+            .key("key");
+        let backend = builder.build().expect("build must succeed");
+
+        let mut pager = SupabasePager::new(backend, "dir/", Some(3));
+
+        let entries = pager.next().await.unwrap().expect("first page must exist");
+        let paths: Vec<_> = entries.iter().map(|e| e.path().to_string()).collect();
+        assert_eq!(paths, vec!["dir/a/", "dir/b"]);
+        assert_eq!(entries[0].metadata().mode(), EntryMode::DIR);
+        assert_eq!(entries[1].metadata().content_length(), 1);
+        assert_eq!(entries[1].metadata().etag(), Some("\"etag_b\""));
+
+        let entries = pager.next().await.unwrap().expect("second page must exist");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path(), "dir/c");
+        assert_eq!(entries[0].metadata().content_length(), 4);
+
+        assert!(pager.next().await.unwrap().is_none());
+    }
+}
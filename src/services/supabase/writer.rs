@@ -0,0 +1,92 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::StatusCode;
+
+use super::backend::SupabaseBackend;
+use super::error::parse_error;
+use crate::ops::OpWrite;
+use crate::raw::*;
+use crate::*;
+
+pub struct SupabaseWriter {
+    backend: SupabaseBackend,
+
+    op: OpWrite,
+    path: String,
+}
+
+impl SupabaseWriter {
+    pub fn new(backend: SupabaseBackend, op: OpWrite, path: String) -> Self {
+        SupabaseWriter { backend, op, path }
+    }
+}
+
+#[async_trait]
+impl oio::Write for SupabaseWriter {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        // Existing objects will be kept if not upsert.
+        let upsert = !self.op.if_not_exists();
+
+        let resp = self
+            .backend
+            .supabase_upload_object(
+                &self.path,
+                Some(bs.len()),
+                self.op.content_type(),
+                upsert,
+                AsyncBody::Bytes(bs),
+            )
+            .await?;
+
+        match resp.status() {
+            StatusCode::OK => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => {
+                let err = parse_error(resp).await?;
+                if !upsert && err.kind() == ErrorKind::AlreadyExists {
+                    Err(Error::new(
+                        ErrorKind::ConditionNotMatch,
+                        "object already exists while writing with if_not_exists",
+                    )
+                    .with_context("path", &self.path)
+                    .set_source(err))
+                } else {
+                    Err(err)
+                }
+            }
+        }
+    }
+
+    async fn append(&mut self, bs: Bytes) -> Result<()> {
+        let _ = bs;
+
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "output writer doesn't support append",
+        ))
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        Ok(RpWrite::new())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -41,7 +41,7 @@ use crate::*;
 /// - [x] read
 /// - [x] write
 /// - [x] list
-/// - [ ] ~~scan~~
+/// - [x] scan
 /// - [ ] ~~presign~~
 /// - [ ] blocking
 ///
@@ -54,6 +54,7 @@ use crate::*;
 ///
 /// - `endpoint`: set the endpoint for webdav
 /// - `root`: Set the work directory for backend
+/// - `enable_lock`: take a `LOCK` on the file while writing
 ///
 /// You can refer to [`WebdavBuilder`]'s docs for more information
 ///
@@ -72,7 +73,7 @@ use crate::*;
 ///     let mut builder = Webdav::default();
 ///
 ///     builder
-///         .endpoint("127.0.0.1")
+///         .endpoint("http://127.0.0.1")
 ///         .username("xxx")
 ///         .password("xxx");
 ///
@@ -87,6 +88,7 @@ pub struct WebdavBuilder {
     password: Option<String>,
     token: Option<String>,
     root: Option<String>,
+    enable_lock: bool,
     http_client: Option<HttpClient>,
 }
 
@@ -95,6 +97,7 @@ impl Debug for WebdavBuilder {
         let mut de = f.debug_struct("Builder");
         de.field("endpoint", &self.endpoint);
         de.field("root", &self.root);
+        de.field("enable_lock", &self.enable_lock);
 
         de.finish()
     }
@@ -155,6 +158,15 @@ impl WebdavBuilder {
         self
     }
 
+    /// Take an exclusive `LOCK` on the file while writing and release it
+    /// after the write is finished.
+    ///
+    /// This is required by servers that reject writes without lock tokens.
+    pub fn enable_lock(&mut self) -> &mut Self {
+        self.enable_lock = true;
+        self
+    }
+
     /// Specify the http client that used by this service.
     ///
     /// # Notes
@@ -179,6 +191,9 @@ impl Builder for WebdavBuilder {
         map.get("username").map(|v| builder.username(v));
         map.get("password").map(|v| builder.password(v));
         map.get("token").map(|v| builder.token(v));
+        map.get("enable_lock")
+            .filter(|v| *v == "on" || *v == "true")
+            .map(|_| builder.enable_lock());
 
         builder
     }
@@ -193,10 +208,16 @@ impl Builder for WebdavBuilder {
                     .with_context("service", Scheme::Webdav))
             }
         };
+        let uri =
+            parse_endpoint(endpoint).map_err(|e| e.with_context("service", Scheme::Webdav))?;
 
         let root = normalize_root(&self.root.take().unwrap_or_default());
         debug!("backend use root {}", root);
 
+        // Hrefs returned by server contain the path of endpoint, for
+        // example: `/remote.php/dav/files/admin/` for nextcloud.
+        let href_root = format!("{}{}", uri.path().trim_end_matches('/'), root);
+
         let client = if let Some(client) = self.http_client.take() {
             client
         } else {
@@ -222,6 +243,8 @@ impl Builder for WebdavBuilder {
             endpoint: endpoint.to_string(),
             authorization: auth,
             root,
+            href_root,
+            enable_lock: self.enable_lock,
             client,
         })
     }
@@ -231,6 +254,8 @@ impl Builder for WebdavBuilder {
 pub struct WebdavBackend {
     endpoint: String,
     root: String,
+    href_root: String,
+    pub(super) enable_lock: bool,
     client: HttpClient,
 
     authorization: Option<String>,
@@ -241,6 +266,7 @@ impl Debug for WebdavBackend {
         f.debug_struct("Backend")
             .field("endpoint", &self.endpoint)
             .field("root", &self.root)
+            .field("enable_lock", &self.enable_lock)
             .field("client", &self.client)
             .finish()
     }
@@ -260,41 +286,48 @@ impl Accessor for WebdavBackend {
         ma.set_scheme(Scheme::Webdav)
             .set_root(&self.root)
             .set_capabilities(
-                AccessorCapability::Read | AccessorCapability::Write | AccessorCapability::List,
+                AccessorCapability::Read
+                    | AccessorCapability::Write
+                    | AccessorCapability::List
+                    | AccessorCapability::Scan,
             )
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                read_with_suffix_range: true,
+                write: true,
+                write_with_content_type: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_delimiter: true,
+                scan: true,
+                ..Default::default()
+            })
             .set_hints(AccessorHint::ReadStreamable);
 
         ma
     }
 
     async fn create(&self, path: &str, _: OpCreate) -> Result<RpCreate> {
-        // create dir recursively, split path by `/` and create each dir except the last one
         let abs_path = build_abs_path(&self.root, path);
-        let abs_path = abs_path.as_str();
-        let mut parts: Vec<&str> = abs_path.split('/').filter(|x| !x.is_empty()).collect();
-        if !parts.is_empty() {
-            parts.pop();
-        }
 
-        let mut sub_path = String::new();
-        for sub_part in parts {
-            let sub_path_with_slash = sub_part.to_owned() + "/";
-            sub_path.push_str(&sub_path_with_slash);
-            self.create_internal(&sub_path).await?;
-        }
-
-        self.create_internal(abs_path).await
+        self.webdav_create_parents(&abs_path).await?;
+        self.create_internal(&abs_path).await
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        let resp = self.webdav_get(path, args.range()).await?;
+        let range = args.range();
+        let resp = self.webdav_get(path, range).await?;
 
         let status = resp.status();
 
         match status {
             StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
-                let meta = parse_into_metadata(path, resp.headers())?;
-                Ok((RpRead::with_metadata(meta), resp.into_body()))
+                let (meta, body) = parse_into_read_response(path, range, resp)?;
+                Ok((RpRead::with_metadata(meta), body))
             }
             _ => Err(parse_error(resp).await?),
         }
@@ -319,16 +352,24 @@ impl Accessor for WebdavBackend {
             return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
         }
 
-        let resp = self.webdav_head(path).await?;
+        let resp = self
+            .webdav_propfind(path, "0", None, "application/xml".into(), allprop_body())
+            .await?;
 
         let status = resp.status();
 
         match status {
-            StatusCode::OK => parse_into_metadata(path, resp.headers()).map(RpStat::new),
-            // HTTP Server like nginx could return FORBIDDEN if auto-index
-            // is not enabled, we should ignore them.
-            StatusCode::NOT_FOUND | StatusCode::FORBIDDEN if path.ends_with('/') => {
-                Ok(RpStat::new(Metadata::new(EntryMode::DIR)))
+            StatusCode::OK | StatusCode::MULTI_STATUS => {
+                let bs = resp.into_body().bytes().await?;
+                let result: Multistatus =
+                    quick_xml::de::from_reader(bs.reader()).map_err(new_xml_deserialize_error)?;
+
+                let de = result.response.first().ok_or_else(|| {
+                    Error::new(ErrorKind::Unexpected, "propfind response is empty")
+                        .with_context("path", path)
+                })?;
+
+                de.parse_into_metadata().map(RpStat::new)
             }
             _ => Err(parse_error(resp).await?),
         }
@@ -346,16 +387,23 @@ impl Accessor for WebdavBackend {
     }
 
     async fn list(&self, path: &str, _: OpList) -> Result<(RpList, Self::Pager)> {
-        // XML body must start without a new line. Otherwise, the server will panic: `xmlParseChunk() failed`
-        let all_prop_xml_body = r#"<?xml version="1.0" encoding="utf-8" ?>
-            <D:propfind xmlns:D="DAV:">
-                <D:allprop/>
-            </D:propfind>
-        "#;
+        let result = self.webdav_list(path).await?;
 
-        let async_body = AsyncBody::Bytes(bytes::Bytes::from(all_prop_xml_body));
+        Ok((
+            RpList::default(),
+            WebdavPager::new(&self.href_root, path, result),
+        ))
+    }
+
+    async fn scan(&self, path: &str, _: OpScan) -> Result<(RpScan, Self::Pager)> {
         let resp = self
-            .webdav_propfind(path, None, "application/xml".into(), async_body)
+            .webdav_propfind(
+                path,
+                "infinity",
+                None,
+                "application/xml".into(),
+                allprop_body(),
+            )
             .await?;
         let status = resp.status();
 
@@ -366,12 +414,27 @@ impl Accessor for WebdavBackend {
                     quick_xml::de::from_reader(bs.reader()).map_err(new_xml_deserialize_error)?;
 
                 Ok((
-                    RpList::default(),
-                    WebdavPager::new(&self.root, path, result),
+                    RpScan::default(),
+                    WebdavPager::new(&self.href_root, path, result),
+                ))
+            }
+            // Servers that reject `Depth: infinity` could return
+            // `403 Forbidden` with `propfind-finite-depth` precondition,
+            // or `400 Bad Request` and `501 Not Implemented` for the older ones.
+            //
+            // We will fall back to depth 1 traversal in this case. Real
+            // permission errors will be returned while listing again.
+            StatusCode::FORBIDDEN | StatusCode::BAD_REQUEST | StatusCode::NOT_IMPLEMENTED => {
+                resp.into_body().consume().await?;
+                debug!("webdav server rejects propfind with infinity depth, fallback to depth 1");
+
+                Ok((
+                    RpScan::default(),
+                    WebdavPager::new_recursive(self.clone(), &self.href_root, path),
                 ))
             }
             StatusCode::NOT_FOUND if path.ends_with('/') => Ok((
-                RpList::default(),
+                RpScan::default(),
                 WebdavPager::new(
                     &self.root,
                     path,
@@ -385,6 +448,18 @@ impl Accessor for WebdavBackend {
     }
 }
 
+/// Build the body of `PROPFIND` that requests all properties.
+fn allprop_body() -> AsyncBody {
+    // XML body must start without a new line. Otherwise, the server will panic: `xmlParseChunk() failed`
+    let all_prop_xml_body = r#"<?xml version="1.0" encoding="utf-8" ?>
+            <D:propfind xmlns:D="DAV:">
+                <D:allprop/>
+            </D:propfind>
+        "#;
+
+    AsyncBody::Bytes(bytes::Bytes::from(all_prop_xml_body))
+}
+
 impl WebdavBackend {
     async fn webdav_get(
         &self,
@@ -418,6 +493,7 @@ impl WebdavBackend {
         size: Option<usize>,
         content_type: Option<&str>,
         content_disposition: Option<&str>,
+        lock_token: Option<&str>,
         body: AsyncBody,
     ) -> Result<Response<IncomingAsyncBody>> {
         let url = format!("{}/{}", self.endpoint, percent_encode_path(abs_path));
@@ -428,6 +504,10 @@ impl WebdavBackend {
             req = req.header(header::AUTHORIZATION, auth.clone())
         }
 
+        if let Some(token) = lock_token {
+            req = req.header("If", format!("({token})"))
+        }
+
         if let Some(size) = size {
             req = req.header(header::CONTENT_LENGTH, size)
         }
@@ -473,9 +553,32 @@ impl WebdavBackend {
         self.client.send_async(req).await
     }
 
+    /// List the direct children of given dir via `PROPFIND` with depth 1.
+    pub(super) async fn webdav_list(&self, path: &str) -> Result<Multistatus> {
+        let resp = self
+            .webdav_propfind(path, "1", None, "application/xml".into(), allprop_body())
+            .await?;
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK | StatusCode::MULTI_STATUS => {
+                let bs = resp.into_body().bytes().await?;
+                let result: Multistatus =
+                    quick_xml::de::from_reader(bs.reader()).map_err(new_xml_deserialize_error)?;
+
+                Ok(result)
+            }
+            StatusCode::NOT_FOUND if path.ends_with('/') => Ok(Multistatus {
+                response: Vec::new(),
+            }),
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
     async fn webdav_propfind(
         &self,
         path: &str,
+        depth: &str,
         size: Option<u64>,
         content_type: Option<&str>,
         body: AsyncBody,
@@ -486,7 +589,7 @@ impl WebdavBackend {
         let mut req = Request::builder()
             .method("PROPFIND")
             .uri(&url)
-            .header("Depth", "1");
+            .header("Depth", depth);
 
         if let Some(auth) = &self.authorization {
             req = req.header(header::AUTHORIZATION, auth);
@@ -505,12 +608,12 @@ impl WebdavBackend {
         self.client.send_async(req).await
     }
 
-    async fn webdav_head(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
-        let p = build_rooted_abs_path(&self.root, path);
+    async fn webdav_delete(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let p = build_abs_path(&self.root, path);
 
-        let url = format!("{}{}", self.endpoint, percent_encode_path(&p));
+        let url = format!("{}/{}", self.endpoint, percent_encode_path(&p));
 
-        let mut req = Request::head(&url);
+        let mut req = Request::delete(&url);
 
         if let Some(auth) = &self.authorization {
             req = req.header(header::AUTHORIZATION, auth.clone())
@@ -523,15 +626,49 @@ impl WebdavBackend {
         self.client.send_async(req).await
     }
 
-    async fn webdav_delete(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
-        let p = build_abs_path(&self.root, path);
+    /// Take an exclusive write lock on given path.
+    pub(super) async fn webdav_lock(&self, abs_path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!("{}/{}", self.endpoint, percent_encode_path(abs_path));
 
-        let url = format!("{}/{}", self.endpoint, percent_encode_path(&p));
+        let mut req = Request::builder()
+            .method("LOCK")
+            .uri(&url)
+            .header("Depth", "0")
+            .header("Timeout", "Second-600")
+            .header(header::CONTENT_TYPE, "application/xml");
 
-        let mut req = Request::delete(&url);
+        if let Some(auth) = &self.authorization {
+            req = req.header(header::AUTHORIZATION, auth);
+        }
+
+        let body = r#"<?xml version="1.0" encoding="utf-8" ?>
+            <D:lockinfo xmlns:D="DAV:">
+                <D:lockscope><D:exclusive/></D:lockscope>
+                <D:locktype><D:write/></D:locktype>
+            </D:lockinfo>
+        "#;
+        let req = req
+            .body(AsyncBody::Bytes(bytes::Bytes::from(body)))
+            .map_err(new_request_build_error)?;
+
+        self.client.send_async(req).await
+    }
+
+    /// Release the lock on given path.
+    pub(super) async fn webdav_unlock(
+        &self,
+        abs_path: &str,
+        lock_token: &str,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!("{}/{}", self.endpoint, percent_encode_path(abs_path));
+
+        let mut req = Request::builder()
+            .method("UNLOCK")
+            .uri(&url)
+            .header("Lock-Token", lock_token);
 
         if let Some(auth) = &self.authorization {
-            req = req.header(header::AUTHORIZATION, auth.clone())
+            req = req.header(header::AUTHORIZATION, auth);
         }
 
         let req = req
@@ -541,12 +678,29 @@ impl WebdavBackend {
         self.client.send_async(req).await
     }
 
+    /// Create all parent dirs of given path via `MKCOL`.
+    pub(super) async fn webdav_create_parents(&self, abs_path: &str) -> Result<()> {
+        let mut parts: Vec<&str> = abs_path.split('/').filter(|x| !x.is_empty()).collect();
+        if !parts.is_empty() {
+            parts.pop();
+        }
+
+        let mut sub_path = String::new();
+        for sub_part in parts {
+            sub_path.push_str(sub_part);
+            sub_path.push('/');
+            self.create_internal(&sub_path).await?;
+        }
+
+        Ok(())
+    }
+
     async fn create_internal(&self, abs_path: &str) -> Result<RpCreate> {
         let resp = if abs_path.ends_with('/') {
             self.webdav_mkcol(abs_path, None, None, AsyncBody::Empty)
                 .await?
         } else {
-            self.webdav_put(abs_path, Some(0), None, None, AsyncBody::Empty)
+            self.webdav_put(abs_path, Some(0), None, None, None, AsyncBody::Empty)
                 .await?
         };
 
@@ -568,3 +722,316 @@ impl WebdavBackend {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use bytes::Bytes;
+    use time::format_description::well_known::Rfc2822;
+    use time::OffsetDateTime;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::raw::oio::Page;
+
+    fn new_multistatus(entries: &[(&str, Option<u64>)]) -> String {
+        let mut s =
+            r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:">"#.to_string();
+        for (href, size) in entries {
+            let (resourcetype, length) = match size {
+                None => ("<D:collection/>".to_string(), "".to_string()),
+                Some(v) => (
+                    "".to_string(),
+                    format!("<D:getcontentlength>{v}</D:getcontentlength>"),
+                ),
+            };
+            s.push_str(&format!(
+                r#"<D:response>
+                  <D:href>{href}</D:href>
+                  <D:propstat>
+                    <D:prop>
+                      <D:getlastmodified>Fri, 17 Feb 2023 03:37:22 GMT</D:getlastmodified>
+                      {length}
+                      <D:resourcetype>{resourcetype}</D:resourcetype>
+                    </D:prop>
+                    <D:status>HTTP/1.1 200 OK</D:status>
+                  </D:propstat>
+                </D:response>"#
+            ));
+        }
+        s.push_str("</D:multistatus>");
+        s
+    }
+
+    async fn scan_all(backend: &WebdavBackend) -> Result<Vec<(String, EntryMode)>> {
+        let (_, mut pager) = backend.scan("/", OpScan::new()).await?;
+
+        let mut entries = Vec::new();
+        while let Some(oes) = pager.next().await? {
+            entries.extend(oes.into_iter().map(|v| (v.path().to_string(), v.mode())));
+        }
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(entries)
+    }
+
+    #[tokio::test]
+    async fn test_scan_with_infinity_depth() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/"))
+            .and(header("Depth", "infinity"))
+            .respond_with(
+                ResponseTemplate::new(207).set_body_string(new_multistatus(&[
+                    ("/", None),
+                    ("/dir/", None),
+                    ("/dir/file", Some(2)),
+                    ("/file", Some(1)),
+                ])),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = WebdavBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        let backend = builder.build()?;
+
+        let (_, mut pager) = backend.scan("/", OpScan::new()).await?;
+        let oes = pager.next().await?.expect("entries must exist");
+        let file = oes
+            .iter()
+            .find(|v| v.path() == "dir/file")
+            .expect("file must exist");
+        assert_eq!(
+            file,
+            &oio::Entry::new(
+                "dir/file",
+                Metadata::new(EntryMode::FILE)
+                    .with_content_length(2)
+                    .with_last_modified(OffsetDateTime::parse(
+                        "Fri, 17 Feb 2023 03:37:22 GMT",
+                        &Rfc2822,
+                    )?),
+            )
+        );
+
+        assert_eq!(
+            scan_all(&backend).await?,
+            vec![
+                ("dir/".to_string(), EntryMode::DIR),
+                ("dir/file".to_string(), EntryMode::FILE),
+                ("file".to_string(), EntryMode::FILE),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scan_fallback_to_depth_one() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(header("Depth", "infinity"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(
+                r#"<?xml version="1.0" encoding="utf-8"?>
+                <D:error xmlns:D="DAV:"><D:propfind-finite-depth/></D:error>"#,
+            ))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/"))
+            .and(header("Depth", "1"))
+            .respond_with(
+                ResponseTemplate::new(207).set_body_string(new_multistatus(&[
+                    ("/", None),
+                    // Collections without tailing `/` must be treated as dir.
+                    ("/dir", None),
+                    ("/file", Some(1)),
+                ])),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dir/"))
+            .and(header("Depth", "1"))
+            .respond_with(
+                ResponseTemplate::new(207)
+                    .set_body_string(new_multistatus(&[("/dir/", None), ("/dir/file", Some(2))])),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = WebdavBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        let backend = builder.build()?;
+
+        assert_eq!(
+            scan_all(&backend).await?,
+            vec![
+                ("dir/".to_string(), EntryMode::DIR),
+                ("dir/file".to_string(), EntryMode::FILE),
+                ("file".to_string(), EntryMode::FILE),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_via_propfind() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/file"))
+            .and(header("Depth", "0"))
+            .respond_with(
+                ResponseTemplate::new(207).set_body_string(new_multistatus(&[("/file", Some(3))])),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/dir/"))
+            .and(header("Depth", "0"))
+            .respond_with(
+                ResponseTemplate::new(207).set_body_string(new_multistatus(&[("/dir/", None)])),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = WebdavBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        let backend = builder.build()?;
+
+        let meta = backend.stat("file", OpStat::new()).await?.into_metadata();
+        assert_eq!(meta.mode(), EntryMode::FILE);
+        assert_eq!(meta.content_length(), 3);
+
+        let meta = backend.stat("dir/", OpStat::new()).await?.into_metadata();
+        assert_eq!(meta.mode(), EntryMode::DIR);
+
+        let err = backend
+            .stat("not_exist", OpStat::new())
+            .await
+            .expect_err("stat must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_with_endpoint_path() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PROPFIND"))
+            .and(path("/remote.php/dav/files/admin/data/"))
+            .and(header("Depth", "1"))
+            .respond_with(
+                ResponseTemplate::new(207).set_body_string(new_multistatus(&[
+                    ("/remote.php/dav/files/admin/data/", None),
+                    ("/remote.php/dav/files/admin/data/dir/", None),
+                    ("/remote.php/dav/files/admin/data/a%20file", Some(1)),
+                ])),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = WebdavBuilder::default();
+        builder
+            .endpoint(&format!("{}/remote.php/dav/files/admin", mock_server.uri()))
+            .root("/data/");
+        let backend = builder.build()?;
+
+        let (_, mut pager) = backend.list("/", OpList::new()).await?;
+        let mut entries = Vec::new();
+        while let Some(oes) = pager.next().await? {
+            entries.extend(oes.into_iter().map(|v| (v.path().to_string(), v.mode())));
+        }
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            entries,
+            vec![
+                ("a file".to_string(), EntryMode::FILE),
+                ("dir/".to_string(), EntryMode::DIR),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_with_lock() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("LOCK"))
+            .and(path("/file"))
+            .respond_with(
+                ResponseTemplate::new(200).insert_header("Lock-Token", "<opaquelocktoken:abc>"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/file"))
+            .and(header("If", "(<opaquelocktoken:abc>)"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("UNLOCK"))
+            .and(path("/file"))
+            .and(header("Lock-Token", "<opaquelocktoken:abc>"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = WebdavBuilder::default();
+        builder.endpoint(&mock_server.uri()).enable_lock();
+        let backend = builder.build()?;
+
+        let (_, mut w) = backend.write("file", OpWrite::new()).await?;
+        oio::Write::write(&mut w, Bytes::from("hello")).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_creates_parents_on_conflict() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/dir/file"))
+            .respond_with(ResponseTemplate::new(409))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("MKCOL"))
+            .and(path("/dir/"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/dir/file"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut builder = WebdavBuilder::default();
+        builder.endpoint(&mock_server.uri());
+        let backend = builder.build()?;
+
+        let (_, mut w) = backend.write("dir/file", OpWrite::new()).await?;
+        oio::Write::write(&mut w, Bytes::from("hello")).await?;
+        Ok(())
+    }
+}
@@ -13,16 +13,89 @@
 // limitations under the License.
 
 use serde::Deserialize;
+use time::format_description::well_known::Rfc2822;
+use time::OffsetDateTime;
 
+use crate::EntryMode;
+use crate::Error;
+use crate::ErrorKind;
+use crate::Metadata;
+use crate::Result;
+
+/// The multistatus response of `PROPFIND`.
+///
+/// # Notes
+///
+/// Servers use different prefixes for the `DAV:` namespace, like `d:` for
+/// sabre (Nextcloud), `D:` and `lp1:` for Apache mod_dav and `a:` for IIS.
+/// Elements are matched by their local names, so the parser works no
+/// matter which prefix is bound to the namespace.
 #[derive(Deserialize, Debug, PartialEq)]
 pub struct Multistatus {
+    #[serde(default)]
     pub response: Vec<ListOpResponse>,
 }
 
 #[derive(Deserialize, Debug, PartialEq)]
 pub struct ListOpResponse {
     pub href: String,
-    pub propstat: Propstat,
+    /// Servers could return multiple propstats for different status, for
+    /// example, `404 Not Found` for the properties that don't exist.
+    #[serde(default)]
+    pub propstat: Vec<Propstat>,
+}
+
+impl ListOpResponse {
+    /// Get the props that are returned successfully.
+    pub fn prop(&self) -> Option<&Prop> {
+        self.propstat
+            .iter()
+            .find(|v| v.status.split_whitespace().nth(1) == Some("200"))
+            .map(|v| &v.prop)
+    }
+
+    /// Parse props into metadata.
+    pub fn parse_into_metadata(&self) -> Result<Metadata> {
+        let prop = self.prop().ok_or_else(|| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "propfind response doesn't have props",
+            )
+            .with_context("href", &self.href)
+        })?;
+
+        let mode = if prop.resourcetype.value == Some(ResourceType::Collection) {
+            EntryMode::DIR
+        } else {
+            EntryMode::FILE
+        };
+        let mut meta = Metadata::new(mode);
+
+        if !prop.getlastmodified.is_empty() {
+            meta.set_last_modified(
+                OffsetDateTime::parse(&prop.getlastmodified, &Rfc2822).map_err(|e| {
+                    Error::new(
+                        ErrorKind::Unexpected,
+                        "last modified is not valid RFC2822 datetime",
+                    )
+                    .with_context("href", &self.href)
+                    .set_source(e)
+                })?,
+            );
+        }
+        if let Some(v) = prop.getcontentlength {
+            meta.set_content_length(v);
+        }
+        // Some servers like IIS return empty elements for missing props.
+        if let Some(v) = prop.getetag.as_deref().filter(|v| !v.is_empty()) {
+            meta.set_etag(v);
+        }
+        if let Some(v) = prop.getcontenttype.as_deref().filter(|v| !v.is_empty()) {
+            meta.set_content_type(v);
+        }
+
+        Ok(meta)
+    }
 }
 
 #[derive(Deserialize, Debug, PartialEq)]
@@ -33,11 +106,20 @@ pub struct Propstat {
 
 #[derive(Deserialize, Debug, PartialEq)]
 pub struct Prop {
+    #[serde(default)]
     pub getlastmodified: String,
+    /// Collections don't have content length.
+    #[serde(default)]
+    pub getcontentlength: Option<u64>,
+    #[serde(default)]
+    pub getetag: Option<String>,
+    #[serde(default)]
+    pub getcontenttype: Option<String>,
+    #[serde(default)]
     pub resourcetype: ResourceTypeContainer,
 }
 
-#[derive(Deserialize, Debug, PartialEq)]
+#[derive(Deserialize, Debug, PartialEq, Default)]
 pub struct ResourceTypeContainer {
     #[serde(rename = "$value")]
     pub value: Option<ResourceType>,
@@ -110,14 +192,14 @@ mod tests {
         let response = from_str::<ListOpResponse>(xml).unwrap();
         assert_eq!(response.href, "/");
         assert_eq!(
-            response.propstat.prop.getlastmodified,
+            response.propstat[0].prop.getlastmodified,
             "Tue, 01 May 2022 06:39:47 GMT"
         );
         assert_eq!(
-            response.propstat.prop.resourcetype.value.unwrap(),
-            ResourceType::Collection
+            response.propstat[0].prop.resourcetype.value,
+            Some(ResourceType::Collection)
         );
-        assert_eq!(response.propstat.status, "HTTP/1.1 200 OK");
+        assert_eq!(response.propstat[0].status, "HTTP/1.1 200 OK");
     }
 
     #[test]
@@ -149,11 +231,12 @@ mod tests {
         let response = from_str::<ListOpResponse>(xml).unwrap();
         assert_eq!(response.href, "/test_file");
         assert_eq!(
-            response.propstat.prop.getlastmodified,
+            response.propstat[0].prop.getlastmodified,
             "Tue, 07 May 2022 05:52:22 GMT"
         );
-        assert_eq!(response.propstat.prop.resourcetype.value, None);
-        assert_eq!(response.propstat.status, "HTTP/1.1 200 OK");
+        assert_eq!(response.propstat[0].prop.getcontentlength, Some(1));
+        assert_eq!(response.propstat[0].prop.resourcetype.value, None);
+        assert_eq!(response.propstat[0].status, "HTTP/1.1 200 OK");
     }
 
     #[test]
@@ -201,7 +284,7 @@ mod tests {
         assert_eq!(multistatus.response.len(), 2);
         assert_eq!(multistatus.response[0].href, "/");
         assert_eq!(
-            multistatus.response[0].propstat.prop.getlastmodified,
+            multistatus.response[0].propstat[0].prop.getlastmodified,
             "Tue, 01 May 2022 06:39:47 GMT"
         );
     }
@@ -289,21 +372,21 @@ mod tests {
         let first_response = &multistatus.response[0];
         assert_eq!(first_response.href, "/");
         assert_eq!(
-            first_response.propstat.prop.getlastmodified,
+            first_response.propstat[0].prop.getlastmodified,
             "Tue, 07 May 2022 06:39:47 GMT"
         );
 
         let second_response = &multistatus.response[1];
         assert_eq!(second_response.href, "/testdir/");
         assert_eq!(
-            second_response.propstat.prop.getlastmodified,
+            second_response.propstat[0].prop.getlastmodified,
             "Tue, 07 May 2022 06:40:10 GMT"
         );
 
         let third_response = &multistatus.response[2];
         assert_eq!(third_response.href, "/test_file");
         assert_eq!(
-            third_response.propstat.prop.getlastmodified,
+            third_response.propstat[0].prop.getlastmodified,
             "Tue, 07 May 2022 05:52:22 GMT"
         );
     }
@@ -422,8 +505,154 @@ mod tests {
         let first_response = &multistatus.response[0];
         assert_eq!(first_response.href, "/");
         assert_eq!(
-            first_response.propstat.prop.getlastmodified,
+            first_response.propstat[0].prop.getlastmodified,
             "Fri, 17 Feb 2023 03:37:22 GMT"
         );
     }
+
+    #[test]
+    fn test_nextcloud() {
+        let xml = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:" xmlns:s="http://sabredav.org/ns" xmlns:oc="http://owncloud.org/ns" xmlns:nc="http://nextcloud.org/ns">
+  <d:response>
+    <d:href>/remote.php/dav/files/admin/opendal/</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:getlastmodified>Mon, 13 Mar 2023 08:13:24 GMT</d:getlastmodified>
+        <d:resourcetype><d:collection/></d:resourcetype>
+        <d:quota-used-bytes>11</d:quota-used-bytes>
+        <d:quota-available-bytes>-3</d:quota-available-bytes>
+        <d:getetag>&quot;640eda14a5ec4&quot;</d:getetag>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/remote.php/dav/files/admin/opendal/hello%20world.txt</d:href>
+    <d:propstat>
+      <d:prop>
+        <d:getlastmodified>Mon, 13 Mar 2023 08:13:24 GMT</d:getlastmodified>
+        <d:getcontentlength>11</d:getcontentlength>
+        <d:resourcetype/>
+        <d:getetag>&quot;2ce3e4a4a3e6b4c1f2f2b4b5b8ae1f0c&quot;</d:getetag>
+        <d:getcontenttype>text/plain</d:getcontenttype>
+      </d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+    <d:propstat>
+      <d:prop>
+        <oc:checksums/>
+      </d:prop>
+      <d:status>HTTP/1.1 404 Not Found</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+        let multistatus: Multistatus = from_str(xml).unwrap();
+        assert_eq!(multistatus.response.len(), 2);
+
+        let dir = &multistatus.response[0];
+        assert_eq!(dir.href, "/remote.php/dav/files/admin/opendal/");
+        let meta = dir.parse_into_metadata().unwrap();
+        assert_eq!(meta.mode(), EntryMode::DIR);
+        assert_eq!(meta.etag(), Some("\"640eda14a5ec4\""));
+
+        let file = &multistatus.response[1];
+        assert_eq!(file.propstat.len(), 2);
+        let meta = file.parse_into_metadata().unwrap();
+        assert_eq!(meta.mode(), EntryMode::FILE);
+        assert_eq!(meta.content_length(), 11);
+        assert_eq!(meta.content_type(), Some("text/plain"));
+        assert_eq!(meta.etag(), Some("\"2ce3e4a4a3e6b4c1f2f2b4b5b8ae1f0c\""));
+        assert_eq!(
+            meta.last_modified(),
+            Some(OffsetDateTime::parse("Mon, 13 Mar 2023 08:13:24 GMT", &Rfc2822).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_apache_mod_dav() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<D:multistatus xmlns:D="DAV:" xmlns:ns0="DAV:">
+<D:response xmlns:lp1="DAV:" xmlns:lp2="http://apache.org/dav/props/">
+<D:href>/dav/dir/</D:href>
+<D:propstat>
+<D:prop>
+<lp1:resourcetype><D:collection/></lp1:resourcetype>
+<lp1:creationdate>2023-03-13T08:13:24Z</lp1:creationdate>
+<lp1:getlastmodified>Mon, 13 Mar 2023 08:13:24 GMT</lp1:getlastmodified>
+<lp1:getetag>"1000-5f6c4f1e3b0c0"</lp1:getetag>
+<D:supportedlock>
+<D:lockentry>
+<D:lockscope><D:exclusive/></D:lockscope>
+<D:locktype><D:write/></D:locktype>
+</D:lockentry>
+</D:supportedlock>
+<D:lockdiscovery/>
+<D:getcontenttype>httpd/unix-directory</D:getcontenttype>
+</D:prop>
+<D:status>HTTP/1.1 200 OK</D:status>
+</D:propstat>
+</D:response>
+<D:response xmlns:lp1="DAV:" xmlns:lp2="http://apache.org/dav/props/">
+<D:href>/dav/dir/file.txt</D:href>
+<D:propstat>
+<D:prop>
+<lp1:resourcetype/>
+<lp1:creationdate>2023-03-13T08:13:24Z</lp1:creationdate>
+<lp1:getcontentlength>12</lp1:getcontentlength>
+<lp1:getlastmodified>Mon, 13 Mar 2023 08:13:24 GMT</lp1:getlastmodified>
+<lp1:getetag>"c-5f6c4f1e3b0c0"</lp1:getetag>
+<lp2:executable>F</lp2:executable>
+<D:supportedlock>
+<D:lockentry>
+<D:lockscope><D:exclusive/></D:lockscope>
+<D:locktype><D:write/></D:locktype>
+</D:lockentry>
+</D:supportedlock>
+<D:lockdiscovery/>
+<D:getcontenttype>text/plain</D:getcontenttype>
+</D:prop>
+<D:status>HTTP/1.1 200 OK</D:status>
+</D:propstat>
+</D:response>
+</D:multistatus>"#;
+
+        let multistatus: Multistatus = from_str(xml).unwrap();
+        assert_eq!(multistatus.response.len(), 2);
+
+        let meta = multistatus.response[0].parse_into_metadata().unwrap();
+        assert_eq!(meta.mode(), EntryMode::DIR);
+
+        let file = &multistatus.response[1];
+        assert_eq!(file.href, "/dav/dir/file.txt");
+        let meta = file.parse_into_metadata().unwrap();
+        assert_eq!(meta.mode(), EntryMode::FILE);
+        assert_eq!(meta.content_length(), 12);
+        assert_eq!(meta.etag(), Some("\"c-5f6c4f1e3b0c0\""));
+        assert_eq!(meta.content_type(), Some("text/plain"));
+    }
+
+    #[test]
+    fn test_iis() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?><a:multistatus xmlns:b="urn:uuid:c2f41010-65b3-11d1-a29f-00aa00c14882/" xmlns:a="DAV:"><a:response><a:href>http://iis.local/dav/</a:href><a:propstat><a:status>HTTP/1.1 200 OK</a:status><a:prop><a:getcontentlength b:dt="int">0</a:getcontentlength><a:creationdate b:dt="dateTime.tz">2023-03-13T08:13:24.000Z</a:creationdate><a:displayname>dav</a:displayname><a:getetag>"a0b7c1d2e5f6d91:0"</a:getetag><a:getlastmodified b:dt="dateTime.rfc1123">Mon, 13 Mar 2023 08:13:24 GMT</a:getlastmodified><a:resourcetype><a:collection/></a:resourcetype><a:supportedlock/><a:ishidden b:dt="boolean">0</a:ishidden><a:iscollection b:dt="boolean">1</a:iscollection><a:getcontenttype/></a:prop></a:propstat></a:response><a:response><a:href>http://iis.local/dav/file.txt</a:href><a:propstat><a:status>HTTP/1.1 200 OK</a:status><a:prop><a:getcontentlength b:dt="int">11</a:getcontentlength><a:creationdate b:dt="dateTime.tz">2023-03-13T08:13:24.000Z</a:creationdate><a:displayname>file.txt</a:displayname><a:getetag>"80a6c3d2e5f6d91:0"</a:getetag><a:getlastmodified b:dt="dateTime.rfc1123">Mon, 13 Mar 2023 08:13:24 GMT</a:getlastmodified><a:resourcetype/><a:supportedlock/><a:ishidden b:dt="boolean">0</a:ishidden><a:iscollection b:dt="boolean">0</a:iscollection><a:getcontenttype>text/plain</a:getcontenttype></a:prop></a:propstat></a:response></a:multistatus>"#;
+
+        let multistatus: Multistatus = from_str(xml).unwrap();
+        assert_eq!(multistatus.response.len(), 2);
+
+        let dir = &multistatus.response[0];
+        assert_eq!(dir.href, "http://iis.local/dav/");
+        assert_eq!(dir.parse_into_metadata().unwrap().mode(), EntryMode::DIR);
+
+        let file = &multistatus.response[1];
+        assert_eq!(file.href, "http://iis.local/dav/file.txt");
+        let meta = file.parse_into_metadata().unwrap();
+        assert_eq!(meta.mode(), EntryMode::FILE);
+        assert_eq!(meta.content_length(), 11);
+        assert_eq!(meta.etag(), Some("\"80a6c3d2e5f6d91:0\""));
+        assert_eq!(
+            meta.last_modified(),
+            Some(OffsetDateTime::parse("Mon, 13 Mar 2023 08:13:24 GMT", &Rfc2822).unwrap())
+        );
+    }
 }
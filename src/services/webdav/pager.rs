@@ -12,21 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::VecDeque;
 use std::mem;
 
 use async_trait::async_trait;
+use percent_encoding::percent_decode_str;
 
+use super::backend::WebdavBackend;
 use super::list_response::Multistatus;
 use crate::raw::build_rel_path;
 use crate::raw::oio;
 use crate::EntryMode;
-use crate::Metadata;
 use crate::Result;
 
 pub struct WebdavPager {
+    /// The root as returned in href, including the path of endpoint.
     root: String,
     path: String,
     multistates: Multistatus,
+
+    /// Only set while scanning on servers that reject `Depth: infinity`,
+    /// the backend will be used to list the dirs one by one.
+    backend: Option<WebdavBackend>,
+    dirs: VecDeque<String>,
 }
 
 impl WebdavPager {
@@ -35,6 +43,22 @@ impl WebdavPager {
             root: root.into(),
             path: path.into(),
             multistates,
+            backend: None,
+            dirs: VecDeque::new(),
+        }
+    }
+
+    /// Create a pager that scans given path recursively by listing
+    /// every dir with depth 1.
+    pub fn new_recursive(backend: WebdavBackend, root: &str, path: &str) -> Self {
+        Self {
+            root: root.into(),
+            path: path.into(),
+            multistates: Multistatus {
+                response: Vec::new(),
+            },
+            backend: Some(backend),
+            dirs: VecDeque::from([path.to_string()]),
         }
     }
 }
@@ -42,38 +66,90 @@ impl WebdavPager {
 #[async_trait]
 impl oio::Page for WebdavPager {
     async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
-        if self.multistates.response.is_empty() {
-            return Ok(None);
-        };
-        let oes = mem::take(&mut self.multistates.response);
-
-        let oes = oes
-            .into_iter()
-            .filter_map(|de| {
-                let path = de.href;
-                let normalized_path = if self.root != path {
-                    build_rel_path(&self.root, &path)
-                } else {
-                    path
-                };
-
-                if normalized_path == self.path {
-                    // WebDav server may return the current path as an entry.
-                    return None;
+        loop {
+            if !self.multistates.response.is_empty() {
+                let oes = mem::take(&mut self.multistates.response);
+
+                let mut entries = Vec::with_capacity(oes.len());
+                for de in oes {
+                    let meta = de.parse_into_metadata()?;
+                    let mode = meta.mode();
+
+                    let path = normalize_href(&de.href);
+                    let mut normalized_path =
+                        if self.root.trim_end_matches('/') == path.trim_end_matches('/') {
+                            "/".to_string()
+                        } else {
+                            build_rel_path(&self.root, &path)
+                        };
+                    // Some servers return collections without tailing `/`.
+                    if mode == EntryMode::DIR && !normalized_path.ends_with('/') {
+                        normalized_path.push('/');
+                    }
+
+                    if normalized_path == self.path {
+                        // WebDav server may return the current path as an entry.
+                        continue;
+                    }
+
+                    if self.backend.is_some() && mode == EntryMode::DIR {
+                        self.dirs.push_back(normalized_path.clone());
+                    }
+
+                    entries.push(oio::Entry::new(&normalized_path, meta));
                 }
 
-                let entry = if de.propstat.prop.resourcetype.value
-                    == Some(super::list_response::ResourceType::Collection)
-                {
-                    oio::Entry::new(&normalized_path, Metadata::new(EntryMode::DIR))
-                } else {
-                    oio::Entry::new(&normalized_path, Metadata::new(EntryMode::FILE))
-                };
+                if !entries.is_empty() {
+                    return Ok(Some(entries));
+                }
+            }
+
+            let (backend, dir) = match (&self.backend, self.dirs.pop_front()) {
+                (Some(backend), Some(dir)) => (backend, dir),
+                _ => return Ok(None),
+            };
+
+            self.multistates = backend.webdav_list(&dir).await?;
+            self.path = dir;
+        }
+    }
+}
 
-                Some(entry)
-            })
-            .collect();
+/// Normalize the href returned by server into an absolute path.
+///
+/// Servers like IIS return full urls in href, and all hrefs are percent
+/// encoded.
+pub fn normalize_href(href: &str) -> String {
+    let path = match href.find("://") {
+        Some(idx) => {
+            let rest = &href[idx + 3..];
+            rest.find('/').map(|v| &rest[v..]).unwrap_or("/")
+        }
+        None => href,
+    };
+
+    percent_decode_str(path).decode_utf8_lossy().to_string()
+}
 
-        Ok(Some(oes))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_href() {
+        let cases = vec![
+            ("absolute path", "/dav/file.txt", "/dav/file.txt"),
+            (
+                "percent encoded",
+                "/dav/hello%20world.txt",
+                "/dav/hello world.txt",
+            ),
+            ("full url", "http://iis.local/dav/file.txt", "/dav/file.txt"),
+            ("full url without path", "https://iis.local", "/"),
+        ];
+
+        for (name, input, expected) in cases {
+            assert_eq!(normalize_href(input), expected, "{name}");
+        }
     }
 }
@@ -14,6 +14,7 @@
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use http::Response;
 use http::StatusCode;
 
 use super::backend::WebdavBackend;
@@ -35,23 +36,76 @@ impl WebdavWriter {
     }
 }
 
-#[async_trait]
-impl oio::Write for WebdavWriter {
-    async fn write(&mut self, bs: Bytes) -> Result<()> {
-        let resp = self
-            .backend
+impl WebdavWriter {
+    async fn put(
+        &self,
+        bs: Bytes,
+        lock_token: Option<&str>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        self.backend
             .webdav_put(
                 &self.path,
                 Some(bs.len()),
                 self.op.content_type(),
                 self.op.content_disposition(),
+                lock_token,
                 AsyncBody::Bytes(bs),
             )
-            .await?;
+            .await
+    }
+
+    async fn lock(&self) -> Result<String> {
+        let mut resp = self.backend.webdav_lock(&self.path).await?;
+
+        // Parent dirs are not exist, create them and try again.
+        if resp.status() == StatusCode::CONFLICT {
+            resp.into_body().consume().await?;
+            self.backend.webdav_create_parents(&self.path).await?;
+            resp = self.backend.webdav_lock(&self.path).await?;
+        }
+
+        match resp.status() {
+            StatusCode::OK | StatusCode::CREATED => {
+                let token = resp
+                    .headers()
+                    .get("Lock-Token")
+                    .and_then(|v| v.to_str().ok())
+                    .map(|v| v.to_string())
+                    .ok_or_else(|| {
+                        Error::new(ErrorKind::Unexpected, "lock response has no Lock-Token")
+                            .with_operation("WebdavWriter::lock")
+                            .with_context("path", &self.path)
+                    })?;
+                resp.into_body().consume().await?;
+                Ok(token)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
 
-        let status = resp.status();
+    async fn unlock(&self, lock_token: &str) -> Result<()> {
+        let resp = self.backend.webdav_unlock(&self.path, lock_token).await?;
 
-        match status {
+        match resp.status() {
+            StatusCode::OK | StatusCode::NO_CONTENT => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn write_with_token(&self, bs: Bytes, lock_token: Option<&str>) -> Result<()> {
+        let mut resp = self.put(bs.clone(), lock_token).await?;
+
+        // Parent dirs are not exist, create them and try again.
+        if resp.status() == StatusCode::CONFLICT {
+            resp.into_body().consume().await?;
+            self.backend.webdav_create_parents(&self.path).await?;
+            resp = self.put(bs, lock_token).await?;
+        }
+
+        match resp.status() {
             StatusCode::CREATED | StatusCode::OK | StatusCode::NO_CONTENT => {
                 resp.into_body().consume().await?;
                 Ok(())
@@ -59,6 +113,23 @@ impl oio::Write for WebdavWriter {
             _ => Err(parse_error(resp).await?),
         }
     }
+}
+
+#[async_trait]
+impl oio::Write for WebdavWriter {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        if !self.backend.enable_lock {
+            return self.write_with_token(bs, None).await;
+        }
+
+        let token = self.lock().await?;
+        let res = self.write_with_token(bs, Some(&token)).await;
+        let unlocked = self.unlock(&token).await;
+
+        // Error of write is more important than unlock.
+        res?;
+        unlocked
+    }
 
     async fn append(&mut self, bs: Bytes) -> Result<()> {
         let _ = bs;
@@ -69,7 +140,11 @@ impl oio::Write for WebdavWriter {
         ))
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn close(&mut self) -> Result<RpWrite> {
+        Ok(RpWrite::new())
+    }
+
+    async fn abort(&mut self) -> Result<()> {
         Ok(())
     }
 }
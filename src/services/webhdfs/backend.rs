@@ -58,6 +58,18 @@ const WEBHDFS_DEFAULT_ENDPOINT: &str = "http://127.0.0.1:9870";
 /// - [ ] ~~presign~~
 /// - [ ] blocking
 ///
+/// # Append
+///
+/// Appending via returned writer is supported: the first chunk will create
+/// the file and the following chunks will be appended via `APPEND`. Writing
+/// with [`OpWrite::with_append_existing`] will append all data to the
+/// existing file instead.
+///
+/// # Delegation
+///
+/// If `delegation` is set, the token will be sent in every request,
+/// including the ones redirected to datanodes.
+///
 /// # Differences with hdfs
 ///
 /// [Hdfs][crate::services::Hdfs] is powered by HDFS's native java client. Users need to setup the hdfs services correctly. But webhdfs can access from HTTP API and no extra setup needed.
@@ -172,7 +184,7 @@ impl WebhdfsBuilder {
 impl WebhdfsBuilder {
     fn auth_str(&mut self) -> Option<String> {
         if let Some(dt) = self.delegation.take() {
-            return Some(format!("delegation_token={dt}"));
+            return Some(format!("delegation={dt}"));
         }
         None
     }
@@ -285,13 +297,7 @@ impl WebhdfsBackend {
             return Ok(req);
         }
 
-        let resp = self.client.send_async(req).await?;
-
-        // should be a 307 TEMPORARY_REDIRECT
-        if resp.status() != StatusCode::TEMPORARY_REDIRECT {
-            return Err(parse_error(resp).await?);
-        }
-        let re_url = self.follow_redirect(resp)?;
+        let re_url = self.webhdfs_redirect(req).await?;
 
         let mut re_builder = Request::put(re_url);
         if let Some(size) = size {
@@ -304,6 +310,35 @@ impl WebhdfsBackend {
         re_builder.body(body).map_err(new_request_build_error)
     }
 
+    /// append to an existing file
+    pub async fn webhdfs_append_object_req(
+        &self,
+        path: &str,
+        size: usize,
+        body: AsyncBody,
+    ) -> Result<Request<AsyncBody>> {
+        let p = build_abs_path(&self.root, path);
+        let mut url = format!(
+            "{}/webhdfs/v1/{}?op=APPEND",
+            self.endpoint,
+            percent_encode_path(&p),
+        );
+        if let Some(auth) = &self.auth {
+            url += format!("&{auth}").as_str();
+        }
+
+        let req = Request::post(&url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        let re_url = self.webhdfs_redirect(req).await?;
+
+        Request::post(re_url)
+            .header(CONTENT_LENGTH, size.to_string())
+            .body(body)
+            .map_err(new_request_build_error)
+    }
+
     async fn webhdfs_open_req(&self, path: &str, range: &BytesRange) -> Result<Request<AsyncBody>> {
         let p = build_abs_path(&self.root, path);
         let mut url = format!(
@@ -315,22 +350,6 @@ impl WebhdfsBackend {
             url += format!("&{auth}").as_str();
         }
 
-        // make a Webhdfs compatible bytes range
-        //
-        // Webhdfs does not support read from end
-        // have to solve manually
-        let range = match (range.offset(), range.size()) {
-            // avoiding reading the whole file
-            (None, Some(size)) => {
-                debug!("converting bytes range to webhdfs compatible");
-                let status = self.stat(path, OpStat::default()).await?;
-                let total_size = status.into_metadata().content_length();
-                let offset = total_size - size;
-                BytesRange::new(Some(offset), Some(size))
-            }
-            _ => *range,
-        };
-
         let (offset, size) = (range.offset(), range.size());
 
         match (offset, size) {
@@ -343,9 +362,13 @@ impl WebhdfsBackend {
             (None, None) => {
                 // read all, do nothing
             }
+            // Webhdfs doesn't support read from end, suffix range will be
+            // resolved into an absolute range before reaching here.
             (None, Some(_)) => {
-                // already handled
-                unreachable!()
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "webhdfs doesn't support read with suffix range",
+                ))
             }
         }
 
@@ -390,9 +413,10 @@ impl WebhdfsBackend {
         let req = self.webhdfs_open_req(path, &range).await?;
         let resp = self.client.send_async(req).await?;
 
-        // this should be a 307 redirect
+        // Namenode will redirect us to the datanode, but gateways like
+        // HttpFS could return the data directly.
         if resp.status() != StatusCode::TEMPORARY_REDIRECT {
-            return Err(parse_error(resp).await?);
+            return Ok(resp);
         }
 
         let re_url = self.follow_redirect(resp)?;
@@ -443,9 +467,24 @@ impl WebhdfsBackend {
 }
 
 impl WebhdfsBackend {
+    /// Send the request to namenode and get the datanode location.
+    ///
+    /// CREATE and APPEND are two-step operations: namenode responds with
+    /// `307 TEMPORARY_REDIRECT` and the data should be sent to the
+    /// redirected datanode only. So the request here must carry no body.
+    async fn webhdfs_redirect(&self, req: Request<AsyncBody>) -> Result<String> {
+        let resp = self.client.send_async(req).await?;
+
+        if resp.status() != StatusCode::TEMPORARY_REDIRECT {
+            return Err(parse_error(resp).await?);
+        }
+
+        self.follow_redirect(resp)
+    }
+
     /// get redirect destination from 307 TEMPORARY_REDIRECT http response
     fn follow_redirect(&self, resp: Response<IncomingAsyncBody>) -> Result<String> {
-        let loc = match parse_location(resp.headers())? {
+        let mut loc = match parse_location(resp.headers())? {
             Some(p) => {
                 if !p.starts_with('/') {
                     // is not relative path
@@ -453,8 +492,7 @@ impl WebhdfsBackend {
                 } else {
                     // is relative path
                     // prefix with endpoint url
-                    let url = self.endpoint.clone();
-                    format!("{url}/{p}")
+                    format!("{}{p}", self.endpoint)
                 }
             }
             None => {
@@ -465,16 +503,28 @@ impl WebhdfsBackend {
                 return Err(err);
             }
         };
+
+        // Namenode will carry the delegation token in location, but we
+        // should make sure datanode receives it too.
+        if let Some(auth) = &self.auth {
+            if !loc.contains("delegation=") {
+                let sep = if loc.contains('?') { '&' } else { '?' };
+                loc = format!("{loc}{sep}{auth}");
+            }
+        }
+
         Ok(loc)
     }
 
     fn consume_success_mkdir(&self, path: &str, parts: Parts, body: &str) -> Result<RpCreate> {
         let mkdir_rsp = serde_json::from_str::<BooleanResp>(body).map_err(|e| {
-            Error::new(ErrorKind::Unexpected, "cannot parse mkdir response")
-                .set_temporary()
-                .with_context("service", Scheme::Webhdfs)
-                .with_context("response", format!("{parts:?}"))
-                .set_source(e)
+            with_error_response_context(
+                Error::new(ErrorKind::Unexpected, "cannot parse mkdir response")
+                    .set_temporary()
+                    .with_context("service", Scheme::Webhdfs),
+                &parts,
+            )
+            .set_source(e)
         })?;
 
         if mkdir_rsp.boolean {
@@ -541,6 +591,21 @@ impl Accessor for WebhdfsBackend {
             .set_capabilities(
                 AccessorCapability::Read | AccessorCapability::Write | AccessorCapability::List,
             )
+            .set_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_next: true,
+                read_with_range: true,
+                write: true,
+                write_can_append: true,
+                write_with_content_type: true,
+                write_with_append_existing: true,
+                create_dir: true,
+                delete: true,
+                list: true,
+                list_with_delimiter: true,
+                ..Default::default()
+            })
             .set_hints(AccessorHint::ReadStreamable);
         am
     }
@@ -600,13 +665,6 @@ impl Accessor for WebhdfsBackend {
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        if args.append() {
-            return Err(Error::new(
-                ErrorKind::Unsupported,
-                "append write is not supported",
-            ));
-        }
-
         Ok((
             RpWrite::default(),
             WebhdfsWriter::new(self.clone(), args, path.to_string()),
@@ -688,3 +746,162 @@ impl Accessor for WebhdfsBackend {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::body_string;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+    use wiremock::matchers::query_param;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+
+    use super::*;
+    use crate::services::Webhdfs;
+
+    fn new_operator(server: &MockServer) -> Operator {
+        let mut builder = Webhdfs::default();
+        builder
+            .root("/root/")
+            .endpoint(&server.uri())
+            .delegation("token");
+
+        Operator::new(builder).unwrap().finish()
+    }
+
+    #[tokio::test]
+    async fn test_write_with_redirect() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        // Namenode must not receive the body.
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/root/file"))
+            .and(query_param("op", "CREATE"))
+            .and(query_param("delegation", "token"))
+            .and(body_string(""))
+            .respond_with(
+                ResponseTemplate::new(307).insert_header(
+                    "location",
+                    format!(
+                        "{}/datanode/root/file?op=CREATE&delegation=token",
+                        server.uri()
+                    )
+                    .as_str(),
+                ),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datanode/root/file"))
+            .and(query_param("delegation", "token"))
+            .and(body_string("Hello, World!"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let op = new_operator(&server);
+        op.write("file", "Hello, World!").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_with_relative_redirect() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/root/file"))
+            .and(query_param("op", "OPEN"))
+            .and(query_param("delegation", "token"))
+            .respond_with(
+                ResponseTemplate::new(307).insert_header("location", "/datanode/root/file?op=OPEN"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        // Delegation token must be carried even if location doesn't have it.
+        Mock::given(method("GET"))
+            .and(path("/datanode/root/file"))
+            .and(query_param("delegation", "token"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("Hello, World!"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let op = new_operator(&server);
+        let bs = op.read("file").await.unwrap();
+        assert_eq!(bs, b"Hello, World!");
+    }
+
+    #[tokio::test]
+    async fn test_append_with_redirect() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/webhdfs/v1/root/file"))
+            .and(query_param("op", "CREATE"))
+            .and(body_string(""))
+            .respond_with(
+                ResponseTemplate::new(307).insert_header("location", "/datanode/create/root/file"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/datanode/create/root/file"))
+            .and(body_string("Hello, "))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/webhdfs/v1/root/file"))
+            .and(query_param("op", "APPEND"))
+            .and(query_param("delegation", "token"))
+            .and(body_string(""))
+            .respond_with(
+                ResponseTemplate::new(307).insert_header("location", "/datanode/append/root/file"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/datanode/append/root/file"))
+            .and(query_param("delegation", "token"))
+            .and(body_string("World!"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let op = new_operator(&server);
+        let mut w = op.writer("file").await.unwrap();
+        w.append("Hello, ").await.unwrap();
+        w.append("World!").await.unwrap();
+        w.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_with_remote_exception() {
+        let _ = env_logger::try_init();
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/webhdfs/v1/root/file"))
+            .and(query_param("op", "OPEN"))
+            .respond_with(ResponseTemplate::new(403).set_body_string(
+                r#"{"RemoteException":{"exception":"AccessControlException","javaClassName":"org.apache.hadoop.security.AccessControlException","message":"Permission denied: user=test"}}"#,
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let op = new_operator(&server);
+        let err = op.read("file").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+    }
+}
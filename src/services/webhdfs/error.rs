@@ -14,7 +14,6 @@
 
 use http::response::Parts;
 use http::Response;
-use http::StatusCode;
 use serde::Deserialize;
 
 use crate::raw::*;
@@ -44,25 +43,35 @@ pub(super) async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Err
 }
 
 fn parse_error_msg(parts: Parts, body: &str) -> Result<Error> {
-    let (kind, retryable) = match parts.status {
-        StatusCode::NOT_FOUND => (ErrorKind::NotFound, false),
-        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => (ErrorKind::PermissionDenied, false),
-        // passing invalid arguments will return BAD_REQUEST
-        // should be unretryable
-        StatusCode::BAD_REQUEST => (ErrorKind::Unexpected, false),
-        StatusCode::INTERNAL_SERVER_ERROR
-        | StatusCode::BAD_GATEWAY
-        | StatusCode::SERVICE_UNAVAILABLE
-        | StatusCode::GATEWAY_TIMEOUT => (ErrorKind::Unexpected, true),
-        _ => (ErrorKind::Unexpected, false),
-    };
+    let (mut kind, mut retryable) = parse_error_status(parts.status);
 
     let message = match serde_json::from_str::<WebHdfsErrorWrapper>(body) {
-        Ok(wh_error) => format!("{:?}", wh_error.remote_exception),
+        Ok(wh_error) => {
+            // The status code returned by namenode doesn't always reflect
+            // the real error, for example, `AccessControlException` could be
+            // returned with `500`. So we prefer the exception name here.
+            match wh_error.remote_exception.exception.as_str() {
+                "FileNotFoundException" => (kind, retryable) = (ErrorKind::NotFound, false),
+                "AccessControlException" | "SecurityException" => {
+                    (kind, retryable) = (ErrorKind::PermissionDenied, false)
+                }
+                "FileAlreadyExistsException" => {
+                    (kind, retryable) = (ErrorKind::AlreadyExists, false)
+                }
+                "RetriableException" | "StandbyException" => retryable = true,
+                _ => {}
+            }
+
+            format!("{:?}", wh_error.remote_exception)
+        }
         Err(_) => body.to_owned(),
     };
 
-    let mut err = Error::new(kind, &message).with_context("response", format!("{parts:?}"));
+    let mut err = with_error_response_context(Error::new(kind, &message), &parts);
+
+    if let Ok(Some(dur)) = parse_retry_after(&parts.headers) {
+        err = err.set_retry_after(dur);
+    }
 
     if retryable {
         err = err.set_temporary();
@@ -75,6 +84,7 @@ fn parse_error_msg(parts: Parts, body: &str) -> Result<Error> {
 mod tests {
     use bytes::Buf;
     use futures::stream;
+    use http::StatusCode;
     use serde_json::from_reader;
 
     use super::*;
@@ -117,6 +127,49 @@ mod tests {
             "Invalid value for webhdfs parameter \"permission\": ..."
         );
 
+        Ok(())
+    }
+    #[tokio::test]
+    async fn test_parse_remote_exception() -> Result<()> {
+        let cases = vec![
+            (
+                StatusCode::NOT_FOUND,
+                "FileNotFoundException",
+                ErrorKind::NotFound,
+            ),
+            (
+                StatusCode::FORBIDDEN,
+                "AccessControlException",
+                ErrorKind::PermissionDenied,
+            ),
+            // namenode could return `AccessControlException` with 500.
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "AccessControlException",
+                ErrorKind::PermissionDenied,
+            ),
+            (
+                StatusCode::FORBIDDEN,
+                "FileAlreadyExistsException",
+                ErrorKind::AlreadyExists,
+            ),
+        ];
+
+        for (status, exception, kind) in cases {
+            let content = format!(
+                r#"{{"RemoteException":{{"exception":"{exception}","javaClassName":"org.apache.hadoop.{exception}","message":"test"}}}}"#
+            );
+            let body = IncomingAsyncBody::new(
+                Box::new(stream::iter(vec![Ok(bytes::Bytes::from(content))])),
+                None,
+            );
+            let resp = Response::builder().status(status).body(body).unwrap();
+
+            let err = parse_error(resp).await?;
+            assert_eq!(err.kind(), kind, "{exception} must be mapped to {kind}");
+            assert!(!err.is_temporary());
+        }
+
         Ok(())
     }
 }
@@ -27,17 +27,24 @@ pub struct WebhdfsWriter {
 
     op: OpWrite,
     path: String,
+
+    /// Whether the file has been created, following chunks will be
+    /// appended via `APPEND`.
+    created: bool,
 }
 
 impl WebhdfsWriter {
     pub fn new(backend: WebhdfsBackend, op: OpWrite, path: String) -> Self {
-        WebhdfsWriter { backend, op, path }
+        let created = op.append_existing();
+        WebhdfsWriter {
+            backend,
+            op,
+            path,
+            created,
+        }
     }
-}
 
-#[async_trait]
-impl oio::Write for WebhdfsWriter {
-    async fn write(&mut self, bs: Bytes) -> Result<()> {
+    async fn create(&mut self, bs: Bytes) -> Result<()> {
         let req = self
             .backend
             .webhdfs_create_object_req(
@@ -53,23 +60,61 @@ impl oio::Write for WebhdfsWriter {
         let status = resp.status();
         match status {
             StatusCode::CREATED | StatusCode::OK => {
+                resp.into_body().consume().await?;
+                self.created = true;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+
+    async fn append_existing(&mut self, bs: Bytes) -> Result<()> {
+        let req = self
+            .backend
+            .webhdfs_append_object_req(&self.path, bs.len(), AsyncBody::Bytes(bs))
+            .await?;
+
+        let resp = self.backend.client.send_async(req).await?;
+
+        let status = resp.status();
+        match status {
+            StatusCode::OK => {
                 resp.into_body().consume().await?;
                 Ok(())
             }
             _ => Err(parse_error(resp).await?),
         }
     }
+}
+
+#[async_trait]
+impl oio::Write for WebhdfsWriter {
+    async fn write(&mut self, bs: Bytes) -> Result<()> {
+        if self.op.append_existing() {
+            self.append_existing(bs).await
+        } else {
+            self.create(bs).await
+        }
+    }
 
     async fn append(&mut self, bs: Bytes) -> Result<()> {
-        let _ = bs;
+        if self.created {
+            self.append_existing(bs).await
+        } else {
+            self.create(bs).await
+        }
+    }
+
+    async fn close(&mut self) -> Result<RpWrite> {
+        // Make sure the file exists even if nothing has been appended.
+        if self.op.append() && !self.created {
+            self.create(Bytes::new()).await?;
+        }
 
-        Err(Error::new(
-            ErrorKind::Unsupported,
-            "output writer doesn't support append",
-        ))
+        Ok(RpWrite::new())
     }
 
-    async fn close(&mut self) -> Result<()> {
+    async fn abort(&mut self) -> Result<()> {
         Ok(())
     }
 }
@@ -0,0 +1,73 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Acl is the POSIX-like access control of a path, including owner, group
+/// and permission bits.
+///
+/// All fields are optional:
+///
+/// - While getting acl, `None` means the service doesn't return this field.
+/// - While setting acl, `None` means this field will be kept unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Acl {
+    owner: Option<String>,
+    group: Option<String>,
+    permissions: Option<String>,
+}
+
+impl Acl {
+    /// Create a new empty acl.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Owner of this path.
+    pub fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    /// Set owner of this path.
+    pub fn with_owner(mut self, owner: &str) -> Self {
+        self.owner = Some(owner.to_string());
+        self
+    }
+
+    /// Owning group of this path.
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    /// Set owning group of this path.
+    pub fn with_group(mut self, group: &str) -> Self {
+        self.group = Some(group.to_string());
+        self
+    }
+
+    /// Permission bits of this path.
+    ///
+    /// Permissions are in symbolic notation like `rwxr-x---`, services
+    /// could append extra flags like `+` (extended acl exists) to it.
+    pub fn permissions(&self) -> Option<&str> {
+        self.permissions.as_deref()
+    }
+
+    /// Set permission bits of this path.
+    ///
+    /// Both symbolic (`rwxr-x---`) and 4-digit octal (`0750`) notation
+    /// could be accepted, depends on the underlying service.
+    pub fn with_permissions(mut self, permissions: &str) -> Self {
+        self.permissions = Some(permissions.to_string());
+        self
+    }
+}
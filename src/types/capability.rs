@@ -0,0 +1,213 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+
+/// Capability is used to describe what operations are supported
+/// by current Operator.
+///
+/// Via capability, OpenDAL users don't need to use internal code to
+/// decide whether an operation is supported or not.
+///
+/// # Naming Style
+///
+/// - Operation itself should be in lower case, like `read`, `write`.
+/// - Operation with sub operations should be named like `presign_read`.
+/// - Operation with variants should be named like `read_can_seek`.
+/// - Operation with arguments should be named like `read_with_range`.
+///
+/// # Examples
+///
+/// ```
+/// # use anyhow::Result;
+/// # use opendal::Operator;
+/// # #[tokio::main]
+/// # async fn test(op: Operator) -> Result<()> {
+/// let cap = op.info().capability();
+/// if cap.presign_read {
+///     let _ = op.presign_read("path/to/file", time::Duration::hours(1))?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+pub struct Capability {
+    /// If operator supports stat, it will be true.
+    pub stat: bool,
+    /// If operator supports stat with tags, it will be true.
+    pub stat_with_tags: bool,
+
+    /// If operator supports read, it will be true.
+    pub read: bool,
+    /// If operator supports seek on returning reader, it will be true.
+    pub read_can_seek: bool,
+    /// If operator supports next on returning reader, it will be true.
+    pub read_can_next: bool,
+    /// If operator supports read with range, it will be true.
+    pub read_with_range: bool,
+    /// If operator supports read with suffix range like `bytes=-1024`
+    /// natively, it will be true. Otherwise, suffix range will be resolved
+    /// via an extra stat.
+    pub read_with_suffix_range: bool,
+
+    /// If operator supports write, it will be true.
+    pub write: bool,
+    /// If operator supports append on returning writer, it will be true.
+    pub write_can_append: bool,
+    /// If operator supports write by multiple parts (like multipart
+    /// upload), it will be true.
+    pub write_multi: bool,
+    /// If operator supports write with content type, it will be true.
+    pub write_with_content_type: bool,
+    /// If operator supports write only if the path doesn't exist
+    /// atomically, it will be true.
+    pub write_with_if_not_exists: bool,
+    /// If operator supports write only if the ETag matches, it will be true.
+    pub write_with_if_match: bool,
+    /// If operator supports write with last modified, it will be true.
+    pub write_with_last_modified: bool,
+    /// If operator supports write by appending to existing file, it will
+    /// be true.
+    pub write_with_append_existing: bool,
+    /// If operator supports write with tags, it will be true.
+    pub write_with_tags: bool,
+    /// If operator supports resuming write from a saved upload session,
+    /// it will be true.
+    pub write_with_session: bool,
+
+    /// If operator supports create dir, it will be true.
+    pub create_dir: bool,
+    /// If operator supports delete, it will be true.
+    pub delete: bool,
+    /// If operator supports copy, it will be true.
+    pub copy: bool,
+    /// If operator supports rename, it will be true.
+    pub rename: bool,
+
+    /// If operator supports list, it will be true.
+    pub list: bool,
+    /// If operator supports list with limit, it will be true.
+    pub list_with_limit: bool,
+    /// If operator supports list with delimiter `/` natively, it will be
+    /// true. Otherwise, list will be emulated via scan.
+    pub list_with_delimiter: bool,
+    /// If operator supports scan, it will be true.
+    pub scan: bool,
+
+    /// If operator supports presign, it will be true.
+    pub presign: bool,
+    /// If operator supports presign read, it will be true.
+    pub presign_read: bool,
+    /// If operator supports presign stat, it will be true.
+    pub presign_stat: bool,
+    /// If operator supports presign write, it will be true.
+    pub presign_write: bool,
+
+    /// If operator supports batch, it will be true.
+    pub batch: bool,
+    /// If operator supports batch delete, it will be true.
+    pub batch_delete: bool,
+
+    /// If operator supports restore archived path, it will be true.
+    pub restore: bool,
+
+    /// If operator supports aborting multipart uploads by upload id, it will be true.
+    pub abort_multipart: bool,
+
+    /// If operator supports get and set acl, it will be true.
+    pub acl: bool,
+
+    /// If operator supports get and put tags, it will be true.
+    pub tags: bool,
+
+    /// If operator supports blocking, it will be true.
+    pub blocking: bool,
+}
+
+impl Debug for Capability {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut s = vec![];
+
+        if self.stat {
+            s.push("Stat");
+        }
+        if self.read {
+            s.push("Read");
+        }
+        if self.write {
+            s.push("Write");
+        }
+        if self.create_dir {
+            s.push("CreateDir");
+        }
+        if self.delete {
+            s.push("Delete");
+        }
+        if self.copy {
+            s.push("Copy");
+        }
+        if self.rename {
+            s.push("Rename");
+        }
+        if self.list {
+            s.push("List");
+        }
+        if self.scan {
+            s.push("Scan");
+        }
+        if self.presign {
+            s.push("Presign");
+        }
+        if self.batch {
+            s.push("Batch");
+        }
+        if self.restore {
+            s.push("Restore");
+        }
+        if self.abort_multipart {
+            s.push("AbortMultipart");
+        }
+        if self.acl {
+            s.push("Acl");
+        }
+        if self.tags {
+            s.push("Tags");
+        }
+        if self.blocking {
+            s.push("Blocking");
+        }
+
+        write!(f, "{{ {} }}", s.join(" | "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capability_debug() {
+        let cap = Capability {
+            stat: true,
+            read: true,
+            read_with_range: true,
+            list: true,
+            ..Default::default()
+        };
+
+        assert_eq!(format!("{cap:?}"), "{ Stat | Read | List }");
+        assert_eq!(format!("{:?}", Capability::default()), "{  }");
+    }
+}
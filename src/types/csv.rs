@@ -0,0 +1,370 @@
+// Copyright 2022 Datafuse Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::mem;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+use csv_core::ReadRecordResult;
+use futures::ready;
+use futures::Stream;
+
+use crate::*;
+
+/// CsvRecord is a single record parsed by [`CsvReader`].
+///
+/// Fields are kept as raw bytes, users can decide how to decode them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvRecord {
+    data: Vec<u8>,
+    ends: Vec<usize>,
+    position: u64,
+}
+
+impl CsvRecord {
+    /// Get the number of fields in this record.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Returns `true` if this record doesn't have any field.
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Get the field at `idx`.
+    pub fn get(&self, idx: usize) -> Option<&[u8]> {
+        let end = *self.ends.get(idx)?;
+        let start = if idx == 0 { 0 } else { self.ends[idx - 1] };
+        Some(&self.data[start..end])
+    }
+
+    /// Iterate over all fields of this record.
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        (0..self.len()).map(move |idx| self.get(idx).expect("field must exist"))
+    }
+
+    /// Get the absolute offset of the first byte of this record in the
+    /// object.
+    ///
+    /// Reading from this offset via [`OpReadCsv::with_range`] will start
+    /// at this record.
+    ///
+    /// [`OpReadCsv::with_range`]: crate::ops::OpReadCsv::with_range
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+/// CsvReader is a stream of [`CsvRecord`] parsed from a [`Reader`].
+///
+/// Created by [`Operator::csv_reader`] and [`Operator::csv_reader_with`].
+///
+/// # Notes
+///
+/// - Records are parsed from the chunks returned by underlying reader
+///   directly, there is no extra buffer between them.
+/// - Data will only be read while polling this stream, so slow consumers
+///   will not make the reader buffer more data.
+/// - Quoted fields (including line terminators inside quotes) could span
+///   any number of chunks.
+/// - While starting from the middle of an object, all bytes until the
+///   first line terminator will be skipped. Line terminators inside quoted
+///   fields can't be told apart at this point, so please make sure the
+///   ranges are aligned to record boundaries (like [`CsvRecord::position`])
+///   if the object contains them.
+pub struct CsvReader {
+    reader: Reader,
+    parser: csv_core::Reader,
+
+    /// Skip the partial record before the first line terminator.
+    skip_partial: bool,
+    /// Stop after records starting at or after this absolute offset.
+    end: Option<u64>,
+    eof: bool,
+    done: bool,
+
+    data: Vec<u8>,
+    data_len: usize,
+    ends: Vec<usize>,
+    ends_len: usize,
+    /// The absolute offset of current record, `None` if it has not started.
+    position: Option<u64>,
+}
+
+impl CsvReader {
+    pub(crate) fn new(
+        reader: Reader,
+        parser: csv_core::Reader,
+        skip_partial: bool,
+        end: Option<u64>,
+    ) -> Self {
+        Self {
+            reader,
+            parser,
+            skip_partial,
+            end,
+            eof: false,
+            done: false,
+            data: vec![0; 1024],
+            data_len: 0,
+            ends: vec![0; 16],
+            ends_len: 0,
+            position: None,
+        }
+    }
+
+    /// Fill the chunk of reader, returns `false` if reader has been drained.
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<Result<bool>> {
+        if self.eof {
+            return Poll::Ready(Ok(false));
+        }
+        let filled = ready!(self.reader.poll_fill_chunk(cx))?;
+        self.eof = !filled;
+        Poll::Ready(Ok(filled))
+    }
+
+    fn take_record(&mut self, position: u64) -> CsvRecord {
+        let mut data = mem::replace(&mut self.data, vec![0; 1024]);
+        data.truncate(self.data_len);
+        let mut ends = mem::replace(&mut self.ends, vec![0; 16]);
+        ends.truncate(self.ends_len);
+        self.data_len = 0;
+        self.ends_len = 0;
+        self.position = None;
+
+        CsvRecord {
+            data,
+            ends,
+            position,
+        }
+    }
+}
+
+impl Stream for CsvReader {
+    type Item = Result<CsvRecord>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            let filled = match ready!(this.poll_fill(cx)) {
+                Ok(v) => v,
+                Err(err) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(err)));
+                }
+            };
+
+            if this.skip_partial {
+                if !filled {
+                    this.done = true;
+                    continue;
+                }
+                let chunk = this.reader.chunk();
+                match chunk.iter().position(|b| *b == b'\n') {
+                    Some(idx) => {
+                        this.reader.consume_chunk(idx + 1);
+                        this.skip_partial = false;
+                    }
+                    None => {
+                        let n = chunk.len();
+                        this.reader.consume_chunk(n);
+                    }
+                }
+                continue;
+            }
+
+            if this.position.is_none() {
+                // Skip empty lines so that position points to the first
+                // byte of the record.
+                let chunk = this.reader.chunk();
+                let n = chunk
+                    .iter()
+                    .take_while(|b| **b == b'\r' || **b == b'\n')
+                    .count();
+                if n > 0 {
+                    this.reader.consume_chunk(n);
+                    continue;
+                }
+                if filled {
+                    let position = this.reader.offset();
+                    if matches!(this.end, Some(end) if position >= end) {
+                        this.done = true;
+                        continue;
+                    }
+                    this.position = Some(position);
+                }
+            }
+
+            let (res, nin, nout, nend) = this.parser.read_record(
+                this.reader.chunk(),
+                &mut this.data[this.data_len..],
+                &mut this.ends[this.ends_len..],
+            );
+            this.reader.consume_chunk(nin);
+            this.data_len += nout;
+            this.ends_len += nend;
+
+            match res {
+                ReadRecordResult::InputEmpty => continue,
+                ReadRecordResult::OutputFull => {
+                    let len = this.data.len();
+                    this.data.resize(len * 2, 0);
+                }
+                ReadRecordResult::OutputEndsFull => {
+                    let len = this.ends.len();
+                    this.ends.resize(len * 2, 0);
+                }
+                ReadRecordResult::Record => {
+                    let position = this.position.unwrap_or_else(|| this.reader.offset());
+                    return Poll::Ready(Some(Ok(this.take_record(position))));
+                }
+                ReadRecordResult::End => this.done = true,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::ops::OpReadCsv;
+    use crate::raw::oio;
+
+    /// Build a csv reader which reads `content[start..]` in `chunk` bytes.
+    fn new_csv_reader(content: &str, chunk: usize, op: OpReadCsv) -> CsvReader {
+        let range = op.range();
+        let start = range.offset().unwrap_or(0);
+        let read_from = start.saturating_sub(1);
+        let cursor = oio::Cursor::from(content.as_bytes()[read_from as usize..].to_vec());
+        let r = Reader::new(
+            Box::new(oio::into_streamable_reader(cursor, chunk)),
+            read_from,
+        );
+
+        CsvReader::new(
+            r,
+            op.build_parser(),
+            start > 0,
+            range.size().map(|v| start + v),
+        )
+    }
+
+    async fn collect(r: CsvReader) -> Vec<(u64, Vec<String>)> {
+        let records: Vec<CsvRecord> = r.try_collect().await.expect("read must succeed");
+        records
+            .into_iter()
+            .map(|r| {
+                (
+                    r.position(),
+                    r.iter()
+                        .map(|v| String::from_utf8_lossy(v).to_string())
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn fields(v: &[&str]) -> Vec<String> {
+        v.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_read_records() {
+        let content = "a,b,c\n\"hello, \"\"world\"\"\",\"multi\nline\",3\r\n\nlast,,";
+
+        // Chunk size must not affect the result.
+        for chunk in [1, 2, 3, 7, 1024] {
+            let records = collect(new_csv_reader(content, chunk, OpReadCsv::new())).await;
+            assert_eq!(
+                records,
+                vec![
+                    (0, fields(&["a", "b", "c"])),
+                    (6, fields(&["hello, \"world\"", "multi\nline", "3"])),
+                    (42, fields(&["last", "", ""])),
+                ],
+                "chunk size {chunk}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_records_in_ranges() {
+        let content = "id,name\n1,alice\n2,bob\n3,carol\n4,dave\n";
+        let total = content.len() as u64;
+
+        for chunk in [1, 4, 1024] {
+            for split in 1..total {
+                let mut records = collect(new_csv_reader(
+                    content,
+                    chunk,
+                    OpReadCsv::new().with_range(0..split),
+                ))
+                .await;
+                records.extend(
+                    collect(new_csv_reader(
+                        content,
+                        chunk,
+                        OpReadCsv::new().with_range(split..),
+                    ))
+                    .await,
+                );
+
+                let ids: Vec<_> = records.iter().map(|(_, v)| v[0].clone()).collect();
+                assert_eq!(
+                    ids,
+                    fields(&["id", "1", "2", "3", "4"]),
+                    "chunk {chunk} split {split}"
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_records_from_position() {
+        let content = "a,b\n\"x\ny\",z\nc,d\n";
+
+        let records = collect(new_csv_reader(content, 3, OpReadCsv::new())).await;
+        let position = records[2].0;
+        assert_eq!(position, 12);
+
+        let records = collect(new_csv_reader(
+            content,
+            3,
+            OpReadCsv::new().with_range(position..),
+        ))
+        .await;
+        assert_eq!(records, vec![(12, fields(&["c", "d"]))]);
+    }
+
+    #[tokio::test]
+    async fn test_read_records_with_options() {
+        let content = "# comment\na;'b;c'\n";
+        let op = OpReadCsv::new()
+            .with_delimiter(b';')
+            .with_quote(b'\'')
+            .with_comment(Some(b'#'));
+
+        let records = collect(new_csv_reader(content, 2, op)).await;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].1, fields(&["a", "b;c"]));
+    }
+}
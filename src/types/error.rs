@@ -37,6 +37,7 @@ use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::io;
+use std::time::Duration;
 
 /// Result that is a wrapper of `Result<T, opendal::Error>`
 pub type Result<T> = std::result::Result<T, Error>;
@@ -65,6 +66,14 @@ pub enum ErrorKind {
     AlreadyExists,
     /// Requests that sent to this path is over the limit, please slow down.
     RateLimited,
+    /// The condition of this operation is not match, for example, writing
+    /// with `if_not_exists` while the path already exists, or writing with
+    /// `if_match` while the ETag has been changed.
+    ConditionNotMatch,
+    /// The given path has been archived (like s3 glacier) and can't be read
+    /// directly. Users need to call [`Operator::restore`](crate::Operator::restore)
+    /// and retry after restore finished.
+    Archived,
 }
 
 impl ErrorKind {
@@ -92,6 +101,8 @@ impl From<ErrorKind> for &'static str {
             ErrorKind::NotADirectory => "NotADirectory",
             ErrorKind::AlreadyExists => "AlreadyExists",
             ErrorKind::RateLimited => "RateLimited",
+            ErrorKind::ConditionNotMatch => "ConditionNotMatch",
+            ErrorKind::Archived => "Archived",
         }
     }
 }
@@ -137,6 +148,7 @@ pub struct Error {
     operation: &'static str,
     context: Vec<(&'static str, String)>,
     source: Option<anyhow::Error>,
+    retry_after: Option<Duration>,
 }
 
 impl Display for Error {
@@ -180,6 +192,7 @@ impl Debug for Error {
             de.field("operation", &self.operation);
             de.field("context", &self.context);
             de.field("source", &self.source);
+            de.field("retry_after", &self.retry_after);
             return de.finish();
         }
 
@@ -222,6 +235,7 @@ impl Error {
             operation: "",
             context: Vec::default(),
             source: None,
+            retry_after: None,
         }
     }
 
@@ -258,6 +272,26 @@ impl Error {
         self
     }
 
+    /// Rewrite all human readable parts of this error with given function.
+    ///
+    /// `message`, the values of `context` and `source` will be rewritten.
+    /// `source` will be flattened into a plain message, so the original
+    /// error chain is no longer reachable after redact.
+    #[cfg(feature = "layers-redact-error")]
+    pub(crate) fn redact<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> String,
+    {
+        self.message = f(&self.message);
+        for (_, v) in self.context.iter_mut() {
+            *v = f(v);
+        }
+        self.source = self
+            .source
+            .map(|src| anyhow::Error::msg(f(&format!("{src:#}"))));
+        self
+    }
+
     /// Operate on error with map.
     pub fn map<F>(self, f: F) -> Self
     where
@@ -288,6 +322,16 @@ impl Error {
         self
     }
 
+    /// Set the duration that services ask us to wait before retrying.
+    ///
+    /// It's usually parsed from the `Retry-After` header of throttled
+    /// responses. [`RetryLayer`](crate::layers::RetryLayer) will prefer this
+    /// hint over its computed backoff.
+    pub fn set_retry_after(mut self, dur: Duration) -> Self {
+        self.retry_after = Some(dur);
+        self
+    }
+
     /// Return error's kind.
     pub fn kind(&self) -> ErrorKind {
         self.kind
@@ -297,6 +341,54 @@ impl Error {
     pub fn is_temporary(&self) -> bool {
         self.status == ErrorStatus::Temporary
     }
+
+    /// Return the duration that services ask us to wait before retrying.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.retry_after
+    }
+
+    /// Return the operation that returns this error, empty if not set.
+    pub fn operation(&self) -> &'static str {
+        self.operation
+    }
+
+    /// Return the value of given context key.
+    ///
+    /// If this error doesn't carry the key, we will look into its source
+    /// if the source is also an opendal [`Error`]. So errors wrapped by
+    /// layers still expose the context added by services.
+    pub fn context_value(&self, key: &str) -> Option<&str> {
+        match self.context.iter().find(|(k, _)| *k == key) {
+            Some((_, v)) => Some(v.as_str()),
+            None => self
+                .source
+                .as_ref()?
+                .downcast_ref::<Error>()?
+                .context_value(key),
+        }
+    }
+
+    /// Return the scheme of the service that returns this error.
+    pub fn service(&self) -> Option<&str> {
+        self.context_value("service")
+    }
+
+    /// Return the path that this error happened on.
+    pub fn path(&self) -> Option<&str> {
+        self.context_value("path")
+    }
+
+    /// Return the http status code of the response that caused this error.
+    pub fn status_code(&self) -> Option<u16> {
+        self.context_value("status")?.parse().ok()
+    }
+
+    /// Return the request id of the response that caused this error.
+    ///
+    /// It's useful to find the request in service side's logs.
+    pub fn request_id(&self) -> Option<&str> {
+        self.context_value("request_id")
+    }
 }
 
 impl From<Error> for io::Error {
@@ -328,6 +420,7 @@ mod tests {
             ("called", "send_async".to_string()),
         ],
         source: Some(anyhow!("networking error")),
+        retry_after: None,
     });
 
     #[test]
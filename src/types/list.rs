@@ -19,13 +19,15 @@ use std::task::Context;
 use std::task::Poll;
 
 use futures::future::BoxFuture;
-use futures::ready;
 use futures::FutureExt;
 use futures::Stream;
 
 use crate::raw::*;
 use crate::*;
 
+/// The default max number of pages that [`Lister`] fetches ahead.
+const DEFAULT_PREFETCH: usize = 4;
+
 /// Lister is designed to list entries at given path in an asynchronous
 /// manner.
 ///
@@ -33,15 +35,34 @@ use crate::*;
 ///
 /// User can use lister as `Stream<Item = Result<Entry>>` or
 /// call `next_page` directly.
+///
+/// # Prefetch
+///
+/// While used as a stream, lister fetches next pages while the caller is
+/// consuming the current one. Pages are chained by continuation tokens,
+/// so there is at most one page request in flight. The number of pages
+/// fetched ahead starts from `1` and doubles every time the caller moves
+/// to the next page, bounded by [`Lister::with_prefetch`]. Dropping the
+/// lister cancels the ongoing page request.
 pub struct Lister {
     pager: Option<oio::Pager>,
 
     buf: VecDeque<oio::Entry>,
+    /// Pages that have been fetched but not consumed yet.
+    pages: VecDeque<Vec<oio::Entry>>,
     /// We will move `pager` inside future and return it back while future is ready.
     /// Thus, we should not allow calling other function while we already have
     /// a future.
     #[allow(clippy::type_complexity)]
     fut: Option<BoxFuture<'static, (oio::Pager, Result<Option<Vec<oio::Entry>>>)>>,
+    /// Error returned by pager, will be returned after fetched pages consumed.
+    err: Option<Error>,
+    done: bool,
+
+    /// The max number of pages to fetch ahead.
+    prefetch: usize,
+    /// The number of pages to fetch ahead currently.
+    window: usize,
 }
 
 impl Lister {
@@ -50,10 +71,26 @@ impl Lister {
         Self {
             pager: Some(pager),
             buf: VecDeque::default(),
+            pages: VecDeque::default(),
             fut: None,
+            err: None,
+            done: false,
+
+            prefetch: DEFAULT_PREFETCH,
+            window: 1,
         }
     }
 
+    /// Set the max number of pages to fetch ahead, default to `4`.
+    ///
+    /// Set to `0` to disable prefetch, then the next page will only be
+    /// fetched after the current one has been consumed.
+    pub fn with_prefetch(mut self, prefetch: usize) -> Self {
+        self.prefetch = prefetch;
+        self.window = self.window.min(prefetch);
+        self
+    }
+
     /// next_page can be used to fetch a new page.
     ///
     /// # Notes
@@ -86,41 +123,74 @@ impl Lister {
 
         Ok(Some(entries.into_iter().map(|v| v.into_entry()).collect()))
     }
+
+    /// Drive the ongoing page request, and start a new one if we have
+    /// nothing to return or not enough pages fetched ahead.
+    fn poll_fetch(&mut self, cx: &mut Context<'_>) {
+        loop {
+            if let Some(fut) = self.fut.as_mut() {
+                let (pager, res) = match fut.poll_unpin(cx) {
+                    Poll::Ready(v) => v,
+                    Poll::Pending => return,
+                };
+                self.fut = None;
+                self.pager = Some(pager);
+
+                match res {
+                    Ok(Some(oes)) => self.pages.push_back(oes),
+                    Ok(None) => self.done = true,
+                    Err(err) => {
+                        self.err = Some(err);
+                        self.done = true;
+                    }
+                }
+            }
+
+            let needed = self.buf.is_empty() && self.pages.is_empty();
+            if self.done || (!needed && self.pages.len() >= self.window) {
+                return;
+            }
+
+            let mut pager = self.pager.take().expect("pager must be valid");
+            let fut = async move {
+                let res = pager.next().await;
+
+                (pager, res)
+            };
+            self.fut = Some(Box::pin(fut));
+        }
+    }
 }
 
 impl Stream for Lister {
     type Item = Result<Entry>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Always drive the page request first so that next pages could be
+        // fetched while the caller is consuming entries.
+        self.poll_fetch(cx);
+
         if let Some(oe) = self.buf.pop_front() {
             return Poll::Ready(Some(Ok(oe.into_entry())));
         }
 
-        if let Some(fut) = self.fut.as_mut() {
-            let (op, res) = ready!(fut.poll_unpin(cx));
-            self.pager = Some(op);
-
-            return match res? {
-                Some(oes) => {
-                    self.fut = None;
-                    self.buf = oes.into();
-                    self.poll_next(cx)
-                }
-                None => {
-                    self.fut = None;
-                    Poll::Ready(None)
-                }
-            };
+        if let Some(oes) = self.pages.pop_front() {
+            // Caller keeps consuming, fetch more pages ahead.
+            self.window = (self.window * 2).min(self.prefetch);
+            // Ideally, the convert from `Vec` to `VecDeque` will not do reallocation.
+            self.buf = oes.into();
+            return self.poll_next(cx);
         }
 
-        let mut pager = self.pager.take().expect("pager must be valid");
-        let fut = async move {
-            let res = pager.next().await;
+        if let Some(err) = self.err.take() {
+            return Poll::Ready(Some(Err(err)));
+        }
 
-            (pager, res)
-        };
-        self.fut = Some(Box::pin(fut));
-        self.poll_next(cx)
+        if self.done {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
     }
 }
 
@@ -181,3 +251,110 @@ impl Iterator for BlockingLister {
         self.next()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use futures::StreamExt;
+
+    use super::*;
+
+    /// Pager returns one entry per page, and counts the fetched pages.
+    struct MockPager {
+        pages: usize,
+        fetched: Arc<AtomicUsize>,
+        fail_at: Option<usize>,
+    }
+
+    #[async_trait]
+    impl oio::Page for MockPager {
+        async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+            let idx = self.fetched.fetch_add(1, Ordering::SeqCst);
+            if Some(idx) == self.fail_at {
+                return Err(Error::new(ErrorKind::Unexpected, "mock error"));
+            }
+            if idx >= self.pages {
+                return Ok(None);
+            }
+
+            Ok(Some(vec![oio::Entry::new(
+                &format!("file-{idx}"),
+                Metadata::new(EntryMode::FILE),
+            )]))
+        }
+    }
+
+    fn new_lister(pages: usize, fail_at: Option<usize>) -> (Lister, Arc<AtomicUsize>) {
+        let fetched = Arc::new(AtomicUsize::new(0));
+        let pager = MockPager {
+            pages,
+            fetched: fetched.clone(),
+            fail_at,
+        };
+
+        (Lister::new(Box::new(pager)), fetched)
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_in_order() {
+        let (lister, _) = new_lister(20, None);
+
+        let paths: Vec<_> = lister
+            .map(|v| v.expect("entry must be valid").path().to_string())
+            .collect()
+            .await;
+
+        let expected: Vec<_> = (0..20).map(|idx| format!("file-{idx}")).collect();
+        assert_eq!(paths, expected);
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_bounded() {
+        let (mut lister, fetched) = new_lister(100, None);
+
+        lister.next().await.expect("entry must exist").unwrap();
+        // The first page plus two pages ahead.
+        assert_eq!(fetched.load(Ordering::SeqCst), 3);
+
+        for _ in 0..9 {
+            lister.next().await.expect("entry must exist").unwrap();
+        }
+        // 10 pages consumed, 4 pages ahead at most.
+        assert_eq!(fetched.load(Ordering::SeqCst), 14);
+
+        // No more pages will be fetched after lister dropped.
+        drop(lister);
+        assert_eq!(fetched.load(Ordering::SeqCst), 14);
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_disabled() {
+        let (lister, fetched) = new_lister(100, None);
+        let mut lister = lister.with_prefetch(0);
+
+        for idx in 1..=10 {
+            lister.next().await.expect("entry must exist").unwrap();
+            assert_eq!(fetched.load(Ordering::SeqCst), idx);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_error_after_fetched_pages() {
+        let (lister, _) = new_lister(100, Some(3));
+
+        let results: Vec<_> = lister.collect().await;
+
+        assert_eq!(results.len(), 4);
+        for (idx, res) in results.iter().take(3).enumerate() {
+            assert_eq!(res.as_ref().unwrap().path(), format!("file-{idx}"));
+        }
+        assert_eq!(
+            results[3].as_ref().expect_err("must be error").kind(),
+            ErrorKind::Unexpected
+        );
+    }
+}
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+
 use flagset::flags;
 use flagset::FlagSet;
 use time::OffsetDateTime;
@@ -33,13 +35,27 @@ pub struct Metadata {
 
     mode: EntryMode,
 
+    cache_control: Option<String>,
     content_disposition: Option<String>,
+    content_encoding: Option<String>,
     content_length: Option<u64>,
     content_md5: Option<String>,
     content_range: Option<BytesContentRange>,
     content_type: Option<String>,
     etag: Option<String>,
+    expires: Option<OffsetDateTime>,
     last_modified: Option<OffsetDateTime>,
+
+    unix_mode: Option<u32>,
+    uid: Option<u32>,
+    gid: Option<u32>,
+
+    tags: Option<HashMap<String, String>>,
+
+    storage_class: Option<String>,
+    restore_status: Option<RestoreStatus>,
+
+    symlink: bool,
 }
 
 impl Metadata {
@@ -63,6 +79,20 @@ impl Metadata {
             last_modified: None,
             etag: None,
             content_disposition: None,
+            content_encoding: None,
+            cache_control: None,
+            expires: None,
+
+            unix_mode: None,
+            uid: None,
+            gid: None,
+
+            tags: None,
+
+            storage_class: None,
+            restore_status: None,
+
+            symlink: false,
         }
     }
 
@@ -380,6 +410,317 @@ impl Metadata {
         self.bit |= Metakey::ContentDisposition;
         self
     }
+
+    /// Content-Encoding of this entry.
+    ///
+    /// `Content-Encoding` is defined by [RFC 7231](https://httpwg.org/specs/rfc7231.html#header.content-encoding).
+    /// Refer to [MDN Content-Encoding](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Content-Encoding) for more information.
+    ///
+    /// OpenDAL will return this value AS-IS like `gzip` or `zstd`.
+    pub fn content_encoding(&self) -> Option<&str> {
+        debug_assert!(
+            self.bit.contains(Metakey::ContentEncoding) || self.bit.contains(Metakey::Complete),
+            "visiting not set metadata: content_encoding, maybe a bug"
+        );
+
+        self.content_encoding.as_deref()
+    }
+
+    /// Set Content-Encoding of this entry.
+    pub fn with_content_encoding(mut self, content_encoding: String) -> Self {
+        self.content_encoding = Some(content_encoding);
+        self.bit |= Metakey::ContentEncoding;
+        self
+    }
+
+    /// Set Content-Encoding of this entry.
+    pub fn set_content_encoding(&mut self, content_encoding: &str) -> &mut Self {
+        self.content_encoding = Some(content_encoding.to_string());
+        self.bit |= Metakey::ContentEncoding;
+        self
+    }
+
+    /// Cache-Control of this entry.
+    ///
+    /// `Cache-Control` is defined by [RFC 9111](https://httpwg.org/specs/rfc9111.html#field.cache-control).
+    /// Refer to [MDN Cache-Control](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Cache-Control) for more information.
+    ///
+    /// OpenDAL will return this value AS-IS like `max-age=3600` or `no-store`.
+    pub fn cache_control(&self) -> Option<&str> {
+        debug_assert!(
+            self.bit.contains(Metakey::CacheControl) || self.bit.contains(Metakey::Complete),
+            "visiting not set metadata: cache_control, maybe a bug"
+        );
+
+        self.cache_control.as_deref()
+    }
+
+    /// Set Cache-Control of this entry.
+    pub fn with_cache_control(mut self, cache_control: String) -> Self {
+        self.cache_control = Some(cache_control);
+        self.bit |= Metakey::CacheControl;
+        self
+    }
+
+    /// Set Cache-Control of this entry.
+    pub fn set_cache_control(&mut self, cache_control: &str) -> &mut Self {
+        self.cache_control = Some(cache_control.to_string());
+        self.bit |= Metakey::CacheControl;
+        self
+    }
+
+    /// Expires of this entry.
+    ///
+    /// `Expires` is defined by [RFC 9111](https://httpwg.org/specs/rfc9111.html#field.expires).
+    /// Invalid values like `0` will be returned as a time in the past.
+    pub fn expires(&self) -> Option<OffsetDateTime> {
+        debug_assert!(
+            self.bit.contains(Metakey::Expires) || self.bit.contains(Metakey::Complete),
+            "visiting not set metadata: expires, maybe a bug"
+        );
+
+        self.expires
+    }
+
+    /// Set Expires of this entry.
+    pub fn with_expires(mut self, expires: OffsetDateTime) -> Self {
+        self.expires = Some(expires);
+        self.bit |= Metakey::Expires;
+        self
+    }
+
+    /// Set Expires of this entry.
+    pub fn set_expires(&mut self, expires: OffsetDateTime) -> &mut Self {
+        self.expires = Some(expires);
+        self.bit |= Metakey::Expires;
+        self
+    }
+
+    /// Unix permission bits of this entry, like `0o644`.
+    ///
+    /// Only services backed by unix file systems (like `fs` on unix) will
+    /// return this value, it will be `None` on other platforms.
+    pub fn unix_mode(&self) -> Option<u32> {
+        debug_assert!(
+            self.bit.contains(Metakey::UnixMode) || self.bit.contains(Metakey::Complete),
+            "visiting not set metadata: unix_mode, maybe a bug"
+        );
+
+        self.unix_mode
+    }
+
+    /// Set unix permission bits of this entry.
+    pub fn set_unix_mode(&mut self, unix_mode: u32) -> &mut Self {
+        self.unix_mode = Some(unix_mode);
+        self.bit |= Metakey::UnixMode;
+        self
+    }
+
+    /// Set unix permission bits of this entry.
+    pub fn with_unix_mode(mut self, unix_mode: u32) -> Self {
+        self.unix_mode = Some(unix_mode);
+        self.bit |= Metakey::UnixMode;
+        self
+    }
+
+    /// User id of the owner of this entry.
+    ///
+    /// Only services backed by unix file systems (like `fs` on unix) will
+    /// return this value, it will be `None` on other platforms.
+    pub fn uid(&self) -> Option<u32> {
+        debug_assert!(
+            self.bit.contains(Metakey::Uid) || self.bit.contains(Metakey::Complete),
+            "visiting not set metadata: uid, maybe a bug"
+        );
+
+        self.uid
+    }
+
+    /// Set user id of the owner of this entry.
+    pub fn set_uid(&mut self, uid: u32) -> &mut Self {
+        self.uid = Some(uid);
+        self.bit |= Metakey::Uid;
+        self
+    }
+
+    /// Set user id of the owner of this entry.
+    pub fn with_uid(mut self, uid: u32) -> Self {
+        self.uid = Some(uid);
+        self.bit |= Metakey::Uid;
+        self
+    }
+
+    /// Group id of the owner of this entry.
+    ///
+    /// Only services backed by unix file systems (like `fs` on unix) will
+    /// return this value, it will be `None` on other platforms.
+    pub fn gid(&self) -> Option<u32> {
+        debug_assert!(
+            self.bit.contains(Metakey::Gid) || self.bit.contains(Metakey::Complete),
+            "visiting not set metadata: gid, maybe a bug"
+        );
+
+        self.gid
+    }
+
+    /// Set group id of the owner of this entry.
+    pub fn set_gid(&mut self, gid: u32) -> &mut Self {
+        self.gid = Some(gid);
+        self.bit |= Metakey::Gid;
+        self
+    }
+
+    /// Set group id of the owner of this entry.
+    pub fn with_gid(mut self, gid: u32) -> Self {
+        self.gid = Some(gid);
+        self.bit |= Metakey::Gid;
+        self
+    }
+
+    /// Tags of this entry.
+    ///
+    /// Tags will only be returned by services that support `stat_with_tags`
+    /// (checked in [`Capability`]) while stat with [`OpStat::with_tags`](crate::ops::OpStat::with_tags).
+    pub fn tags(&self) -> Option<&HashMap<String, String>> {
+        debug_assert!(
+            self.bit.contains(Metakey::Tags) || self.bit.contains(Metakey::Complete),
+            "visiting not set metadata: tags, maybe a bug"
+        );
+
+        self.tags.as_ref()
+    }
+
+    /// Set tags of this entry.
+    pub fn set_tags(&mut self, tags: HashMap<String, String>) -> &mut Self {
+        self.tags = Some(tags);
+        self.bit |= Metakey::Tags;
+        self
+    }
+
+    /// Set tags of this entry.
+    pub fn with_tags(mut self, tags: HashMap<String, String>) -> Self {
+        self.tags = Some(tags);
+        self.bit |= Metakey::Tags;
+        self
+    }
+
+    /// Storage class of this entry, like `STANDARD` or `GLACIER` for s3.
+    ///
+    /// Entries in archive storage classes may need to be restored via
+    /// [`Operator::restore`](crate::Operator::restore) before reading.
+    pub fn storage_class(&self) -> Option<&str> {
+        debug_assert!(
+            self.bit.contains(Metakey::StorageClass) || self.bit.contains(Metakey::Complete),
+            "visiting not set metadata: storage_class, maybe a bug"
+        );
+
+        self.storage_class.as_deref()
+    }
+
+    /// Set storage class of this entry.
+    pub fn set_storage_class(&mut self, v: &str) -> &mut Self {
+        self.storage_class = Some(v.to_string());
+        self.bit |= Metakey::StorageClass;
+        self
+    }
+
+    /// Set storage class of this entry.
+    pub fn with_storage_class(mut self, v: String) -> Self {
+        self.storage_class = Some(v);
+        self.bit |= Metakey::StorageClass;
+        self
+    }
+
+    /// Restore status of this entry.
+    ///
+    /// `None` means no restore has been requested for this entry.
+    pub fn restore_status(&self) -> Option<RestoreStatus> {
+        debug_assert!(
+            self.bit.contains(Metakey::RestoreStatus) || self.bit.contains(Metakey::Complete),
+            "visiting not set metadata: restore_status, maybe a bug"
+        );
+
+        self.restore_status
+    }
+
+    /// Set restore status of this entry.
+    ///
+    /// Set to `None` if no restore has been requested.
+    pub fn set_restore_status(&mut self, v: Option<RestoreStatus>) -> &mut Self {
+        self.restore_status = v;
+        self.bit |= Metakey::RestoreStatus;
+        self
+    }
+
+    /// Set restore status of this entry.
+    ///
+    /// Set to `None` if no restore has been requested.
+    pub fn with_restore_status(mut self, v: Option<RestoreStatus>) -> Self {
+        self.restore_status = v;
+        self.bit |= Metakey::RestoreStatus;
+        self
+    }
+
+    /// Check if this entry is a symlink that not followed.
+    ///
+    /// Only `fs` with symlink policy `report` will return symlinks, the
+    /// mode of them will be [`EntryMode::Unknown`].
+    pub fn is_symlink(&self) -> bool {
+        self.symlink
+    }
+
+    /// Set if this entry is a symlink that not followed.
+    pub fn set_symlink(&mut self, symlink: bool) -> &mut Self {
+        self.symlink = symlink;
+        self
+    }
+
+    /// Set if this entry is a symlink that not followed.
+    pub fn with_symlink(mut self, symlink: bool) -> Self {
+        self.symlink = symlink;
+        self
+    }
+}
+
+/// RestoreStatus describes the status of restoring an archived entry.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RestoreStatus {
+    ongoing: bool,
+    expiry: Option<OffsetDateTime>,
+}
+
+impl RestoreStatus {
+    /// Create a new restore status.
+    pub fn new(ongoing: bool, expiry: Option<OffsetDateTime>) -> Self {
+        Self { ongoing, expiry }
+    }
+
+    /// Check if the restore is still in progress.
+    ///
+    /// Entry can be read only after restore finished.
+    pub fn is_ongoing(&self) -> bool {
+        self.ongoing
+    }
+
+    /// The time that the restored copy will be removed.
+    ///
+    /// Only available after restore finished.
+    pub fn expiry(&self) -> Option<OffsetDateTime> {
+        self.expiry
+    }
+}
+
+/// StatSource describes where the metadata returned by
+/// [`Operator::stat_many`] comes from.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StatSource {
+    /// The metadata is answered by listing the parent dir.
+    ///
+    /// Metadata from listing is not complete, only mode, content length
+    /// and last modified are guaranteed to be set for files.
+    List,
+    /// The metadata is fetched by a `stat` call on the path.
+    Stat,
 }
 
 flags! {
@@ -405,6 +746,8 @@ flags! {
         Mode,
         /// Key for content disposition.
         ContentDisposition,
+        /// Key for content encoding.
+        ContentEncoding,
         /// Key for content length.
         ContentLength,
         /// Key for content md5.
@@ -417,5 +760,21 @@ flags! {
         Etag,
         /// Key for last last modified.
         LastModified,
+        /// Key for unix mode.
+        UnixMode,
+        /// Key for uid.
+        Uid,
+        /// Key for gid.
+        Gid,
+        /// Key for tags.
+        Tags,
+        /// Key for storage class.
+        StorageClass,
+        /// Key for restore status.
+        RestoreStatus,
+        /// Key for cache control.
+        CacheControl,
+        /// Key for expires.
+        Expires,
     }
 }
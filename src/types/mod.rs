@@ -21,14 +21,25 @@ pub use entry::Entry;
 mod metadata;
 pub use metadata::Metadata;
 pub use metadata::Metakey;
+pub use metadata::RestoreStatus;
+pub use metadata::StatSource;
 
 mod reader;
 pub use reader::BlockingReader;
 pub use reader::Reader;
+pub use reader::ReaderStream;
+
+#[cfg(feature = "csv")]
+mod csv;
+#[cfg(feature = "csv")]
+pub use self::csv::CsvReader;
+#[cfg(feature = "csv")]
+pub use self::csv::CsvRecord;
 
 mod writer;
 pub use writer::BlockingWriter;
 pub use writer::Writer;
+pub use writer::WriterSink;
 
 mod list;
 pub use list::BlockingLister;
@@ -51,4 +62,10 @@ pub use error::Result;
 mod scheme;
 pub use scheme::Scheme;
 
+mod capability;
+pub use capability::Capability;
+
+mod acl;
+pub use acl::Acl;
+
 pub mod ops;
@@ -105,6 +105,32 @@ impl BlockingOperator {
 
 /// # Operator blocking API.
 impl BlockingOperator {
+    /// Check if this operator can work correctly.
+    ///
+    /// This operation is the blocking version of [`Operator::check`].
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// use opendal::BlockingOperator;
+    ///
+    /// # fn test(op: BlockingOperator) -> Result<()> {
+    /// op.check()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn check(&self) -> Result<()> {
+        let res = if self.info().capability().list {
+            match self.inner().blocking_list("/", OpList::new().with_limit(1)) {
+                Ok((_, pager)) => BlockingLister::new(pager).next().transpose().map(|_| ()),
+                Err(err) => Err(err),
+            }
+        } else {
+            self.inner().blocking_stat("/", OpStat::new()).map(|_| ())
+        };
+
+        res.map_err(|err| err.with_operation("BlockingOperator::check"))
+    }
+
     /// Get current path's metadata **without cache** directly.
     ///
     /// # Notes
@@ -136,9 +162,16 @@ impl BlockingOperator {
     /// # }
     /// ```
     pub fn stat(&self, path: &str) -> Result<Metadata> {
+        self.stat_with(path, OpStat::new())
+    }
+
+    /// Get current path's metadata with extra options.
+    ///
+    /// Read [`Operator::stat_with`](crate::Operator::stat_with) for more details.
+    pub fn stat_with(&self, path: &str, args: OpStat) -> Result<Metadata> {
         let path = normalize_path(path);
 
-        let rp = self.inner().blocking_stat(&path, OpStat::new())?;
+        let rp = self.inner().blocking_stat(&path, args)?;
         let meta = rp.into_metadata();
 
         Ok(meta)
@@ -225,15 +258,23 @@ impl BlockingOperator {
     /// # }
     /// ```
     pub fn metadata(&self, entry: &Entry, flags: impl Into<FlagSet<Metakey>>) -> Result<Metadata> {
+        let flags = flags.into();
+
         // Check if cached metadata saticifies the query.
+        //
+        // Tags are not carried by complete metadata, so we need to
+        // request them explicitly.
         if let Some(meta) = entry.metadata() {
-            if meta.bit().contains(flags) || meta.bit().contains(Metakey::Complete) {
+            if meta.bit().contains(flags)
+                || (meta.bit().contains(Metakey::Complete) && !flags.contains(Metakey::Tags))
+            {
                 return Ok(meta.clone());
             }
         }
 
         // Else request from backend..
-        let meta = self.stat(entry.path())?;
+        let args = OpStat::new().with_tags(flags.contains(Metakey::Tags));
+        let meta = self.stat_with(entry.path(), args)?;
         Ok(meta)
     }
 
@@ -341,26 +382,45 @@ impl BlockingOperator {
     /// # }
     /// ```
     pub fn range_read(&self, path: &str, range: impl RangeBounds<u64>) -> Result<Vec<u8>> {
+        self.read_with(path, OpRead::new().with_range(range.into()))
+    }
+
+    /// Read the whole path into a bytes with extra options.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io::Result;
+    /// # use std::time::Duration;
+    /// # use std::time::Instant;
+    /// # use opendal::BlockingOperator;
+    /// use opendal::ops::OpRead;
+    ///
+    /// # fn test(op: BlockingOperator) -> Result<()> {
+    /// let args = OpRead::new().with_deadline(Instant::now() + Duration::from_millis(100));
+    /// let bs = op.read_with("path/to/file", args)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_with(&self, path: &str, args: OpRead) -> Result<Vec<u8>> {
         let path = normalize_path(path);
 
         if !validate_path(&path, EntryMode::FILE) {
             return Err(
                 Error::new(ErrorKind::IsADirectory, "read path is a directory")
-                    .with_operation("BlockingOperator::range_read")
+                    .with_operation("BlockingOperator::read_with")
                     .with_context("service", self.info().scheme().into_static())
                     .with_context("path", &path),
             );
         }
 
-        let br = BytesRange::from(range);
-        let (rp, mut s) = self
-            .inner()
-            .blocking_read(&path, OpRead::new().with_range(br))?;
+        let br = args.range();
+        let (rp, mut s) = self.inner().blocking_read(&path, args)?;
 
         let mut buffer = Vec::with_capacity(rp.into_metadata().content_length() as usize);
         s.read_to_end(&mut buffer).map_err(|err| {
             Error::new(ErrorKind::Unexpected, "blocking range read failed")
-                .with_operation("BlockingOperator::range_read")
+                .with_operation("BlockingOperator::read_with")
                 .with_context("service", self.info().scheme().into_static())
                 .with_context("path", path)
                 .with_context("range", br.to_string())
@@ -370,6 +430,99 @@ impl BlockingOperator {
         Ok(buffer)
     }
 
+    /// Read the path into the given buffer, returns the number of bytes read.
+    ///
+    /// Read [`Operator::read_into`](crate::Operator::read_into) for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io::Result;
+    /// # use opendal::BlockingOperator;
+    /// # fn test(op: BlockingOperator) -> Result<()> {
+    /// let mut buf = vec![0; 4096];
+    /// for path in ["path/to/a", "path/to/b"] {
+    ///     let n = op.read_into(path, &mut buf)?;
+    ///     let content = &buf[..n];
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_into(&self, path: &str, buf: &mut [u8]) -> Result<usize> {
+        let br = if !buf.is_empty() && self.info().capability().read_with_range {
+            BytesRange::new(Some(0), Some(buf.len() as u64))
+        } else {
+            BytesRange::default()
+        };
+
+        self.read_into_with(path, br, buf, "BlockingOperator::read_into")
+    }
+
+    /// Read the specified range of path into the given buffer, returns the
+    /// number of bytes read.
+    ///
+    /// At most `buf.len()` bytes will be read.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io::Result;
+    /// # use opendal::BlockingOperator;
+    /// # fn test(op: BlockingOperator) -> Result<()> {
+    /// let mut buf = vec![0; 1024];
+    /// let n = op.range_read_into("path/to/file", 1024..2048, &mut buf)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn range_read_into(
+        &self,
+        path: &str,
+        range: impl RangeBounds<u64>,
+        buf: &mut [u8],
+    ) -> Result<usize> {
+        self.read_into_with(path, range.into(), buf, "BlockingOperator::range_read_into")
+    }
+
+    fn read_into_with(
+        &self,
+        path: &str,
+        br: BytesRange,
+        buf: &mut [u8],
+        operation: &'static str,
+    ) -> Result<usize> {
+        let path = normalize_path(path);
+
+        if !validate_path(&path, EntryMode::FILE) {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "read path is a directory")
+                    .with_operation(operation)
+                    .with_context("service", self.info().scheme().into_static())
+                    .with_context("path", &path),
+            );
+        }
+
+        let (_, r) = self
+            .inner()
+            .blocking_read(&path, OpRead::new().with_range(br))?;
+        // Make sure we will not read more than the buffer could hold.
+        let mut r = oio::into_sliced_reader(r, buf.len() as u64);
+
+        let mut n = 0;
+        while n < buf.len() {
+            let size = oio::BlockingRead::read(&mut r, &mut buf[n..]).map_err(|err| {
+                err.with_operation(operation)
+                    .with_context("path", &path)
+                    .with_context("range", br.to_string())
+            })?;
+            if size == 0 {
+                break;
+            }
+            n += size;
+        }
+
+        Ok(n)
+    }
+
     /// Create a new reader which can read the whole path.
     ///
     /// # Examples
@@ -417,6 +570,47 @@ impl BlockingOperator {
         BlockingReader::create(self.inner().clone(), &path, op)
     }
 
+    /// Create a new reader which only reads the first `size` bytes.
+    ///
+    /// Read [`Operator::sliced_reader`](crate::Operator::sliced_reader) for
+    /// more details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use opendal::BlockingOperator;
+    /// use std::io::Read;
+    ///
+    /// # fn test(op: BlockingOperator) -> Result<()> {
+    /// let mut r = op.sliced_reader("path/to/file", 4)?;
+    /// let mut magic = vec![];
+    /// r.read_to_end(&mut magic)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sliced_reader(&self, path: &str, size: u64) -> Result<BlockingReader> {
+        let path = normalize_path(path);
+
+        if !validate_path(&path, EntryMode::FILE) {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "read path is a directory")
+                    .with_operation("BlockingOperator::sliced_reader")
+                    .with_context("service", self.info().scheme().into_static())
+                    .with_context("path", &path),
+            );
+        }
+
+        // Empty range is not allowed by http, read without range instead.
+        let op = if size > 0 && self.info().capability().read_with_range {
+            OpRead::new().with_range(BytesRange::new(Some(0), Some(size)))
+        } else {
+            OpRead::new()
+        };
+
+        BlockingReader::create_sliced(self.inner().clone(), &path, op, size)
+    }
+
     /// Write bytes into given path.
     ///
     /// # Notes
@@ -438,7 +632,8 @@ impl BlockingOperator {
     /// # }
     /// ```
     pub fn write(&self, path: &str, bs: impl Into<Bytes>) -> Result<()> {
-        self.write_with(path, OpWrite::new(), bs)
+        self.write_with(path, OpWrite::new(), bs)?;
+        Ok(())
     }
 
     /// Write data with option described in OpenDAL [rfc-0661](../../docs/rfcs/0661-path-in-accessor.md)
@@ -446,6 +641,8 @@ impl BlockingOperator {
     /// # Notes
     ///
     /// - Write will make sure all bytes has been written, or an error will be returned.
+    /// - The returned [`RpWrite`] carries the etag and version of the written
+    ///   object if services return them, so no extra `stat` is needed.
     ///
     /// # Examples
     ///
@@ -458,11 +655,12 @@ impl BlockingOperator {
     /// # async fn test(op: BlockingOperator) -> Result<()> {
     /// let bs = b"hello, world!".to_vec();
     /// let ow = OpWrite::new().with_content_type("text/plain");
-    /// let _ = op.write_with("hello.txt", ow, bs)?;
+    /// let rp = op.write_with("hello.txt", ow, bs)?;
+    /// let etag = rp.etag();
     /// # Ok(())
     /// # }
     /// ```
-    pub fn write_with(&self, path: &str, args: OpWrite, bs: impl Into<Bytes>) -> Result<()> {
+    pub fn write_with(&self, path: &str, args: OpWrite, bs: impl Into<Bytes>) -> Result<RpWrite> {
         let path = normalize_path(path);
 
         if !validate_path(&path, EntryMode::FILE) {
@@ -474,11 +672,15 @@ impl BlockingOperator {
             );
         }
 
-        let (_, mut w) = self.inner().blocking_write(&path, args)?;
-        w.write(bs.into())?;
-        w.close()?;
+        let bs = bs.into();
+        let args = match args.content_length() {
+            Some(_) => args,
+            None => args.with_content_length(bs.len() as u64),
+        };
 
-        Ok(())
+        let (_, mut w) = self.inner().blocking_write(&path, args)?;
+        w.write(bs)?;
+        w.close()
     }
 
     /// Write multiple bytes into given path.
@@ -505,18 +707,46 @@ impl BlockingOperator {
     /// # }
     /// ```
     pub fn writer(&self, path: &str) -> Result<BlockingWriter> {
+        self.writer_with(path, OpWrite::new())
+    }
+
+    /// Create a new writer with extra options.
+    ///
+    /// # Notes
+    ///
+    /// - If `content_length` is set, writer will make sure exactly the same
+    ///   size of bytes has been appended before `close`, or an error will be
+    ///   returned and the write will be aborted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::Result;
+    /// # use opendal::BlockingOperator;
+    /// use opendal::ops::OpWrite;
+    ///
+    /// # fn test(op: BlockingOperator) -> Result<()> {
+    /// let args = OpWrite::new().with_content_length(8192);
+    /// let mut w = op.writer_with("path/to/file", args)?;
+    /// w.append(vec![0; 4096])?;
+    /// w.append(vec![1; 4096])?;
+    /// w.close()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn writer_with(&self, path: &str, args: OpWrite) -> Result<BlockingWriter> {
         let path = normalize_path(path);
 
         if !validate_path(&path, EntryMode::FILE) {
             return Err(
                 Error::new(ErrorKind::IsADirectory, "write path is a directory")
-                    .with_operation("BlockingOperator::writer")
+                    .with_operation("BlockingOperator::writer_with")
                     .with_context("service", self.info().scheme().into_static())
                     .with_context("path", &path),
             );
         }
 
-        let op = OpWrite::default().with_append();
+        let op = args.with_append();
         BlockingWriter::create(self.inner().clone(), &path, op)
     }
 
@@ -538,9 +768,30 @@ impl BlockingOperator {
     /// # }
     /// ```
     pub fn delete(&self, path: &str) -> Result<()> {
+        self.delete_with(path, OpDelete::new())
+    }
+
+    /// Delete given path with extra options.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use std::time::Duration;
+    /// # use std::time::Instant;
+    /// # use opendal::BlockingOperator;
+    /// use opendal::ops::OpDelete;
+    ///
+    /// # fn test(op: BlockingOperator) -> Result<()> {
+    /// let args = OpDelete::new().with_deadline(Instant::now() + Duration::from_secs(1));
+    /// op.delete_with("path/to/file", args)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn delete_with(&self, path: &str, args: OpDelete) -> Result<()> {
         let path = normalize_path(path);
 
-        let _ = self.inner().blocking_delete(&path, OpDelete::new())?;
+        let _ = self.inner().blocking_delete(&path, args)?;
 
         Ok(())
     }
@@ -579,6 +830,40 @@ impl BlockingOperator {
     /// # }
     /// ```
     pub fn list(&self, path: &str) -> Result<BlockingLister> {
+        self.list_with(path, OpList::new())
+    }
+
+    /// List given path with extra options.
+    ///
+    /// An error will be returned if path doesn't end with `/`.
+    ///
+    /// # Notes
+    ///
+    /// Entries will be filtered by their last modified while listing if
+    /// [`OpList::with_modified_after`] or [`OpList::with_modified_before`]
+    /// is set.
+    ///
+    /// At most `limit` entries will be returned if [`OpList::with_limit`]
+    /// is set, no more pages will be fetched after the limit reached.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use opendal::Result;
+    /// # use opendal::BlockingOperator;
+    /// use opendal::ops::OpList;
+    /// use time::Duration;
+    /// use time::OffsetDateTime;
+    /// # fn test(op: BlockingOperator) -> Result<()> {
+    /// let cutoff = OffsetDateTime::now_utc() - Duration::days(1);
+    /// let ds = op.list_with("path/to/dir/", OpList::new().with_modified_after(cutoff))?;
+    /// for de in ds {
+    ///     println!("{} is modified in last day", de?.path())
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn list_with(&self, path: &str, args: OpList) -> Result<BlockingLister> {
         let path = normalize_path(path);
 
         if !validate_path(&path, EntryMode::DIR) {
@@ -591,7 +876,19 @@ impl BlockingOperator {
             .with_context("path", &path));
         }
 
-        let (_, pager) = self.inner().blocking_list(&path, OpList::new())?;
+        let (_, mut pager) = self.inner().blocking_list(&path, args.clone())?;
+        let limit = args.limit();
+        if args.has_modified_filter() {
+            pager = Box::new(oio::filter_modified_pager(
+                self.inner().clone(),
+                pager,
+                args,
+            ));
+        }
+        if let Some(limit) = limit {
+            pager = Box::new(oio::limit_pager(pager, limit));
+        }
+
         Ok(BlockingLister::new(pager))
     }
 
@@ -644,4 +941,50 @@ impl BlockingOperator {
         let (_, pager) = self.inner().blocking_scan(&path, OpScan::new())?;
         Ok(BlockingLister::new(pager))
     }
+
+    /// Walk all entries under given path.
+    ///
+    /// Refer to [`Operator::walk`] for more information.
+    ///
+    /// An error will be returned if given path doesn't end with `/`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use opendal::Result;
+    /// # use opendal::BlockingOperator;
+    /// use opendal::ops::OpWalk;
+    /// # fn test(op: BlockingOperator) -> Result<()> {
+    /// let ds = op.walk("path/to/dir/", OpWalk::new().with_include_dirs(false))?;
+    /// for de in ds {
+    ///     println!("Handling file {}", de?.path())
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn walk(&self, path: &str, args: OpWalk) -> Result<BlockingLister> {
+        let path = normalize_path(path);
+
+        if !validate_path(&path, EntryMode::DIR) {
+            return Err(Error::new(
+                ErrorKind::NotADirectory,
+                "the path trying to walk is not a directory",
+            )
+            .with_operation("BlockingOperator::walk")
+            .with_context("service", self.info().scheme().into_static())
+            .with_context("path", &path));
+        }
+        if !self.info().can_list() && !self.info().can_scan() {
+            return Err(
+                Error::new(ErrorKind::Unsupported, "operation is not supported")
+                    .with_operation("BlockingOperator::walk")
+                    .with_context("service", self.info().scheme().into_static())
+                    .with_context("path", &path),
+            );
+        }
+
+        let pager: oio::WalkPager<_, oio::BlockingPager> =
+            oio::to_walk_pager(self.inner().clone(), &path, args);
+        Ok(BlockingLister::new(Box::new(pager)))
+    }
 }
@@ -143,6 +143,9 @@ impl Operator {
     ///
     /// It's always recommended to use `OperatorBuilder::layer()` instead.
     ///
+    /// Just like `OperatorBuilder::layer()`, the new layer will wrap all
+    /// existing layers and become the outermost one.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -225,10 +228,21 @@ impl<A: Accessor> OperatorBuilder<A> {
         OperatorBuilder { accessor }
             .layer(ErrorContextLayer)
             .layer(CompleteLayer)
+            .layer(DeadlineLayer)
     }
 
     /// Create a new layer with static dispatch.
     ///
+    /// # Ordering
+    ///
+    /// Every call wraps all layers added before it, so the last added
+    /// layer is the outermost one: it sees calls first and results last.
+    /// For example, adding `RetryLayer` before `MetricsLayer` makes
+    /// metrics observe the final result of a retried operation instead
+    /// of every single attempt.
+    ///
+    /// The applied layers could be inspected via [`OperatorInfo::layers`].
+    ///
     /// # Notes
     ///
     /// `OperatorBuilder::layer()` is using static dispatch which is zero
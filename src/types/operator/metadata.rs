@@ -44,6 +44,23 @@ impl OperatorInfo {
         self.0.name()
     }
 
+    /// Get [`Capability`] of operator.
+    ///
+    /// Capability describes which operations and options could be used
+    /// on this operator.
+    pub fn capability(&self) -> Capability {
+        self.0.capability()
+    }
+
+    /// Names of layers applied on this operator, outermost first.
+    ///
+    /// For example, an operator built with
+    /// `.layer(LoggingLayer::default()).layer(RetryLayer::new())` returns
+    /// `["RetryLayer", "LoggingLayer"]`.
+    pub fn layers(&self) -> &[&'static str] {
+        self.0.layers()
+    }
+
     /// Check if current backend supports [`Accessor::read`] or not.
     pub fn can_read(&self) -> bool {
         self.0.capabilities().contains(AccessorCapability::Read)
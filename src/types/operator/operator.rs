@@ -12,16 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::ops::RangeBounds;
+use std::path::Path;
+use std::path::PathBuf;
 
 use bytes::Bytes;
+use bytes::BytesMut;
+use filetime::FileTime;
 use flagset::FlagSet;
 use futures::stream;
 use futures::AsyncReadExt;
 use futures::Stream;
 use futures::StreamExt;
 use futures::TryStreamExt;
+use log::debug;
+use log::warn;
+use md5::Digest;
+use md5::Md5;
 use time::Duration;
+use tokio::io::AsyncWriteExt;
 use tokio::io::ReadBuf;
 
 use super::BlockingOperator;
@@ -29,6 +40,15 @@ use crate::ops::*;
 use crate::raw::*;
 use crate::*;
 
+/// The size of chunks that [`Operator::write_from_stream`] sends to services.
+///
+/// 8 MiB is larger than the minimal part size of most multipart upload
+/// implementations, like 5 MiB of s3.
+const WRITE_FROM_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// The concurrent of fallback stat calls in [`Operator::stat_many`].
+const STAT_MANY_CONCURRENT: usize = 8;
+
 /// Operator is the entry for all public async APIs.
 ///
 /// Read [`concepts`][docs::concepts] for know more about [`Operator`].
@@ -61,6 +81,8 @@ pub struct Operator {
     accessor: FusedAccessor,
 
     limit: usize,
+    /// The chunk size and concurrency for reads, `None` means disabled.
+    read_chunk: Option<(u64, usize)>,
 }
 
 /// # Operator basic API.
@@ -73,6 +95,7 @@ impl Operator {
         Self {
             accessor,
             limit: 1000,
+            read_chunk: None,
         }
     }
 
@@ -94,6 +117,54 @@ impl Operator {
         op
     }
 
+    /// Specify the chunk size and concurrency for reads.
+    ///
+    /// Reads larger than `chunk_size` will be split into ranged reads of
+    /// `chunk_size`, and at most `concurrent` of them will be sent at the
+    /// same time. Chunks are assembled in order behind one [`Reader`].
+    ///
+    /// # Notes
+    ///
+    /// - Only async `read`, `range_read`, `read_with` and readers are affected.
+    /// - Services that don't support `read_with_range` will read as usual.
+    /// - An extra `stat` will be sent to get the size of the path.
+    /// - Any failed chunk will fail the read. If [`RetryLayer`] is present,
+    ///   only the failed chunk will be retried.
+    ///
+    /// Default: disabled
+    ///
+    /// [`RetryLayer`]: crate::layers::RetryLayer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// // Read by 8 concurrent chunks of 16 MiB.
+    /// let op = op.with_read_chunk(16 * 1024 * 1024, 8);
+    /// let bs = op.read("path/to/large/file").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_read_chunk(&self, chunk_size: u64, concurrent: usize) -> Self {
+        let mut op = self.clone();
+        op.read_chunk = Some((chunk_size, concurrent));
+        op
+    }
+
+    /// Create a reader via given args, read by chunks if enabled.
+    async fn create_reader(&self, path: &str, args: OpRead) -> Result<Reader> {
+        match self.read_chunk {
+            Some((chunk_size, concurrent)) => {
+                Reader::create_concurrent(self.inner().clone(), path, args, chunk_size, concurrent)
+                    .await
+            }
+            None => Reader::create(self.inner().clone(), path, args).await,
+        }
+    }
+
     /// Get information of underlying accessor.
     ///
     /// # Examples
@@ -125,7 +196,19 @@ impl Operator {
 impl Operator {
     /// Check if this operator can work correctly.
     ///
-    /// We will send a `list` request to path and return any errors we met.
+    /// We will send a cheap and non-destructive request to the root of
+    /// operator and return any errors we met:
+    ///
+    /// - `list` the root with limit `1` if list is supported.
+    /// - `stat` the root otherwise.
+    ///
+    /// No entries will be created during checking. Errors returned by
+    /// underlying services will be kept so users can decide what to do:
+    ///
+    /// - [`ErrorKind::ConfigInvalid`]: credentials are invalid.
+    /// - [`ErrorKind::NotFound`]: bucket or root is not exist.
+    /// - [`ErrorKind::PermissionDenied`]: credentials don't have permission
+    ///   to access this root.
     ///
     /// ```
     /// # use std::sync::Arc;
@@ -139,12 +222,16 @@ impl Operator {
     /// # }
     /// ```
     pub async fn check(&self) -> Result<()> {
-        let mut ds = self.list("/").await?;
+        let res = if self.info().capability().list {
+            match self.inner().list("/", OpList::new().with_limit(1)).await {
+                Ok((_, pager)) => Lister::new(pager).next().await.transpose().map(|_| ()),
+                Err(err) => Err(err),
+            }
+        } else {
+            self.inner().stat("/", OpStat::new()).await.map(|_| ())
+        };
 
-        match ds.next().await {
-            Some(Err(e)) if e.kind() != ErrorKind::NotFound => Err(e),
-            _ => Ok(()),
-        }
+        res.map_err(|err| err.with_operation("Operator::check"))
     }
 
     /// Get current path's metadata **without cache** directly.
@@ -179,14 +266,165 @@ impl Operator {
     /// # }
     /// ```
     pub async fn stat(&self, path: &str) -> Result<Metadata> {
+        self.stat_with(path, OpStat::new()).await
+    }
+
+    /// Get current path's metadata with extra options.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use opendal::ops::OpStat;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let meta = op
+    ///     .stat_with("path/to/file", OpStat::new().with_tags(true))
+    ///     .await?;
+    /// let tags = meta.tags();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stat_with(&self, path: &str, args: OpStat) -> Result<Metadata> {
         let path = normalize_path(path);
 
-        let rp = self.inner().stat(&path, OpStat::new()).await?;
+        let rp = self.inner().stat(&path, args).await?;
         let meta = rp.into_metadata();
 
         Ok(meta)
     }
 
+    /// Stat many paths at once and return a map of path to metadata.
+    ///
+    /// Paths that share the same parent will be answered by one `list`
+    /// call on the parent instead of `stat` on every path, which is much
+    /// faster on services like s3 for metadata heavy workloads. Paths not
+    /// covered by the listing will fall back to `stat`.
+    ///
+    /// Every value carries a [`StatSource`] to mark where it comes from.
+    ///
+    /// # Notes
+    ///
+    /// - Metadata from listing may not be complete, for example, s3 doesn't
+    ///   return content type while listing. Use [`Operator::stat`] if you
+    ///   need all metadata.
+    /// - Listing will only be used for parents that more than one path
+    ///   shares. Entries that listing doesn't carry content length and
+    ///   last modified will fall back to `stat`.
+    /// - Paths that not exist will not be contained in the returning map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use opendal::StatSource;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let metas = op
+    ///     .stat_many(vec!["dir/a".to_string(), "dir/b".to_string()])
+    ///     .await?;
+    /// for (path, (meta, source)) in metas {
+    ///     if source == StatSource::List {
+    ///         println!("{path} answered by list: {}", meta.content_length());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stat_many(
+        &self,
+        paths: Vec<String>,
+    ) -> Result<HashMap<String, (Metadata, StatSource)>> {
+        let mut groups: HashMap<String, HashSet<String>> = HashMap::new();
+        for path in paths {
+            let path = normalize_path(&path);
+            groups
+                .entry(get_parent(&path).to_string())
+                .or_default()
+                .insert(path);
+        }
+
+        let mut results = HashMap::new();
+        let mut fallbacks = Vec::new();
+        for (parent, mut paths) in groups {
+            if paths.len() > 1 && self.info().can_list() {
+                self.stat_many_via_list(&parent, &mut paths, &mut results)
+                    .await;
+            }
+            fallbacks.extend(paths);
+        }
+
+        let mut stats = stream::iter(fallbacks)
+            .map(|path| async move {
+                let meta = self.stat(&path).await;
+                (path, meta)
+            })
+            .buffer_unordered(STAT_MANY_CONCURRENT);
+        while let Some((path, meta)) = stats.next().await {
+            match meta {
+                Ok(meta) => {
+                    results.insert(path, (meta, StatSource::Stat));
+                }
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.with_operation("Operator::stat_many")),
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// List the parent dir to answer stats of paths.
+    ///
+    /// Answered paths will be removed from `paths`, so that the left ones
+    /// can fall back to `stat`. Errors are ignored here for the same reason.
+    async fn stat_many_via_list(
+        &self,
+        parent: &str,
+        paths: &mut HashSet<String>,
+        results: &mut HashMap<String, (Metadata, StatSource)>,
+    ) {
+        let mut lister = match self.list(parent).await {
+            Ok(lister) => lister,
+            Err(err) => {
+                debug!("stat_many list {parent} failed, fallback to stat: {err:?}");
+                return;
+            }
+        };
+
+        while !paths.is_empty() {
+            let entry = match lister.try_next().await {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                Err(err) => {
+                    debug!("stat_many list {parent} failed, fallback to stat: {err:?}");
+                    break;
+                }
+            };
+            if !paths.contains(entry.path()) {
+                continue;
+            }
+
+            let meta = match entry.metadata() {
+                Some(meta)
+                    if meta.mode().is_dir()
+                        || meta.bit().contains(Metakey::Complete)
+                        || meta
+                            .bit()
+                            .contains(Metakey::ContentLength | Metakey::LastModified) =>
+                {
+                    meta.clone()
+                }
+                _ => continue,
+            };
+            paths.remove(entry.path());
+            results.insert(entry.path().to_string(), (meta, StatSource::List));
+        }
+    }
+
     /// Get current metadata with cache.
     ///
     /// `metadata` will check the given query with already cached metadata
@@ -276,15 +514,23 @@ impl Operator {
         entry: &Entry,
         flags: impl Into<FlagSet<Metakey>>,
     ) -> Result<Metadata> {
+        let flags = flags.into();
+
         // Check if cached metadata saticifies the query.
+        //
+        // Tags are not carried by complete metadata, so we need to
+        // request them explicitly.
         if let Some(meta) = entry.metadata() {
-            if meta.bit().contains(flags) || meta.bit().contains(Metakey::Complete) {
+            if meta.bit().contains(flags)
+                || (meta.bit().contains(Metakey::Complete) && !flags.contains(Metakey::Tags))
+            {
                 return Ok(meta.clone());
             }
         }
 
         // Else request from backend..
-        let meta = self.stat(entry.path()).await?;
+        let args = OpStat::new().with_tags(flags.contains(Metakey::Tags));
+        let meta = self.stat_with(entry.path(), args).await?;
         Ok(meta)
     }
 
@@ -402,24 +648,74 @@ impl Operator {
     /// # }
     /// ```
     pub async fn range_read(&self, path: &str, range: impl RangeBounds<u64>) -> Result<Vec<u8>> {
+        self.read_with(path, OpRead::new().with_range(range.into()))
+            .await
+    }
+
+    /// Read the whole path into a bytes with extra options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::Result;
+    /// # use std::time::Duration;
+    /// # use std::time::Instant;
+    /// # use opendal::Operator;
+    /// use opendal::ops::OpRead;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let args = OpRead::new().with_deadline(Instant::now() + Duration::from_millis(100));
+    /// let bs = op.read_with("path/to/file", args).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_with(&self, path: &str, args: OpRead) -> Result<Vec<u8>> {
         let path = normalize_path(path);
 
         if !validate_path(&path, EntryMode::FILE) {
             return Err(
                 Error::new(ErrorKind::IsADirectory, "read path is a directory")
-                    .with_operation("range_read")
+                    .with_operation("Operator::read_with")
                     .with_context("service", self.inner().info().scheme())
                     .with_context("path", &path),
             );
         }
 
-        let br = BytesRange::from(range);
+        let br = args.range();
+
+        if self.read_chunk.is_some() {
+            let mut r = self.create_reader(&path, args).await?;
+            let mut buffer = Vec::new();
+            r.read_to_end(&mut buffer).await.map_err(|err| {
+                Error::new(ErrorKind::Unexpected, "read from storage")
+                    .with_operation("Operator::read_with")
+                    .with_context("service", self.inner().info().scheme().into_static())
+                    .with_context("path", &path)
+                    .with_context("range", br.to_string())
+                    .set_source(err)
+            })?;
+            return Ok(buffer);
+        }
 
-        let op = OpRead::new().with_range(br);
+        let (rp, mut s) = self.inner().read(&path, args).await?;
 
-        let (rp, mut s) = self.inner().read(&path, op).await?;
+        let meta = rp.into_metadata();
+        // Read until EOF if the size is unknown.
+        if !meta.bit().contains(Metakey::ContentLength) {
+            let mut buffer = Vec::new();
+            s.read_to_end(&mut buffer).await.map_err(|err| {
+                Error::new(ErrorKind::Unexpected, "read from storage")
+                    .with_operation("Operator::read_with")
+                    .with_context("service", self.inner().info().scheme().into_static())
+                    .with_context("path", &path)
+                    .with_context("range", br.to_string())
+                    .set_source(err)
+            })?;
+            return Ok(buffer);
+        }
 
-        let length = rp.into_metadata().content_length() as usize;
+        let length = meta.content_length() as usize;
         let mut buffer = Vec::with_capacity(length);
 
         let dst = buffer.spare_capacity_mut();
@@ -431,7 +727,7 @@ impl Operator {
         // TODO: use native read api
         s.read_exact(buf.initialized_mut()).await.map_err(|err| {
             Error::new(ErrorKind::Unexpected, "read from storage")
-                .with_operation("range_read")
+                .with_operation("Operator::read_with")
                 .with_context("service", self.inner().info().scheme().into_static())
                 .with_context("path", &path)
                 .with_context("range", br.to_string())
@@ -444,226 +740,1322 @@ impl Operator {
         Ok(buffer)
     }
 
-    /// Create a new reader which can read the whole path.
+    /// Read the path into the given buffer, returns the number of bytes read.
+    ///
+    /// At most `buf.len()` bytes will be read from the start of path, so
+    /// the same buffer can be reused across reads without allocating.
+    ///
+    /// # Notes
+    ///
+    /// - The returning size will be smaller than `buf.len()` if the path
+    ///   is smaller than the buffer.
+    /// - Services that support `read_with_range` (checked in [`Capability`])
+    ///   will only request `buf.len()` bytes.
     ///
     /// # Examples
     ///
-    /// ```no_run
+    /// ```
     /// # use std::io::Result;
     /// # use opendal::Operator;
-    /// # use futures::TryStreamExt;
-    /// # use opendal::Scheme;
     /// # #[tokio::main]
     /// # async fn test(op: Operator) -> Result<()> {
-    /// let r = op.reader("path/to/file").await?;
+    /// let mut buf = vec![0; 4096];
+    /// for path in ["path/to/a", "path/to/b"] {
+    ///     let n = op.read_into(path, &mut buf).await?;
+    ///     let content = &buf[..n];
+    /// }
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn reader(&self, path: &str) -> Result<Reader> {
-        self.range_reader(path, ..).await
+    pub async fn read_into(&self, path: &str, buf: &mut [u8]) -> Result<usize> {
+        let br = if !buf.is_empty() && self.info().capability().read_with_range {
+            BytesRange::new(Some(0), Some(buf.len() as u64))
+        } else {
+            BytesRange::default()
+        };
+
+        self.read_into_with(path, br, buf, "Operator::read_into")
+            .await
     }
 
-    /// Create a new reader which can read the specified range.
-    ///
-    /// # Notes
+    /// Read the specified range of path into the given buffer, returns the
+    /// number of bytes read.
     ///
-    /// - The returning content's length may be smaller than the range specified.
+    /// At most `buf.len()` bytes will be read.
     ///
     /// # Examples
     ///
-    /// ```no_run
+    /// ```
     /// # use std::io::Result;
     /// # use opendal::Operator;
-    /// # use futures::TryStreamExt;
     /// # #[tokio::main]
     /// # async fn test(op: Operator) -> Result<()> {
-    /// let r = op.range_reader("path/to/file", 1024..2048).await?;
+    /// let mut buf = vec![0; 1024];
+    /// let n = op.range_read_into("path/to/file", 1024..2048, &mut buf).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn range_reader(&self, path: &str, range: impl RangeBounds<u64>) -> Result<Reader> {
+    pub async fn range_read_into(
+        &self,
+        path: &str,
+        range: impl RangeBounds<u64>,
+        buf: &mut [u8],
+    ) -> Result<usize> {
+        self.read_into_with(path, range.into(), buf, "Operator::range_read_into")
+            .await
+    }
+
+    async fn read_into_with(
+        &self,
+        path: &str,
+        br: BytesRange,
+        buf: &mut [u8],
+        operation: &'static str,
+    ) -> Result<usize> {
         let path = normalize_path(path);
 
         if !validate_path(&path, EntryMode::FILE) {
             return Err(
                 Error::new(ErrorKind::IsADirectory, "read path is a directory")
-                    .with_operation("Operator::range_reader")
+                    .with_operation(operation)
                     .with_context("service", self.info().scheme())
-                    .with_context("path", path),
+                    .with_context("path", &path),
             );
         }
 
-        let op = OpRead::new().with_range(range.into());
+        let (_, r) = self
+            .inner()
+            .read(&path, OpRead::new().with_range(br))
+            .await?;
+        // Make sure we will not download more than the buffer could hold.
+        let mut r = oio::into_sliced_reader(r, buf.len() as u64);
+
+        let mut n = 0;
+        while n < buf.len() {
+            let size = oio::ReadExt::read(&mut r, &mut buf[n..])
+                .await
+                .map_err(|err| {
+                    err.with_operation(operation)
+                        .with_context("path", &path)
+                        .with_context("range", br.to_string())
+                })?;
+            if size == 0 {
+                break;
+            }
+            n += size;
+        }
 
-        Reader::create(self.inner().clone(), &path, op).await
+        Ok(n)
     }
 
-    /// Write bytes into path.
+    /// Create a new reader which can read the whole path.
     ///
     /// # Notes
     ///
-    /// - Write will make sure all bytes has been written, or an error will be returned.
+    /// [`Reader`] implements `futures::AsyncRead` and `futures::AsyncBufRead`
+    /// (and the `tokio` ones) itself, so it could be used with
+    /// `futures::io::copy` and friends directly without an adapter.
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
     /// # use std::io::Result;
     /// # use opendal::Operator;
-    /// # use futures::StreamExt;
-    /// # use futures::SinkExt;
-    /// use bytes::Bytes;
-    ///
+    /// # use futures::io;
+    /// # use futures::TryStreamExt;
+    /// # use opendal::Scheme;
     /// # #[tokio::main]
     /// # async fn test(op: Operator) -> Result<()> {
-    /// op.write("path/to/file", vec![0; 4096]).await?;
+    /// let r = op.reader("path/to/file").await?;
+    /// io::copy(r, &mut io::sink()).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn write(&self, path: &str, bs: impl Into<Bytes>) -> Result<()> {
-        self.write_with(path, OpWrite::new(), bs).await
+    pub async fn reader(&self, path: &str) -> Result<Reader> {
+        self.range_reader(path, ..).await
     }
 
-    /// Write multiple bytes into path.
+    /// Create a new reader which can read the specified range.
     ///
     /// # Notes
     ///
-    /// - Write will make sure all bytes has been written, or an error will be returned.
+    /// - The returning content's length may be smaller than the range specified.
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```no_run
     /// # use std::io::Result;
     /// # use opendal::Operator;
-    /// # use futures::StreamExt;
-    /// # use futures::SinkExt;
-    /// use bytes::Bytes;
-    ///
+    /// # use futures::TryStreamExt;
     /// # #[tokio::main]
     /// # async fn test(op: Operator) -> Result<()> {
-    /// let mut w = op.writer("path/to/file").await?;
-    /// w.append(vec![0; 4096]).await?;
-    /// w.append(vec![1; 4096]).await?;
-    /// w.close().await?;
+    /// let r = op.range_reader("path/to/file", 1024..2048).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn writer(&self, path: &str) -> Result<Writer> {
+    pub async fn range_reader(&self, path: &str, range: impl RangeBounds<u64>) -> Result<Reader> {
         let path = normalize_path(path);
 
         if !validate_path(&path, EntryMode::FILE) {
             return Err(
-                Error::new(ErrorKind::IsADirectory, "write path is a directory")
-                    .with_operation("Operator::writer")
-                    .with_context("service", self.inner().info().scheme().into_static())
-                    .with_context("path", &path),
+                Error::new(ErrorKind::IsADirectory, "read path is a directory")
+                    .with_operation("Operator::range_reader")
+                    .with_context("service", self.info().scheme())
+                    .with_context("path", path),
             );
         }
 
-        let op = OpWrite::default().with_append();
-        Writer::create(self.inner().clone(), &path, op).await
+        self.create_reader(&path, OpRead::new().with_range(range.into()))
+            .await
     }
 
-    /// Write data with extra options.
-    ///
-    /// # Notes
-    ///
-    /// - Write will make sure all bytes has been written, or an error will be returned.
+    /// Create a new reader with extra options.
     ///
     /// # Examples
     ///
     /// ```no_run
     /// # use std::io::Result;
+    /// # use std::time::Duration;
+    /// # use std::time::Instant;
     /// # use opendal::Operator;
-    /// use bytes::Bytes;
-    /// use opendal::ops::OpWrite;
+    /// use opendal::ops::OpRead;
     ///
     /// # #[tokio::main]
     /// # async fn test(op: Operator) -> Result<()> {
-    /// let bs = b"hello, world!".to_vec();
-    /// let args = OpWrite::new().with_content_type("text/plain");
-    /// let _ = op.write_with("path/to/file", args, bs).await?;
+    /// let args = OpRead::new().with_deadline(Instant::now() + Duration::from_secs(1));
+    /// let r = op.reader_with("path/to/file", args).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn write_with(&self, path: &str, args: OpWrite, bs: impl Into<Bytes>) -> Result<()> {
+    pub async fn reader_with(&self, path: &str, args: OpRead) -> Result<Reader> {
         let path = normalize_path(path);
 
         if !validate_path(&path, EntryMode::FILE) {
             return Err(
-                Error::new(ErrorKind::IsADirectory, "write path is a directory")
-                    .with_operation("Operator::write_with")
-                    .with_context("service", self.info().scheme().into_static())
-                    .with_context("path", &path),
+                Error::new(ErrorKind::IsADirectory, "read path is a directory")
+                    .with_operation("Operator::reader_with")
+                    .with_context("service", self.info().scheme())
+                    .with_context("path", path),
             );
         }
 
-        let (_, mut w) = self.inner().write(&path, args).await?;
-        w.write(bs.into()).await?;
-        w.close().await?;
-
-        Ok(())
+        self.create_reader(&path, args).await
     }
 
-    /// Delete the given path.
+    /// Create a new reader which only reads the first `size` bytes.
+    ///
+    /// The returning reader will report EOF after `size` bytes, which makes
+    /// operations like format sniffing cheap.
     ///
     /// # Notes
     ///
-    /// - Delete not existing error won't return errors.
+    /// - Services that support `read_with_range` (checked in [`Capability`])
+    ///   will send a ranged request of exactly `size` bytes.
+    /// - For other services, the underlying reader will be dropped after
+    ///   `size` bytes so that the rest will not be downloaded.
+    /// - The returning reader doesn't support seeking.
     ///
     /// # Examples
     ///
-    /// ```
-    /// # use anyhow::Result;
-    /// # use futures::io;
+    /// ```no_run
+    /// # use std::io::Result;
     /// # use opendal::Operator;
+    /// use futures::AsyncReadExt;
+    ///
     /// # #[tokio::main]
     /// # async fn test(op: Operator) -> Result<()> {
-    /// op.delete("test").await?;
+    /// let mut r = op.sliced_reader("path/to/file", 4).await?;
+    /// let mut magic = vec![];
+    /// r.read_to_end(&mut magic).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn delete(&self, path: &str) -> Result<()> {
+    pub async fn sliced_reader(&self, path: &str, size: u64) -> Result<Reader> {
         let path = normalize_path(path);
 
-        let _ = self.inner().delete(&path, OpDelete::new()).await?;
+        if !validate_path(&path, EntryMode::FILE) {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "read path is a directory")
+                    .with_operation("Operator::sliced_reader")
+                    .with_context("service", self.info().scheme())
+                    .with_context("path", path),
+            );
+        }
+
+        // Empty range is not allowed by http, read without range instead.
+        let op = if size > 0 && self.info().capability().read_with_range {
+            OpRead::new().with_range(BytesRange::new(Some(0), Some(size)))
+        } else {
+            OpRead::new()
+        };
 
-        Ok(())
+        Reader::create_sliced(self.inner().clone(), &path, op, size).await
     }
 
+    /// Create a new reader which starts reading at the given offset.
     ///
-    /// # Notes
-    ///
-    /// If underlying services support delete in batch, we will use batch
-    /// delete instead.
+    /// This is a shortcut of `range_reader(path, offset..)`. Combined
+    /// with [`Reader::offset`], users can resume an interrupted download
+    /// from where it stopped.
     ///
     /// # Examples
     ///
-    /// ```
-    /// # use anyhow::Result;
-    /// # use futures::io;
+    /// ```no_run
+    /// # use std::io::Result;
     /// # use opendal::Operator;
-    /// #
+    /// use futures::AsyncReadExt;
+    ///
     /// # #[tokio::main]
     /// # async fn test(op: Operator) -> Result<()> {
-    /// op.remove(vec!["abc".to_string(), "def".to_string()])
-    ///     .await?;
+    /// let total = op.stat("path/to/file").await?.content_length();
+    ///
+    /// let mut offset = 0;
+    /// let mut buf = vec![0; 4096];
+    /// while offset < total {
+    ///     let mut r = op.reader_from("path/to/file", offset).await?;
+    ///     // Read until meeting an error, and resume from the last offset.
+    ///     while let Ok(n) = r.read(&mut buf).await {
+    ///         if n == 0 {
+    ///             break;
+    ///         }
+    ///     }
+    ///     offset = r.offset();
+    /// }
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn remove(&self, paths: Vec<String>) -> Result<()> {
-        self.remove_via(stream::iter(paths)).await
+    pub async fn reader_from(&self, path: &str, offset: u64) -> Result<Reader> {
+        self.range_reader(path, offset..).await
     }
 
-    /// remove will given paths.
-
-    /// remove_via will remove files via given stream.
+    /// Create a new csv reader which streams records of the whole path.
     ///
-    /// We will delete by chunks with given batch limit on the stream.
+    /// Read [`Operator::csv_reader_with`] for more details.
     ///
-    /// # Notes
+    /// # Examples
     ///
-    /// If underlying services support delete in batch, we will use batch
-    /// delete instead.
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use futures::TryStreamExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let mut r = op.csv_reader("path/to/file.csv").await?;
+    /// while let Some(record) = r.try_next().await? {
+    ///     println!("first field: {:?}", record.get(0));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "csv")]
+    pub async fn csv_reader(&self, path: &str) -> Result<CsvReader> {
+        self.csv_reader_with(path, OpReadCsv::new()).await
+    }
+
+    /// Create a new csv reader with extra options.
+    ///
+    /// Records are parsed from the chunks returned by services directly
+    /// and only read while polling, see [`CsvReader`] for details.
+    ///
+    /// # Ranges
+    ///
+    /// With [`OpReadCsv::with_range`], only records that start inside the
+    /// range will be returned. The partial record before the first line
+    /// terminator will be skipped, and the last record will be read
+    /// completely even if it crosses the end of range. So an object could
+    /// be split into adjacent ranges and processed in parallel.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use futures::TryStreamExt;
+    /// use opendal::ops::OpReadCsv;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let args = OpReadCsv::new().with_range(1024..2048).with_delimiter(b';');
+    /// let records: Vec<_> = op
+    ///     .csv_reader_with("path/to/file.csv", args)
+    ///     .await?
+    ///     .try_collect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "csv")]
+    pub async fn csv_reader_with(&self, path: &str, args: OpReadCsv) -> Result<CsvReader> {
+        let range = args.range();
+        let start = match (range.offset(), range.size()) {
+            (Some(offset), _) => offset,
+            (None, None) => 0,
+            (None, Some(_)) => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "suffix range is not supported by csv reader",
+                )
+                .with_operation("Operator::csv_reader")
+                .with_context("service", self.info().scheme())
+                .with_context("path", path)
+                .with_context("range", range.to_string()))
+            }
+        };
+
+        // Read from the previous byte so that a record starts at `start`
+        // will not be skipped.
+        let r = self.reader_from(path, start.saturating_sub(1)).await?;
+
+        Ok(CsvReader::new(
+            r,
+            args.build_parser(),
+            start > 0,
+            range.size().map(|v| start + v),
+        ))
+    }
+
+    /// Read the whole path into a local file.
+    ///
+    /// Read [`Operator::read_into_file_with`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let size = op
+    ///     .read_into_file("path/to/file", "/tmp/path/to/file")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_into_file(&self, path: &str, local_path: impl AsRef<Path>) -> Result<u64> {
+        self.read_into_file_with(path, local_path, OpReadIntoFile::new())
+            .await
+    }
+
+    /// Read the whole path into a local file with extra options.
+    ///
+    /// The size of local file will be returned.
+    ///
+    /// # Notes
+    ///
+    /// - Parent dirs of `local_path` will be created if not exist.
+    /// - Data will be written into a temporary file in the same dir first,
+    ///   and renamed to `local_path` after all data has been written. So
+    ///   `local_path` will never be visible with partial content.
+    /// - Last modified of `local_path` will be set to the same as path if
+    ///   returned by services. Failing to set it will be ignored.
+    /// - With [`OpReadIntoFile::with_resume`], the temporary file will be
+    ///   kept if read failed, and the next call will continue from its end
+    ///   via a ranged read. The temporary file is named after the etag (or
+    ///   content length and last modified if etag is not available) of path,
+    ///   so that a changed path will never be resumed from a stale file.
+    ///   Paths without neither of them can't be resumed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use opendal::ops::OpReadIntoFile;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let args = OpReadIntoFile::new()
+    ///     .with_buffer_size(4 * 1024 * 1024)
+    ///     .with_resume(true);
+    /// let size = op
+    ///     .read_into_file_with("path/to/file", "/tmp/path/to/file", args)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_into_file_with(
+        &self,
+        path: &str,
+        local_path: impl AsRef<Path>,
+        args: OpReadIntoFile,
+    ) -> Result<u64> {
+        let path = normalize_path(path);
+        let local_path = local_path.as_ref();
+
+        if !validate_path(&path, EntryMode::FILE) {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "read path is a directory")
+                    .with_operation("Operator::read_into_file")
+                    .with_context("service", self.info().scheme())
+                    .with_context("path", path),
+            );
+        }
+
+        let meta = self.stat(&path).await?;
+        let total = meta.content_length();
+
+        let (tmp_path, resumable) = read_into_file_tmp_path(local_path, &meta, args.resume())?;
+        if let Some(parent) = tmp_path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|err| new_local_io_error(parent, err))?;
+        }
+
+        let mut offset = 0;
+        if resumable {
+            match tokio::fs::metadata(&tmp_path).await {
+                // Partial file larger than expected must be broken, start over.
+                Ok(m) if m.len() <= total => offset = m.len(),
+                Ok(_) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(new_local_io_error(&tmp_path, err)),
+            }
+        }
+
+        let mut opts = tokio::fs::OpenOptions::new();
+        opts.write(true).create(true);
+        if offset > 0 {
+            opts.append(true);
+        } else {
+            opts.truncate(true);
+        }
+        let mut f = opts
+            .open(&tmp_path)
+            .await
+            .map_err(|err| new_local_io_error(&tmp_path, err))?;
+
+        let mut written = offset;
+        let res: Result<()> = async {
+            if offset < total {
+                let mut s = self
+                    .reader_from(&path, offset)
+                    .await?
+                    .into_stream(args.buffer_size());
+                while let Some(bs) = s.try_next().await? {
+                    f.write_all(&bs)
+                        .await
+                        .map_err(|err| new_local_io_error(&tmp_path, err))?;
+                    written += bs.len() as u64;
+                }
+            }
+
+            f.sync_all()
+                .await
+                .map_err(|err| new_local_io_error(&tmp_path, err))
+        }
+        .await;
+        drop(f);
+
+        if let Err(err) = res {
+            if !resumable {
+                let _ = tokio::fs::remove_file(&tmp_path).await;
+            }
+            return Err(err
+                .with_operation("Operator::read_into_file")
+                .with_context("local_path", local_path.to_string_lossy())
+                .with_context("written", written.to_string()));
+        }
+
+        if written != total {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "read size is not match with content length",
+            )
+            .with_operation("Operator::read_into_file")
+            .with_context("path", &path)
+            .with_context("expect", total.to_string())
+            .with_context("actual", written.to_string()));
+        }
+
+        tokio::fs::rename(&tmp_path, local_path)
+            .await
+            .map_err(|err| new_local_io_error(local_path, err))?;
+
+        if let Some(t) = meta.last_modified() {
+            let mtime = FileTime::from_unix_time(t.unix_timestamp(), t.nanosecond());
+            if let Err(err) = filetime::set_file_mtime(local_path, mtime) {
+                warn!(target: "opendal::services", "set last modified of {} failed: {err:?}", local_path.display());
+            }
+        }
+
+        Ok(total)
+    }
+
+    /// Write bytes into path.
+    ///
+    /// # Notes
+    ///
+    /// - Write will make sure all bytes has been written, or an error will be returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::Result;
+    /// # use opendal::Operator;
+    /// # use futures::StreamExt;
+    /// # use futures::SinkExt;
+    /// use bytes::Bytes;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// op.write("path/to/file", vec![0; 4096]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn write(&self, path: &str, bs: impl Into<Bytes>) -> Result<()> {
+        self.write_with(path, OpWrite::new(), bs).await?;
+        Ok(())
+    }
+
+    /// Write multiple bytes into path.
+    ///
+    /// # Notes
+    ///
+    /// - Write will make sure all bytes has been written, or an error will be returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::Result;
+    /// # use opendal::Operator;
+    /// # use futures::StreamExt;
+    /// # use futures::SinkExt;
+    /// use bytes::Bytes;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let mut w = op.writer("path/to/file").await?;
+    /// w.append(vec![0; 4096]).await?;
+    /// w.append(vec![1; 4096]).await?;
+    /// w.close().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn writer(&self, path: &str) -> Result<Writer> {
+        self.writer_with(path, OpWrite::new()).await
+    }
+
+    /// Create a new writer with extra options.
+    ///
+    /// # Notes
+    ///
+    /// - If `content_length` is set, writer will make sure exactly the same
+    ///   size of bytes has been appended before `close`, or an error will be
+    ///   returned and the write will be aborted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::Result;
+    /// # use opendal::Operator;
+    /// use opendal::ops::OpWrite;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let args = OpWrite::new().with_content_length(8192);
+    /// let mut w = op.writer_with("path/to/file", args).await?;
+    /// w.append(vec![0; 4096]).await?;
+    /// w.append(vec![1; 4096]).await?;
+    /// w.close().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn writer_with(&self, path: &str, args: OpWrite) -> Result<Writer> {
+        let path = normalize_path(path);
+
+        if !validate_path(&path, EntryMode::FILE) {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "write path is a directory")
+                    .with_operation("Operator::writer_with")
+                    .with_context("service", self.inner().info().scheme().into_static())
+                    .with_context("path", &path),
+            );
+        }
+
+        let op = args.with_append();
+        Writer::create(self.inner().clone(), &path, op).await
+    }
+
+    /// Write data with extra options.
+    ///
+    /// # Notes
+    ///
+    /// - Write will make sure all bytes has been written, or an error will be returned.
+    /// - The returned [`RpWrite`] carries the etag and version of the written
+    ///   object if services return them, so no extra `stat` is needed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io::Result;
+    /// # use opendal::Operator;
+    /// use bytes::Bytes;
+    /// use opendal::ops::OpWrite;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let bs = b"hello, world!".to_vec();
+    /// let args = OpWrite::new().with_content_type("text/plain");
+    /// let rp = op.write_with("path/to/file", args, bs).await?;
+    /// let etag = rp.etag();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn write_with(
+        &self,
+        path: &str,
+        args: OpWrite,
+        bs: impl Into<Bytes>,
+    ) -> Result<RpWrite> {
+        let path = normalize_path(path);
+
+        if !validate_path(&path, EntryMode::FILE) {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "write path is a directory")
+                    .with_operation("Operator::write_with")
+                    .with_context("service", self.info().scheme().into_static())
+                    .with_context("path", &path),
+            );
+        }
+
+        let bs = bs.into();
+        let args = match args.content_length() {
+            Some(_) => args,
+            None => args.with_content_length(bs.len() as u64),
+        };
+
+        let (_, mut w) = self.inner().write(&path, args).await?;
+        w.write(bs).await?;
+        w.close().await
+    }
+
+    /// Write data from an [`AsyncRead`][futures::AsyncRead] into path.
+    ///
+    /// Data will be read from `r` in chunks and streamed into the service
+    /// without buffering the whole payload. Read [`Operator::write_from_stream`]
+    /// for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let r = futures::io::Cursor::new(vec![0; 16 * 1024 * 1024]);
+    /// op.write_from("path/to/file", Some(16 * 1024 * 1024), r)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn write_from(
+        &self,
+        path: &str,
+        content_length: Option<u64>,
+        r: impl futures::AsyncRead + Unpin + Send,
+    ) -> Result<()> {
+        let s = stream::try_unfold(r, |mut r| async move {
+            let mut buf = Vec::with_capacity(WRITE_FROM_CHUNK_SIZE);
+            let res = (&mut r)
+                .take(WRITE_FROM_CHUNK_SIZE as u64)
+                .read_to_end(&mut buf)
+                .await;
+
+            match res {
+                Ok(0) => Ok(None),
+                Ok(_) => Ok(Some((Bytes::from(buf), r))),
+                Err(err) => Err(Error::new(ErrorKind::Unexpected, "read from source failed")
+                    .with_operation("Operator::write_from")
+                    .set_source(err)),
+            }
+        });
+
+        self.write_from_stream(path, content_length, s).await
+    }
+
+    /// Write data from a [`Stream`] of [`Bytes`] into path.
+    ///
+    /// # Notes
+    ///
+    /// - Data will be sent in chunks of 8 MiB via [`Operator::writer`] so
+    ///   that services like s3 can upload them by multipart.
+    /// - Data smaller than one chunk will be sent in a single write.
+    /// - Services that don't support append (checked by `write_can_append`
+    ///   in [`Capability`]) have to buffer the whole payload before write.
+    /// - If `content_length` is given, the total size of the source must
+    ///   match, or an error will be returned.
+    /// - If the source returns an error, the pending write will be aborted
+    ///   and the error will be returned with bytes already sent in context
+    ///   `written`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use bytes::Bytes;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let s = futures::stream::iter(vec![Ok(Bytes::from("Hello, ")), Ok(Bytes::from("World!"))]);
+    /// op.write_from_stream("path/to/file", None, s).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn write_from_stream(
+        &self,
+        path: &str,
+        content_length: Option<u64>,
+        s: impl Stream<Item = Result<Bytes>> + Send,
+    ) -> Result<()> {
+        let path = normalize_path(path);
+
+        if !validate_path(&path, EntryMode::FILE) {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "write path is a directory")
+                    .with_operation("Operator::write_from_stream")
+                    .with_context("service", self.info().scheme().into_static())
+                    .with_context("path", &path),
+            );
+        }
+
+        futures::pin_mut!(s);
+
+        let can_append = self.info().capability().write_can_append;
+        let mut w: Option<oio::Writer> = None;
+        let mut buf = BytesMut::new();
+        let mut written = 0;
+
+        while let Some(bs) = s.next().await {
+            let bs = match bs {
+                Ok(bs) => bs,
+                Err(err) => return Err(abort_write_from(w, err, written).await),
+            };
+            buf.extend_from_slice(&bs);
+
+            if !can_append {
+                continue;
+            }
+
+            while buf.len() >= WRITE_FROM_CHUNK_SIZE {
+                let chunk = buf.split_to(WRITE_FROM_CHUNK_SIZE).freeze();
+                let size = chunk.len() as u64;
+
+                if w.is_none() {
+                    let mut op = OpWrite::new().with_append();
+                    if let Some(size) = content_length {
+                        op = op.with_content_length(size);
+                    }
+                    let (_, writer) = self.inner().write(&path, op).await?;
+                    w = Some(writer);
+                }
+                let writer = w.as_mut().expect("writer must be initiated");
+                if let Err(err) = writer.append(chunk).await {
+                    return Err(abort_write_from(w, err, written).await);
+                }
+                written += size;
+            }
+        }
+
+        let total = written + buf.len() as u64;
+        if let Some(expect) = content_length {
+            if total != expect {
+                let err = Error::new(
+                    ErrorKind::Unexpected,
+                    "source content length is not match with given",
+                )
+                .with_context("expect", expect.to_string())
+                .with_context("actual", total.to_string());
+                return Err(abort_write_from(w, err, written).await);
+            }
+        }
+
+        match w {
+            None => {
+                let op = OpWrite::new().with_content_length(total);
+                let (_, mut w) = self.inner().write(&path, op).await?;
+                w.write(buf.freeze()).await?;
+                w.close().await?;
+            }
+            Some(mut w) => {
+                if !buf.is_empty() {
+                    if let Err(err) = w.append(buf.freeze()).await {
+                        return Err(abort_write_from(Some(w), err, written).await);
+                    }
+                }
+                w.close().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Delete the given path.
+    ///
+    /// # Notes
+    ///
+    /// - Delete not existing error won't return errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use futures::io;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// op.delete("test").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete(&self, path: &str) -> Result<()> {
+        self.delete_with(path, OpDelete::new()).await
+    }
+
+    /// Delete given path with extra options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use std::time::Duration;
+    /// # use std::time::Instant;
+    /// # use opendal::Operator;
+    /// use opendal::ops::OpDelete;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let args = OpDelete::new().with_deadline(Instant::now() + Duration::from_secs(1));
+    /// op.delete_with("test", args).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_with(&self, path: &str, args: OpDelete) -> Result<()> {
+        let path = normalize_path(path);
+
+        let _ = self.inner().delete(&path, args).await?;
+
+        Ok(())
+    }
+
+    /// Restore the given archived path so that it can be read again.
+    ///
+    /// This is a shortcut of `restore_with(path, OpRestore::new(days))`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// op.restore("path/to/file", 7).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn restore(&self, path: &str, days: u32) -> Result<()> {
+        self.restore_with(path, OpRestore::new(days)).await
+    }
+
+    /// Restore the given archived path with extra options.
+    ///
+    /// Reading an archived path (like s3 objects in `GLACIER` storage class)
+    /// returns [`ErrorKind::Archived`]. Restore will return as soon as the
+    /// restore has been initiated, users can poll [`Metadata::restore_status`]
+    /// via `stat` to wait for it finished.
+    ///
+    /// # Notes
+    ///
+    /// - Check `restore` of [`Capability`] before using it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use opendal::ops::OpRestore;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// op.restore_with("path/to/file", OpRestore::new(7).with_tier("Bulk"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn restore_with(&self, path: &str, args: OpRestore) -> Result<()> {
+        let path = normalize_path(path);
+
+        if !validate_path(&path, EntryMode::FILE) {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "restore path is a directory")
+                    .with_operation("Operator::restore_with")
+                    .with_context("service", self.info().scheme())
+                    .with_context("path", &path),
+            );
+        }
+
+        let _ = self.inner().restore(&path, args).await?;
+
+        Ok(())
+    }
+
+    /// Abort the multipart upload of given path by its upload id.
+    ///
+    /// Uploads interrupted without [`Writer::abort`] (for example, the
+    /// process crashed) could leave uploaded parts on services which are
+    /// still billed. Users can save the upload id returned by
+    /// [`Writer::session`] and abort it later.
+    ///
+    /// # Notes
+    ///
+    /// - Check `abort_multipart` of [`Capability`] before using it.
+    /// - Returns [`ErrorKind::NotFound`] if the upload doesn't exist or has
+    ///   been completed or aborted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let w = op.writer("path/to/file").await?;
+    /// let upload_id = w.session().map(|v| v.to_string());
+    ///
+    /// // The writer is interrupted, abort the upload later.
+    /// if let Some(upload_id) = upload_id {
+    ///     op.abort_multipart("path/to/file", &upload_id).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn abort_multipart(&self, path: &str, upload_id: &str) -> Result<()> {
+        let path = normalize_path(path);
+
+        if !validate_path(&path, EntryMode::FILE) {
+            return Err(Error::new(
+                ErrorKind::IsADirectory,
+                "abort multipart path is a directory",
+            )
+            .with_operation("Operator::abort_multipart")
+            .with_context("service", self.info().scheme())
+            .with_context("path", &path));
+        }
+
+        let _ = self
+            .inner()
+            .abort_multipart(&path, OpAbortMultipart::new(upload_id))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Rename given path from `from` to `to`.
+    ///
+    /// `from` and `to` must be both files or both dirs. Rename a dir will
+    /// move the whole subtree under it, services like `azdfs` with
+    /// hierarchical namespace enabled will do it atomically.
+    ///
+    /// # Notes
+    ///
+    /// - Check `rename` of [`Capability`] before using it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// op.rename("path/to/file", "path/to/file2").await?;
+    /// op.rename("path/to/dir/", "path/to/dir2/").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn rename(&self, from: &str, to: &str) -> Result<()> {
+        let from = normalize_path(from);
+        let to = normalize_path(to);
+
+        if from == "/" || to == "/" {
+            return Err(
+                Error::new(ErrorKind::Unsupported, "rename root is not supported")
+                    .with_operation("Operator::rename")
+                    .with_context("service", self.info().scheme())
+                    .with_context("from", &from)
+                    .with_context("to", &to),
+            );
+        }
+
+        let from_is_dir = validate_path(&from, EntryMode::DIR);
+        if from_is_dir != validate_path(&to, EntryMode::DIR) {
+            let (kind, msg) = if from_is_dir {
+                (ErrorKind::NotADirectory, "rename dir to a file path")
+            } else {
+                (ErrorKind::IsADirectory, "rename file to a dir path")
+            };
+
+            return Err(Error::new(kind, msg)
+                .with_operation("Operator::rename")
+                .with_context("service", self.info().scheme())
+                .with_context("from", &from)
+                .with_context("to", &to));
+        }
+
+        let _ = self.inner().rename(&from, &to, OpRename::new()).await?;
+
+        Ok(())
+    }
+
+    /// Get the acl (owner, group and permission bits) of given path.
+    ///
+    /// # Notes
+    ///
+    /// - Check `acl` of [`Capability`] before using it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let acl = op.get_acl("path/to/file").await?;
+    /// println!("owner: {:?}", acl.owner());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_acl(&self, path: &str) -> Result<Acl> {
+        let path = normalize_path(path);
+
+        let rp = self.inner().get_acl(&path, OpGetAcl::new()).await?;
+
+        Ok(rp.into_acl())
+    }
+
+    /// Set the acl (owner, group and permission bits) of given path.
+    ///
+    /// Fields that not set in `acl` will be kept unchanged.
+    ///
+    /// # Notes
+    ///
+    /// - Check `acl` of [`Capability`] before using it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use opendal::Acl;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// op.set_acl("path/to/file", Acl::new().with_permissions("rwxr-x---"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_acl(&self, path: &str, acl: Acl) -> Result<()> {
+        let path = normalize_path(path);
+
+        let _ = self.inner().set_acl(&path, OpSetAcl::new(acl)).await?;
+
+        Ok(())
+    }
+
+    /// Get the tags of given path.
+    ///
+    /// Tags differ from user metadata in that they can be changed via
+    /// [`Operator::put_tags`] without rewriting the object.
+    ///
+    /// # Notes
+    ///
+    /// - Check `tags` of [`Capability`] before using it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let tags = op.get_tags("path/to/file").await?;
+    /// println!("env: {:?}", tags.get("env"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_tags(&self, path: &str) -> Result<HashMap<String, String>> {
+        let path = normalize_path(path);
+
+        let rp = self.inner().get_tags(&path, OpGetTags::new()).await?;
+
+        Ok(rp.into_tags())
+    }
+
+    /// Put tags on given path.
+    ///
+    /// All existing tags of the path will be replaced by `tags`, the
+    /// content of the path is kept untouched. Pass an empty map to remove
+    /// all tags.
+    ///
+    /// # Notes
+    ///
+    /// - Check `tags` of [`Capability`] before using it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let tags = HashMap::from([("env".to_string(), "prod".to_string())]);
+    /// op.put_tags("path/to/file", tags).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn put_tags(&self, path: &str, tags: HashMap<String, String>) -> Result<()> {
+        let path = normalize_path(path);
+
+        if !validate_path(&path, EntryMode::FILE) {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "put tags on dir is not supported")
+                    .with_operation("Operator::put_tags")
+                    .with_context("service", self.info().scheme())
+                    .with_context("path", &path),
+            );
+        }
+
+        let _ = self.inner().put_tags(&path, OpPutTags::new(tags)).await?;
+
+        Ok(())
+    }
+
+    /// Remove given paths.
+    ///
+    /// # Notes
+    ///
+    /// - If underlying services support delete in batch, we will use batch
+    ///   delete instead.
+    /// - The first error will be returned. Use [`Operator::remove_with`] to
+    ///   get results for every path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use futures::io;
+    /// # use opendal::Operator;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// op.remove(vec!["abc".to_string(), "def".to_string()])
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn remove(&self, paths: Vec<String>) -> Result<()> {
+        for (_, result) in self.remove_with(paths, OpRemove::new()).await? {
+            result?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove given paths with extra options and return results for every path.
+    ///
+    /// # Notes
+    ///
+    /// - If underlying services support delete in batch, paths will be
+    ///   deleted by chunks of [`Operator::limit`] (1000 by default).
+    ///   Otherwise, they will be deleted concurrently.
+    /// - `NotFound` will be treated as success by default, use
+    ///   [`OpRemove::with_ignore_not_found`] to surface it. Please note that
+    ///   most services treat deleting a not existing path as success, so
+    ///   `NotFound` can only be returned by services that report it.
+    /// - Errors of the whole request (like a failed batch call) will be
+    ///   returned directly instead of per-path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use opendal::ops::OpRemove;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let results = op
+    ///     .remove_with(
+    ///         vec!["abc".to_string(), "def".to_string()],
+    ///         OpRemove::new().with_concurrent(16),
+    ///     )
+    ///     .await?;
+    /// for (path, result) in results {
+    ///     if let Err(err) = result {
+    ///         println!("remove {path} failed: {err}");
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn remove_with(
+        &self,
+        paths: Vec<String>,
+        args: OpRemove,
+    ) -> Result<Vec<(String, Result<()>)>> {
+        let ignore_not_found = args.ignore_not_found();
+        let handle = move |path: String, result: Result<RpDelete>| match result {
+            Err(err) if ignore_not_found && err.kind() == ErrorKind::NotFound => (path, Ok(())),
+            Err(err) => (path, Err(err)),
+            Ok(_) => (path, Ok(())),
+        };
+
+        if !self.info().can_batch() {
+            let results = stream::iter(paths)
+                .map(|path| async move {
+                    let result = self.inner().delete(&path, OpDelete::new()).await;
+                    handle(path, result)
+                })
+                .buffered(args.concurrent())
+                .collect()
+                .await;
+
+            return Ok(results);
+        }
+
+        let mut results = Vec::with_capacity(paths.len());
+        let mut input = stream::iter(paths)
+            .map(|v| (v, OpDelete::new()))
+            .chunks(self.limit());
+
+        while let Some(batches) = input.next().await {
+            let rp = self
+                .inner()
+                .batch(OpBatch::new(BatchOperations::Delete(batches)))
+                .await?;
+
+            let BatchedResults::Delete(batched) = rp.into_results();
+            results.extend(
+                batched
+                    .into_iter()
+                    .map(|(path, result)| handle(path, result)),
+            );
+        }
+
+        Ok(results)
+    }
+
+    /// remove_via will remove files via given stream.
+    ///
+    /// We will delete by chunks with given batch limit on the stream.
+    ///
+    /// # Notes
+    ///
+    /// If underlying services support delete in batch, we will use batch
+    /// delete instead.
     ///
     /// # Examples
     ///
@@ -765,6 +2157,203 @@ impl Operator {
         Ok(())
     }
 
+    /// Copy all entries under `from` to `to` recursively.
+    ///
+    /// # Notes
+    ///
+    /// - Both `from` and `to` must be dirs which end with `/`.
+    /// - Entries will be copied with their paths relative to `from` kept.
+    /// - `to` must not be inside `from`, otherwise the copied entries could
+    ///   be copied again.
+    /// - All entries will be tried even if some of them failed, an error
+    ///   with the count of failed entries will be returned at the end.
+    ///   Use [`Operator::copy_all_with`] to get results for every entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// op.copy_all("path/to/src/", "path/to/dst/").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_all(&self, from: &str, to: &str) -> Result<()> {
+        let results = self.copy_all_with(from, to, OpCopyAll::new()).await?;
+
+        copy_all_results_into_error(results, "Operator::copy_all")
+    }
+
+    /// Copy all entries under `from` to `to` recursively with extra options
+    /// and return results for every entry.
+    ///
+    /// # Notes
+    ///
+    /// - Files will be copied on the server side if the service supports
+    ///   `copy` (checked by [`Capability`]), otherwise they will be read
+    ///   and written back in stream.
+    /// - Entries are copied concurrently while listing, the listing will
+    ///   not be loaded into memory at once.
+    /// - Results are keyed by source path and returned in the order they
+    ///   finished.
+    /// - Errors of listing will be returned directly instead of per-entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use opendal::ops::OpCopyAll;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let results = op
+    ///     .copy_all_with(
+    ///         "path/to/src/",
+    ///         "path/to/dst/",
+    ///         OpCopyAll::new().with_concurrent(16),
+    ///     )
+    ///     .await?;
+    /// for (path, result) in results {
+    ///     if let Err(err) = result {
+    ///         println!("copy {path} failed: {err}");
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_all_with(
+        &self,
+        from: &str,
+        to: &str,
+        args: OpCopyAll,
+    ) -> Result<Vec<(String, Result<()>)>> {
+        self.copy_all_between(self, from, to, args, true).await
+    }
+
+    /// Copy all entries under `from` to `to` of another operator recursively.
+    ///
+    /// This works like [`Operator::copy_all`], but the data will always be
+    /// transferred through the client even if both operators point to the
+    /// same service.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator, backup: Operator) -> Result<()> {
+    /// op.copy_all_to("path/to/src/", &backup, "path/to/dst/")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_all_to(&self, from: &str, target: &Operator, to: &str) -> Result<()> {
+        let results = self
+            .copy_all_to_with(from, target, to, OpCopyAll::new())
+            .await?;
+
+        copy_all_results_into_error(results, "Operator::copy_all_to")
+    }
+
+    /// Copy all entries under `from` to `to` of another operator recursively
+    /// with extra options and return results for every entry.
+    ///
+    /// See [`Operator::copy_all_with`] for details.
+    pub async fn copy_all_to_with(
+        &self,
+        from: &str,
+        target: &Operator,
+        to: &str,
+        args: OpCopyAll,
+    ) -> Result<Vec<(String, Result<()>)>> {
+        self.copy_all_between(target, from, to, args, false).await
+    }
+
+    async fn copy_all_between(
+        &self,
+        target: &Operator,
+        from: &str,
+        to: &str,
+        args: OpCopyAll,
+        same_operator: bool,
+    ) -> Result<Vec<(String, Result<()>)>> {
+        let from = normalize_path(from);
+        let to = normalize_path(to);
+
+        if !validate_path(&from, EntryMode::DIR) || !validate_path(&to, EntryMode::DIR) {
+            return Err(Error::new(
+                ErrorKind::NotADirectory,
+                "the path trying to copy all is not a directory",
+            )
+            .with_operation("Operator::copy_all")
+            .with_context("service", self.info().scheme().into_static())
+            .with_context("from", &from)
+            .with_context("to", &to));
+        }
+
+        // Copying into the source itself will copy the copied entries again.
+        if same_operator && (from == "/" || to.starts_with(&from)) {
+            return Err(Error::new(
+                ErrorKind::Unexpected,
+                "the path trying to copy all to is inside the source",
+            )
+            .with_operation("Operator::copy_all")
+            .with_context("service", self.info().scheme().into_static())
+            .with_context("from", &from)
+            .with_context("to", &to));
+        }
+
+        let server_side = same_operator && self.info().capability().copy;
+        let create_dirs = args.create_dirs() && target.info().capability().create_dir;
+
+        let lister = self.walk(&from, OpWalk::new()).await?;
+
+        let (from, to) = (from.as_str(), to.as_str());
+        lister
+            .map_ok(move |entry| async move {
+                let src = entry.path().to_string();
+                let rel = if from == "/" {
+                    src.as_str()
+                } else {
+                    src.strip_prefix(from).unwrap_or(src.as_str())
+                };
+                let dst = if to == "/" {
+                    rel.to_string()
+                } else {
+                    format!("{to}{rel}")
+                };
+
+                let result = if src.ends_with('/') {
+                    if create_dirs {
+                        target.create_dir(&dst).await
+                    } else {
+                        Ok(())
+                    }
+                } else if server_side {
+                    self.inner()
+                        .copy(&src, &dst, OpCopy::new())
+                        .await
+                        .map(|_| ())
+                } else {
+                    match self.reader(&src).await {
+                        Ok(r) => target.write_from(&dst, None, r).await,
+                        Err(err) => Err(err),
+                    }
+                };
+
+                Ok::<_, Error>((src, result))
+            })
+            .try_buffer_unordered(args.concurrent())
+            .try_collect()
+            .await
+    }
+
     /// List given path.
     ///
     /// This function will create a new handle to list entries.
@@ -799,6 +2388,49 @@ impl Operator {
     /// # }
     /// ```
     pub async fn list(&self, path: &str) -> Result<Lister> {
+        self.list_with(path, OpList::new()).await
+    }
+
+    /// List given path with extra options.
+    ///
+    /// An error will be returned if given path doesn't end with `/`.
+    ///
+    /// # Notes
+    ///
+    /// Entries will be filtered by their last modified while listing if
+    /// [`OpList::with_modified_after`] or [`OpList::with_modified_before`]
+    /// is set.
+    ///
+    /// At most `limit` entries will be returned if [`OpList::with_limit`]
+    /// is set, no more pages will be fetched after the limit reached.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// # use futures::TryStreamExt;
+    /// use opendal::ops::OpList;
+    /// use time::Duration;
+    /// use time::OffsetDateTime;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let cutoff = OffsetDateTime::now_utc() - Duration::days(1);
+    /// let mut ds = op
+    ///     .list_with(
+    ///         "path/to/dir/",
+    ///         OpList::new()
+    ///             .with_modified_after(cutoff)
+    ///             .with_stat_unknown_modified(true),
+    ///     )
+    ///     .await?;
+    /// while let Some(de) = ds.try_next().await? {
+    ///     println!("{} is modified in last day", de.path())
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_with(&self, path: &str, args: OpList) -> Result<Lister> {
         let path = normalize_path(path);
 
         if !validate_path(&path, EntryMode::DIR) {
@@ -811,9 +2443,25 @@ impl Operator {
             .with_context("path", &path));
         }
 
-        let (_, pager) = self.inner().list(&path, OpList::new()).await?;
+        let (_, mut pager) = self.inner().list(&path, args.clone()).await?;
+        let prefetch = args.prefetch();
+        let limit = args.limit();
+        if args.has_modified_filter() {
+            pager = Box::new(oio::filter_modified_pager(
+                self.inner().clone(),
+                pager,
+                args,
+            ));
+        }
+        if let Some(limit) = limit {
+            pager = Box::new(oio::limit_pager(pager, limit));
+        }
+        let lister = Lister::new(pager);
 
-        Ok(Lister::new(pager))
+        Ok(match prefetch {
+            Some(prefetch) => lister.with_prefetch(prefetch),
+            None => lister,
+        })
     }
 
     /// List dir in flat way.
@@ -867,6 +2515,174 @@ impl Operator {
 
         Ok(Lister::new(pager))
     }
+
+    /// Walk all entries under given path.
+    ///
+    /// `walk` is the recommended way to visit a dir tree, it returns the
+    /// same entries no matter how the service lists:
+    ///
+    /// - Services that support [`Operator::scan`] natively will be walked
+    ///   via `scan`, entries will be filtered by `max_depth`.
+    /// - Others will be walked via [`Operator::list`] recursively, dirs
+    ///   deeper than `max_depth` will never be listed.
+    ///
+    /// Use `list` or `scan` directly if the raw behavior of service is
+    /// wanted.
+    ///
+    /// An error will be returned if given path doesn't end with `/`.
+    ///
+    /// # Notes
+    ///
+    /// The walking path itself will not be returned, and there is no
+    /// guarantee about the order of returning entries.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// # use futures::TryStreamExt;
+    /// use opendal::ops::OpWalk;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let mut ds = op
+    ///     .walk(
+    ///         "path/to/dir/",
+    ///         OpWalk::new().with_max_depth(2).with_include_dirs(false),
+    ///     )
+    ///     .await?;
+    /// while let Some(de) = ds.try_next().await? {
+    ///     println!("Handling file {}", de.path())
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn walk(&self, path: &str, args: OpWalk) -> Result<Lister> {
+        let path = normalize_path(path);
+
+        if !validate_path(&path, EntryMode::DIR) {
+            return Err(Error::new(
+                ErrorKind::NotADirectory,
+                "the path trying to walk is not a directory",
+            )
+            .with_operation("Operator::walk")
+            .with_context("service", self.info().scheme().into_static())
+            .with_context("path", &path));
+        }
+        if !self.info().can_list() && !self.info().can_scan() {
+            return Err(
+                Error::new(ErrorKind::Unsupported, "operation is not supported")
+                    .with_operation("Operator::walk")
+                    .with_context("service", self.info().scheme().into_static())
+                    .with_context("path", &path),
+            );
+        }
+
+        let pager: oio::WalkPager<_, oio::Pager> =
+            oio::to_walk_pager(self.inner().clone(), &path, args);
+        Ok(Lister::new(Box::new(pager)))
+    }
+
+    /// Get the total size and count of files under given path.
+    ///
+    /// Returns `(total_bytes, file_count)`, dirs are not counted.
+    ///
+    /// # Notes
+    ///
+    /// - Files are visited via [`Operator::walk`], so the most efficient
+    ///   listing of the service will be used.
+    /// - Sizes are taken from listing. An error will be returned if the
+    ///   service doesn't return content length while listing, use
+    ///   [`Operator::total_size_with`] with `stat_fallback` enabled for them.
+    /// - If path is a file, its own size will be returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let (size, count) = op.total_size("path/to/dir/").await?;
+    /// println!("{count} files take {size} bytes");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn total_size(&self, path: &str) -> Result<(u64, u64)> {
+        self.total_size_with(path, OpTotalSize::new()).await
+    }
+
+    /// Get the total size and count of files under given path with extra
+    /// options.
+    ///
+    /// Files that removed between listing and `stat` will be skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use opendal::ops::OpTotalSize;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let (size, count) = op
+    ///     .total_size_with("path/to/dir/", OpTotalSize::new().with_stat_fallback(true))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn total_size_with(&self, path: &str, args: OpTotalSize) -> Result<(u64, u64)> {
+        let path = normalize_path(path);
+
+        if !validate_path(&path, EntryMode::DIR) {
+            let meta = self.stat(&path).await?;
+            return Ok((meta.content_length(), 1));
+        }
+
+        let lister = self
+            .walk(&path, OpWalk::new().with_include_dirs(false))
+            .await?;
+
+        let stat_fallback = args.stat_fallback();
+        lister
+            .map_ok(move |entry| async move {
+                match entry.metadata() {
+                    Some(meta)
+                        if meta.bit().contains(Metakey::ContentLength)
+                            || meta.bit().contains(Metakey::Complete) =>
+                    {
+                        return Ok(Some(meta.content_length()))
+                    }
+                    _ => {}
+                }
+
+                if !stat_fallback {
+                    return Err(Error::new(
+                        ErrorKind::Unsupported,
+                        "content length is not returned by listing, please enable stat_fallback",
+                    )
+                    .with_operation("Operator::total_size")
+                    .with_context("service", self.info().scheme().into_static())
+                    .with_context("path", entry.path()));
+                }
+
+                match self.stat(entry.path()).await {
+                    Ok(meta) => Ok(Some(meta.content_length())),
+                    Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+                    Err(err) => Err(err.with_operation("Operator::total_size")),
+                }
+            })
+            .try_buffer_unordered(args.concurrent())
+            .try_fold((0, 0), |(size, count), len| async move {
+                Ok(match len {
+                    Some(len) => (size + len, count + 1),
+                    None => (size, count),
+                })
+            })
+            .await
+    }
 }
 
 /// Operator presign API.
@@ -1005,3 +2821,333 @@ impl Operator {
         Ok(rp.into_presigned_request())
     }
 }
+
+/// Abort the pending writer (if any) and attach `written` to the error.
+/// Turn results of `copy_all_with` into a single error that summarizes
+/// the failed entries.
+fn copy_all_results_into_error(
+    results: Vec<(String, Result<()>)>,
+    operation: &'static str,
+) -> Result<()> {
+    let total = results.len();
+    let mut failed = results
+        .into_iter()
+        .filter_map(|(path, result)| match result {
+            Ok(_) => None,
+            Err(err) => Some((path, err)),
+        });
+
+    let (path, err) = match failed.next() {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+    let failed_count = failed.count() + 1;
+
+    Err(
+        Error::new(ErrorKind::Unexpected, "copy failed for some entries")
+            .with_operation(operation)
+            .with_context("failed", failed_count.to_string())
+            .with_context("total", total.to_string())
+            .with_context("first_failed_path", path)
+            .set_source(err),
+    )
+}
+
+async fn abort_write_from(w: Option<oio::Writer>, err: Error, written: u64) -> Error {
+    if let Some(mut w) = w {
+        if let Err(e) = w.abort().await {
+            warn!(target: "opendal::services", "abort writer failed: {e:?}");
+        }
+    }
+
+    err.with_operation("Operator::write_from_stream")
+        .with_context("written", written.to_string())
+}
+
+/// Build the temporary path for `read_into_file`.
+///
+/// Returns whether the temporary path could be resumed.
+fn read_into_file_tmp_path(
+    local_path: &Path,
+    meta: &Metadata,
+    resume: bool,
+) -> Result<(PathBuf, bool)> {
+    let file_name = local_path.file_name().ok_or_else(|| {
+        Error::new(ErrorKind::Unexpected, "local path doesn't have file name")
+            .with_operation("Operator::read_into_file")
+            .with_context("local_path", local_path.to_string_lossy())
+    })?;
+
+    let version = if !resume {
+        None
+    } else if let Some(etag) = meta.etag() {
+        Some(etag.to_string())
+    } else {
+        meta.last_modified()
+            .map(|t| format!("{}-{}", meta.content_length(), t.unix_timestamp_nanos()))
+    };
+
+    let suffix = match &version {
+        Some(v) => format!("{:x}", Md5::digest(v.as_bytes())),
+        None => uuid::Uuid::new_v4().to_string(),
+    };
+    let tmp_path =
+        local_path.with_file_name(format!(".{}.{suffix}.part", file_name.to_string_lossy()));
+
+    Ok((tmp_path, version.is_some()))
+}
+
+fn new_local_io_error(path: &Path, err: std::io::Error) -> Error {
+    Error::new(ErrorKind::Unexpected, "local file io failed")
+        .with_operation("Operator::read_into_file")
+        .with_context("local_path", path.to_string_lossy())
+        .set_source(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use anyhow::Result;
+    use rand::RngCore;
+    use time::OffsetDateTime;
+
+    use super::*;
+    use crate::services::Fs;
+
+    fn new_fs_operator() -> Result<(Operator, PathBuf)> {
+        let root = env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+
+        let mut builder = Fs::default();
+        builder.root(&root.to_string_lossy());
+
+        Ok((Operator::new(builder)?.finish(), root))
+    }
+
+    fn gen_content(size: usize) -> Vec<u8> {
+        let mut content = vec![0; size];
+        rand::thread_rng().fill_bytes(&mut content);
+        content
+    }
+
+    #[tokio::test]
+    async fn test_read_into_file() -> Result<()> {
+        let (op, root) = new_fs_operator()?;
+        let content = gen_content(4 * 1024 * 1024 + 17);
+        op.write("src/file", content.clone()).await?;
+
+        let local_path = root.join("local/dst/file");
+        let size = op
+            .read_into_file_with(
+                "src/file",
+                &local_path,
+                OpReadIntoFile::new().with_buffer_size(1024 * 1024),
+            )
+            .await?;
+        assert_eq!(size, content.len() as u64);
+        assert_eq!(std::fs::read(&local_path)?, content);
+
+        // Last modified should be preserved.
+        let meta = op.stat("src/file").await?;
+        let mtime = OffsetDateTime::from(std::fs::metadata(&local_path)?.modified()?);
+        assert_eq!(Some(mtime), meta.last_modified());
+
+        // Temporary file should be renamed.
+        let entries = std::fs::read_dir(root.join("local/dst"))?.count();
+        assert_eq!(entries, 1);
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_into_file_resume() -> Result<()> {
+        let (op, root) = new_fs_operator()?;
+        let content = gen_content(4 * 1024 * 1024 + 17);
+        op.write("src/file", content.clone()).await?;
+
+        let local_path = root.join("local/file");
+        let meta = op.stat("src/file").await?;
+        let (tmp_path, resumable) = read_into_file_tmp_path(&local_path, &meta, true)?;
+        assert!(resumable);
+
+        // Simulate an interrupted read by cutting the reader mid-way.
+        let mut r = op.reader("src/file").await?;
+        let mut partial = vec![0; 1536 * 1024];
+        r.read_exact(&mut partial).await?;
+        drop(r);
+        std::fs::create_dir_all(root.join("local"))?;
+        std::fs::write(&tmp_path, &partial)?;
+
+        let size = op
+            .read_into_file_with(
+                "src/file",
+                &local_path,
+                OpReadIntoFile::new().with_resume(true),
+            )
+            .await?;
+        assert_eq!(size, content.len() as u64);
+        assert_eq!(std::fs::read(&local_path)?, content);
+        assert!(!tmp_path.exists());
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_into_file_resume_from_changed_path() -> Result<()> {
+        let (op, root) = new_fs_operator()?;
+        let content = gen_content(1024 * 1024);
+        op.write("src/file", content.clone()).await?;
+
+        let local_path = root.join("local/file");
+        let meta = op.stat("src/file").await?;
+        let (tmp_path, _) = read_into_file_tmp_path(&local_path, &meta, true)?;
+        std::fs::create_dir_all(root.join("local"))?;
+        std::fs::write(&tmp_path, gen_content(512 * 1024))?;
+
+        // Path changed after interrupted, the stale file must not be used.
+        let content = gen_content(2 * 1024 * 1024);
+        op.write("src/file", content.clone()).await?;
+
+        op.read_into_file_with(
+            "src/file",
+            &local_path,
+            OpReadIntoFile::new().with_resume(true),
+        )
+        .await?;
+        assert_eq!(std::fs::read(&local_path)?, content);
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stat_many() -> Result<()> {
+        let (op, root) = new_fs_operator()?;
+        op.write("dir/a", "a").await?;
+        op.write("dir/b", "bb").await?;
+        op.create_dir("dir/sub/").await?;
+        op.write("other", "ccc").await?;
+
+        let metas = op
+            .stat_many(vec![
+                "dir/a".to_string(),
+                "dir/b".to_string(),
+                "dir/sub/".to_string(),
+                "dir/not_exist".to_string(),
+                "other".to_string(),
+            ])
+            .await?;
+
+        assert_eq!(metas.len(), 4);
+        assert!(!metas.contains_key("dir/not_exist"));
+        assert_eq!(metas["dir/a"].0.content_length(), 1);
+        assert_eq!(metas["dir/b"].0.content_length(), 2);
+        assert_eq!(metas["other"].0.content_length(), 3);
+        assert_eq!(metas["other"].1, StatSource::Stat);
+        // Dirs are always answered by list.
+        assert!(metas["dir/sub/"].0.mode().is_dir());
+        assert_eq!(metas["dir/sub/"].1, StatSource::List);
+        // Fs doesn't return content length while listing.
+        assert_eq!(metas["dir/a"].1, StatSource::Stat);
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_all_into_source() -> Result<()> {
+        let (op, root) = new_fs_operator()?;
+        op.write("src/a", "a").await?;
+
+        for (from, to) in [("src/", "src/"), ("src/", "src/dst/"), ("/", "dst/")] {
+            let err = op.copy_all(from, to).await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::Unexpected, "{from} -> {to}");
+        }
+        // Sibling prefixes are not inside the source.
+        op.copy_all("src/", "src2/").await?;
+        assert_eq!(op.read("src2/a").await?, b"a");
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_all_to() -> Result<()> {
+        let (op, root) = new_fs_operator()?;
+        let (target, target_root) = new_fs_operator()?;
+        op.write("src/a", "a").await?;
+        op.write("src/x/b", "bb").await?;
+        op.create_dir("src/empty/").await?;
+
+        let mut results = op
+            .copy_all_to_with("src/", &target, "dst/", OpCopyAll::new())
+            .await?;
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let paths: Vec<_> = results.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, ["src/a", "src/empty/", "src/x/", "src/x/b"]);
+        assert!(results.iter().all(|(_, r)| r.is_ok()));
+
+        assert_eq!(target.read("dst/a").await?, b"a");
+        assert_eq!(target.read("dst/x/b").await?, b"bb");
+        // Empty dirs should be recreated too.
+        assert!(target.stat("dst/empty/").await?.mode().is_dir());
+
+        std::fs::remove_dir_all(root)?;
+        std::fs::remove_dir_all(target_root)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_total_size() -> Result<()> {
+        use crate::services::Memory;
+
+        let op = Operator::new(Memory::default())?.finish();
+        op.write("dir/a", "a").await?;
+        op.write("dir/x/b", "bb").await?;
+        op.write("dir/x/y/c", "ccc").await?;
+        op.write("other", "dddd").await?;
+
+        // Memory doesn't return content length while listing.
+        let err = op.total_size("dir/").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        let args = OpTotalSize::new().with_stat_fallback(true);
+        assert_eq!(op.total_size_with("dir/", args.clone()).await?, (6, 3));
+        assert_eq!(op.total_size_with("dir/x/", args.clone()).await?, (5, 2));
+        assert_eq!(
+            op.total_size_with("dir/not_exist/", args.clone()).await?,
+            (0, 0)
+        );
+        assert_eq!(op.total_size_with("/", args).await?, (10, 4));
+
+        // File's own size should be returned.
+        assert_eq!(op.total_size("other").await?, (4, 1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_layers_outermost_first() -> Result<()> {
+        use crate::layers::ConcurrentLimitLayer;
+        use crate::layers::LoggingLayer;
+        use crate::layers::RetryLayer;
+        use crate::services::Memory;
+
+        let op = Operator::new(Memory::default())?.finish();
+        assert!(op.info().layers().is_empty());
+
+        let op = Operator::new(Memory::default())?
+            .layer(LoggingLayer::default())
+            .layer(RetryLayer::new())
+            .finish()
+            .layer(ConcurrentLimitLayer::new(4));
+        assert_eq!(
+            op.info().layers(),
+            ["ConcurrentLimitLayer", "RetryLayer", "LoggingLayer"]
+        );
+        Ok(())
+    }
+}
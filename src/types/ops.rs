@@ -16,7 +16,13 @@
 //!
 //! By using ops, users can add more context for operation.
 
+use std::collections::HashMap;
+#[cfg(feature = "csv")]
+use std::ops::RangeBounds;
+use std::time::Instant;
+
 use time::Duration;
+use time::OffsetDateTime;
 
 use crate::raw::*;
 use crate::*;
@@ -45,21 +51,243 @@ impl OpCreate {
 ///
 /// The path must be normalized.
 #[derive(Debug, Clone, Default)]
-pub struct OpDelete {}
+pub struct OpDelete {
+    deadline: Option<Instant>,
+}
 
 impl OpDelete {
     /// Create a new `OpDelete`.
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Set the deadline of this operation.
+    ///
+    /// See [`OpRead::with_deadline`] for details.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Get deadline from option.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+}
+
+/// Args for `remove_with` operation.
+#[derive(Debug, Clone)]
+pub struct OpRemove {
+    /// Treat `NotFound` as success or not.
+    ignore_not_found: bool,
+    /// The max concurrent deletes for services without batch support.
+    concurrent: usize,
+}
+
+impl Default for OpRemove {
+    fn default() -> Self {
+        Self {
+            ignore_not_found: true,
+            concurrent: 8,
+        }
+    }
+}
+
+impl OpRemove {
+    /// Create a new `OpRemove`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get ignore_not_found from option.
+    pub fn ignore_not_found(&self) -> bool {
+        self.ignore_not_found
+    }
+
+    /// Set whether `NotFound` should be treated as success.
+    ///
+    /// Default to `true` so that removing is idempotent.
+    pub fn with_ignore_not_found(mut self, ignore_not_found: bool) -> Self {
+        self.ignore_not_found = ignore_not_found;
+        self
+    }
+
+    /// Get concurrent from option.
+    pub fn concurrent(&self) -> usize {
+        self.concurrent
+    }
+
+    /// Set the max concurrent deletes for services without batch support.
+    ///
+    /// Default to `8`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `concurrent` is `0`.
+    pub fn with_concurrent(mut self, concurrent: usize) -> Self {
+        assert!(concurrent > 0, "concurrent must be greater than 0");
+
+        self.concurrent = concurrent;
+        self
+    }
+}
+
+/// Args for `copy_all_with` operation.
+#[derive(Debug, Clone)]
+pub struct OpCopyAll {
+    /// The max concurrent copies.
+    concurrent: usize,
+    /// Recreate dir entries at destination or not.
+    create_dirs: bool,
+}
+
+impl Default for OpCopyAll {
+    fn default() -> Self {
+        Self {
+            concurrent: 8,
+            create_dirs: true,
+        }
+    }
+}
+
+impl OpCopyAll {
+    /// Create a new `OpCopyAll`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get concurrent from option.
+    pub fn concurrent(&self) -> usize {
+        self.concurrent
+    }
+
+    /// Set the max concurrent copies.
+    ///
+    /// Default to `8`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `concurrent` is `0`.
+    pub fn with_concurrent(mut self, concurrent: usize) -> Self {
+        assert!(concurrent > 0, "concurrent must be greater than 0");
+
+        self.concurrent = concurrent;
+        self
+    }
+
+    /// Get create_dirs from option.
+    pub fn create_dirs(&self) -> bool {
+        self.create_dirs
+    }
+
+    /// Set whether dir entries should be recreated at destination.
+    ///
+    /// Dirs will only be created on services that support `create_dir`.
+    ///
+    /// Default to `true`.
+    pub fn with_create_dirs(mut self, create_dirs: bool) -> Self {
+        self.create_dirs = create_dirs;
+        self
+    }
+}
+
+/// Args for `total_size_with` operation.
+#[derive(Debug, Clone)]
+pub struct OpTotalSize {
+    /// Stat entries that listing doesn't carry content length or not.
+    stat_fallback: bool,
+    /// The max concurrent stats.
+    concurrent: usize,
+}
+
+impl Default for OpTotalSize {
+    fn default() -> Self {
+        Self {
+            stat_fallback: false,
+            concurrent: 8,
+        }
+    }
+}
+
+impl OpTotalSize {
+    /// Create a new `OpTotalSize`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get stat_fallback from option.
+    pub fn stat_fallback(&self) -> bool {
+        self.stat_fallback
+    }
+
+    /// Set whether entries without content length in listing should be
+    /// stated one by one.
+    ///
+    /// This could be very expensive for large dirs since every entry
+    /// costs an extra request, an error will be returned for such entries
+    /// if it's disabled.
+    ///
+    /// Default to `false`.
+    pub fn with_stat_fallback(mut self, stat_fallback: bool) -> Self {
+        self.stat_fallback = stat_fallback;
+        self
+    }
+
+    /// Get concurrent from option.
+    pub fn concurrent(&self) -> usize {
+        self.concurrent
+    }
+
+    /// Set the max concurrent stats while falling back.
+    ///
+    /// Default to `8`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `concurrent` is `0`.
+    pub fn with_concurrent(mut self, concurrent: usize) -> Self {
+        assert!(concurrent > 0, "concurrent must be greater than 0");
+
+        self.concurrent = concurrent;
+        self
     }
 }
 
 /// Args for `list` operation.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct OpList {
-    /// The limit passed to underlying service to specify the max results
-    /// that could return.
+    /// The max number of entries that could return in total.
     limit: Option<usize>,
+
+    /// Only return entries modified after this time.
+    modified_after: Option<OffsetDateTime>,
+    /// Only return entries modified before this time.
+    modified_before: Option<OffsetDateTime>,
+    /// Send `stat` for entries whose last modified is unknown.
+    stat_unknown_modified: bool,
+    /// Include entries whose last modified is unknown or not.
+    include_unknown_modified: bool,
+    /// The max number of pages to fetch ahead while listing.
+    prefetch: Option<usize>,
+    /// Return `NotFound` error while listing a dir that doesn't exist.
+    error_on_not_found: bool,
+    deadline: Option<Instant>,
+}
+
+impl Default for OpList {
+    fn default() -> Self {
+        Self {
+            limit: None,
+
+            modified_after: None,
+            modified_before: None,
+            stat_unknown_modified: false,
+            include_unknown_modified: true,
+            prefetch: None,
+            error_on_not_found: false,
+            deadline: None,
+        }
+    }
 }
 
 impl OpList {
@@ -68,7 +296,26 @@ impl OpList {
         Self::default()
     }
 
-    /// Change the limit of this list operation.
+    /// Set the deadline of this operation.
+    ///
+    /// The deadline covers listing all pages via the returned lister.
+    /// See [`OpRead::with_deadline`] for details.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Get deadline from option.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Set the max number of entries to return in total.
+    ///
+    /// Listing will stop once the limit reached and no more pages will be
+    /// fetched. Services that support `max-keys` like params will also use
+    /// it as page size, so that we don't fetch entries that will be
+    /// discarded.
     pub fn with_limit(mut self, limit: usize) -> Self {
         self.limit = Some(limit);
         self
@@ -78,6 +325,111 @@ impl OpList {
     pub fn limit(&self) -> Option<usize> {
         self.limit
     }
+
+    /// Only return entries whose last modified is after given time.
+    pub fn with_modified_after(mut self, t: OffsetDateTime) -> Self {
+        self.modified_after = Some(t);
+        self
+    }
+
+    /// Get modified_after from option.
+    pub fn modified_after(&self) -> Option<OffsetDateTime> {
+        self.modified_after
+    }
+
+    /// Only return entries whose last modified is before given time.
+    pub fn with_modified_before(mut self, t: OffsetDateTime) -> Self {
+        self.modified_before = Some(t);
+        self
+    }
+
+    /// Get modified_before from option.
+    pub fn modified_before(&self) -> Option<OffsetDateTime> {
+        self.modified_before
+    }
+
+    /// Send `stat` for files whose last modified is not returned by list.
+    ///
+    /// Default to `false` since it will send one extra request per entry.
+    pub fn with_stat_unknown_modified(mut self, v: bool) -> Self {
+        self.stat_unknown_modified = v;
+        self
+    }
+
+    /// Get stat_unknown_modified from option.
+    pub fn stat_unknown_modified(&self) -> bool {
+        self.stat_unknown_modified
+    }
+
+    /// Include entries whose last modified is unknown or not.
+    ///
+    /// Default to `true` so that no entries will be missed silently.
+    pub fn with_include_unknown_modified(mut self, v: bool) -> Self {
+        self.include_unknown_modified = v;
+        self
+    }
+
+    /// Get include_unknown_modified from option.
+    pub fn include_unknown_modified(&self) -> bool {
+        self.include_unknown_modified
+    }
+
+    /// Set the max number of pages to fetch ahead while the caller is
+    /// consuming the current one.
+    ///
+    /// Default to `4`, set to `0` to disable prefetch. Refer to
+    /// [`Lister`](crate::Lister) for more details.
+    pub fn with_prefetch(mut self, prefetch: usize) -> Self {
+        self.prefetch = Some(prefetch);
+        self
+    }
+
+    /// Get prefetch from option.
+    pub fn prefetch(&self) -> Option<usize> {
+        self.prefetch
+    }
+
+    /// Return `NotFound` error while listing a dir that doesn't exist.
+    ///
+    /// Default to `false`, which means list on a not exist dir will return
+    /// nothing, the same as an empty dir.
+    pub fn with_error_on_not_found(mut self, v: bool) -> Self {
+        self.error_on_not_found = v;
+        self
+    }
+
+    /// Get error_on_not_found from option.
+    pub fn error_on_not_found(&self) -> bool {
+        self.error_on_not_found
+    }
+
+    /// Check if this list operation needs filtering by last modified.
+    pub fn has_modified_filter(&self) -> bool {
+        self.modified_after.is_some() || self.modified_before.is_some()
+    }
+
+    /// Check if given last modified is in the time window of this list
+    /// operation.
+    ///
+    /// `None` means the last modified is unknown.
+    pub fn is_modified_in(&self, last_modified: Option<OffsetDateTime>) -> bool {
+        let t = match last_modified {
+            Some(t) => t,
+            None => return self.include_unknown_modified,
+        };
+
+        if let Some(after) = self.modified_after {
+            if t <= after {
+                return false;
+            }
+        }
+        if let Some(before) = self.modified_before {
+            if t >= before {
+                return false;
+            }
+        }
+        true
+    }
 }
 
 /// Args for `scan` operation.
@@ -106,6 +458,64 @@ impl OpScan {
     }
 }
 
+/// Args for `walk` operation.
+#[derive(Debug, Clone)]
+pub struct OpWalk {
+    /// The max depth of entries to return.
+    max_depth: Option<usize>,
+    /// Return dir entries or not.
+    include_dirs: bool,
+}
+
+impl Default for OpWalk {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            include_dirs: true,
+        }
+    }
+}
+
+impl OpWalk {
+    /// Create a new `OpWalk`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only return entries whose depth is not larger than `max_depth`.
+    ///
+    /// Direct children of the walking path are at depth `1`, so walking
+    /// with `max_depth` of `1` is the same as `list`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_depth` is `0`.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        assert!(max_depth > 0, "max_depth must be greater than 0");
+
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Get max_depth from option.
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Set whether dir entries should be returned.
+    ///
+    /// Default to `true`.
+    pub fn with_include_dirs(mut self, include_dirs: bool) -> Self {
+        self.include_dirs = include_dirs;
+        self
+    }
+
+    /// Get include_dirs from option.
+    pub fn include_dirs(&self) -> bool {
+        self.include_dirs
+    }
+}
+
 /// Args for `presign` operation.
 ///
 /// The path must be normalized.
@@ -166,6 +576,162 @@ impl From<OpWrite> for PresignOperation {
     }
 }
 
+/// Args for `restore` operation.
+///
+/// The path must be normalized.
+#[derive(Debug, Clone)]
+pub struct OpRestore {
+    days: u32,
+    tier: Option<String>,
+}
+
+impl OpRestore {
+    /// Create a new `OpRestore`.
+    ///
+    /// `days` is the number of days that the restored copy will be kept.
+    pub fn new(days: u32) -> Self {
+        Self { days, tier: None }
+    }
+
+    /// Get days from option.
+    pub fn days(&self) -> u32 {
+        self.days
+    }
+
+    /// Get tier from option.
+    pub fn tier(&self) -> Option<&str> {
+        self.tier.as_deref()
+    }
+
+    /// Set the tier of restore.
+    ///
+    /// Tier decides how fast the path will be restored, for example, `s3`
+    /// supports `Expedited`, `Standard` and `Bulk`. Services' default tier
+    /// will be used if not set.
+    pub fn with_tier(mut self, tier: &str) -> Self {
+        self.tier = Some(tier.to_string());
+        self
+    }
+}
+
+/// Args for `abort_multipart` operation.
+///
+/// The path must be normalized.
+#[derive(Debug, Clone)]
+pub struct OpAbortMultipart {
+    upload_id: String,
+}
+
+impl OpAbortMultipart {
+    /// Create a new `OpAbortMultipart`.
+    ///
+    /// `upload_id` is the upload session returned by [`Writer::session`](crate::Writer::session).
+    pub fn new(upload_id: &str) -> Self {
+        Self {
+            upload_id: upload_id.to_string(),
+        }
+    }
+
+    /// Get upload id from option.
+    pub fn upload_id(&self) -> &str {
+        &self.upload_id
+    }
+}
+
+/// Args for `rename` operation.
+///
+/// The path must be normalized.
+#[derive(Debug, Clone, Default)]
+pub struct OpRename {}
+
+impl OpRename {
+    /// Create a new `OpRename`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Args for `copy` operation.
+///
+/// The path must be normalized.
+#[derive(Debug, Clone, Default)]
+pub struct OpCopy {}
+
+impl OpCopy {
+    /// Create a new `OpCopy`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Args for `get_acl` operation.
+///
+/// The path must be normalized.
+#[derive(Debug, Clone, Default)]
+pub struct OpGetAcl {}
+
+impl OpGetAcl {
+    /// Create a new `OpGetAcl`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Args for `set_acl` operation.
+///
+/// The path must be normalized.
+#[derive(Debug, Clone)]
+pub struct OpSetAcl {
+    acl: Acl,
+}
+
+impl OpSetAcl {
+    /// Create a new `OpSetAcl`.
+    pub fn new(acl: Acl) -> Self {
+        Self { acl }
+    }
+
+    /// Get acl from option.
+    pub fn acl(&self) -> &Acl {
+        &self.acl
+    }
+}
+
+/// Args for `get_tags` operation.
+///
+/// The path must be normalized.
+#[derive(Debug, Clone, Default)]
+pub struct OpGetTags {}
+
+impl OpGetTags {
+    /// Create a new `OpGetTags`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Args for `put_tags` operation.
+///
+/// The path must be normalized.
+#[derive(Debug, Clone)]
+pub struct OpPutTags {
+    tags: HashMap<String, String>,
+}
+
+impl OpPutTags {
+    /// Create a new `OpPutTags`.
+    ///
+    /// The given tags will replace all existing tags of the path.
+    pub fn new(tags: HashMap<String, String>) -> Self {
+        Self { tags }
+    }
+
+    /// Get tags from option.
+    pub fn tags(&self) -> &HashMap<String, String> {
+        &self.tags
+    }
+}
+
 /// Args for `batch` operation.
 #[derive(Debug, Clone)]
 pub struct OpBatch {
@@ -227,6 +793,7 @@ impl BatchOperations {
 #[derive(Debug, Clone, Default)]
 pub struct OpRead {
     br: BytesRange,
+    deadline: Option<Instant>,
 }
 
 impl OpRead {
@@ -235,6 +802,27 @@ impl OpRead {
         Self::default()
     }
 
+    /// Set the deadline of this operation.
+    ///
+    /// The deadline covers the operation and all IO on the returned reader.
+    /// Once it's reached, an error with [`ErrorKind::Unexpected`] will be
+    /// returned, which is not temporary so it will not be retried.
+    ///
+    /// - HTTP based services will set the remaining time as the timeout of
+    ///   every request sent, so requests in flight will be cancelled.
+    /// - Other services will only check the deadline before every call.
+    ///
+    /// Different calls on the same operator could carry different deadlines.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Get deadline from option.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
     /// Create a new OpRead with range.
     pub fn with_range(mut self, range: BytesRange) -> Self {
         self.br = range;
@@ -247,14 +835,206 @@ impl OpRead {
     }
 }
 
+/// Args for `read_into_file` operation.
+#[derive(Debug, Clone)]
+pub struct OpReadIntoFile {
+    buffer_size: usize,
+    resume: bool,
+}
+
+impl Default for OpReadIntoFile {
+    fn default() -> Self {
+        Self {
+            buffer_size: 256 * 1024,
+            resume: false,
+        }
+    }
+}
+
+impl OpReadIntoFile {
+    /// Create a new `OpReadIntoFile`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get buffer size from option.
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    /// Set the size of chunks that read from services and written into
+    /// local file.
+    ///
+    /// Default to `256 KiB`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer_size` is `0`.
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        assert!(buffer_size > 0, "buffer_size must be greater than 0");
+
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// Get resume from option.
+    pub fn resume(&self) -> bool {
+        self.resume
+    }
+
+    /// Set whether to resume from the partial file left by a previous
+    /// interrupted read.
+    ///
+    /// Default to `false`.
+    pub fn with_resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+}
+
+/// Args for `csv_reader` operation.
+#[cfg(feature = "csv")]
+#[derive(Debug, Clone)]
+pub struct OpReadCsv {
+    br: BytesRange,
+    delimiter: u8,
+    quote: u8,
+    escape: Option<u8>,
+    double_quote: bool,
+    comment: Option<u8>,
+}
+
+#[cfg(feature = "csv")]
+impl Default for OpReadCsv {
+    fn default() -> Self {
+        Self {
+            br: BytesRange::default(),
+            delimiter: b',',
+            quote: b'"',
+            escape: None,
+            double_quote: true,
+            comment: None,
+        }
+    }
+}
+
+#[cfg(feature = "csv")]
+impl OpReadCsv {
+    /// Create a new `OpReadCsv`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get range from option.
+    pub fn range(&self) -> BytesRange {
+        self.br
+    }
+
+    /// Set the byte range of records to read.
+    ///
+    /// Only records that start inside the range will be returned, and the
+    /// last record will be read completely even if it crosses the end of
+    /// range. So splitting an object into adjacent ranges returns every
+    /// record exactly once.
+    ///
+    /// Suffix range like `..1024` is not supported.
+    pub fn with_range(mut self, range: impl RangeBounds<u64>) -> Self {
+        self.br = range.into();
+        self
+    }
+
+    /// Set the field delimiter.
+    ///
+    /// Default to `b','`.
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Set the quote character.
+    ///
+    /// Default to `b'"'`.
+    pub fn with_quote(mut self, quote: u8) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Set the escape character for quotes, like `b'\\'`.
+    ///
+    /// Default to `None`, quotes are escaped by doubling them.
+    pub fn with_escape(mut self, escape: Option<u8>) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// Set whether two adjacent quotes in a quoted field are treated as
+    /// one escaped quote.
+    ///
+    /// Default to `true`.
+    pub fn with_double_quote(mut self, double_quote: bool) -> Self {
+        self.double_quote = double_quote;
+        self
+    }
+
+    /// Set the comment character, lines start with it will be ignored.
+    ///
+    /// Default to `None`.
+    pub fn with_comment(mut self, comment: Option<u8>) -> Self {
+        self.comment = comment;
+        self
+    }
+
+    /// Build the csv parser of this option.
+    pub(crate) fn build_parser(&self) -> csv_core::Reader {
+        csv_core::ReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .escape(self.escape)
+            .double_quote(self.double_quote)
+            .comment(self.comment)
+            .build()
+    }
+}
+
 /// Args for `stat` operation.
 #[derive(Debug, Clone, Default)]
-pub struct OpStat {}
+pub struct OpStat {
+    tags: bool,
+    deadline: Option<Instant>,
+}
 
 impl OpStat {
     /// Create a new `OpStat`.
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Set the deadline of this operation.
+    ///
+    /// See [`OpRead::with_deadline`] for details.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Get deadline from option.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Get tags from option
+    pub fn tags(&self) -> bool {
+        self.tags
+    }
+
+    /// Set tags of option.
+    ///
+    /// If enabled, tags of the path will be returned in [`Metadata::tags`].
+    /// Services like `s3` need an extra request to fetch them. Check
+    /// `stat_with_tags` of [`Capability`] before using it.
+    pub fn with_tags(mut self, tags: bool) -> Self {
+        self.tags = tags;
+        self
     }
 }
 
@@ -262,9 +1042,20 @@ impl OpStat {
 #[derive(Debug, Clone, Default)]
 pub struct OpWrite {
     append: bool,
+    append_existing: bool,
+    if_not_exists: bool,
+    if_match: Option<String>,
+    atomic: Option<bool>,
 
+    content_length: Option<u64>,
     content_type: Option<String>,
     content_disposition: Option<String>,
+    last_modified: Option<OffsetDateTime>,
+    replication: Option<usize>,
+    block_size: Option<usize>,
+    tags: HashMap<String, String>,
+    session: Option<String>,
+    deadline: Option<Instant>,
 }
 
 impl OpWrite {
@@ -274,12 +1065,37 @@ impl OpWrite {
     pub fn new() -> Self {
         Self {
             append: false,
+            append_existing: false,
+            if_not_exists: false,
+            if_match: None,
+            atomic: None,
 
+            content_length: None,
             content_type: None,
             content_disposition: None,
+            last_modified: None,
+            replication: None,
+            block_size: None,
+            tags: HashMap::new(),
+            session: None,
+            deadline: None,
         }
     }
 
+    /// Set the deadline of this operation.
+    ///
+    /// The deadline covers all IO on the returned writer, including
+    /// `close`. See [`OpRead::with_deadline`] for details.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Get deadline from option.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
     pub(crate) fn with_append(mut self) -> Self {
         self.append = true;
         self
@@ -289,6 +1105,21 @@ impl OpWrite {
         self.append
     }
 
+    /// Get the content length from option
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// Set the content length of option.
+    ///
+    /// Content length is a hint of the total size to write, layers like
+    /// [`ProgressLayer`](crate::layers::ProgressLayer) will use it as the
+    /// total. It will be filled automatically by [`Operator::write_with`](crate::Operator::write_with).
+    pub fn with_content_length(mut self, content_length: u64) -> Self {
+        self.content_length = Some(content_length);
+        self
+    }
+
     /// Get the content type from option
     pub fn content_type(&self) -> Option<&str> {
         self.content_type.as_deref()
@@ -305,9 +1136,177 @@ impl OpWrite {
         self.content_disposition.as_deref()
     }
 
+    /// Get if_not_exists from option
+    pub fn if_not_exists(&self) -> bool {
+        self.if_not_exists
+    }
+
+    /// Set if_not_exists of option.
+    ///
+    /// If enabled, the write will only succeed if the path doesn't exist,
+    /// or an error with [`ErrorKind::ConditionNotMatch`] will be returned.
+    /// Check `write_with_if_not_exists` of [`Capability`] before using it.
+    ///
+    /// # Atomicity
+    ///
+    /// The check happens at the moment the object becomes visible, so
+    /// concurrent writers can't overwrite each other:
+    ///
+    /// - `s3`: `If-None-Match: *` on `PutObject` and `CompleteMultipartUpload`.
+    /// - `gcs`: `ifGenerationMatch=0` on upload.
+    /// - `azblob`: `If-None-Match: *` on `Put Blob`.
+    /// - `fs`: hard link from the temp file (or `O_CREAT | O_EXCL` while atomic
+    ///   write is disabled), which requires the filesystem to support hard links.
+    ///
+    /// Other services will return [`ErrorKind::Unsupported`] instead of
+    /// falling back to a racy stat-then-write.
+    pub fn with_if_not_exists(mut self, if_not_exists: bool) -> Self {
+        self.if_not_exists = if_not_exists;
+        self
+    }
+
+    /// Get if_match from option
+    pub fn if_match(&self) -> Option<&str> {
+        self.if_match.as_deref()
+    }
+
+    /// Set if_match of option.
+    ///
+    /// If set, the write will only succeed if the current ETag of the path
+    /// matches the given one, or an error with [`ErrorKind::ConditionNotMatch`]
+    /// will be returned. Together with the ETag returned by `stat`, users can
+    /// build a compare-and-swap loop. Check `write_with_if_match` of
+    /// [`Capability`] before using it.
+    ///
+    /// # Services
+    ///
+    /// The check is enforced by services server-side:
+    ///
+    /// - `s3`: `If-Match` on `PutObject` and `CompleteMultipartUpload`. AWS S3
+    ///   and some s3 compatible services honor it, services that ignore it
+    ///   will overwrite silently.
+    /// - `azblob`: `If-Match` on `Put Blob`.
+    ///
+    /// Other services (including `gcs` which uses generation instead of
+    /// ETag) will return [`ErrorKind::Unsupported`].
+    pub fn with_if_match(mut self, etag: &str) -> Self {
+        self.if_match = Some(etag.to_string());
+        self
+    }
+
     /// Set the content disposition of option
     pub fn with_content_disposition(mut self, content_disposition: &str) -> Self {
         self.content_disposition = Some(content_disposition.to_string());
         self
     }
+
+    /// Get the last modified from option
+    pub fn last_modified(&self) -> Option<OffsetDateTime> {
+        self.last_modified
+    }
+
+    /// Set the last modified of option.
+    ///
+    /// The written file will carry this last modified instead of the time
+    /// it's written, which is useful for sync tools to replicate timestamps.
+    /// Check `write_with_last_modified` of [`Capability`] before using it.
+    pub fn with_last_modified(mut self, last_modified: OffsetDateTime) -> Self {
+        self.last_modified = Some(last_modified);
+        self
+    }
+
+    /// Get append_existing from option
+    pub fn append_existing(&self) -> bool {
+        self.append_existing
+    }
+
+    /// Set append_existing of option.
+    ///
+    /// If enabled, data will be appended to the end of the existing file
+    /// instead of overwriting it. The file will be created if not exist.
+    /// Check `write_with_append_existing` of [`Capability`] before using it.
+    pub fn with_append_existing(mut self, append_existing: bool) -> Self {
+        self.append_existing = append_existing;
+        self
+    }
+
+    /// Get atomic from option
+    pub fn atomic(&self) -> Option<bool> {
+        self.atomic
+    }
+
+    /// Set atomic of option.
+    ///
+    /// If enabled, data will be staged in a temp file and moved to the
+    /// target path while closing, so readers will never see a partial
+    /// written file. If disabled, data will be written into the target
+    /// path directly. Services will use their own default if not set.
+    ///
+    /// Only `fs` supports this option for now, other services will
+    /// ignore it.
+    pub fn with_atomic(mut self, atomic: bool) -> Self {
+        self.atomic = Some(atomic);
+        self
+    }
+
+    /// Get the replication factor from option
+    pub fn replication(&self) -> Option<usize> {
+        self.replication
+    }
+
+    /// Set the replication factor of option.
+    ///
+    /// Only `hdfs` supports this option for now, other services will
+    /// ignore it.
+    pub fn with_replication(mut self, replication: usize) -> Self {
+        self.replication = Some(replication);
+        self
+    }
+
+    /// Get the tags from option
+    pub fn tags(&self) -> &HashMap<String, String> {
+        &self.tags
+    }
+
+    /// Set the tags of option.
+    ///
+    /// Tags will be set on the path while writing. Services may have extra
+    /// limits on tags, for example, `s3` only allows up to 10 tags. Check
+    /// `write_with_tags` of [`Capability`] before using it.
+    pub fn with_tags(mut self, tags: HashMap<String, String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Get the block size from option
+    pub fn block_size(&self) -> Option<usize> {
+        self.block_size
+    }
+
+    /// Set the block size in bytes of option.
+    ///
+    /// Only `hdfs` supports this option for now, other services will
+    /// ignore it.
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = Some(block_size);
+        self
+    }
+
+    /// Get the upload session from option
+    pub fn session(&self) -> Option<&str> {
+        self.session.as_deref()
+    }
+
+    /// Set the upload session of option.
+    ///
+    /// Writer will resume the given upload session returned by a previous
+    /// writer instead of starting a new one, users should continue to
+    /// append bytes from the committed offset of the writer. Check
+    /// `write_with_session` of [`Capability`] before using it.
+    ///
+    /// Only `gcs` supports this option for now.
+    pub fn with_session(mut self, session: &str) -> Self {
+        self.session = Some(session.to_string());
+        self
+    }
 }
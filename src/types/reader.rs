@@ -12,18 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::min;
 use std::io;
+use std::mem;
 use std::pin::Pin;
 use std::task::Context;
 use std::task::Poll;
 
+use bytes::Buf;
 use bytes::Bytes;
+use bytes::BytesMut;
 use futures::ready;
+use futures::AsyncBufRead;
 use futures::AsyncRead;
 use futures::AsyncSeek;
 use futures::Stream;
 
 use crate::ops::OpRead;
+use crate::ops::OpStat;
 use crate::raw::*;
 use crate::*;
 
@@ -35,9 +41,14 @@ use crate::*;
 /// Reader implements the following APIs:
 ///
 /// - `AsyncRead`
+/// - `AsyncBufRead`
 /// - `AsyncSeek`
 /// - `Stream<Item = <io::Result<Bytes>>>`
 ///
+/// All of them are implemented for both `futures` and `tokio`, so no
+/// adapter is needed: `Reader` could be passed to `futures::io::copy`,
+/// `tokio::io::copy` and friends directly.
+///
 /// For reading data, we can use `AsyncRead` and `Stream`. The mainly
 /// different is where the `copy` happens.
 ///
@@ -51,9 +62,25 @@ use crate::*;
 ///
 /// Besides, `Stream` **COULD** reduce an extra copy if underlying reader is
 /// stream based (like services s3, azure which based on HTTP).
+///
+/// `AsyncBufRead` exposes the `Bytes` chunk returned by underlying reader
+/// directly, so wrapping `Reader` with another `BufReader` is not needed.
+///
+/// # Offset
+///
+/// Reader keeps track of the absolute offset within the object it has
+/// read so far. Users can build a resumable download via [`Reader::offset`]
+/// and [`Operator::reader_from`].
 pub struct Reader {
     inner: oio::Reader,
     seek_state: SeekState,
+    /// The chunk filled by `poll_fill_buf` but not consumed yet.
+    chunk: Bytes,
+
+    /// The absolute offset of the start of this reader in the object.
+    start: u64,
+    /// The position relative to `start`.
+    pos: u64,
 }
 
 impl Reader {
@@ -65,26 +92,229 @@ impl Reader {
     /// We don't want to expose those details to users so keep this function
     /// in crate only.
     pub(crate) async fn create(acc: FusedAccessor, path: &str, op: OpRead) -> Result<Self> {
-        let (_, r) = acc.read(path, op).await?;
+        let range = op.range();
+        let (rp, r) = acc.read(path, op).await?;
+
+        let start = match (range.offset(), range.size()) {
+            (Some(offset), _) => offset,
+            (None, None) => 0,
+            // Suffix range: try to resolve the start via returning content
+            // range first, and fallback to stat if it's not available.
+            (None, Some(size)) => match rp.metadata().content_range().and_then(|v| v.range()) {
+                Some(v) => v.start,
+                None => {
+                    let total = acc
+                        .stat(path, OpStat::new())
+                        .await?
+                        .into_metadata()
+                        .content_length();
+                    total.saturating_sub(size)
+                }
+            },
+        };
 
-        Ok(Reader {
-            inner: r,
+        Ok(Reader::new(r, start))
+    }
+
+    /// Create a new reader from an [`oio::Reader`] which starts at `start`
+    /// of the object.
+    pub(crate) fn new(inner: oio::Reader, start: u64) -> Self {
+        Reader {
+            inner,
             seek_state: SeekState::Init,
-        })
+            chunk: Bytes::new(),
+            start,
+            pos: 0,
+        }
+    }
+
+    /// Create a new reader that only yields the first `size` bytes.
+    ///
+    /// Inner reader will be dropped after `size` bytes have been read.
+    pub(crate) async fn create_sliced(
+        acc: FusedAccessor,
+        path: &str,
+        op: OpRead,
+        size: u64,
+    ) -> Result<Self> {
+        let mut r = Self::create(acc, path, op).await?;
+        r.inner = Box::new(oio::into_sliced_reader(r.inner, size));
+        Ok(r)
+    }
+
+    /// Create a new reader which reads by at most `concurrent` chunks of
+    /// `chunk_size` at the same time.
+    ///
+    /// Fallback to [`Reader::create`] if the service can't read with range
+    /// or the range is not larger than `chunk_size`.
+    pub(crate) async fn create_concurrent(
+        acc: FusedAccessor,
+        path: &str,
+        op: OpRead,
+        chunk_size: u64,
+        concurrent: usize,
+    ) -> Result<Self> {
+        if !acc.info().capability().read_with_range {
+            return Self::create(acc, path, op).await;
+        }
+
+        let total = acc
+            .stat(path, OpStat::new())
+            .await?
+            .into_metadata()
+            .content_length();
+
+        let range = op.range();
+        let (offset, size) = match (range.offset(), range.size()) {
+            (Some(offset), Some(size)) => (offset, size.min(total.saturating_sub(offset))),
+            (Some(offset), None) => (offset, total.saturating_sub(offset)),
+            (None, Some(size)) => (total.saturating_sub(size), size.min(total)),
+            (None, None) => (0, total),
+        };
+
+        if size <= chunk_size {
+            return Self::create(acc, path, op).await;
+        }
+
+        let r = oio::into_concurrent_reader(acc, path, op, offset, size, chunk_size, concurrent);
+        Ok(Reader::new(Box::new(r), offset))
+    }
+
+    /// Get the current absolute offset of this reader in the object.
+    ///
+    /// The offset includes the start of the range this reader created
+    /// with. For example, after reading `10` bytes from a reader created
+    /// by `op.range_reader(path, 1024..)`, the offset will be `1034`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use futures::AsyncReadExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let mut r = op.reader_from("path/to/file", 1024).await?;
+    /// let mut buf = vec![0; 10];
+    /// r.read_exact(&mut buf).await?;
+    /// assert_eq!(r.offset(), 1034);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn offset(&self) -> u64 {
+        self.start + self.pos
+    }
+
+    /// Convert this reader into a stream of [`Bytes`] in `chunk_size`.
+    ///
+    /// All chunks will be exactly `chunk_size` except the last one. The
+    /// stream ends cleanly at EOF, and errors happened in the middle
+    /// will be returned as an item without panic.
+    ///
+    /// This is useful for HTTP frameworks (like `axum` and `hyper`) which
+    /// require `Stream<Item = Result<Bytes, E>>` as body.
+    ///
+    /// # Panics
+    ///
+    /// `chunk_size` must be larger than 0.
+    ///
+    /// # Examples
+    ///
+    /// Serve a file via hyper:
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use hyper::Body;
+    /// use hyper::Response;
+    ///
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let r = op.reader("path/to/file").await?;
+    /// let resp = Response::new(Body::wrap_stream(r.into_stream(64 * 1024)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_stream(self, chunk_size: usize) -> ReaderStream {
+        assert!(chunk_size > 0, "chunk size must be larger than 0");
+
+        ReaderStream {
+            inner: self,
+            chunk_size,
+            buf: BytesMut::new(),
+            done: false,
+        }
+    }
+
+    /// Fill the internal chunk from underlying reader if it's empty.
+    ///
+    /// Return `false` if the reader has been drained.
+    pub(super) fn poll_fill_chunk(&mut self, cx: &mut Context<'_>) -> Poll<Result<bool>> {
+        while self.chunk.is_empty() {
+            match ready!(self.inner.poll_next(cx)) {
+                Some(Ok(bs)) => self.chunk = bs,
+                Some(Err(err)) => return Poll::Ready(Err(err)),
+                None => return Poll::Ready(Ok(false)),
+            }
+        }
+
+        Poll::Ready(Ok(true))
+    }
+
+    /// The chunk filled by `poll_fill_chunk` but not consumed yet.
+    #[cfg(feature = "csv")]
+    pub(super) fn chunk(&self) -> &[u8] {
+        &self.chunk
+    }
+
+    pub(super) fn consume_chunk(&mut self, amt: usize) {
+        let amt = min(amt, self.chunk.len());
+        self.chunk.advance(amt);
+        self.pos += amt as u64;
     }
 }
 
 impl oio::Read for Reader {
     fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
-        self.inner.poll_read(cx, buf)
+        // Drain the chunk filled by `poll_fill_buf` first.
+        if !self.chunk.is_empty() {
+            let n = min(self.chunk.len(), buf.len());
+            buf[..n].copy_from_slice(&self.chunk[..n]);
+            self.consume_chunk(n);
+            return Poll::Ready(Ok(n));
+        }
+
+        let n = ready!(self.inner.poll_read(cx, buf))?;
+        self.pos += n as u64;
+        Poll::Ready(Ok(n))
     }
 
     fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
-        self.inner.poll_seek(cx, pos)
+        // Underlying reader has been advanced by the remaining chunk,
+        // so we need to take it into account for relative seeking.
+        let pos = match pos {
+            io::SeekFrom::Current(n) => io::SeekFrom::Current(n - self.chunk.len() as i64),
+            pos => pos,
+        };
+
+        let n = ready!(self.inner.poll_seek(cx, pos))?;
+        self.chunk.clear();
+        self.pos = n;
+        Poll::Ready(Ok(n))
     }
 
     fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
-        self.inner.poll_next(cx)
+        if !self.chunk.is_empty() {
+            let bs = mem::take(&mut self.chunk);
+            self.pos += bs.len() as u64;
+            return Poll::Ready(Some(Ok(bs)));
+        }
+
+        let bs = ready!(self.inner.poll_next(cx));
+        if let Some(Ok(bs)) = &bs {
+            self.pos += bs.len() as u64;
+        }
+        Poll::Ready(bs)
     }
 }
 
@@ -94,7 +324,20 @@ impl AsyncRead for Reader {
         cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
-        Pin::new(&mut self.inner).poll_read(cx, buf)
+        let n = ready!(oio::Read::poll_read(&mut *self, cx, buf))?;
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl AsyncBufRead for Reader {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+        ready!(this.poll_fill_chunk(cx))?;
+        Poll::Ready(Ok(&this.chunk[..]))
+    }
+
+    fn consume(mut self: Pin<&mut Self>, amt: usize) {
+        self.consume_chunk(amt)
     }
 }
 
@@ -104,7 +347,8 @@ impl AsyncSeek for Reader {
         cx: &mut Context<'_>,
         pos: io::SeekFrom,
     ) -> Poll<io::Result<u64>> {
-        Pin::new(&mut self.inner).poll_seek(cx, pos)
+        let n = ready!(oio::Read::poll_seek(&mut *self, cx, pos))?;
+        Poll::Ready(Ok(n))
     }
 }
 
@@ -115,7 +359,7 @@ impl tokio::io::AsyncRead for Reader {
         buf: &mut tokio::io::ReadBuf<'_>,
     ) -> Poll<io::Result<()>> {
         let b = buf.initialize_unfilled();
-        let n = ready!(self.inner.poll_read(cx, b))?;
+        let n = ready!(oio::Read::poll_read(&mut *self, cx, b))?;
         unsafe {
             buf.assume_init(n);
         }
@@ -124,6 +368,18 @@ impl tokio::io::AsyncRead for Reader {
     }
 }
 
+impl tokio::io::AsyncBufRead for Reader {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+        ready!(this.poll_fill_chunk(cx))?;
+        Poll::Ready(Ok(&this.chunk[..]))
+    }
+
+    fn consume(mut self: Pin<&mut Self>, amt: usize) {
+        self.consume_chunk(amt)
+    }
+}
+
 impl tokio::io::AsyncSeek for Reader {
     fn start_seek(self: Pin<&mut Self>, pos: io::SeekFrom) -> io::Result<()> {
         let this = self.get_mut();
@@ -148,7 +404,8 @@ impl tokio::io::AsyncSeek for Reader {
                 Poll::Ready(Ok(0))
             }
             SeekState::Start(pos) => {
-                let n = ready!(self.inner.poll_seek(cx, pos))?;
+                let n = ready!(oio::Read::poll_seek(&mut *self, cx, pos))?;
+                self.seek_state = SeekState::Init;
                 Poll::Ready(Ok(n))
             }
         }
@@ -168,12 +425,56 @@ impl Stream for Reader {
     type Item = io::Result<Bytes>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        Pin::new(&mut self.inner)
-            .poll_next(cx)
+        oio::Read::poll_next(&mut *self, cx)
             .map_err(|err| io::Error::new(io::ErrorKind::Interrupted, err))
     }
 }
 
+/// ReaderStream is a stream of [`Bytes`] in fixed chunk size.
+///
+/// Created by [`Reader::into_stream`].
+pub struct ReaderStream {
+    inner: Reader,
+    chunk_size: usize,
+    buf: BytesMut,
+    done: bool,
+}
+
+impl Stream for ReaderStream {
+    type Item = Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.buf.len() >= this.chunk_size {
+                return Poll::Ready(Some(Ok(this.buf.split_to(this.chunk_size).freeze())));
+            }
+            if this.done {
+                if this.buf.is_empty() {
+                    return Poll::Ready(None);
+                }
+                return Poll::Ready(Some(Ok(this.buf.split().freeze())));
+            }
+
+            match ready!(oio::Read::poll_next(&mut this.inner, cx)) {
+                // Return the chunk directly to avoid extra copy.
+                Some(Ok(bs)) if this.buf.is_empty() && bs.len() == this.chunk_size => {
+                    return Poll::Ready(Some(Ok(bs)));
+                }
+                Some(Ok(bs)) => this.buf.extend_from_slice(&bs),
+                Some(Err(err)) => {
+                    // Stream will be ended after error.
+                    this.done = true;
+                    this.buf.clear();
+                    return Poll::Ready(Some(Err(err)));
+                }
+                None => this.done = true,
+            }
+        }
+    }
+}
+
 /// BlockingReader is designed to read data from given path in an blocking
 /// manner.
 pub struct BlockingReader {
@@ -210,6 +511,21 @@ impl BlockingReader {
 
         Ok(BlockingReader { inner: r })
     }
+
+    /// Create a new blocking reader that only yields the first `size` bytes.
+    ///
+    /// Inner reader will be dropped after `size` bytes have been read.
+    pub(crate) fn create_sliced(
+        acc: FusedAccessor,
+        path: &str,
+        op: OpRead,
+        size: u64,
+    ) -> Result<Self> {
+        let r = Self::create(acc, path, op)?;
+        Ok(BlockingReader {
+            inner: Box::new(oio::into_sliced_reader(r.inner, size)),
+        })
+    }
 }
 
 impl oio::BlockingRead for BlockingReader {
@@ -256,9 +572,12 @@ impl Iterator for BlockingReader {
 
 #[cfg(test)]
 mod tests {
+    use futures::StreamExt;
+    use futures::TryStreamExt;
     use rand::rngs::ThreadRng;
     use rand::Rng;
     use rand::RngCore;
+    use tokio::io::AsyncBufReadExt;
     use tokio::io::AsyncReadExt;
     use tokio::io::AsyncSeekExt;
 
@@ -322,4 +641,146 @@ mod tests {
             .expect("read to end must succeed");
         assert_eq!(buf, content);
     }
+
+    #[tokio::test]
+    async fn test_reader_offset() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let path = "test_file";
+
+        let content = gen_random_bytes();
+        op.write(path, content.clone())
+            .await
+            .expect("write must succeed");
+
+        let offset = content.len() as u64 / 2;
+        let mut reader = op.reader_from(path, offset).await.unwrap();
+        assert_eq!(reader.offset(), offset);
+
+        let mut buf = vec![0; 1];
+        reader
+            .read_exact(&mut buf)
+            .await
+            .expect("read exact must succeed");
+        assert_eq!(reader.offset(), offset + 1);
+
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("read to end must succeed");
+        assert_eq!(buf, content[offset as usize + 1..]);
+        assert_eq!(reader.offset(), content.len() as u64);
+
+        reader.seek(tokio::io::SeekFrom::Start(0)).await.unwrap();
+        assert_eq!(reader.offset(), offset);
+    }
+
+    #[tokio::test]
+    async fn test_reader_tokio_copy() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let path = "test_file";
+
+        let content = gen_random_bytes();
+        op.write(path, content.clone())
+            .await
+            .expect("write must succeed");
+
+        let mut reader = op.reader(path).await.unwrap();
+        let mut buf = Vec::new();
+        let n = tokio::io::copy(&mut reader, &mut buf)
+            .await
+            .expect("copy must succeed");
+        assert_eq!(n, content.len() as u64);
+        assert_eq!(buf, content);
+
+        let mut reader = op.reader(path).await.unwrap();
+        let mut buf = Vec::new();
+        let n = tokio::io::copy_buf(&mut reader, &mut buf)
+            .await
+            .expect("copy buf must succeed");
+        assert_eq!(n, content.len() as u64);
+        assert_eq!(buf, content);
+        assert_eq!(reader.offset(), content.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_reader_buf_read_lines() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let path = "test_file";
+
+        let content = "Hello\nWorld\n\nOpenDAL";
+        op.write(path, content).await.expect("write must succeed");
+
+        let reader = op.reader(path).await.unwrap();
+        let mut lines = reader.lines();
+        let mut actual = Vec::new();
+        while let Some(line) = lines.next_line().await.expect("next line must succeed") {
+            actual.push(line);
+        }
+        assert_eq!(actual, vec!["Hello", "World", "", "OpenDAL"]);
+    }
+
+    #[tokio::test]
+    async fn test_reader_buf_read_mixed() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let path = "test_file";
+
+        let content = gen_random_bytes();
+        op.write(path, content.clone())
+            .await
+            .expect("write must succeed");
+
+        let mut reader = op.reader(path).await.unwrap();
+        let chunk = reader.fill_buf().await.expect("fill buf must succeed");
+        let amt = chunk.len().min(1);
+        assert_eq!(chunk[..amt], content[..amt]);
+        reader.consume(amt);
+        assert_eq!(reader.offset(), amt as u64);
+
+        // Read after fill buf must continue with the buffered chunk.
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("read to end must succeed");
+        assert_eq!(buf, content[amt..]);
+
+        reader.seek(tokio::io::SeekFrom::Start(0)).await.unwrap();
+        reader.fill_buf().await.expect("fill buf must succeed");
+        let n = reader.seek(tokio::io::SeekFrom::Current(1)).await.unwrap();
+        assert_eq!(n, 1);
+
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("read to end must succeed");
+        assert_eq!(buf, content[1..]);
+    }
+
+    #[tokio::test]
+    async fn test_reader_into_stream() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let path = "test_file";
+
+        let content = gen_random_bytes();
+        op.write(path, content.clone())
+            .await
+            .expect("write must succeed");
+
+        let chunk_size = 64 * 1024;
+        let mut s = op.reader(path).await.unwrap().into_stream(chunk_size);
+        let mut buf = Vec::new();
+        let mut last = None;
+        while let Some(bs) = s.try_next().await.expect("next must succeed") {
+            // All chunks except the last one must be in chunk size.
+            if let Some(size) = last {
+                assert_eq!(size, chunk_size);
+            }
+            last = Some(bs.len());
+            buf.extend_from_slice(&bs);
+        }
+        assert_eq!(buf, content);
+        assert!(s.next().await.is_none(), "stream must be ended");
+    }
 }
@@ -32,13 +32,29 @@ pub enum Scheme {
     Azblob,
     /// [azdfs][crate::services::Azdfs]: Azure Data Lake Storage Gen2.
     Azdfs,
+    /// [b2][crate::services::B2]: Backblaze B2 services.
+    #[cfg(feature = "services-b2")]
+    B2,
+    /// [cachefs][crate::services::CacheFs]: Local file system with size budget.
+    CacheFs,
+    /// [cos][crate::services::Cos]: Tencent Cloud COS services.
+    Cos,
     /// [dashmap][crate::services::Dashmap]: dashmap backend support.
     #[cfg(feature = "services-dashmap")]
     Dashmap,
+    /// [dropbox][crate::services::Dropbox]: Dropbox services.
+    #[cfg(feature = "services-dropbox")]
+    Dropbox,
+    /// [etcd][crate::services::Etcd]: Etcd services.
+    #[cfg(feature = "services-etcd")]
+    Etcd,
     /// [fs][crate::services::Fs]: POSIX alike file system.
     Fs,
     /// [gcs][crate::services::Gcs]: Google Cloud Storage backend.
     Gcs,
+    /// [gdrive][crate::services::Gdrive]: Google Drive services.
+    #[cfg(feature = "services-gdrive")]
+    Gdrive,
     /// [ghac][crate::services::Ghac]: Github Action Cache services.
     Ghac,
     /// [hdfs][crate::services::Hdfs]: Hadoop Distributed File System.
@@ -64,6 +80,9 @@ pub enum Scheme {
     Moka,
     /// [obs][crate::services::Obs]: Huawei Cloud OBS services.
     Obs,
+    /// [onedrive][crate::services::Onedrive]: Microsoft OneDrive services.
+    #[cfg(feature = "services-onedrive")]
+    Onedrive,
     /// [oss][crate::services::Oss]: Aliyun Object Storage Services
     Oss,
     /// [redis][crate::services::Redis]: Redis services
@@ -74,9 +93,15 @@ pub enum Scheme {
     Rocksdb,
     /// [s3][crate::services::S3]: AWS S3 alike services.
     S3,
+    /// [sftp][crate::services::Sftp]: SFTP services
+    #[cfg(feature = "services-sftp")]
+    Sftp,
     /// [sled][crate::services::Sled]: Sled services
     #[cfg(feature = "services-sled")]
     Sled,
+    /// [supabase][crate::services::Supabase]: Supabase Storage services
+    #[cfg(feature = "services-supabase")]
+    Supabase,
     /// [webdav][crate::services::Webdav]: WebDAV support.
     Webdav,
     /// [webhdfs][crate::services::Webhdfs]: WebHDFS RESTful API Services
@@ -117,10 +142,20 @@ impl FromStr for Scheme {
         match s.as_str() {
             "azblob" => Ok(Scheme::Azblob),
             "azdfs" => Ok(Scheme::Azdfs),
+            #[cfg(feature = "services-b2")]
+            "b2" => Ok(Scheme::B2),
+            "cachefs" => Ok(Scheme::CacheFs),
+            "cos" => Ok(Scheme::Cos),
             #[cfg(feature = "services-dashmap")]
             "dashmap" => Ok(Scheme::Dashmap),
+            #[cfg(feature = "services-dropbox")]
+            "dropbox" => Ok(Scheme::Dropbox),
+            #[cfg(feature = "services-etcd")]
+            "etcd" => Ok(Scheme::Etcd),
             "fs" => Ok(Scheme::Fs),
             "gcs" => Ok(Scheme::Gcs),
+            #[cfg(feature = "services-gdrive")]
+            "gdrive" => Ok(Scheme::Gdrive),
             "ghac" => Ok(Scheme::Ghac),
             #[cfg(feature = "services-hdfs")]
             "hdfs" => Ok(Scheme::Hdfs),
@@ -136,13 +171,19 @@ impl FromStr for Scheme {
             #[cfg(feature = "services-moka")]
             "moka" => Ok(Scheme::Moka),
             "obs" => Ok(Scheme::Obs),
+            #[cfg(feature = "services-onedrive")]
+            "onedrive" => Ok(Scheme::Onedrive),
             #[cfg(feature = "services-redis")]
             "redis" => Ok(Scheme::Redis),
             #[cfg(feature = "services-rocksdb")]
             "rocksdb" => Ok(Scheme::Rocksdb),
             "s3" => Ok(Scheme::S3),
+            #[cfg(feature = "services-sftp")]
+            "sftp" => Ok(Scheme::Sftp),
             #[cfg(feature = "services-sled")]
             "sled" => Ok(Scheme::Sled),
+            #[cfg(feature = "services-supabase")]
+            "supabase" => Ok(Scheme::Supabase),
             "oss" => Ok(Scheme::Oss),
             "webdav" => Ok(Scheme::Webdav),
             "webhdfs" => Ok(Scheme::Webhdfs),
@@ -156,10 +197,20 @@ impl From<Scheme> for &'static str {
         match v {
             Scheme::Azblob => "azblob",
             Scheme::Azdfs => "azdfs",
+            #[cfg(feature = "services-b2")]
+            Scheme::B2 => "b2",
+            Scheme::CacheFs => "cachefs",
+            Scheme::Cos => "cos",
             #[cfg(feature = "services-dashmap")]
             Scheme::Dashmap => "dashmap",
+            #[cfg(feature = "services-dropbox")]
+            Scheme::Dropbox => "dropbox",
+            #[cfg(feature = "services-etcd")]
+            Scheme::Etcd => "etcd",
             Scheme::Fs => "fs",
             Scheme::Gcs => "gcs",
+            #[cfg(feature = "services-gdrive")]
+            Scheme::Gdrive => "gdrive",
             Scheme::Ghac => "ghac",
             #[cfg(feature = "services-hdfs")]
             Scheme::Hdfs => "hdfs",
@@ -175,13 +226,19 @@ impl From<Scheme> for &'static str {
             #[cfg(feature = "services-moka")]
             Scheme::Moka => "moka",
             Scheme::Obs => "obs",
+            #[cfg(feature = "services-onedrive")]
+            Scheme::Onedrive => "onedrive",
             #[cfg(feature = "services-redis")]
             Scheme::Redis => "redis",
             #[cfg(feature = "services-rocksdb")]
             Scheme::Rocksdb => "rocksdb",
             Scheme::S3 => "s3",
+            #[cfg(feature = "services-sftp")]
+            Scheme::Sftp => "sftp",
             #[cfg(feature = "services-sled")]
             Scheme::Sled => "sled",
+            #[cfg(feature = "services-supabase")]
+            Scheme::Supabase => "supabase",
             Scheme::Oss => "oss",
             Scheme::Webdav => "webdav",
             Scheme::Webhdfs => "webhdfs",
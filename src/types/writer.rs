@@ -19,10 +19,12 @@ use std::task::Context;
 use std::task::Poll;
 
 use bytes::Bytes;
+use futures::future;
 use futures::future::BoxFuture;
 use futures::ready;
 use futures::AsyncWrite;
 use futures::FutureExt;
+use futures::Sink;
 
 use crate::ops::OpWrite;
 use crate::raw::*;
@@ -31,12 +33,43 @@ use crate::*;
 /// Writer is designed to write data into given path in an asynchronous
 /// manner.
 ///
+/// # Usage
+///
+/// Writer implements `AsyncWrite` for both `futures` and `tokio`, so it
+/// could be used with `futures::io::copy` and `tokio::io::copy` directly.
+/// Please remember to call `close` (or `shutdown` for tokio) to make sure
+/// all data have been stored.
+///
+/// # Abort
+///
+/// Uploads that not closed could leave uploaded parts on services, which
+/// are still billed (like s3 multipart uploads).
+///
+/// - Call [`Writer::abort`] to discard the upload explicitly.
+/// - Writer dropped without `close` or `abort` will abort the upload on
+///   following services:
+///   - `s3`, `obs` and `cos`: the multipart upload is aborted in a background
+///     task. It's best-effort only: the abort will not happen if there is no
+///     tokio runtime, the process exits or the request fails.
+///   - `gcs`: the resumable upload session is cancelled in the same way,
+///     unless it's resumed via [`OpWrite::with_session`], which will be kept
+///     so that it could be resumed again later.
+///   - `fs` and `hdfs`: the temporary file of atomic write is removed.
+/// - `azblob` can't abort uploads, uncommitted blocks will be garbage
+///   collected by the service after a week.
+/// - Other services don't abort uploads while dropping.
+/// - Save [`Writer::session`] and use [`Operator::abort_multipart`] to abort
+///   the upload even if the writer is lost.
+///
 /// # Notes
 ///
 /// Writer is designed for appending multiple blocks which could
 /// lead to much requests. If only want to send all data in single chunk,
 /// please use [`Operator::write`] instead.
 pub struct Writer {
+    rp: RpWrite,
+    /// The reply returned by service while closing.
+    closed: Option<RpWrite>,
     state: State,
 }
 
@@ -49,19 +82,61 @@ impl Writer {
     /// We don't want to expose those details to users so keep this function
     /// in crate only.
     pub(crate) async fn create(acc: FusedAccessor, path: &str, op: OpWrite) -> Result<Self> {
-        let (_, w) = acc.write(path, op).await?;
+        let (rp, w) = acc.write(path, op).await?;
 
         Ok(Writer {
+            rp,
+            closed: None,
             state: State::Idle(Some(w)),
         })
     }
 
+    /// Get the upload session of this writer.
+    ///
+    /// Services that support `write_with_session` will return the session
+    /// that could be saved and passed to [`OpWrite::with_session`] to resume
+    /// this write later, for example after the process restarts.
+    ///
+    /// Services that support `abort_multipart` will return the upload id,
+    /// which could be passed to [`Operator::abort_multipart`] to abort this
+    /// write.
+    pub fn session(&self) -> Option<&str> {
+        self.rp.session()
+    }
+
+    /// Get the size of bytes that have been committed when this writer is
+    /// created.
+    ///
+    /// It's always `0` for new writes. For writers resuming a session,
+    /// users should continue appending bytes from this offset.
+    pub fn offset(&self) -> u64 {
+        self.rp.offset()
+    }
+
+    /// Get the etag of the written object.
+    ///
+    /// It's only available after the writer has been closed, and will be
+    /// `None` if services don't return it while writing.
+    pub fn etag(&self) -> Option<&str> {
+        self.closed.as_ref().and_then(|rp| rp.etag())
+    }
+
+    /// Get the version id of the written object.
+    ///
+    /// It's only available after the writer has been closed, and will be
+    /// `None` if services don't enable versioning.
+    pub fn version(&self) -> Option<&str> {
+        self.closed.as_ref().and_then(|rp| rp.version())
+    }
+
     /// Append data into writer.
     ///
     /// It is highly recommended to align the length of the input bytes
     /// into blocks of 4MiB (except the last block) for better performance
     /// and compatibility.
     pub async fn append(&mut self, bs: impl Into<Bytes>) -> Result<()> {
+        future::poll_fn(|cx| self.poll_flush_pending(cx)).await?;
+
         if let State::Idle(Some(w)) = &mut self.state {
             w.append(bs.into()).await
         } else {
@@ -72,10 +147,40 @@ impl Writer {
         }
     }
 
+    /// Convert this writer into a [`Sink`] of [`Bytes`].
+    ///
+    /// Every item sent to the sink will be appended into the underlying
+    /// writer. The sink will only be ready for the next item after the
+    /// previous one has been appended, and `poll_close` will close the
+    /// underlying writer with errors returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use bytes::Bytes;
+    /// use futures::SinkExt;
+    ///
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let mut s = op.writer("path/to/file").await?.into_sink();
+    /// s.send(Bytes::from(vec![0; 4096])).await?;
+    /// s.send(Bytes::from(vec![1; 4096])).await?;
+    /// s.close().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_sink(self) -> WriterSink {
+        WriterSink { state: self.state }
+    }
+
     /// Close the writer and make sure all data have been stored.
     pub async fn close(&mut self) -> Result<()> {
+        future::poll_fn(|cx| self.poll_flush_pending(cx)).await?;
+
         if let State::Idle(Some(w)) = &mut self.state {
-            w.close().await
+            self.closed = Some(w.close().await?);
+            Ok(())
         } else {
             unreachable!(
                 "writer state invalid while close, expect Idle, actual {}",
@@ -83,12 +188,32 @@ impl Writer {
             );
         }
     }
+
+    /// Abort the writer and discard all data appended.
+    ///
+    /// The target file will not be created or updated after abort, and
+    /// uploaded parts will be freed (for example, s3 `AbortMultipartUpload`
+    /// will be called).
+    pub async fn abort(&mut self) -> Result<()> {
+        // Errors of the in flight append don't matter since we are aborting.
+        let _ = future::poll_fn(|cx| self.poll_flush_pending(cx)).await;
+
+        match &mut self.state {
+            State::Idle(Some(w)) => w.abort().await,
+            // The underlying writer has been dropped after previous error.
+            State::Idle(None) => Err(Self::new_unavailable_error()),
+            _ => unreachable!(
+                "writer state invalid while abort, expect Idle, actual {}",
+                self.state
+            ),
+        }
+    }
 }
 
 enum State {
     Idle(Option<oio::Writer>),
     Write(BoxFuture<'static, Result<(usize, oio::Writer)>>),
-    Close(BoxFuture<'static, Result<oio::Writer>>),
+    Close(BoxFuture<'static, Result<(RpWrite, oio::Writer)>>),
 }
 
 impl Display for State {
@@ -101,33 +226,73 @@ impl Display for State {
     }
 }
 
+impl Writer {
+    fn new_unavailable_error() -> Error {
+        Error::new(
+            ErrorKind::Unexpected,
+            "writer is not available after previous error",
+        )
+        .with_operation("Writer")
+    }
+
+    /// Wait for the in flight append started by [`AsyncWrite::poll_write`].
+    fn poll_flush_pending(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match &mut self.state {
+            State::Idle(Some(_)) => Poll::Ready(Ok(())),
+            State::Idle(None) => Poll::Ready(Err(Self::new_unavailable_error())),
+            State::Write(fut) => match ready!(fut.poll_unpin(cx)) {
+                Ok((_, w)) => {
+                    self.state = State::Idle(Some(w));
+                    Poll::Ready(Ok(()))
+                }
+                Err(err) => {
+                    self.state = State::Idle(None);
+                    Poll::Ready(Err(err))
+                }
+            },
+            State::Close(_) => {
+                unreachable!("invalid state of writer: flush with State::Close")
+            }
+        }
+    }
+}
+
+/// Writer implements [`AsyncWrite`] for interop with `futures::io::copy`
+/// and friends.
+///
+/// - `poll_write` will start appending the given buffer and return
+///   immediately. At most one append will be in flight, so the next
+///   `poll_write` will wait for the previous one to finish.
+/// - Errors of the in flight append will be returned by the next
+///   `poll_write`, `poll_flush` or `poll_close`.
+/// - `poll_flush` waits for the in flight append.
+/// - `poll_close` waits for the in flight append and closes the writer.
 impl AsyncWrite for Writer {
     fn poll_write(
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
         loop {
             match &mut self.state {
                 State::Idle(w) => {
                     let mut w = w
                         .take()
-                        .expect("invalid state of writer: Idle state with empty write");
-                    let bs = Bytes::from(buf.to_vec());
+                        .ok_or_else(|| io::Error::from(Self::new_unavailable_error()))?;
+                    let bs = Bytes::copy_from_slice(buf);
                     let size = bs.len();
                     let fut = async move {
                         w.append(bs).await?;
                         Ok((size, w))
                     };
                     self.state = State::Write(Box::pin(fut));
+                    return Poll::Ready(Ok(size));
                 }
-                State::Write(fut) => match ready!(fut.poll_unpin(cx)) {
-                    Ok((size, w)) => {
-                        self.state = State::Idle(Some(w));
-                        return Poll::Ready(Ok(size));
-                    }
-                    Err(err) => return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, err))),
-                },
+                State::Write(_) => ready!(self.as_mut().poll_flush(cx))?,
                 State::Close(_) => {
                     unreachable!("invalid state of writer: poll_write with State::Close")
                 }
@@ -135,9 +300,9 @@ impl AsyncWrite for Writer {
         }
     }
 
-    /// Writer makes sure that every write is flushed.
-    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
-        Poll::Ready(Ok(()))
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let res = ready!(self.poll_flush_pending(cx));
+        Poll::Ready(res.map_err(io::Error::from))
     }
 
     fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
@@ -146,32 +311,151 @@ impl AsyncWrite for Writer {
                 State::Idle(w) => {
                     let mut w = w
                         .take()
-                        .expect("invalid state of writer: Idle state with empty write");
+                        .ok_or_else(|| io::Error::from(Self::new_unavailable_error()))?;
                     let fut = async move {
-                        w.close().await?;
-                        Ok(w)
+                        let rp = w.close().await?;
+                        Ok((rp, w))
                     };
                     self.state = State::Close(Box::pin(fut));
                 }
-                State::Write(_) => {
-                    unreachable!("invalid state of writer: poll_close with State::Write")
-                }
+                State::Write(_) => ready!(self.as_mut().poll_flush(cx))?,
                 State::Close(fut) => match ready!(fut.poll_unpin(cx)) {
-                    Ok(w) => {
+                    Ok((rp, w)) => {
+                        self.closed = Some(rp);
                         self.state = State::Idle(Some(w));
                         return Poll::Ready(Ok(()));
                     }
-                    Err(err) => return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, err))),
+                    Err(err) => {
+                        self.state = State::Idle(None);
+                        return Poll::Ready(Err(err.into()));
+                    }
                 },
             }
         }
     }
 }
 
+impl tokio::io::AsyncWrite for Writer {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        AsyncWrite::poll_write(self, cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_flush(self, cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_close(self, cx)
+    }
+}
+
+/// WriterSink is a [`Sink`] of [`Bytes`] which appends into the writer.
+///
+/// Created by [`Writer::into_sink`].
+pub struct WriterSink {
+    state: State,
+}
+
+impl WriterSink {
+    fn new_unavailable_error() -> Error {
+        Error::new(
+            ErrorKind::Unexpected,
+            "writer is not available after previous error",
+        )
+        .with_operation("WriterSink")
+    }
+}
+
+impl Sink<Bytes> for WriterSink {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        // Only accept new item after previous one has been appended.
+        self.poll_flush(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Bytes) -> Result<()> {
+        match &mut self.state {
+            State::Idle(w) => {
+                let mut w = w.take().ok_or_else(Self::new_unavailable_error)?;
+                let size = item.len();
+                let fut = async move {
+                    w.append(item).await?;
+                    Ok((size, w))
+                };
+                self.state = State::Write(Box::pin(fut));
+                Ok(())
+            }
+            _ => unreachable!(
+                "invalid state of writer sink: start_send must be called after poll_ready, actual {}",
+                self.state
+            ),
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match &mut self.state {
+            State::Idle(Some(_)) => Poll::Ready(Ok(())),
+            State::Idle(None) => Poll::Ready(Err(Self::new_unavailable_error())),
+            State::Write(fut) => {
+                let res = ready!(fut.poll_unpin(cx));
+                match res {
+                    Ok((_, w)) => {
+                        self.state = State::Idle(Some(w));
+                        Poll::Ready(Ok(()))
+                    }
+                    Err(err) => {
+                        self.state = State::Idle(None);
+                        Poll::Ready(Err(err))
+                    }
+                }
+            }
+            State::Close(_) => {
+                unreachable!("invalid state of writer sink: poll_flush with State::Close")
+            }
+        }
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        loop {
+            match &mut self.state {
+                State::Idle(w) => {
+                    let mut w = w.take().ok_or_else(Self::new_unavailable_error)?;
+                    let fut = async move {
+                        let rp = w.close().await?;
+                        Ok((rp, w))
+                    };
+                    self.state = State::Close(Box::pin(fut));
+                }
+                State::Write(_) => ready!(self.as_mut().poll_flush(cx))?,
+                State::Close(fut) => {
+                    let res = ready!(fut.poll_unpin(cx));
+                    return match res {
+                        Ok((_, w)) => {
+                            self.state = State::Idle(Some(w));
+                            Poll::Ready(Ok(()))
+                        }
+                        Err(err) => {
+                            self.state = State::Idle(None);
+                            Poll::Ready(Err(err))
+                        }
+                    };
+                }
+            }
+        }
+    }
+}
+
 /// BlockingWriter is designed to write data into given path in an blocking
 /// manner.
 pub struct BlockingWriter {
     pub(crate) inner: oio::BlockingWriter,
+    /// The reply returned by service while closing.
+    closed: Option<RpWrite>,
 }
 
 impl BlockingWriter {
@@ -185,7 +469,26 @@ impl BlockingWriter {
     pub(crate) fn create(acc: FusedAccessor, path: &str, op: OpWrite) -> Result<Self> {
         let (_, w) = acc.blocking_write(path, op)?;
 
-        Ok(BlockingWriter { inner: w })
+        Ok(BlockingWriter {
+            inner: w,
+            closed: None,
+        })
+    }
+
+    /// Get the etag of the written object.
+    ///
+    /// It's only available after the writer has been closed, and will be
+    /// `None` if services don't return it while writing.
+    pub fn etag(&self) -> Option<&str> {
+        self.closed.as_ref().and_then(|rp| rp.etag())
+    }
+
+    /// Get the version id of the written object.
+    ///
+    /// It's only available after the writer has been closed, and will be
+    /// `None` if services don't enable versioning.
+    pub fn version(&self) -> Option<&str> {
+        self.closed.as_ref().and_then(|rp| rp.version())
     }
 
     /// Append data into writer.
@@ -199,7 +502,15 @@ impl BlockingWriter {
 
     /// Close the writer and make sure all data have been stored.
     pub fn close(&mut self) -> Result<()> {
-        self.inner.close()
+        self.closed = Some(self.inner.close()?);
+        Ok(())
+    }
+
+    /// Abort the writer and discard all data appended.
+    ///
+    /// The target file will not be created or updated after abort.
+    pub fn abort(&mut self) -> Result<()> {
+        self.inner.abort()
     }
 }
 
@@ -215,3 +526,97 @@ impl io::Write for BlockingWriter {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::stream;
+    use futures::SinkExt;
+    use futures::TryStreamExt;
+    use rand::rngs::ThreadRng;
+    use rand::RngCore;
+    use tokio::io::AsyncWriteExt;
+
+    use crate::services;
+    use crate::Operator;
+
+    #[tokio::test]
+    async fn test_writer_tokio_write() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let path = "test_file";
+
+        let mut writer = op.writer(path).await.unwrap();
+        let n = tokio::io::copy(&mut "Hello, World!".as_bytes(), &mut writer)
+            .await
+            .expect("copy must succeed");
+        assert_eq!(n, 13);
+        writer.shutdown().await.expect("shutdown must succeed");
+
+        let bs = op.read(path).await.expect("read must succeed");
+        assert_eq!(bs, b"Hello, World!");
+    }
+
+    #[tokio::test]
+    async fn test_writer_futures_copy() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let path = "test_file";
+
+        let mut rng = ThreadRng::default();
+        let mut content = vec![0; 4 * 1024 * 1024 + 1];
+        rng.fill_bytes(&mut content);
+
+        let mut writer = op.writer(path).await.unwrap();
+        for chunk in content.chunks(256 * 1024) {
+            futures::AsyncWriteExt::write_all(&mut writer, chunk)
+                .await
+                .expect("write must succeed");
+        }
+        // Inherent close must wait for the in flight append.
+        writer.close().await.expect("close must succeed");
+        assert_eq!(op.read(path).await.unwrap(), content);
+
+        // Copy from reader to another writer.
+        let reader = op.reader(path).await.unwrap();
+        let mut writer = op.writer("copied_file").await.unwrap();
+        let n = futures::io::copy_buf(reader, &mut writer)
+            .await
+            .expect("copy must succeed");
+        assert_eq!(n, content.len() as u64);
+        futures::AsyncWriteExt::close(&mut writer)
+            .await
+            .expect("close must succeed");
+        assert_eq!(op.read("copied_file").await.unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn test_writer_into_sink() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let path = "test_file";
+
+        let mut rng = ThreadRng::default();
+        let mut content = vec![0; 4 * 1024 * 1024 + 1];
+        rng.fill_bytes(&mut content);
+
+        let mut sink = op.writer(path).await.unwrap().into_sink();
+        let mut chunks = stream::iter(
+            content
+                .chunks(256 * 1024)
+                .map(|v| Ok(Bytes::from(v.to_vec()))),
+        );
+        sink.send_all(&mut chunks)
+            .await
+            .expect("send all must succeed");
+        sink.close().await.expect("close must succeed");
+
+        // Round trip via reader stream.
+        let bs: Vec<Bytes> = op
+            .reader(path)
+            .await
+            .unwrap()
+            .into_stream(1024 * 1024)
+            .try_collect()
+            .await
+            .expect("read must succeed");
+        assert_eq!(bs.concat(), content);
+    }
+}
@@ -80,6 +80,8 @@ macro_rules! behavior_blocking_write_tests {
                 test_read_full,
                 test_read_range,
                 test_read_large_range,
+                test_sliced_reader,
+                test_read_into,
                 test_read_not_exist,
                 test_fuzz_range_reader,
                 test_fuzz_offset_reader,
@@ -262,6 +264,59 @@ pub fn test_read_range(op: BlockingOperator) -> Result<()> {
     Ok(())
 }
 
+/// Sliced reader should only yield the first bytes.
+pub fn test_sliced_reader(op: BlockingOperator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    debug!("Generate a random file: {}", &path);
+    let (content, size) = gen_bytes();
+    let (_, length) = gen_offset_length(size);
+
+    op.write(&path, content.clone())
+        .expect("write must succeed");
+
+    let mut r = op.sliced_reader(&path, length)?;
+    let mut bs = Vec::new();
+    r.read_to_end(&mut bs)?;
+    assert_eq!(bs.len() as u64, length, "read size");
+    assert_eq!(bs, content[..length as usize], "read content");
+
+    op.delete(&path).expect("delete must succeed");
+    Ok(())
+}
+
+/// Read into buffer should only fill up to buffer's length.
+pub fn test_read_into(op: BlockingOperator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    debug!("Generate a random file: {}", &path);
+    let (content, size) = gen_bytes();
+    let (offset, length) = gen_offset_length(size);
+
+    op.write(&path, content.clone())
+        .expect("write must succeed");
+
+    let mut buf = vec![0; length as usize];
+    let n = op.read_into(&path, &mut buf)?;
+    assert_eq!(n as u64, length, "read size");
+    assert_eq!(buf, content[..length as usize], "read content");
+
+    let n = op.range_read_into(&path, offset..offset + length, &mut buf)?;
+    assert_eq!(n as u64, length, "read size");
+    assert_eq!(
+        buf,
+        content[offset as usize..(offset + length) as usize],
+        "read content"
+    );
+
+    // Buffer larger than content should be partially filled.
+    let mut buf = vec![0; size + 1024];
+    let n = op.read_into(&path, &mut buf)?;
+    assert_eq!(n, size, "read size");
+    assert_eq!(buf[..n], content, "read content");
+
+    op.delete(&path).expect("delete must succeed");
+    Ok(())
+}
+
 /// Read large range content should match.
 pub fn test_read_large_range(op: BlockingOperator) -> Result<()> {
     let path = uuid::Uuid::new_v4().to_string();
@@ -20,9 +20,13 @@ use futures::stream::FuturesUnordered;
 use futures::StreamExt;
 use futures::TryStreamExt;
 use log::debug;
+use opendal::ops::OpList;
+use opendal::ops::OpWalk;
 use opendal::EntryMode;
 use opendal::ErrorKind;
 use opendal::Operator;
+use time::Duration;
+use time::OffsetDateTime;
 
 use super::utils::*;
 
@@ -75,10 +79,15 @@ macro_rules! behavior_list_tests {
                 test_list_rich_dir,
                 test_list_empty_dir,
                 test_list_non_exist_dir,
+                test_list_non_exist_dir_with_error_on_not_found,
+                test_stat_implicit_dir,
                 test_list_sub_dir,
                 test_list_nested_dir,
                 test_list_dir_with_file_path,
+                test_list_with_modified_filter,
+                test_list_with_limit,
                 test_scan,
+                test_walk,
                 test_remove_all,
             );
         )*
@@ -118,6 +127,80 @@ pub async fn test_list_dir(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// List dir with modified filter should only return entries in the time window.
+pub async fn test_list_with_modified_filter(op: Operator) -> Result<()> {
+    let dir = format!("{}/", uuid::Uuid::new_v4());
+    let path = format!("{dir}{}", uuid::Uuid::new_v4());
+    let (content, _) = gen_bytes();
+
+    op.create_dir(&dir).await.expect("create must succeed");
+    op.write(&path, content).await.expect("write must succeed");
+
+    let now = OffsetDateTime::now_utc();
+    let list_paths = |args: OpList| {
+        let op = op.clone();
+        let dir = dir.clone();
+        async move {
+            op.list_with(&dir, args)
+                .await?
+                .map_ok(|de| de.path().to_string())
+                .try_collect::<Vec<_>>()
+                .await
+        }
+    };
+
+    let args = OpList::new()
+        .with_stat_unknown_modified(true)
+        .with_include_unknown_modified(false);
+
+    let paths = list_paths(args.clone().with_modified_after(now - Duration::hours(1))).await?;
+    assert_eq!(paths, vec![path.clone()], "file should be found in list");
+
+    let paths = list_paths(args.clone().with_modified_before(now - Duration::hours(1))).await?;
+    assert!(paths.is_empty(), "file should be filtered out");
+
+    let paths = list_paths(args.with_modified_after(now + Duration::hours(1))).await?;
+    assert!(paths.is_empty(), "file should be filtered out");
+
+    op.remove_all(&dir).await.expect("remove must succeed");
+    Ok(())
+}
+
+/// List with limit should return at most limit entries.
+pub async fn test_list_with_limit(op: Operator) -> Result<()> {
+    let dir = format!("{}/", uuid::Uuid::new_v4());
+    op.create_dir(&dir).await.expect("create must succeed");
+
+    let expected: Vec<String> = (0..5).map(|num| format!("{dir}file-{num}")).collect();
+    for path in expected.iter() {
+        op.write(path, "test_list_with_limit")
+            .await
+            .expect("write must succeed");
+    }
+
+    let paths: Vec<String> = op
+        .list_with(&dir, OpList::new().with_limit(3))
+        .await?
+        .map_ok(|de| de.path().to_string())
+        .try_collect()
+        .await?;
+    assert_eq!(paths.len(), 3, "list must stop at limit");
+    for path in paths {
+        assert!(expected.contains(&path), "unexpected entry: {path}");
+    }
+
+    let paths: Vec<String> = op
+        .list_with(&dir, OpList::new().with_limit(10))
+        .await?
+        .map_ok(|de| de.path().to_string())
+        .try_collect()
+        .await?;
+    assert_eq!(paths.len(), 5);
+
+    op.remove_all(&dir).await.expect("remove must succeed");
+    Ok(())
+}
+
 /// listing a directory, which contains more objects than a single page can take.
 pub async fn test_list_rich_dir(op: Operator) -> Result<()> {
     op.create_dir("test_list_rich_dir/").await?;
@@ -188,6 +271,44 @@ pub async fn test_list_non_exist_dir(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// List non exist dir with error_on_not_found should return NotFound.
+pub async fn test_list_non_exist_dir_with_error_on_not_found(op: Operator) -> Result<()> {
+    let dir = format!("{}/", uuid::Uuid::new_v4());
+
+    let res = op
+        .list_with(&dir, OpList::new().with_error_on_not_found(true))
+        .await;
+    assert_eq!(res.err().map(|e| e.kind()), Some(ErrorKind::NotFound));
+
+    op.create_dir(&dir).await.expect("create must succeed");
+    let obs = op
+        .list_with(&dir, OpList::new().with_error_on_not_found(true))
+        .await?;
+    let objects: Vec<_> = obs.try_collect().await?;
+    assert_eq!(objects.len(), 0, "dir should only return empty");
+
+    op.delete(&dir).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Stat dir that only exists via its children should return DIR.
+pub async fn test_stat_implicit_dir(op: Operator) -> Result<()> {
+    let parent = uuid::Uuid::new_v4().to_string();
+    let dir = format!("{parent}/{}/", uuid::Uuid::new_v4());
+    let path = format!("{dir}{}", uuid::Uuid::new_v4());
+    let (content, _) = gen_bytes();
+
+    op.write(&path, content).await.expect("write must succeed");
+
+    let meta = op.stat(&dir).await?;
+    assert_eq!(meta.mode(), EntryMode::DIR);
+    let meta = op.stat(&format!("{parent}/")).await?;
+    assert_eq!(meta.mode(), EntryMode::DIR);
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
 /// List dir should return correct sub dir.
 pub async fn test_list_sub_dir(op: Operator) -> Result<()> {
     let path = format!("{}/", uuid::Uuid::new_v4());
@@ -305,6 +426,50 @@ pub async fn test_scan(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Walk should return the same entries no matter how services list.
+pub async fn test_walk(op: Operator) -> Result<()> {
+    let dir = format!("{}/", uuid::Uuid::new_v4());
+    let expected = ["x/", "x/y", "x/x/", "x/x/y", "x/x/x/", "x/x/x/y"];
+    for path in expected.iter() {
+        let path = format!("{dir}{path}");
+        if path.ends_with('/') {
+            op.create_dir(&path).await?;
+        } else {
+            op.write(&path, "test_walk").await?;
+        }
+    }
+
+    let walk = |args: OpWalk| {
+        let op = op.clone();
+        let dir = dir.clone();
+        async move {
+            op.walk(&dir, args)
+                .await?
+                .map_ok(|v| v.path().strip_prefix(&dir).unwrap().to_string())
+                .try_collect::<HashSet<_>>()
+                .await
+        }
+    };
+
+    let actual = walk(OpWalk::new()).await?;
+    debug!("walk all: {:?}", actual);
+    assert_eq!(actual, expected.iter().map(|v| v.to_string()).collect());
+
+    let actual = walk(OpWalk::new().with_max_depth(2).with_include_dirs(false)).await?;
+    debug!("walk files with depth 2: {:?}", actual);
+    assert_eq!(actual, HashSet::from(["x/y".to_string()]));
+
+    let actual = walk(OpWalk::new().with_max_depth(2)).await?;
+    debug!("walk with depth 2: {:?}", actual);
+    assert_eq!(
+        actual,
+        HashSet::from(["x/".to_string(), "x/y".to_string(), "x/x/".to_string()])
+    );
+
+    op.remove_all(&dir).await?;
+    Ok(())
+}
+
 // Remove all should remove all in this path.
 pub async fn test_remove_all(op: Operator) -> Result<()> {
     let expected = vec![
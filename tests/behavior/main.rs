@@ -58,13 +58,19 @@ macro_rules! behavior_tests {
 
 behavior_tests!(Azblob);
 behavior_tests!(Azdfs);
+cfg_if::cfg_if! { if #[cfg(feature = "services-b2")] { behavior_tests!(B2); }}
+behavior_tests!(CacheFs);
+behavior_tests!(Cos);
 cfg_if::cfg_if! { if #[cfg(feature = "services-dashmap")] { behavior_tests!(Dashmap); }}
+cfg_if::cfg_if! { if #[cfg(feature = "services-dropbox")] { behavior_tests!(Dropbox); }}
+cfg_if::cfg_if! { if #[cfg(feature = "services-etcd")] { behavior_tests!(Etcd); }}
 behavior_tests!(Fs);
 cfg_if::cfg_if! { if #[cfg(feature = "services-ftp")] { behavior_tests!(Ftp); }}
 cfg_if::cfg_if! { if #[cfg(feature = "services-memcached")] { behavior_tests!(Memcached); }}
 behavior_tests!(Memory);
 cfg_if::cfg_if! { if #[cfg(feature = "services-moka")] { behavior_tests!(Moka); }}
 behavior_tests!(Gcs);
+cfg_if::cfg_if! { if #[cfg(feature = "services-gdrive")] { behavior_tests!(Gdrive); }}
 behavior_tests!(Ghac);
 cfg_if::cfg_if! { if #[cfg(feature = "services-ipfs")] { behavior_tests!(Ipfs); }}
 behavior_tests!(Ipmfs);
@@ -73,8 +79,11 @@ cfg_if::cfg_if! { if #[cfg(feature = "services-http")] { behavior_tests!(Http);
 behavior_tests!(Obs);
 cfg_if::cfg_if! { if #[cfg(feature = "services-redis")] { behavior_tests!(Redis); }}
 cfg_if::cfg_if! { if #[cfg(feature = "services-rocksdb")] { behavior_tests!(Rocksdb); }}
+cfg_if::cfg_if! { if #[cfg(feature = "services-onedrive")] { behavior_tests!(Onedrive); }}
 behavior_tests!(Oss);
 behavior_tests!(S3);
+cfg_if::cfg_if! { if #[cfg(feature = "services-sftp")] { behavior_tests!(Sftp); }}
 cfg_if::cfg_if! { if #[cfg(feature = "services-sled")] { behavior_tests!(Sled); }}
+cfg_if::cfg_if! { if #[cfg(feature = "services-supabase")] { behavior_tests!(Supabase); }}
 behavior_tests!(Webdav);
 behavior_tests!(Webhdfs);
@@ -12,12 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+
 use anyhow::Result;
+use bytes::Bytes;
 use futures::AsyncReadExt;
 use futures::AsyncSeekExt;
 use futures::StreamExt;
 use log::debug;
 use log::warn;
+use opendal::ops::OpRemove;
+use opendal::ops::OpWrite;
 use opendal::EntryMode;
 use opendal::ErrorKind;
 use opendal::Operator;
@@ -70,6 +75,7 @@ macro_rules! behavior_write_tests {
                 test_create_dir_existing,
                 test_write,
                 test_write_with_dir_path,
+                test_write_with_parent_dir_path,
                 test_write_with_special_chars,
                 test_stat,
                 test_stat_dir,
@@ -83,6 +89,8 @@ macro_rules! behavior_write_tests {
                 test_reader_range,
                 test_reader_from,
                 test_reader_tail,
+                test_sliced_reader,
+                test_read_into,
                 test_read_not_exist,
                 test_fuzz_range_reader,
                 test_fuzz_offset_reader,
@@ -94,7 +102,20 @@ macro_rules! behavior_write_tests {
                 test_delete_with_special_chars,
                 test_delete_not_existing,
                 test_delete_stream,
+                test_remove_with,
                 test_append,
+                test_writer_with_content_length,
+                test_writer_with_content_length_mismatch,
+                test_writer_with_session,
+                test_write_with_append_existing,
+                test_write_with_if_match,
+                test_rename_file,
+                test_rename_dir,
+                test_put_and_get_tags,
+                test_copy_all,
+                test_write_from,
+                test_write_from_stream,
+                test_write_from_stream_abort,
             );
         )*
     };
@@ -154,6 +175,18 @@ pub async fn test_write_with_dir_path(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Write a file with `..` in path should return ConfigInvalid.
+pub async fn test_write_with_parent_dir_path(op: Operator) -> Result<()> {
+    let path = format!("{}/../{}", uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+    let (content, _) = gen_bytes();
+
+    let result = op.write(&path, content).await;
+    assert!(result.is_err());
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::ConfigInvalid);
+
+    Ok(())
+}
+
 /// Write a single file with special chars should succeed.
 pub async fn test_write_with_special_chars(op: Operator) -> Result<()> {
     let path = format!("{} !@#$%^&()_+-=;',.txt", uuid::Uuid::new_v4());
@@ -380,6 +413,69 @@ pub async fn test_reader_from(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Sliced reader should only yield the first bytes.
+pub async fn test_sliced_reader(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    debug!("Generate a random file: {}", &path);
+    let (content, size) = gen_bytes();
+    let (_, length) = gen_offset_length(size);
+
+    op.write(&path, content.clone())
+        .await
+        .expect("write must succeed");
+
+    let mut r = op.sliced_reader(&path, length).await?;
+    let mut bs = Vec::new();
+    r.read_to_end(&mut bs).await?;
+    assert_eq!(bs.len() as u64, length, "read size");
+    assert_eq!(bs, content[..length as usize], "read content");
+
+    // Slice larger than content should yield the whole content.
+    let mut r = op.sliced_reader(&path, size as u64 + 1024).await?;
+    let mut bs = Vec::new();
+    r.read_to_end(&mut bs).await?;
+    assert_eq!(bs, content, "read content");
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Read into buffer should only fill up to buffer's length.
+pub async fn test_read_into(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    debug!("Generate a random file: {}", &path);
+    let (content, size) = gen_bytes();
+    let (offset, length) = gen_offset_length(size);
+
+    op.write(&path, content.clone())
+        .await
+        .expect("write must succeed");
+
+    let mut buf = vec![0; length as usize];
+    let n = op.read_into(&path, &mut buf).await?;
+    assert_eq!(n as u64, length, "read size");
+    assert_eq!(buf, content[..length as usize], "read content");
+
+    let n = op
+        .range_read_into(&path, offset..offset + length, &mut buf)
+        .await?;
+    assert_eq!(n as u64, length, "read size");
+    assert_eq!(
+        buf,
+        content[offset as usize..(offset + length) as usize],
+        "read content"
+    );
+
+    // Buffer larger than content should be partially filled.
+    let mut buf = vec![0; size + 1024];
+    let n = op.read_into(&path, &mut buf).await?;
+    assert_eq!(n, size, "read size");
+    assert_eq!(buf[..n], content, "read content");
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
 /// Read range tail should match.
 pub async fn test_reader_tail(op: Operator) -> Result<()> {
     let path = uuid::Uuid::new_v4().to_string();
@@ -391,15 +487,9 @@ pub async fn test_reader_tail(op: Operator) -> Result<()> {
         .await
         .expect("write must succeed");
 
-    let mut r = match op.range_reader(&path, ..length).await {
-        Ok(r) => r,
-        // Not all services support range with tail range, let's tolerate this.
-        Err(err) if err.kind() == ErrorKind::Unsupported => {
-            warn!("service doesn't support range with tail");
-            return Ok(());
-        }
-        Err(err) => return Err(err.into()),
-    };
+    // Services that don't support suffix range will be served by stat
+    // and a normal ranged read.
+    let mut r = op.range_reader(&path, ..length).await?;
 
     let mut bs = Vec::new();
     r.read_to_end(&mut bs).await?;
@@ -653,6 +743,30 @@ pub async fn test_delete_stream(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Remove with paths should return results for every path.
+pub async fn test_remove_with(op: Operator) -> Result<()> {
+    let dir = uuid::Uuid::new_v4().to_string();
+
+    let mut paths: Vec<_> = (0..10).map(|v| format!("{dir}/{v}")).collect();
+    for path in paths.iter() {
+        op.write(path, "remove_with").await?;
+    }
+    // Removing not existing path should be treated as success.
+    paths.push(format!("{dir}/not_exist"));
+
+    let results = op
+        .with_limit(3)
+        .remove_with(paths.clone(), OpRemove::new().with_concurrent(4))
+        .await?;
+    assert_eq!(results.len(), paths.len());
+    for (path, result) in results {
+        assert!(result.is_ok(), "{path} should be removed: {result:?}");
+        assert!(!op.is_exist(&path).await?, "{path} should be removed")
+    }
+
+    Ok(())
+}
+
 // Append write
 pub async fn test_append(op: Operator) -> Result<()> {
     let path = uuid::Uuid::new_v4().to_string();
@@ -691,3 +805,379 @@ pub async fn test_append(op: Operator) -> Result<()> {
     op.delete(&path).await.expect("delete must succeed");
     Ok(())
 }
+
+/// Append write with content length declared should succeed if sizes match.
+pub async fn test_writer_with_content_length(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    let size = 5 * 1024 * 1024; // write file with 5 MiB
+    let content_a = gen_fixed_bytes(size);
+    let content_b = gen_fixed_bytes(size);
+
+    let args = OpWrite::new().with_content_length((size * 2) as u64);
+    let mut w = match op.writer_with(&path, args).await {
+        Ok(w) => w,
+        Err(err) if err.kind() == ErrorKind::Unsupported => {
+            warn!("service doesn't support write with append");
+            return Ok(());
+        }
+        Err(err) => return Err(err.into()),
+    };
+    w.append(content_a).await?;
+    w.append(content_b).await?;
+    w.close().await?;
+
+    let meta = op.stat(&path).await.expect("stat must succeed");
+    assert_eq!(meta.content_length(), (size * 2) as u64);
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Append write with content length declared should fail if sizes mismatch.
+pub async fn test_writer_with_content_length_mismatch(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    let size = 5 * 1024 * 1024; // write file with 5 MiB
+    let content = gen_fixed_bytes(size);
+
+    // Append more data than declared.
+    let args = OpWrite::new().with_content_length(size as u64);
+    let mut w = match op.writer_with(&path, args).await {
+        Ok(w) => w,
+        Err(err) if err.kind() == ErrorKind::Unsupported => {
+            warn!("service doesn't support write with append");
+            return Ok(());
+        }
+        Err(err) => return Err(err.into()),
+    };
+    w.append(content.clone()).await?;
+    let res = w.append(content.clone()).await;
+    assert!(res.is_err(), "append more data than declared must fail");
+
+    // Close with less data than declared.
+    let args = OpWrite::new().with_content_length((size * 2) as u64);
+    let mut w = op.writer_with(&path, args).await?;
+    w.append(content).await?;
+    let res = w.close().await;
+    assert!(res.is_err(), "close with less data than declared must fail");
+
+    Ok(())
+}
+
+/// Writer resumed from session should continue the previous write.
+pub async fn test_writer_with_session(op: Operator) -> Result<()> {
+    if !op.info().capability().write_with_session {
+        let err = op
+            .writer_with("not_used", OpWrite::new().with_session("not_used"))
+            .await
+            .err()
+            .expect("write with session must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content, size) = gen_bytes();
+
+    let w = op.writer(&path).await?;
+    let session = w.session().expect("session must be returned").to_string();
+    assert_eq!(w.offset(), 0);
+    // Drop the writer like the process crashed.
+    drop(w);
+
+    let mut w = op
+        .writer_with(&path, OpWrite::new().with_session(&session))
+        .await?;
+    assert_eq!(w.session(), Some(session.as_str()));
+    let offset = w.offset() as usize;
+    assert_eq!(offset, 0, "nothing has been committed yet");
+    w.append(content[offset..].to_vec()).await?;
+    w.close().await?;
+
+    let bs = op.read(&path).await?;
+    assert_eq!(bs.len(), size, "read size");
+    assert_eq!(
+        format!("{:x}", Sha256::digest(&bs)),
+        format!("{:x}", Sha256::digest(&content)),
+        "read content"
+    );
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Write with append_existing should append data to the existing file.
+pub async fn test_write_with_append_existing(op: Operator) -> Result<()> {
+    if !op.info().capability().write_with_append_existing {
+        let err = op
+            .write_with("not_used", OpWrite::new().with_append_existing(true), "")
+            .await
+            .expect_err("write with append_existing must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content_a, size_a) = gen_bytes();
+    let (content_b, size_b) = gen_bytes();
+    let args = OpWrite::new().with_append_existing(true);
+
+    // File will be created if not exist.
+    op.write_with(&path, args.clone(), content_a.clone())
+        .await?;
+    op.write_with(&path, args, content_b.clone()).await?;
+
+    let meta = op.stat(&path).await.expect("stat must succeed");
+    assert_eq!(meta.content_length(), (size_a + size_b) as u64);
+
+    let bs = op.read(&path).await?;
+    assert_eq!(bs, [content_a, content_b].concat(), "read content");
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Write with if_match should only succeed while etag matches.
+pub async fn test_write_with_if_match(op: Operator) -> Result<()> {
+    if !op.info().capability().write_with_if_match {
+        let err = op
+            .write_with("not_used", OpWrite::new().with_if_match("\"etag\""), "")
+            .await
+            .expect_err("write with if_match must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content_a, _) = gen_bytes();
+    let (content_b, _) = gen_bytes();
+    let (content_c, _) = gen_bytes();
+
+    op.write(&path, content_a).await?;
+    let etag = op
+        .stat(&path)
+        .await?
+        .etag()
+        .expect("etag must exist")
+        .to_string();
+
+    // Write with current etag should succeed.
+    op.write_with(
+        &path,
+        OpWrite::new().with_if_match(&etag),
+        content_b.clone(),
+    )
+    .await?;
+
+    // Write with stale etag should fail.
+    let err = op
+        .write_with(&path, OpWrite::new().with_if_match(&etag), content_c)
+        .await
+        .expect_err("write with stale etag must fail");
+    assert_eq!(err.kind(), ErrorKind::ConditionNotMatch);
+
+    let bs = op.read(&path).await?;
+    assert_eq!(bs, content_b, "read content");
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Rename a file should move the content to the new path.
+pub async fn test_rename_file(op: Operator) -> Result<()> {
+    if !op.info().capability().rename {
+        let err = op
+            .rename("not_used_from", "not_used_to")
+            .await
+            .expect_err("rename must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        return Ok(());
+    }
+
+    let from = uuid::Uuid::new_v4().to_string();
+    let to = format!("{}/{}", uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+    let (content, _) = gen_bytes();
+
+    op.write(&from, content.clone()).await?;
+
+    op.rename(&from, &to).await?;
+
+    let err = op.stat(&from).await.expect_err("stat must fail");
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    let bs = op.read(&to).await?;
+    assert_eq!(bs, content, "read content");
+
+    op.delete(&to).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Rename a dir should move the whole subtree to the new path.
+pub async fn test_rename_dir(op: Operator) -> Result<()> {
+    if !op.info().capability().rename {
+        return Ok(());
+    }
+
+    let from = format!("{}/", uuid::Uuid::new_v4());
+    let to = format!("{}/", uuid::Uuid::new_v4());
+    let (content, _) = gen_bytes();
+
+    op.write(&format!("{from}file"), content.clone()).await?;
+
+    op.rename(&from, &to).await?;
+
+    let err = op
+        .stat(&format!("{from}file"))
+        .await
+        .expect_err("stat must fail");
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    let bs = op.read(&format!("{to}file")).await?;
+    assert_eq!(bs, content, "read content");
+
+    op.remove_all(&to).await.expect("remove_all must succeed");
+    Ok(())
+}
+
+/// Put tags should replace existing tags without touching the content.
+pub async fn test_put_and_get_tags(op: Operator) -> Result<()> {
+    if !op.info().capability().tags {
+        let err = op
+            .get_tags("not_used_path")
+            .await
+            .expect_err("get tags must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content, _) = gen_bytes();
+
+    op.write(&path, content.clone()).await?;
+    assert!(op.get_tags(&path).await?.is_empty(), "no tags by default");
+
+    let tags = HashMap::from([
+        ("env".to_string(), "prod".to_string()),
+        ("team".to_string(), "data".to_string()),
+    ]);
+    op.put_tags(&path, tags.clone()).await?;
+    assert_eq!(op.get_tags(&path).await?, tags);
+
+    let tags = HashMap::from([("env".to_string(), "test".to_string())]);
+    op.put_tags(&path, tags.clone()).await?;
+    assert_eq!(op.get_tags(&path).await?, tags, "tags must be replaced");
+
+    let bs = op.read(&path).await?;
+    assert_eq!(bs, content, "read content");
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Copy all should copy every file under the dir with relative paths kept.
+pub async fn test_copy_all(op: Operator) -> Result<()> {
+    if !op.info().can_list() && !op.info().can_scan() {
+        return Ok(());
+    }
+
+    let from = format!("{}/", uuid::Uuid::new_v4());
+    let to = format!("{}/", uuid::Uuid::new_v4());
+    let (content_a, _) = gen_bytes();
+    let (content_b, _) = gen_bytes();
+
+    op.write(&format!("{from}a"), content_a.clone()).await?;
+    op.write(&format!("{from}x/y/b"), content_b.clone()).await?;
+
+    op.copy_all(&from, &to).await?;
+
+    let bs = op.read(&format!("{to}a")).await?;
+    assert_eq!(bs, content_a, "read content");
+    let bs = op.read(&format!("{to}x/y/b")).await?;
+    assert_eq!(bs, content_b, "read content");
+
+    // Source should be kept as is.
+    let bs = op.read(&format!("{from}x/y/b")).await?;
+    assert_eq!(bs, content_b, "read content");
+
+    op.remove_all(&from).await.expect("remove_all must succeed");
+    op.remove_all(&to).await.expect("remove_all must succeed");
+    Ok(())
+}
+
+/// Write from an async reader should make sure all data has been written.
+pub async fn test_write_from(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    let size = 50 * 1024 * 1024; // write file with 50 MiB
+    let content = gen_fixed_bytes(size);
+
+    op.write_from(
+        &path,
+        Some(size as u64),
+        futures::io::Cursor::new(content.clone()),
+    )
+    .await?;
+
+    let meta = op.stat(&path).await.expect("stat must succeed");
+    assert_eq!(meta.content_length(), size as u64);
+
+    let bs = op.read(&path).await?;
+    assert_eq!(bs.len(), size, "read size");
+    assert_eq!(
+        format!("{:x}", Sha256::digest(&bs)),
+        format!("{:x}", Sha256::digest(&content)),
+        "read content"
+    );
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Write from a stream with unaligned chunks and unknown length should
+/// make sure all data has been written.
+pub async fn test_write_from_stream(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    let chunk_size = 3 * 1024 * 1024 + 7;
+    let chunks: Vec<Bytes> = (0..17)
+        .map(|_| Bytes::from(gen_fixed_bytes(chunk_size)))
+        .collect();
+
+    let mut hasher = Sha256::new();
+    for chunk in &chunks {
+        hasher.update(chunk);
+    }
+    let expected = format!("{:x}", hasher.finalize());
+
+    let s = futures::stream::iter(chunks.into_iter().map(Ok));
+    op.write_from_stream(&path, None, s).await?;
+
+    let bs = op.read(&path).await?;
+    assert_eq!(bs.len(), chunk_size * 17, "read size");
+    assert_eq!(
+        format!("{:x}", Sha256::digest(&bs)),
+        expected,
+        "read content"
+    );
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Write from a stream which returns error mid-way should abort the write.
+pub async fn test_write_from_stream_abort(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    let size = 10 * 1024 * 1024;
+
+    let s = futures::stream::iter(vec![
+        Ok(Bytes::from(gen_fixed_bytes(size))),
+        Err(opendal::Error::new(ErrorKind::Unexpected, "source failed")),
+    ]);
+    let err = op
+        .write_from_stream(&path, None, s)
+        .await
+        .expect_err("write from stream must fail");
+    assert_eq!(err.kind(), ErrorKind::Unexpected);
+    assert!(err.to_string().contains("source failed"));
+    assert!(err.to_string().contains("written"));
+
+    assert!(!op.is_exist(&path).await?, "file must not be created");
+    Ok(())
+}